@@ -1,16 +1,31 @@
 use marshal::context::Context;
-use marshal_core::decode::{AnySpecDecoder, Decoder};
+use marshal_core::decode::{AnySpecDecoder, Decoder, SpecDecoder};
 use marshal_core::decode::depth_budget::{DepthBudgetDecoder, WithDepthBudget};
 use marshal_core::decode::poison::PoisonDecoder;
 use marshal_core::derive_decoder_for_newtype;
 
 use crate::decode::{BinAnyDecoder, BinDecoderSchema, SimpleBinSpecDecoder};
+use crate::slice::PackedPrimitive;
 use crate::DeserializeBin;
 
 pub struct BinSpecDecoder<'de>(PoisonDecoder<DepthBudgetDecoder<SimpleBinSpecDecoder<'de>>>);
 
 derive_decoder_for_newtype!(BinSpecDecoder<'de>(PoisonDecoder<DepthBudgetDecoder<SimpleBinSpecDecoder<'de>>>));
 
+impl<'de> BinSpecDecoder<'de> {
+    /// Reads the remaining elements of `seq` at once if it is packed with elements of type `T`.
+    #[inline]
+    pub(crate) fn read_packed_slice<T: PackedPrimitive>(
+        &mut self,
+        seq: &mut <Self as SpecDecoder<'de>>::SeqDecoder,
+    ) -> anyhow::Result<Option<Vec<T>>> {
+        self.0
+            .inner_mut()
+            .inner_mut()
+            .read_packed_slice(seq.inner_mut().inner_mut())
+    }
+}
+
 pub struct BinDecoderBuilder<'de> {
     inner: BinSpecDecoder<'de>,
     depth_budget: usize,
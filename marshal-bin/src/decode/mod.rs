@@ -1,11 +1,15 @@
 use std::borrow::Cow;
+use std::cell::RefCell;
+use std::collections::BTreeSet;
 use std::fmt::{Debug, Display, Formatter};
 
 use by_address::ByAddress;
 use num_traits::FromPrimitive;
 use safe_once_map::cell::OnceCellMap;
 
-use crate::util::StableCellVec;
+use crate::encode::save_schema;
+use crate::slice::{is_packable, PackedPrimitive};
+use crate::util::{fingerprint, StableCellVec};
 use crate::{TypeTag};
 use marshal_core::decode::{DecodeHint, DecodeVariantHint, SimpleDecoderView, SpecDecoder};
 use marshal_core::{Primitive, PrimitiveType};
@@ -30,21 +34,131 @@ struct EnumDefForeign {
     custom_translation: OnceCellMap<ByAddress<EnumDefNative>, EnumDefTranslation>,
 }
 
+/// A field that was present in the input but not in the type it was decoded into.
+#[derive(Debug, Clone, Eq, Ord, PartialEq, PartialOrd, Hash)]
+pub struct UnmatchedField {
+    /// The name of the struct, or `"<enum>"` for the fields of a struct variant.
+    pub type_name: &'static str,
+    pub field: String,
+}
+
+fn string_slots(strings: &[Option<String>]) -> impl Iterator<Item = (usize, &str)> {
+    strings
+        .iter()
+        .enumerate()
+        .filter_map(|(index, x)| Some((index, x.as_deref()?)))
+}
+
 pub struct BinDecoderSchema {
     enum_defs: StableCellVec<EnumDefForeign>,
+    message_enum_defs: StableCellVec<EnumDefForeign>,
+    strings: RefCell<Vec<Option<String>>>,
+    string_capacity: usize,
+    unmatched_fields: RefCell<BTreeSet<UnmatchedField>>,
 }
 
 impl BinDecoderSchema {
     pub fn new() -> Self {
         BinDecoderSchema {
             enum_defs: StableCellVec::new(),
+            message_enum_defs: StableCellVec::new(),
+            strings: RefCell::new(vec![]),
+            string_capacity: 0,
+            unmatched_fields: RefCell::new(BTreeSet::new()),
         }
     }
+    /// Lists the fields that were ignored because they matched no field or alias of the type
+    /// being decoded, such as those added by a newer version of the encoder.
+    pub fn unmatched_fields(&self) -> Vec<UnmatchedField> {
+        self.unmatched_fields.borrow().iter().cloned().collect()
+    }
+    /// Accepts interned strings from an encoder with up to `capacity` strings, as configured by
+    /// [BinEncoderSchema::set_string_capacity](crate::encode::BinEncoderSchema::set_string_capacity).
+    pub fn set_string_capacity(&mut self, capacity: usize) -> &mut Self {
+        self.string_capacity = capacity;
+        self.strings.get_mut().clear();
+        self
+    }
+    pub(crate) fn enum_def_fields(&self) -> impl Iterator<Item = &[String]> {
+        (0..self.enum_defs.len()).map(|index| self.enum_defs.get(index).unwrap().fields.as_slice())
+    }
+    pub(crate) fn string_capacity(&self) -> usize {
+        self.string_capacity
+    }
+    /// Returns the interned strings, which an encoder assigns to consecutive slots.
+    pub(crate) fn into_strings(self) -> anyhow::Result<Vec<String>> {
+        Ok(self
+            .strings
+            .into_inner()
+            .into_iter()
+            .enumerate()
+            .map(|(index, x)| x.ok_or(BinDecoderError::NoSuchString(index)))
+            .collect::<Result<_, _>>()?)
+    }
+    pub fn fingerprint(&self) -> u64 {
+        let strings = self.strings.borrow();
+        fingerprint(
+            self.enum_def_fields(),
+            self.string_capacity,
+            string_slots(&strings),
+        )
+    }
+    /// Serializes the definitions and interned strings read so far. The result can be restored
+    /// as either a [BinDecoderSchema] or a [BinEncoderSchema](crate::encode::BinEncoderSchema).
+    pub fn save(&self) -> Vec<u8> {
+        let strings = self.strings.borrow();
+        save_schema(
+            self.fingerprint(),
+            self.enum_def_fields(),
+            self.string_capacity,
+            string_slots(&strings),
+        )
+    }
+    pub(crate) fn len(&self) -> usize {
+        self.enum_defs.len()
+    }
+    /// Reads `count` definitions from the start of `data`, returning the number of bytes read.
+    pub(crate) fn read_enum_defs(&mut self, data: &[u8], count: usize) -> anyhow::Result<usize> {
+        let mut decoder = SimpleBinSpecDecoder::new(data, self);
+        for _ in 0..count {
+            match decoder.decode_type_tag()? {
+                TypeTag::EnumDef => decoder.read_enum_def()?,
+                tag => return Err(BinDecoderError::BadTag(tag as u8).into()),
+            }
+        }
+        Ok(data.len() - decoder.content.len())
+    }
+    /// Restores a saved schema, so that a stream can be resumed at the point where it was saved.
+    pub fn restore(data: &[u8]) -> anyhow::Result<Self> {
+        let (expected, mut defs) = data.split_first_chunk::<8>().ok_or(BinDecoderError::Eof)?;
+        let expected = u64::from_le_bytes(*expected);
+        if defs.len() < VU128_PADDING {
+            return Err(BinDecoderError::Eof.into());
+        }
+        let mut schema = BinDecoderSchema::new();
+        schema.set_string_capacity(usize::try_from(defs.read_vu128::<u64>()?)?);
+        let mut decoder = SimpleBinSpecDecoder::new(defs, &mut schema);
+        while decoder.content.len() > VU128_PADDING {
+            match decoder.decode_type_tag()? {
+                TypeTag::EnumDef => decoder.read_enum_def()?,
+                TypeTag::StringDef => decoder.read_string_def()?,
+                tag => return Err(BinDecoderError::BadTag(tag as u8).into()),
+            }
+        }
+        decoder.end()?;
+        let found = schema.fingerprint();
+        if found != expected {
+            return Err(BinDecoderError::FingerprintMismatch { expected, found }.into());
+        }
+        Ok(schema)
+    }
 }
 
 pub struct SimpleBinSpecDecoder<'de> {
     content: &'de [u8],
     schema: &'de BinDecoderSchema,
+    self_contained: bool,
+    foreign_field: Option<(&'static str, &'de str)>,
 }
 
 #[derive(Debug)]
@@ -55,6 +169,8 @@ pub enum BinDecoderError {
     BadTag(u8),
     NoSuchEnumDef,
     MissingField(&'static str),
+    FingerprintMismatch { expected: u64, found: u64 },
+    NoSuchString(usize),
 }
 
 impl Display for BinDecoderError {
@@ -64,8 +180,10 @@ impl Display for BinDecoderError {
             BinDecoderError::NonZeroPadding => write!(f, "final padding is not zero"),
             BinDecoderError::Eof => write!(f, "unexpected end of file"),
             BinDecoderError::BadTag(x) => write!(f, "unknown type tag {}", x),
-            BinDecoderError::NoSuchEnumDef => write!(f, "reference to unknown enum definition (did you remember to reuse the BinDecoderSchema?)"),
+            BinDecoderError::NoSuchEnumDef => write!(f, "reference to unknown enum definition (did you remember to reuse or restore the BinDecoderSchema?)"),
             BinDecoderError::MissingField(field) => write!(f, "attempted to deserialize struct with missing field `{}'", field),
+            BinDecoderError::NoSuchString(index) => write!(f, "reference to unknown interned string {} (is the string capacity of the BinDecoderSchema large enough?)", index),
+            BinDecoderError::FingerprintMismatch { expected, found } => write!(f, "restored schema has fingerprint {:016x} but expected {:016x}", found, expected),
         }
     }
 }
@@ -75,9 +193,12 @@ impl std::error::Error for BinDecoderError {}
 impl<'de> SimpleBinSpecDecoder<'de> {
     #[inline]
     pub fn new(data: &'de [u8], schema: &'de mut BinDecoderSchema) -> SimpleBinSpecDecoder<'de> {
+        schema.message_enum_defs = StableCellVec::new();
         SimpleBinSpecDecoder {
             content: data,
             schema,
+            self_contained: false,
+            foreign_field: None,
         }
     }
     #[inline]
@@ -120,20 +241,85 @@ impl<'de> SimpleBinSpecDecoder<'de> {
         let fields = (0..count)
             .map(|_| Ok(self.read_str()?.to_string()))
             .collect::<anyhow::Result<Vec<_>>>()?;
+        // Without a struct hint (e.g. when ignoring a value), fields are read by name so that
+        // every value is consumed.
+        let default_translation = EnumDefTranslation {
+            keys: fields.iter().map(|x| EnumDefKey::Foreign(x.clone())).collect(),
+        };
         let def = EnumDefForeign {
             fields,
-            default_translation: EnumDefTranslation { keys: vec![] },
+            default_translation,
             custom_translation: Default::default(),
         };
-        self.schema.enum_defs.push(def);
+        self.enum_defs().push(def);
+        Ok(())
+    }
+    fn read_string_def(&mut self) -> anyhow::Result<()> {
+        let index = self.read_usize()?;
+        let value = self.read_str()?;
+        if index >= self.schema.string_capacity {
+            return Err(BinDecoderError::NoSuchString(index).into());
+        }
+        let mut strings = self.schema.strings.borrow_mut();
+        if strings.len() <= index {
+            strings.resize(index + 1, None);
+        }
+        strings[index] = Some(value.to_string());
         Ok(())
     }
+    fn read_string_ref(&mut self) -> anyhow::Result<String> {
+        let index = self.read_usize()?;
+        let strings = self.schema.strings.borrow();
+        Ok(strings
+            .get(index)
+            .cloned()
+            .flatten()
+            .ok_or(BinDecoderError::NoSuchString(index))?)
+    }
+    /// Reads an untagged element of a packed sequence.
+    fn read_packed(&mut self, element: TypeTag) -> anyhow::Result<Primitive> {
+        Ok(match element {
+            TypeTag::I8 => Primitive::I8(self.content.read_vu128()?),
+            TypeTag::I16 => Primitive::I16(self.content.read_vu128()?),
+            TypeTag::I32 => Primitive::I32(self.content.read_vu128()?),
+            TypeTag::I64 => Primitive::I64(self.content.read_vu128()?),
+            TypeTag::I128 => Primitive::I128(self.content.read_vu128()?),
+            TypeTag::U16 => Primitive::U16(self.content.read_vu128()?),
+            TypeTag::U32 => Primitive::U32(self.content.read_vu128()?),
+            TypeTag::U64 => Primitive::U64(self.content.read_vu128()?),
+            TypeTag::U128 => Primitive::U128(self.content.read_vu128()?),
+            TypeTag::F32 => Primitive::F32(self.content.read_vu128()?),
+            TypeTag::F64 => Primitive::F64(self.content.read_vu128()?),
+            _ => return Err(BinDecoderError::BadTag(element as u8).into()),
+        })
+    }
+    /// Reads the remaining elements of `seq` at once if it is packed with elements of type `T`.
+    pub(crate) fn read_packed_slice<T: PackedPrimitive>(
+        &mut self,
+        seq: &mut BinSeqDecoder,
+    ) -> anyhow::Result<Option<Vec<T>>> {
+        if seq.packed != Some(T::TAG) {
+            return Ok(None);
+        }
+        let mut result = vec![];
+        self.content.read_vu128_slice(seq.len, &mut result)?;
+        seq.len = 0;
+        Ok(Some(result))
+    }
+    /// The definitions in scope, which are local to the message if it is self-contained.
+    #[inline]
+    fn enum_defs(&self) -> &'de StableCellVec<EnumDefForeign> {
+        if self.self_contained {
+            &self.schema.message_enum_defs
+        } else {
+            &self.schema.enum_defs
+        }
+    }
     #[inline]
     fn read_enum_def_ref(&mut self) -> anyhow::Result<&'de EnumDefForeign> {
         let index = self.read_usize()?;
         Ok(self
-            .schema
-            .enum_defs
+            .enum_defs()
             .get(index)
             .ok_or(BinDecoderError::NoSuchEnumDef)?)
     }
@@ -150,6 +336,7 @@ impl EnumDefForeign {
                     keys: self
                         .fields
                         .iter()
+                        // Fields that are not native, such as aliases, are matched by name.
                         .map(|foreign| {
                             if let Some(native) = native.iter().position(|native| native == foreign)
                             {
@@ -173,17 +360,22 @@ impl<'s> Default for BinAnyDecoder<'s> {
 pub enum BinAnyDecoder<'de> {
     U32(u32),
     Str(&'de str),
+    Packed(TypeTag),
     Read,
 }
 
 pub struct BinSeqDecoder {
     len: usize,
+    packed: Option<TypeTag>,
 }
 
 pub struct BinMapDecoder<'de>(BinMapDecoderInner<'de>);
 
 enum BinMapDecoderInner<'de> {
-    WithSchema(&'de [EnumDefKey]),
+    WithSchema {
+        type_name: Option<&'static str>,
+        keys: &'de [EnumDefKey],
+    },
     WithLength(usize),
 }
 
@@ -220,8 +412,19 @@ impl<'de> SpecDecoder<'de> for SimpleBinSpecDecoder<'de> {
             BinAnyDecoder::Str(x) => {
                 return Ok(SimpleDecoderView::String(Cow::Owned(x.to_string())));
             }
+            BinAnyDecoder::Packed(tag) => {
+                return Ok(SimpleDecoderView::Primitive(self.read_packed(tag)?));
+            }
             BinAnyDecoder::Read => {}
         }
+        if let Some((type_name, field)) = self.foreign_field.take() {
+            if let DecodeHint::Ignore = hint {
+                self.schema.unmatched_fields.borrow_mut().insert(UnmatchedField {
+                    type_name,
+                    field: field.to_string(),
+                });
+            }
+        }
         loop {
             let tag = self.decode_type_tag()?;
             let () = match tag {
@@ -287,7 +490,7 @@ impl<'de> SpecDecoder<'de> for SimpleBinSpecDecoder<'de> {
                     )));
                 }
                 TypeTag::F64 => {
-                    return Ok(SimpleDecoderView::Primitive(Primitive::F32(
+                    return Ok(SimpleDecoderView::Primitive(Primitive::F64(
                         self.content.read_vu128()?,
                     )));
                 }
@@ -298,18 +501,21 @@ impl<'de> SpecDecoder<'de> for SimpleBinSpecDecoder<'de> {
                 }
                 TypeTag::Struct => {
                     let enum_def = self.read_enum_def_ref()?;
-                    let fields = match hint {
-                        DecodeHint::Struct { name: _, fields } => Some(fields),
-                        _ => None,
+                    let (type_name, fields) = match hint {
+                        DecodeHint::Struct { name, fields } => (Some(name), Some(fields)),
+                        _ => (None, None),
                     };
                     let trans = enum_def.get_translation(fields);
                     return Ok(SimpleDecoderView::Map(BinMapDecoder(
-                        BinMapDecoderInner::WithSchema(&trans.keys),
+                        BinMapDecoderInner::WithSchema {
+                            type_name,
+                            keys: &trans.keys,
+                        },
                     )));
                 }
                 TypeTag::TupleStruct => {
                     let len = self.read_usize()?;
-                    return Ok(SimpleDecoderView::Seq(BinSeqDecoder { len }));
+                    return Ok(SimpleDecoderView::Seq(BinSeqDecoder { len, packed: None }));
                 }
                 TypeTag::Enum => {
                     let enum_def = self.read_enum_def_ref()?;
@@ -323,7 +529,18 @@ impl<'de> SpecDecoder<'de> for SimpleBinSpecDecoder<'de> {
                 }
                 TypeTag::Seq => {
                     let len = self.read_usize()?;
-                    return Ok(SimpleDecoderView::Seq(BinSeqDecoder { len }));
+                    return Ok(SimpleDecoderView::Seq(BinSeqDecoder { len, packed: None }));
+                }
+                TypeTag::PackedSeq => {
+                    let element = self.decode_type_tag()?;
+                    if !is_packable(element) {
+                        return Err(BinDecoderError::BadTag(element as u8).into());
+                    }
+                    let len = self.read_usize()?;
+                    return Ok(SimpleDecoderView::Seq(BinSeqDecoder {
+                        len,
+                        packed: Some(element),
+                    }));
                 }
                 TypeTag::Map => {
                     let len = self.read_usize()?;
@@ -335,9 +552,23 @@ impl<'de> SpecDecoder<'de> for SimpleBinSpecDecoder<'de> {
                     let len = self.content.read_vu128::<u64>()?;
                     return Ok(SimpleDecoderView::Seq(BinSeqDecoder {
                         len: usize::try_from(len)?,
+                        packed: None,
                     }));
                 }
                 TypeTag::EnumDef => self.read_enum_def()?,
+                TypeTag::LocalSchema => self.self_contained = true,
+                TypeTag::StringDef => self.read_string_def()?,
+                TypeTag::StringRef => {
+                    return Ok(SimpleDecoderView::String(Cow::Owned(self.read_string_ref()?)));
+                }
+                TypeTag::Skippable => {
+                    let len = self.read_count(4)?;
+                    let len = u32::from_le_bytes(len.try_into().unwrap());
+                    if let DecodeHint::Ignore = hint {
+                        self.read_count(usize::try_from(len)?)?;
+                        return Ok(SimpleDecoderView::Primitive(Primitive::Unit));
+                    }
+                }
                 TypeTag::String => return Ok(SimpleDecoderView::String(self.read_str()?.into())),
                 TypeTag::UnitStruct => return Ok(SimpleDecoderView::Primitive(Primitive::Unit)),
                 TypeTag::Bytes => return Ok(SimpleDecoderView::Bytes(self.read_bytes()?.into())),
@@ -359,7 +590,10 @@ impl<'de> SpecDecoder<'de> for SimpleBinSpecDecoder<'de> {
     ) -> anyhow::Result<Option<Self::AnyDecoder>> {
         if let Some(len2) = seq.len.checked_sub(1) {
             seq.len = len2;
-            Ok(Some(BinAnyDecoder::Read))
+            match seq.packed {
+                Some(element) => Ok(Some(BinAnyDecoder::Packed(element))),
+                None => Ok(Some(BinAnyDecoder::Read)),
+            }
         } else {
             Ok(None)
         }
@@ -376,11 +610,15 @@ impl<'de> SpecDecoder<'de> for SimpleBinSpecDecoder<'de> {
         map: &mut Self::MapDecoder,
     ) -> anyhow::Result<Option<Self::KeyDecoder>> {
         match &mut map.0 {
-            BinMapDecoderInner::WithSchema(schema) => {
-                if let Some(key) = schema.take_first() {
+            BinMapDecoderInner::WithSchema { type_name, keys } => {
+                if let Some(key) = keys.take_first() {
                     match key {
                         EnumDefKey::Native(x) => Ok(Some(BinKeyDecoder::Native(*x))),
-                        EnumDefKey::Foreign(x) => Ok(Some(BinKeyDecoder::Foreign(x))),
+                        EnumDefKey::Foreign(x) => {
+                            // Remember the name in case the value is ignored.
+                            self.foreign_field = type_name.map(|type_name| (type_name, &**x));
+                            Ok(Some(BinKeyDecoder::Foreign(x)))
+                        }
                     }
                 } else {
                     Ok(None)
@@ -1,15 +1,31 @@
 use marshal::context::Context;
 use marshal_core::derive_encoder_for_newtype;
-use marshal_core::encode::{AnySpecEncoder, Encoder};
+use marshal_core::encode::{AnySpecEncoder, Encoder, SpecEncoder};
 use marshal_core::encode::poison::PoisonEncoder;
 
 use crate::encode::{BinEncoderSchema, SimpleBinSpecEncoder};
+use crate::slice::PackedPrimitive;
 use crate::SerializeBin;
 
 pub struct BinSpecEncoder<'s>(PoisonEncoder<SimpleBinSpecEncoder<'s>>);
 
 derive_encoder_for_newtype!(BinSpecEncoder<'s>(PoisonEncoder<SimpleBinSpecEncoder<'s>>));
 
+impl<'s> BinSpecEncoder<'s> {
+    /// Encodes a sequence of primitives, as a single packed sequence if packing is enabled.
+    #[inline]
+    pub(crate) fn encode_packed<T: PackedPrimitive>(
+        &mut self,
+        any: <Self as SpecEncoder>::AnySpecEncoder,
+        slice: &[T],
+    ) -> anyhow::Result<()> {
+        self.0.inner_mut().pack_next_seq(T::TAG);
+        let seq = self.0.encode_seq(any, slice.len())?;
+        self.0.inner_mut().write_seq_elements(T::TAG, slice)?;
+        self.0.seq_end(seq)
+    }
+}
+
 pub struct BinEncoderBuilder<'s> {
     inner: BinSpecEncoder<'s>,
 }
@@ -20,6 +36,28 @@ impl<'s> BinEncoderBuilder<'s> {
             inner: BinSpecEncoder(PoisonEncoder::new(SimpleBinSpecEncoder::new(schema))),
         }
     }
+    /// Produces a message that starts with an empty schema, so that it can be decoded alone.
+    /// Repeated definitions within the message are still shared.
+    pub fn set_self_contained(&mut self, self_contained: bool) -> &mut Self {
+        self.inner.0.inner_mut().set_self_contained(self_contained);
+        self
+    }
+    /// Writes containers with byte-length prefixes, so that a decoder can ignore them in
+    /// constant time. Either form is readable by the same decoder.
+    pub fn set_skippable(&mut self, skippable: bool) -> &mut Self {
+        self.inner.0.inner_mut().set_skippable(skippable);
+        self
+    }
+    /// Writes `Vec`s and slices of numbers as [PackedSeq](crate::TypeTag::PackedSeq), which is smaller and faster
+    /// to read, but cannot be read by decoders that predate that tag. Off by default.
+    pub fn set_packed(&mut self, packed: bool) -> &mut Self {
+        self.inner.0.inner_mut().set_pack_seqs(packed);
+        self
+    }
+    pub(crate) fn set_inline_definitions(&mut self, inline_definitions: bool) -> &mut Self {
+        self.inner.0.inner_mut().set_inline_definitions(inline_definitions);
+        self
+    }
     pub fn build<'w>(&'w mut self) -> AnySpecEncoder<'w, BinSpecEncoder<'s>> {
         let any = self.inner.0.start(());
         AnySpecEncoder::new(&mut self.inner, any)
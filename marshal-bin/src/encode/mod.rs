@@ -6,31 +6,145 @@ use by_address::ByAddress;
 use marshal_core::encode::SpecEncoder;
 use marshal_core::Primitive;
 use marshal_vu128::{ToFromVu128, VU128_PADDING, WriteVu128};
+use crate::decode::BinDecoderSchema;
+use crate::util::{fingerprint, Interned, StringInterner};
 use crate::{TypeTag};
 
 pub mod full;
 
 pub struct BinEncoderSchema {
     enum_def_indexes: HashMap<ByAddress<&'static [&'static str]>, usize>,
+    restored_indexes: HashMap<Vec<String>, usize>,
+    pub(crate) enum_defs: Vec<Vec<String>>,
+    strings: StringInterner,
 }
 
+/// The size of a [BinEncoderSchema] before a message, for undoing a message that failed.
+pub(crate) struct BinEncoderSnapshot {
+    enum_defs: usize,
+    strings: Option<StringInterner>,
+}
+
+/// Longer strings are unlikely to repeat, so they are not interned.
+const MAX_INTERNED_LEN: usize = 128;
+
 impl BinEncoderSchema {
     pub fn new() -> Self {
         BinEncoderSchema {
             enum_def_indexes: HashMap::new(),
+            restored_indexes: HashMap::new(),
+            enum_defs: vec![],
+            strings: StringInterner::new(0),
+        }
+    }
+    /// Replaces each repeated string with a reference to a table of up to `capacity` strings,
+    /// which is shared by the messages encoded with this schema. Messages must be decoded in
+    /// order by a [BinDecoderSchema] with at least the same capacity. Interning is disabled by
+    /// default, and is not used for self-contained messages.
+    pub fn set_string_capacity(&mut self, capacity: usize) -> &mut Self {
+        self.strings = StringInterner::new(capacity);
+        self
+    }
+    pub(crate) fn snapshot(&self) -> BinEncoderSnapshot {
+        BinEncoderSnapshot {
+            enum_defs: self.enum_defs.len(),
+            // The table is only copied if interning is enabled.
+            strings: (self.strings.capacity() > 0).then(|| self.strings.clone()),
+        }
+    }
+    /// Forgets the definitions and strings added since `snapshot` was taken.
+    pub(crate) fn rollback(&mut self, snapshot: BinEncoderSnapshot) {
+        self.enum_defs.truncate(snapshot.enum_defs);
+        self.enum_def_indexes.retain(|_, index| *index < snapshot.enum_defs);
+        if let Some(strings) = snapshot.strings {
+            self.strings = strings;
         }
     }
+    pub(crate) fn string_capacity(&self) -> usize {
+        self.strings.capacity()
+    }
+    pub fn fingerprint(&self) -> u64 {
+        fingerprint(
+            self.enum_defs.iter().map(|x| x.as_slice()),
+            self.strings.capacity(),
+            self.strings.strings(),
+        )
+    }
+    /// Serializes the definitions and interned strings written so far. The result can be restored
+    /// as either a [BinEncoderSchema] or a [BinDecoderSchema].
+    pub fn save(&self) -> Vec<u8> {
+        save_schema(
+            self.fingerprint(),
+            self.enum_defs.iter().map(|x| x.as_slice()),
+            self.strings.capacity(),
+            self.strings.strings(),
+        )
+    }
+    /// Restores a schema saved by [BinEncoderSchema::save] or [BinDecoderSchema::save], so that
+    /// the stream can be continued by another process.
+    pub fn restore(data: &[u8]) -> anyhow::Result<Self> {
+        let decoder = BinDecoderSchema::restore(data)?;
+        let mut result = BinEncoderSchema::new();
+        for (index, fields) in decoder.enum_def_fields().enumerate() {
+            result.restored_indexes.insert(fields.to_vec(), index);
+            result.enum_defs.push(fields.to_vec());
+        }
+        let capacity = decoder.string_capacity();
+        result.strings = StringInterner::with_strings(capacity, decoder.into_strings()?);
+        Ok(result)
+    }
+}
+
+pub(crate) fn write_enum_def<'a>(
+    output: &mut Vec<u8>,
+    fields: impl ExactSizeIterator<Item = &'a str>,
+) {
+    output.push(TypeTag::EnumDef as u8);
+    output.write_vu128(fields.len() as u64);
+    for field in fields {
+        output.write_vu128(field.len() as u64);
+        output.extend_from_slice(field.as_bytes());
+    }
+}
+
+pub(crate) fn write_string_def(output: &mut Vec<u8>, index: usize, value: &str) {
+    output.push(TypeTag::StringDef as u8);
+    output.write_vu128(index as u64);
+    output.write_vu128(value.len() as u64);
+    output.extend_from_slice(value.as_bytes());
+}
+
+/// Writes a fingerprint and the string capacity, followed by the definitions as they appear in a
+/// message.
+pub(crate) fn save_schema<'a>(
+    fingerprint: u64,
+    enum_defs: impl Iterator<Item = &'a [String]>,
+    string_capacity: usize,
+    strings: impl Iterator<Item = (usize, &'a str)>,
+) -> Vec<u8> {
+    let mut output = fingerprint.to_le_bytes().to_vec();
+    output.write_vu128(string_capacity as u64);
+    for fields in enum_defs {
+        write_enum_def(&mut output, fields.iter().map(|x| x.as_str()));
+    }
+    for (index, value) in strings {
+        write_string_def(&mut output, index, value);
+    }
+    output.resize(output.len() + VU128_PADDING, 0);
+    output
 }
 
 #[derive(Debug)]
 pub enum BinEncoderError {
     MissingLen,
+    TooLarge,
 }
 
 impl Display for BinEncoderError {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
             BinEncoderError::MissingLen => write!(f, "cannot encode repeated data without length"),
+            BinEncoderError::TooLarge => write!(f, "skippable value is larger than 4GiB"),
         }
     }
 }
@@ -40,6 +154,14 @@ impl std::error::Error for BinEncoderError {}
 pub struct SimpleBinSpecEncoder<'s> {
     output: Vec<u8>,
     schema: &'s mut BinEncoderSchema,
+    self_contained: bool,
+    message_schema: BinEncoderSchema,
+    inline_definitions: bool,
+    skippable: bool,
+    skippable_starts: Vec<usize>,
+    pending_defs: Vec<u8>,
+    pack_seqs: bool,
+    packed: Option<TypeTag>,
 }
 
 impl<'s> SimpleBinSpecEncoder<'s> {
@@ -48,6 +170,77 @@ impl<'s> SimpleBinSpecEncoder<'s> {
         SimpleBinSpecEncoder {
             output: vec![],
             schema,
+            self_contained: false,
+            message_schema: BinEncoderSchema::new(),
+            inline_definitions: true,
+            skippable: false,
+            skippable_starts: vec![],
+            pending_defs: vec![],
+            pack_seqs: false,
+            packed: None,
+        }
+    }
+    /// Writes definitions into the message itself instead of the shared schema, so that the
+    /// message can be decoded without having seen any other message.
+    pub fn set_self_contained(&mut self, self_contained: bool) {
+        self.self_contained = self_contained;
+    }
+    /// Records new definitions in the schema without writing them, for callers that transmit
+    /// them separately.
+    pub(crate) fn set_inline_definitions(&mut self, inline_definitions: bool) {
+        self.inline_definitions = inline_definitions;
+    }
+    /// Prefixes containers with their length in bytes, so that decoders can ignore them without
+    /// reading their contents.
+    pub fn set_skippable(&mut self, skippable: bool) {
+        self.skippable = skippable;
+    }
+    /// Writes sequences of numbers as [TypeTag::PackedSeq].
+    pub fn set_pack_seqs(&mut self, pack_seqs: bool) {
+        self.pack_seqs = pack_seqs;
+    }
+    #[inline]
+    fn start_skippable(&mut self) {
+        if self.skippable {
+            self.output.push(TypeTag::Skippable as u8);
+            self.output.extend_from_slice(&[0; 4]);
+            self.skippable_starts.push(self.output.len());
+        }
+    }
+    #[inline]
+    fn end_skippable(&mut self) -> anyhow::Result<()> {
+        if !self.skippable {
+            return Ok(());
+        }
+        let start = self.skippable_starts.pop().unwrap();
+        let len = u32::try_from(self.output.len() - start).map_err(|_| BinEncoderError::TooLarge)?;
+        self.output[start - 4..start].copy_from_slice(&len.to_le_bytes());
+        if self.skippable_starts.is_empty() && !self.pending_defs.is_empty() {
+            // Definitions must not be skipped, so they are moved in front of the outermost value.
+            let tag = start - 5;
+            self.output.splice(tag..tag, self.pending_defs.drain(..));
+        }
+        Ok(())
+    }
+    /// Returns the slot of an interned string, writing its definition if it is new.
+    fn intern_str(&mut self, value: &str) -> Option<usize> {
+        if self.self_contained || value.len() > MAX_INTERNED_LEN {
+            return None;
+        }
+        // Evicting a string inside a skippable value could redefine a slot before earlier
+        // references to it are read, because definitions are moved in front of the value.
+        let evict = self.skippable_starts.is_empty();
+        match self.schema.strings.intern(value, evict)? {
+            Interned::Existing(index) => Some(index),
+            Interned::New(index) => {
+                let output = if evict {
+                    &mut self.output
+                } else {
+                    &mut self.pending_defs
+                };
+                write_string_def(output, index, value);
+                Some(index)
+            }
         }
     }
     #[inline]
@@ -66,6 +259,31 @@ impl<'s> SimpleBinSpecEncoder<'s> {
         self.output.write_vu128(value);
         Ok(())
     }
+    /// Writes `values` as the elements of a sequence of type `element`, untagged if the
+    /// sequence is packed.
+    #[inline]
+    pub(crate) fn write_seq_elements<T: ToFromVu128>(
+        &mut self,
+        element: TypeTag,
+        values: &[T],
+    ) -> anyhow::Result<()> {
+        if self.pack_seqs {
+            self.output.write_vu128_slice(values);
+        } else {
+            for &value in values {
+                self.write_tag(element)?;
+                self.write_vu128(value)?;
+            }
+        }
+        Ok(())
+    }
+    /// Writes the next sequence as untagged elements of type `element`, if packing is enabled.
+    #[inline]
+    pub(crate) fn pack_next_seq(&mut self, element: TypeTag) {
+        if self.pack_seqs {
+            self.packed = Some(element);
+        }
+    }
     #[inline]
     pub fn write_tag(&mut self, tag: TypeTag) -> anyhow::Result<()> {
         self.output.push(tag as u8);
@@ -90,18 +308,33 @@ impl<'s> SimpleBinSpecEncoder<'s> {
         &mut self,
         variants: &'static [&'static str],
     ) -> anyhow::Result<usize> {
-        if let Some(index) = self.schema.enum_def_indexes.get(&ByAddress(variants)) {
+        let schema = if self.self_contained {
+            &mut self.message_schema
+        } else {
+            &mut *self.schema
+        };
+        if let Some(index) = schema.enum_def_indexes.get(&ByAddress(variants)) {
             return Ok(*index);
         }
-        self.write_tag(TypeTag::EnumDef)?;
-        self.write_usize(variants.len())?;
-        for x in variants {
-            self.write_str_slice(x)?;
+        let fields: Vec<String> = variants.iter().map(|x| x.to_string()).collect();
+        if let Some(&index) = schema.restored_indexes.get(&fields) {
+            schema.enum_def_indexes.insert(ByAddress(variants), index);
+            return Ok(index);
+        }
+        let output = if self.skippable_starts.is_empty() {
+            &mut self.output
+        } else {
+            &mut self.pending_defs
+        };
+        if self.self_contained && schema.enum_defs.is_empty() {
+            output.push(TypeTag::LocalSchema as u8);
+        }
+        if self.inline_definitions {
+            write_enum_def(output, variants.iter().cloned());
         }
-        let new_index = self.schema.enum_def_indexes.len();
-        self.schema
-            .enum_def_indexes
-            .insert(ByAddress(variants), new_index);
+        let new_index = schema.enum_defs.len();
+        schema.enum_defs.push(fields);
+        schema.enum_def_indexes.insert(ByAddress(variants), new_index);
         Ok(new_index)
     }
 }
@@ -187,8 +420,13 @@ impl<'s> SpecEncoder for SimpleBinSpecEncoder<'s> {
 
     #[inline]
     fn encode_str(&mut self, _any: Self::AnySpecEncoder, s: &str) -> anyhow::Result<()> {
-        self.write_tag(TypeTag::String)?;
-        self.write_str_slice(s)?;
+        if let Some(index) = self.intern_str(s) {
+            self.write_tag(TypeTag::StringRef)?;
+            self.write_usize(index)?;
+        } else {
+            self.write_tag(TypeTag::String)?;
+            self.write_str_slice(s)?;
+        }
         Ok(())
     }
 
@@ -230,6 +468,7 @@ impl<'s> SpecEncoder for SimpleBinSpecEncoder<'s> {
         _name: &'static str,
         len: usize,
     ) -> anyhow::Result<Self::TupleStructEncoder> {
+        self.start_skippable();
         self.write_tag(TypeTag::TupleStruct)?;
         self.write_usize(len)?;
         Ok(())
@@ -243,6 +482,7 @@ impl<'s> SpecEncoder for SimpleBinSpecEncoder<'s> {
         fields: &'static [&'static str],
     ) -> anyhow::Result<Self::StructEncoder> {
         let def = self.get_or_write_enum_def(fields)?;
+        self.start_skippable();
         self.write_tag(TypeTag::Struct)?;
         self.write_usize(def)?;
         Ok(())
@@ -277,6 +517,7 @@ impl<'s> SpecEncoder for SimpleBinSpecEncoder<'s> {
         self.write_tag(TypeTag::Enum)?;
         self.write_usize(enum_def)?;
         self.write_usize(variant_index)?;
+        self.start_skippable();
         self.write_tag(TypeTag::TupleStruct)?;
         self.write_usize(len)?;
         Ok(())
@@ -296,6 +537,7 @@ impl<'s> SpecEncoder for SimpleBinSpecEncoder<'s> {
         self.write_tag(TypeTag::Enum)?;
         self.write_usize(variant_def)?;
         self.write_usize(variant_index)?;
+        self.start_skippable();
         self.write_tag(TypeTag::Struct)?;
         self.write_usize(field_def)?;
         Ok(())
@@ -307,7 +549,13 @@ impl<'s> SpecEncoder for SimpleBinSpecEncoder<'s> {
         _any: Self::AnySpecEncoder,
         len: usize,
     ) -> anyhow::Result<Self::SeqEncoder> {
-        self.write_tag(TypeTag::Seq)?;
+        self.start_skippable();
+        if let Some(element) = self.packed.take() {
+            self.write_tag(TypeTag::PackedSeq)?;
+            self.write_tag(element)?;
+        } else {
+            self.write_tag(TypeTag::Seq)?;
+        }
         self.write_usize(len)?;
         Ok(())
     }
@@ -318,6 +566,7 @@ impl<'s> SpecEncoder for SimpleBinSpecEncoder<'s> {
         _any: Self::AnySpecEncoder,
         len: usize,
     ) -> anyhow::Result<Self::TupleEncoder> {
+        self.start_skippable();
         self.write_tag(TypeTag::Tuple)?;
         self.write_usize(len)?;
         Ok(())
@@ -329,6 +578,7 @@ impl<'s> SpecEncoder for SimpleBinSpecEncoder<'s> {
         _any: Self::AnySpecEncoder,
         len: usize,
     ) -> anyhow::Result<Self::MapEncoder> {
+        self.start_skippable();
         self.write_tag(TypeTag::Map)?;
         self.write_usize(len)?;
         Ok(())
@@ -349,7 +599,7 @@ impl<'s> SpecEncoder for SimpleBinSpecEncoder<'s> {
 
     #[inline]
     fn tuple_end(&mut self, _tuple: Self::TupleEncoder) -> anyhow::Result<()> {
-        Ok(())
+        self.end_skippable()
     }
 
     #[inline]
@@ -362,7 +612,7 @@ impl<'s> SpecEncoder for SimpleBinSpecEncoder<'s> {
 
     #[inline]
     fn seq_end(&mut self, _tuple: Self::SeqEncoder) -> anyhow::Result<()> {
-        Ok(())
+        self.end_skippable()
     }
 
     #[inline]
@@ -375,7 +625,7 @@ impl<'s> SpecEncoder for SimpleBinSpecEncoder<'s> {
 
     #[inline]
     fn map_end(&mut self, _map: Self::MapEncoder) -> anyhow::Result<()> {
-        Ok(())
+        self.end_skippable()
     }
 
     #[inline]
@@ -401,7 +651,7 @@ impl<'s> SpecEncoder for SimpleBinSpecEncoder<'s> {
 
     #[inline]
     fn tuple_struct_end(&mut self, _map: Self::TupleStructEncoder) -> anyhow::Result<()> {
-        Ok(())
+        self.end_skippable()
     }
 
     #[inline]
@@ -415,7 +665,7 @@ impl<'s> SpecEncoder for SimpleBinSpecEncoder<'s> {
 
     #[inline]
     fn struct_end(&mut self, _map: Self::StructEncoder) -> anyhow::Result<()> {
-        Ok(())
+        self.end_skippable()
     }
 
     #[inline]
@@ -428,7 +678,7 @@ impl<'s> SpecEncoder for SimpleBinSpecEncoder<'s> {
 
     #[inline]
     fn tuple_variant_end(&mut self, _map: Self::TupleVariantEncoder) -> anyhow::Result<()> {
-        Ok(())
+        self.end_skippable()
     }
 
     #[inline]
@@ -442,7 +692,7 @@ impl<'s> SpecEncoder for SimpleBinSpecEncoder<'s> {
 
     #[inline]
     fn struct_variant_end(&mut self, _map: Self::StructVariantEncoder) -> anyhow::Result<()> {
-        Ok(())
+        self.end_skippable()
     }
 
     #[inline]
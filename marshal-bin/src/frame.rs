@@ -0,0 +1,334 @@
+use std::fmt::{Debug, Display, Formatter};
+use std::io::{ErrorKind, Read, Write};
+
+use marshal::context::Context;
+use marshal_vu128::{ReadVu128, WriteVu128, VU128_PADDING};
+use num_derive::FromPrimitive;
+use num_traits::FromPrimitive;
+
+use crate::decode::full::BinDecoderBuilder;
+use crate::decode::BinDecoderSchema;
+use crate::encode::full::BinEncoderBuilder;
+use crate::encode::{write_enum_def, BinEncoderSchema};
+use crate::util::crc32c;
+use crate::{DeserializeBin, SerializeBin};
+
+/// Marks the start of every frame, so that a reader can find the next frame after corruption.
+const FRAME_MAGIC: [u8; 2] = [0xB1, 0xF5];
+
+const CHECKSUM_LEN: usize = 4;
+
+/// The most bytes a vu128-encoded `u64` can occupy.
+const MAX_LEN_LEN: usize = 9;
+
+#[derive(Debug, Copy, Clone, Eq, Ord, PartialEq, PartialOrd, Hash, FromPrimitive)]
+pub enum FrameKind {
+    /// A message using the stream's schema, preceded by the definitions it adds to the schema.
+    Message = 0,
+    /// A message that carries its own definitions.
+    SelfContained = 1,
+    /// A complete schema, as written by [BinEncoderSchema::save].
+    Schema = 2,
+    /// The fingerprint and string capacity of the writer's schema. The reader checks the
+    /// fingerprint against its own, after adopting the capacity if it started from an empty
+    /// schema. This is the first frame of a stream.
+    Handshake = 3,
+}
+
+#[derive(Debug)]
+pub enum BinFrameError {
+    Corrupted,
+    Truncated,
+    BadKind(u8),
+    SchemaGap { expected: usize, found: usize },
+    SchemaMismatch { writer: u64, reader: u64 },
+}
+
+impl Display for BinFrameError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BinFrameError::Corrupted => write!(f, "frame is corrupted"),
+            BinFrameError::Truncated => write!(f, "stream ended in the middle of a frame"),
+            BinFrameError::BadKind(x) => write!(f, "unknown frame kind {}", x),
+            BinFrameError::SchemaGap { expected, found } => write!(
+                f,
+                "frame extends the schema from definition {} but {} definitions are known (was a frame lost?)",
+                found, expected
+            ),
+            BinFrameError::SchemaMismatch { writer, reader } => write!(
+                f,
+                "writer's schema has fingerprint {:016x} but reader's has {:016x} (do the schemas have the same string capacity?)",
+                writer, reader
+            ),
+        }
+    }
+}
+
+impl std::error::Error for BinFrameError {}
+
+/// Writes `marshal-bin` messages to a byte stream, each in a checksummed frame.
+pub struct FrameWriter<W> {
+    writer: W,
+    schema: BinEncoderSchema,
+    handshake_written: bool,
+}
+
+impl<W: Write> FrameWriter<W> {
+    pub fn new(writer: W) -> Self {
+        Self::with_schema(writer, BinEncoderSchema::new())
+    }
+    /// Continues a stream whose schema was restored with [BinEncoderSchema::restore].
+    pub fn with_schema(writer: W, schema: BinEncoderSchema) -> Self {
+        FrameWriter {
+            writer,
+            schema,
+            handshake_written: false,
+        }
+    }
+    pub fn schema(&self) -> &BinEncoderSchema {
+        &self.schema
+    }
+    fn write_frame(&mut self, body: &[u8]) -> anyhow::Result<()> {
+        let mut header = FRAME_MAGIC.to_vec();
+        header.write_vu128(body.len() as u64);
+        header.extend_from_slice(&crc32c(body).to_le_bytes());
+        self.writer.write_all(&header)?;
+        self.writer.write_all(body)?;
+        Ok(())
+    }
+    /// Writes the handshake before the first frame, so that the reader can check that it starts
+    /// from the same schema.
+    fn write_handshake(&mut self) -> anyhow::Result<()> {
+        if !self.handshake_written {
+            let mut body = vec![FrameKind::Handshake as u8];
+            body.extend_from_slice(&self.schema.fingerprint().to_le_bytes());
+            body.write_vu128(self.schema.string_capacity() as u64);
+            self.write_frame(&body)?;
+            self.handshake_written = true;
+        }
+        Ok(())
+    }
+    pub fn write<T: SerializeBin>(&mut self, value: &T, ctx: Context) -> anyhow::Result<()> {
+        self.write_handshake()?;
+        let base = self.schema.enum_defs.len();
+        let snapshot = self.schema.snapshot();
+        let mut builder = BinEncoderBuilder::new(&mut self.schema);
+        builder.set_inline_definitions(false);
+        let payload = match builder.serialize(value, ctx) {
+            Ok(payload) => payload,
+            Err(error) => {
+                // The frame is not written, so neither are the definitions it added.
+                self.schema.rollback(snapshot);
+                return Err(error);
+            }
+        };
+        let mut body = vec![FrameKind::Message as u8];
+        body.write_vu128(base as u64);
+        body.write_vu128((self.schema.enum_defs.len() - base) as u64);
+        for fields in &self.schema.enum_defs[base..] {
+            write_enum_def(&mut body, fields.iter().map(|x| x.as_str()));
+        }
+        body.extend_from_slice(&payload[..payload.len() - VU128_PADDING]);
+        self.write_frame(&body)
+    }
+    /// Writes a message that can be decoded even if earlier frames were lost.
+    pub fn write_self_contained<T: SerializeBin>(
+        &mut self,
+        value: &T,
+        ctx: Context,
+    ) -> anyhow::Result<()> {
+        self.write_handshake()?;
+        let mut builder = BinEncoderBuilder::new(&mut self.schema);
+        builder.set_self_contained(true);
+        let payload = builder.serialize(value, ctx)?;
+        let mut body = vec![FrameKind::SelfContained as u8];
+        body.extend_from_slice(&payload[..payload.len() - VU128_PADDING]);
+        self.write_frame(&body)
+    }
+    /// Writes the whole schema, which lets readers recover from lost frames.
+    pub fn write_schema(&mut self) -> anyhow::Result<()> {
+        self.write_handshake()?;
+        let saved = self.schema.save();
+        let mut body = vec![FrameKind::Schema as u8];
+        body.extend_from_slice(&saved[..saved.len() - VU128_PADDING]);
+        self.write_frame(&body)
+    }
+    pub fn flush(&mut self) -> anyhow::Result<()> {
+        Ok(self.writer.flush()?)
+    }
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+/// Reads messages written by a [FrameWriter]. After an error, reading resumes at the next frame.
+pub struct FrameReader<R> {
+    reader: R,
+    schema: BinDecoderSchema,
+    buffer: Vec<u8>,
+    max_frame_len: usize,
+    resync: bool,
+    /// Whether the string capacity is taken from the handshake instead of the schema.
+    adopt_capacity: bool,
+}
+
+impl<R: Read> FrameReader<R> {
+    /// Reads a stream from its start, using the string capacity announced by the writer.
+    pub fn new(reader: R) -> Self {
+        let mut result = Self::with_schema(reader, BinDecoderSchema::new());
+        result.adopt_capacity = true;
+        result
+    }
+    /// Resumes a stream whose schema was restored with [BinDecoderSchema::restore]. The schema
+    /// must have the same string capacity as the writer's.
+    pub fn with_schema(reader: R, schema: BinDecoderSchema) -> Self {
+        FrameReader {
+            reader,
+            schema,
+            buffer: vec![],
+            max_frame_len: 1 << 26,
+            resync: false,
+            adopt_capacity: false,
+        }
+    }
+    /// Frames claiming to be longer than this are treated as corrupted.
+    pub fn set_max_frame_len(&mut self, max_frame_len: usize) -> &mut Self {
+        self.max_frame_len = max_frame_len;
+        self
+    }
+    pub fn schema(&self) -> &BinDecoderSchema {
+        &self.schema
+    }
+
+    /// Reads until the buffer holds `len` bytes, returning false if the stream ends first.
+    fn fill(&mut self, len: usize) -> anyhow::Result<bool> {
+        let mut chunk = [0u8; 8192];
+        while self.buffer.len() < len {
+            let count = match self.reader.read(&mut chunk) {
+                Ok(count) => count,
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e.into()),
+            };
+            if count == 0 {
+                return Ok(false);
+            }
+            self.buffer.extend_from_slice(&chunk[..count]);
+        }
+        Ok(true)
+    }
+
+    /// Discards the start of the failed frame and any input before the next [FRAME_MAGIC].
+    fn skip_to_magic(&mut self) -> anyhow::Result<()> {
+        self.buffer.drain(..self.buffer.len().min(1));
+        loop {
+            let found = self
+                .buffer
+                .windows(FRAME_MAGIC.len())
+                .position(|x| x == FRAME_MAGIC);
+            if let Some(position) = found {
+                self.buffer.drain(..position);
+                return Ok(());
+            }
+            // Keep the last byte, which may begin a magic that is split across reads.
+            self.buffer
+                .drain(..self.buffer.len().saturating_sub(FRAME_MAGIC.len() - 1));
+            if !self.fill(self.buffer.len() + 1)? {
+                self.buffer.clear();
+                return Ok(());
+            }
+        }
+    }
+
+    fn corrupted(&mut self) -> anyhow::Result<Option<Vec<u8>>> {
+        self.resync = true;
+        Err(BinFrameError::Corrupted.into())
+    }
+
+    /// Reads the body of the next frame, followed by padding for decoding.
+    fn read_frame(&mut self) -> anyhow::Result<Option<Vec<u8>>> {
+        if self.resync {
+            self.resync = false;
+            self.skip_to_magic()?;
+        }
+        if !self.fill(1)? {
+            return Ok(None);
+        }
+        if !self.fill(FRAME_MAGIC.len())? || self.buffer[..FRAME_MAGIC.len()] != FRAME_MAGIC {
+            return self.corrupted();
+        }
+        self.fill(FRAME_MAGIC.len() + MAX_LEN_LEN)?;
+        let mut len_bytes = self.buffer[FRAME_MAGIC.len()..].to_vec();
+        len_bytes.truncate(MAX_LEN_LEN);
+        len_bytes.resize(VU128_PADDING, 0);
+        let mut cursor = len_bytes.as_slice();
+        let len = cursor.read_vu128::<u64>()?;
+        let header_len = FRAME_MAGIC.len() + VU128_PADDING - cursor.len() + CHECKSUM_LEN;
+        if len > self.max_frame_len as u64 {
+            return self.corrupted();
+        }
+        let frame_len = header_len + len as usize;
+        if !self.fill(frame_len)? {
+            self.buffer.clear();
+            return Err(BinFrameError::Truncated.into());
+        }
+        let checksum = &self.buffer[header_len - CHECKSUM_LEN..header_len];
+        let body = &self.buffer[header_len..frame_len];
+        if crc32c(body).to_le_bytes() != checksum {
+            return self.corrupted();
+        }
+        let mut body = body.to_vec();
+        body.resize(body.len() + VU128_PADDING, 0);
+        self.buffer.drain(..frame_len);
+        Ok(Some(body))
+    }
+
+    /// Reads the next message, or returns `None` at the end of the stream.
+    pub fn read<T: DeserializeBin>(&mut self, ctx: Context) -> anyhow::Result<Option<T>> {
+        loop {
+            let Some(body) = self.read_frame()? else {
+                return Ok(None);
+            };
+            let kind = FrameKind::from_u8(body[0]).ok_or(BinFrameError::BadKind(body[0]))?;
+            let mut content = &body[1..];
+            match kind {
+                FrameKind::Message => {
+                    let base = usize::try_from(content.read_vu128::<u64>()?)?;
+                    let count = usize::try_from(content.read_vu128::<u64>()?)?;
+                    if base != self.schema.len() {
+                        return Err(BinFrameError::SchemaGap {
+                            expected: self.schema.len(),
+                            found: base,
+                        }
+                        .into());
+                    }
+                    let read = self.schema.read_enum_defs(content, count)?;
+                    let content = &content[read..];
+                    return Ok(Some(
+                        BinDecoderBuilder::new(content, &mut self.schema).deserialize(ctx)?,
+                    ));
+                }
+                FrameKind::SelfContained => {
+                    return Ok(Some(
+                        BinDecoderBuilder::new(content, &mut self.schema).deserialize(ctx)?,
+                    ));
+                }
+                FrameKind::Schema => self.schema = BinDecoderSchema::restore(content)?,
+                FrameKind::Handshake => {
+                    let writer = u64::from_le_bytes(
+                        *content.first_chunk::<8>().ok_or(BinFrameError::Truncated)?,
+                    );
+                    let mut rest = &content[8..];
+                    let capacity = usize::try_from(rest.read_vu128::<u64>()?)?;
+                    if self.adopt_capacity {
+                        self.adopt_capacity = false;
+                        self.schema.set_string_capacity(capacity);
+                    }
+                    let reader = self.schema.fingerprint();
+                    if writer != reader {
+                        return Err(BinFrameError::SchemaMismatch { writer, reader }.into());
+                    }
+                }
+            }
+        }
+    }
+}
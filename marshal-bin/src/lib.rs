@@ -10,6 +10,8 @@
 #![feature(coerce_unsized)]
 #![feature(unsize)]
 #![feature(type_alias_impl_trait)]
+#![feature(specialization)]
+#![allow(incomplete_features)]
 
 use num_derive::FromPrimitive;
 
@@ -21,6 +23,8 @@ use crate::encode::full::BinEncoder;
 
 pub mod decode;
 pub mod encode;
+pub mod frame;
+mod slice;
 #[cfg(test)]
 mod test;
 mod util;
@@ -54,6 +58,14 @@ pub enum TypeTag {
     Bytes = 24,
     None = 25,
     Some = 26,
+    LocalSchema = 27,
+    Skippable = 28,
+    StringDef = 29,
+    StringRef = 30,
+    /// A sequence of untagged primitives, written as the element's tag, the length and the values.
+    /// Only written when [BinEncoderBuilder::set_packed](encode::full::BinEncoderBuilder::set_packed)
+    /// is enabled, because decoders that predate this tag cannot read it.
+    PackedSeq = 31,
 }
 
 
@@ -0,0 +1,103 @@
+use marshal::context::Context;
+use marshal::de::Deserialize;
+use marshal::decode::{AnyDecoder, DecodeHint, DecoderView};
+use marshal::encode::AnyEncoder;
+use marshal::ser::Serialize;
+use marshal_vu128::ToFromVu128;
+
+use crate::decode::full::BinDecoder;
+use crate::encode::full::BinEncoder;
+use crate::TypeTag;
+
+/// A primitive that can be an element of a [TypeTag::PackedSeq].
+pub(crate) trait PackedPrimitive: ToFromVu128 {
+    const TAG: TypeTag;
+}
+
+pub(crate) fn is_packable(tag: TypeTag) -> bool {
+    matches!(
+        tag,
+        TypeTag::I8
+            | TypeTag::I16
+            | TypeTag::I32
+            | TypeTag::I64
+            | TypeTag::I128
+            | TypeTag::U16
+            | TypeTag::U32
+            | TypeTag::U64
+            | TypeTag::U128
+            | TypeTag::F32
+            | TypeTag::F64
+    )
+}
+
+macro_rules! impl_packed_slice {
+    ($($ty:ty => $tag:ident),*) => {
+        $(
+            impl PackedPrimitive for $ty {
+                const TAG: TypeTag = TypeTag::$tag;
+            }
+
+            impl Serialize<BinEncoder> for [$ty] {
+                #[inline]
+                fn serialize<'w, 'en>(
+                    &self,
+                    e: AnyEncoder<'w, 'en, BinEncoder>,
+                    _ctx: Context,
+                ) -> anyhow::Result<()> {
+                    let (encoder, any) = e.into_raw();
+                    encoder.encode_packed(any, self)
+                }
+            }
+
+            impl Serialize<BinEncoder> for Vec<$ty> {
+                #[inline]
+                fn serialize<'w, 'en>(
+                    &self,
+                    e: AnyEncoder<'w, 'en, BinEncoder>,
+                    ctx: Context,
+                ) -> anyhow::Result<()> {
+                    self.as_slice().serialize(e, ctx)
+                }
+            }
+
+            impl Deserialize<BinDecoder> for Vec<$ty> {
+                #[inline]
+                fn deserialize<'p, 'de>(
+                    d: AnyDecoder<'p, 'de, BinDecoder>,
+                    mut ctx: Context,
+                ) -> anyhow::Result<Self> {
+                    match d.decode(DecodeHint::Seq)? {
+                        DecoderView::Seq(mut seq) => {
+                            if let Some((decoder, raw)) = seq.raw_mut() {
+                                if let Some(result) = decoder.read_packed_slice(raw)? {
+                                    seq.ignore()?;
+                                    return Ok(result);
+                                }
+                            }
+                            seq.seq_into_iter(|x| {
+                                <$ty as Deserialize<BinDecoder>>::deserialize(x, ctx.reborrow())
+                            })
+                            .collect()
+                        }
+                        unexpected => unexpected.mismatch("seq")?,
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_packed_slice!(
+    i8 => I8,
+    i16 => I16,
+    i32 => I32,
+    i64 => I64,
+    i128 => I128,
+    u16 => U16,
+    u32 => U32,
+    u64 => U64,
+    u128 => U128,
+    f32 => F32,
+    f64 => F64
+);
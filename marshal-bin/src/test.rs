@@ -1,16 +1,19 @@
 use std::collections::BTreeMap;
 use std::fmt::Debug;
 
-use marshal::context::OwnedContext;
+use marshal::context::{Context, OwnedContext};
 use marshal::de::Deserialize;
 use marshal::ser::Serialize;
+use marshal_core::encode::{AnyEncoder, Encoder};
 use marshal_derive::{Deserialize, Serialize};
-use marshal_vu128::VU128_PADDING;
+use marshal_vu128::{WriteVu128, VU128_PADDING};
 use crate::{BinDecoder};
-use crate::decode::BinDecoderSchema;
+use crate::decode::{BinDecoderSchema, UnmatchedField};
 use crate::decode::full::BinDecoderBuilder;
 use crate::encode::BinEncoderSchema;
 use crate::encode::full::{BinEncoder, BinEncoderBuilder};
+use crate::frame::{BinFrameError, FrameReader, FrameWriter};
+use crate::util::crc32c;
 
 #[track_caller]
 fn test_round_trip<
@@ -68,6 +71,19 @@ fn test_int() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_float() -> anyhow::Result<()> {
+    // F64 values used to be decoded as if they were F32.
+    let mut c = OwnedContext::new();
+    let input = (0.1f64, 0.1f32, f64::MAX);
+    let encoded = BinEncoderBuilder::new(&mut BinEncoderSchema::new()).serialize(&input, c.borrow())?;
+    assert_eq!(encoded[..3], [15, 3, 13]);
+    let found: (f64, f32, f64) =
+        BinDecoderBuilder::new(&encoded, &mut BinDecoderSchema::new()).deserialize(c.borrow())?;
+    assert_eq!(found, input);
+    Ok(())
+}
+
 #[test]
 fn test_tuple() -> anyhow::Result<()> {
     test_round_trip((123u8, 124u16), &[15, 2, 7, 123, 8, 124])?;
@@ -83,6 +99,90 @@ fn test_vec() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[track_caller]
+fn test_packed_round_trip<T: Debug + PartialEq + Serialize<BinEncoder> + Deserialize<BinDecoder>>(
+    input: T,
+    expected: &[u8],
+) -> anyhow::Result<()> {
+    let mut c = OwnedContext::new();
+    let mut encoder_schema = BinEncoderSchema::new();
+    let mut w = BinEncoderBuilder::new(&mut encoder_schema);
+    w.set_packed(true);
+    let encoded = w.serialize(&input, c.borrow())?;
+    assert_eq!(&encoded[..encoded.len() - VU128_PADDING], expected);
+    let found: T =
+        BinDecoderBuilder::new(&encoded, &mut BinDecoderSchema::new()).deserialize(c.borrow())?;
+    assert_eq!(found, input);
+    Ok(())
+}
+
+#[test]
+fn test_packed_seq() -> anyhow::Result<()> {
+    test_packed_round_trip(vec![1u32, 2, 3], &[31, 9, 3, 1, 2, 3])?;
+    test_packed_round_trip(vec![-1i64, 2], &[31, 5, 2, 1, 4])?;
+    test_packed_round_trip(Vec::<u16>::new(), &[31, 8, 0])?;
+    test_packed_round_trip((0..1000u64).map(|x| x * x).collect::<Vec<_>>(), &{
+        let mut expected = vec![31, 10];
+        expected.write_vu128(1000u64);
+        for x in 0..1000u64 {
+            expected.write_vu128(x * x);
+        }
+        expected
+    })?;
+    test_packed_round_trip(vec![1.5f64, -2.0, f64::MAX], &{
+        let mut expected = vec![31, 13, 3];
+        for x in [1.5f64, -2.0, f64::MAX] {
+            expected.write_vu128(x);
+        }
+        expected
+    })?;
+
+    let mut c = OwnedContext::new();
+    // Elements are read one at a time when the types differ.
+    let mut encoder_schema = BinEncoderSchema::new();
+    let mut w = BinEncoderBuilder::new(&mut encoder_schema);
+    w.set_packed(true);
+    let encoded = w.serialize(&vec![1u32, 2], c.borrow())?;
+    assert_eq!(&encoded[..encoded.len() - VU128_PADDING], &[31, 9, 2, 1, 2]);
+    let found: (u32, u32) =
+        BinDecoderBuilder::new(&encoded, &mut BinDecoderSchema::new()).deserialize(c.borrow())?;
+    assert_eq!(found, (1, 2));
+    test_transmute((1u32, 2u32), vec![1u32, 2], &[15, 2, 9, 1, 9, 2])?;
+
+    let mut encoder_schema = BinEncoderSchema::new();
+    let mut w = BinEncoderBuilder::new(&mut encoder_schema);
+    w.set_packed(true).set_skippable(true);
+    let encoded = w.serialize(&vec![4i32, 5], c.borrow())?;
+    assert_eq!(&encoded[..encoded.len() - VU128_PADDING], &[28, 5, 0, 0, 0, 31, 4, 2, 8, 10]);
+    let found: Vec<i32> =
+        BinDecoderBuilder::new(&encoded, &mut BinDecoderSchema::new()).deserialize(c.borrow())?;
+    assert_eq!(found, vec![4, 5]);
+
+    let truncated = [31, 9, 3, 1, 2];
+    let result: anyhow::Result<Vec<u32>> =
+        BinDecoderBuilder::new(&truncated, &mut BinDecoderSchema::new()).deserialize(c.borrow());
+    result.unwrap_err();
+    Ok(())
+}
+
+#[test]
+fn test_unpacked_seq() -> anyhow::Result<()> {
+    // Without set_packed, sequences of numbers stay readable by decoders that predate PackedSeq.
+    test_round_trip(vec![1u32, 2, 3], &[19, 3, 9, 1, 9, 2, 9, 3])?;
+    test_round_trip(vec![-1i64, 2], &[19, 2, 5, 1, 5, 4])?;
+    test_round_trip(Vec::<u16>::new(), &[19, 0])?;
+    test_transmute(vec![1u32, 2], (1u32, 2u32), &[19, 2, 9, 1, 9, 2])?;
+
+    let mut c = OwnedContext::new();
+    let input = vec![1.5f64, -2.0, f64::MAX];
+    let encoded = BinEncoderBuilder::new(&mut BinEncoderSchema::new()).serialize(&input, c.borrow())?;
+    assert_eq!(encoded[..3], [19, 3, 13]);
+    let found: Vec<f64> =
+        BinDecoderBuilder::new(&encoded, &mut BinDecoderSchema::new()).deserialize(c.borrow())?;
+    assert_eq!(found, input);
+    Ok(())
+}
+
 #[test]
 fn test_string() -> anyhow::Result<()> {
     test_round_trip("abc".to_string(), &[22, 3, b'a', b'b', b'c'])?;
@@ -262,6 +362,45 @@ fn test_enum_reorder() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_ignore() -> anyhow::Result<()> {
+    #[derive(Serialize, Deserialize, Debug, Eq, PartialEq)]
+    struct Inner {
+        x: u8,
+    }
+    #[derive(Serialize, Deserialize, Debug, Eq, PartialEq)]
+    enum Mode {
+        Fast,
+        Slow(u8),
+        Custom { level: u8 },
+    }
+    #[derive(Serialize, Deserialize, Debug, Eq, PartialEq)]
+    struct Full {
+        a: u8,
+        inner: Inner,
+        modes: Vec<Mode>,
+        b: u8,
+    }
+    #[derive(Serialize, Deserialize, Debug, Eq, PartialEq)]
+    struct Small {
+        a: u8,
+        b: u8,
+    }
+    let mut c = OwnedContext::new();
+    let input = Full {
+        a: 1,
+        inner: Inner { x: 2 },
+        modes: vec![Mode::Fast, Mode::Slow(3), Mode::Custom { level: 4 }],
+        b: 5,
+    };
+    let encoded = BinEncoderBuilder::new(&mut BinEncoderSchema::new()).serialize(&input, c.borrow())?;
+    // Ignored structs are read field by field, and ignored enums are closed.
+    let found: Small =
+        BinDecoderBuilder::new(&encoded, &mut BinDecoderSchema::new()).deserialize(c.borrow())?;
+    assert_eq!(found, Small { a: 1, b: 5 });
+    Ok(())
+}
+
 #[test]
 fn test_map() -> anyhow::Result<()> {
     test_round_trip::<BTreeMap<u8, u8>>(
@@ -282,3 +421,569 @@ fn test_some() -> anyhow::Result<()> {
     test_round_trip(Some(()), &[26, 0])?;
     Ok(())
 }
+
+#[test]
+fn test_schema_restore() -> anyhow::Result<()> {
+    #[derive(Serialize, Deserialize, Debug, Eq, PartialEq)]
+    enum Foo {
+        A,
+        B { x: u8 },
+    }
+    let mut c = OwnedContext::new();
+
+    let mut encoder_schema = BinEncoderSchema::new();
+    let first = BinEncoderBuilder::new(&mut encoder_schema).serialize(&Foo::A, c.borrow())?;
+    let mut decoder_schema = BinDecoderSchema::new();
+    let found: Foo = BinDecoderBuilder::new(&first, &mut decoder_schema).deserialize(c.borrow())?;
+    assert_eq!(found, Foo::A);
+
+    let saved = encoder_schema.save();
+    assert_eq!(saved, decoder_schema.save());
+    assert_eq!(encoder_schema.fingerprint(), decoder_schema.fingerprint());
+
+    // Continue the stream in a new encoder and decoder.
+    let mut encoder_schema = BinEncoderSchema::restore(&saved)?;
+    let second =
+        BinEncoderBuilder::new(&mut encoder_schema).serialize(&Foo::B { x: 51 }, c.borrow())?;
+    assert_eq!(
+        &second[0..second.len() - VU128_PADDING],
+        &[
+            21, 1, 1, b'x', //
+            18, 0, 1, 16, 1, 7, 51
+        ]
+    );
+    let mut decoder_schema = BinDecoderSchema::restore(&saved)?;
+    let found: Foo = BinDecoderBuilder::new(&second, &mut decoder_schema).deserialize(c.borrow())?;
+    assert_eq!(found, Foo::B { x: 51 });
+    assert_eq!(encoder_schema.fingerprint(), decoder_schema.fingerprint());
+
+    let mut corrupted = saved.clone();
+    *corrupted.iter_mut().rev().find(|x| **x == b'A').unwrap() = b'C';
+    assert!(BinDecoderSchema::restore(&corrupted).is_err());
+    assert!(BinEncoderSchema::restore(&corrupted).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_self_contained() -> anyhow::Result<()> {
+    #[derive(Serialize, Deserialize, Debug, Eq, PartialEq)]
+    enum Foo {
+        A,
+        B,
+    }
+    #[derive(Serialize, Deserialize, Debug, Eq, PartialEq)]
+    struct Bar {
+        x: u8,
+    }
+    let mut c = OwnedContext::new();
+    let mut encoder_schema = BinEncoderSchema::new();
+    let mut decoder_schema = BinDecoderSchema::new();
+
+    let shared1 = BinEncoderBuilder::new(&mut encoder_schema).serialize(&Foo::A, c.borrow())?;
+    let mut w = BinEncoderBuilder::new(&mut encoder_schema);
+    w.set_self_contained(true);
+    let contained = w.serialize(&vec![Bar { x: 51 }, Bar { x: 52 }], c.borrow())?;
+    assert_eq!(
+        &contained[0..contained.len() - VU128_PADDING],
+        &[
+            19, 2, //
+            27, 21, 1, 1, b'x', //
+            16, 0, 7, 51, //
+            16, 0, 7, 52, //
+        ]
+    );
+    let shared2 =
+        BinEncoderBuilder::new(&mut encoder_schema).serialize(&Bar { x: 53 }, c.borrow())?;
+
+    // The self-contained message decodes alone, and does not disturb the shared stream.
+    let found: Vec<Bar> =
+        BinDecoderBuilder::new(&contained, &mut BinDecoderSchema::new()).deserialize(c.borrow())?;
+    assert_eq!(found, vec![Bar { x: 51 }, Bar { x: 52 }]);
+    let found: Foo = BinDecoderBuilder::new(&shared1, &mut decoder_schema).deserialize(c.borrow())?;
+    assert_eq!(found, Foo::A);
+    let found: Vec<Bar> =
+        BinDecoderBuilder::new(&contained, &mut decoder_schema).deserialize(c.borrow())?;
+    assert_eq!(found, vec![Bar { x: 51 }, Bar { x: 52 }]);
+    let found: Bar = BinDecoderBuilder::new(&shared2, &mut decoder_schema).deserialize(c.borrow())?;
+    assert_eq!(found, Bar { x: 53 });
+    Ok(())
+}
+
+#[test]
+fn test_crc32c() {
+    assert_eq!(crc32c(b""), 0);
+    assert_eq!(crc32c(b"123456789"), 0xE3069283);
+}
+
+#[derive(Serialize, Deserialize, Debug, Eq, PartialEq)]
+enum FrameTest {
+    A { x: u8 },
+    B(String),
+}
+
+#[test]
+fn test_frame_round_trip() -> anyhow::Result<()> {
+    let mut c = OwnedContext::new();
+    let mut writer = FrameWriter::new(vec![]);
+    writer.write(&FrameTest::A { x: 1 }, c.borrow())?;
+    writer.write_self_contained(&FrameTest::B("b".to_string()), c.borrow())?;
+    writer.write_schema()?;
+    writer.write(&FrameTest::A { x: 2 }, c.borrow())?;
+    let data = writer.into_inner();
+
+    let mut reader = FrameReader::new(data.as_slice());
+    assert_eq!(
+        reader.read::<FrameTest>(c.borrow())?,
+        Some(FrameTest::A { x: 1 })
+    );
+    assert_eq!(
+        reader.read::<FrameTest>(c.borrow())?,
+        Some(FrameTest::B("b".to_string()))
+    );
+    assert_eq!(
+        reader.read::<FrameTest>(c.borrow())?,
+        Some(FrameTest::A { x: 2 })
+    );
+    assert_eq!(reader.read::<FrameTest>(c.borrow())?, None);
+    Ok(())
+}
+
+#[test]
+fn test_frame_handshake() -> anyhow::Result<()> {
+    let mut c = OwnedContext::new();
+    let mut schema = BinEncoderSchema::new();
+    schema.set_string_capacity(4);
+    let mut writer = FrameWriter::with_schema(vec![], schema);
+    writer.write(&FrameTest::B("b".to_string()), c.borrow())?;
+    let saved = writer.schema().save();
+    let data = writer.into_inner();
+
+    // A fresh reader adopts the writer's string capacity.
+    let mut reader = FrameReader::new(data.as_slice());
+    assert_eq!(
+        reader.read::<FrameTest>(c.borrow())?,
+        Some(FrameTest::B("b".to_string()))
+    );
+    assert_eq!(reader.schema().fingerprint(), BinDecoderSchema::restore(&saved)?.fingerprint());
+    // A reader given its own schema must match the writer's capacity.
+    let mut reader = FrameReader::with_schema(data.as_slice(), BinDecoderSchema::new());
+    let error = reader.read::<FrameTest>(c.borrow()).unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<BinFrameError>(),
+        Some(BinFrameError::SchemaMismatch { .. })
+    ));
+    let mut schema = BinDecoderSchema::new();
+    schema.set_string_capacity(4);
+    let mut reader = FrameReader::with_schema(data.as_slice(), schema);
+    assert_eq!(
+        reader.read::<FrameTest>(c.borrow())?,
+        Some(FrameTest::B("b".to_string()))
+    );
+
+    // A resumed stream must be read with the schema saved at the same point.
+    let mut writer = FrameWriter::with_schema(vec![], BinEncoderSchema::restore(&saved)?);
+    writer.write(&FrameTest::B("b".to_string()), c.borrow())?;
+    let data = writer.into_inner();
+    let mut reader = FrameReader::with_schema(data.as_slice(), BinDecoderSchema::restore(&saved)?);
+    assert_eq!(
+        reader.read::<FrameTest>(c.borrow())?,
+        Some(FrameTest::B("b".to_string()))
+    );
+    let mut schema = BinDecoderSchema::new();
+    schema.set_string_capacity(4);
+    let mut reader = FrameReader::with_schema(data.as_slice(), schema);
+    let error = reader.read::<FrameTest>(c.borrow()).unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<BinFrameError>(),
+        Some(BinFrameError::SchemaMismatch { .. })
+    ));
+    Ok(())
+}
+
+struct FrameFailing;
+
+impl<E: Encoder> Serialize<E> for FrameFailing {
+    fn serialize<'w, 'en>(&self, _: AnyEncoder<'w, 'en, E>, _: Context) -> anyhow::Result<()> {
+        Err(anyhow::anyhow!("cannot serialize"))
+    }
+}
+
+#[test]
+fn test_frame_failed_write() -> anyhow::Result<()> {
+    #[derive(Serialize)]
+    struct Partial {
+        y: u8,
+        z: FrameFailing,
+    }
+    let mut c = OwnedContext::new();
+    let mut writer = FrameWriter::new(vec![]);
+    assert!(writer
+        .write(&Partial { y: 1, z: FrameFailing }, c.borrow())
+        .is_err());
+    // The definition of `Partial` was never sent, so it must not be referenced.
+    writer.write(&FrameTest::A { x: 2 }, c.borrow())?;
+    let data = writer.into_inner();
+
+    let mut reader = FrameReader::new(data.as_slice());
+    assert_eq!(
+        reader.read::<FrameTest>(c.borrow())?,
+        Some(FrameTest::A { x: 2 })
+    );
+    assert_eq!(reader.read::<FrameTest>(c.borrow())?, None);
+    Ok(())
+}
+
+#[derive(Serialize, Deserialize, Debug, Eq, PartialEq)]
+struct FrameLost {
+    y: u8,
+}
+
+#[test]
+fn test_frame_resync() -> anyhow::Result<()> {
+    let mut c = OwnedContext::new();
+    let mut writer = FrameWriter::new(vec![]);
+    writer.write(&FrameTest::A { x: 1 }, c.borrow())?;
+    let first_len = writer.into_inner().len();
+
+    let mut writer = FrameWriter::new(vec![]);
+    writer.write(&FrameTest::A { x: 1 }, c.borrow())?;
+    writer.write(&FrameLost { y: 2 }, c.borrow())?;
+    writer.write_self_contained(&FrameTest::A { x: 3 }, c.borrow())?;
+    writer.write(&FrameTest::A { x: 5 }, c.borrow())?;
+    writer.write_schema()?;
+    writer.write(&FrameTest::A { x: 4 }, c.borrow())?;
+    let mut data = writer.into_inner();
+    // Corrupt the body of the second frame.
+    data[first_len + 8] ^= 0xFF;
+
+    let mut reader = FrameReader::new(data.as_slice());
+    assert_eq!(
+        reader.read::<FrameTest>(c.borrow())?,
+        Some(FrameTest::A { x: 1 })
+    );
+    let error = reader.read::<FrameTest>(c.borrow()).unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<BinFrameError>(),
+        Some(BinFrameError::Corrupted)
+    ));
+    assert_eq!(
+        reader.read::<FrameTest>(c.borrow())?,
+        Some(FrameTest::A { x: 3 })
+    );
+    // The next frame extends the schema past the definition in the lost frame.
+    let error = reader.read::<FrameTest>(c.borrow()).unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<BinFrameError>(),
+        Some(BinFrameError::SchemaGap { .. })
+    ));
+    // The schema frame lets the reader recover.
+    assert_eq!(
+        reader.read::<FrameTest>(c.borrow())?,
+        Some(FrameTest::A { x: 4 })
+    );
+    assert_eq!(reader.read::<FrameTest>(c.borrow())?, None);
+    Ok(())
+}
+
+#[test]
+fn test_skippable() -> anyhow::Result<()> {
+    #[derive(Serialize, Deserialize, Debug, Eq, PartialEq)]
+    struct Foo {
+        abc: u8,
+        xyz: u32,
+    }
+    #[derive(Serialize, Deserialize, Debug, Eq, PartialEq)]
+    struct Outer {
+        a: u8,
+        big: Vec<Foo>,
+        b: u8,
+    }
+    #[derive(Serialize, Deserialize, Debug, Eq, PartialEq)]
+    struct Small {
+        a: u8,
+        b: u8,
+    }
+    let mut c = OwnedContext::new();
+    let mut encoder_schema = BinEncoderSchema::new();
+    let mut w = BinEncoderBuilder::new(&mut encoder_schema);
+    w.set_skippable(true);
+    let encoded = w.serialize(&vec![Foo { abc: 123, xyz: 124 }], c.borrow())?;
+    // The definition is moved in front of the outermost skippable value.
+    assert_eq!(
+        &encoded[0..encoded.len() - VU128_PADDING],
+        &[
+            21, 2, 3, b'a', b'b', b'c', 3, b'x', b'y', b'z', //
+            28, 13, 0, 0, 0, 19, 1, //
+            28, 6, 0, 0, 0, 16, 0, 7, 123, 9, 124,
+        ]
+    );
+    let found: Vec<Foo> =
+        BinDecoderBuilder::new(&encoded, &mut BinDecoderSchema::new()).deserialize(c.borrow())?;
+    assert_eq!(found, vec![Foo { abc: 123, xyz: 124 }]);
+
+    let input = Outer {
+        a: 1,
+        big: vec![Foo { abc: 2, xyz: 3 }, Foo { abc: 4, xyz: 5 }],
+        b: 6,
+    };
+    for skippable in [false, true] {
+        let mut encoder_schema = BinEncoderSchema::new();
+        let mut w = BinEncoderBuilder::new(&mut encoder_schema);
+        w.set_skippable(skippable);
+        let mut encoded = w.serialize(&input, c.borrow())?;
+        let found: Outer =
+            BinDecoderBuilder::new(&encoded, &mut BinDecoderSchema::new()).deserialize(c.borrow())?;
+        assert_eq!(found, input);
+        // Replace the tag of the last element of `big` with an invalid one.
+        let last = encoded.len() - VU128_PADDING - 8;
+        assert_eq!(encoded[last], 16);
+        encoded[last] = 0xFF;
+        let result: anyhow::Result<Small> =
+            BinDecoderBuilder::new(&encoded, &mut BinDecoderSchema::new()).deserialize(c.borrow());
+        if skippable {
+            assert_eq!(result?, Small { a: 1, b: 6 });
+        } else {
+            result.unwrap_err();
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_string_interning() -> anyhow::Result<()> {
+    let mut c = OwnedContext::new();
+    let mut encoder_schema = BinEncoderSchema::new();
+    encoder_schema.set_string_capacity(2);
+    let mut decoder_schema = BinDecoderSchema::new();
+    decoder_schema.set_string_capacity(2);
+
+    let first = BTreeMap::from([("a".to_string(), 1u32), ("b".to_string(), 2u32)]);
+    let encoded1 = BinEncoderBuilder::new(&mut encoder_schema).serialize(&first, c.borrow())?;
+    assert_eq!(
+        &encoded1[0..encoded1.len() - VU128_PADDING],
+        &[
+            20, 2, //
+            29, 0, 1, b'a', 30, 0, 9, 1, //
+            29, 1, 1, b'b', 30, 1, 9, 2,
+        ]
+    );
+    // "a" was used again, so "b" is evicted to make room for "c".
+    let second = BTreeMap::from([("a".to_string(), 3u32), ("c".to_string(), 4u32)]);
+    let encoded2 = BinEncoderBuilder::new(&mut encoder_schema).serialize(&second, c.borrow())?;
+    assert_eq!(
+        &encoded2[0..encoded2.len() - VU128_PADDING],
+        &[
+            20, 2, //
+            30, 0, 9, 3, //
+            29, 1, 1, b'c', 30, 1, 9, 4,
+        ]
+    );
+    let found: BTreeMap<String, u32> =
+        BinDecoderBuilder::new(&encoded1, &mut decoder_schema).deserialize(c.borrow())?;
+    assert_eq!(found, first);
+    let found: BTreeMap<String, u32> =
+        BinDecoderBuilder::new(&encoded2, &mut decoder_schema).deserialize(c.borrow())?;
+    assert_eq!(found, second);
+
+    let result: anyhow::Result<BTreeMap<String, u32>> =
+        BinDecoderBuilder::new(&encoded1, &mut BinDecoderSchema::new()).deserialize(c.borrow());
+    result.unwrap_err();
+
+    // Inside a skippable value, a full table falls back to plain strings.
+    let mut encoder_schema = BinEncoderSchema::new();
+    encoder_schema.set_string_capacity(1);
+    let mut w = BinEncoderBuilder::new(&mut encoder_schema);
+    w.set_skippable(true);
+    let input = vec!["x".to_string(), "y".to_string(), "x".to_string()];
+    let encoded = w.serialize(&input, c.borrow())?;
+    assert_eq!(
+        &encoded[0..encoded.len() - VU128_PADDING],
+        &[
+            29, 0, 1, b'x', //
+            28, 9, 0, 0, 0, 19, 3, //
+            30, 0, 22, 1, b'y', 30, 0,
+        ]
+    );
+    let mut decoder_schema = BinDecoderSchema::new();
+    decoder_schema.set_string_capacity(1);
+    let found: Vec<String> =
+        BinDecoderBuilder::new(&encoded, &mut decoder_schema).deserialize(c.borrow())?;
+    assert_eq!(found, input);
+    Ok(())
+}
+
+#[test]
+fn test_string_interning_restore() -> anyhow::Result<()> {
+    let mut c = OwnedContext::new();
+    let mut encoder_schema = BinEncoderSchema::new();
+    encoder_schema.set_string_capacity(2);
+    let mut decoder_schema = BinDecoderSchema::new();
+    decoder_schema.set_string_capacity(2);
+    let first = vec!["a".to_string()];
+    let encoded = BinEncoderBuilder::new(&mut encoder_schema).serialize(&first, c.borrow())?;
+    let found: Vec<String> =
+        BinDecoderBuilder::new(&encoded, &mut decoder_schema).deserialize(c.borrow())?;
+    assert_eq!(found, first);
+
+    let saved = encoder_schema.save();
+    assert_eq!(saved, decoder_schema.save());
+    assert_eq!(encoder_schema.fingerprint(), decoder_schema.fingerprint());
+    let mut empty = BinEncoderSchema::new();
+    empty.set_string_capacity(2);
+    assert_ne!(encoder_schema.fingerprint(), empty.fingerprint());
+    empty.set_string_capacity(3);
+    assert_ne!(encoder_schema.fingerprint(), empty.fingerprint());
+
+    // Both sides resume with "a" in the table, so the next message refers to it.
+    let mut encoder_schema = BinEncoderSchema::restore(&saved)?;
+    let second = vec!["a".to_string(), "b".to_string()];
+    let encoded = BinEncoderBuilder::new(&mut encoder_schema).serialize(&second, c.borrow())?;
+    assert_eq!(
+        &encoded[0..encoded.len() - VU128_PADDING],
+        &[
+            19, 2, //
+            30, 0, //
+            29, 1, 1, b'b', 30, 1,
+        ]
+    );
+    let mut decoder_schema = BinDecoderSchema::restore(&saved)?;
+    let found: Vec<String> =
+        BinDecoderBuilder::new(&encoded, &mut decoder_schema).deserialize(c.borrow())?;
+    assert_eq!(found, second);
+    assert_eq!(encoder_schema.fingerprint(), decoder_schema.fingerprint());
+
+    // A reader that missed the first frame recovers the table from a schema frame.
+    let message = FrameTest::B("s".to_string());
+    let mut schema = BinEncoderSchema::new();
+    schema.set_string_capacity(2);
+    let mut writer = FrameWriter::with_schema(vec![], schema);
+    writer.write(&message, c.borrow())?;
+    let first_len = writer.into_inner().len();
+    let mut schema = BinEncoderSchema::new();
+    schema.set_string_capacity(2);
+    let mut writer = FrameWriter::with_schema(vec![], schema);
+    writer.write(&message, c.borrow())?;
+    writer.write_schema()?;
+    writer.write(&message, c.borrow())?;
+    let data = writer.into_inner();
+    let mut reader = FrameReader::new(&data[first_len..]);
+    assert_eq!(reader.read::<FrameTest>(c.borrow())?, Some(message));
+    assert_eq!(reader.read::<FrameTest>(c.borrow())?, None);
+    Ok(())
+}
+
+#[test]
+fn test_schema_evolution() -> anyhow::Result<()> {
+    #[derive(Serialize, Deserialize, Debug, Eq, PartialEq)]
+    enum ModeV1 {
+        Fast,
+        Slow,
+    }
+    #[derive(Serialize, Deserialize, Debug, Eq, PartialEq)]
+    struct ConfigV1 {
+        host: String,
+        mode: ModeV1,
+    }
+    #[derive(Serialize, Deserialize, Debug, Eq, PartialEq)]
+    enum ModeV2 {
+        #[marshal(alias = "Fast")]
+        Quick,
+        Slow,
+    }
+    #[derive(Serialize, Deserialize, Debug, Eq, PartialEq)]
+    struct Limits {
+        max: u32,
+    }
+    fn default_retries() -> u8 {
+        3
+    }
+    #[derive(Serialize, Deserialize, Debug, Eq, PartialEq)]
+    struct ConfigV2 {
+        #[marshal(alias = "host")]
+        address: String,
+        mode: ModeV2,
+        #[marshal(default)]
+        limits: Option<Limits>,
+        #[marshal(default = "default_retries")]
+        retries: u8,
+    }
+    let mut c = OwnedContext::new();
+
+    // A newer decoder reads renamed fields and variants, and fills in missing fields.
+    let old = ConfigV1 {
+        host: "a".to_string(),
+        mode: ModeV1::Fast,
+    };
+    let encoded = BinEncoderBuilder::new(&mut BinEncoderSchema::new()).serialize(&old, c.borrow())?;
+    let mut decoder_schema = BinDecoderSchema::new();
+    let found: ConfigV2 =
+        BinDecoderBuilder::new(&encoded, &mut decoder_schema).deserialize(c.borrow())?;
+    assert_eq!(
+        found,
+        ConfigV2 {
+            address: "a".to_string(),
+            mode: ModeV2::Quick,
+            limits: None,
+            retries: 3,
+        }
+    );
+    assert_eq!(decoder_schema.unmatched_fields(), vec![]);
+
+    // An older decoder ignores new fields, and reports them.
+    #[derive(Serialize, Deserialize, Debug, Eq, PartialEq)]
+    struct ConfigV0 {
+        address: String,
+    }
+    let new = ConfigV2 {
+        address: "b".to_string(),
+        mode: ModeV2::Slow,
+        limits: Some(Limits { max: 10 }),
+        retries: 4,
+    };
+    let encoded = BinEncoderBuilder::new(&mut BinEncoderSchema::new()).serialize(&new, c.borrow())?;
+    let mut decoder_schema = BinDecoderSchema::new();
+    let found: ConfigV0 =
+        BinDecoderBuilder::new(&encoded, &mut decoder_schema).deserialize(c.borrow())?;
+    assert_eq!(
+        found,
+        ConfigV0 {
+            address: "b".to_string()
+        }
+    );
+    assert_eq!(
+        decoder_schema
+            .unmatched_fields()
+            .iter()
+            .map(|x| (x.type_name, x.field.as_str()))
+            .collect::<Vec<_>>(),
+        vec![
+            ("ConfigV0", "limits"),
+            ("ConfigV0", "mode"),
+            ("ConfigV0", "retries"),
+        ]
+    );
+
+    // Fields of struct variants are reported without the name of the enum.
+    #[derive(Serialize, Deserialize, Debug, Eq, PartialEq)]
+    enum ShapeV2 {
+        Circle { radius: u8, color: u8 },
+    }
+    #[derive(Serialize, Deserialize, Debug, Eq, PartialEq)]
+    enum ShapeV1 {
+        Circle { radius: u8 },
+    }
+    let encoded = BinEncoderBuilder::new(&mut BinEncoderSchema::new())
+        .serialize(&ShapeV2::Circle { radius: 1, color: 2 }, c.borrow())?;
+    let mut decoder_schema = BinDecoderSchema::new();
+    let found: ShapeV1 =
+        BinDecoderBuilder::new(&encoded, &mut decoder_schema).deserialize(c.borrow())?;
+    assert_eq!(found, ShapeV1::Circle { radius: 1 });
+    assert_eq!(
+        decoder_schema.unmatched_fields(),
+        vec![UnmatchedField {
+            type_name: "<enum>",
+            field: "color".to_string(),
+        }]
+    );
+    Ok(())
+}
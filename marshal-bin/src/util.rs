@@ -1,4 +1,5 @@
 use std::cell::Cell;
+use std::collections::HashMap;
 
 use safe_once::cell::{OnceCell, RawFusedCell};
 use safe_once_map::util::index_arena::IndexArena;
@@ -18,6 +19,9 @@ impl<T> StableCellVec<T> {
     pub fn get(&self, index: usize) -> Option<&T> {
         Some(self.arena.try_get(index)?.get()?)
     }
+    pub fn len(&self) -> usize {
+        self.next.get()
+    }
     pub fn push(&self, value: T) -> usize {
         let index = self.next.get();
         self.next.set(index + 1);
@@ -31,3 +35,138 @@ impl<T> Default for StableCellVec<T> {
         StableCellVec::new()
     }
 }
+
+/// Identifies a list of enum definitions and a table of interned strings, using FNV-1a so that it
+/// is stable across processes.
+pub fn fingerprint<'a>(
+    defs: impl Iterator<Item = &'a [String]>,
+    string_capacity: usize,
+    strings: impl Iterator<Item = (usize, &'a str)>,
+) -> u64 {
+    let mut hash = 0xcbf29ce484222325u64;
+    let mut write = |bytes: &[u8]| {
+        for byte in bytes {
+            hash ^= *byte as u64;
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+    };
+    for def in defs {
+        write(&(def.len() as u64).to_le_bytes());
+        for field in def {
+            write(&(field.len() as u64).to_le_bytes());
+            write(field.as_bytes());
+        }
+    }
+    write(&(string_capacity as u64).to_le_bytes());
+    for (index, string) in strings {
+        write(&(index as u64).to_le_bytes());
+        write(&(string.len() as u64).to_le_bytes());
+        write(string.as_bytes());
+    }
+    hash
+}
+
+const fn crc32c_table() -> [u32; 256] {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0x82F63B78
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+}
+
+static CRC32C_TABLE: [u32; 256] = crc32c_table();
+
+/// The CRC-32C (Castagnoli) checksum.
+pub fn crc32c(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for byte in data {
+        crc = CRC32C_TABLE[((crc ^ *byte as u32) & 0xFF) as usize] ^ (crc >> 8);
+    }
+    !crc
+}
+
+/// A bounded table of strings that evicts with the CLOCK algorithm, an approximation of evicting
+/// the least recently used string.
+#[derive(Clone)]
+pub struct StringInterner {
+    indexes: HashMap<String, usize>,
+    slots: Vec<(String, bool)>,
+    capacity: usize,
+    hand: usize,
+}
+
+pub enum Interned {
+    Existing(usize),
+    New(usize),
+}
+
+impl StringInterner {
+    pub fn new(capacity: usize) -> Self {
+        StringInterner {
+            indexes: HashMap::new(),
+            slots: vec![],
+            capacity,
+            hand: 0,
+        }
+    }
+    /// Restores a table from the strings in each slot.
+    pub fn with_strings(capacity: usize, strings: Vec<String>) -> Self {
+        StringInterner {
+            indexes: strings
+                .iter()
+                .enumerate()
+                .map(|(index, x)| (x.clone(), index))
+                .collect(),
+            slots: strings.into_iter().map(|x| (x, false)).collect(),
+            capacity,
+            hand: 0,
+        }
+    }
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+    pub fn strings(&self) -> impl Iterator<Item = (usize, &str)> {
+        self.slots.iter().map(|(x, _)| x.as_str()).enumerate()
+    }
+    /// Finds or assigns a slot for `value`. Returns `None` if the table is full and `evict` is
+    /// false.
+    pub fn intern(&mut self, value: &str, evict: bool) -> Option<Interned> {
+        if let Some(&index) = self.indexes.get(value) {
+            self.slots[index].1 = true;
+            return Some(Interned::Existing(index));
+        }
+        if self.slots.len() < self.capacity {
+            self.indexes.insert(value.to_string(), self.slots.len());
+            self.slots.push((value.to_string(), false));
+            return Some(Interned::New(self.slots.len() - 1));
+        }
+        if !evict || self.capacity == 0 {
+            return None;
+        }
+        loop {
+            let index = self.hand;
+            self.hand = (self.hand + 1) % self.capacity;
+            let (old, referenced) = &mut self.slots[index];
+            if *referenced {
+                *referenced = false;
+            } else {
+                self.indexes.remove(old.as_str());
+                *old = value.to_string();
+                self.indexes.insert(value.to_string(), index);
+                return Some(Interned::New(index));
+            }
+        }
+    }
+}
@@ -0,0 +1,432 @@
+use std::borrow::Cow;
+use std::fmt::{Display, Formatter};
+
+use marshal_core::decode::{
+    DecodeHint, DecodeVariantHint, SimpleDecoderView, SpecDecoder, WideInt,
+};
+use marshal_core::{Primitive, PrimitiveType};
+
+use crate::float::f64_from_f16;
+use crate::{
+    BREAK, FALSE, FLOAT16, FLOAT32, FLOAT64, INFO_INDEFINITE, INFO_U16, INFO_U32, INFO_U64,
+    INFO_U8, MAJOR_ARRAY, MAJOR_BYTES, MAJOR_MAP, MAJOR_NEGATIVE, MAJOR_SIMPLE, MAJOR_TAG,
+    MAJOR_TEXT, MAJOR_UNSIGNED, NULL, SOME_TAG, TAG_NEGATIVE_BIGNUM, TAG_POSITIVE_BIGNUM, TRUE,
+    UNDEFINED,
+};
+
+pub mod full;
+
+#[derive(Debug)]
+pub enum CborDecoderError {
+    Eof,
+    TrailingData,
+    BadAdditionalInfo(u8),
+    BadSimpleValue(u8),
+    UnexpectedBreak,
+    BadChunk,
+    BadEnum,
+    BignumTooLarge,
+}
+
+impl Display for CborDecoderError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CborDecoderError::Eof => write!(f, "unexpected end of input"),
+            CborDecoderError::TrailingData => write!(f, "input contains trailing bytes"),
+            CborDecoderError::BadAdditionalInfo(x) => write!(f, "reserved additional information {}", x),
+            CborDecoderError::BadSimpleValue(x) => write!(f, "unsupported simple value {}", x),
+            CborDecoderError::UnexpectedBreak => write!(f, "break outside of an indefinite-length item"),
+            CborDecoderError::BadChunk => write!(f, "indefinite-length string contains a chunk of another type"),
+            CborDecoderError::BadEnum => write!(f, "enum must be a string or a map with one entry"),
+            CborDecoderError::BignumTooLarge => write!(f, "bignum does not fit in 128 bits"),
+        }
+    }
+}
+
+impl std::error::Error for CborDecoderError {}
+
+pub struct SimpleCborSpecDecoder<'de> {
+    content: &'de [u8],
+}
+
+/// An integer, which is `-1 - n` if negative.
+#[derive(Copy, Clone)]
+enum CborInt {
+    Positive(u128),
+    Negative(u128),
+}
+
+pub struct CborSeqDecoder {
+    /// The number of remaining items, or `None` if terminated by a break.
+    remaining: Option<usize>,
+}
+
+pub enum CborDiscriminantDecoder {
+    Unit,
+    Map { indefinite: bool },
+}
+
+impl<'de> SimpleCborSpecDecoder<'de> {
+    pub fn new(input: &'de [u8]) -> Self {
+        SimpleCborSpecDecoder { content: input }
+    }
+    pub fn end(self) -> anyhow::Result<()> {
+        if !self.content.is_empty() {
+            return Err(CborDecoderError::TrailingData.into());
+        }
+        Ok(())
+    }
+    fn peek_byte(&self) -> anyhow::Result<u8> {
+        Ok(*self.content.first().ok_or(CborDecoderError::Eof)?)
+    }
+    fn read_count(&mut self, count: usize) -> anyhow::Result<&'de [u8]> {
+        Ok(self.content.take(..count).ok_or(CborDecoderError::Eof)?)
+    }
+    fn read_array<const N: usize>(&mut self) -> anyhow::Result<[u8; N]> {
+        Ok(self.read_count(N)?.try_into().unwrap())
+    }
+    /// Reads an initial byte and its argument, which is `None` for an indefinite length.
+    fn read_header(&mut self) -> anyhow::Result<(u8, Option<u64>)> {
+        let initial = self.read_array::<1>()?[0];
+        let info = initial & 0x1f;
+        let argument = match info {
+            0..INFO_U8 => info as u64,
+            INFO_U8 => self.read_array::<1>()?[0] as u64,
+            INFO_U16 => u16::from_be_bytes(self.read_array()?) as u64,
+            INFO_U32 => u32::from_be_bytes(self.read_array()?) as u64,
+            INFO_U64 => u64::from_be_bytes(self.read_array()?),
+            INFO_INDEFINITE => return Ok((initial >> 5, None)),
+            _ => return Err(CborDecoderError::BadAdditionalInfo(info).into()),
+        };
+        Ok((initial >> 5, Some(argument)))
+    }
+    fn read_len(&mut self) -> anyhow::Result<Option<usize>> {
+        let (_, len) = self.read_header()?;
+        Ok(len.map(usize::try_from).transpose()?)
+    }
+    fn peek_tag(&self) -> anyhow::Result<Option<u64>> {
+        if self.peek_byte()? >> 5 != MAJOR_TAG {
+            return Ok(None);
+        }
+        let mut peek = SimpleCborSpecDecoder {
+            content: self.content,
+        };
+        Ok(peek.read_header()?.1)
+    }
+    fn try_read_break(&mut self) -> anyhow::Result<bool> {
+        if self.peek_byte()? == BREAK {
+            self.content = &self.content[1..];
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+    /// Reads a byte or text string, joining the chunks of an indefinite-length string.
+    fn read_string_bytes(&mut self) -> anyhow::Result<Cow<'de, [u8]>> {
+        let (major, len) = self.read_header()?;
+        if let Some(len) = len {
+            return Ok(Cow::Borrowed(self.read_count(usize::try_from(len)?)?));
+        }
+        let mut result = vec![];
+        while !self.try_read_break()? {
+            let (chunk_major, len) = self.read_header()?;
+            let len = len.ok_or(CborDecoderError::BadChunk)?;
+            if chunk_major != major {
+                return Err(CborDecoderError::BadChunk.into());
+            }
+            result.extend_from_slice(self.read_count(usize::try_from(len)?)?);
+        }
+        Ok(Cow::Owned(result))
+    }
+    fn read_text(&mut self) -> anyhow::Result<Cow<'de, str>> {
+        Ok(match self.read_string_bytes()? {
+            Cow::Borrowed(x) => Cow::Borrowed(std::str::from_utf8(x)?),
+            Cow::Owned(x) => Cow::Owned(String::from_utf8(x)?),
+        })
+    }
+    fn read_bignum(&mut self) -> anyhow::Result<u128> {
+        let bytes = self.read_string_bytes()?;
+        let start = bytes.iter().position(|x| *x != 0).unwrap_or(bytes.len());
+        let bytes = &bytes[start..];
+        if bytes.len() > 16 {
+            return Err(CborDecoderError::BignumTooLarge.into());
+        }
+        let mut buffer = [0u8; 16];
+        buffer[16 - bytes.len()..].copy_from_slice(bytes);
+        Ok(u128::from_be_bytes(buffer))
+    }
+    fn read_float(&mut self) -> anyhow::Result<Primitive> {
+        Ok(match self.read_array::<1>()?[0] {
+            FLOAT16 => Primitive::F32(f64_from_f16(u16::from_be_bytes(self.read_array()?)) as f32),
+            FLOAT32 => Primitive::F32(f32::from_be_bytes(self.read_array()?)),
+            _ => Primitive::F64(f64::from_be_bytes(self.read_array()?)),
+        })
+    }
+    fn decode_seq(&mut self) -> anyhow::Result<CborSeqDecoder> {
+        Ok(CborSeqDecoder {
+            remaining: self.read_len()?,
+        })
+    }
+    fn decode_enum_map(&mut self) -> anyhow::Result<CborDiscriminantDecoder> {
+        match self.read_len()? {
+            Some(1) => Ok(CborDiscriminantDecoder::Map { indefinite: false }),
+            None => Ok(CborDiscriminantDecoder::Map { indefinite: true }),
+            Some(_) => Err(CborDecoderError::BadEnum.into()),
+        }
+    }
+}
+
+impl WideInt for CborInt {
+    fn natural_type(self) -> PrimitiveType {
+        match self {
+            CborInt::Positive(x) if x <= u64::MAX as u128 => PrimitiveType::U64,
+            CborInt::Positive(_) => PrimitiveType::U128,
+            CborInt::Negative(x) if x <= i64::MAX as u128 => PrimitiveType::I64,
+            CborInt::Negative(_) => PrimitiveType::I128,
+        }
+    }
+    fn unsigned(self) -> anyhow::Result<u128> {
+        match self {
+            CborInt::Positive(x) => Ok(x),
+            CborInt::Negative(_) => Ok(u128::try_from(self.signed()?)?),
+        }
+    }
+    fn signed(self) -> anyhow::Result<i128> {
+        match self {
+            CborInt::Positive(x) => Ok(i128::try_from(x)?),
+            CborInt::Negative(x) => Ok(-1 - i128::try_from(x)?),
+        }
+    }
+    fn float(self) -> f64 {
+        match self {
+            CborInt::Positive(x) => x as f64,
+            CborInt::Negative(x) => -1.0 - x as f64,
+        }
+    }
+}
+
+impl<'de> SpecDecoder<'de> for SimpleCborSpecDecoder<'de> {
+    type AnyDecoder = ();
+    type SeqDecoder = CborSeqDecoder;
+    type MapDecoder = CborSeqDecoder;
+    type KeyDecoder = ();
+    type ValueDecoder = ();
+    type DiscriminantDecoder = CborDiscriminantDecoder;
+    type VariantDecoder = CborDiscriminantDecoder;
+    type EnumCloser = CborDiscriminantDecoder;
+    type SomeDecoder = ();
+    type SomeCloser = ();
+
+    fn decode(
+        &mut self,
+        _any: Self::AnyDecoder,
+        hint: DecodeHint,
+    ) -> anyhow::Result<SimpleDecoderView<'de, Self>> {
+        loop {
+            let initial = self.peek_byte()?;
+            let major = initial >> 5;
+            if let DecodeHint::Option = hint {
+                if initial == NULL || initial == UNDEFINED {
+                    self.read_count(1)?;
+                    return Ok(SimpleDecoderView::None);
+                }
+                if self.peek_tag()? == Some(SOME_TAG) {
+                    self.read_header()?;
+                }
+                return Ok(SimpleDecoderView::Some(()));
+            }
+            match major {
+                MAJOR_UNSIGNED | MAJOR_NEGATIVE => {
+                    if let DecodeHint::Enum { .. } = hint {
+                        return Ok(SimpleDecoderView::Enum(CborDiscriminantDecoder::Unit));
+                    }
+                    let (_, value) = self.read_header()?;
+                    let value = value.ok_or(CborDecoderError::BadAdditionalInfo(INFO_INDEFINITE))?;
+                    let int = if major == MAJOR_UNSIGNED {
+                        CborInt::Positive(value as u128)
+                    } else {
+                        CborInt::Negative(value as u128)
+                    };
+                    return Ok(SimpleDecoderView::Primitive(int.into_primitive(&hint)?));
+                }
+                MAJOR_BYTES => return Ok(SimpleDecoderView::Bytes(self.read_string_bytes()?)),
+                MAJOR_TEXT => {
+                    return match hint {
+                        DecodeHint::Enum { .. } => {
+                            Ok(SimpleDecoderView::Enum(CborDiscriminantDecoder::Unit))
+                        }
+                        DecodeHint::Primitive(PrimitiveType::Char) => {
+                            let text = self.read_text()?;
+                            let mut chars = text.chars();
+                            match (chars.next(), chars.next()) {
+                                (Some(c), None) => Ok(SimpleDecoderView::Primitive(Primitive::Char(c))),
+                                _ => Ok(SimpleDecoderView::String(text)),
+                            }
+                        }
+                        _ => Ok(SimpleDecoderView::String(self.read_text()?)),
+                    };
+                }
+                MAJOR_ARRAY => return Ok(SimpleDecoderView::Seq(self.decode_seq()?)),
+                MAJOR_MAP => {
+                    if let DecodeHint::Enum { .. } = hint {
+                        return Ok(SimpleDecoderView::Enum(self.decode_enum_map()?));
+                    }
+                    return Ok(SimpleDecoderView::Map(self.decode_seq()?));
+                }
+                MAJOR_TAG => {
+                    let (_, tag) = self.read_header()?;
+                    match tag.ok_or(CborDecoderError::BadAdditionalInfo(INFO_INDEFINITE))? {
+                        TAG_POSITIVE_BIGNUM => {
+                            let int = CborInt::Positive(self.read_bignum()?);
+                            return Ok(SimpleDecoderView::Primitive(int.into_primitive(&hint)?));
+                        }
+                        TAG_NEGATIVE_BIGNUM => {
+                            let int = CborInt::Negative(self.read_bignum()?);
+                            return Ok(SimpleDecoderView::Primitive(int.into_primitive(&hint)?));
+                        }
+                        SOME_TAG => {
+                            if let DecodeHint::Any | DecodeHint::Ignore = hint {
+                                return Ok(SimpleDecoderView::Some(()));
+                            }
+                        }
+                        // Other tags only add meaning to the item that follows.
+                        _ => {}
+                    }
+                }
+                MAJOR_SIMPLE => {
+                    return match initial {
+                        FALSE | TRUE => {
+                            self.read_count(1)?;
+                            Ok(SimpleDecoderView::Primitive(Primitive::Bool(initial == TRUE)))
+                        }
+                        NULL | UNDEFINED => {
+                            self.read_count(1)?;
+                            Ok(SimpleDecoderView::Primitive(Primitive::Unit))
+                        }
+                        FLOAT16 | FLOAT32 | FLOAT64 => {
+                            let value = self.read_float()?;
+                            Ok(SimpleDecoderView::Primitive(match (hint, value) {
+                                (DecodeHint::Primitive(PrimitiveType::F64), Primitive::F32(x)) => {
+                                    Primitive::F64(x as f64)
+                                }
+                                (DecodeHint::Primitive(PrimitiveType::F32), Primitive::F64(x)) => {
+                                    Primitive::F32(x as f32)
+                                }
+                                (_, value) => value,
+                            }))
+                        }
+                        BREAK => Err(CborDecoderError::UnexpectedBreak.into()),
+                        _ => Err(CborDecoderError::BadSimpleValue(initial & 0x1f).into()),
+                    };
+                }
+                _ => unreachable!(),
+            }
+        }
+    }
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+
+    fn decode_seq_next(
+        &mut self,
+        seq: &mut Self::SeqDecoder,
+    ) -> anyhow::Result<Option<Self::AnyDecoder>> {
+        match &mut seq.remaining {
+            Some(remaining) => {
+                if let Some(next) = remaining.checked_sub(1) {
+                    *remaining = next;
+                    Ok(Some(()))
+                } else {
+                    Ok(None)
+                }
+            }
+            None => {
+                if self.try_read_break()? {
+                    Ok(None)
+                } else {
+                    Ok(Some(()))
+                }
+            }
+        }
+    }
+
+    fn decode_seq_exact_size(&self, seq: &Self::SeqDecoder) -> Option<usize> {
+        // The length is untrusted, so it is only reported if the input could hold that many items.
+        seq.remaining.filter(|x| *x <= self.content.len())
+    }
+
+    fn decode_seq_end(&mut self, _seq: Self::SeqDecoder) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn decode_map_next(
+        &mut self,
+        map: &mut Self::MapDecoder,
+    ) -> anyhow::Result<Option<Self::KeyDecoder>> {
+        self.decode_seq_next(map)
+    }
+
+    fn decode_map_exact_size(&self, map: &Self::MapDecoder) -> Option<usize> {
+        map.remaining.filter(|x| *x <= self.content.len() / 2)
+    }
+
+    fn decode_map_end(&mut self, _map: Self::MapDecoder) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn decode_entry_key(
+        &mut self,
+        _key: Self::KeyDecoder,
+    ) -> anyhow::Result<(Self::AnyDecoder, Self::ValueDecoder)> {
+        Ok(((), ()))
+    }
+
+    fn decode_entry_value(&mut self, _value: Self::ValueDecoder) -> anyhow::Result<Self::AnyDecoder> {
+        Ok(())
+    }
+
+    fn decode_enum_discriminant(
+        &mut self,
+        e: Self::DiscriminantDecoder,
+    ) -> anyhow::Result<(Self::AnyDecoder, Self::VariantDecoder)> {
+        Ok(((), e))
+    }
+
+    fn decode_enum_variant(
+        &mut self,
+        e: Self::VariantDecoder,
+        hint: DecodeVariantHint,
+    ) -> anyhow::Result<(SimpleDecoderView<'de, Self>, Self::EnumCloser)> {
+        match e {
+            CborDiscriminantDecoder::Unit => Ok((
+                SimpleDecoderView::Primitive(Primitive::Unit),
+                CborDiscriminantDecoder::Unit,
+            )),
+            CborDiscriminantDecoder::Map { indefinite } => Ok((
+                self.decode((), hint.content_hint())?,
+                CborDiscriminantDecoder::Map { indefinite },
+            )),
+        }
+    }
+
+    fn decode_enum_end(&mut self, e: Self::EnumCloser) -> anyhow::Result<()> {
+        if let CborDiscriminantDecoder::Map { indefinite: true } = e {
+            if !self.try_read_break()? {
+                return Err(CborDecoderError::BadEnum.into());
+            }
+        }
+        Ok(())
+    }
+
+    fn decode_some_inner(
+        &mut self,
+        _e: Self::SomeDecoder,
+    ) -> anyhow::Result<(Self::AnyDecoder, Self::SomeCloser)> {
+        Ok(((), ()))
+    }
+
+    fn decode_some_end(&mut self, _p: Self::SomeCloser) -> anyhow::Result<()> {
+        Ok(())
+    }
+}
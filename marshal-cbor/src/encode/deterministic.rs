@@ -0,0 +1,23 @@
+use crate::encode::CborEncoderError;
+
+/// Reorders the entries of a map, given as the start and key end of each entry (the last running
+/// to the end of `output`), by the bytes of their encoded keys as required by RFC 8949 §4.2.1.
+pub fn sort_entries(output: &mut Vec<u8>, entries: &[(usize, usize)]) -> anyhow::Result<()> {
+    let Some(&(first, _)) = entries.first() else {
+        return Ok(());
+    };
+    let tail = output.split_off(first);
+    let mut sorted = Vec::with_capacity(entries.len());
+    for (index, &(start, key_end)) in entries.iter().enumerate() {
+        let end = entries.get(index + 1).map_or(tail.len(), |(next, _)| next - first);
+        sorted.push((&tail[start - first..key_end - first], &tail[start - first..end]));
+    }
+    sorted.sort_by(|(k1, _), (k2, _)| k1.cmp(k2));
+    for (index, (key, entry)) in sorted.iter().enumerate() {
+        if index > 0 && sorted[index - 1].0 == *key {
+            return Err(CborEncoderError::DuplicateKey.into());
+        }
+        output.extend_from_slice(entry);
+    }
+    Ok(())
+}
@@ -0,0 +1,50 @@
+use marshal::context::Context;
+use marshal::ser::Serialize;
+use marshal_core::derive_encoder_for_newtype;
+use marshal_core::encode::poison::PoisonEncoder;
+use marshal_core::encode::{AnySpecEncoder, Encoder};
+
+use crate::encode::{CborAnySpecEncoder, SimpleCborSpecEncoder};
+
+pub struct CborSpecEncoder(PoisonEncoder<SimpleCborSpecEncoder>);
+
+derive_encoder_for_newtype!(CborSpecEncoder(PoisonEncoder<SimpleCborSpecEncoder>));
+
+pub struct CborEncoderBuilder {
+    inner: CborSpecEncoder,
+}
+
+impl CborEncoderBuilder {
+    pub fn new() -> Self {
+        CborEncoderBuilder {
+            inner: CborSpecEncoder(PoisonEncoder::new(SimpleCborSpecEncoder::new())),
+        }
+    }
+    /// Produces the deterministic encoding of RFC 8949 §4.2, so that equal values are encoded
+    /// as equal bytes.
+    pub fn set_deterministic(&mut self, deterministic: bool) -> &mut Self {
+        self.inner.0.inner_mut().set_deterministic(deterministic);
+        self
+    }
+    pub fn build(&mut self) -> AnySpecEncoder<'_, CborSpecEncoder> {
+        let any = self.inner.0.start(CborAnySpecEncoder::new());
+        AnySpecEncoder::new(&mut self.inner, any)
+    }
+    pub fn end(self) -> anyhow::Result<Vec<u8>> {
+        self.inner.0.end()?.end()
+    }
+    pub fn serialize<T: ?Sized + Serialize<CborEncoder>>(
+        mut self,
+        value: &T,
+        ctx: Context,
+    ) -> anyhow::Result<Vec<u8>> {
+        value.serialize(self.build(), ctx)?;
+        self.end()
+    }
+}
+
+pub struct CborEncoder;
+
+impl Encoder for CborEncoder {
+    type SpecEncoder<'en> = CborSpecEncoder;
+}
@@ -0,0 +1,479 @@
+use std::fmt::{Display, Formatter};
+
+use marshal_core::encode::SpecEncoder;
+use marshal_core::Primitive;
+
+use crate::float::f16_from_f64;
+use crate::{
+    FALSE, FLOAT16, FLOAT32, FLOAT64, INFO_U16, INFO_U32, INFO_U64, INFO_U8, MAJOR_ARRAY,
+    MAJOR_BYTES, MAJOR_MAP, MAJOR_NEGATIVE, MAJOR_TAG, MAJOR_TEXT, MAJOR_UNSIGNED, NULL, SOME_TAG,
+    TAG_NEGATIVE_BIGNUM, TAG_POSITIVE_BIGNUM, TRUE,
+};
+
+mod deterministic;
+pub mod full;
+
+#[derive(Debug)]
+pub enum CborEncoderError {
+    WrongLength,
+    DuplicateKey,
+}
+
+impl Display for CborEncoderError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CborEncoderError::WrongLength => {
+                write!(f, "number of elements does not match the declared length")
+            }
+            CborEncoderError::DuplicateKey => write!(f, "map contains a duplicate key"),
+        }
+    }
+}
+
+impl std::error::Error for CborEncoderError {}
+
+pub struct SimpleCborSpecEncoder {
+    output: Vec<u8>,
+    deterministic: bool,
+    entries: Vec<(usize, usize)>,
+}
+
+impl SimpleCborSpecEncoder {
+    pub fn new() -> Self {
+        SimpleCborSpecEncoder {
+            output: vec![],
+            deterministic: false,
+            entries: vec![],
+        }
+    }
+    /// Produces the deterministic encoding of RFC 8949 §4.2: floats in their shortest exact
+    /// form and map entries sorted by their encoded keys.
+    pub fn set_deterministic(&mut self, deterministic: bool) {
+        self.deterministic = deterministic;
+    }
+    pub fn end(self) -> anyhow::Result<Vec<u8>> {
+        Ok(self.output)
+    }
+    fn write_header(&mut self, major: u8, value: u64) {
+        let major = major << 5;
+        if value < INFO_U8 as u64 {
+            self.output.push(major | value as u8);
+        } else if let Ok(value) = u8::try_from(value) {
+            self.output.push(major | INFO_U8);
+            self.output.push(value);
+        } else if let Ok(value) = u16::try_from(value) {
+            self.output.push(major | INFO_U16);
+            self.output.extend_from_slice(&value.to_be_bytes());
+        } else if let Ok(value) = u32::try_from(value) {
+            self.output.push(major | INFO_U32);
+            self.output.extend_from_slice(&value.to_be_bytes());
+        } else {
+            self.output.push(major | INFO_U64);
+            self.output.extend_from_slice(&value.to_be_bytes());
+        }
+    }
+    fn write_len(&mut self, major: u8, len: usize) {
+        self.write_header(major, len as u64);
+    }
+    /// Writes `null`, tagged if it would otherwise be mistaken for `None`.
+    fn write_null(&mut self, any: CborAnySpecEncoder) {
+        if any.cannot_be_null {
+            self.write_header(MAJOR_TAG, SOME_TAG);
+        }
+        self.output.push(NULL);
+    }
+    fn write_bignum(&mut self, tag: u64, value: u128) {
+        let bytes = value.to_be_bytes();
+        let start = value.leading_zeros() as usize / 8;
+        self.write_header(MAJOR_TAG, tag);
+        self.write_len(MAJOR_BYTES, bytes.len() - start);
+        self.output.extend_from_slice(&bytes[start..]);
+    }
+    fn write_unsigned(&mut self, value: u128) {
+        match u64::try_from(value) {
+            Ok(value) => self.write_header(MAJOR_UNSIGNED, value),
+            Err(_) => self.write_bignum(TAG_POSITIVE_BIGNUM, value),
+        }
+    }
+    fn write_signed(&mut self, value: i128) {
+        if value >= 0 {
+            self.write_unsigned(value as u128);
+        } else {
+            // Negative integers are encoded as -1 - n.
+            let value = !value as u128;
+            match u64::try_from(value) {
+                Ok(value) => self.write_header(MAJOR_NEGATIVE, value),
+                Err(_) => self.write_bignum(TAG_NEGATIVE_BIGNUM, value),
+            }
+        }
+    }
+    fn write_f32(&mut self, value: f32) {
+        if self.deterministic {
+            self.write_f64(value as f64);
+        } else {
+            self.output.push(FLOAT32);
+            self.output.extend_from_slice(&value.to_be_bytes());
+        }
+    }
+    fn write_f64(&mut self, value: f64) {
+        if !self.deterministic {
+            self.output.push(FLOAT64);
+            self.output.extend_from_slice(&value.to_be_bytes());
+        } else if value.is_nan() {
+            self.output.extend_from_slice(&[FLOAT16, 0x7e, 0x00]);
+        } else if let Some(half) = f16_from_f64(value) {
+            self.output.push(FLOAT16);
+            self.output.extend_from_slice(&half.to_be_bytes());
+        } else if value as f32 as f64 == value {
+            self.output.push(FLOAT32);
+            self.output.extend_from_slice(&(value as f32).to_be_bytes());
+        } else {
+            self.output.push(FLOAT64);
+            self.output.extend_from_slice(&value.to_be_bytes());
+        }
+    }
+    fn write_text(&mut self, value: &str) {
+        self.write_len(MAJOR_TEXT, value.len());
+        self.output.extend_from_slice(value.as_bytes());
+    }
+    fn start_map(&mut self, len: usize) -> CborMapEncoder {
+        self.write_len(MAJOR_MAP, len);
+        CborMapEncoder {
+            remaining: len,
+            first_entry: self.entries.len(),
+        }
+    }
+    /// Records the start of an entry, so that it can be sorted when the map ends.
+    fn start_entry(&mut self, map: &mut CborMapEncoder) -> anyhow::Result<usize> {
+        map.remaining = map
+            .remaining
+            .checked_sub(1)
+            .ok_or(CborEncoderError::WrongLength)?;
+        let index = self.entries.len();
+        if self.deterministic {
+            self.entries.push((self.output.len(), self.output.len()));
+        }
+        Ok(index)
+    }
+    fn end_key(&mut self, index: usize) {
+        if let Some(entry) = self.entries.get_mut(index) {
+            entry.1 = self.output.len();
+        }
+    }
+    fn end_map(&mut self, map: CborMapEncoder) -> anyhow::Result<()> {
+        if map.remaining != 0 {
+            return Err(CborEncoderError::WrongLength.into());
+        }
+        if self.deterministic {
+            let entries = self.entries.split_off(map.first_entry);
+            deterministic::sort_entries(&mut self.output, &entries)?;
+        }
+        Ok(())
+    }
+    fn start_array(&mut self, len: usize) -> CborArrayEncoder {
+        self.write_len(MAJOR_ARRAY, len);
+        CborArrayEncoder { remaining: len }
+    }
+    fn array_element(&mut self, array: &mut CborArrayEncoder) -> anyhow::Result<CborAnySpecEncoder> {
+        array.remaining = array
+            .remaining
+            .checked_sub(1)
+            .ok_or(CborEncoderError::WrongLength)?;
+        Ok(CborAnySpecEncoder::new())
+    }
+    fn end_array(&mut self, array: CborArrayEncoder) -> anyhow::Result<()> {
+        if array.remaining != 0 {
+            return Err(CborEncoderError::WrongLength.into());
+        }
+        Ok(())
+    }
+    fn struct_field(
+        &mut self,
+        map: &mut CborMapEncoder,
+        key: &'static str,
+    ) -> anyhow::Result<CborAnySpecEncoder> {
+        let index = self.start_entry(map)?;
+        self.write_text(key);
+        self.end_key(index);
+        Ok(CborAnySpecEncoder::new())
+    }
+}
+
+impl SpecEncoder for SimpleCborSpecEncoder {
+    type AnySpecEncoder = CborAnySpecEncoder;
+    type SomeCloser = ();
+    type TupleEncoder = CborArrayEncoder;
+    type SeqEncoder = CborArrayEncoder;
+    type MapEncoder = CborMapEncoder;
+    type ValueEncoder = CborValueEncoder;
+    type EntryCloser = ();
+    type TupleStructEncoder = CborArrayEncoder;
+    type StructEncoder = CborMapEncoder;
+    type TupleVariantEncoder = CborArrayEncoder;
+    type StructVariantEncoder = CborMapEncoder;
+
+    fn encode_prim(&mut self, any: Self::AnySpecEncoder, prim: Primitive) -> anyhow::Result<()> {
+        match prim {
+            Primitive::Unit => self.write_null(any),
+            Primitive::Bool(x) => self.output.push(if x { TRUE } else { FALSE }),
+            Primitive::I8(x) => self.write_signed(x as i128),
+            Primitive::I16(x) => self.write_signed(x as i128),
+            Primitive::I32(x) => self.write_signed(x as i128),
+            Primitive::I64(x) => self.write_signed(x as i128),
+            Primitive::I128(x) => self.write_signed(x),
+            Primitive::U8(x) => self.write_unsigned(x as u128),
+            Primitive::U16(x) => self.write_unsigned(x as u128),
+            Primitive::U32(x) => self.write_unsigned(x as u128),
+            Primitive::U64(x) => self.write_unsigned(x as u128),
+            Primitive::U128(x) => self.write_unsigned(x),
+            Primitive::F32(x) => self.write_f32(x),
+            Primitive::F64(x) => self.write_f64(x),
+            Primitive::Char(x) => self.write_text(x.encode_utf8(&mut [0u8; 4])),
+        }
+        Ok(())
+    }
+
+    fn encode_str(&mut self, _any: Self::AnySpecEncoder, s: &str) -> anyhow::Result<()> {
+        self.write_text(s);
+        Ok(())
+    }
+
+    fn encode_bytes(&mut self, _any: Self::AnySpecEncoder, s: &[u8]) -> anyhow::Result<()> {
+        self.write_len(MAJOR_BYTES, s.len());
+        self.output.extend_from_slice(s);
+        Ok(())
+    }
+
+    fn encode_none(&mut self, any: Self::AnySpecEncoder) -> anyhow::Result<()> {
+        self.write_null(any);
+        Ok(())
+    }
+
+    fn encode_some(
+        &mut self,
+        any: Self::AnySpecEncoder,
+    ) -> anyhow::Result<(Self::AnySpecEncoder, Self::SomeCloser)> {
+        if any.cannot_be_null {
+            self.write_header(MAJOR_TAG, SOME_TAG);
+        }
+        Ok((
+            CborAnySpecEncoder {
+                cannot_be_null: true,
+            },
+            (),
+        ))
+    }
+
+    fn encode_unit_struct(
+        &mut self,
+        any: Self::AnySpecEncoder,
+        _name: &'static str,
+    ) -> anyhow::Result<()> {
+        self.write_null(any);
+        Ok(())
+    }
+
+    fn encode_tuple_struct(
+        &mut self,
+        _any: Self::AnySpecEncoder,
+        _name: &'static str,
+        len: usize,
+    ) -> anyhow::Result<Self::TupleStructEncoder> {
+        Ok(self.start_array(len))
+    }
+
+    fn encode_struct(
+        &mut self,
+        _any: Self::AnySpecEncoder,
+        _name: &'static str,
+        fields: &'static [&'static str],
+    ) -> anyhow::Result<Self::StructEncoder> {
+        Ok(self.start_map(fields.len()))
+    }
+
+    fn encode_unit_variant(
+        &mut self,
+        _any: Self::AnySpecEncoder,
+        _name: &'static str,
+        variants: &'static [&'static str],
+        variant_index: usize,
+    ) -> anyhow::Result<()> {
+        self.write_text(variants[variant_index]);
+        Ok(())
+    }
+
+    fn encode_tuple_variant(
+        &mut self,
+        _any: Self::AnySpecEncoder,
+        _name: &'static str,
+        variants: &'static [&'static str],
+        variant_index: usize,
+        len: usize,
+    ) -> anyhow::Result<Self::TupleVariantEncoder> {
+        self.write_len(MAJOR_MAP, 1);
+        self.write_text(variants[variant_index]);
+        Ok(self.start_array(len))
+    }
+
+    fn encode_struct_variant(
+        &mut self,
+        _any: Self::AnySpecEncoder,
+        _name: &'static str,
+        variants: &'static [&'static str],
+        variant_index: usize,
+        fields: &'static [&'static str],
+    ) -> anyhow::Result<Self::StructVariantEncoder> {
+        self.write_len(MAJOR_MAP, 1);
+        self.write_text(variants[variant_index]);
+        Ok(self.start_map(fields.len()))
+    }
+
+    fn encode_seq(
+        &mut self,
+        _any: Self::AnySpecEncoder,
+        len: usize,
+    ) -> anyhow::Result<Self::SeqEncoder> {
+        Ok(self.start_array(len))
+    }
+
+    fn encode_tuple(
+        &mut self,
+        _any: Self::AnySpecEncoder,
+        len: usize,
+    ) -> anyhow::Result<Self::TupleEncoder> {
+        Ok(self.start_array(len))
+    }
+
+    fn encode_map(
+        &mut self,
+        _any: Self::AnySpecEncoder,
+        len: usize,
+    ) -> anyhow::Result<Self::MapEncoder> {
+        Ok(self.start_map(len))
+    }
+
+    fn some_end(&mut self, _some: Self::SomeCloser) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn tuple_encode_element(
+        &mut self,
+        tuple: &mut Self::TupleEncoder,
+    ) -> anyhow::Result<Self::AnySpecEncoder> {
+        self.array_element(tuple)
+    }
+
+    fn tuple_end(&mut self, tuple: Self::TupleEncoder) -> anyhow::Result<()> {
+        self.end_array(tuple)
+    }
+
+    fn seq_encode_element(
+        &mut self,
+        seq: &mut Self::SeqEncoder,
+    ) -> anyhow::Result<Self::AnySpecEncoder> {
+        self.array_element(seq)
+    }
+
+    fn seq_end(&mut self, seq: Self::SeqEncoder) -> anyhow::Result<()> {
+        self.end_array(seq)
+    }
+
+    fn map_encode_element(
+        &mut self,
+        map: &mut Self::MapEncoder,
+    ) -> anyhow::Result<(Self::AnySpecEncoder, Self::ValueEncoder)> {
+        let index = self.start_entry(map)?;
+        Ok((CborAnySpecEncoder::new(), CborValueEncoder { index }))
+    }
+
+    fn map_end(&mut self, map: Self::MapEncoder) -> anyhow::Result<()> {
+        self.end_map(map)
+    }
+
+    fn entry_encode_value(
+        &mut self,
+        value: Self::ValueEncoder,
+    ) -> anyhow::Result<(Self::AnySpecEncoder, Self::EntryCloser)> {
+        self.end_key(value.index);
+        Ok((CborAnySpecEncoder::new(), ()))
+    }
+
+    fn entry_end(&mut self, _closer: Self::EntryCloser) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn tuple_struct_encode_field(
+        &mut self,
+        tuple: &mut Self::TupleStructEncoder,
+    ) -> anyhow::Result<Self::AnySpecEncoder> {
+        self.array_element(tuple)
+    }
+
+    fn tuple_struct_end(&mut self, tuple: Self::TupleStructEncoder) -> anyhow::Result<()> {
+        self.end_array(tuple)
+    }
+
+    fn struct_encode_field(
+        &mut self,
+        s: &mut Self::StructEncoder,
+        key: &'static str,
+    ) -> anyhow::Result<Self::AnySpecEncoder> {
+        self.struct_field(s, key)
+    }
+
+    fn struct_end(&mut self, s: Self::StructEncoder) -> anyhow::Result<()> {
+        self.end_map(s)
+    }
+
+    fn tuple_variant_encode_field(
+        &mut self,
+        tuple: &mut Self::TupleVariantEncoder,
+    ) -> anyhow::Result<Self::AnySpecEncoder> {
+        self.array_element(tuple)
+    }
+
+    fn tuple_variant_end(&mut self, tuple: Self::TupleVariantEncoder) -> anyhow::Result<()> {
+        self.end_array(tuple)
+    }
+
+    fn struct_variant_encode_field(
+        &mut self,
+        s: &mut Self::StructVariantEncoder,
+        key: &'static str,
+    ) -> anyhow::Result<Self::AnySpecEncoder> {
+        self.struct_field(s, key)
+    }
+
+    fn struct_variant_end(&mut self, s: Self::StructVariantEncoder) -> anyhow::Result<()> {
+        self.end_map(s)
+    }
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+}
+
+pub struct CborAnySpecEncoder {
+    cannot_be_null: bool,
+}
+
+impl CborAnySpecEncoder {
+    pub fn new() -> Self {
+        CborAnySpecEncoder {
+            cannot_be_null: false,
+        }
+    }
+}
+
+pub struct CborArrayEncoder {
+    remaining: usize,
+}
+
+pub struct CborMapEncoder {
+    remaining: usize,
+    first_entry: usize,
+}
+
+pub struct CborValueEncoder {
+    index: usize,
+}
@@ -0,0 +1,50 @@
+//! Conversions to and from IEEE 754 half precision, which has no native Rust type.
+
+/// Returns the half-precision encoding of `value` if it can be represented exactly.
+pub fn f16_from_f64(value: f64) -> Option<u16> {
+    let bits = value.to_bits();
+    let sign = ((bits >> 48) & 0x8000) as u16;
+    if value == 0.0 {
+        return Some(sign);
+    }
+    if value.is_infinite() {
+        return Some(sign | 0x7c00);
+    }
+    if value.is_nan() {
+        return None;
+    }
+    let exponent = ((bits >> 52) & 0x7ff) as i32 - 1023;
+    let significand = (bits & ((1 << 52) - 1)) | (1 << 52);
+    let shift = if exponent >= -14 {
+        if exponent > 15 {
+            return None;
+        }
+        42
+    } else if exponent >= -24 {
+        // Subnormal, so the implicit bit becomes explicit.
+        28 - exponent
+    } else {
+        return None;
+    };
+    if significand & ((1 << shift) - 1) != 0 {
+        return None;
+    }
+    let mantissa = (significand >> shift) as u16;
+    if exponent >= -14 {
+        Some(sign | (((exponent + 15) as u16) << 10) | (mantissa & 0x3ff))
+    } else {
+        Some(sign | mantissa)
+    }
+}
+
+pub fn f64_from_f16(half: u16) -> f64 {
+    let sign = if half & 0x8000 == 0 { 1.0 } else { -1.0 };
+    let exponent = ((half >> 10) & 0x1f) as i32;
+    let mantissa = (half & 0x3ff) as f64;
+    match exponent {
+        0 => sign * mantissa * 2f64.powi(-24),
+        31 if mantissa == 0.0 => sign * f64::INFINITY,
+        31 => f64::NAN,
+        _ => sign * (1.0 + mantissa / 1024.0) * 2f64.powi(exponent - 15),
+    }
+}
@@ -0,0 +1,50 @@
+#![feature(slice_take)]
+#![deny(unused_must_use)]
+#![feature(never_type)]
+#![feature(trait_alias)]
+
+use marshal::de::Deserialize;
+use marshal::ser::Serialize;
+
+use crate::decode::full::CborDecoder;
+use crate::encode::full::CborEncoder;
+
+pub mod decode;
+pub mod encode;
+mod float;
+#[cfg(test)]
+mod test;
+
+/// Marks a `Some` whose contents would otherwise be read as `None`, such as `Some(None)` or
+/// `Some(())`.
+pub const SOME_TAG: u64 = 0x6f70;
+
+const MAJOR_UNSIGNED: u8 = 0;
+const MAJOR_NEGATIVE: u8 = 1;
+const MAJOR_BYTES: u8 = 2;
+const MAJOR_TEXT: u8 = 3;
+const MAJOR_ARRAY: u8 = 4;
+const MAJOR_MAP: u8 = 5;
+const MAJOR_TAG: u8 = 6;
+const MAJOR_SIMPLE: u8 = 7;
+
+const INFO_U8: u8 = 24;
+const INFO_U16: u8 = 25;
+const INFO_U32: u8 = 26;
+const INFO_U64: u8 = 27;
+const INFO_INDEFINITE: u8 = 31;
+
+const FALSE: u8 = 0xf4;
+const TRUE: u8 = 0xf5;
+const NULL: u8 = 0xf6;
+const UNDEFINED: u8 = 0xf7;
+const FLOAT16: u8 = 0xf9;
+const FLOAT32: u8 = 0xfa;
+const FLOAT64: u8 = 0xfb;
+const BREAK: u8 = 0xff;
+
+const TAG_POSITIVE_BIGNUM: u64 = 2;
+const TAG_NEGATIVE_BIGNUM: u64 = 3;
+
+pub trait SerializeCbor = Serialize<CborEncoder>;
+pub trait DeserializeCbor = Deserialize<CborDecoder>;
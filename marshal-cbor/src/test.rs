@@ -0,0 +1,250 @@
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+
+use marshal::context::OwnedContext;
+use marshal_core::decode::{AnySpecDecoder, DecodeHint};
+use marshal_derive::{Deserialize, Serialize};
+
+use crate::decode::full::CborDecoderBuilder;
+use crate::decode::SimpleCborSpecDecoder;
+use crate::encode::full::CborEncoderBuilder;
+use crate::{DeserializeCbor, SerializeCbor};
+
+fn hex(input: &str) -> Vec<u8> {
+    (0..input.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&input[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+fn encode<T: ?Sized + SerializeCbor>(value: &T, deterministic: bool) -> anyhow::Result<Vec<u8>> {
+    let mut c = OwnedContext::new();
+    let mut w = CborEncoderBuilder::new();
+    w.set_deterministic(deterministic);
+    w.serialize(value, c.borrow())
+}
+
+fn decode<T: DeserializeCbor>(input: &[u8]) -> anyhow::Result<T> {
+    let mut c = OwnedContext::new();
+    CborDecoderBuilder::new(input).deserialize(c.borrow())
+}
+
+#[track_caller]
+fn test_round_trip<T: Debug + PartialEq + SerializeCbor + DeserializeCbor>(
+    input: T,
+    expected: &str,
+) -> anyhow::Result<()> {
+    let encoded = encode(&input, true)?;
+    assert_eq!(encoded, hex(expected), "{:?}", input);
+    assert_eq!(decode::<T>(&encoded)?, input);
+    Ok(())
+}
+
+#[test]
+fn test_integers() -> anyhow::Result<()> {
+    // Examples from RFC 8949 Appendix A.
+    test_round_trip(0u8, "00")?;
+    test_round_trip(1u32, "01")?;
+    test_round_trip(10u8, "0a")?;
+    test_round_trip(23u8, "17")?;
+    test_round_trip(24u8, "1818")?;
+    test_round_trip(25u16, "1819")?;
+    test_round_trip(100i32, "1864")?;
+    test_round_trip(1000u16, "1903e8")?;
+    test_round_trip(1000000u32, "1a000f4240")?;
+    test_round_trip(1000000000000u64, "1b000000e8d4a51000")?;
+    test_round_trip(18446744073709551615u64, "1bffffffffffffffff")?;
+    test_round_trip(18446744073709551616u128, "c249010000000000000000")?;
+    test_round_trip(-18446744073709551616i128, "3bffffffffffffffff")?;
+    test_round_trip(-18446744073709551617i128, "c349010000000000000000")?;
+    test_round_trip(-1i8, "20")?;
+    test_round_trip(-10i64, "29")?;
+    test_round_trip(-100i16, "3863")?;
+    test_round_trip(-1000i32, "3903e7")?;
+    test_round_trip(i128::MIN, "c3507fffffffffffffffffffffffffffffff")?;
+    test_round_trip(u128::MAX, "c250ffffffffffffffffffffffffffffffff")?;
+    assert!(decode::<u8>(&hex("190100")).is_err());
+    assert!(decode::<u32>(&hex("20")).is_err());
+    assert_eq!(decode::<i64>(&hex("c24101"))?, 1);
+    Ok(())
+}
+
+#[test]
+fn test_floats() -> anyhow::Result<()> {
+    test_round_trip(0.0f64, "f90000")?;
+    test_round_trip(-0.0f64, "f98000")?;
+    test_round_trip(1.0f64, "f93c00")?;
+    test_round_trip(1.1f64, "fb3ff199999999999a")?;
+    test_round_trip(1.5f32, "f93e00")?;
+    test_round_trip(65504.0f64, "f97bff")?;
+    test_round_trip(100000.0f64, "fa47c35000")?;
+    test_round_trip(3.4028234663852886e+38f64, "fa7f7fffff")?;
+    test_round_trip(1.0e+300f64, "fb7e37e43c8800759c")?;
+    test_round_trip(5.960464477539063e-8f64, "f90001")?;
+    test_round_trip(0.00006103515625f64, "f90400")?;
+    test_round_trip(-4.0f64, "f9c400")?;
+    test_round_trip(-4.1f64, "fbc010666666666666")?;
+    test_round_trip(f64::INFINITY, "f97c00")?;
+    test_round_trip(f64::NEG_INFINITY, "f9fc00")?;
+    assert_eq!(encode(&f64::NAN, true)?, hex("f97e00"));
+    assert!(decode::<f64>(&hex("f97e00"))?.is_nan());
+    assert_eq!(encode(&1.0f64, false)?, hex("fb3ff0000000000000"));
+    assert_eq!(encode(&1.0f32, false)?, hex("fa3f800000"));
+    assert_eq!(decode::<f32>(&hex("fb3ff8000000000000"))?, 1.5);
+    assert_eq!(decode::<f64>(&hex("1864"))?, 100.0);
+    Ok(())
+}
+
+#[test]
+fn test_simple() -> anyhow::Result<()> {
+    test_round_trip(false, "f4")?;
+    test_round_trip(true, "f5")?;
+    test_round_trip((), "f6")?;
+    test_round_trip('a', "6161")?;
+    test_round_trip("".to_string(), "60")?;
+    test_round_trip("IETF".to_string(), "6449455446")?;
+    test_round_trip("\"\\".to_string(), "62225c")?;
+    test_round_trip("\u{00fc}".to_string(), "62c3bc")?;
+    test_round_trip("\u{6c34}".to_string(), "63e6b0b4")?;
+    test_round_trip(Vec::<u8>::new(), "40")?;
+    test_round_trip(vec![1u8, 2, 3, 4], "4401020304")?;
+    Ok(())
+}
+
+#[test]
+fn test_containers() -> anyhow::Result<()> {
+    test_round_trip(Vec::<u32>::new(), "80")?;
+    test_round_trip(vec![1u32, 2, 3], "83010203")?;
+    test_round_trip((1u32, vec![2u32, 3], vec![4u32, 5]), "8301820203820405")?;
+    test_round_trip(BTreeMap::<u32, u32>::new(), "a0")?;
+    test_round_trip(BTreeMap::from([(1u32, 2u32), (3, 4)]), "a201020304")?;
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Foo {
+        a: u32,
+        b: Vec<u32>,
+    }
+    test_round_trip(
+        Foo {
+            a: 1,
+            b: vec![2, 3],
+        },
+        "a26161016162820203",
+    )?;
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Pair(u8, String);
+    test_round_trip(Pair(1, "x".to_string()), "82016178")?;
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Empty;
+    test_round_trip(Empty, "f6")?;
+    Ok(())
+}
+
+#[test]
+fn test_enum() -> anyhow::Result<()> {
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    enum Op {
+        Nop,
+        Add(u8, u8),
+        Move { x: i32 },
+    }
+    test_round_trip(Op::Nop, "634e6f70")?;
+    test_round_trip(Op::Add(1, 2), "a163416464820102")?;
+    test_round_trip(Op::Move { x: -1 }, "a1644d6f7665a1617820")?;
+    assert_eq!(decode::<Op>(&hex("bf63416464820102ff"))?, Op::Add(1, 2));
+    assert!(decode::<Op>(&hex("a2634e6f70f6634e6f70f6")).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_option() -> anyhow::Result<()> {
+    test_round_trip(None::<u8>, "f6")?;
+    test_round_trip(Some(5u8), "05")?;
+    test_round_trip(Some(None::<u8>), "d96f70f6")?;
+    test_round_trip(Some(Some(5u8)), "d96f7005")?;
+    test_round_trip(Some(Some(None::<u8>)), "d96f70d96f70f6")?;
+    test_round_trip(Some(()), "d96f70f6")?;
+    test_round_trip(vec![None, Some(None), Some(Some(1u8))], "83f6d96f70f6d96f7001")?;
+    // A reader that does not expect an option ignores the tag.
+    assert_eq!(decode::<()>(&hex("d96f70f6"))?, ());
+    Ok(())
+}
+
+#[test]
+fn test_deterministic() -> anyhow::Result<()> {
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Foo {
+        zz: u8,
+        b: u8,
+        a: u8,
+    }
+    let input = Foo { zz: 1, b: 2, a: 3 };
+    assert_eq!(encode(&input, false)?, hex("a3627a7a01616202616103"));
+    // Shorter keys sort first, because the length is part of the encoded key.
+    assert_eq!(encode(&input, true)?, hex("a3616103616202627a7a01"));
+    assert_eq!(decode::<Foo>(&hex("a3616103616202627a7a01"))?, input);
+    let map = BTreeMap::from([(-1i32, 1u8), (10, 2), (100, 3)]);
+    assert_eq!(encode(&map, true)?, hex("a30a021864032001"));
+    Ok(())
+}
+
+#[test]
+fn test_indefinite() -> anyhow::Result<()> {
+    assert_eq!(decode::<Vec<u8>>(&hex("5f42010243030405ff"))?, vec![1, 2, 3, 4, 5]);
+    assert_eq!(decode::<String>(&hex("7f657374726561646d696e67ff"))?, "streaming");
+    assert_eq!(decode::<Vec<u32>>(&hex("9fff"))?, Vec::<u32>::new());
+    assert_eq!(
+        decode::<(u8, Vec<u32>, Vec<u32>)>(&hex("9f018202039f0405ffff"))?,
+        (1, vec![2, 3], vec![4, 5])
+    );
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Foo {
+        a: u32,
+        b: Vec<u32>,
+    }
+    assert_eq!(
+        decode::<Foo>(&hex("bf61610161629f0203ffff"))?,
+        Foo {
+            a: 1,
+            b: vec![2, 3]
+        }
+    );
+    assert!(decode::<String>(&hex("7f4161ff")).is_err());
+    assert!(decode::<Vec<u32>>(&hex("9f01")).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_errors() -> anyhow::Result<()> {
+    assert!(decode::<u8>(&hex("0101")).is_err());
+    assert!(decode::<u8>(&hex("18")).is_err());
+    assert!(decode::<u8>(&hex("1c")).is_err());
+    assert!(decode::<u8>(&hex("ff")).is_err());
+    assert!(decode::<String>(&hex("6261")).is_err());
+    let deep = [hex("81").repeat(200), hex("01")].concat();
+    assert!(CborDecoderBuilder::new(&deep)
+        .set_budget(10)
+        .build()
+        .ignore()
+        .is_err());
+    Ok(())
+}
+
+#[test]
+fn test_untrusted_length() -> anyhow::Result<()> {
+    fn seq_size(input: &[u8]) -> anyhow::Result<Option<usize>> {
+        let mut decoder = SimpleCborSpecDecoder::new(input);
+        let any = AnySpecDecoder::new(&mut decoder, ());
+        Ok(any.decode(DecodeHint::Seq)?.try_into_seq()?.exact_size())
+    }
+    fn map_size(input: &[u8]) -> anyhow::Result<Option<usize>> {
+        let mut decoder = SimpleCborSpecDecoder::new(input);
+        let any = AnySpecDecoder::new(&mut decoder, ());
+        Ok(any.decode(DecodeHint::Map)?.try_into_map()?.exact_size())
+    }
+    assert_eq!(seq_size(&hex("9bffffffffffffffff"))?, None);
+    assert_eq!(seq_size(&hex("820102"))?, Some(2));
+    assert_eq!(map_size(&hex("bb000000000000000201"))?, None);
+    assert_eq!(map_size(&hex("a10102"))?, Some(1));
+    assert!(decode::<Vec<u64>>(&hex("9bffffffffffffffff01")).is_err());
+    Ok(())
+}
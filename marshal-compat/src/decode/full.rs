@@ -0,0 +1,61 @@
+use marshal::context::Context;
+use marshal_core::decode::depth_budget::{DepthBudgetDecoder, WithDepthBudget};
+use marshal_core::decode::poison::PoisonDecoder;
+use marshal_core::decode::{AnySpecDecoder, Decoder};
+use marshal_core::derive_decoder_for_newtype;
+
+use crate::decode::{CompatAnyDecoder, SimpleCompatSpecDecoder};
+use crate::{DeserializeCompat, WireFormat};
+
+pub struct CompatSpecDecoder<'de>(PoisonDecoder<DepthBudgetDecoder<SimpleCompatSpecDecoder<'de>>>);
+
+derive_decoder_for_newtype!(CompatSpecDecoder<'de>(PoisonDecoder<DepthBudgetDecoder<SimpleCompatSpecDecoder<'de>>>));
+
+/// Reads values written by serde with bincode or postcard. Unlike `bincode::deserialize`, trailing
+/// bytes are an error.
+pub struct CompatDecoderBuilder<'de> {
+    inner: CompatSpecDecoder<'de>,
+    depth_budget: usize,
+}
+
+impl<'de> CompatDecoderBuilder<'de> {
+    pub fn new(input: &'de [u8]) -> Self {
+        CompatDecoderBuilder {
+            inner: CompatSpecDecoder(PoisonDecoder::new(DepthBudgetDecoder::new(
+                SimpleCompatSpecDecoder::new(input),
+            ))),
+            depth_budget: 100,
+        }
+    }
+    pub fn set_format(&mut self, format: WireFormat) -> &mut Self {
+        self.inner.0.inner_mut().inner_mut().set_format(format);
+        self
+    }
+    pub fn set_budget(&mut self, depth_budget: usize) -> &mut Self {
+        self.depth_budget = depth_budget;
+        self
+    }
+    pub fn build<'p>(&'p mut self) -> AnySpecDecoder<'p, 'de, CompatSpecDecoder<'de>> {
+        let any = WithDepthBudget::new(self.depth_budget, CompatAnyDecoder::Any);
+        let any = self.inner.0.start(any);
+        AnySpecDecoder::new(&mut self.inner, any)
+    }
+    /// Deserializes the input, reporting the offset at which decoding failed.
+    pub fn deserialize<T: DeserializeCompat>(mut self, ctx: Context) -> anyhow::Result<T> {
+        let result = T::deserialize(self.build(), ctx).map_err(|e| e.context(self.location()))?;
+        self.end()?;
+        Ok(result)
+    }
+    pub fn location(&self) -> String {
+        self.inner.0.inner().inner().location()
+    }
+    pub fn end(self) -> anyhow::Result<()> {
+        Ok(self.inner.0.end()?.end()?.end()?)
+    }
+}
+
+pub struct CompatDecoder;
+
+impl Decoder for CompatDecoder {
+    type SpecDecoder<'de> = CompatSpecDecoder<'de>;
+}
@@ -0,0 +1,339 @@
+use std::borrow::Cow;
+
+use marshal_core::decode::{DecodeHint, DecodeVariantHint, SimpleDecoderView, SpecDecoder};
+use marshal_core::{Primitive, PrimitiveType};
+
+use crate::varint::{read_varint, zigzag_decode};
+use crate::{CompatError, WireFormat};
+
+pub mod full;
+
+pub struct SimpleCompatSpecDecoder<'de> {
+    input: &'de [u8],
+    data: &'de [u8],
+    format: WireFormat,
+}
+
+impl<'de> SimpleCompatSpecDecoder<'de> {
+    pub fn new(input: &'de [u8]) -> Self {
+        SimpleCompatSpecDecoder {
+            input,
+            data: input,
+            format: WireFormat::Bincode,
+        }
+    }
+    pub fn set_format(&mut self, format: WireFormat) {
+        self.format = format;
+    }
+    pub fn location(&self) -> String {
+        format!("at byte {}", self.input.len() - self.data.len())
+    }
+    #[inline]
+    fn read_slice(&mut self, len: usize) -> anyhow::Result<&'de [u8]> {
+        if len > self.data.len() {
+            return Err(CompatError::UnexpectedEof.into());
+        }
+        let (result, rest) = self.data.split_at(len);
+        self.data = rest;
+        Ok(result)
+    }
+    #[inline]
+    fn read_byte(&mut self) -> anyhow::Result<u8> {
+        Ok(self.read_slice(1)?[0])
+    }
+    #[inline]
+    fn read_array<const N: usize>(&mut self) -> anyhow::Result<[u8; N]> {
+        Ok(self.read_slice(N)?.try_into().unwrap())
+    }
+    #[inline]
+    fn read_unsigned<T: TryFrom<u128>, const N: usize>(
+        &mut self,
+        from_le_bytes: fn([u8; N]) -> T,
+    ) -> anyhow::Result<T> {
+        match self.format {
+            WireFormat::Bincode => Ok(from_le_bytes(self.read_array()?)),
+            WireFormat::Postcard => Ok(T::try_from(read_varint(&mut self.data)?)
+                .map_err(|_| CompatError::VarintOverflow)?),
+        }
+    }
+    #[inline]
+    fn read_signed<T: TryFrom<i128>, const N: usize>(
+        &mut self,
+        from_le_bytes: fn([u8; N]) -> T,
+    ) -> anyhow::Result<T> {
+        match self.format {
+            WireFormat::Bincode => Ok(from_le_bytes(self.read_array()?)),
+            WireFormat::Postcard => Ok(T::try_from(zigzag_decode(read_varint(&mut self.data)?))
+                .map_err(|_| CompatError::VarintOverflow)?),
+        }
+    }
+    #[inline]
+    fn read_len(&mut self) -> anyhow::Result<usize> {
+        Ok(usize::try_from(self.read_unsigned(u64::from_le_bytes)?)?)
+    }
+    #[inline]
+    fn read_bytes(&mut self) -> anyhow::Result<&'de [u8]> {
+        let len = self.read_len()?;
+        self.read_slice(len)
+    }
+    #[inline]
+    fn read_variant(&mut self) -> anyhow::Result<usize> {
+        Ok(self.read_unsigned(u32::from_le_bytes)? as usize)
+    }
+    fn read_char(&mut self) -> anyhow::Result<char> {
+        let bytes = match self.format {
+            WireFormat::Bincode => {
+                let width = match self.data.first() {
+                    Some(0x00..=0x7F) => 1,
+                    Some(0xC2..=0xDF) => 2,
+                    Some(0xE0..=0xEF) => 3,
+                    Some(0xF0..=0xF4) => 4,
+                    Some(_) => return Err(CompatError::BadChar.into()),
+                    None => return Err(CompatError::UnexpectedEof.into()),
+                };
+                self.read_slice(width)?
+            }
+            WireFormat::Postcard => self.read_bytes()?,
+        };
+        let s = std::str::from_utf8(bytes).map_err(|_| CompatError::BadChar)?;
+        let mut chars = s.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Ok(c),
+            _ => Err(CompatError::BadChar.into()),
+        }
+    }
+    #[inline]
+    fn decode_prim(&mut self, hint: PrimitiveType) -> anyhow::Result<Primitive> {
+        Ok(match hint {
+            PrimitiveType::Unit => Primitive::Unit,
+            PrimitiveType::Bool => match self.read_byte()? {
+                0 => Primitive::Bool(false),
+                1 => Primitive::Bool(true),
+                x => return Err(CompatError::BadBool(x).into()),
+            },
+            PrimitiveType::I8 => Primitive::I8(self.read_byte()? as i8),
+            PrimitiveType::I16 => Primitive::I16(self.read_signed(i16::from_le_bytes)?),
+            PrimitiveType::I32 => Primitive::I32(self.read_signed(i32::from_le_bytes)?),
+            PrimitiveType::I64 => Primitive::I64(self.read_signed(i64::from_le_bytes)?),
+            PrimitiveType::I128 => Primitive::I128(self.read_signed(i128::from_le_bytes)?),
+            PrimitiveType::U8 => Primitive::U8(self.read_byte()?),
+            PrimitiveType::U16 => Primitive::U16(self.read_unsigned(u16::from_le_bytes)?),
+            PrimitiveType::U32 => Primitive::U32(self.read_unsigned(u32::from_le_bytes)?),
+            PrimitiveType::U64 => Primitive::U64(self.read_unsigned(u64::from_le_bytes)?),
+            PrimitiveType::U128 => Primitive::U128(self.read_unsigned(u128::from_le_bytes)?),
+            PrimitiveType::F32 => Primitive::F32(f32::from_le_bytes(self.read_array()?)),
+            PrimitiveType::F64 => Primitive::F64(f64::from_le_bytes(self.read_array()?)),
+            PrimitiveType::Char => Primitive::Char(self.read_char()?),
+        })
+    }
+    #[inline]
+    fn decode_str(&mut self) -> anyhow::Result<Cow<'de, str>> {
+        Ok(Cow::Borrowed(std::str::from_utf8(self.read_bytes()?)?))
+    }
+    #[inline]
+    fn decode_bytes(&mut self) -> anyhow::Result<Cow<'de, [u8]>> {
+        Ok(Cow::Borrowed(self.read_bytes()?))
+    }
+    pub fn end(self) -> anyhow::Result<()> {
+        if !self.data.is_empty() {
+            return Err(CompatError::TrailingData.into());
+        }
+        Ok(())
+    }
+}
+
+pub enum CompatAnyDecoder {
+    Any,
+    Discriminant(usize),
+}
+
+pub struct CompatSeqDecoder {
+    len: usize,
+}
+
+pub struct CompatMapDecoder {
+    len: usize,
+}
+
+pub struct CompatDiscriminantDecoder {
+    discriminant: usize,
+}
+
+impl<'de> SpecDecoder<'de> for SimpleCompatSpecDecoder<'de> {
+    type AnyDecoder = CompatAnyDecoder;
+    type SeqDecoder = CompatSeqDecoder;
+    type MapDecoder = CompatMapDecoder;
+    type KeyDecoder = ();
+    type ValueDecoder = ();
+    type DiscriminantDecoder = CompatDiscriminantDecoder;
+    type VariantDecoder = ();
+    type EnumCloser = ();
+    type SomeDecoder = ();
+    type SomeCloser = ();
+
+    #[inline]
+    fn decode(
+        &mut self,
+        any: Self::AnyDecoder,
+        hint: DecodeHint,
+    ) -> anyhow::Result<SimpleDecoderView<'de, Self>> {
+        match any {
+            CompatAnyDecoder::Any => {}
+            CompatAnyDecoder::Discriminant(disc) => match hint {
+                DecodeHint::Primitive(p) => {
+                    return Ok(SimpleDecoderView::Primitive(match p {
+                        PrimitiveType::I8 => Primitive::I8(disc.try_into()?),
+                        PrimitiveType::I16 => Primitive::I16(disc.try_into()?),
+                        PrimitiveType::I32 => Primitive::I32(disc.try_into()?),
+                        PrimitiveType::I64 => Primitive::I64(disc.try_into()?),
+                        PrimitiveType::I128 => Primitive::I128(disc.try_into()?),
+                        PrimitiveType::U8 => Primitive::U8(disc.try_into()?),
+                        PrimitiveType::U16 => Primitive::U16(disc.try_into()?),
+                        PrimitiveType::U32 => Primitive::U32(disc.try_into()?),
+                        PrimitiveType::U64 => Primitive::U64(disc.try_into()?),
+                        PrimitiveType::U128 => Primitive::U128(disc.try_into()?),
+                        _ => return Err(CompatError::UnsupportedHint.into()),
+                    }))
+                }
+                DecodeHint::Identifier => {
+                    return Ok(SimpleDecoderView::Primitive(Primitive::U64(
+                        disc.try_into()?,
+                    )));
+                }
+                _ => return Err(CompatError::UnsupportedHint.into()),
+            },
+        }
+        match hint {
+            DecodeHint::Any => Err(CompatError::UnsupportedHint.into()),
+            DecodeHint::Primitive(x) => Ok(SimpleDecoderView::Primitive(self.decode_prim(x)?)),
+            DecodeHint::String => Ok(SimpleDecoderView::String(self.decode_str()?)),
+            DecodeHint::Bytes => Ok(SimpleDecoderView::Bytes(self.decode_bytes()?)),
+            DecodeHint::Option => match self.read_byte()? {
+                0 => Ok(SimpleDecoderView::None),
+                1 => Ok(SimpleDecoderView::Some(())),
+                tag => Err(CompatError::BadOptionTag(tag).into()),
+            },
+            DecodeHint::UnitStruct { .. } => Ok(SimpleDecoderView::Primitive(Primitive::Unit)),
+            DecodeHint::Seq => {
+                let len = self.read_len()?;
+                Ok(SimpleDecoderView::Seq(CompatSeqDecoder { len }))
+            }
+            DecodeHint::Tuple { len } => Ok(SimpleDecoderView::Seq(CompatSeqDecoder { len })),
+            DecodeHint::TupleStruct { name: _, len } => {
+                Ok(SimpleDecoderView::Seq(CompatSeqDecoder { len }))
+            }
+            DecodeHint::Map => Ok(SimpleDecoderView::Map(CompatMapDecoder {
+                len: self.read_len()?,
+            })),
+            DecodeHint::Struct { fields, .. } => Ok(SimpleDecoderView::Seq(CompatSeqDecoder {
+                len: fields.len(),
+            })),
+            DecodeHint::Enum { .. } => Ok(SimpleDecoderView::Enum(CompatDiscriminantDecoder {
+                discriminant: self.read_variant()?,
+            })),
+            DecodeHint::Identifier => Err(CompatError::UnsupportedHint.into()),
+            DecodeHint::Ignore => Err(CompatError::UnsupportedHint.into()),
+        }
+    }
+
+    #[inline]
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+
+    #[inline]
+    fn decode_seq_next(
+        &mut self,
+        seq: &mut Self::SeqDecoder,
+    ) -> anyhow::Result<Option<Self::AnyDecoder>> {
+        if let Some(new_len) = seq.len.checked_sub(1) {
+            seq.len = new_len;
+            Ok(Some(CompatAnyDecoder::Any))
+        } else {
+            Ok(None)
+        }
+    }
+
+    #[inline]
+    fn decode_seq_end(&mut self, _: Self::SeqDecoder) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    #[inline]
+    fn decode_map_next(
+        &mut self,
+        map: &mut Self::MapDecoder,
+    ) -> anyhow::Result<Option<Self::KeyDecoder>> {
+        if let Some(new_len) = map.len.checked_sub(1) {
+            map.len = new_len;
+            Ok(Some(()))
+        } else {
+            Ok(None)
+        }
+    }
+
+    #[inline]
+    fn decode_map_end(&mut self, _: Self::MapDecoder) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    #[inline]
+    fn decode_entry_key(
+        &mut self,
+        _: Self::KeyDecoder,
+    ) -> anyhow::Result<(Self::AnyDecoder, Self::ValueDecoder)> {
+        Ok((CompatAnyDecoder::Any, ()))
+    }
+
+    #[inline]
+    fn decode_entry_value(&mut self, _: Self::ValueDecoder) -> anyhow::Result<Self::AnyDecoder> {
+        Ok(CompatAnyDecoder::Any)
+    }
+
+    #[inline]
+    fn decode_enum_discriminant(
+        &mut self,
+        e: Self::DiscriminantDecoder,
+    ) -> anyhow::Result<(Self::AnyDecoder, Self::VariantDecoder)> {
+        Ok((CompatAnyDecoder::Discriminant(e.discriminant), ()))
+    }
+
+    #[inline]
+    fn decode_enum_variant(
+        &mut self,
+        _: Self::VariantDecoder,
+        hint: DecodeVariantHint,
+    ) -> anyhow::Result<(SimpleDecoderView<'de, Self>, Self::EnumCloser)> {
+        match hint {
+            DecodeVariantHint::UnitVariant => {
+                Ok((SimpleDecoderView::Primitive(Primitive::Unit), ()))
+            }
+            DecodeVariantHint::TupleVariant { len } => {
+                Ok((SimpleDecoderView::Seq(CompatSeqDecoder { len }), ()))
+            }
+            DecodeVariantHint::StructVariant { fields } => Ok((
+                SimpleDecoderView::Seq(CompatSeqDecoder { len: fields.len() }),
+                (),
+            )),
+            DecodeVariantHint::Ignore => Err(CompatError::UnsupportedHint.into()),
+        }
+    }
+
+    #[inline]
+    fn decode_enum_end(&mut self, _: Self::EnumCloser) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    #[inline]
+    fn decode_some_inner(
+        &mut self,
+        _: Self::SomeDecoder,
+    ) -> anyhow::Result<(Self::AnyDecoder, Self::SomeCloser)> {
+        Ok((CompatAnyDecoder::Any, ()))
+    }
+
+    #[inline]
+    fn decode_some_end(&mut self, _: Self::SomeCloser) -> anyhow::Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,50 @@
+use marshal::context::Context;
+use marshal_core::derive_encoder_for_newtype;
+use marshal_core::encode::poison::PoisonEncoder;
+use marshal_core::encode::{AnySpecEncoder, Encoder};
+
+use crate::encode::SimpleCompatSpecEncoder;
+use crate::{SerializeCompat, WireFormat};
+
+pub struct CompatSpecEncoder(PoisonEncoder<SimpleCompatSpecEncoder>);
+
+derive_encoder_for_newtype!(CompatSpecEncoder(PoisonEncoder<SimpleCompatSpecEncoder>));
+
+/// Writes values with the same bytes as serde with bincode or postcard, so that either side of a
+/// connection can be migrated independently.
+pub struct CompatEncoderBuilder {
+    inner: CompatSpecEncoder,
+}
+
+impl CompatEncoderBuilder {
+    pub fn new() -> Self {
+        CompatEncoderBuilder {
+            inner: CompatSpecEncoder(PoisonEncoder::new(SimpleCompatSpecEncoder::new())),
+        }
+    }
+    pub fn set_format(&mut self, format: WireFormat) -> &mut Self {
+        self.inner.0.inner_mut().set_format(format);
+        self
+    }
+    pub fn build(&mut self) -> AnySpecEncoder<'_, CompatSpecEncoder> {
+        let any = self.inner.0.start(());
+        AnySpecEncoder::new(&mut self.inner, any)
+    }
+    pub fn serialize<T: ?Sized + SerializeCompat>(
+        mut self,
+        value: &T,
+        ctx: Context,
+    ) -> anyhow::Result<Vec<u8>> {
+        value.serialize(self.build(), ctx)?;
+        self.end()
+    }
+    pub fn end(self) -> anyhow::Result<Vec<u8>> {
+        self.inner.0.end()?.end()
+    }
+}
+
+pub struct CompatEncoder;
+
+impl Encoder for CompatEncoder {
+    type SpecEncoder<'en> = CompatSpecEncoder;
+}
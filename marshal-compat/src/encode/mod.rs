@@ -0,0 +1,339 @@
+use marshal_core::encode::SpecEncoder;
+use marshal_core::Primitive;
+
+use crate::varint::{write_varint, zigzag_encode};
+use crate::WireFormat;
+
+pub mod full;
+
+pub struct SimpleCompatSpecEncoder {
+    output: Vec<u8>,
+    format: WireFormat,
+}
+
+impl SimpleCompatSpecEncoder {
+    pub fn new() -> Self {
+        SimpleCompatSpecEncoder {
+            output: vec![],
+            format: WireFormat::Bincode,
+        }
+    }
+    pub fn set_format(&mut self, format: WireFormat) {
+        self.format = format;
+    }
+    #[inline]
+    fn write_unsigned(&mut self, value: u128, le_bytes: &[u8]) {
+        match self.format {
+            WireFormat::Bincode => self.output.extend_from_slice(le_bytes),
+            WireFormat::Postcard => write_varint(&mut self.output, value),
+        }
+    }
+    #[inline]
+    fn write_signed(&mut self, value: i128, le_bytes: &[u8]) {
+        match self.format {
+            WireFormat::Bincode => self.output.extend_from_slice(le_bytes),
+            WireFormat::Postcard => write_varint(&mut self.output, zigzag_encode(value)),
+        }
+    }
+    #[inline]
+    fn write_len(&mut self, len: usize) {
+        let len = len as u64;
+        self.write_unsigned(len as u128, &len.to_le_bytes());
+    }
+    #[inline]
+    fn write_bytes(&mut self, bytes: &[u8]) {
+        self.write_len(bytes.len());
+        self.output.extend_from_slice(bytes);
+    }
+    #[inline]
+    fn write_variant(&mut self, index: usize) {
+        let index = index as u32;
+        self.write_unsigned(index as u128, &index.to_le_bytes());
+    }
+    pub fn end(self) -> anyhow::Result<Vec<u8>> {
+        Ok(self.output)
+    }
+}
+
+impl SpecEncoder for SimpleCompatSpecEncoder {
+    type AnySpecEncoder = ();
+    type SomeCloser = ();
+    type TupleEncoder = ();
+    type SeqEncoder = ();
+    type MapEncoder = ();
+    type ValueEncoder = ();
+    type EntryCloser = ();
+    type TupleStructEncoder = ();
+    type StructEncoder = ();
+    type TupleVariantEncoder = ();
+    type StructVariantEncoder = ();
+
+    #[inline]
+    fn encode_prim(&mut self, _: Self::AnySpecEncoder, prim: Primitive) -> anyhow::Result<()> {
+        match prim {
+            Primitive::Unit => {}
+            Primitive::Bool(x) => self.output.push(x as u8),
+            Primitive::I8(x) => self.output.push(x as u8),
+            Primitive::I16(x) => self.write_signed(x as i128, &x.to_le_bytes()),
+            Primitive::I32(x) => self.write_signed(x as i128, &x.to_le_bytes()),
+            Primitive::I64(x) => self.write_signed(x as i128, &x.to_le_bytes()),
+            Primitive::I128(x) => self.write_signed(x, &x.to_le_bytes()),
+            Primitive::U8(x) => self.output.push(x),
+            Primitive::U16(x) => self.write_unsigned(x as u128, &x.to_le_bytes()),
+            Primitive::U32(x) => self.write_unsigned(x as u128, &x.to_le_bytes()),
+            Primitive::U64(x) => self.write_unsigned(x as u128, &x.to_le_bytes()),
+            Primitive::U128(x) => self.write_unsigned(x, &x.to_le_bytes()),
+            Primitive::F32(x) => self.output.extend_from_slice(&x.to_le_bytes()),
+            Primitive::F64(x) => self.output.extend_from_slice(&x.to_le_bytes()),
+            Primitive::Char(x) => {
+                let mut buffer = [0; 4];
+                let bytes = x.encode_utf8(&mut buffer).as_bytes();
+                match self.format {
+                    // bincode writes the UTF-8 bytes alone, since the first byte gives the width.
+                    WireFormat::Bincode => self.output.extend_from_slice(bytes),
+                    WireFormat::Postcard => self.write_bytes(bytes),
+                }
+            }
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn encode_str(&mut self, _: Self::AnySpecEncoder, s: &str) -> anyhow::Result<()> {
+        self.write_bytes(s.as_bytes());
+        Ok(())
+    }
+
+    #[inline]
+    fn encode_bytes(&mut self, _: Self::AnySpecEncoder, s: &[u8]) -> anyhow::Result<()> {
+        self.write_bytes(s);
+        Ok(())
+    }
+
+    #[inline]
+    fn encode_none(&mut self, _: Self::AnySpecEncoder) -> anyhow::Result<()> {
+        self.output.push(0);
+        Ok(())
+    }
+
+    #[inline]
+    fn encode_some(
+        &mut self,
+        _: Self::AnySpecEncoder,
+    ) -> anyhow::Result<(Self::AnySpecEncoder, Self::SomeCloser)> {
+        self.output.push(1);
+        Ok(((), ()))
+    }
+
+    #[inline]
+    fn encode_unit_struct(
+        &mut self,
+        _: Self::AnySpecEncoder,
+        _: &'static str,
+    ) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    #[inline]
+    fn encode_tuple_struct(
+        &mut self,
+        _: Self::AnySpecEncoder,
+        _: &'static str,
+        _: usize,
+    ) -> anyhow::Result<Self::TupleStructEncoder> {
+        Ok(())
+    }
+
+    #[inline]
+    fn encode_struct(
+        &mut self,
+        _: Self::AnySpecEncoder,
+        _: &'static str,
+        _: &'static [&'static str],
+    ) -> anyhow::Result<Self::StructEncoder> {
+        Ok(())
+    }
+
+    #[inline]
+    fn encode_unit_variant(
+        &mut self,
+        _: Self::AnySpecEncoder,
+        _: &'static str,
+        _: &'static [&'static str],
+        variant_index: usize,
+    ) -> anyhow::Result<()> {
+        self.write_variant(variant_index);
+        Ok(())
+    }
+
+    #[inline]
+    fn encode_tuple_variant(
+        &mut self,
+        _: Self::AnySpecEncoder,
+        _: &'static str,
+        _: &'static [&'static str],
+        variant_index: usize,
+        _: usize,
+    ) -> anyhow::Result<Self::TupleVariantEncoder> {
+        self.write_variant(variant_index);
+        Ok(())
+    }
+
+    #[inline]
+    fn encode_struct_variant(
+        &mut self,
+        _: Self::AnySpecEncoder,
+        _: &'static str,
+        _: &'static [&'static str],
+        variant_index: usize,
+        _: &'static [&'static str],
+    ) -> anyhow::Result<Self::StructVariantEncoder> {
+        self.write_variant(variant_index);
+        Ok(())
+    }
+
+    #[inline]
+    fn encode_seq(
+        &mut self,
+        _: Self::AnySpecEncoder,
+        len: usize,
+    ) -> anyhow::Result<Self::SeqEncoder> {
+        self.write_len(len);
+        Ok(())
+    }
+
+    #[inline]
+    fn encode_tuple(
+        &mut self,
+        _: Self::AnySpecEncoder,
+        _: usize,
+    ) -> anyhow::Result<Self::TupleEncoder> {
+        Ok(())
+    }
+
+    #[inline]
+    fn encode_map(
+        &mut self,
+        _: Self::AnySpecEncoder,
+        len: usize,
+    ) -> anyhow::Result<Self::MapEncoder> {
+        self.write_len(len);
+        Ok(())
+    }
+
+    #[inline]
+    fn some_end(&mut self, _: Self::SomeCloser) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    #[inline]
+    fn tuple_encode_element(
+        &mut self,
+        _: &mut Self::TupleEncoder,
+    ) -> anyhow::Result<Self::AnySpecEncoder> {
+        Ok(())
+    }
+
+    #[inline]
+    fn tuple_end(&mut self, _: Self::TupleEncoder) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    #[inline]
+    fn seq_encode_element(
+        &mut self,
+        _: &mut Self::SeqEncoder,
+    ) -> anyhow::Result<Self::AnySpecEncoder> {
+        Ok(())
+    }
+
+    #[inline]
+    fn seq_end(&mut self, _: Self::SeqEncoder) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    #[inline]
+    fn map_encode_element(
+        &mut self,
+        _: &mut Self::MapEncoder,
+    ) -> anyhow::Result<(Self::AnySpecEncoder, Self::ValueEncoder)> {
+        Ok(((), ()))
+    }
+
+    #[inline]
+    fn map_end(&mut self, _: Self::MapEncoder) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    #[inline]
+    fn entry_encode_value(
+        &mut self,
+        _: Self::ValueEncoder,
+    ) -> anyhow::Result<(Self::AnySpecEncoder, Self::EntryCloser)> {
+        Ok(((), ()))
+    }
+
+    #[inline]
+    fn entry_end(&mut self, _: Self::EntryCloser) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    #[inline]
+    fn tuple_struct_encode_field(
+        &mut self,
+        _: &mut Self::TupleStructEncoder,
+    ) -> anyhow::Result<Self::AnySpecEncoder> {
+        Ok(())
+    }
+
+    #[inline]
+    fn tuple_struct_end(&mut self, _: Self::TupleStructEncoder) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    #[inline]
+    fn struct_encode_field(
+        &mut self,
+        _: &mut Self::StructEncoder,
+        _: &'static str,
+    ) -> anyhow::Result<Self::AnySpecEncoder> {
+        Ok(())
+    }
+
+    #[inline]
+    fn struct_end(&mut self, _: Self::StructEncoder) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    #[inline]
+    fn tuple_variant_encode_field(
+        &mut self,
+        _: &mut Self::TupleVariantEncoder,
+    ) -> anyhow::Result<Self::AnySpecEncoder> {
+        Ok(())
+    }
+
+    #[inline]
+    fn tuple_variant_end(&mut self, _: Self::TupleVariantEncoder) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    #[inline]
+    fn struct_variant_encode_field(
+        &mut self,
+        _: &mut Self::StructVariantEncoder,
+        _: &'static str,
+    ) -> anyhow::Result<Self::AnySpecEncoder> {
+        Ok(())
+    }
+
+    #[inline]
+    fn struct_variant_end(&mut self, _: Self::StructVariantEncoder) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    #[inline]
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+}
@@ -0,0 +1,59 @@
+#![deny(unused_must_use)]
+#![feature(never_type)]
+#![feature(trait_alias)]
+
+use std::fmt::{Display, Formatter};
+
+use marshal::de::Deserialize;
+use marshal::ser::Serialize;
+
+use crate::decode::full::CompatDecoder;
+use crate::encode::full::CompatEncoder;
+
+pub mod decode;
+pub mod encode;
+#[cfg(test)]
+mod test;
+mod varint;
+
+/// The serde format whose byte layout is reproduced.
+#[derive(Debug, Copy, Clone, Eq, Ord, PartialEq, PartialOrd, Hash, Default)]
+pub enum WireFormat {
+    /// The layout of `bincode::serialize` in bincode 1.x: fixed-width little-endian integers,
+    /// `u64` lengths and `u32` variant indices.
+    #[default]
+    Bincode,
+    /// The layout of postcard 1.x: varint integers and lengths, with zigzag encoding for signed
+    /// integers.
+    Postcard,
+}
+
+#[derive(Debug)]
+pub enum CompatError {
+    UnsupportedHint,
+    UnexpectedEof,
+    TrailingData,
+    VarintOverflow,
+    BadBool(u8),
+    BadOptionTag(u8),
+    BadChar,
+}
+
+impl Display for CompatError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CompatError::UnsupportedHint => write!(f, "the format is not self-describing"),
+            CompatError::UnexpectedEof => write!(f, "unexpected end of input"),
+            CompatError::TrailingData => write!(f, "trailing data after the value"),
+            CompatError::VarintOverflow => write!(f, "varint is out of range"),
+            CompatError::BadBool(x) => write!(f, "invalid bool {}", x),
+            CompatError::BadOptionTag(x) => write!(f, "invalid option tag {}", x),
+            CompatError::BadChar => write!(f, "invalid char encoding"),
+        }
+    }
+}
+
+impl std::error::Error for CompatError {}
+
+pub trait SerializeCompat = Serialize<CompatEncoder>;
+pub trait DeserializeCompat = Deserialize<CompatDecoder>;
@@ -0,0 +1,290 @@
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+
+use marshal::context::OwnedContext;
+
+use crate::decode::full::CompatDecoderBuilder;
+use crate::encode::full::CompatEncoderBuilder;
+use crate::{DeserializeCompat, SerializeCompat, WireFormat};
+
+fn serde_encode<T: serde::Serialize>(value: &T, format: WireFormat) -> Vec<u8> {
+    match format {
+        WireFormat::Bincode => bincode::serialize(value).unwrap(),
+        WireFormat::Postcard => postcard::to_allocvec(value).unwrap(),
+    }
+}
+
+fn serde_decode<'de, T: serde::Deserialize<'de>>(input: &'de [u8], format: WireFormat) -> T {
+    match format {
+        WireFormat::Bincode => bincode::deserialize(input).unwrap(),
+        WireFormat::Postcard => postcard::from_bytes(input).unwrap(),
+    }
+}
+
+fn decode<T: DeserializeCompat>(input: &[u8], format: WireFormat) -> anyhow::Result<T> {
+    let mut c = OwnedContext::new();
+    let mut builder = CompatDecoderBuilder::new(input);
+    builder.set_format(format);
+    builder.deserialize(c.borrow())
+}
+
+/// Checks that marshal and serde produce the same bytes and can read each other's output.
+#[track_caller]
+fn test_differential<T>(value: T) -> anyhow::Result<()>
+where
+    T: Debug
+        + PartialEq
+        + SerializeCompat
+        + DeserializeCompat
+        + serde::Serialize
+        + for<'de> serde::Deserialize<'de>,
+{
+    for format in [WireFormat::Bincode, WireFormat::Postcard] {
+        let mut c = OwnedContext::new();
+        let mut builder = CompatEncoderBuilder::new();
+        builder.set_format(format);
+        let encoded = builder.serialize(&value, c.borrow())?;
+        let expected = serde_encode(&value, format);
+        assert_eq!(encoded, expected, "{:?} {:?}", format, value);
+        assert_eq!(decode::<T>(&expected, format)?, value);
+        assert_eq!(serde_decode::<T>(&encoded, format), value);
+    }
+    Ok(())
+}
+
+#[track_caller]
+fn test_error<T: Debug + DeserializeCompat>(input: &[u8], format: WireFormat, expected: &str) {
+    let error = decode::<T>(input, format).unwrap_err();
+    assert_eq!(format!("{:#}", error), expected);
+}
+
+#[derive(
+    serde::Serialize,
+    serde::Deserialize,
+    marshal_derive::Serialize,
+    marshal_derive::Deserialize,
+    Debug,
+    PartialEq,
+)]
+struct Marker;
+
+#[derive(
+    serde::Serialize,
+    serde::Deserialize,
+    marshal_derive::Serialize,
+    marshal_derive::Deserialize,
+    Debug,
+    PartialEq,
+)]
+struct Meters(u32);
+
+#[derive(
+    serde::Serialize,
+    serde::Deserialize,
+    marshal_derive::Serialize,
+    marshal_derive::Deserialize,
+    Debug,
+    PartialEq,
+)]
+enum Shape {
+    Point,
+    Circle(f64),
+    Pair(i16, u128),
+    Rect { width: u32, height: i64 },
+}
+
+#[derive(
+    serde::Serialize,
+    serde::Deserialize,
+    marshal_derive::Serialize,
+    marshal_derive::Deserialize,
+    Debug,
+    PartialEq,
+)]
+struct Inner {
+    name: String,
+    data: Vec<u8>,
+    chars: (char, char, char),
+}
+
+#[derive(
+    serde::Serialize,
+    serde::Deserialize,
+    marshal_derive::Serialize,
+    marshal_derive::Deserialize,
+    Debug,
+    PartialEq,
+)]
+struct Everything {
+    flag: bool,
+    i8: i8,
+    i16: i16,
+    i32: i32,
+    i64: i64,
+    i128: i128,
+    u8: u8,
+    u16: u16,
+    u32: u32,
+    u64: u64,
+    u128: u128,
+    usize: usize,
+    f32: f32,
+    f64: f64,
+    unit: (),
+    marker: Marker,
+    meters: Meters,
+    none: Option<u32>,
+    some: Option<String>,
+    shapes: Vec<Shape>,
+    map: BTreeMap<String, u16>,
+    nested: Vec<Vec<u32>>,
+    inner: Inner,
+    ok: Result<u8, String>,
+    err: Result<u8, String>,
+}
+
+#[test]
+fn test_primitives() -> anyhow::Result<()> {
+    test_differential(true)?;
+    test_differential(0u8)?;
+    test_differential(255u8)?;
+    test_differential(-128i8)?;
+    test_differential(127u16)?;
+    test_differential(128u16)?;
+    test_differential(u16::MAX)?;
+    test_differential(-1i16)?;
+    test_differential(i32::MIN)?;
+    test_differential(i32::MAX)?;
+    test_differential(u64::MAX)?;
+    test_differential(i64::MIN)?;
+    test_differential(u128::MAX)?;
+    test_differential(i128::MIN)?;
+    test_differential(i128::MAX)?;
+    test_differential(1.5f32)?;
+    test_differential(-0.25f64)?;
+    test_differential('a')?;
+    test_differential('é')?;
+    test_differential('€')?;
+    test_differential('🦀')?;
+    test_differential("hello".to_string())?;
+    test_differential(String::new())?;
+    test_differential("x".repeat(200))?;
+    test_differential(vec![0u8; 300])?;
+    test_differential(())?;
+    test_differential(Some(3u32))?;
+    test_differential(None::<u32>)?;
+    Ok(())
+}
+
+#[test]
+fn test_structs() -> anyhow::Result<()> {
+    test_differential(Marker)?;
+    test_differential(Meters(1_000_000))?;
+    test_differential(Shape::Point)?;
+    test_differential(Shape::Circle(2.0))?;
+    test_differential(Shape::Pair(-300, 1 << 100))?;
+    test_differential(Shape::Rect {
+        width: 3,
+        height: -4,
+    })?;
+    test_differential(Everything {
+        flag: true,
+        i8: -1,
+        i16: -1000,
+        i32: 100_000,
+        i64: -10_000_000_000,
+        i128: i128::MIN + 1,
+        u8: 200,
+        u16: 60_000,
+        u32: 4_000_000_000,
+        u64: u64::MAX - 1,
+        u128: u128::MAX / 3,
+        usize: 12345,
+        f32: f32::MIN_POSITIVE,
+        f64: f64::NEG_INFINITY,
+        unit: (),
+        marker: Marker,
+        meters: Meters(42),
+        none: None,
+        some: Some("some".to_string()),
+        shapes: vec![
+            Shape::Point,
+            Shape::Circle(0.5),
+            Shape::Rect {
+                width: 1,
+                height: 2,
+            },
+        ],
+        map: BTreeMap::from([("a".to_string(), 1), ("b".to_string(), 1000)]),
+        nested: vec![vec![], vec![1, 2], vec![300]],
+        inner: Inner {
+            name: "inner".to_string(),
+            data: vec![1, 2, 3],
+            chars: ('z', 'ß', '🦀'),
+        },
+        ok: Ok(7),
+        err: Err("error".to_string()),
+    })?;
+    Ok(())
+}
+
+#[test]
+fn test_layout() -> anyhow::Result<()> {
+    let mut c = OwnedContext::new();
+    let value = (300u16, -2i32, "ab".to_string(), Shape::Circle(0.0));
+    assert_eq!(
+        CompatEncoderBuilder::new().serialize(&value, c.borrow())?,
+        vec![
+            44, 1, 254, 255, 255, 255, 2, 0, 0, 0, 0, 0, 0, 0, b'a', b'b', 1, 0, 0, 0, 0, 0, 0, 0,
+            0, 0, 0, 0
+        ]
+    );
+    let mut builder = CompatEncoderBuilder::new();
+    builder.set_format(WireFormat::Postcard);
+    assert_eq!(
+        builder.serialize(&value, c.borrow())?,
+        vec![172, 2, 3, 2, b'a', b'b', 1, 0, 0, 0, 0, 0, 0, 0, 0]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_errors() -> anyhow::Result<()> {
+    test_error::<u8>(
+        &[1, 2],
+        WireFormat::Bincode,
+        "trailing data after the value",
+    );
+    test_error::<u32>(
+        &[1, 2],
+        WireFormat::Bincode,
+        "at byte 0: unexpected end of input",
+    );
+    test_error::<(bool, bool)>(&[1, 2], WireFormat::Bincode, "at byte 2: invalid bool 2");
+    test_error::<Option<u8>>(
+        &[5, 0],
+        WireFormat::Postcard,
+        "at byte 1: invalid option tag 5",
+    );
+    test_error::<u16>(
+        &[0xFF, 0xFF, 0x7F],
+        WireFormat::Postcard,
+        "at byte 3: varint is out of range",
+    );
+    test_error::<u128>(
+        &[0xFF; 20],
+        WireFormat::Postcard,
+        "at byte 19: varint is out of range",
+    );
+    test_error::<char>(
+        &[0xFF],
+        WireFormat::Bincode,
+        "at byte 0: invalid char encoding",
+    );
+    test_error::<char>(
+        &[2, b'a', b'b'],
+        WireFormat::Postcard,
+        "at byte 3: invalid char encoding",
+    );
+    Ok(())
+}
@@ -0,0 +1,35 @@
+use crate::CompatError;
+
+/// Writes an unsigned LEB128 varint, as used by postcard.
+pub fn write_varint(output: &mut Vec<u8>, mut value: u128) {
+    while value >= 0x80 {
+        output.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    output.push(value as u8);
+}
+
+pub fn read_varint(data: &mut &[u8]) -> Result<u128, CompatError> {
+    let mut result = 0u128;
+    for shift in (0..128).step_by(7) {
+        let (&byte, rest) = data.split_first().ok_or(CompatError::UnexpectedEof)?;
+        *data = rest;
+        let bits = (byte & 0x7F) as u128;
+        if shift == 126 && bits > 0b11 {
+            return Err(CompatError::VarintOverflow);
+        }
+        result |= bits << shift;
+        if byte & 0x80 == 0 {
+            return Ok(result);
+        }
+    }
+    Err(CompatError::VarintOverflow)
+}
+
+pub fn zigzag_encode(value: i128) -> u128 {
+    ((value << 1) ^ (value >> 127)) as u128
+}
+
+pub fn zigzag_decode(value: u128) -> i128 {
+    (value >> 1) as i128 ^ -((value & 1) as i128)
+}
@@ -0,0 +1,60 @@
+use marshal::context::Context;
+use marshal::de::Deserialize;
+use marshal_core::decode::depth_budget::{DepthBudgetDecoder, WithDepthBudget};
+use marshal_core::decode::poison::PoisonDecoder;
+use marshal_core::decode::{AnySpecDecoder, Decoder};
+use marshal_core::derive_decoder_for_newtype;
+
+use crate::decode::SimpleConfigSpecDecoder;
+use crate::source::ConfigSources;
+
+pub struct ConfigSpecDecoder<'de>(PoisonDecoder<DepthBudgetDecoder<SimpleConfigSpecDecoder<'de>>>);
+
+derive_decoder_for_newtype!(ConfigSpecDecoder<'de>(PoisonDecoder<DepthBudgetDecoder<SimpleConfigSpecDecoder<'de>>>));
+
+/// Reads a struct from layered [ConfigSources]. Keys are resolved against the fields of each
+/// struct, so `APP_MAX_CONN` sets `max_conn` and `APP_DB__HOST` sets the `host` of `db`. Leaves are
+/// parsed according to the requested type, and missing keys need `#[marshal(default)]`.
+pub struct ConfigDecoderBuilder<'de> {
+    inner: ConfigSpecDecoder<'de>,
+    depth_budget: usize,
+}
+
+impl<'de> ConfigDecoderBuilder<'de> {
+    pub fn new(sources: &'de ConfigSources) -> Self {
+        ConfigDecoderBuilder {
+            inner: ConfigSpecDecoder(PoisonDecoder::new(DepthBudgetDecoder::new(
+                SimpleConfigSpecDecoder::new(sources.entries()),
+            ))),
+            depth_budget: 100,
+        }
+    }
+    pub fn set_budget(&mut self, depth_budget: usize) -> &mut Self {
+        self.depth_budget = depth_budget;
+        self
+    }
+    pub fn build<'p>(&'p mut self) -> AnySpecDecoder<'p, 'de, ConfigSpecDecoder<'de>> {
+        let any = self.inner.0.inner().inner().start();
+        let any = WithDepthBudget::new(self.depth_budget, any);
+        let any = self.inner.0.start(any);
+        AnySpecDecoder::new(&mut self.inner, any)
+    }
+    /// Deserializes the merged sources, reporting the source of the entry that failed.
+    pub fn deserialize<T: Deserialize<ConfigDecoder>>(mut self, ctx: Context) -> anyhow::Result<T> {
+        let result = T::deserialize(self.build(), ctx).map_err(|e| e.context(self.location()))?;
+        self.end()?;
+        Ok(result)
+    }
+    pub fn location(&self) -> String {
+        self.inner.0.inner().inner().location()
+    }
+    pub fn end(self) -> anyhow::Result<()> {
+        Ok(self.inner.0.end()?.end()?.end()?)
+    }
+}
+
+pub struct ConfigDecoder;
+
+impl Decoder for ConfigDecoder {
+    type SpecDecoder<'de> = ConfigSpecDecoder<'de>;
+}
@@ -0,0 +1,468 @@
+use std::borrow::Cow;
+use std::fmt::{Display, Formatter};
+
+use marshal_core::decode::text::parse_primitive;
+use marshal_core::decode::{DecodeHint, DecodeVariantHint, SimpleDecoderView, SpecDecoder};
+use marshal_core::{Primitive, PrimitiveType};
+
+use crate::source::ConfigEntry;
+
+pub mod full;
+
+#[derive(Debug)]
+pub enum ConfigDecoderError {
+    UnexpectedArgument(String),
+    ExpectedValue,
+    ExpectedNested,
+    BadIndex(String),
+    ExpectedEmpty,
+}
+
+impl Display for ConfigDecoderError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigDecoderError::UnexpectedArgument(arg) => {
+                write!(
+                    f,
+                    "expected an argument starting with \"--\", found {:?}",
+                    arg
+                )
+            }
+            ConfigDecoderError::ExpectedValue => write!(f, "expected a value, found nested keys"),
+            ConfigDecoderError::ExpectedNested => write!(f, "expected nested keys, found a value"),
+            ConfigDecoderError::BadIndex(index) => {
+                write!(f, "expected a sequence index, found {:?}", index)
+            }
+            ConfigDecoderError::ExpectedEmpty => write!(f, "expected an empty value"),
+        }
+    }
+}
+
+impl std::error::Error for ConfigDecoderError {}
+
+/// An entry below the value being decoded.
+#[derive(Copy, Clone)]
+pub struct ConfigItem<'de> {
+    entry: &'de ConfigEntry,
+    /// The rest of the key, which is empty if the entry holds the value itself.
+    key: &'de str,
+}
+
+impl<'de> ConfigItem<'de> {
+    fn is_leaf(&self) -> bool {
+        self.key.is_empty()
+    }
+    /// Splits off the first segment of the key, and removes any escapes from it.
+    fn split(self) -> (Cow<'de, str>, ConfigItem<'de>) {
+        if self.entry.kind.escapes_segments() {
+            return self.split_escaped();
+        }
+        let (segment, key) = self
+            .key
+            .split_once(self.entry.kind.separator())
+            .unwrap_or((self.key, ""));
+        (
+            Cow::Borrowed(segment),
+            ConfigItem {
+                entry: self.entry,
+                key,
+            },
+        )
+    }
+    fn split_escaped(self) -> (Cow<'de, str>, ConfigItem<'de>) {
+        let mut segment = String::new();
+        let mut escaped = false;
+        let mut chars = self.key.char_indices();
+        let end = loop {
+            match chars.next() {
+                None => break self.key.len(),
+                Some((_, '\\')) => {
+                    escaped = true;
+                    if let Some((_, c)) = chars.next() {
+                        segment.push(c);
+                    }
+                }
+                Some((index, '.')) => break index,
+                Some((_, c)) => segment.push(c),
+            }
+        };
+        let key = self.key.get(end + 1..).unwrap_or("");
+        let segment = if escaped {
+            Cow::Owned(segment)
+        } else {
+            Cow::Borrowed(&self.key[..end])
+        };
+        (
+            segment,
+            ConfigItem {
+                entry: self.entry,
+                key,
+            },
+        )
+    }
+    /// Strips a field name, which may itself contain a separator, from the start of the key.
+    fn strip_field(self, field: &str) -> Option<ConfigItem<'de>> {
+        let field = self.entry.kind.normalize(field);
+        let key = self.key.strip_prefix(&field)?;
+        let key = if key.is_empty() {
+            key
+        } else {
+            key.strip_prefix(self.entry.kind.separator())?
+        };
+        Some(ConfigItem {
+            entry: self.entry,
+            key,
+        })
+    }
+}
+
+pub enum ConfigAnyDecoder<'de> {
+    Node(Vec<ConfigItem<'de>>),
+    /// A sequence element, map key or enum discriminant.
+    Text(Cow<'de, str>),
+}
+
+pub struct SimpleConfigSpecDecoder<'de> {
+    entries: &'de [ConfigEntry],
+    /// The origin of the most recent entry, for reporting errors.
+    origin: Option<&'de str>,
+}
+
+impl<'de> SimpleConfigSpecDecoder<'de> {
+    pub fn new(entries: &'de [ConfigEntry]) -> Self {
+        SimpleConfigSpecDecoder {
+            entries,
+            origin: None,
+        }
+    }
+    pub fn start(&self) -> ConfigAnyDecoder<'de> {
+        ConfigAnyDecoder::Node(
+            self.entries
+                .iter()
+                .map(|entry| ConfigItem {
+                    entry,
+                    key: &entry.key,
+                })
+                .collect(),
+        )
+    }
+    pub fn end(self) -> anyhow::Result<()> {
+        Ok(())
+    }
+    pub fn location(&self) -> String {
+        match self.origin {
+            None => "at the root".to_string(),
+            Some(origin) => format!("in {}", origin),
+        }
+    }
+    fn visit(&mut self, item: &ConfigItem<'de>) {
+        self.origin = Some(&item.entry.origin);
+    }
+    /// Fails if the highest-precedence entry replaces nested keys with a value.
+    fn check_nested(&mut self, items: &[ConfigItem<'de>]) -> anyhow::Result<()> {
+        if let Some(last) = items.last() {
+            self.visit(last);
+            if last.is_leaf() && last.entry.value.is_some() {
+                return Err(ConfigDecoderError::ExpectedNested.into());
+            }
+        }
+        Ok(())
+    }
+    /// The value of the highest-precedence entry with a value.
+    fn leaf(&mut self, items: &[ConfigItem<'de>]) -> anyhow::Result<Cow<'de, str>> {
+        for item in items.iter().rev() {
+            if let (true, Some(value)) = (item.is_leaf(), &item.entry.value) {
+                self.visit(item);
+                return Ok(Cow::Borrowed(value));
+            }
+        }
+        Err(ConfigDecoderError::ExpectedValue.into())
+    }
+    /// Groups the items by their first segment, in the order that the segments first appear.
+    fn group_segments(items: Vec<ConfigItem<'de>>) -> Vec<(Cow<'de, str>, Vec<ConfigItem<'de>>)> {
+        let mut groups: Vec<(Cow<'de, str>, Vec<ConfigItem<'de>>)> = vec![];
+        for item in items {
+            if item.is_leaf() {
+                continue;
+            }
+            let (segment, rest) = item.split();
+            match groups.iter_mut().find(|(key, _)| *key == segment) {
+                Some((_, group)) => group.push(rest),
+                None => groups.push((segment, vec![rest])),
+            }
+        }
+        groups
+    }
+    /// Groups the items by field. A key such as `db_host` belongs to the longest matching field,
+    /// so that it is not mistaken for the `host` field of `db`.
+    fn group_fields(
+        items: Vec<ConfigItem<'de>>,
+        fields: &'static [&'static str],
+    ) -> Vec<(Cow<'de, str>, Vec<ConfigItem<'de>>)> {
+        let mut groups = vec![vec![]; fields.len()];
+        for item in items {
+            let best = fields
+                .iter()
+                .enumerate()
+                .filter_map(|(index, field)| Some((index, field, item.strip_field(field)?)))
+                .max_by_key(|(_, field, _)| field.len());
+            if let Some((index, _, rest)) = best {
+                groups[index].push(rest);
+            }
+        }
+        fields
+            .iter()
+            .zip(groups)
+            .filter(|(_, group)| !group.is_empty())
+            .map(|(field, group)| (Cow::Borrowed(*field), group))
+            .collect()
+    }
+    /// The elements of a sequence. The highest-precedence source replaces the whole sequence when
+    /// it sets the key to a list, while indexed keys such as `servers.0.host` are merged.
+    fn elements(
+        &mut self,
+        items: Vec<ConfigItem<'de>>,
+    ) -> anyhow::Result<Vec<ConfigAnyDecoder<'de>>> {
+        let last = match items.last() {
+            None => return Ok(vec![]),
+            Some(last) => *last,
+        };
+        self.visit(&last);
+        if last.is_leaf() {
+            let mut elements = vec![];
+            for item in items {
+                if !item.is_leaf() || item.entry.layer != last.entry.layer {
+                    continue;
+                }
+                match &item.entry.value {
+                    None => {}
+                    Some(value) if !item.entry.kind.splits_lists() => {
+                        elements.push(ConfigAnyDecoder::Text(Cow::Borrowed(value)))
+                    }
+                    Some(value) if value.is_empty() => {}
+                    Some(value) => elements.extend(
+                        value
+                            .split(',')
+                            .map(|x| ConfigAnyDecoder::Text(Cow::Borrowed(x))),
+                    ),
+                }
+            }
+            return Ok(elements);
+        }
+        let mut indexed = vec![];
+        for (index, group) in Self::group_segments(items) {
+            let index = index
+                .parse::<usize>()
+                .map_err(|_| ConfigDecoderError::BadIndex(index.into_owned()))?;
+            indexed.push((index, group));
+        }
+        indexed.sort_by_key(|(index, _)| *index);
+        Ok(indexed
+            .into_iter()
+            .map(|(_, group)| ConfigAnyDecoder::Node(group))
+            .collect())
+    }
+    /// Reads an enum as a variant name, or as a single nested key naming the variant.
+    fn discriminant(
+        &mut self,
+        items: Vec<ConfigItem<'de>>,
+        variants: &'static [&'static str],
+    ) -> anyhow::Result<(Cow<'de, str>, Option<Vec<ConfigItem<'de>>>)> {
+        let last = match items.last() {
+            None => return Err(ConfigDecoderError::ExpectedValue.into()),
+            Some(last) => *last,
+        };
+        if last.is_leaf() {
+            return Ok((self.leaf(&items)?, None));
+        }
+        self.visit(&last);
+        let (segment, _) = last.split();
+        let variant = variants
+            .iter()
+            .find(|variant| last.entry.kind.normalize(variant) == segment)
+            .map_or(segment, |variant| Cow::Borrowed(*variant));
+        let items = items
+            .into_iter()
+            .filter_map(|item| item.strip_field(&variant))
+            .collect();
+        Ok((variant, Some(items)))
+    }
+    fn decode_node(
+        &mut self,
+        items: Vec<ConfigItem<'de>>,
+        hint: DecodeHint,
+    ) -> anyhow::Result<SimpleDecoderView<'de, Self>> {
+        Ok(match hint {
+            DecodeHint::Struct { fields, .. } => {
+                self.check_nested(&items)?;
+                SimpleDecoderView::Map(Self::group_fields(items, fields).into_iter())
+            }
+            DecodeHint::Map => {
+                self.check_nested(&items)?;
+                SimpleDecoderView::Map(Self::group_segments(items).into_iter())
+            }
+            DecodeHint::Any | DecodeHint::Ignore => {
+                if items.iter().all(|item| item.is_leaf()) {
+                    if let Ok(text) = self.leaf(&items) {
+                        return Ok(SimpleDecoderView::String(text));
+                    }
+                }
+                SimpleDecoderView::Map(Self::group_segments(items).into_iter())
+            }
+            DecodeHint::Seq | DecodeHint::Tuple { .. } | DecodeHint::TupleStruct { .. } => {
+                SimpleDecoderView::Seq(self.elements(items)?.into_iter())
+            }
+            DecodeHint::Option => SimpleDecoderView::Some(ConfigAnyDecoder::Node(items)),
+            DecodeHint::Enum { variants, .. } => {
+                SimpleDecoderView::Enum(self.discriminant(items, variants)?)
+            }
+            _ => {
+                let text = self.leaf(&items)?;
+                return self.decode_text(text, hint);
+            }
+        })
+    }
+    fn decode_text(
+        &mut self,
+        text: Cow<'de, str>,
+        hint: DecodeHint,
+    ) -> anyhow::Result<SimpleDecoderView<'de, Self>> {
+        Ok(match hint {
+            DecodeHint::Primitive(PrimitiveType::Unit) | DecodeHint::UnitStruct { .. } => {
+                if !text.is_empty() {
+                    return Err(ConfigDecoderError::ExpectedEmpty.into());
+                }
+                SimpleDecoderView::Primitive(Primitive::Unit)
+            }
+            DecodeHint::Primitive(prim) => {
+                SimpleDecoderView::Primitive(parse_primitive(&text, prim)?)
+            }
+            DecodeHint::Bytes => SimpleDecoderView::Bytes(match text {
+                Cow::Borrowed(text) => Cow::Borrowed(text.as_bytes()),
+                Cow::Owned(text) => Cow::Owned(text.into_bytes()),
+            }),
+            DecodeHint::Option => SimpleDecoderView::Some(ConfigAnyDecoder::Text(text)),
+            DecodeHint::Enum { .. } => SimpleDecoderView::Enum((text, None)),
+            DecodeHint::Seq
+            | DecodeHint::Tuple { .. }
+            | DecodeHint::TupleStruct { .. }
+            | DecodeHint::Map
+            | DecodeHint::Struct { .. } => return Err(ConfigDecoderError::ExpectedNested.into()),
+            _ => SimpleDecoderView::String(text),
+        })
+    }
+}
+
+impl<'de> SpecDecoder<'de> for SimpleConfigSpecDecoder<'de> {
+    type AnyDecoder = ConfigAnyDecoder<'de>;
+    type SeqDecoder = std::vec::IntoIter<ConfigAnyDecoder<'de>>;
+    type MapDecoder = std::vec::IntoIter<(Cow<'de, str>, Vec<ConfigItem<'de>>)>;
+    type KeyDecoder = (Cow<'de, str>, Vec<ConfigItem<'de>>);
+    type ValueDecoder = Vec<ConfigItem<'de>>;
+    type DiscriminantDecoder = (Cow<'de, str>, Option<Vec<ConfigItem<'de>>>);
+    type VariantDecoder = Option<Vec<ConfigItem<'de>>>;
+    type EnumCloser = ();
+    type SomeDecoder = ConfigAnyDecoder<'de>;
+    type SomeCloser = ();
+
+    fn decode(
+        &mut self,
+        any: Self::AnyDecoder,
+        hint: DecodeHint,
+    ) -> anyhow::Result<SimpleDecoderView<'de, Self>> {
+        match any {
+            ConfigAnyDecoder::Node(items) => self.decode_node(items, hint),
+            ConfigAnyDecoder::Text(text) => self.decode_text(text, hint),
+        }
+    }
+
+    fn is_human_readable(&self) -> bool {
+        true
+    }
+
+    fn decode_seq_next(
+        &mut self,
+        seq: &mut Self::SeqDecoder,
+    ) -> anyhow::Result<Option<Self::AnyDecoder>> {
+        Ok(seq.next())
+    }
+
+    fn decode_seq_exact_size(&self, seq: &Self::SeqDecoder) -> Option<usize> {
+        Some(seq.len())
+    }
+
+    fn decode_seq_end(&mut self, _seq: Self::SeqDecoder) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn decode_map_next(
+        &mut self,
+        map: &mut Self::MapDecoder,
+    ) -> anyhow::Result<Option<Self::KeyDecoder>> {
+        Ok(map.next())
+    }
+
+    fn decode_map_exact_size(&self, map: &Self::MapDecoder) -> Option<usize> {
+        Some(map.len())
+    }
+
+    fn decode_map_end(&mut self, _map: Self::MapDecoder) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn decode_entry_key(
+        &mut self,
+        (key, items): Self::KeyDecoder,
+    ) -> anyhow::Result<(Self::AnyDecoder, Self::ValueDecoder)> {
+        Ok((ConfigAnyDecoder::Text(key), items))
+    }
+
+    fn decode_entry_value(
+        &mut self,
+        items: Self::ValueDecoder,
+    ) -> anyhow::Result<Self::AnyDecoder> {
+        Ok(ConfigAnyDecoder::Node(items))
+    }
+
+    fn decode_enum_discriminant(
+        &mut self,
+        (variant, items): Self::DiscriminantDecoder,
+    ) -> anyhow::Result<(Self::AnyDecoder, Self::VariantDecoder)> {
+        Ok((ConfigAnyDecoder::Text(variant), items))
+    }
+
+    fn decode_enum_variant(
+        &mut self,
+        items: Self::VariantDecoder,
+        hint: DecodeVariantHint,
+    ) -> anyhow::Result<(SimpleDecoderView<'de, Self>, Self::EnumCloser)> {
+        let view = match (items, hint) {
+            (_, DecodeVariantHint::UnitVariant | DecodeVariantHint::Ignore) => {
+                SimpleDecoderView::Primitive(Primitive::Unit)
+            }
+            (None, _) => return Err(ConfigDecoderError::ExpectedNested.into()),
+            (Some(items), DecodeVariantHint::TupleVariant { len }) => {
+                self.decode_node(items, DecodeHint::Tuple { len })?
+            }
+            (Some(items), DecodeVariantHint::StructVariant { fields }) => {
+                self.decode_node(items, DecodeHint::Struct { name: "", fields })?
+            }
+        };
+        Ok((view, ()))
+    }
+
+    fn decode_enum_end(&mut self, _e: Self::EnumCloser) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn decode_some_inner(
+        &mut self,
+        e: Self::SomeDecoder,
+    ) -> anyhow::Result<(Self::AnyDecoder, Self::SomeCloser)> {
+        Ok((e, ()))
+    }
+
+    fn decode_some_end(&mut self, _p: Self::SomeCloser) -> anyhow::Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,47 @@
+use marshal::context::Context;
+use marshal::ser::Serialize;
+use marshal_core::derive_encoder_for_newtype;
+use marshal_core::encode::poison::PoisonEncoder;
+use marshal_core::encode::{AnySpecEncoder, Encoder};
+
+use crate::encode::{ConfigAnySpecEncoder, SimpleConfigSpecEncoder};
+
+pub struct ConfigSpecEncoder(PoisonEncoder<SimpleConfigSpecEncoder>);
+
+derive_encoder_for_newtype!(ConfigSpecEncoder(PoisonEncoder<SimpleConfigSpecEncoder>));
+
+/// Flattens a value into entries such as `("db.host", Some("localhost"))`, which can be layered
+/// under other sources with [crate::source::ConfigSources::add_value]. Sequence elements are keyed
+/// by their index, `None` fields are omitted and empty sequences and maps have no value.
+pub struct ConfigEncoderBuilder {
+    inner: ConfigSpecEncoder,
+}
+
+impl ConfigEncoderBuilder {
+    pub fn new() -> Self {
+        ConfigEncoderBuilder {
+            inner: ConfigSpecEncoder(PoisonEncoder::new(SimpleConfigSpecEncoder::new())),
+        }
+    }
+    pub fn build(&mut self) -> AnySpecEncoder<'_, ConfigSpecEncoder> {
+        let any = self.inner.0.start(ConfigAnySpecEncoder::Document);
+        AnySpecEncoder::new(&mut self.inner, any)
+    }
+    pub fn end(self) -> anyhow::Result<Vec<(String, Option<String>)>> {
+        self.inner.0.end()?.end()
+    }
+    pub fn serialize<T: ?Sized + Serialize<ConfigEncoder>>(
+        mut self,
+        value: &T,
+        ctx: Context,
+    ) -> anyhow::Result<Vec<(String, Option<String>)>> {
+        value.serialize(self.build(), ctx)?;
+        self.end()
+    }
+}
+
+pub struct ConfigEncoder;
+
+impl Encoder for ConfigEncoder {
+    type SpecEncoder<'en> = ConfigSpecEncoder;
+}
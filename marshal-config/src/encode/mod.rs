@@ -0,0 +1,368 @@
+use std::fmt::{Display, Formatter};
+
+use marshal_core::encode::SpecEncoder;
+use marshal_core::Primitive;
+
+pub mod full;
+
+#[derive(Debug)]
+pub enum ConfigEncoderError {
+    NotAStruct,
+    BadKey(String),
+    NotUtf8,
+}
+
+impl Display for ConfigEncoderError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigEncoderError::NotAStruct => {
+                write!(f, "a config must be a struct, map or enum with data")
+            }
+            ConfigEncoderError::BadKey(key) => {
+                write!(
+                    f,
+                    "map key {:?} must be a non-empty scalar",
+                    key
+                )
+            }
+            ConfigEncoderError::NotUtf8 => write!(f, "byte strings must be UTF-8"),
+        }
+    }
+}
+
+impl std::error::Error for ConfigEncoderError {}
+
+pub enum ConfigAnySpecEncoder {
+    Document,
+    Key(String),
+    /// A map key, which becomes the next segment of the keys within the entry.
+    MapKey,
+}
+
+/// The prefix of the keys within a sequence, map or struct, and the number of entries before it
+/// started, so that an empty one is recorded rather than missing.
+pub struct ConfigNestedEncoder {
+    prefix: Option<String>,
+    start: usize,
+    len: usize,
+}
+
+pub struct SimpleConfigSpecEncoder {
+    entries: Vec<(String, Option<String>)>,
+    /// The text of the most recent map key.
+    map_key: Option<String>,
+}
+
+fn child(prefix: &Option<String>, segment: &str) -> String {
+    match prefix {
+        None => segment.to_string(),
+        Some(prefix) => format!("{}.{}", prefix, segment),
+    }
+}
+
+impl SimpleConfigSpecEncoder {
+    pub fn new() -> Self {
+        SimpleConfigSpecEncoder {
+            entries: vec![],
+            map_key: None,
+        }
+    }
+    pub fn end(self) -> anyhow::Result<Vec<(String, Option<String>)>> {
+        Ok(self.entries)
+    }
+    fn write_value(&mut self, any: ConfigAnySpecEncoder, value: String) -> anyhow::Result<()> {
+        match any {
+            ConfigAnySpecEncoder::Document => return Err(ConfigEncoderError::NotAStruct.into()),
+            ConfigAnySpecEncoder::Key(key) => self.entries.push((key, Some(value))),
+            ConfigAnySpecEncoder::MapKey => {
+                if value.is_empty() {
+                    return Err(ConfigEncoderError::BadKey(value).into());
+                }
+                self.map_key = Some(value.replace('\\', "\\\\").replace('.', "\\."))
+            }
+        }
+        Ok(())
+    }
+    fn open_nested(&mut self, any: ConfigAnySpecEncoder) -> anyhow::Result<ConfigNestedEncoder> {
+        let prefix = match any {
+            ConfigAnySpecEncoder::Document => None,
+            ConfigAnySpecEncoder::Key(key) => Some(key),
+            ConfigAnySpecEncoder::MapKey => {
+                return Err(ConfigEncoderError::BadKey("".to_string()).into())
+            }
+        };
+        Ok(ConfigNestedEncoder {
+            prefix,
+            start: self.entries.len(),
+            len: 0,
+        })
+    }
+    fn open_seq(&mut self, any: ConfigAnySpecEncoder) -> anyhow::Result<ConfigNestedEncoder> {
+        match any {
+            ConfigAnySpecEncoder::Document => Err(ConfigEncoderError::NotAStruct.into()),
+            any => self.open_nested(any),
+        }
+    }
+    fn next_element(&mut self, seq: &mut ConfigNestedEncoder) -> ConfigAnySpecEncoder {
+        let key = child(&seq.prefix, &seq.len.to_string());
+        seq.len += 1;
+        ConfigAnySpecEncoder::Key(key)
+    }
+    /// Records an empty sequence, map or struct as a key without a value.
+    fn close_nested(&mut self, nested: ConfigNestedEncoder) -> anyhow::Result<()> {
+        if let Some(prefix) = nested.prefix {
+            if self.entries.len() == nested.start {
+                self.entries.push((prefix, None));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl SpecEncoder for SimpleConfigSpecEncoder {
+    type AnySpecEncoder = ConfigAnySpecEncoder;
+    type SomeCloser = ();
+    type TupleEncoder = ConfigNestedEncoder;
+    type SeqEncoder = ConfigNestedEncoder;
+    type MapEncoder = ConfigNestedEncoder;
+    type ValueEncoder = Option<String>;
+    type EntryCloser = ();
+    type TupleStructEncoder = ConfigNestedEncoder;
+    type StructEncoder = ConfigNestedEncoder;
+    type TupleVariantEncoder = ConfigNestedEncoder;
+    type StructVariantEncoder = ConfigNestedEncoder;
+
+    fn encode_prim(&mut self, any: Self::AnySpecEncoder, prim: Primitive) -> anyhow::Result<()> {
+        let value = match prim {
+            Primitive::Unit => String::new(),
+            Primitive::Bool(x) => x.to_string(),
+            Primitive::I8(x) => x.to_string(),
+            Primitive::I16(x) => x.to_string(),
+            Primitive::I32(x) => x.to_string(),
+            Primitive::I64(x) => x.to_string(),
+            Primitive::I128(x) => x.to_string(),
+            Primitive::U8(x) => x.to_string(),
+            Primitive::U16(x) => x.to_string(),
+            Primitive::U32(x) => x.to_string(),
+            Primitive::U64(x) => x.to_string(),
+            Primitive::U128(x) => x.to_string(),
+            Primitive::F32(x) => x.to_string(),
+            Primitive::F64(x) => x.to_string(),
+            Primitive::Char(x) => x.to_string(),
+        };
+        self.write_value(any, value)
+    }
+
+    fn encode_str(&mut self, any: Self::AnySpecEncoder, s: &str) -> anyhow::Result<()> {
+        self.write_value(any, s.to_string())
+    }
+
+    fn encode_bytes(&mut self, any: Self::AnySpecEncoder, s: &[u8]) -> anyhow::Result<()> {
+        let s = std::str::from_utf8(s).map_err(|_| ConfigEncoderError::NotUtf8)?;
+        self.write_value(any, s.to_string())
+    }
+
+    fn encode_none(&mut self, any: Self::AnySpecEncoder) -> anyhow::Result<()> {
+        // A missing key, which is read with `#[marshal(default)]`.
+        match any {
+            ConfigAnySpecEncoder::Document => Err(ConfigEncoderError::NotAStruct.into()),
+            ConfigAnySpecEncoder::Key(_) => Ok(()),
+            ConfigAnySpecEncoder::MapKey => Err(ConfigEncoderError::BadKey("".to_string()).into()),
+        }
+    }
+
+    fn encode_some(
+        &mut self,
+        any: Self::AnySpecEncoder,
+    ) -> anyhow::Result<(Self::AnySpecEncoder, Self::SomeCloser)> {
+        Ok((any, ()))
+    }
+
+    fn encode_unit_struct(
+        &mut self,
+        any: Self::AnySpecEncoder,
+        _name: &'static str,
+    ) -> anyhow::Result<()> {
+        self.write_value(any, String::new())
+    }
+
+    fn encode_tuple_struct(
+        &mut self,
+        any: Self::AnySpecEncoder,
+        _name: &'static str,
+        _len: usize,
+    ) -> anyhow::Result<Self::TupleStructEncoder> {
+        self.open_seq(any)
+    }
+
+    fn encode_struct(
+        &mut self,
+        any: Self::AnySpecEncoder,
+        _name: &'static str,
+        _fields: &'static [&'static str],
+    ) -> anyhow::Result<Self::StructEncoder> {
+        self.open_nested(any)
+    }
+
+    fn encode_unit_variant(
+        &mut self,
+        any: Self::AnySpecEncoder,
+        _name: &'static str,
+        variants: &'static [&'static str],
+        variant_index: usize,
+    ) -> anyhow::Result<()> {
+        self.write_value(any, variants[variant_index].to_string())
+    }
+
+    fn encode_tuple_variant(
+        &mut self,
+        any: Self::AnySpecEncoder,
+        _name: &'static str,
+        variants: &'static [&'static str],
+        variant_index: usize,
+        _len: usize,
+    ) -> anyhow::Result<Self::TupleVariantEncoder> {
+        let prefix = self.open_nested(any)?.prefix;
+        let key = child(&prefix, variants[variant_index]);
+        self.open_seq(ConfigAnySpecEncoder::Key(key))
+    }
+
+    fn encode_struct_variant(
+        &mut self,
+        any: Self::AnySpecEncoder,
+        _name: &'static str,
+        variants: &'static [&'static str],
+        variant_index: usize,
+        _fields: &'static [&'static str],
+    ) -> anyhow::Result<Self::StructVariantEncoder> {
+        let prefix = self.open_nested(any)?.prefix;
+        let key = child(&prefix, variants[variant_index]);
+        self.open_nested(ConfigAnySpecEncoder::Key(key))
+    }
+
+    fn encode_seq(
+        &mut self,
+        any: Self::AnySpecEncoder,
+        _len: usize,
+    ) -> anyhow::Result<Self::SeqEncoder> {
+        self.open_seq(any)
+    }
+
+    fn encode_tuple(
+        &mut self,
+        any: Self::AnySpecEncoder,
+        _len: usize,
+    ) -> anyhow::Result<Self::TupleEncoder> {
+        self.open_seq(any)
+    }
+
+    fn encode_map(
+        &mut self,
+        any: Self::AnySpecEncoder,
+        _len: usize,
+    ) -> anyhow::Result<Self::MapEncoder> {
+        self.open_nested(any)
+    }
+
+    fn some_end(&mut self, _some: Self::SomeCloser) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn tuple_encode_element(
+        &mut self,
+        tuple: &mut Self::TupleEncoder,
+    ) -> anyhow::Result<Self::AnySpecEncoder> {
+        Ok(self.next_element(tuple))
+    }
+
+    fn tuple_end(&mut self, tuple: Self::TupleEncoder) -> anyhow::Result<()> {
+        self.close_nested(tuple)
+    }
+
+    fn seq_encode_element(
+        &mut self,
+        seq: &mut Self::SeqEncoder,
+    ) -> anyhow::Result<Self::AnySpecEncoder> {
+        Ok(self.next_element(seq))
+    }
+
+    fn seq_end(&mut self, seq: Self::SeqEncoder) -> anyhow::Result<()> {
+        self.close_nested(seq)
+    }
+
+    fn map_encode_element(
+        &mut self,
+        map: &mut Self::MapEncoder,
+    ) -> anyhow::Result<(Self::AnySpecEncoder, Self::ValueEncoder)> {
+        Ok((ConfigAnySpecEncoder::MapKey, map.prefix.clone()))
+    }
+
+    fn map_end(&mut self, map: Self::MapEncoder) -> anyhow::Result<()> {
+        self.close_nested(map)
+    }
+
+    fn entry_encode_value(
+        &mut self,
+        value: Self::ValueEncoder,
+    ) -> anyhow::Result<(Self::AnySpecEncoder, Self::EntryCloser)> {
+        let key = self
+            .map_key
+            .take()
+            .ok_or_else(|| ConfigEncoderError::BadKey("".to_string()))?;
+        Ok((ConfigAnySpecEncoder::Key(child(&value, &key)), ()))
+    }
+
+    fn entry_end(&mut self, _closer: Self::EntryCloser) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn tuple_struct_encode_field(
+        &mut self,
+        s: &mut Self::TupleStructEncoder,
+    ) -> anyhow::Result<Self::AnySpecEncoder> {
+        Ok(self.next_element(s))
+    }
+
+    fn tuple_struct_end(&mut self, s: Self::TupleStructEncoder) -> anyhow::Result<()> {
+        self.close_nested(s)
+    }
+
+    fn struct_encode_field(
+        &mut self,
+        s: &mut Self::StructEncoder,
+        field: &'static str,
+    ) -> anyhow::Result<Self::AnySpecEncoder> {
+        Ok(ConfigAnySpecEncoder::Key(child(&s.prefix, field)))
+    }
+
+    fn struct_end(&mut self, s: Self::StructEncoder) -> anyhow::Result<()> {
+        self.close_nested(s)
+    }
+
+    fn tuple_variant_encode_field(
+        &mut self,
+        v: &mut Self::TupleVariantEncoder,
+    ) -> anyhow::Result<Self::AnySpecEncoder> {
+        Ok(self.next_element(v))
+    }
+
+    fn tuple_variant_end(&mut self, v: Self::TupleVariantEncoder) -> anyhow::Result<()> {
+        self.close_nested(v)
+    }
+
+    fn struct_variant_encode_field(
+        &mut self,
+        v: &mut Self::StructVariantEncoder,
+        key: &'static str,
+    ) -> anyhow::Result<Self::AnySpecEncoder> {
+        Ok(ConfigAnySpecEncoder::Key(child(&v.prefix, key)))
+    }
+
+    fn struct_variant_end(&mut self, v: Self::StructVariantEncoder) -> anyhow::Result<()> {
+        self.close_nested(v)
+    }
+
+    fn is_human_readable(&self) -> bool {
+        true
+    }
+}
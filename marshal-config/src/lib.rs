@@ -0,0 +1,18 @@
+#![deny(unused_must_use)]
+#![feature(never_type)]
+#![feature(trait_alias)]
+
+use marshal::de::Deserialize;
+use marshal::ser::Serialize;
+
+use crate::decode::full::ConfigDecoder;
+use crate::encode::full::ConfigEncoder;
+
+pub mod decode;
+pub mod encode;
+pub mod source;
+#[cfg(test)]
+mod test;
+
+pub trait SerializeConfig = Serialize<ConfigEncoder>;
+pub trait DeserializeConfig = Deserialize<ConfigDecoder>;
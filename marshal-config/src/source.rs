@@ -0,0 +1,144 @@
+use marshal::context::Context;
+
+use crate::decode::ConfigDecoderError;
+use crate::encode::full::ConfigEncoderBuilder;
+use crate::SerializeConfig;
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum SourceKind {
+    /// A value flattened by [ConfigEncoderBuilder], such as a config file that was already decoded.
+    /// A `.` or `\` within a map key is escaped with `\`.
+    Value,
+    /// Environment variables such as `APP_DB__HOST`, which are matched case-insensitively.
+    Env,
+    /// Arguments such as `--db.host=x`, where `-` within a key is the same as `_`.
+    Args,
+}
+
+impl SourceKind {
+    /// The separator between the segments of a key.
+    pub fn separator(self) -> &'static str {
+        match self {
+            SourceKind::Value | SourceKind::Args => ".",
+            SourceKind::Env => "__",
+        }
+    }
+    /// Converts a key or field name into the form used for matching.
+    pub fn normalize(self, key: &str) -> String {
+        match self {
+            SourceKind::Value => key.to_string(),
+            SourceKind::Env => key.to_lowercase(),
+            SourceKind::Args => key.replace('-', "_"),
+        }
+    }
+    /// Whether segments are escaped, so that a map key may contain the separator.
+    pub fn escapes_segments(self) -> bool {
+        match self {
+            SourceKind::Value => true,
+            SourceKind::Env | SourceKind::Args => false,
+        }
+    }
+    /// Whether a value read as a sequence is a comma-separated list.
+    pub fn splits_lists(self) -> bool {
+        match self {
+            SourceKind::Value => false,
+            SourceKind::Env | SourceKind::Args => true,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ConfigEntry {
+    pub kind: SourceKind,
+    /// The position of the source, where later sources take precedence.
+    pub layer: usize,
+    /// The normalized key, without any prefix.
+    pub key: String,
+    /// The value, or `None` for an empty sequence or map.
+    pub value: Option<String>,
+    /// A description of where the entry came from, for reporting errors.
+    pub origin: String,
+}
+
+/// Key-value entries from several sources, such as a config file, the environment and the command
+/// line. Keys are not split into fields until decoding, so that they can be resolved against the
+/// fields of the struct being decoded. Sources added later take precedence.
+#[derive(Debug, Default)]
+pub struct ConfigSources {
+    entries: Vec<ConfigEntry>,
+    layers: usize,
+}
+
+impl ConfigSources {
+    pub fn new() -> Self {
+        ConfigSources {
+            entries: vec![],
+            layers: 0,
+        }
+    }
+    pub fn entries(&self) -> &[ConfigEntry] {
+        &self.entries
+    }
+    fn push(&mut self, kind: SourceKind, key: &str, value: Option<String>, origin: String) {
+        self.entries.push(ConfigEntry {
+            kind,
+            layer: self.layers,
+            key: kind.normalize(key),
+            value,
+            origin,
+        });
+    }
+    /// Adds a value, typically the contents of a config file, as the lowest layer so far.
+    pub fn add_value<T: ?Sized + SerializeConfig>(
+        &mut self,
+        value: &T,
+        ctx: Context,
+    ) -> anyhow::Result<&mut Self> {
+        for (key, value) in ConfigEncoderBuilder::new().serialize(value, ctx)? {
+            let origin = format!("config key {:?}", key);
+            self.push(SourceKind::Value, &key, value, origin);
+        }
+        self.layers += 1;
+        Ok(self)
+    }
+    /// Adds the variables that start with `prefix`, with nested fields separated by `__`.
+    pub fn add_env(
+        &mut self,
+        prefix: &str,
+        vars: impl IntoIterator<Item = (String, String)>,
+    ) -> &mut Self {
+        for (name, value) in vars {
+            if let Some(key) = name.strip_prefix(prefix) {
+                let origin = format!("environment variable {:?}", name);
+                self.push(SourceKind::Env, key, Some(value), origin);
+            }
+        }
+        self.layers += 1;
+        self
+    }
+    /// Adds arguments of the form `--key=value`, `--key value` or `--flag`, which is `true`.
+    /// Nested fields are separated by `.`.
+    pub fn add_args(
+        &mut self,
+        args: impl IntoIterator<Item = String>,
+    ) -> Result<&mut Self, ConfigDecoderError> {
+        let mut args = args.into_iter().peekable();
+        while let Some(arg) = args.next() {
+            let key = match arg.strip_prefix("--") {
+                Some(key) if !key.is_empty() => key,
+                _ => return Err(ConfigDecoderError::UnexpectedArgument(arg)),
+            };
+            let (key, value) = match key.split_once('=') {
+                Some((key, value)) => (key, value.to_string()),
+                None => match args.next_if(|next| !next.starts_with("--")) {
+                    Some(value) => (key, value),
+                    None => (key, "true".to_string()),
+                },
+            };
+            let origin = format!("argument \"--{}\"", key);
+            self.push(SourceKind::Args, key, Some(value), origin);
+        }
+        self.layers += 1;
+        Ok(self)
+    }
+}
@@ -0,0 +1,285 @@
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+
+use marshal::context::OwnedContext;
+use marshal_derive::{Deserialize, Serialize};
+
+use crate::decode::full::ConfigDecoderBuilder;
+use crate::encode::full::ConfigEncoderBuilder;
+use crate::source::ConfigSources;
+use crate::DeserializeConfig;
+
+fn env(vars: &[(&str, &str)]) -> Vec<(String, String)> {
+    vars.iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect()
+}
+
+fn args(args: &[&str]) -> Vec<String> {
+    args.iter().map(|x| x.to_string()).collect()
+}
+
+fn decode<T: DeserializeConfig>(sources: &ConfigSources) -> anyhow::Result<T> {
+    let mut c = OwnedContext::new();
+    ConfigDecoderBuilder::new(sources).deserialize(c.borrow())
+}
+
+#[track_caller]
+fn test_error<T: Debug + DeserializeConfig>(sources: &ConfigSources, expected: &str) {
+    let error = decode::<T>(sources).unwrap_err();
+    assert_eq!(format!("{:#}", error), expected);
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+enum Level {
+    Debug,
+    Info,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+struct Database {
+    host: String,
+    port: u16,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+struct Server {
+    name: String,
+    weight: f64,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+struct Config {
+    db: Database,
+    db_host: String,
+    max_conn: u32,
+    verbose: bool,
+    level: Level,
+    tags: Vec<String>,
+    servers: Vec<Server>,
+    labels: BTreeMap<String, String>,
+    #[marshal(default)]
+    timeout: Option<u32>,
+}
+
+fn base() -> Config {
+    Config {
+        db: Database {
+            host: "localhost".to_string(),
+            port: 5432,
+        },
+        db_host: "other".to_string(),
+        max_conn: 10,
+        verbose: false,
+        level: Level::Info,
+        tags: vec![],
+        servers: vec![
+            Server {
+                name: "a".to_string(),
+                weight: 1.0,
+            },
+            Server {
+                name: "b".to_string(),
+                weight: 0.5,
+            },
+        ],
+        labels: BTreeMap::new(),
+        timeout: None,
+    }
+}
+
+#[test]
+fn test_encode() -> anyhow::Result<()> {
+    let mut c = OwnedContext::new();
+    let entries = ConfigEncoderBuilder::new().serialize(&base(), c.borrow())?;
+    let entries: Vec<_> = entries
+        .iter()
+        .map(|(k, v)| (k.as_str(), v.as_deref()))
+        .collect();
+    assert_eq!(
+        entries,
+        vec![
+            ("db.host", Some("localhost")),
+            ("db.port", Some("5432")),
+            ("db_host", Some("other")),
+            ("max_conn", Some("10")),
+            ("verbose", Some("false")),
+            ("level", Some("Info")),
+            ("tags", None),
+            ("servers.0.name", Some("a")),
+            ("servers.0.weight", Some("1")),
+            ("servers.1.name", Some("b")),
+            ("servers.1.weight", Some("0.5")),
+            ("labels", None),
+        ]
+    );
+    let mut sources = ConfigSources::new();
+    sources.add_value(&base(), c.borrow())?;
+    assert_eq!(decode::<Config>(&sources)?, base());
+    Ok(())
+}
+
+#[test]
+fn test_layers() -> anyhow::Result<()> {
+    let mut c = OwnedContext::new();
+    let mut sources = ConfigSources::new();
+    sources
+        .add_value(&base(), c.borrow())?
+        .add_env(
+            "APP_",
+            env(&[
+                ("APP_DB__HOST", "db.internal"),
+                ("APP_DB_HOST", "env"),
+                ("APP_MAX_CONN", "20"),
+                ("APP_TAGS", "x,y"),
+                ("APP_SERVERS__1__WEIGHT", "2"),
+                ("APP_LABELS__TEAM", "core"),
+                ("HOME", "/root"),
+            ]),
+        )
+        .add_args(args(&[
+            "--max-conn=30",
+            "--verbose",
+            "--level",
+            "Debug",
+            "--tags",
+            "z",
+            "--tags",
+            "w",
+            "--timeout=5",
+        ]))?;
+    let mut expected = base();
+    expected.db.host = "db.internal".to_string();
+    expected.db_host = "env".to_string();
+    expected.max_conn = 30;
+    expected.verbose = true;
+    expected.level = Level::Debug;
+    expected.tags = vec!["z".to_string(), "w".to_string()];
+    expected.servers[1].weight = 2.0;
+    expected
+        .labels
+        .insert("team".to_string(), "core".to_string());
+    expected.timeout = Some(5);
+    assert_eq!(decode::<Config>(&sources)?, expected);
+    Ok(())
+}
+
+#[test]
+fn test_dotted_map_keys() -> anyhow::Result<()> {
+    let mut c = OwnedContext::new();
+    let mut config = base();
+    config.labels = BTreeMap::from([
+        ("app.kubernetes.io/name".to_string(), "web".to_string()),
+        ("a\\b".to_string(), "x".to_string()),
+    ]);
+    let entries = ConfigEncoderBuilder::new().serialize(&config.labels, c.borrow())?;
+    assert_eq!(
+        entries,
+        vec![
+            ("a\\\\b".to_string(), Some("x".to_string())),
+            ("app\\.kubernetes\\.io/name".to_string(), Some("web".to_string())),
+        ]
+    );
+    let mut sources = ConfigSources::new();
+    sources
+        .add_value(&config, c.borrow())?
+        .add_args(args(&["--labels.team=core"]))?;
+    config
+        .labels
+        .insert("team".to_string(), "core".to_string());
+    assert_eq!(decode::<Config>(&sources)?, config);
+    Ok(())
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+enum Shape {
+    Point,
+    Circle(f64),
+    Rect { width: u32, height: u32 },
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Shapes {
+    a: Shape,
+    b: Shape,
+    c: Shape,
+    pair: (u8, char),
+}
+
+#[test]
+fn test_enums() -> anyhow::Result<()> {
+    let mut c = OwnedContext::new();
+    let shapes = Shapes {
+        a: Shape::Point,
+        b: Shape::Circle(1.5),
+        c: Shape::Rect {
+            width: 1,
+            height: 2,
+        },
+        pair: (1, 'x'),
+    };
+    let mut sources = ConfigSources::new();
+    sources.add_value(&shapes, c.borrow())?;
+    assert_eq!(decode::<Shapes>(&sources)?, shapes);
+    sources
+        .add_env(
+            "APP_",
+            env(&[("APP_A__CIRCLE", "2"), ("APP_C__RECT__HEIGHT", "3")]),
+        )
+        .add_args(args(&["--b=Point", "--pair=2,y"]))?;
+    assert_eq!(
+        decode::<Shapes>(&sources)?,
+        Shapes {
+            a: Shape::Circle(2.0),
+            b: Shape::Point,
+            c: Shape::Rect {
+                width: 1,
+                height: 3,
+            },
+            pair: (2, 'y'),
+        }
+    );
+    Ok(())
+}
+
+#[test]
+fn test_errors() -> anyhow::Result<()> {
+    let mut c = OwnedContext::new();
+    let error = ConfigSources::new()
+        .add_args(args(&["--a", "b", "c"]))
+        .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "expected an argument starting with \"--\", found \"c\""
+    );
+    let mut sources = ConfigSources::new();
+    sources.add_args(args(&["--host=a", "--port=x"]))?;
+    test_error::<Database>(
+        &sources,
+        "in argument \"--port\": invalid digit found in string",
+    );
+    let mut sources = ConfigSources::new();
+    sources.add_value(&base(), c.borrow())?;
+    sources.add_env("APP_", env(&[("APP_DB", "x")]));
+    test_error::<Config>(
+        &sources,
+        "in environment variable \"APP_DB\": expected nested keys, found a value",
+    );
+    let mut sources = ConfigSources::new();
+    sources.add_env("APP_", env(&[("APP_HOST", "a")]));
+    test_error::<Database>(
+        &sources,
+        "in environment variable \"APP_HOST\": MissingField { field_name: \"port\" }",
+    );
+    let mut sources = ConfigSources::new();
+    sources.add_args(args(&["--first.name=a"]))?;
+    test_error::<Vec<Server>>(
+        &sources,
+        "in argument \"--first.name\": expected a sequence index, found \"first\"",
+    );
+    let error = ConfigEncoderBuilder::new()
+        .serialize(&BTreeMap::from([("".to_string(), 1)]), c.borrow())
+        .unwrap_err();
+    assert_eq!(error.to_string(), "map key \"\" must be a non-empty scalar");
+    Ok(())
+}
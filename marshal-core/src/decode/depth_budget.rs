@@ -28,6 +28,12 @@ impl<T> WithDepthBudget<T> {
     pub fn new(budget: usize, inner: T) -> Self {
         WithDepthBudget { budget, inner }
     }
+    /// Returns the wrapped state. This is public so that format crates can reach their own state
+    /// through the wrapper, e.g. to read a whole sequence at once.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
 }
 
 impl<'de, D: SpecDecoder<'de>> DepthBudgetDecoder<D> {
@@ -89,6 +95,20 @@ impl<'de, D: SpecDecoder<'de>> SpecDecoder<'de> for DepthBudgetDecoder<D> {
         ))
     }
 
+    #[inline]
+    fn decode_tagged_struct(
+        &mut self,
+        any: Self::AnyDecoder,
+        name: &'static str,
+        fields: &'static [&'static str],
+        tags: &'static [u32],
+    ) -> anyhow::Result<SimpleDecoderView<'de, Self>> {
+        Ok(Self::wrap_view(
+            any.budget.checked_sub(1).ok_or(OverflowError)?,
+            self.inner.decode_tagged_struct(any.inner, name, fields, tags)?,
+        ))
+    }
+
     #[inline]
     fn is_human_readable(&self) -> bool {
         self.inner.is_human_readable()
@@ -10,6 +10,7 @@ mod enum_helper;
 pub mod newtype;
 pub mod poison;
 mod struct_helper;
+pub mod text;
 mod tuple_helper;
 // pub mod depth_budget;
 // pub mod poison;
@@ -52,6 +53,24 @@ pub enum DecodeVariantHint {
     Ignore,
 }
 
+impl DecodeVariantHint {
+    /// The hint for the contents of a variant, for formats that write them as an ordinary value.
+    pub fn content_hint(self) -> DecodeHint {
+        match self {
+            DecodeVariantHint::UnitVariant => DecodeHint::Primitive(PrimitiveType::Unit),
+            DecodeVariantHint::TupleVariant { len } => DecodeHint::TupleStruct {
+                name: "<enum>",
+                len,
+            },
+            DecodeVariantHint::StructVariant { fields } => DecodeHint::Struct {
+                name: "<enum>",
+                fields,
+            },
+            DecodeVariantHint::Ignore => DecodeHint::Ignore,
+        }
+    }
+}
+
 pub struct SeqIter<'p, 'de, D: ?Sized + SpecDecoder<'de> + 'p, F> {
     seq: SeqDecoder<'p, 'de, D>,
     map: F,
@@ -179,6 +198,40 @@ impl Primitive {
     }
 }
 
+/// An integer read from a format that does not record its width, which is converted to the
+/// width requested by the hint.
+pub trait WideInt: Copy {
+    /// The type to decode when the hint does not request one.
+    fn natural_type(self) -> PrimitiveType;
+    fn unsigned(self) -> anyhow::Result<u128>;
+    fn signed(self) -> anyhow::Result<i128>;
+    fn float(self) -> f64;
+    fn into_primitive(self, hint: &DecodeHint) -> anyhow::Result<Primitive> {
+        let prim = match hint {
+            DecodeHint::Primitive(prim) => *prim,
+            _ => self.natural_type(),
+        };
+        Ok(match prim {
+            PrimitiveType::I8 => Primitive::I8(self.signed()?.try_into()?),
+            PrimitiveType::I16 => Primitive::I16(self.signed()?.try_into()?),
+            PrimitiveType::I32 => Primitive::I32(self.signed()?.try_into()?),
+            PrimitiveType::I64 => Primitive::I64(self.signed()?.try_into()?),
+            PrimitiveType::I128 => Primitive::I128(self.signed()?),
+            PrimitiveType::U8 => Primitive::U8(self.unsigned()?.try_into()?),
+            PrimitiveType::U16 => Primitive::U16(self.unsigned()?.try_into()?),
+            PrimitiveType::U32 => Primitive::U32(self.unsigned()?.try_into()?),
+            PrimitiveType::U64 => Primitive::U64(self.unsigned()?.try_into()?),
+            PrimitiveType::U128 => Primitive::U128(self.unsigned()?),
+            PrimitiveType::F32 => Primitive::F32(self.float() as f32),
+            PrimitiveType::F64 => Primitive::F64(self.float()),
+            PrimitiveType::Char => Primitive::Char(u32::try_from(self.unsigned()?)?.try_into()?),
+            PrimitiveType::Unit | PrimitiveType::Bool => {
+                return self.into_primitive(&DecodeHint::Any);
+            }
+        })
+    }
+}
+
 pub enum SimpleDecoderView<'de, P: ?Sized + SpecDecoder<'de>> {
     Primitive(Primitive),
     String(Cow<'de, str>),
@@ -225,6 +278,17 @@ pub trait SpecDecoder<'de> {
         any: Self::AnyDecoder,
         hint: DecodeHint,
     ) -> anyhow::Result<SimpleDecoderView<'de, Self>>;
+    /// Decodes a struct whose fields are numbered by `#[marshal(tag = N)]`. Formats without field
+    /// numbers decode it as an ordinary struct.
+    fn decode_tagged_struct(
+        &mut self,
+        any: Self::AnyDecoder,
+        name: &'static str,
+        fields: &'static [&'static str],
+        _tags: &'static [u32],
+    ) -> anyhow::Result<SimpleDecoderView<'de, Self>> {
+        self.decode(any, DecodeHint::Struct { name, fields })
+    }
     fn is_human_readable(&self) -> bool;
 
     fn decode_seq_next(
@@ -323,6 +387,18 @@ impl<'p, 'de, D: ?Sized + SpecDecoder<'de>> AnySpecDecoder<'p, 'de, D> {
         Ok(self.this.decode(self.any, hint)?.wrap(self.this))
     }
     #[inline]
+    pub fn decode_tagged_struct(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        tags: &'static [u32],
+    ) -> anyhow::Result<DecoderView<'p, 'de, D>> {
+        Ok(self
+            .this
+            .decode_tagged_struct(self.any, name, fields, tags)?
+            .wrap(self.this))
+    }
+    #[inline]
     pub fn ignore(self) -> anyhow::Result<()> {
         self.decode(DecodeHint::Ignore)?.ignore()
     }
@@ -353,6 +429,12 @@ impl<'p, 'de, D: ?Sized + SpecDecoder<'de>> SeqDecoder<'p, 'de, D> {
     pub fn exact_size(&self) -> Option<usize> {
         self.this.decode_seq_exact_size(self.seq.as_ref().unwrap())
     }
+    /// Returns the decoder and the state of the sequence, for formats that read several elements
+    /// at once (e.g. packed sequences in marshal-bin). Returns `None` once the sequence has ended.
+    #[inline]
+    pub fn raw_mut(&mut self) -> Option<(&mut D, &mut D::SeqDecoder)> {
+        Some((&mut *self.this, self.seq.as_mut()?))
+    }
     #[inline]
     pub fn ignore(mut self) -> anyhow::Result<()> {
         while let Some(next) = self.decode_next()? {
@@ -493,6 +575,8 @@ where
     pub fn ignore(mut self) -> anyhow::Result<()> {
         self.decode_discriminant()?.ignore()?;
         self.decode_variant(DecodeVariantHint::Ignore)?.ignore()?;
+        // Otherwise the decoder is left inside the enum, which `PoisonDecoder` rejects.
+        self.decode_end()?;
         Ok(())
     }
 }
@@ -525,6 +609,10 @@ impl<'p, 'de, T: ?Sized + SpecDecoder<'de>> AnySpecDecoder<'p, 'de, T> {
     pub fn new(decoder: &'p mut T, any: T::AnyDecoder) -> Self {
         AnySpecDecoder { this: decoder, any }
     }
+    #[inline]
+    pub fn into_raw(self) -> (&'p mut T, T::AnyDecoder) {
+        (self.this, self.any)
+    }
 }
 
 impl<'p, 'de, T: ?Sized + SpecDecoder<'de>> EntryDecoder<'p, 'de, T> {
@@ -54,6 +54,18 @@ macro_rules! derive_decoder_for_newtype {
                     Ok(cast_simple_decoder_view(self.0.decode(any, hint)?))
                 }
                 #[inline]
+                fn decode_tagged_struct(
+                    &mut self,
+                    any: Self::AnyDecoder,
+                    name: &'static str,
+                    fields: &'static [&'static str],
+                    tags: &'static [u32],
+                ) -> anyhow::Result<SimpleDecoderView<'de, Self>> {
+                    Ok(cast_simple_decoder_view(
+                        self.0.decode_tagged_struct(any, name, fields, tags)?,
+                    ))
+                }
+                #[inline]
                 fn is_human_readable(&self) -> bool {
                     self.0.is_human_readable()
                 }
@@ -23,6 +23,15 @@ impl Display for PoisonError {
 }
 impl std::error::Error for PoisonError {}
 
+impl<T> PoisonWrapper<T> {
+    /// Returns the wrapped state. This is public so that format crates can reach their own state
+    /// through the wrapper, e.g. to read a whole sequence at once.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+}
+
 impl<'de, D: SpecDecoder<'de>> PoisonDecoder<D> {
     #[inline]
     pub fn new(inner: D) -> Self {
@@ -111,6 +120,19 @@ impl<'de, D: SpecDecoder<'de>> SpecDecoder<'de> for PoisonDecoder<D> {
         Ok(self.wrap_view(decoder))
     }
 
+    #[inline]
+    fn decode_tagged_struct(
+        &mut self,
+        any: Self::AnyDecoder,
+        name: &'static str,
+        fields: &'static [&'static str],
+        tags: &'static [u32],
+    ) -> anyhow::Result<SimpleDecoderView<'de, Self>> {
+        let any = self.pop(any)?;
+        let decoder = self.inner.decode_tagged_struct(any, name, fields, tags)?;
+        Ok(self.wrap_view(decoder))
+    }
+
     #[inline]
     fn is_human_readable(&self) -> bool {
         self.inner.is_human_readable()
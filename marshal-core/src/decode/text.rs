@@ -0,0 +1,56 @@
+use crate::decode::TypeMismatch;
+use crate::{Primitive, PrimitiveType};
+
+/// The [SpecDecoder::AnyDecoder](crate::decode::SpecDecoder::AnyDecoder) of a format that parses
+/// its whole input before decoding it.
+pub enum DocumentAnyDecoder<T> {
+    /// The whole document, which is parsed when it is first decoded.
+    Document,
+    Parsed(T),
+}
+
+impl<T> From<T> for DocumentAnyDecoder<T> {
+    fn from(value: T) -> Self {
+        DocumentAnyDecoder::Parsed(value)
+    }
+}
+
+/// Formats the one-based line and column of a byte offset.
+pub fn location(input: &str, offset: usize) -> String {
+    let consumed = &input[..offset.min(input.len())];
+    let line = consumed.matches('\n').count() + 1;
+    let column = consumed[consumed.rfind('\n').map_or(0, |x| x + 1)..]
+        .chars()
+        .count()
+        + 1;
+    format!("at line {} column {}", line, column)
+}
+
+/// Parses a primitive from its text, for formats in which every value is a string. Unit is the
+/// empty string.
+pub fn parse_primitive(text: &str, prim: PrimitiveType) -> anyhow::Result<Primitive> {
+    Ok(match prim {
+        PrimitiveType::Unit if text.is_empty() => Primitive::Unit,
+        PrimitiveType::Unit => {
+            return Err(TypeMismatch {
+                found: "string",
+                expected: "unit",
+            }
+            .into())
+        }
+        PrimitiveType::Bool => Primitive::Bool(text.parse()?),
+        PrimitiveType::I8 => Primitive::I8(text.parse()?),
+        PrimitiveType::I16 => Primitive::I16(text.parse()?),
+        PrimitiveType::I32 => Primitive::I32(text.parse()?),
+        PrimitiveType::I64 => Primitive::I64(text.parse()?),
+        PrimitiveType::I128 => Primitive::I128(text.parse()?),
+        PrimitiveType::U8 => Primitive::U8(text.parse()?),
+        PrimitiveType::U16 => Primitive::U16(text.parse()?),
+        PrimitiveType::U32 => Primitive::U32(text.parse()?),
+        PrimitiveType::U64 => Primitive::U64(text.parse()?),
+        PrimitiveType::U128 => Primitive::U128(text.parse()?),
+        PrimitiveType::F32 => Primitive::F32(text.parse()?),
+        PrimitiveType::F64 => Primitive::F64(text.parse()?),
+        PrimitiveType::Char => Primitive::Char(text.parse()?),
+    })
+}
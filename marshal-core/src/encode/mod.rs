@@ -46,6 +46,17 @@ pub trait SpecEncoder {
         name: &'static str,
         fields: &'static [&'static str],
     ) -> anyhow::Result<Self::StructEncoder>;
+    /// Encodes a struct whose fields are numbered by `#[marshal(tag = N)]`. Formats without field
+    /// numbers encode it as an ordinary struct.
+    fn encode_tagged_struct(
+        &mut self,
+        any: Self::AnySpecEncoder,
+        name: &'static str,
+        fields: &'static [&'static str],
+        _tags: &'static [u32],
+    ) -> anyhow::Result<Self::StructEncoder> {
+        self.encode_struct(any, name, fields)
+    }
     fn encode_unit_variant(
         &mut self,
         any: Self::AnySpecEncoder,
@@ -228,6 +239,23 @@ impl<'w, T: SpecEncoder> AnySpecEncoder<'w, T> {
         })
     }
 
+    #[inline]
+    pub fn encode_tagged_struct(
+        mut self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        tags: &'static [u32],
+    ) -> anyhow::Result<StructEncoder<'w, T>> {
+        let inner = self
+            .encoder
+            .encode_tagged_struct(self.inner, name, fields, tags)?;
+        Ok(StructEncoder {
+            encoder: self.encoder,
+            fields: fields.iter(),
+            inner,
+        })
+    }
+
     #[inline]
     pub fn encode_unit_variant(
         mut self,
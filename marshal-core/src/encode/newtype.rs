@@ -74,6 +74,17 @@ macro_rules! derive_encoder_for_newtype {
                     self.0.encode_struct(any, name, fields)
                 }
 
+                #[inline]
+                fn encode_tagged_struct(
+                    &mut self,
+                    any: Self::AnySpecEncoder,
+                    name: &'static str,
+                    fields: &'static [&'static str],
+                    tags: &'static [u32],
+                ) -> anyhow::Result<Self::StructEncoder> {
+                    self.0.encode_tagged_struct(any, name, fields, tags)
+                }
+
                 #[inline]
                 fn encode_unit_variant(
                     &mut self,
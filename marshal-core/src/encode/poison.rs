@@ -61,6 +61,14 @@ impl<E: SpecEncoder> PoisonEncoder<E> {
             Err(PoisonError::UnexpectedEncodeState.into())
         }
     }
+    #[inline]
+    pub fn inner(&self) -> &E {
+        &self.inner
+    }
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut E {
+        &mut self.inner
+    }
 }
 
 pub struct PoisonWrapper<T> {
@@ -151,6 +159,19 @@ impl<E: SpecEncoder> SpecEncoder for PoisonEncoder<E> {
         Ok(self.push(struct_encoder))
     }
 
+    #[inline]
+    fn encode_tagged_struct(
+        &mut self,
+        any: Self::AnySpecEncoder,
+        name: &'static str,
+        fields: &'static [&'static str],
+        tags: &'static [u32],
+    ) -> anyhow::Result<Self::StructEncoder> {
+        let any = self.pop(any)?;
+        let struct_encoder = self.inner.encode_tagged_struct(any, name, fields, tags)?;
+        Ok(self.push(struct_encoder))
+    }
+
     #[inline]
     fn encode_unit_variant(
         &mut self,
@@ -11,6 +11,10 @@ use std::fmt::{Debug, Display, Formatter};
 pub mod decode;
 pub mod encode;
 
+/// The struct name of the `{ id, inner }` records written by `marshal-shared`, which formats with
+/// their own references, such as YAML anchors, may recognize.
+pub const SHARED_STRUCT_NAME: &str = "marshal_shared::Shared";
+
 #[derive(Debug)]
 pub enum Primitive {
     Unit,
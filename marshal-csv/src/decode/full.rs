@@ -0,0 +1,66 @@
+use marshal::context::Context;
+use marshal::de::Deserialize;
+use marshal_core::decode::depth_budget::{DepthBudgetDecoder, WithDepthBudget};
+use marshal_core::decode::poison::PoisonDecoder;
+use marshal_core::decode::text::DocumentAnyDecoder;
+use marshal_core::decode::{AnySpecDecoder, Decoder};
+use marshal_core::derive_decoder_for_newtype;
+
+use crate::decode::SimpleCsvSpecDecoder;
+
+pub struct CsvSpecDecoder<'de>(PoisonDecoder<DepthBudgetDecoder<SimpleCsvSpecDecoder<'de>>>);
+
+derive_decoder_for_newtype!(CsvSpecDecoder<'de>(PoisonDecoder<DepthBudgetDecoder<SimpleCsvSpecDecoder<'de>>>));
+
+/// Reads CSV with a header into a sequence of structs. Columns are matched to fields by name, and
+/// an unquoted empty cell is read as `None`.
+pub struct CsvDecoderBuilder<'de> {
+    inner: CsvSpecDecoder<'de>,
+    depth_budget: usize,
+}
+
+impl<'de> CsvDecoderBuilder<'de> {
+    pub fn new(input: &'de str) -> Self {
+        CsvDecoderBuilder {
+            inner: CsvSpecDecoder(PoisonDecoder::new(DepthBudgetDecoder::new(
+                SimpleCsvSpecDecoder::new(input),
+            ))),
+            depth_budget: 100,
+        }
+    }
+    pub fn set_delimiter(&mut self, delimiter: char) -> &mut Self {
+        self.inner
+            .0
+            .inner_mut()
+            .inner_mut()
+            .set_delimiter(delimiter);
+        self
+    }
+    pub fn set_budget(&mut self, depth_budget: usize) -> &mut Self {
+        self.depth_budget = depth_budget;
+        self
+    }
+    pub fn build<'p>(&'p mut self) -> AnySpecDecoder<'p, 'de, CsvSpecDecoder<'de>> {
+        let any = WithDepthBudget::new(self.depth_budget, DocumentAnyDecoder::Document);
+        let any = self.inner.0.start(any);
+        AnySpecDecoder::new(&mut self.inner, any)
+    }
+    /// Deserializes the document, reporting the line and column of the cell that failed.
+    pub fn deserialize<T: Deserialize<CsvDecoder>>(mut self, ctx: Context) -> anyhow::Result<T> {
+        let result = T::deserialize(self.build(), ctx).map_err(|e| e.context(self.location()))?;
+        self.end()?;
+        Ok(result)
+    }
+    pub fn location(&self) -> String {
+        self.inner.0.inner().inner().location()
+    }
+    pub fn end(self) -> anyhow::Result<()> {
+        Ok(self.inner.0.end()?.end()?.end()?)
+    }
+}
+
+pub struct CsvDecoder;
+
+impl Decoder for CsvDecoder {
+    type SpecDecoder<'de> = CsvSpecDecoder<'de>;
+}
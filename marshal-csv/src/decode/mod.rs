@@ -0,0 +1,268 @@
+use std::borrow::Cow;
+use std::fmt::{Display, Formatter};
+
+use marshal_core::decode::text::{location, parse_primitive, DocumentAnyDecoder};
+use marshal_core::decode::{DecodeHint, DecodeVariantHint, SimpleDecoderView, SpecDecoder};
+use marshal_core::{Primitive, PrimitiveType};
+
+use crate::decode::parse::{CsvCell, CsvParser};
+
+pub mod full;
+mod parse;
+
+#[derive(Debug)]
+pub enum CsvDecoderError {
+    UnexpectedCharacter(char),
+    UnterminatedQuote,
+    BareQuote,
+    RowLength { expected: usize, found: usize },
+    NotAStruct,
+    Nested(String),
+    ExpectedEmpty,
+}
+
+impl Display for CsvDecoderError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CsvDecoderError::UnexpectedCharacter(c) => {
+                write!(f, "unexpected character {:?} after a quoted cell", c)
+            }
+            CsvDecoderError::UnterminatedQuote => write!(f, "quoted cell is not terminated"),
+            CsvDecoderError::BareQuote => write!(f, "quote within an unquoted cell"),
+            CsvDecoderError::RowLength { expected, found } => write!(
+                f,
+                "row has {} cells, but the header has {}",
+                found, expected
+            ),
+            CsvDecoderError::NotAStruct => write!(f, "each CSV row must be decoded as a struct"),
+            CsvDecoderError::Nested(field) => {
+                write!(
+                    f,
+                    "field {:?} is nested, but CSV cells must be scalars",
+                    field
+                )
+            }
+            CsvDecoderError::ExpectedEmpty => write!(f, "expected an empty cell"),
+        }
+    }
+}
+
+impl std::error::Error for CsvDecoderError {}
+
+pub struct SimpleCsvSpecDecoder<'de> {
+    input: &'de str,
+    delimiter: char,
+    header: Vec<CsvCell>,
+    /// The offset of the most recent cell, for reporting errors.
+    offset: usize,
+}
+
+pub type CsvAnyDecoder = DocumentAnyDecoder<CsvItem>;
+
+pub enum CsvItem {
+    Row(Vec<CsvCell>),
+    Cell(CsvCell),
+}
+
+pub enum CsvSeqDecoder {
+    Rows(std::vec::IntoIter<Vec<CsvCell>>),
+    Cells(std::vec::IntoIter<CsvCell>),
+}
+
+impl<'de> SimpleCsvSpecDecoder<'de> {
+    pub fn new(input: &'de str) -> Self {
+        SimpleCsvSpecDecoder {
+            input,
+            delimiter: ',',
+            header: vec![],
+            offset: 0,
+        }
+    }
+    pub fn set_delimiter(&mut self, delimiter: char) {
+        self.delimiter = delimiter;
+    }
+    pub fn end(self) -> anyhow::Result<()> {
+        Ok(())
+    }
+    pub fn location(&self) -> String {
+        location(self.input, self.offset)
+    }
+    /// Reads the header and the rows that follow it.
+    fn read_rows(&mut self) -> anyhow::Result<Vec<Vec<CsvCell>>> {
+        let mut parser = CsvParser::new(self.input, self.delimiter);
+        let mut records = match parser.read_records() {
+            Ok(records) => records.into_iter(),
+            Err(e) => {
+                self.offset = parser.offset;
+                return Err(e.into());
+            }
+        };
+        self.header = records.next().unwrap_or_default();
+        let rows: Vec<_> = records.collect();
+        for row in &rows {
+            if row.len() != self.header.len() {
+                self.offset = row[0].offset;
+                return Err(CsvDecoderError::RowLength {
+                    expected: self.header.len(),
+                    found: row.len(),
+                }
+                .into());
+            }
+        }
+        Ok(rows)
+    }
+    fn nested(&self, cell: &CsvCell) -> anyhow::Error {
+        CsvDecoderError::Nested(self.header[cell.column].text.clone()).into()
+    }
+}
+
+impl<'de> SpecDecoder<'de> for SimpleCsvSpecDecoder<'de> {
+    type AnyDecoder = CsvAnyDecoder;
+    type SeqDecoder = CsvSeqDecoder;
+    type MapDecoder = std::vec::IntoIter<CsvCell>;
+    type KeyDecoder = CsvCell;
+    type ValueDecoder = CsvCell;
+    type DiscriminantDecoder = CsvCell;
+    type VariantDecoder = ();
+    type EnumCloser = ();
+    type SomeDecoder = CsvCell;
+    type SomeCloser = ();
+
+    fn decode(
+        &mut self,
+        any: Self::AnyDecoder,
+        hint: DecodeHint,
+    ) -> anyhow::Result<SimpleDecoderView<'de, Self>> {
+        let cell = match any {
+            DocumentAnyDecoder::Document => {
+                let rows = self.read_rows()?;
+                return Ok(SimpleDecoderView::Seq(CsvSeqDecoder::Rows(
+                    rows.into_iter(),
+                )));
+            }
+            DocumentAnyDecoder::Parsed(CsvItem::Row(cells)) => {
+                if let Some(cell) = cells.first() {
+                    self.offset = cell.offset;
+                }
+                return Ok(match hint {
+                    DecodeHint::Struct { .. }
+                    | DecodeHint::Map
+                    | DecodeHint::Any
+                    | DecodeHint::Ignore => SimpleDecoderView::Map(cells.into_iter()),
+                    DecodeHint::Seq | DecodeHint::Tuple { .. } | DecodeHint::TupleStruct { .. } => {
+                        SimpleDecoderView::Seq(CsvSeqDecoder::Cells(cells.into_iter()))
+                    }
+                    _ => return Err(CsvDecoderError::NotAStruct.into()),
+                });
+            }
+            DocumentAnyDecoder::Parsed(CsvItem::Cell(cell)) => cell,
+        };
+        self.offset = cell.offset;
+        Ok(match hint {
+            DecodeHint::Option if cell.text.is_empty() && !cell.quoted => SimpleDecoderView::None,
+            DecodeHint::Option => SimpleDecoderView::Some(cell),
+            DecodeHint::Primitive(PrimitiveType::Unit) | DecodeHint::UnitStruct { .. } => {
+                if !cell.text.is_empty() {
+                    return Err(CsvDecoderError::ExpectedEmpty.into());
+                }
+                SimpleDecoderView::Primitive(Primitive::Unit)
+            }
+            DecodeHint::Primitive(prim) => {
+                SimpleDecoderView::Primitive(parse_primitive(&cell.text, prim)?)
+            }
+            DecodeHint::Enum { .. } => SimpleDecoderView::Enum(cell),
+            DecodeHint::Bytes
+            | DecodeHint::Seq
+            | DecodeHint::Tuple { .. }
+            | DecodeHint::TupleStruct { .. }
+            | DecodeHint::Map
+            | DecodeHint::Struct { .. } => return Err(self.nested(&cell)),
+            _ => SimpleDecoderView::String(Cow::Owned(cell.text)),
+        })
+    }
+
+    fn is_human_readable(&self) -> bool {
+        true
+    }
+
+    fn decode_seq_next(
+        &mut self,
+        seq: &mut Self::SeqDecoder,
+    ) -> anyhow::Result<Option<Self::AnyDecoder>> {
+        Ok(match seq {
+            CsvSeqDecoder::Rows(rows) => rows.next().map(|x| CsvItem::Row(x).into()),
+            CsvSeqDecoder::Cells(cells) => cells.next().map(|x| CsvItem::Cell(x).into()),
+        })
+    }
+
+    fn decode_seq_exact_size(&self, seq: &Self::SeqDecoder) -> Option<usize> {
+        match seq {
+            CsvSeqDecoder::Rows(rows) => Some(rows.len()),
+            CsvSeqDecoder::Cells(cells) => Some(cells.len()),
+        }
+    }
+
+    fn decode_seq_end(&mut self, _seq: Self::SeqDecoder) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn decode_map_next(
+        &mut self,
+        map: &mut Self::MapDecoder,
+    ) -> anyhow::Result<Option<Self::KeyDecoder>> {
+        Ok(map.next())
+    }
+
+    fn decode_map_exact_size(&self, map: &Self::MapDecoder) -> Option<usize> {
+        Some(map.len())
+    }
+
+    fn decode_map_end(&mut self, _map: Self::MapDecoder) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn decode_entry_key(
+        &mut self,
+        cell: Self::KeyDecoder,
+    ) -> anyhow::Result<(Self::AnyDecoder, Self::ValueDecoder)> {
+        let key = self.header[cell.column].clone();
+        Ok((CsvItem::Cell(key).into(), cell))
+    }
+
+    fn decode_entry_value(
+        &mut self,
+        value: Self::ValueDecoder,
+    ) -> anyhow::Result<Self::AnyDecoder> {
+        Ok(CsvItem::Cell(value).into())
+    }
+
+    fn decode_enum_discriminant(
+        &mut self,
+        e: Self::DiscriminantDecoder,
+    ) -> anyhow::Result<(Self::AnyDecoder, Self::VariantDecoder)> {
+        Ok((CsvItem::Cell(e).into(), ()))
+    }
+
+    fn decode_enum_variant(
+        &mut self,
+        _e: Self::VariantDecoder,
+        _hint: DecodeVariantHint,
+    ) -> anyhow::Result<(SimpleDecoderView<'de, Self>, Self::EnumCloser)> {
+        Ok((SimpleDecoderView::Primitive(Primitive::Unit), ()))
+    }
+
+    fn decode_enum_end(&mut self, _e: Self::EnumCloser) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn decode_some_inner(
+        &mut self,
+        e: Self::SomeDecoder,
+    ) -> anyhow::Result<(Self::AnyDecoder, Self::SomeCloser)> {
+        Ok((CsvItem::Cell(e).into(), ()))
+    }
+
+    fn decode_some_end(&mut self, _p: Self::SomeCloser) -> anyhow::Result<()> {
+        Ok(())
+    }
+}
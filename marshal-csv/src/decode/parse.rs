@@ -0,0 +1,115 @@
+use crate::decode::CsvDecoderError;
+
+#[derive(Clone)]
+pub struct CsvCell {
+    pub(crate) text: String,
+    /// Whether the cell was quoted, which keeps `""` distinct from an empty cell.
+    pub(crate) quoted: bool,
+    /// The index of the cell within its record.
+    pub(crate) column: usize,
+    pub(crate) offset: usize,
+}
+
+pub(crate) struct CsvParser<'de> {
+    input: &'de str,
+    delimiter: char,
+    pub(crate) offset: usize,
+}
+
+impl<'de> CsvParser<'de> {
+    pub fn new(input: &'de str, delimiter: char) -> Self {
+        CsvParser {
+            input,
+            delimiter,
+            offset: 0,
+        }
+    }
+    fn peek(&self) -> Option<char> {
+        self.input[self.offset..].chars().next()
+    }
+    fn try_read_newline(&mut self) -> bool {
+        let rest = &self.input[self.offset..];
+        let len = if rest.starts_with("\r\n") {
+            2
+        } else if rest.starts_with(['\r', '\n']) {
+            1
+        } else {
+            return false;
+        };
+        self.offset += len;
+        true
+    }
+    fn read_quoted(&mut self) -> Result<String, CsvDecoderError> {
+        let start = self.offset;
+        self.offset += 1;
+        let mut text = String::new();
+        loop {
+            let rest = &self.input[self.offset..];
+            let Some(end) = rest.find('"') else {
+                self.offset = start;
+                return Err(CsvDecoderError::UnterminatedQuote);
+            };
+            text.push_str(&rest[..end]);
+            self.offset += end + 1;
+            if self.peek() == Some('"') {
+                text.push('"');
+                self.offset += 1;
+            } else {
+                return Ok(text);
+            }
+        }
+    }
+    fn read_cell(&mut self, column: usize) -> Result<CsvCell, CsvDecoderError> {
+        let offset = self.offset;
+        if self.peek() == Some('"') {
+            let text = self.read_quoted()?;
+            return Ok(CsvCell {
+                text,
+                quoted: true,
+                column,
+                offset,
+            });
+        }
+        let rest = &self.input[self.offset..];
+        let end = rest
+            .find([self.delimiter, '\r', '\n'])
+            .unwrap_or(rest.len());
+        let text = &rest[..end];
+        if let Some(quote) = text.find('"') {
+            self.offset += quote;
+            return Err(CsvDecoderError::BareQuote);
+        }
+        self.offset += end;
+        Ok(CsvCell {
+            text: text.to_string(),
+            quoted: false,
+            column,
+            offset,
+        })
+    }
+    fn read_record(&mut self) -> Result<Vec<CsvCell>, CsvDecoderError> {
+        let mut record = vec![];
+        loop {
+            record.push(self.read_cell(record.len())?);
+            match self.peek() {
+                Some(c) if c == self.delimiter => self.offset += c.len_utf8(),
+                None => return Ok(record),
+                Some('\r' | '\n') => {
+                    self.try_read_newline();
+                    return Ok(record);
+                }
+                Some(c) => return Err(CsvDecoderError::UnexpectedCharacter(c)),
+            }
+        }
+    }
+    /// Reads every record, skipping blank lines.
+    pub fn read_records(&mut self) -> Result<Vec<Vec<CsvCell>>, CsvDecoderError> {
+        let mut records = vec![];
+        while self.offset < self.input.len() {
+            if !self.try_read_newline() {
+                records.push(self.read_record()?);
+            }
+        }
+        Ok(records)
+    }
+}
@@ -0,0 +1,66 @@
+use marshal::context::Context;
+use marshal::ser::Serialize;
+use marshal_core::derive_encoder_for_newtype;
+use marshal_core::encode::poison::PoisonEncoder;
+use marshal_core::encode::{AnySpecEncoder, Encoder};
+
+use crate::encode::{CsvAnySpecEncoder, SimpleCsvSpecEncoder};
+
+pub struct CsvSpecEncoder(PoisonEncoder<SimpleCsvSpecEncoder>);
+
+derive_encoder_for_newtype!(CsvSpecEncoder(PoisonEncoder<SimpleCsvSpecEncoder>));
+
+/// Writes a sequence of flat structs as CSV, with a header of the field names. `None` is written
+/// as an empty cell and an empty string as `""`.
+pub struct CsvEncoderBuilder {
+    inner: CsvSpecEncoder,
+}
+
+impl CsvEncoderBuilder {
+    pub fn new() -> Self {
+        CsvEncoderBuilder {
+            inner: CsvSpecEncoder(PoisonEncoder::new(SimpleCsvSpecEncoder::new())),
+        }
+    }
+    pub fn set_delimiter(&mut self, delimiter: char) -> &mut Self {
+        self.inner.0.inner_mut().set_delimiter(delimiter);
+        self
+    }
+    /// Encodes a sequence of rows.
+    pub fn build(&mut self) -> AnySpecEncoder<'_, CsvSpecEncoder> {
+        let any = self.inner.0.start(CsvAnySpecEncoder::Document);
+        AnySpecEncoder::new(&mut self.inner, any)
+    }
+    /// Encodes a single row, so that rows can be written as they are produced.
+    pub fn build_row(&mut self) -> AnySpecEncoder<'_, CsvSpecEncoder> {
+        let any = self.inner.0.start(CsvAnySpecEncoder::Row);
+        AnySpecEncoder::new(&mut self.inner, any)
+    }
+    pub fn end(self) -> anyhow::Result<String> {
+        self.inner.0.end()?.end()
+    }
+    pub fn serialize<T: ?Sized + Serialize<CsvEncoder>>(
+        mut self,
+        value: &T,
+        ctx: Context,
+    ) -> anyhow::Result<String> {
+        value.serialize(self.build(), ctx)?;
+        self.end()
+    }
+    pub fn serialize_iter<T: Serialize<CsvEncoder>>(
+        mut self,
+        rows: impl IntoIterator<Item = T>,
+        mut ctx: Context,
+    ) -> anyhow::Result<String> {
+        for row in rows {
+            row.serialize(self.build_row(), ctx.reborrow())?;
+        }
+        self.end()
+    }
+}
+
+pub struct CsvEncoder;
+
+impl Encoder for CsvEncoder {
+    type SpecEncoder<'en> = CsvSpecEncoder;
+}
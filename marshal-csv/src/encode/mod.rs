@@ -0,0 +1,378 @@
+use std::fmt::{Display, Formatter};
+
+use marshal_core::encode::SpecEncoder;
+use marshal_core::Primitive;
+
+pub mod full;
+
+#[derive(Debug)]
+pub enum CsvEncoderError {
+    NotASequence,
+    NotAStruct,
+    Nested(&'static str),
+    HeaderMismatch,
+    UnknownField(&'static str),
+}
+
+impl Display for CsvEncoderError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CsvEncoderError::NotASequence => write!(f, "a CSV document must be a sequence of rows"),
+            CsvEncoderError::NotAStruct => write!(f, "each CSV row must be a struct"),
+            CsvEncoderError::Nested(field) => {
+                write!(
+                    f,
+                    "field {:?} is nested, but CSV cells must be scalars",
+                    field
+                )
+            }
+            CsvEncoderError::HeaderMismatch => {
+                write!(f, "every row must be a struct with the same fields")
+            }
+            CsvEncoderError::UnknownField(field) => {
+                write!(f, "field {:?} is not in the header", field)
+            }
+        }
+    }
+}
+
+impl std::error::Error for CsvEncoderError {}
+
+#[derive(Copy, Clone)]
+pub enum CsvAnySpecEncoder {
+    Document,
+    Row,
+    /// The cell of a row at a column of the header.
+    Cell(usize),
+}
+
+pub struct SimpleCsvSpecEncoder {
+    output: String,
+    delimiter: char,
+    /// The fields of the first row, which are written as the header.
+    header: Option<&'static [&'static str]>,
+    /// The escaped cells of the current row.
+    row: Vec<String>,
+}
+
+impl SimpleCsvSpecEncoder {
+    pub fn new() -> Self {
+        SimpleCsvSpecEncoder {
+            output: String::new(),
+            delimiter: ',',
+            header: None,
+            row: vec![],
+        }
+    }
+    pub fn set_delimiter(&mut self, delimiter: char) {
+        self.delimiter = delimiter;
+    }
+    pub fn end(self) -> anyhow::Result<String> {
+        Ok(self.output)
+    }
+    /// Quotes a cell if it holds the delimiter, a quote or a line break. Empty strings are quoted
+    /// so that they stay distinct from `None`.
+    fn escape(&self, cell: &str) -> String {
+        if cell.is_empty() {
+            return "\"\"".to_string();
+        }
+        if cell.contains([self.delimiter, '"', '\n', '\r']) {
+            format!("\"{}\"", cell.replace('"', "\"\""))
+        } else {
+            cell.to_string()
+        }
+    }
+    fn write_line<'a>(&mut self, cells: impl Iterator<Item = &'a str>) {
+        for (index, cell) in cells.enumerate() {
+            if index > 0 {
+                self.output.push(self.delimiter);
+            }
+            self.output.push_str(cell);
+        }
+        self.output.push('\n');
+    }
+    fn write_cell(&mut self, any: CsvAnySpecEncoder, cell: String) -> anyhow::Result<()> {
+        match any {
+            CsvAnySpecEncoder::Document => Err(CsvEncoderError::NotASequence.into()),
+            CsvAnySpecEncoder::Row => Err(CsvEncoderError::NotAStruct.into()),
+            CsvAnySpecEncoder::Cell(column) => {
+                self.row[column] = cell;
+                Ok(())
+            }
+        }
+    }
+    fn write_str(&mut self, any: CsvAnySpecEncoder, s: &str) -> anyhow::Result<()> {
+        let cell = self.escape(s);
+        self.write_cell(any, cell)
+    }
+    fn nested(&self, any: CsvAnySpecEncoder) -> anyhow::Error {
+        match any {
+            CsvAnySpecEncoder::Document => CsvEncoderError::NotASequence.into(),
+            CsvAnySpecEncoder::Row => CsvEncoderError::NotAStruct.into(),
+            CsvAnySpecEncoder::Cell(column) => {
+                CsvEncoderError::Nested(self.header.unwrap()[column]).into()
+            }
+        }
+    }
+    fn open_rows(&mut self, any: CsvAnySpecEncoder) -> anyhow::Result<()> {
+        match any {
+            CsvAnySpecEncoder::Document => Ok(()),
+            _ => Err(self.nested(any)),
+        }
+    }
+}
+
+impl SpecEncoder for SimpleCsvSpecEncoder {
+    type AnySpecEncoder = CsvAnySpecEncoder;
+    type SomeCloser = ();
+    type TupleEncoder = ();
+    type SeqEncoder = ();
+    type MapEncoder = !;
+    type ValueEncoder = !;
+    type EntryCloser = !;
+    type TupleStructEncoder = !;
+    type StructEncoder = ();
+    type TupleVariantEncoder = !;
+    type StructVariantEncoder = !;
+
+    fn encode_prim(&mut self, any: Self::AnySpecEncoder, prim: Primitive) -> anyhow::Result<()> {
+        let cell = match prim {
+            Primitive::Unit => String::new(),
+            Primitive::Bool(x) => x.to_string(),
+            Primitive::I8(x) => x.to_string(),
+            Primitive::I16(x) => x.to_string(),
+            Primitive::I32(x) => x.to_string(),
+            Primitive::I64(x) => x.to_string(),
+            Primitive::I128(x) => x.to_string(),
+            Primitive::U8(x) => x.to_string(),
+            Primitive::U16(x) => x.to_string(),
+            Primitive::U32(x) => x.to_string(),
+            Primitive::U64(x) => x.to_string(),
+            Primitive::U128(x) => x.to_string(),
+            Primitive::F32(x) => x.to_string(),
+            Primitive::F64(x) => x.to_string(),
+            Primitive::Char(x) => return self.write_str(any, x.encode_utf8(&mut [0; 4])),
+        };
+        self.write_cell(any, cell)
+    }
+
+    fn encode_str(&mut self, any: Self::AnySpecEncoder, s: &str) -> anyhow::Result<()> {
+        self.write_str(any, s)
+    }
+
+    fn encode_bytes(&mut self, any: Self::AnySpecEncoder, _s: &[u8]) -> anyhow::Result<()> {
+        Err(self.nested(any))
+    }
+
+    fn encode_none(&mut self, any: Self::AnySpecEncoder) -> anyhow::Result<()> {
+        // An empty cell, as opposed to the quoted `""` of an empty string.
+        self.write_cell(any, String::new())
+    }
+
+    fn encode_some(
+        &mut self,
+        any: Self::AnySpecEncoder,
+    ) -> anyhow::Result<(Self::AnySpecEncoder, Self::SomeCloser)> {
+        Ok((any, ()))
+    }
+
+    fn encode_unit_struct(
+        &mut self,
+        any: Self::AnySpecEncoder,
+        _name: &'static str,
+    ) -> anyhow::Result<()> {
+        self.write_cell(any, String::new())
+    }
+
+    fn encode_tuple_struct(
+        &mut self,
+        any: Self::AnySpecEncoder,
+        _name: &'static str,
+        _len: usize,
+    ) -> anyhow::Result<Self::TupleStructEncoder> {
+        Err(self.nested(any))
+    }
+
+    fn encode_struct(
+        &mut self,
+        any: Self::AnySpecEncoder,
+        _name: &'static str,
+        fields: &'static [&'static str],
+    ) -> anyhow::Result<Self::StructEncoder> {
+        match any {
+            CsvAnySpecEncoder::Row => {}
+            _ => return Err(self.nested(any)),
+        }
+        match self.header {
+            None => {
+                self.header = Some(fields);
+                let header: Vec<String> = fields.iter().map(|field| self.escape(field)).collect();
+                self.write_line(header.iter().map(|x| &**x));
+            }
+            Some(header) if header == fields => {}
+            Some(_) => return Err(CsvEncoderError::HeaderMismatch.into()),
+        }
+        self.row = vec![String::new(); fields.len()];
+        Ok(())
+    }
+
+    fn encode_unit_variant(
+        &mut self,
+        any: Self::AnySpecEncoder,
+        _name: &'static str,
+        variants: &'static [&'static str],
+        variant_index: usize,
+    ) -> anyhow::Result<()> {
+        self.write_str(any, variants[variant_index])
+    }
+
+    fn encode_tuple_variant(
+        &mut self,
+        any: Self::AnySpecEncoder,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        _variant_index: usize,
+        _len: usize,
+    ) -> anyhow::Result<Self::TupleVariantEncoder> {
+        Err(self.nested(any))
+    }
+
+    fn encode_struct_variant(
+        &mut self,
+        any: Self::AnySpecEncoder,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        _variant_index: usize,
+        _fields: &'static [&'static str],
+    ) -> anyhow::Result<Self::StructVariantEncoder> {
+        Err(self.nested(any))
+    }
+
+    fn encode_seq(
+        &mut self,
+        any: Self::AnySpecEncoder,
+        _len: usize,
+    ) -> anyhow::Result<Self::SeqEncoder> {
+        self.open_rows(any)
+    }
+
+    fn encode_tuple(
+        &mut self,
+        any: Self::AnySpecEncoder,
+        _len: usize,
+    ) -> anyhow::Result<Self::TupleEncoder> {
+        self.open_rows(any)
+    }
+
+    fn encode_map(
+        &mut self,
+        any: Self::AnySpecEncoder,
+        _len: usize,
+    ) -> anyhow::Result<Self::MapEncoder> {
+        Err(self.nested(any))
+    }
+
+    fn some_end(&mut self, _some: Self::SomeCloser) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn tuple_encode_element(
+        &mut self,
+        _tuple: &mut Self::TupleEncoder,
+    ) -> anyhow::Result<Self::AnySpecEncoder> {
+        Ok(CsvAnySpecEncoder::Row)
+    }
+
+    fn tuple_end(&mut self, _tuple: Self::TupleEncoder) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn seq_encode_element(
+        &mut self,
+        _seq: &mut Self::SeqEncoder,
+    ) -> anyhow::Result<Self::AnySpecEncoder> {
+        Ok(CsvAnySpecEncoder::Row)
+    }
+
+    fn seq_end(&mut self, _seq: Self::SeqEncoder) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn map_encode_element(
+        &mut self,
+        map: &mut Self::MapEncoder,
+    ) -> anyhow::Result<(Self::AnySpecEncoder, Self::ValueEncoder)> {
+        *map
+    }
+
+    fn map_end(&mut self, map: Self::MapEncoder) -> anyhow::Result<()> {
+        map
+    }
+
+    fn entry_encode_value(
+        &mut self,
+        value: Self::ValueEncoder,
+    ) -> anyhow::Result<(Self::AnySpecEncoder, Self::EntryCloser)> {
+        value
+    }
+
+    fn entry_end(&mut self, closer: Self::EntryCloser) -> anyhow::Result<()> {
+        closer
+    }
+
+    fn tuple_struct_encode_field(
+        &mut self,
+        s: &mut Self::TupleStructEncoder,
+    ) -> anyhow::Result<Self::AnySpecEncoder> {
+        *s
+    }
+
+    fn tuple_struct_end(&mut self, s: Self::TupleStructEncoder) -> anyhow::Result<()> {
+        s
+    }
+
+    fn struct_encode_field(
+        &mut self,
+        _s: &mut Self::StructEncoder,
+        field: &'static str,
+    ) -> anyhow::Result<Self::AnySpecEncoder> {
+        let column = self
+            .header
+            .and_then(|header| header.iter().position(|x| *x == field))
+            .ok_or(CsvEncoderError::UnknownField(field))?;
+        Ok(CsvAnySpecEncoder::Cell(column))
+    }
+
+    fn struct_end(&mut self, _s: Self::StructEncoder) -> anyhow::Result<()> {
+        let row = std::mem::take(&mut self.row);
+        self.write_line(row.iter().map(|x| &**x));
+        Ok(())
+    }
+
+    fn tuple_variant_encode_field(
+        &mut self,
+        v: &mut Self::TupleVariantEncoder,
+    ) -> anyhow::Result<Self::AnySpecEncoder> {
+        *v
+    }
+
+    fn tuple_variant_end(&mut self, v: Self::TupleVariantEncoder) -> anyhow::Result<()> {
+        v
+    }
+
+    fn struct_variant_encode_field(
+        &mut self,
+        v: &mut Self::StructVariantEncoder,
+        _key: &'static str,
+    ) -> anyhow::Result<Self::AnySpecEncoder> {
+        *v
+    }
+
+    fn struct_variant_end(&mut self, v: Self::StructVariantEncoder) -> anyhow::Result<()> {
+        v
+    }
+
+    fn is_human_readable(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,19 @@
+#![deny(unused_must_use)]
+#![feature(never_type)]
+#![feature(trait_alias)]
+#![feature(specialization)]
+#![allow(incomplete_features)]
+
+use marshal::de::Deserialize;
+use marshal::ser::Serialize;
+
+use crate::decode::full::CsvDecoder;
+use crate::encode::full::CsvEncoder;
+
+pub mod decode;
+pub mod encode;
+#[cfg(test)]
+mod test;
+
+pub trait SerializeCsv = Serialize<CsvEncoder>;
+pub trait DeserializeCsv = Deserialize<CsvDecoder>;
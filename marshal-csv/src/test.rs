@@ -0,0 +1,232 @@
+use std::fmt::Debug;
+
+use marshal::context::OwnedContext;
+use marshal_core::encode::SpecEncoder;
+use marshal_derive::{Deserialize, Serialize};
+
+use crate::decode::full::CsvDecoderBuilder;
+use crate::encode::full::CsvEncoderBuilder;
+use crate::encode::{CsvAnySpecEncoder, SimpleCsvSpecEncoder};
+use crate::{DeserializeCsv, SerializeCsv};
+
+fn decode<T: DeserializeCsv>(input: &str) -> anyhow::Result<T> {
+    let mut c = OwnedContext::new();
+    CsvDecoderBuilder::new(input).deserialize(c.borrow())
+}
+
+#[track_caller]
+fn test_round_trip<T: Debug + PartialEq + SerializeCsv + DeserializeCsv>(
+    input: T,
+    expected: &str,
+) -> anyhow::Result<()> {
+    println!("{:?}", input);
+    let mut c = OwnedContext::new();
+    let found = CsvEncoderBuilder::new().serialize(&input, c.borrow())?;
+    assert_eq!(found, expected);
+    assert_eq!(decode::<T>(&found)?, input);
+    Ok(())
+}
+
+#[track_caller]
+fn test_error<T: Debug + DeserializeCsv>(input: &str, expected: &str) {
+    let error = decode::<T>(input).unwrap_err();
+    assert_eq!(format!("{:#}", error), expected);
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+enum Color {
+    Red,
+    Blue,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Record {
+    name: String,
+    count: u32,
+    ratio: f64,
+    enabled: bool,
+    color: Color,
+}
+
+#[test]
+fn test_records() -> anyhow::Result<()> {
+    test_round_trip(
+        vec![
+            Record {
+                name: "a".to_string(),
+                count: 1,
+                ratio: 0.5,
+                enabled: true,
+                color: Color::Red,
+            },
+            Record {
+                name: "b".to_string(),
+                count: 2,
+                ratio: -1.0,
+                enabled: false,
+                color: Color::Blue,
+            },
+        ],
+        "name,count,ratio,enabled,color\na,1,0.5,true,Red\nb,2,-1,false,Blue\n",
+    )?;
+    test_round_trip(Vec::<Record>::new(), "")?;
+    Ok(())
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Text {
+    text: String,
+    other: String,
+}
+
+#[test]
+fn test_quoting() -> anyhow::Result<()> {
+    test_round_trip(
+        vec![
+            Text {
+                text: "a,b".to_string(),
+                other: "say \"hi\"".to_string(),
+            },
+            Text {
+                text: "two\nlines".to_string(),
+                other: "".to_string(),
+            },
+        ],
+        "text,other\n\"a,b\",\"say \"\"hi\"\"\"\n\"two\nlines\",\"\"\n",
+    )?;
+    assert_eq!(
+        decode::<Vec<Text>>("text,other\r\nx,y\r\n\r\n")?,
+        vec![Text {
+            text: "x".to_string(),
+            other: "y".to_string(),
+        }]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_delimiter() -> anyhow::Result<()> {
+    let rows = vec![Text {
+        text: "a,b".to_string(),
+        other: "c;d".to_string(),
+    }];
+    let mut c = OwnedContext::new();
+    let mut encoder = CsvEncoderBuilder::new();
+    encoder.set_delimiter(';');
+    let found = encoder.serialize(&rows, c.borrow())?;
+    assert_eq!(found, "text;other\na,b;\"c;d\"\n");
+    let mut decoder = CsvDecoderBuilder::new(&found);
+    decoder.set_delimiter(';');
+    assert_eq!(decoder.deserialize::<Vec<Text>>(c.borrow())?, rows);
+    Ok(())
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Optional {
+    id: u8,
+    label: Option<String>,
+    score: Option<i32>,
+}
+
+#[test]
+fn test_option() -> anyhow::Result<()> {
+    test_round_trip(
+        vec![
+            Optional {
+                id: 1,
+                label: None,
+                score: Some(-3),
+            },
+            Optional {
+                id: 2,
+                label: Some("".to_string()),
+                score: None,
+            },
+        ],
+        "id,label,score\n1,,-3\n2,\"\",\n",
+    )
+}
+
+#[test]
+fn test_header_order() -> anyhow::Result<()> {
+    assert_eq!(
+        decode::<Vec<Optional>>("score,extra,id,label\n5,ignored,1,x\n")?,
+        vec![Optional {
+            id: 1,
+            label: Some("x".to_string()),
+            score: Some(5),
+        }]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_serialize_iter() -> anyhow::Result<()> {
+    let mut c = OwnedContext::new();
+    let found = CsvEncoderBuilder::new().serialize_iter(
+        (1..=3).map(|id| Optional {
+            id,
+            label: None,
+            score: Some(id as i32 * 10),
+        }),
+        c.borrow(),
+    )?;
+    assert_eq!(found, "id,label,score\n1,,10\n2,,20\n3,,30\n");
+    Ok(())
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Nested {
+    id: u8,
+    tags: Vec<String>,
+}
+
+#[test]
+fn test_errors() {
+    let mut c = OwnedContext::new();
+    let error = CsvEncoderBuilder::new()
+        .serialize(
+            &vec![Nested {
+                id: 1,
+                tags: vec![],
+            }],
+            c.borrow(),
+        )
+        .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "field \"tags\" is nested, but CSV cells must be scalars"
+    );
+    test_error::<Vec<Nested>>(
+        "id,tags\n1,a\n",
+        "at line 2 column 3: field \"tags\" is nested, but CSV cells must be scalars",
+    );
+    test_error::<Vec<Text>>(
+        "text,other\na\"b,c\n",
+        "at line 2 column 2: quote within an unquoted cell",
+    );
+    test_error::<Vec<Text>>(
+        "text,other\n\"a,b\n",
+        "at line 2 column 1: quoted cell is not terminated",
+    );
+    test_error::<Vec<Text>>(
+        "text,other\na,b,c\n",
+        "at line 2 column 1: row has 3 cells, but the header has 2",
+    );
+    test_error::<Vec<Optional>>(
+        "id,label,score\nx,,\n",
+        "at line 2 column 1: invalid digit found in string",
+    );
+}
+
+#[test]
+fn test_unknown_field() -> anyhow::Result<()> {
+    let mut encoder = SimpleCsvSpecEncoder::new();
+    encoder.encode_struct(CsvAnySpecEncoder::Row, "Row", &["a", "b"])?;
+    encoder.struct_encode_field(&mut (), "b")?;
+    let Err(error) = encoder.struct_encode_field(&mut (), "c") else {
+        panic!("expected an error");
+    };
+    assert_eq!(error.to_string(), "field \"c\" is not in the header");
+    Ok(())
+}
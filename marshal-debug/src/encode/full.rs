@@ -0,0 +1,82 @@
+use std::fmt::{Debug, Formatter};
+
+use marshal::context::{Context, OwnedContext};
+use marshal_core::derive_encoder_for_newtype;
+use marshal_core::encode::poison::PoisonEncoder;
+use marshal_core::encode::{AnySpecEncoder, Encoder};
+
+use crate::encode::{DebugAnySpecEncoder, SimpleDebugSpecEncoder};
+use crate::SerializeDebug;
+
+pub struct DebugSpecEncoder(PoisonEncoder<SimpleDebugSpecEncoder>);
+
+derive_encoder_for_newtype!(DebugSpecEncoder(PoisonEncoder<SimpleDebugSpecEncoder>));
+
+/// Writes values as Rust-like text in the style of `Debug`, such as `Point { x: 1, y: 2 }`. The
+/// output cannot be decoded.
+pub struct DebugEncoderBuilder {
+    inner: DebugSpecEncoder,
+}
+
+impl DebugEncoderBuilder {
+    pub fn new() -> Self {
+        DebugEncoderBuilder {
+            inner: DebugSpecEncoder(PoisonEncoder::new(SimpleDebugSpecEncoder::new())),
+        }
+    }
+    /// Writes one element per line with trailing commas, as with `{:#?}`.
+    pub fn set_pretty(&mut self, pretty: bool) -> &mut Self {
+        self.inner.0.inner_mut().set_pretty(pretty);
+        self
+    }
+    /// Replaces the contents of containers nested more than `max_depth` deep with `..`.
+    pub fn set_max_depth(&mut self, max_depth: usize) -> &mut Self {
+        self.inner.0.inner_mut().set_max_depth(max_depth);
+        self
+    }
+    /// Replaces the elements or fields of a container after the first `max_width` with `..`.
+    pub fn set_max_width(&mut self, max_width: usize) -> &mut Self {
+        self.inner.0.inner_mut().set_max_width(max_width);
+        self
+    }
+    pub fn build(&mut self) -> AnySpecEncoder<'_, DebugSpecEncoder> {
+        let any = self.inner.0.start(DebugAnySpecEncoder {
+            depth: 0,
+            hidden: false,
+        });
+        AnySpecEncoder::new(&mut self.inner, any)
+    }
+    pub fn serialize<T: ?Sized + SerializeDebug>(
+        mut self,
+        value: &T,
+        ctx: Context,
+    ) -> anyhow::Result<String> {
+        value.serialize(self.build(), ctx)?;
+        self.end()
+    }
+    pub fn end(self) -> anyhow::Result<String> {
+        self.inner.0.end()?.end()
+    }
+}
+
+pub struct DebugEncoder;
+
+impl Encoder for DebugEncoder {
+    type SpecEncoder<'en> = DebugSpecEncoder;
+}
+
+/// Implements [Debug] for a type that only implements [marshal::ser::Serialize], using the
+/// multi-line format for `{:#?}`.
+pub struct AsDebug<'a, T: ?Sized>(pub &'a T);
+
+impl<'a, T: ?Sized + SerializeDebug> Debug for AsDebug<'a, T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mut ctx = OwnedContext::new();
+        let mut builder = DebugEncoderBuilder::new();
+        builder.set_pretty(f.alternate());
+        let output = builder
+            .serialize(self.0, ctx.borrow())
+            .map_err(|_| std::fmt::Error)?;
+        f.write_str(&output)
+    }
+}
@@ -0,0 +1,460 @@
+use std::fmt::Write;
+
+use marshal_core::encode::SpecEncoder;
+use marshal_core::Primitive;
+
+pub mod full;
+
+pub struct SimpleDebugSpecEncoder {
+    output: String,
+    pretty: bool,
+    max_depth: usize,
+    max_width: usize,
+}
+
+#[derive(Copy, Clone)]
+pub struct DebugAnySpecEncoder {
+    depth: usize,
+    /// Set for values beneath an elision, which are visited but not written.
+    hidden: bool,
+}
+
+#[derive(Copy, Clone, Eq, PartialEq)]
+enum Delimiter {
+    Paren,
+    Bracket,
+    Brace,
+}
+
+impl Delimiter {
+    fn open(self) -> &'static str {
+        match self {
+            Delimiter::Paren => "(",
+            Delimiter::Bracket => "[",
+            Delimiter::Brace => "{",
+        }
+    }
+    fn close(self) -> &'static str {
+        match self {
+            Delimiter::Paren => ")",
+            Delimiter::Bracket => "]",
+            Delimiter::Brace => "}",
+        }
+    }
+}
+
+pub struct DebugBlock {
+    depth: usize,
+    hidden: bool,
+    delimiter: Delimiter,
+    /// Whether the block follows a name, so that it is omitted when empty (as in `Unit`) and braces
+    /// are padded with spaces (as in `Point { x: 1 }`).
+    named: bool,
+    len: usize,
+    /// Whether the remaining elements were replaced by `..`.
+    elided: bool,
+}
+
+impl DebugBlock {
+    fn new(any: DebugAnySpecEncoder, delimiter: Delimiter, named: bool) -> Self {
+        DebugBlock {
+            depth: any.depth,
+            hidden: any.hidden,
+            delimiter,
+            named,
+            len: 0,
+            elided: false,
+        }
+    }
+}
+
+impl SimpleDebugSpecEncoder {
+    pub fn new() -> Self {
+        SimpleDebugSpecEncoder {
+            output: String::new(),
+            pretty: false,
+            max_depth: usize::MAX,
+            max_width: usize::MAX,
+        }
+    }
+    pub fn set_pretty(&mut self, pretty: bool) {
+        self.pretty = pretty;
+    }
+    pub fn set_max_depth(&mut self, max_depth: usize) {
+        self.max_depth = max_depth;
+    }
+    pub fn set_max_width(&mut self, max_width: usize) {
+        self.max_width = max_width;
+    }
+    pub fn end(self) -> anyhow::Result<String> {
+        Ok(self.output)
+    }
+    fn write_name(&mut self, any: DebugAnySpecEncoder, name: &str) {
+        if !any.hidden {
+            self.output.push_str(name);
+        }
+    }
+    fn write_indent(&mut self, depth: usize) {
+        for _ in 0..depth {
+            self.output.push_str("    ");
+        }
+    }
+    fn open(&mut self, block: &DebugBlock) {
+        if block.named && block.delimiter == Delimiter::Brace {
+            self.output.push(' ');
+        }
+        self.output.push_str(block.delimiter.open());
+    }
+    /// Writes the separator before the next element.
+    fn separate(&mut self, block: &DebugBlock) {
+        if block.len == 0 {
+            self.open(block);
+            if block.named && block.delimiter == Delimiter::Brace && !self.pretty {
+                self.output.push(' ');
+            }
+        } else if !self.pretty {
+            self.output.push_str(", ");
+        } else {
+            self.output.push(',');
+        }
+        if self.pretty {
+            self.output.push('\n');
+            self.write_indent(block.depth + 1);
+        }
+    }
+    /// Starts the next element of a block, or hides it if it is past the maximum depth or width.
+    fn start_element(&mut self, block: &mut DebugBlock) -> DebugAnySpecEncoder {
+        let hidden = DebugAnySpecEncoder {
+            depth: block.depth + 1,
+            hidden: true,
+        };
+        if block.hidden || block.elided {
+            return hidden;
+        }
+        if block.depth >= self.max_depth {
+            self.open(block);
+            if block.named && block.delimiter == Delimiter::Brace {
+                self.output.push_str(" .. ");
+            } else {
+                self.output.push_str("..");
+            }
+            self.output.push_str(block.delimiter.close());
+            block.hidden = true;
+            return hidden;
+        }
+        self.separate(block);
+        if block.len == self.max_width {
+            self.output.push_str("..");
+            block.elided = true;
+            return hidden;
+        }
+        block.len += 1;
+        DebugAnySpecEncoder {
+            depth: block.depth + 1,
+            hidden: false,
+        }
+    }
+    fn end_block(&mut self, block: DebugBlock) {
+        if block.hidden {
+            return;
+        }
+        if block.len == 0 && !block.elided {
+            if !block.named {
+                self.output.push_str(block.delimiter.open());
+                self.output.push_str(block.delimiter.close());
+            }
+            return;
+        }
+        if self.pretty {
+            if !block.elided {
+                self.output.push(',');
+            }
+            self.output.push('\n');
+            self.write_indent(block.depth);
+        } else {
+            if block.len == 1
+                && !block.elided
+                && !block.named
+                && block.delimiter == Delimiter::Paren
+            {
+                self.output.push(',');
+            }
+            if block.named && block.delimiter == Delimiter::Brace {
+                self.output.push(' ');
+            }
+        }
+        self.output.push_str(block.delimiter.close());
+    }
+}
+
+impl SpecEncoder for SimpleDebugSpecEncoder {
+    type AnySpecEncoder = DebugAnySpecEncoder;
+    type SomeCloser = DebugBlock;
+    type TupleEncoder = DebugBlock;
+    type SeqEncoder = DebugBlock;
+    type MapEncoder = DebugBlock;
+    type ValueEncoder = DebugAnySpecEncoder;
+    type EntryCloser = ();
+    type TupleStructEncoder = DebugBlock;
+    type StructEncoder = DebugBlock;
+    type TupleVariantEncoder = DebugBlock;
+    type StructVariantEncoder = DebugBlock;
+
+    fn encode_prim(&mut self, any: Self::AnySpecEncoder, prim: Primitive) -> anyhow::Result<()> {
+        if any.hidden {
+            return Ok(());
+        }
+        match prim {
+            Primitive::Unit => write!(self.output, "()")?,
+            Primitive::Bool(x) => write!(self.output, "{}", x)?,
+            Primitive::I8(x) => write!(self.output, "{}", x)?,
+            Primitive::I16(x) => write!(self.output, "{}", x)?,
+            Primitive::I32(x) => write!(self.output, "{}", x)?,
+            Primitive::I64(x) => write!(self.output, "{}", x)?,
+            Primitive::I128(x) => write!(self.output, "{}", x)?,
+            Primitive::U8(x) => write!(self.output, "{}", x)?,
+            Primitive::U16(x) => write!(self.output, "{}", x)?,
+            Primitive::U32(x) => write!(self.output, "{}", x)?,
+            Primitive::U64(x) => write!(self.output, "{}", x)?,
+            Primitive::U128(x) => write!(self.output, "{}", x)?,
+            Primitive::F32(x) => write!(self.output, "{:?}", x)?,
+            Primitive::F64(x) => write!(self.output, "{:?}", x)?,
+            Primitive::Char(x) => write!(self.output, "{:?}", x)?,
+        }
+        Ok(())
+    }
+
+    fn encode_str(&mut self, any: Self::AnySpecEncoder, s: &str) -> anyhow::Result<()> {
+        if !any.hidden {
+            write!(self.output, "{:?}", s)?;
+        }
+        Ok(())
+    }
+
+    fn encode_bytes(&mut self, any: Self::AnySpecEncoder, s: &[u8]) -> anyhow::Result<()> {
+        let mut block = DebugBlock::new(any, Delimiter::Bracket, false);
+        for x in s {
+            let element = self.start_element(&mut block);
+            self.encode_prim(element, Primitive::U8(*x))?;
+        }
+        self.end_block(block);
+        Ok(())
+    }
+
+    fn encode_none(&mut self, any: Self::AnySpecEncoder) -> anyhow::Result<()> {
+        self.write_name(any, "None");
+        Ok(())
+    }
+
+    fn encode_some(
+        &mut self,
+        any: Self::AnySpecEncoder,
+    ) -> anyhow::Result<(Self::AnySpecEncoder, Self::SomeCloser)> {
+        self.write_name(any, "Some");
+        let mut block = DebugBlock::new(any, Delimiter::Paren, true);
+        let inner = self.start_element(&mut block);
+        Ok((inner, block))
+    }
+
+    fn encode_unit_struct(
+        &mut self,
+        any: Self::AnySpecEncoder,
+        name: &'static str,
+    ) -> anyhow::Result<()> {
+        self.write_name(any, name);
+        Ok(())
+    }
+
+    fn encode_tuple_struct(
+        &mut self,
+        any: Self::AnySpecEncoder,
+        name: &'static str,
+        _: usize,
+    ) -> anyhow::Result<Self::TupleStructEncoder> {
+        self.write_name(any, name);
+        Ok(DebugBlock::new(any, Delimiter::Paren, true))
+    }
+
+    fn encode_struct(
+        &mut self,
+        any: Self::AnySpecEncoder,
+        name: &'static str,
+        _: &'static [&'static str],
+    ) -> anyhow::Result<Self::StructEncoder> {
+        self.write_name(any, name);
+        Ok(DebugBlock::new(any, Delimiter::Brace, true))
+    }
+
+    fn encode_unit_variant(
+        &mut self,
+        any: Self::AnySpecEncoder,
+        _: &'static str,
+        variants: &'static [&'static str],
+        variant_index: usize,
+    ) -> anyhow::Result<()> {
+        self.write_name(any, variants[variant_index]);
+        Ok(())
+    }
+
+    fn encode_tuple_variant(
+        &mut self,
+        any: Self::AnySpecEncoder,
+        _: &'static str,
+        variants: &'static [&'static str],
+        variant_index: usize,
+        _: usize,
+    ) -> anyhow::Result<Self::TupleVariantEncoder> {
+        self.write_name(any, variants[variant_index]);
+        Ok(DebugBlock::new(any, Delimiter::Paren, true))
+    }
+
+    fn encode_struct_variant(
+        &mut self,
+        any: Self::AnySpecEncoder,
+        _: &'static str,
+        variants: &'static [&'static str],
+        variant_index: usize,
+        _: &'static [&'static str],
+    ) -> anyhow::Result<Self::StructVariantEncoder> {
+        self.write_name(any, variants[variant_index]);
+        Ok(DebugBlock::new(any, Delimiter::Brace, true))
+    }
+
+    fn encode_seq(
+        &mut self,
+        any: Self::AnySpecEncoder,
+        _: usize,
+    ) -> anyhow::Result<Self::SeqEncoder> {
+        Ok(DebugBlock::new(any, Delimiter::Bracket, false))
+    }
+
+    fn encode_tuple(
+        &mut self,
+        any: Self::AnySpecEncoder,
+        _: usize,
+    ) -> anyhow::Result<Self::TupleEncoder> {
+        Ok(DebugBlock::new(any, Delimiter::Paren, false))
+    }
+
+    fn encode_map(
+        &mut self,
+        any: Self::AnySpecEncoder,
+        _: usize,
+    ) -> anyhow::Result<Self::MapEncoder> {
+        Ok(DebugBlock::new(any, Delimiter::Brace, false))
+    }
+
+    fn some_end(&mut self, some: Self::SomeCloser) -> anyhow::Result<()> {
+        self.end_block(some);
+        Ok(())
+    }
+
+    fn tuple_encode_element(
+        &mut self,
+        tuple: &mut Self::TupleEncoder,
+    ) -> anyhow::Result<Self::AnySpecEncoder> {
+        Ok(self.start_element(tuple))
+    }
+
+    fn tuple_end(&mut self, tuple: Self::TupleEncoder) -> anyhow::Result<()> {
+        self.end_block(tuple);
+        Ok(())
+    }
+
+    fn seq_encode_element(
+        &mut self,
+        seq: &mut Self::SeqEncoder,
+    ) -> anyhow::Result<Self::AnySpecEncoder> {
+        Ok(self.start_element(seq))
+    }
+
+    fn seq_end(&mut self, seq: Self::SeqEncoder) -> anyhow::Result<()> {
+        self.end_block(seq);
+        Ok(())
+    }
+
+    fn map_encode_element(
+        &mut self,
+        map: &mut Self::MapEncoder,
+    ) -> anyhow::Result<(Self::AnySpecEncoder, Self::ValueEncoder)> {
+        let key = self.start_element(map);
+        Ok((key, key))
+    }
+
+    fn map_end(&mut self, map: Self::MapEncoder) -> anyhow::Result<()> {
+        self.end_block(map);
+        Ok(())
+    }
+
+    fn entry_encode_value(
+        &mut self,
+        value: Self::ValueEncoder,
+    ) -> anyhow::Result<(Self::AnySpecEncoder, Self::EntryCloser)> {
+        self.write_name(value, ": ");
+        Ok((value, ()))
+    }
+
+    fn entry_end(&mut self, _: Self::EntryCloser) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn tuple_struct_encode_field(
+        &mut self,
+        tuple_struct: &mut Self::TupleStructEncoder,
+    ) -> anyhow::Result<Self::AnySpecEncoder> {
+        Ok(self.start_element(tuple_struct))
+    }
+
+    fn tuple_struct_end(&mut self, tuple_struct: Self::TupleStructEncoder) -> anyhow::Result<()> {
+        self.end_block(tuple_struct);
+        Ok(())
+    }
+
+    fn struct_encode_field(
+        &mut self,
+        s: &mut Self::StructEncoder,
+        field: &'static str,
+    ) -> anyhow::Result<Self::AnySpecEncoder> {
+        let any = self.start_element(s);
+        self.write_name(any, field);
+        self.write_name(any, ": ");
+        Ok(any)
+    }
+
+    fn struct_end(&mut self, s: Self::StructEncoder) -> anyhow::Result<()> {
+        self.end_block(s);
+        Ok(())
+    }
+
+    fn tuple_variant_encode_field(
+        &mut self,
+        variant: &mut Self::TupleVariantEncoder,
+    ) -> anyhow::Result<Self::AnySpecEncoder> {
+        Ok(self.start_element(variant))
+    }
+
+    fn tuple_variant_end(&mut self, variant: Self::TupleVariantEncoder) -> anyhow::Result<()> {
+        self.end_block(variant);
+        Ok(())
+    }
+
+    fn struct_variant_encode_field(
+        &mut self,
+        variant: &mut Self::StructVariantEncoder,
+        field: &'static str,
+    ) -> anyhow::Result<Self::AnySpecEncoder> {
+        let any = self.start_element(variant);
+        self.write_name(any, field);
+        self.write_name(any, ": ");
+        Ok(any)
+    }
+
+    fn struct_variant_end(&mut self, variant: Self::StructVariantEncoder) -> anyhow::Result<()> {
+        self.end_block(variant);
+        Ok(())
+    }
+
+    fn is_human_readable(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,13 @@
+#![deny(unused_must_use)]
+#![feature(never_type)]
+#![feature(trait_alias)]
+
+use marshal::ser::Serialize;
+
+use crate::encode::full::DebugEncoder;
+
+pub mod encode;
+#[cfg(test)]
+mod test;
+
+pub trait SerializeDebug = Serialize<DebugEncoder>;
@@ -0,0 +1,121 @@
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+
+use marshal::context::OwnedContext;
+use marshal_derive::Serialize;
+
+use crate::encode::full::{AsDebug, DebugEncoderBuilder};
+use crate::SerializeDebug;
+
+fn encode<T: SerializeDebug>(value: &T, max_depth: usize, max_width: usize) -> String {
+    let mut c = OwnedContext::new();
+    let mut builder = DebugEncoderBuilder::new();
+    builder.set_max_depth(max_depth).set_max_width(max_width);
+    builder.serialize(value, c.borrow()).unwrap()
+}
+
+/// Checks that the output matches the derived `Debug` implementation in both modes.
+#[track_caller]
+fn test_matches_debug<T: Debug + SerializeDebug>(value: T) {
+    assert_eq!(format!("{:?}", AsDebug(&value)), format!("{:?}", value));
+    assert_eq!(format!("{:#?}", AsDebug(&value)), format!("{:#?}", value));
+}
+
+#[derive(Serialize, Debug)]
+struct Unit;
+
+#[derive(Serialize, Debug)]
+struct Empty {}
+
+#[derive(Serialize, Debug)]
+struct Meters(f64);
+
+#[derive(Serialize, Debug)]
+struct Point {
+    x: i32,
+    y: i32,
+}
+
+#[derive(Serialize, Debug)]
+enum Shape {
+    Dot,
+    Circle(Point, u32),
+    Rect { min: Point, max: Point },
+}
+
+#[derive(Serialize, Debug)]
+struct Scene {
+    name: String,
+    shapes: Vec<Shape>,
+    tags: BTreeMap<String, Option<char>>,
+    data: Vec<u8>,
+    pair: (bool, ()),
+    single: (u8,),
+    empty: Vec<u32>,
+    unit: Unit,
+    meters: Meters,
+}
+
+fn scene() -> Scene {
+    Scene {
+        name: "a \"scene\"\n".to_string(),
+        shapes: vec![
+            Shape::Dot,
+            Shape::Circle(Point { x: 1, y: -2 }, 3),
+            Shape::Rect {
+                min: Point { x: 0, y: 0 },
+                max: Point { x: 4, y: 5 },
+            },
+        ],
+        tags: BTreeMap::from([("k".to_string(), Some('\'')), ("none".to_string(), None)]),
+        data: vec![1, 2, 255],
+        pair: (true, ()),
+        single: (7,),
+        empty: vec![],
+        unit: Unit,
+        meters: Meters(1.0),
+    }
+}
+
+#[test]
+fn test_debug() {
+    test_matches_debug(1u8);
+    test_matches_debug(-1.5f32);
+    test_matches_debug('\n');
+    test_matches_debug("text\t".to_string());
+    test_matches_debug(Some(Some(3u32)));
+    test_matches_debug(None::<u32>);
+    test_matches_debug(Unit);
+    test_matches_debug(Empty {});
+    test_matches_debug(Meters(2.5));
+    test_matches_debug(Point { x: 1, y: 2 });
+    test_matches_debug(vec![1u32, 2]);
+    test_matches_debug(scene());
+}
+
+#[test]
+fn test_elision() {
+    assert_eq!(encode(&scene(), 0, usize::MAX), "Scene { .. }");
+    assert_eq!(
+        encode(&scene(), 1, 2),
+        r#"Scene { name: "a \"scene\"\n", shapes: [..], .. }"#
+    );
+    assert_eq!(
+        encode(&scene().shapes, 2, usize::MAX),
+        "[Dot, Circle(Point { .. }, 3), Rect { min: Point { .. }, max: Point { .. } }]"
+    );
+    assert_eq!(encode(&vec![1u32, 2, 3], usize::MAX, 0), "[..]");
+    assert_eq!(encode(&(1u8, 2u8, 3u8), usize::MAX, 1), "(1, ..)");
+    assert_eq!(encode(&Some(Some(1u8)), 1, usize::MAX), "Some(Some(..))");
+    assert_eq!(
+        encode(&BTreeMap::from([(1u8, 2u8), (3, 4)]), usize::MAX, 1),
+        "{1: 2, ..}"
+    );
+    let mut c = OwnedContext::new();
+    let mut builder = DebugEncoderBuilder::new();
+    builder.set_pretty(true).set_max_depth(1).set_max_width(1);
+    assert_eq!(
+        builder.serialize(&scene().shapes, c.borrow()).unwrap(),
+        "[\n    Dot,\n    ..\n]"
+    );
+}
@@ -4,7 +4,7 @@ use syn::parse::ParseStream;
 use syn::{Data, DeriveInput, Token, Variant};
 
 use crate::generics::DeriveGenerics;
-use crate::ident_to_lit;
+use crate::type_name_lit;
 use crate::parsed_enum::ParsedEnum;
 use crate::parsed_fields::{ParsedFields, ParsedFieldsNamed, ParsedFieldsUnnamed};
 
@@ -53,7 +53,7 @@ pub fn derive_deserialize_impl(input: &DeriveInput) -> Result<TokenStream, syn::
     let decode_hint_type = quote!(::marshal::decode::DecodeHint);
     let decode_variant_hint_type = quote!(::marshal::decode::DecodeVariantHint);
     let decoder_view_type = quote!(::marshal::decode::DecoderView);
-    let type_name = ident_to_lit(&type_ident);
+    let type_name = type_name_lit(type_ident, attrs)?;
     let option_type = quote! {::std::option::Option};
     let schema_error = quote! {::marshal::SchemaError};
 
@@ -72,19 +72,23 @@ pub fn derive_deserialize_impl(input: &DeriveInput) -> Result<TokenStream, syn::
                 field_types,
                 field_literals,
                 field_indices,
-            }) => Ok(quote! {
+                field_aliases,
+                field_missing_values,
+                field_tags,
+            }) => {
+                let decode_struct = if field_tags.is_empty() {
+                    quote!(decode(#decode_hint_type::Struct {
+                        name: #type_name,
+                        fields: &[#(#field_literals),*],
+                    }))
+                } else {
+                    quote!(decode_tagged_struct( #type_name, &[#(#field_literals),*], &[#(#field_tags),*]))
+                };
+                Ok(quote! {
                 #imp {
                     #[allow(unreachable_code)]
                     fn deserialize<'p, 'de>(decoder: #any_gen_decoder_type<'p, 'de, D>, mut ctx: #context_type) -> #result_type<Self>{
-                        let hint = #decode_hint_type::Struct{
-                            fields: &[
-                                #(
-                                    #field_literals
-                                ),*
-                            ],
-                            name: #type_name,
-                        };
-                        let decoder = decoder.decode( hint)?;
+                        let decoder = decoder.#decode_struct?;
                         match decoder {
                             #decoder_view_type::Map(mut decoder) => {
                                 #(
@@ -94,7 +98,7 @@ pub fn derive_deserialize_impl(input: &DeriveInput) -> Result<TokenStream, syn::
                                     let field_index: Option<usize> = match entry.decode_key()?.decode(#decode_hint_type::Identifier)?{
                                         #decoder_view_type::String(name) => match &*name {
                                             #(
-                                                #field_literals => Some(#field_indices),
+                                                #field_literals #(| #field_aliases)* => Some(#field_indices),
                                             )*
                                             _ => None,
                                         },
@@ -119,7 +123,7 @@ pub fn derive_deserialize_impl(input: &DeriveInput) -> Result<TokenStream, syn::
                                     entry.decode_end()?;
                                 }
                                 #(
-                                    let #field_var_idents = #field_var_idents.ok_or(#schema_error::MissingField{field_name:#field_literals})?;
+                                    let #field_var_idents = match #field_var_idents { Some(x) => x, None => #field_missing_values };
                                 )*
                                 ::std::result::Result::Ok(#type_ident {
                                     #(
@@ -145,7 +149,8 @@ pub fn derive_deserialize_impl(input: &DeriveInput) -> Result<TokenStream, syn::
                         }
                     }
                 }
-            }),
+            })
+            }
             ParsedFields::Unnamed(ParsedFieldsUnnamed {
                 field_count,
                 field_types,
@@ -191,6 +196,7 @@ pub fn derive_deserialize_impl(input: &DeriveInput) -> Result<TokenStream, syn::
             let ParsedEnum {
                 variant_literals,
                 variant_indices,
+                variant_aliases,
             } = ParsedEnum::new(data)?;
             let mut matches: Vec<TokenStream> = vec![];
             for (variant_index, variant) in data.variants.iter().enumerate() {
@@ -207,8 +213,17 @@ pub fn derive_deserialize_impl(input: &DeriveInput) -> Result<TokenStream, syn::
                             field_var_idents,
                             field_types,
                             field_literals,
-                            field_indices
+                            field_indices,
+                            field_aliases,
+                            field_missing_values,
+                            field_tags,
                         }) => {
+                        if !field_tags.is_empty() {
+                            return Err(syn::Error::new(
+                                variant_ident.span(),
+                                "tags are only supported on the fields of structs",
+                            ));
+                        }
                         matches.push(quote! {
                             #variant_index => {
                                 let hint = #decode_variant_hint_type::StructVariant{
@@ -228,7 +243,7 @@ pub fn derive_deserialize_impl(input: &DeriveInput) -> Result<TokenStream, syn::
                                             let field_index:Option<usize> = match entry.decode_key()?.decode(#decode_hint_type::Identifier)?{
                                                 #decoder_view_type::String(name) => match &*name{
                                                     #(
-                                                        #field_literals => Some(#field_indices),
+                                                        #field_literals #(| #field_aliases)* => Some(#field_indices),
                                                     )*
                                                     _ => None,
                                                 },
@@ -251,7 +266,7 @@ pub fn derive_deserialize_impl(input: &DeriveInput) -> Result<TokenStream, syn::
                                             entry.decode_end()?;
                                         }
                                         #(
-                                            let #field_var_idents = #field_var_idents.ok_or(#schema_error::MissingField{field_name:#field_literals})?;
+                                            let #field_var_idents = match #field_var_idents { Some(x) => x, None => #field_missing_values };
                                         )*
                                         #type_ident::#variant_ident {
                                             #(
@@ -344,7 +359,7 @@ pub fn derive_deserialize_impl(input: &DeriveInput) -> Result<TokenStream, syn::
                                         #decoder_view_type::Primitive(variant_index) => usize::try_from(variant_index)?,
                                         #decoder_view_type::String(disc) => match &*disc {
                                             #(
-                                                #variant_literals => #variant_indices,
+                                                #variant_literals #(| #variant_aliases)* => #variant_indices,
                                             )*
                                             _ => return #result_type::Err(#schema_error::UnknownDiscriminantName {
                                                 disc: disc.into_owned(),
@@ -3,13 +3,13 @@ use quote::quote;
 use syn::{Data, DeriveInput, Variant};
 
 use crate::generics::DeriveGenerics;
-use crate::ident_to_lit;
+use crate::type_name_lit;
 use crate::parsed_enum::ParsedEnum;
 use crate::parsed_fields::{ParsedFields, ParsedFieldsNamed, ParsedFieldsUnnamed};
 
 pub fn derive_deserialize_update_impl(input: &DeriveInput) -> Result<TokenStream, syn::Error> {
     let DeriveInput {
-        attrs: _,
+        attrs,
         vis: _,
         ident: type_ident,
         generics,
@@ -30,7 +30,7 @@ pub fn derive_deserialize_update_impl(input: &DeriveInput) -> Result<TokenStream
     let decode_hint_type = quote!(::marshal::decode::DecodeHint);
     let decode_variant_hint_type = quote!(::marshal::decode::DecodeVariantHint);
     let decoder_view_type = quote!(::marshal::decode::DecoderView);
-    let type_name = ident_to_lit(&type_ident);
+    let type_name = type_name_lit(type_ident, attrs)?;
     let option_type = quote! {::std::option::Option};
     let schema_error = quote! {::marshal::SchemaError};
 
@@ -40,30 +40,34 @@ pub fn derive_deserialize_update_impl(input: &DeriveInput) -> Result<TokenStream
         Data::Struct(data) => match ParsedFields::new(&data.fields)? {
             ParsedFields::Named(ParsedFieldsNamed {
                 field_idents,
-                field_var_idents:_,
+                field_var_idents: _,
                 field_types,
                 field_literals,
                 field_indices,
-            }) => Ok(quote! {
+                field_aliases,
+                field_missing_values: _,
+                field_tags,
+            }) => {
+                let decode_struct = if field_tags.is_empty() {
+                    quote!(decode(#decode_hint_type::Struct {
+                        name: #type_name,
+                        fields: &[#(#field_literals),*],
+                    }))
+                } else {
+                    quote!(decode_tagged_struct( #type_name, &[#(#field_literals),*], &[#(#field_tags),*]))
+                };
+                Ok(quote! {
                 #imp {
                     #[allow(unreachable_code)]
                     fn deserialize_update<'p, 'de>(&mut self, decoder: #any_gen_decoder_type<'p,'de,D>, mut ctx: #context_type) -> #result_type<()>{
-                        let hint = #decode_hint_type::Struct{
-                            fields: &[
-                                #(
-                                    #field_literals
-                                ),*
-                            ],
-                            name: #type_name,
-                        };
-                        let decoder = decoder.decode(hint)?;
+                        let decoder = decoder.#decode_struct?;
                         match decoder {
                             #decoder_view_type::Map(mut decoder) => {
                                 while let Some(mut entry) = decoder.decode_next()?{
                                     let field_index: Option<usize> = match entry.decode_key()?.decode(#decode_hint_type::Identifier)?{
                                         #decoder_view_type::String(name) => match &*name {
                                             #(
-                                                #field_literals => Some(#field_indices),
+                                                #field_literals #(| #field_aliases)* => Some(#field_indices),
                                             )*
                                             _ => None,
                                         },
@@ -93,7 +97,8 @@ pub fn derive_deserialize_update_impl(input: &DeriveInput) -> Result<TokenStream
                         ::std::result::Result::Ok(())
                     }
                 }
-            }),
+            })
+            }
             ParsedFields::Unnamed(ParsedFieldsUnnamed {
                 field_count,
                 field_types,
@@ -131,6 +136,7 @@ pub fn derive_deserialize_update_impl(input: &DeriveInput) -> Result<TokenStream
             let ParsedEnum {
                 variant_literals,
                 variant_indices,
+                variant_aliases,
             } = ParsedEnum::new(data)?;
             let mut matches: Vec<TokenStream> = vec![];
             for (variant_index, variant) in data.variants.iter().enumerate() {
@@ -147,8 +153,17 @@ pub fn derive_deserialize_update_impl(input: &DeriveInput) -> Result<TokenStream
                             field_var_idents:_,
                             field_types,
                             field_literals,
-                            field_indices
+                            field_indices,
+                            field_aliases,
+                            field_missing_values,
+                            field_tags,
                         }) => {
+                        if !field_tags.is_empty() {
+                            return Err(syn::Error::new(
+                                variant_ident.span(),
+                                "tags are only supported on the fields of structs",
+                            ));
+                        }
                         matches.push(quote! {
                             #variant_index => {
                                 let hint = #decode_variant_hint_type::StructVariant{
@@ -168,7 +183,7 @@ pub fn derive_deserialize_update_impl(input: &DeriveInput) -> Result<TokenStream
                                             let field_index:Option<usize> = match entry.decode_key()?.decode(#decode_hint_type::Identifier)?{
                                                 #decoder_view_type::String(name) => match &*name{
                                                     #(
-                                                        #field_literals => Some(#field_indices),
+                                                        #field_literals #(| #field_aliases)* => Some(#field_indices),
                                                     )*
                                                     _ => None,
                                                 },
@@ -194,7 +209,7 @@ pub fn derive_deserialize_update_impl(input: &DeriveInput) -> Result<TokenStream
                                     v => v.mismatch("expected map")?
                                 }
                                 #(
-                                    let #field_idents = #field_idents.ok_or(#schema_error::MissingField{field_name:#field_literals})?;
+                                    let #field_idents = match #field_idents { Some(x) => x, None => #field_missing_values };
                                 )*
                                 #type_ident::#variant_ident {
                                     #(
@@ -268,7 +283,7 @@ pub fn derive_deserialize_update_impl(input: &DeriveInput) -> Result<TokenStream
                                         #decoder_view_type::Primitive(variant_index) => usize::try_from(variant_index)?,
                                         #decoder_view_type::String(disc) => match &*disc {
                                             #(
-                                                #variant_literals => #variant_indices,
+                                                #variant_literals #(| #variant_aliases)* => #variant_indices,
                                             )*
                                             _ => return #result_type::Err(#schema_error::UnknownVariant.into()),
                                         },
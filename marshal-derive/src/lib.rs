@@ -3,13 +3,15 @@
 extern crate proc_macro;
 
 use proc_macro2::Ident;
-use syn::{DeriveInput, LitStr, parse_macro_input};
+use syn::{Attribute, DeriveInput, LitStr, parse_macro_input};
 
 use crate::deserialize::derive_deserialize_impl;
 use crate::deserialize_update::derive_deserialize_update_impl;
+use crate::parse_attr::ParsedAttrs;
 use crate::serialize::derive_serialize_impl;
 use crate::serialize_stream::derive_serialize_stream_impl;
 use crate::serialize_update::derive_serialize_update_impl;
+use crate::type_shape::derive_type_shape_impl;
 
 mod deserialize;
 mod deserialize_update;
@@ -20,6 +22,7 @@ mod serialize;
 mod serialize_stream;
 mod serialize_update;
 mod parse_attr;
+mod type_shape;
 
 #[proc_macro_derive(Deserialize, attributes(deserialize, marshal))]
 pub fn derive_deserialize(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
@@ -41,6 +44,12 @@ fn ident_to_lit(ident: &Ident) -> LitStr {
     LitStr::new(&format!("{}", ident), ident.span())
 }
 
+/// The name passed to encoders and decoders, which is the identifier unless the type has a
+/// `#[marshal(rename = "...")]` attribute.
+fn type_name_lit(ident: &Ident, attrs: &[Attribute]) -> syn::Result<LitStr> {
+    Ok(ParsedAttrs::new(attrs)?.rename.unwrap_or_else(|| ident_to_lit(ident)))
+}
+
 #[proc_macro_derive(Serialize, attributes(serialize, marshal))]
 pub fn derive_serialize(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
@@ -64,3 +73,11 @@ pub fn derive_serialize_stream(input: proc_macro::TokenStream) -> proc_macro::To
         .unwrap_or_else(|e| e.into_compile_error())
         .into()
 }
+
+#[proc_macro_derive(TypeShape, attributes(marshal))]
+pub fn derive_type_shape(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    derive_type_shape_impl(&input)
+        .unwrap_or_else(|e| e.into_compile_error())
+        .into()
+}
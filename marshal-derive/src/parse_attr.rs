@@ -1,29 +1,68 @@
+use proc_macro2::TokenStream;
+use quote::quote;
 use syn::meta::ParseNestedMeta;
 use syn::spanned::Spanned;
-use syn::{Attribute, LitStr, Token};
+use syn::{Attribute, ExprPath, LitInt, LitStr, Token};
 
 pub struct ParsedAttrs {
     pub rename: Option<LitStr>,
+    /// Other names that are accepted when deserializing.
+    pub aliases: Vec<LitStr>,
+    /// An expression for the value of a field that is missing when deserializing.
+    pub default: Option<TokenStream>,
+    /// The field number used by formats such as protobuf.
+    pub tag: Option<LitInt>,
 }
 impl ParsedAttrs {
     pub fn new(attrs: &[Attribute]) -> syn::Result<Self> {
         let mut rename = None;
+        let mut aliases = vec![];
+        let mut default = None;
+        let mut tag = None;
         for attrs in attrs {
             if attrs.path().is_ident("marshal") {
                 attrs.parse_nested_meta(|x: ParseNestedMeta| {
-                    x.input.parse::<Token![=]>()?;
                     if x.path.is_ident("rename") {
+                        x.input.parse::<Token![=]>()?;
                         if rename.is_some() {
                             return Err(syn::Error::new(x.path.span(), "two renames"));
                         }
                         rename = Some(x.input.parse::<LitStr>()?);
                         return Ok(());
+                    } else if x.path.is_ident("alias") {
+                        x.input.parse::<Token![=]>()?;
+                        aliases.push(x.input.parse::<LitStr>()?);
+                        return Ok(());
+                    } else if x.path.is_ident("default") {
+                        if default.is_some() {
+                            return Err(syn::Error::new(x.path.span(), "two defaults"));
+                        }
+                        if x.input.peek(Token![=]) {
+                            x.input.parse::<Token![=]>()?;
+                            let path = x.input.parse::<LitStr>()?.parse::<ExprPath>()?;
+                            default = Some(quote!(#path()));
+                        } else {
+                            default = Some(quote!(::std::default::Default::default()));
+                        }
+                        return Ok(());
+                    } else if x.path.is_ident("tag") {
+                        x.input.parse::<Token![=]>()?;
+                        if tag.is_some() {
+                            return Err(syn::Error::new(x.path.span(), "two tags"));
+                        }
+                        tag = Some(x.input.parse::<LitInt>()?);
+                        return Ok(());
                     } else {
                         return Err(syn::Error::new(x.path.span(), "attribute not recognized"));
                     }
                 })?;
             }
         }
-        Ok(ParsedAttrs { rename })
+        Ok(ParsedAttrs {
+            rename,
+            aliases,
+            default,
+            tag,
+        })
     }
 }
@@ -6,6 +6,7 @@ use crate::parse_attr::ParsedAttrs;
 pub struct ParsedEnum {
     pub variant_literals: Vec<LitStr>,
     pub variant_indices: Vec<usize>,
+    pub variant_aliases: Vec<Vec<LitStr>>,
 }
 
 impl ParsedEnum {
@@ -13,15 +14,18 @@ impl ParsedEnum {
         let mut variant_idents = vec![];
         let mut variant_literals = vec![];
         let mut variant_indices = vec![];
+        let mut variant_aliases = vec![];
         for (index, variant) in enu.variants.iter().enumerate() {
             variant_idents.push(&variant.ident);
             let attrs = ParsedAttrs::new(&variant.attrs)?;
             variant_literals.push(attrs.rename.unwrap_or_else(|| ident_to_lit(&variant.ident)));
             variant_indices.push(index);
+            variant_aliases.push(attrs.aliases);
         }
         Ok(ParsedEnum {
             variant_literals,
             variant_indices,
+            variant_aliases,
         })
     }
 }
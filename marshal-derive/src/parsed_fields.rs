@@ -1,15 +1,24 @@
-use proc_macro2::Ident;
-use quote::format_ident;
+use proc_macro2::{Ident, TokenStream};
+use quote::{format_ident, quote};
 use syn::spanned::Spanned;
 use syn::{Fields, LitStr, Type};
 use crate::parse_attr::ParsedAttrs;
 
+/// The range of protobuf field numbers.
+const MIN_TAG: u32 = 1;
+const MAX_TAG: u32 = (1 << 29) - 1;
+
 pub struct ParsedFieldsNamed<'a> {
     pub field_idents: Vec<&'a Ident>,
     pub field_var_idents: Vec<Ident>,
     pub field_types: Vec<&'a Type>,
     pub field_literals: Vec<LitStr>,
     pub field_indices: Vec<usize>,
+    pub field_aliases: Vec<Vec<LitStr>>,
+    /// Expressions for the values of fields that are missing when deserializing.
+    pub field_missing_values: Vec<TokenStream>,
+    /// The field numbers from `#[marshal(tag = N)]`, which is empty if no field has a tag.
+    pub field_tags: Vec<u32>,
 }
 
 pub struct ParsedFieldsUnnamed<'a> {
@@ -34,16 +43,51 @@ impl<'a> ParsedFields<'a> {
                 let mut field_types = vec![];
                 let mut field_literals = vec![];
                 let mut field_indices = vec![];
+                let mut field_aliases = vec![];
+                let mut field_missing_values = vec![];
+                let mut field_tags = vec![];
                 for (index, field) in fields.named.iter().enumerate() {
                     let ident = field.ident.as_ref().unwrap();
                     let attrs = ParsedAttrs::new(&field.attrs)?;
                     field_idents.push(ident);
                     field_var_idents.push(format_ident!("_{}", ident));
                     field_types.push(&field.ty);
-                    field_literals.push(
-                        attrs.rename.unwrap_or_else(|| LitStr::new(&format!("{}", ident), ident.span())),
-                    );
+                    let literal =
+                        attrs.rename.unwrap_or_else(|| LitStr::new(&format!("{}", ident), ident.span()));
+                    field_missing_values.push(attrs.default.unwrap_or_else(|| {
+                        quote! {
+                            return ::std::result::Result::Err(
+                                ::marshal::SchemaError::MissingField { field_name: #literal }.into()
+                            )
+                        }
+                    }));
+                    field_literals.push(literal);
                     field_indices.push(index);
+                    field_aliases.push(attrs.aliases);
+                    if let Some(tag) = attrs.tag {
+                        if field_tags.len() != index {
+                            return Err(syn::Error::new(
+                                tag.span(),
+                                "either every field or no field has a tag",
+                            ));
+                        }
+                        let value = tag.base10_parse::<u32>()?;
+                        if !(MIN_TAG..=MAX_TAG).contains(&value) {
+                            return Err(syn::Error::new(
+                                tag.span(),
+                                format!("tag must be between {} and {}", MIN_TAG, MAX_TAG),
+                            ));
+                        }
+                        if field_tags.contains(&value) {
+                            return Err(syn::Error::new(tag.span(), "duplicate tag"));
+                        }
+                        field_tags.push(value);
+                    } else if !field_tags.is_empty() {
+                        return Err(syn::Error::new(
+                            ident.span(),
+                            "either every field or no field has a tag",
+                        ));
+                    }
                 }
 
                 Ok(ParsedFields::Named(ParsedFieldsNamed {
@@ -52,6 +96,9 @@ impl<'a> ParsedFields<'a> {
                     field_types,
                     field_literals,
                     field_indices,
+                    field_aliases,
+                    field_missing_values,
+                    field_tags,
                 }))
             }
             Fields::Unnamed(fields) => {
@@ -75,3 +122,4 @@ impl<'a> ParsedFields<'a> {
         }
     }
 }
+
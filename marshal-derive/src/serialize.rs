@@ -1,9 +1,10 @@
 use proc_macro2::{Ident, TokenStream};
 use quote::quote;
-use syn::{Data, DeriveInput, LitStr, Token, Variant};
+use syn::{Data, DeriveInput, Token, Variant};
 use syn::parse::ParseStream;
 
 use crate::generics::DeriveGenerics;
+use crate::type_name_lit;
 use crate::parsed_enum::ParsedEnum;
 use crate::parsed_fields::{ParsedFields, ParsedFieldsNamed, ParsedFieldsUnnamed};
 
@@ -43,7 +44,7 @@ pub fn derive_serialize_impl(input: &DeriveInput) -> Result<TokenStream, syn::Er
     let gen_encoder_trait = quote! { ::marshal::encode::Encoder };
     let serialize_trait = quote! { ::marshal::ser::Serialize };
     let context_type = quote! { ::marshal::context::Context };
-    let type_name = LitStr::new(&format!("{}", type_ident), type_ident.span());
+    let type_name = type_name_lit(type_ident, attrs)?;
     let any_gen_encoder_type = quote!(::marshal::encode::AnyEncoder);
 
     let anyhow = quote!(::marshal::reexports::anyhow);
@@ -71,14 +72,19 @@ pub fn derive_serialize_impl(input: &DeriveInput) -> Result<TokenStream, syn::Er
                 field_types: _,
                 field_literals,
                 field_indices: _,
-            }) => Ok(quote! {
+                field_aliases: _,
+                field_missing_values: _,
+                field_tags,
+            }) => {
+                let encode_struct = if field_tags.is_empty() {
+                    quote!(encode_struct( #type_name, &[#(#field_literals),*]))
+                } else {
+                    quote!(encode_tagged_struct( #type_name, &[#(#field_literals),*], &[#(#field_tags),*]))
+                };
+                Ok(quote! {
                 #imp {
                     fn serialize<'w,'en>(&self, encoder: #any_gen_encoder_type<'w,'en, E>, mut ctx: #context_type) -> #result_type<()> {
-                        let mut encoder = encoder.encode_struct( #type_name, &[
-                                #(
-                                    #field_literals
-                                ),*
-                            ])?;
+                        let mut encoder = encoder.#encode_struct?;
                         #(
                             #serialize_trait::<E>::serialize(&self.#field_idents, encoder.encode_field()?, ctx.reborrow())?;
                         )*
@@ -86,7 +92,8 @@ pub fn derive_serialize_impl(input: &DeriveInput) -> Result<TokenStream, syn::Er
                         ::std::result::Result::Ok(())
                     }
                 }
-            }),
+                })
+            }
             ParsedFields::Unnamed(ParsedFieldsUnnamed {
                 field_count,
                 field_types: _,
@@ -109,6 +116,7 @@ pub fn derive_serialize_impl(input: &DeriveInput) -> Result<TokenStream, syn::Er
             let ParsedEnum {
                 variant_literals,
                 variant_indices: _,
+                variant_aliases: _,
             } = ParsedEnum::new(data)?;
             let mut matches = vec![];
             for (variant_index, variant) in data.variants.iter().enumerate() {
@@ -125,7 +133,16 @@ pub fn derive_serialize_impl(input: &DeriveInput) -> Result<TokenStream, syn::Er
                         field_types: _,
                         field_literals,
                         field_indices: _,
+                        field_aliases: _,
+                        field_missing_values: _,
+                        field_tags,
                     }) => {
+                        if !field_tags.is_empty() {
+                            return Err(syn::Error::new(
+                                variant_ident.span(),
+                                "tags are only supported on the fields of structs",
+                            ));
+                        }
                         matches.push(quote! {
                             Self::#variant_ident{ #(#field_idents),* } => {
                                 let mut encoder = encoder.encode_struct_variant( #type_name, &[#( #variant_literals ),*], #variant_index, &[#(#field_literals),*])?;
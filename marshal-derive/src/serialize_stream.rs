@@ -1,14 +1,15 @@
 use proc_macro2::TokenStream;
 use quote::{format_ident, quote};
-use syn::{Data, DeriveInput, LitStr, Variant};
+use syn::{Data, DeriveInput, Variant};
 
 use crate::generics::DeriveGenerics;
+use crate::type_name_lit;
 use crate::parsed_enum::ParsedEnum;
 use crate::parsed_fields::{ParsedFields, ParsedFieldsNamed, ParsedFieldsUnnamed};
 
 pub fn derive_serialize_stream_impl(input: &DeriveInput) -> Result<TokenStream, syn::Error> {
     let DeriveInput {
-        attrs: _,
+        attrs,
         vis: _,
         ident: type_ident,
         generics,
@@ -20,7 +21,7 @@ pub fn derive_serialize_stream_impl(input: &DeriveInput) -> Result<TokenStream,
     } = DeriveGenerics::new(generics, &quote! {::marshal_update::ser::SerializeStream});
     let serialize_stream_trait = quote! { ::marshal_update::ser::SerializeStream };
     let context_type = quote! { ::marshal::context::Context };
-    let type_name = LitStr::new(&format!("{}", type_ident), type_ident.span());
+    let type_name = type_name_lit(type_ident, attrs)?;
 
     let anyhow = quote!(::marshal::reexports::anyhow);
     let result_type = quote!(#anyhow::Result);
@@ -42,10 +43,13 @@ pub fn derive_serialize_stream_impl(input: &DeriveInput) -> Result<TokenStream,
             }),
             ParsedFields::Named(ParsedFieldsNamed {
                 field_idents,
-                field_var_idents:_,
+                field_var_idents: _,
                 field_types,
                 field_literals: _,
                 field_indices: _,
+                field_aliases: _,
+                field_missing_values: _,
+                field_tags: _,
             }) => Ok(quote! {
                 pub struct #stream_ident{
                     #(
@@ -90,6 +94,7 @@ pub fn derive_serialize_stream_impl(input: &DeriveInput) -> Result<TokenStream,
             let ParsedEnum {
                 variant_literals,
                 variant_indices: _,
+                variant_aliases: _,
             } = ParsedEnum::new(data)?;
             let mut matches = vec![];
             for (variant_index, variant) in data.variants.iter().enumerate() {
@@ -102,11 +107,20 @@ pub fn derive_serialize_stream_impl(input: &DeriveInput) -> Result<TokenStream,
                 match ParsedFields::new(&variant.fields)? {
                     ParsedFields::Named(ParsedFieldsNamed {
                         field_idents,
-                        field_var_idents:_,
+                        field_var_idents: _,
                         field_types: _,
                         field_literals: _,
                         field_indices: _,
+                        field_aliases: _,
+                        field_missing_values: _,
+                        field_tags,
                     }) => {
+                        if !field_tags.is_empty() {
+                            return Err(syn::Error::new(
+                                variant_ident.span(),
+                                "tags are only supported on the fields of structs",
+                            ));
+                        }
                         matches.push(quote! {
                             Self::#variant_ident{ #(#field_idents),* } => {
                                 todo!();
@@ -1,14 +1,15 @@
 use proc_macro2::TokenStream;
 use quote::quote;
-use syn::{Data, DeriveInput, LitStr, Variant};
+use syn::{Data, DeriveInput, Variant};
 
 use crate::generics::DeriveGenerics;
+use crate::type_name_lit;
 use crate::parsed_enum::ParsedEnum;
 use crate::parsed_fields::{ParsedFields, ParsedFieldsNamed, ParsedFieldsUnnamed};
 
 pub fn derive_serialize_update_impl(input: &DeriveInput) -> Result<TokenStream, syn::Error> {
     let DeriveInput {
-        attrs: _,
+        attrs,
         vis: _,
         ident: type_ident,
         generics,
@@ -24,7 +25,7 @@ pub fn derive_serialize_update_impl(input: &DeriveInput) -> Result<TokenStream,
     let gen_encoder_trait = quote! { ::marshal::encode::Encoder };
     let serialize_update_trait = quote! { ::marshal_update::ser::SerializeUpdate };
     let context_type = quote! { ::marshal::context::Context };
-    let type_name = LitStr::new(&format!("{}", type_ident), type_ident.span());
+    let type_name = type_name_lit(type_ident, attrs)?;
     let any_gen_encoder_type = quote!(::marshal::encode::AnyEncoder);
 
     let anyhow = quote!(::marshal::reexports::anyhow);
@@ -46,26 +47,32 @@ pub fn derive_serialize_update_impl(input: &DeriveInput) -> Result<TokenStream,
                 field_types,
                 field_literals,
                 field_indices: _,
-            }) => Ok(quote! {
-                #imp {
-                    fn serialize_update<'w,'en>(&self, stream:&mut Self::Stream, encoder: #any_gen_encoder_type<'w,'en, W>, mut ctx: #context_type) -> #result_type<()> {
-                        let mut encoder = encoder.encode_struct( #type_name, &[
-                                #(
-                                    #field_literals
-                                ),*
-                            ])?;
-                        #(
-                            <#field_types as #serialize_update_trait<W>>::serialize_update(
-                                &self.#field_idents,
-                                &mut stream.#field_idents,
-                                encoder.encode_field()?,ctx.reborrow()
-                            )?;
-                        )*
-                        encoder.end()?;
-                        ::std::result::Result::Ok(())
+                field_aliases: _,
+                field_missing_values: _,
+                field_tags,
+            }) => {
+                let encode_struct = if field_tags.is_empty() {
+                    quote!(encode_struct( #type_name, &[#(#field_literals),*]))
+                } else {
+                    quote!(encode_tagged_struct( #type_name, &[#(#field_literals),*], &[#(#field_tags),*]))
+                };
+                Ok(quote! {
+                    #imp {
+                        fn serialize_update<'w,'en>(&self, stream:&mut Self::Stream, encoder: #any_gen_encoder_type<'w,'en, W>, mut ctx: #context_type) -> #result_type<()> {
+                            let mut encoder = encoder.#encode_struct?;
+                            #(
+                                <#field_types as #serialize_update_trait<W>>::serialize_update(
+                                    &self.#field_idents,
+                                    &mut stream.#field_idents,
+                                    encoder.encode_field()?,ctx.reborrow()
+                                )?;
+                            )*
+                            encoder.end()?;
+                            ::std::result::Result::Ok(())
+                        }
                     }
-                }
-            }),
+                })
+            }
             ParsedFields::Unnamed(ParsedFieldsUnnamed {
                 field_count,
                 field_types,
@@ -92,6 +99,7 @@ pub fn derive_serialize_update_impl(input: &DeriveInput) -> Result<TokenStream,
             let ParsedEnum {
                 variant_literals,
                 variant_indices: _,
+                variant_aliases: _,
             } = ParsedEnum::new(data)?;
             let mut matches = vec![];
             for (variant_index, variant) in data.variants.iter().enumerate() {
@@ -108,7 +116,16 @@ pub fn derive_serialize_update_impl(input: &DeriveInput) -> Result<TokenStream,
                         field_types: _,
                         field_literals,
                         field_indices: _,
+                        field_aliases: _,
+                        field_missing_values: _,
+                        field_tags,
                     }) => {
+                        if !field_tags.is_empty() {
+                            return Err(syn::Error::new(
+                                variant_ident.span(),
+                                "tags are only supported on the fields of structs",
+                            ));
+                        }
                         matches.push(quote! {
                             Self::#variant_ident{ #(#field_idents),* } => {
                                 let mut encoder = encoder.encode_struct_variant( #type_name, &[#( #variant_literals ),*], #variant_index, &[#(#field_literals),*])?;
@@ -0,0 +1,92 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Data, DeriveInput, Fields};
+
+use crate::generics::DeriveGenerics;
+use crate::parsed_enum::ParsedEnum;
+use crate::parsed_fields::{ParsedFields, ParsedFieldsNamed, ParsedFieldsUnnamed};
+
+fn fields_shape(fields: &Fields, hasher: &TokenStream) -> syn::Result<TokenStream> {
+    let primitive_type = quote!(::marshal::PrimitiveType);
+    Ok(match ParsedFields::new(fields)? {
+        ParsedFields::Named(ParsedFieldsNamed {
+            field_idents: _,
+            field_var_idents: _,
+            field_types,
+            field_literals,
+            field_indices: _,
+            field_aliases: _,
+            field_missing_values: _,
+            field_tags: _,
+        }) => quote! {
+            #hasher.write_struct(&[#(#field_literals),*]);
+            #(
+                #hasher.visit::<#field_types>();
+            )*
+        },
+        ParsedFields::Unnamed(ParsedFieldsUnnamed {
+            field_count,
+            field_types,
+            field_index_idents: _,
+            field_named_idents: _,
+        }) => quote! {
+            #hasher.write_tuple(#field_count);
+            #(
+                #hasher.visit::<#field_types>();
+            )*
+        },
+        ParsedFields::Unit => quote! {
+            #hasher.write_primitive(#primitive_type::Unit);
+        },
+    })
+}
+
+pub fn derive_type_shape_impl(input: &DeriveInput) -> Result<TokenStream, syn::Error> {
+    let DeriveInput {
+        attrs: _,
+        vis: _,
+        ident: type_ident,
+        generics,
+        data,
+    } = input;
+    let type_shape_trait = quote!(::marshal::shape::TypeShape);
+    let DeriveGenerics {
+        generic_params,
+        generic_args,
+    } = DeriveGenerics::new(generics, &type_shape_trait);
+    let shape_hasher_type = quote!(::marshal::shape::ShapeHasher);
+    let hasher = quote!(hasher);
+
+    let body = match data {
+        Data::Struct(data) => fields_shape(&data.fields, &hasher)?,
+        Data::Enum(data) => {
+            let ParsedEnum {
+                variant_literals,
+                variant_indices: _,
+                variant_aliases: _,
+            } = ParsedEnum::new(data)?;
+            let variants = data
+                .variants
+                .iter()
+                .map(|variant| fields_shape(&variant.fields, &hasher))
+                .collect::<syn::Result<Vec<_>>>()?;
+            quote! {
+                #hasher.write_enum(&[#(#variant_literals),*]);
+                #(#variants)*
+            }
+        }
+        Data::Union(u) => {
+            return Err(syn::Error::new(
+                u.union_token.span,
+                "Cannot derive TypeShape for unions.",
+            ));
+        }
+    };
+    Ok(quote! {
+        impl<#(#generic_params),*> #type_shape_trait for #type_ident <#(#generic_args),*> {
+            fn hash_shape(#hasher: &mut #shape_hasher_type) {
+                #body
+            }
+        }
+    })
+}
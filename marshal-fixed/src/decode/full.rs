@@ -1,14 +1,34 @@
 use marshal::context::Context;
-use marshal::decode::{AnySpecDecoder, Decoder};
+use marshal::decode::{AnySpecDecoder, DecodeHint, Decoder, SimpleDecoderView, SpecDecoder};
 use marshal::decode::depth_budget::{DepthBudgetDecoder, WithDepthBudget};
 use marshal::decode::poison::PoisonDecoder;
 use marshal::derive_decoder_for_newtype;
+use marshal::shape::{fingerprint, TypeShape};
 use crate::decode::{FixedAnyDecoder, SimpleFixedSpecDecoder};
-use crate::{DeserializeFixed};
+use crate::{DeserializeFixed, FixedError, FixedIntEncoding, FixedPrimitive};
 pub struct FixedSpecDecoder<'de>(PoisonDecoder<DepthBudgetDecoder<SimpleFixedSpecDecoder<'de>>>);
 
 derive_decoder_for_newtype!(FixedSpecDecoder<'de>(PoisonDecoder<DepthBudgetDecoder<SimpleFixedSpecDecoder<'de>>>));
 
+impl<'de> FixedSpecDecoder<'de> {
+    /// Decodes a sequence of primitives without visiting each element.
+    #[inline]
+    pub(crate) fn decode_vec<T: FixedPrimitive>(
+        &mut self,
+        any: <Self as SpecDecoder<'de>>::AnyDecoder,
+    ) -> anyhow::Result<Vec<T>> {
+        let len = self.0.inner_mut().inner_mut().read_len()?;
+        match self.0.decode(any, DecodeHint::Tuple { len })? {
+            SimpleDecoderView::Seq(seq) => {
+                let result = self.0.inner_mut().inner_mut().read_slice(len)?;
+                self.0.decode_seq_end(seq)?;
+                Ok(result)
+            }
+            _ => Err(FixedError::UnsupportedHint.into()),
+        }
+    }
+}
+
 pub struct FixedDecoderBuilder<'de> {
     inner: FixedSpecDecoder<'de>,
     depth_budget: usize,
@@ -23,6 +43,22 @@ impl<'de> FixedDecoderBuilder<'de> {
             depth_budget: 100,
         }
     }
+    pub fn set_int_encoding(&mut self, int_encoding: FixedIntEncoding) -> &mut Self {
+        self.inner.0.inner_mut().inner_mut().set_int_encoding(int_encoding);
+        self
+    }
+    /// Reads a header written by
+    /// [FixedEncoderBuilder::write_fingerprint](crate::encode::full::FixedEncoderBuilder::write_fingerprint)
+    /// and checks that it matches `T` and the int encoding. Must be called after
+    /// [Self::set_int_encoding] and before [Self::build].
+    pub fn check_fingerprint<T: ?Sized + TypeShape>(&mut self) -> anyhow::Result<&mut Self> {
+        let expected = fingerprint::<T>();
+        let found = self.inner.0.inner_mut().inner_mut().read_fingerprint()?;
+        if expected != found {
+            return Err(FixedError::FingerprintMismatch { expected, found }.into());
+        }
+        Ok(self)
+    }
     pub fn build<'p>(&'p mut self) -> AnySpecDecoder<'p, 'de, FixedSpecDecoder<'de,>> {
         let any = self.inner.0.start(WithDepthBudget::new(
             self.depth_budget,
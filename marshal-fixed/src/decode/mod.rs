@@ -1,50 +1,114 @@
 pub mod full;
 
-use crate::{DiscriminantWidth, FixedError};
+use crate::{DiscriminantWidth, FixedError, FixedIntEncoding, FixedPrimitive, LeBytes};
 use marshal::decode::{DecodeHint, DecodeVariantHint, SimpleDecoderView, SpecDecoder};
 use marshal::{Primitive, PrimitiveType};
-use marshal_vu128::{ReadVu128, VU128_PADDING};
+use marshal_vu128::{ReadVu128, ToFromVu128, VU128_PADDING};
 use std::borrow::Cow;
 
 pub struct SimpleFixedSpecDecoder<'de> {
     data: &'de [u8],
+    int_encoding: FixedIntEncoding,
 }
 
 impl<'de> SimpleFixedSpecDecoder<'de> {
     #[inline]
     pub fn new(data: &'de [u8]) -> Self {
-        SimpleFixedSpecDecoder { data }
+        SimpleFixedSpecDecoder {
+            data,
+            int_encoding: FixedIntEncoding::Vu128,
+        }
+    }
+    pub fn set_int_encoding(&mut self, int_encoding: FixedIntEncoding) {
+        self.int_encoding = int_encoding;
+    }
+    #[inline]
+    fn read<T: ToFromVu128 + LeBytes>(&mut self) -> anyhow::Result<T> {
+        match self.int_encoding {
+            FixedIntEncoding::Vu128 => Ok(self.data.read_vu128()?),
+            FixedIntEncoding::LittleEndian => Ok(T::read_le(
+                self.data.take(..T::SIZE).ok_or(FixedError::UnexpectedEof)?,
+            )),
+        }
+    }
+    #[inline]
+    pub(crate) fn read_len(&mut self) -> anyhow::Result<usize> {
+        Ok(usize::try_from(self.read::<u64>()?)?)
+    }
+    /// Reads the elements of a sequence whose length has already been read.
+    #[inline]
+    pub(crate) fn read_slice<T: FixedPrimitive>(&mut self, len: usize) -> anyhow::Result<Vec<T>> {
+        match self.int_encoding {
+            FixedIntEncoding::Vu128 => {
+                let mut result = vec![];
+                self.data.read_vu128_slice(len, &mut result)?;
+                Ok(result)
+            }
+            FixedIntEncoding::LittleEndian => {
+                let size = len.checked_mul(T::SIZE).ok_or(FixedError::UnexpectedEof)?;
+                let bytes = self.data.take(..size).ok_or(FixedError::UnexpectedEof)?;
+                if cfg!(target_endian = "little") {
+                    let mut result = Vec::<T>::with_capacity(len);
+                    // Safety: FixedPrimitive types accept any bit pattern, and their in-memory
+                    // representation is the little-endian encoding on this target.
+                    unsafe {
+                        std::ptr::copy_nonoverlapping(
+                            bytes.as_ptr(),
+                            result.as_mut_ptr() as *mut u8,
+                            size,
+                        );
+                        result.set_len(len);
+                    }
+                    Ok(result)
+                } else {
+                    Ok(bytes.chunks_exact(T::SIZE).map(T::read_le).collect())
+                }
+            }
+        }
+    }
+    /// Reads the fingerprint header, checking that it was written with this int encoding.
+    pub(crate) fn read_fingerprint(&mut self) -> anyhow::Result<u64> {
+        let header = self.data.take(..9).ok_or(FixedError::UnexpectedEof)?;
+        let found = FixedIntEncoding::from_header_byte(header[8]);
+        if found != Some(self.int_encoding) {
+            return Err(FixedError::IntEncodingMismatch {
+                expected: self.int_encoding,
+                found,
+            }
+            .into());
+        }
+        Ok(u64::from_le_bytes(header[..8].try_into().unwrap()))
     }
     #[inline]
     fn decode_prim(&mut self, hint: PrimitiveType) -> anyhow::Result<Primitive> {
         match hint {
             PrimitiveType::Unit => Ok(Primitive::Unit),
-            PrimitiveType::Bool => Ok(Primitive::Bool(self.data.read_vu128()?)),
-            PrimitiveType::I8 => Ok(Primitive::I8(self.data.read_vu128()?)),
-            PrimitiveType::I16 => Ok(Primitive::I16(self.data.read_vu128()?)),
-            PrimitiveType::I32 => Ok(Primitive::I32(self.data.read_vu128()?)),
-            PrimitiveType::I64 => Ok(Primitive::I64(self.data.read_vu128()?)),
-            PrimitiveType::I128 => Ok(Primitive::I128(self.data.read_vu128()?)),
-            PrimitiveType::U8 => Ok(Primitive::U8(self.data.read_vu128()?)),
-            PrimitiveType::U16 => Ok(Primitive::U16(self.data.read_vu128()?)),
-            PrimitiveType::U32 => Ok(Primitive::U32(self.data.read_vu128()?)),
-            PrimitiveType::U64 => Ok(Primitive::U64(self.data.read_vu128()?)),
-            PrimitiveType::U128 => Ok(Primitive::U128(self.data.read_vu128()?)),
-            PrimitiveType::F32 => Ok(Primitive::F32(self.data.read_vu128()?)),
-            PrimitiveType::F64 => Ok(Primitive::F64(self.data.read_vu128()?)),
-            PrimitiveType::Char => Ok(Primitive::Char(self.data.read_vu128::<u32>()?.try_into()?)),
+            PrimitiveType::Bool => Ok(Primitive::Bool(self.read()?)),
+            PrimitiveType::I8 => Ok(Primitive::I8(self.read()?)),
+            PrimitiveType::I16 => Ok(Primitive::I16(self.read()?)),
+            PrimitiveType::I32 => Ok(Primitive::I32(self.read()?)),
+            PrimitiveType::I64 => Ok(Primitive::I64(self.read()?)),
+            PrimitiveType::I128 => Ok(Primitive::I128(self.read()?)),
+            PrimitiveType::U8 => Ok(Primitive::U8(self.read()?)),
+            PrimitiveType::U16 => Ok(Primitive::U16(self.read()?)),
+            PrimitiveType::U32 => Ok(Primitive::U32(self.read()?)),
+            PrimitiveType::U64 => Ok(Primitive::U64(self.read()?)),
+            PrimitiveType::U128 => Ok(Primitive::U128(self.read()?)),
+            PrimitiveType::F32 => Ok(Primitive::F32(self.read()?)),
+            PrimitiveType::F64 => Ok(Primitive::F64(self.read()?)),
+            PrimitiveType::Char => Ok(Primitive::Char(self.read::<u32>()?.try_into()?)),
         }
     }
     #[inline]
     fn decode_str(&mut self) -> anyhow::Result<Cow<'de, str>> {
-        let len = usize::try_from(self.data.read_vu128::<u64>()?)?;
+        let len = self.read_len()?;
         Ok(Cow::Borrowed(std::str::from_utf8(
             self.data.take(..len).ok_or(FixedError::UnexpectedEof)?,
         )?))
     }
     #[inline]
     fn decode_bytes(&mut self) -> anyhow::Result<Cow<'de, [u8]>> {
-        let len = usize::try_from(self.data.read_vu128::<u64>()?)?;
+        let len = self.read_len()?;
         Ok(Cow::Borrowed(
             self.data.take(..len).ok_or(FixedError::UnexpectedEof)?,
         ))
@@ -52,15 +116,19 @@ impl<'de> SimpleFixedSpecDecoder<'de> {
     #[inline]
     fn decode_discriminant(&mut self, variants: usize) -> anyhow::Result<usize> {
         Ok(match DiscriminantWidth::from_max(variants) {
-            DiscriminantWidth::U8 => self.data.read_vu128::<u8>()? as usize,
-            DiscriminantWidth::U16 => self.data.read_vu128::<u16>()? as usize,
-            DiscriminantWidth::U32 => self.data.read_vu128::<u32>()? as usize,
-            DiscriminantWidth::U64 => self.data.read_vu128::<u64>()? as usize,
+            DiscriminantWidth::U8 => self.read::<u8>()? as usize,
+            DiscriminantWidth::U16 => self.read::<u16>()? as usize,
+            DiscriminantWidth::U32 => self.read::<u32>()? as usize,
+            DiscriminantWidth::U64 => self.read::<u64>()? as usize,
         })
     }
     #[inline]
     pub fn end(self) -> anyhow::Result<()> {
-        if self.data.len() > VU128_PADDING {
+        let padding = match self.int_encoding {
+            FixedIntEncoding::Vu128 => VU128_PADDING,
+            FixedIntEncoding::LittleEndian => 0,
+        };
+        if self.data.len() > padding {
             return Err(FixedError::TrailingData.into());
         }
         if self.data.iter().any(|x| *x != 0) {
@@ -137,7 +205,7 @@ impl<'de> SpecDecoder<'de> for SimpleFixedSpecDecoder<'de> {
             DecodeHint::String => Ok(SimpleDecoderView::String(self.decode_str()?)),
             DecodeHint::Bytes => Ok(SimpleDecoderView::Bytes(self.decode_bytes()?)),
             DecodeHint::Option => {
-                if self.data.read_vu128::<bool>()? {
+                if self.read::<bool>()? {
                     Ok(SimpleDecoderView::Some(()))
                 } else {
                     Ok(SimpleDecoderView::None)
@@ -145,7 +213,7 @@ impl<'de> SpecDecoder<'de> for SimpleFixedSpecDecoder<'de> {
             }
             DecodeHint::UnitStruct { .. } => Ok(SimpleDecoderView::Primitive(Primitive::Unit)),
             DecodeHint::Seq => {
-                let len = self.data.read_vu128::<u64>()? as usize;
+                let len = self.read_len()?;
                 Ok(SimpleDecoderView::Seq(FixedSeqDecoder { len }))
             }
             DecodeHint::Tuple { len } => Ok(SimpleDecoderView::Seq(FixedSeqDecoder { len })),
@@ -153,7 +221,7 @@ impl<'de> SpecDecoder<'de> for SimpleFixedSpecDecoder<'de> {
                 Ok(SimpleDecoderView::Seq(FixedSeqDecoder { len }))
             }
             DecodeHint::Map => Ok(SimpleDecoderView::Map(FixedMapDecoder {
-                len: self.data.read_vu128::<u64>()? as usize,
+                len: self.read_len()?,
             })),
             DecodeHint::Struct { name: _, fields } => Ok(SimpleDecoderView::Seq(FixedSeqDecoder {
                 len: fields.len(),
@@ -1,25 +1,55 @@
 use crate::encode::SimpleFixedSpecEncoder;
-use crate::SerializeFixed;
+use crate::{FixedIntEncoding, FixedPrimitive, SerializeFixed};
 use marshal::context::Context;
 use marshal::derive_encoder_for_newtype;
 use marshal::encode::poison::PoisonEncoder;
-use marshal::encode::{AnySpecEncoder, Encoder};
+use marshal::encode::{AnySpecEncoder, Encoder, SpecEncoder};
+use marshal::shape::{fingerprint, TypeShape};
 
 pub struct FixedSpecEncoder(PoisonEncoder<SimpleFixedSpecEncoder>);
 
 derive_encoder_for_newtype!(FixedSpecEncoder<>(PoisonEncoder<SimpleFixedSpecEncoder>));
 
+impl FixedSpecEncoder {
+    /// Encodes a sequence of primitives without visiting each element.
+    #[inline]
+    pub(crate) fn encode_slice<T: FixedPrimitive>(
+        &mut self,
+        any: <Self as SpecEncoder>::AnySpecEncoder,
+        slice: &[T],
+    ) -> anyhow::Result<()> {
+        let seq = self.0.encode_seq(any, slice.len())?;
+        self.0.inner_mut().write_slice(slice);
+        self.0.seq_end(seq)
+    }
+}
+
 pub struct FixedEncoderBuilder {
     inner: FixedSpecEncoder,
+    fingerprint: Option<u64>,
 }
 
 impl FixedEncoderBuilder {
     pub fn new() -> Self {
         FixedEncoderBuilder {
             inner: FixedSpecEncoder(PoisonEncoder::new(SimpleFixedSpecEncoder::new())),
+            fingerprint: None,
         }
     }
+    pub fn set_int_encoding(&mut self, int_encoding: FixedIntEncoding) -> &mut Self {
+        self.inner.0.inner_mut().set_int_encoding(int_encoding);
+        self
+    }
+    /// Writes a header with the shape fingerprint of `T` and the int encoding. The header is
+    /// written by [Self::build].
+    pub fn write_fingerprint<T: ?Sized + TypeShape>(&mut self) -> &mut Self {
+        self.fingerprint = Some(fingerprint::<T>());
+        self
+    }
     pub fn build<'w>(&'w mut self) -> AnySpecEncoder<'w, FixedSpecEncoder> {
+        if let Some(fingerprint) = self.fingerprint.take() {
+            self.inner.0.inner_mut().write_fingerprint(fingerprint);
+        }
         let any = self.inner.0.start(());
         AnySpecEncoder::new(&mut self.inner, any)
     }
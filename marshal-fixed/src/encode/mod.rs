@@ -1,30 +1,79 @@
 pub mod full;
 
-use crate::DiscriminantWidth;
+use crate::{DiscriminantWidth, FixedIntEncoding, FixedPrimitive, LeBytes};
 use marshal::encode::SpecEncoder;
 use marshal::Primitive;
-use marshal_vu128::{WriteVu128, VU128_PADDING};
+use marshal_vu128::{ToFromVu128, WriteVu128, VU128_PADDING};
 
 pub struct SimpleFixedSpecEncoder {
     output: Vec<u8>,
+    int_encoding: FixedIntEncoding,
 }
 
 impl SimpleFixedSpecEncoder {
     pub fn new() -> Self {
-        SimpleFixedSpecEncoder { output: vec![] }
+        SimpleFixedSpecEncoder {
+            output: vec![],
+            int_encoding: FixedIntEncoding::Vu128,
+        }
+    }
+    pub fn set_int_encoding(&mut self, int_encoding: FixedIntEncoding) {
+        self.int_encoding = int_encoding;
+    }
+    #[inline]
+    fn write<T: ToFromVu128 + LeBytes>(&mut self, value: T) {
+        match self.int_encoding {
+            FixedIntEncoding::Vu128 => self.output.write_vu128(value),
+            FixedIntEncoding::LittleEndian => value.write_le(&mut self.output),
+        }
+    }
+    #[inline]
+    fn write_len(&mut self, len: usize) {
+        self.write(len as u64);
+    }
+    /// Writes the elements of a sequence whose length has already been written.
+    #[inline]
+    pub(crate) fn write_slice<T: FixedPrimitive>(&mut self, slice: &[T]) {
+        match self.int_encoding {
+            FixedIntEncoding::Vu128 => self.output.write_vu128_slice(slice),
+            FixedIntEncoding::LittleEndian => {
+                if cfg!(target_endian = "little") {
+                    // Safety: FixedPrimitive types have no padding, and their in-memory
+                    // representation is the little-endian encoding on this target.
+                    let bytes = unsafe {
+                        std::slice::from_raw_parts(
+                            slice.as_ptr() as *const u8,
+                            std::mem::size_of_val(slice),
+                        )
+                    };
+                    self.output.extend_from_slice(bytes);
+                } else {
+                    for x in slice {
+                        x.write_le(&mut self.output);
+                    }
+                }
+            }
+        }
+    }
+    /// Writes the fingerprint header, which also records the int encoding.
+    pub(crate) fn write_fingerprint(&mut self, fingerprint: u64) {
+        self.output.extend_from_slice(&fingerprint.to_le_bytes());
+        self.output.push(self.int_encoding.header_byte());
     }
     #[inline]
     pub fn encode_discriminant(&mut self, index: usize, max: usize) {
         match DiscriminantWidth::from_max(max) {
-            DiscriminantWidth::U8 => self.output.write_vu128(index as u8),
-            DiscriminantWidth::U16 => self.output.write_vu128(index as u16),
-            DiscriminantWidth::U32 => self.output.write_vu128(index as u32),
-            DiscriminantWidth::U64 => self.output.write_vu128(index as u64),
+            DiscriminantWidth::U8 => self.write(index as u8),
+            DiscriminantWidth::U16 => self.write(index as u16),
+            DiscriminantWidth::U32 => self.write(index as u32),
+            DiscriminantWidth::U64 => self.write(index as u64),
         }
     }
     #[inline]
     pub fn end(mut self) -> anyhow::Result<Vec<u8>> {
-        self.output.resize(self.output.len() + VU128_PADDING, 0);
+        if self.int_encoding == FixedIntEncoding::Vu128 {
+            self.output.resize(self.output.len() + VU128_PADDING, 0);
+        }
         Ok(self.output)
     }
 }
@@ -46,41 +95,41 @@ impl SpecEncoder for SimpleFixedSpecEncoder {
     fn encode_prim(&mut self, _: Self::AnySpecEncoder, prim: Primitive) -> anyhow::Result<()> {
         match prim {
             Primitive::Unit => {}
-            Primitive::Bool(x) => self.output.write_vu128(x),
-            Primitive::I8(x) => self.output.write_vu128(x),
-            Primitive::I16(x) => self.output.write_vu128(x),
-            Primitive::I32(x) => self.output.write_vu128(x),
-            Primitive::I64(x) => self.output.write_vu128(x),
-            Primitive::I128(x) => self.output.write_vu128(x),
-            Primitive::U8(x) => self.output.write_vu128(x),
-            Primitive::U16(x) => self.output.write_vu128(x),
-            Primitive::U32(x) => self.output.write_vu128(x),
-            Primitive::U64(x) => self.output.write_vu128(x),
-            Primitive::U128(x) => self.output.write_vu128(x),
-            Primitive::F32(x) => self.output.write_vu128(x),
-            Primitive::F64(x) => self.output.write_vu128(x),
-            Primitive::Char(x) => self.output.write_vu128(x as u32),
+            Primitive::Bool(x) => self.write(x),
+            Primitive::I8(x) => self.write(x),
+            Primitive::I16(x) => self.write(x),
+            Primitive::I32(x) => self.write(x),
+            Primitive::I64(x) => self.write(x),
+            Primitive::I128(x) => self.write(x),
+            Primitive::U8(x) => self.write(x),
+            Primitive::U16(x) => self.write(x),
+            Primitive::U32(x) => self.write(x),
+            Primitive::U64(x) => self.write(x),
+            Primitive::U128(x) => self.write(x),
+            Primitive::F32(x) => self.write(x),
+            Primitive::F64(x) => self.write(x),
+            Primitive::Char(x) => self.write(x as u32),
         }
         Ok(())
     }
 
     #[inline]
     fn encode_str(&mut self, _: Self::AnySpecEncoder, s: &str) -> anyhow::Result<()> {
-        self.output.write_vu128(s.len() as u64);
+        self.write_len(s.len());
         self.output.extend_from_slice(s.as_bytes());
         Ok(())
     }
 
     #[inline]
     fn encode_bytes(&mut self, _: Self::AnySpecEncoder, s: &[u8]) -> anyhow::Result<()> {
-        self.output.write_vu128(s.len() as u64);
+        self.write_len(s.len());
         self.output.extend_from_slice(s);
         Ok(())
     }
 
     #[inline]
     fn encode_none(&mut self, _: Self::AnySpecEncoder) -> anyhow::Result<()> {
-        self.output.write_vu128(false);
+        self.write(false);
         Ok(())
     }
 
@@ -89,7 +138,7 @@ impl SpecEncoder for SimpleFixedSpecEncoder {
         &mut self,
         _: Self::AnySpecEncoder,
     ) -> anyhow::Result<(Self::AnySpecEncoder, Self::SomeCloser)> {
-        self.output.write_vu128(true);
+        self.write(true);
         Ok(((), ()))
     }
 
@@ -166,8 +215,7 @@ impl SpecEncoder for SimpleFixedSpecEncoder {
         _: Self::AnySpecEncoder,
         len: usize,
     ) -> anyhow::Result<Self::SeqEncoder> {
-        self.output
-            .write_vu128(len as u64);
+        self.write_len(len);
         Ok(())
     }
 
@@ -186,8 +234,7 @@ impl SpecEncoder for SimpleFixedSpecEncoder {
         _: Self::AnySpecEncoder,
         len: usize,
     ) -> anyhow::Result<Self::MapEncoder> {
-        self.output
-            .write_vu128(len as u64);
+        self.write_len(len);
         Ok(())
     }
 
@@ -1,15 +1,46 @@
 #![feature(never_type)]
 #![feature(trait_alias)]
 #![feature(slice_take)]
+#![feature(specialization)]
+#![allow(incomplete_features)]
 
 use std::fmt::{Display, Formatter};
 use marshal::de::Deserialize;
 use marshal::ser::Serialize;
 use crate::decode::full::FixedDecoder;
 use crate::encode::full::FixedEncoder;
+use marshal_vu128::ToFromVu128;
 
 pub mod decode;
 pub mod encode;
+mod slice;
+
+/// How primitives, lengths and discriminants are written.
+#[derive(Debug, Copy, Clone, Eq, Ord, PartialEq, PartialOrd, Hash, Default)]
+pub enum FixedIntEncoding {
+    /// Variable-length vu128 encoding. The output is followed by padding.
+    #[default]
+    Vu128,
+    /// Fixed-width little-endian encoding with no padding. Lengths are `u64`.
+    LittleEndian,
+}
+
+impl FixedIntEncoding {
+    /// The byte that identifies this encoding in a fingerprint header.
+    fn header_byte(self) -> u8 {
+        match self {
+            FixedIntEncoding::Vu128 => 0,
+            FixedIntEncoding::LittleEndian => 1,
+        }
+    }
+    fn from_header_byte(byte: u8) -> Option<Self> {
+        match byte {
+            0 => Some(FixedIntEncoding::Vu128),
+            1 => Some(FixedIntEncoding::LittleEndian),
+            _ => None,
+        }
+    }
+}
 
 #[derive(Debug)]
 pub enum FixedError {
@@ -17,6 +48,11 @@ pub enum FixedError {
     TrailingData,
     NonZeroPadding,
     UnexpectedEof,
+    FingerprintMismatch { expected: u64, found: u64 },
+    IntEncodingMismatch {
+        expected: FixedIntEncoding,
+        found: Option<FixedIntEncoding>,
+    },
 }
 
 impl Display for FixedError {
@@ -49,6 +85,62 @@ impl DiscriminantWidth {
     }
 }
 
+/// A primitive with a fixed-width little-endian representation.
+trait LeBytes: Sized {
+    const SIZE: usize;
+    fn write_le(self, output: &mut Vec<u8>);
+    fn read_le(input: &[u8]) -> Self;
+}
+
+macro_rules! impl_le_bytes {
+    ($($ty:ty),*) => {
+        $(
+            impl LeBytes for $ty {
+                const SIZE: usize = size_of::<$ty>();
+                #[inline]
+                fn write_le(self, output: &mut Vec<u8>) {
+                    output.extend_from_slice(&self.to_le_bytes());
+                }
+                #[inline]
+                fn read_le(input: &[u8]) -> Self {
+                    <$ty>::from_le_bytes(input.try_into().unwrap())
+                }
+            }
+        )*
+    };
+}
+
+impl_le_bytes!(i8, i16, i32, i64, i128, u8, u16, u32, u64, u128, f32, f64);
+
+impl LeBytes for bool {
+    const SIZE: usize = 1;
+    #[inline]
+    fn write_le(self, output: &mut Vec<u8>) {
+        output.push(self as u8);
+    }
+    #[inline]
+    fn read_le(input: &[u8]) -> Self {
+        input[0] != 0
+    }
+}
+
+/// A primitive whose slices can be copied directly to and from little-endian output.
+///
+/// # Safety
+/// Every bit pattern must be a valid value and the type must contain no padding.
+unsafe trait FixedPrimitive: LeBytes + ToFromVu128 {}
+
+unsafe impl FixedPrimitive for i8 {}
+unsafe impl FixedPrimitive for i16 {}
+unsafe impl FixedPrimitive for i32 {}
+unsafe impl FixedPrimitive for i64 {}
+unsafe impl FixedPrimitive for i128 {}
+unsafe impl FixedPrimitive for u16 {}
+unsafe impl FixedPrimitive for u32 {}
+unsafe impl FixedPrimitive for u64 {}
+unsafe impl FixedPrimitive for u128 {}
+unsafe impl FixedPrimitive for f32 {}
+unsafe impl FixedPrimitive for f64 {}
 
 pub trait SerializeFixed = Serialize<FixedEncoder>;
 pub trait DeserializeFixed = Deserialize<FixedDecoder>;
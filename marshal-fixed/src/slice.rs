@@ -0,0 +1,49 @@
+use crate::decode::full::FixedDecoder;
+use crate::encode::full::FixedEncoder;
+use marshal::context::Context;
+use marshal::de::Deserialize;
+use marshal::decode::AnyDecoder;
+use marshal::encode::AnyEncoder;
+use marshal::ser::Serialize;
+
+macro_rules! impl_fixed_slice {
+    ($($ty:ty),*) => {
+        $(
+            impl Serialize<FixedEncoder> for [$ty] {
+                #[inline]
+                fn serialize<'w, 'en>(
+                    &self,
+                    e: AnyEncoder<'w, 'en, FixedEncoder>,
+                    _ctx: Context,
+                ) -> anyhow::Result<()> {
+                    let (encoder, any) = e.into_raw();
+                    encoder.encode_slice(any, self)
+                }
+            }
+
+            impl Serialize<FixedEncoder> for Vec<$ty> {
+                #[inline]
+                fn serialize<'w, 'en>(
+                    &self,
+                    e: AnyEncoder<'w, 'en, FixedEncoder>,
+                    ctx: Context,
+                ) -> anyhow::Result<()> {
+                    self.as_slice().serialize(e, ctx)
+                }
+            }
+
+            impl Deserialize<FixedDecoder> for Vec<$ty> {
+                #[inline]
+                fn deserialize<'p, 'de>(
+                    d: AnyDecoder<'p, 'de, FixedDecoder>,
+                    _ctx: Context,
+                ) -> anyhow::Result<Self> {
+                    let (decoder, any) = d.into_raw();
+                    decoder.decode_vec(any)
+                }
+            }
+        )*
+    };
+}
+
+impl_fixed_slice!(i8, i16, i32, i64, i128, u16, u32, u64, u128, f32, f64);
@@ -3,9 +3,11 @@
 use marshal::context::OwnedContext;
 use marshal::de::Deserialize;
 use marshal::ser::Serialize;
-use marshal::{Deserialize, Serialize};
+use marshal::shape::fingerprint;
+use marshal::{Deserialize, Serialize, TypeShape};
 use marshal_fixed::decode::full::{FixedDecoder, FixedDecoderBuilder};
 use marshal_fixed::encode::full::{FixedEncoder, FixedEncoderBuilder};
+use marshal_fixed::{FixedError, FixedIntEncoding};
 use marshal_vu128::VU128_PADDING;
 use std::fmt::Debug;
 use std::time::{Duration, Instant, SystemTime};
@@ -96,3 +98,212 @@ fn test_rt() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+fn encode<T: ?Sized + Serialize<FixedEncoder>>(
+    input: &T,
+    int_encoding: FixedIntEncoding,
+) -> anyhow::Result<Vec<u8>> {
+    let mut w = FixedEncoderBuilder::new();
+    w.set_int_encoding(int_encoding);
+    input.serialize(w.build(), OwnedContext::new().borrow())?;
+    w.end()
+}
+
+fn decode<T: Deserialize<FixedDecoder>>(
+    input: &[u8],
+    int_encoding: FixedIntEncoding,
+) -> anyhow::Result<T> {
+    let mut p = FixedDecoderBuilder::new(input);
+    p.set_int_encoding(int_encoding);
+    p.deserialize(OwnedContext::new().borrow())
+}
+
+#[track_caller]
+fn test_round_trip_with<
+    T: Debug + PartialEq + Serialize<FixedEncoder> + Deserialize<FixedDecoder>,
+>(
+    input: T,
+    int_encoding: FixedIntEncoding,
+) -> anyhow::Result<Vec<u8>> {
+    let found = encode(&input, int_encoding)?;
+    assert_eq!(input, decode::<T>(&found, int_encoding)?);
+    Ok(found)
+}
+
+#[test]
+fn test_little_endian() -> anyhow::Result<()> {
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    enum Shape {
+        Point,
+        Circle { radius: f32 },
+    }
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Sample {
+        id: u16,
+        name: String,
+        shape: Option<Shape>,
+        values: Vec<f64>,
+        flags: Vec<bool>,
+    }
+    let found = test_round_trip_with(
+        Sample {
+            id: 0x0102,
+            name: "ab".to_string(),
+            shape: Some(Shape::Circle { radius: 1.0 }),
+            values: vec![1.5, -2.0],
+            flags: vec![true, false],
+        },
+        FixedIntEncoding::LittleEndian,
+    )?;
+    let mut expected = vec![2, 1];
+    expected.extend_from_slice(&2u64.to_le_bytes());
+    expected.extend_from_slice(b"ab");
+    expected.extend_from_slice(&[1, 1]);
+    expected.extend_from_slice(&1.0f32.to_le_bytes());
+    expected.extend_from_slice(&2u64.to_le_bytes());
+    expected.extend_from_slice(&1.5f64.to_le_bytes());
+    expected.extend_from_slice(&(-2.0f64).to_le_bytes());
+    expected.extend_from_slice(&2u64.to_le_bytes());
+    expected.extend_from_slice(&[1, 0]);
+    assert_eq!(found, expected);
+    test_round_trip_with(
+        (i8::MIN, u128::MAX, 'x', Shape::Point),
+        FixedIntEncoding::LittleEndian,
+    )?;
+    Ok(())
+}
+
+#[test]
+fn test_slice_fast_path() -> anyhow::Result<()> {
+    for int_encoding in [FixedIntEncoding::Vu128, FixedIntEncoding::LittleEndian] {
+        let fast = test_round_trip_with(vec![0u32, 1, 300, u32::MAX], int_encoding)?;
+        let slow = encode(&vec![(0u32,), (1,), (300,), (u32::MAX,)], int_encoding)?;
+        assert_eq!(fast, slow);
+        assert_eq!(decode::<Vec<(u32,)>>(&fast, int_encoding)?.len(), 4);
+        let fast = test_round_trip_with(vec![-1i128, 2, i128::MAX], int_encoding)?;
+        let slow = encode(&vec![(-1i128,), (2,), (i128::MAX,)], int_encoding)?;
+        assert_eq!(fast, slow);
+        let fast = encode(&[0.5f32, 2.0][..], int_encoding)?;
+        let slow = encode(&vec![(0.5f32,), (2.0,)], int_encoding)?;
+        assert_eq!(fast, slow);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_little_endian_errors() -> anyhow::Result<()> {
+    let found = encode(&vec![1u64, 2, 3], FixedIntEncoding::LittleEndian)?;
+    assert!(matches!(
+        decode::<Vec<u64>>(&found[..found.len() - 1], FixedIntEncoding::LittleEndian)
+            .unwrap_err()
+            .downcast::<FixedError>()?,
+        FixedError::UnexpectedEof
+    ));
+    let mut padded = found.clone();
+    padded.push(0);
+    assert!(matches!(
+        decode::<Vec<u64>>(&padded, FixedIntEncoding::LittleEndian)
+            .unwrap_err()
+            .downcast::<FixedError>()?,
+        FixedError::TrailingData
+    ));
+    assert!(decode::<Vec<u64>>(&u64::MAX.to_le_bytes(), FixedIntEncoding::LittleEndian).is_err());
+    let huge = encode(&u64::MAX, FixedIntEncoding::Vu128)?;
+    assert!(decode::<Vec<u64>>(&huge, FixedIntEncoding::Vu128).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_fingerprint() -> anyhow::Result<()> {
+    #[derive(Serialize, Deserialize, TypeShape, Debug, PartialEq)]
+    struct V1 {
+        id: u32,
+        tags: Vec<String>,
+    }
+    #[allow(dead_code)]
+    #[derive(TypeShape)]
+    struct Renamed {
+        ident: u32,
+        tags: Vec<String>,
+    }
+    #[allow(dead_code)]
+    #[derive(TypeShape)]
+    struct Widened {
+        id: u64,
+        tags: Vec<String>,
+    }
+    #[allow(dead_code)]
+    #[derive(TypeShape)]
+    struct V1Again {
+        #[marshal(rename = "id")]
+        key: u32,
+        tags: Vec<String>,
+    }
+    #[derive(Serialize, Deserialize, TypeShape, Debug, PartialEq)]
+    enum Tree<T> {
+        Leaf(T),
+        Node { children: Vec<Tree<T>> },
+    }
+    #[allow(dead_code)]
+    #[derive(TypeShape)]
+    enum TreeWithEmpty<T> {
+        Leaf(T),
+        Node { children: Vec<TreeWithEmpty<T>> },
+        Empty,
+    }
+
+    // Fingerprints are stored, so they must not change between releases.
+    assert_eq!(fingerprint::<u32>(), 0x08327f07b4eb60a2);
+    assert_eq!(fingerprint::<V1>(), 0xac8d82ef3e3cf99e);
+    assert_eq!(fingerprint::<Tree<u8>>(), 0x99a27e8d829f3f7e);
+    assert_eq!(fingerprint::<V1>(), fingerprint::<V1Again>());
+    assert_ne!(fingerprint::<V1>(), fingerprint::<Renamed>());
+    assert_ne!(fingerprint::<V1>(), fingerprint::<Widened>());
+    assert_ne!(fingerprint::<Vec<u8>>(), fingerprint::<Vec<i8>>());
+    assert_ne!(fingerprint::<(u8, (u8, u8))>(), fingerprint::<((u8, u8), u8)>());
+    assert_ne!(fingerprint::<Tree<u8>>(), fingerprint::<Tree<u16>>());
+    assert_ne!(fingerprint::<Tree<u8>>(), fingerprint::<TreeWithEmpty<u8>>());
+
+    let input = Tree::Node {
+        children: vec![Tree::Leaf(1u8), Tree::Node { children: vec![] }],
+    };
+    let mut w = FixedEncoderBuilder::new();
+    w.write_fingerprint::<Tree<u8>>();
+    let found = w.serialize(&input, OwnedContext::new().borrow())?;
+    assert_eq!(&found[..8], &fingerprint::<Tree<u8>>().to_le_bytes());
+    assert_eq!(found[8], 0);
+
+    let mut p = FixedDecoderBuilder::new(&found);
+    p.check_fingerprint::<Tree<u8>>()?;
+    assert_eq!(input, p.deserialize::<Tree<u8>>(OwnedContext::new().borrow())?);
+
+    let mut p = FixedDecoderBuilder::new(&found);
+    let error = p.check_fingerprint::<V1>().err().unwrap().downcast::<FixedError>()?;
+    assert!(matches!(
+        error,
+        FixedError::FingerprintMismatch { expected, found }
+            if expected == fingerprint::<V1>() && found == fingerprint::<Tree<u8>>()
+    ));
+
+    let mut p = FixedDecoderBuilder::new(&found);
+    p.set_int_encoding(FixedIntEncoding::LittleEndian);
+    let error = p.check_fingerprint::<Tree<u8>>().err().unwrap().downcast::<FixedError>()?;
+    assert!(matches!(
+        error,
+        FixedError::IntEncodingMismatch {
+            expected: FixedIntEncoding::LittleEndian,
+            found: Some(FixedIntEncoding::Vu128)
+        }
+    ));
+
+    let mut w = FixedEncoderBuilder::new();
+    w.write_fingerprint::<Tree<u8>>();
+    w.set_int_encoding(FixedIntEncoding::LittleEndian);
+    let found = w.serialize(&input, OwnedContext::new().borrow())?;
+    assert_eq!(found[8], 1);
+    let mut p = FixedDecoderBuilder::new(&found);
+    p.set_int_encoding(FixedIntEncoding::LittleEndian);
+    p.check_fingerprint::<Tree<u8>>()?;
+    assert_eq!(input, p.deserialize::<Tree<u8>>(OwnedContext::new().borrow())?);
+    Ok(())
+}
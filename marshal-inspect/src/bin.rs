@@ -0,0 +1,321 @@
+use marshal_bin::TypeTag;
+use num_traits::FromPrimitive;
+
+use crate::{Dump, InspectError};
+
+/// The deepest nesting of values that is dumped, so that hostile input cannot overflow the stack.
+const MAX_DEPTH: usize = 100;
+
+/// Dumps `marshal-bin` messages, keeping the definitions from each message for the next one,
+/// like a [BinDecoderSchema](marshal_bin::decode::BinDecoderSchema).
+pub struct BinInspector {
+    enum_defs: Vec<Vec<String>>,
+    strings: Vec<Option<String>>,
+}
+
+struct BinDump<'a> {
+    dump: Dump,
+    inspector: &'a mut BinInspector,
+    local_enum_defs: Option<Vec<Vec<String>>>,
+}
+
+/// Dumps a single `marshal-bin` message that was encoded with a fresh schema.
+pub fn inspect_bin(data: &[u8]) -> String {
+    BinInspector::new().inspect(data)
+}
+
+impl BinInspector {
+    pub fn new() -> Self {
+        BinInspector {
+            enum_defs: vec![],
+            strings: vec![],
+        }
+    }
+    /// Loads the definitions and interned strings from a schema saved by
+    /// [BinEncoderSchema::save](marshal_bin::encode::BinEncoderSchema::save) or
+    /// [BinDecoderSchema::save](marshal_bin::decode::BinDecoderSchema::save).
+    pub fn load_schema(&mut self, saved: &[u8]) -> anyhow::Result<()> {
+        let defs = saved.get(8..).ok_or(InspectError::Eof)?;
+        let mut dump = BinDump {
+            dump: Dump::new(defs),
+            inspector: self,
+            local_enum_defs: None,
+        };
+        let _string_capacity = dump.dump.read_usize()?;
+        while dump.dump.offset < dump.dump.len && dump.dump.data[dump.dump.offset] != 0 {
+            let start = dump.dump.offset;
+            match dump.read_tag()? {
+                TypeTag::EnumDef => {
+                    dump.read_enum_def(0)?;
+                }
+                TypeTag::StringDef => {
+                    dump.read_string_def(start, 0)?;
+                }
+                tag => return Err(InspectError::BadTag(tag as u8).into()),
+            }
+        }
+        Ok(())
+    }
+    /// Dumps one message as a tree with one item per line.
+    pub fn inspect(&mut self, data: &[u8]) -> String {
+        let mut dump = BinDump {
+            dump: Dump::new(data),
+            inspector: self,
+            local_enum_defs: None,
+        };
+        let result = dump.value(0, "");
+        dump.dump.finish(result)
+    }
+}
+
+impl<'a> BinDump<'a> {
+    fn read_tag(&mut self) -> anyhow::Result<TypeTag> {
+        let byte = *self.dump.data.get(self.dump.offset).ok_or(InspectError::Eof)?;
+        let tag = TypeTag::from_u8(byte).ok_or(InspectError::BadTag(byte))?;
+        self.dump.advance(1)?;
+        Ok(tag)
+    }
+    fn enum_defs(&mut self) -> &mut Vec<Vec<String>> {
+        match &mut self.local_enum_defs {
+            Some(local) => local,
+            None => &mut self.inspector.enum_defs,
+        }
+    }
+    fn read_enum_def(&mut self, depth: usize) -> anyhow::Result<()> {
+        let start = self.dump.offset - 1;
+        let count = self.dump.read_usize()?;
+        let fields = (0..count)
+            .map(|_| self.dump.read_str())
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        let index = self.enum_defs().len();
+        self.dump.line(
+            start,
+            depth,
+            format_args!("enum definition #{} [{}]", index, fields.join(", ")),
+        );
+        self.enum_defs().push(fields);
+        Ok(())
+    }
+    fn read_string_def(&mut self, start: usize, depth: usize) -> anyhow::Result<()> {
+        let index = self.dump.read_usize()?;
+        let value = self.dump.read_str()?;
+        // Slots are assigned in order, so a definition can only replace a string or add the next.
+        if index > self.inspector.strings.len() {
+            return Err(InspectError::BadStringDef(index).into());
+        }
+        self.dump.line(
+            start,
+            depth,
+            format_args!("string definition #{} {:?}", index, value),
+        );
+        let strings = &mut self.inspector.strings;
+        if index == strings.len() {
+            strings.push(Some(value));
+        } else {
+            strings[index] = Some(value);
+        }
+        Ok(())
+    }
+    fn read_enum_def_ref(&mut self) -> anyhow::Result<(usize, Vec<String>)> {
+        let index = self.dump.read_usize()?;
+        let fields = self
+            .enum_defs()
+            .get(index)
+            .ok_or(InspectError::NoSuchEnumDef(index))?
+            .clone();
+        Ok((index, fields))
+    }
+    fn value(&mut self, depth: usize, label: &str) -> anyhow::Result<()> {
+        if depth > MAX_DEPTH {
+            return self.dump.fail(self.dump.offset, Err(InspectError::TooDeep.into()));
+        }
+        loop {
+            let start = self.dump.offset;
+            let result = self.item(start, depth, label);
+            if self.dump.fail(start, result)? {
+                return Ok(());
+            }
+        }
+    }
+    fn packed_element(
+        &mut self,
+        start: usize,
+        depth: usize,
+        index: usize,
+        element: TypeTag,
+    ) -> anyhow::Result<()> {
+        macro_rules! prim {
+            ($name:literal, $ty:ty) => {{
+                let value = self.dump.read_vu128::<$ty>()?;
+                self.dump.line(start, depth, format_args!("[{}] {} {:?}", index, $name, value));
+            }};
+        }
+        match element {
+            TypeTag::I8 => prim!("i8", i8),
+            TypeTag::I16 => prim!("i16", i16),
+            TypeTag::I32 => prim!("i32", i32),
+            TypeTag::I64 => prim!("i64", i64),
+            TypeTag::I128 => prim!("i128", i128),
+            TypeTag::U16 => prim!("u16", u16),
+            TypeTag::U32 => prim!("u32", u32),
+            TypeTag::U64 => prim!("u64", u64),
+            TypeTag::U128 => prim!("u128", u128),
+            TypeTag::F32 => prim!("f32", f32),
+            TypeTag::F64 => prim!("f64", f64),
+            _ => return Err(InspectError::BadTag(element as u8).into()),
+        }
+        Ok(())
+    }
+    /// Dumps one tagged item, returning false if it only prefixes the value.
+    fn item(&mut self, start: usize, depth: usize, label: &str) -> anyhow::Result<bool> {
+        let tag = self.read_tag()?;
+        macro_rules! prim {
+            ($name:literal, $ty:ty) => {{
+                let value = self.dump.read_vu128::<$ty>()?;
+                self.dump.line(start, depth, format_args!("{}{} {:?}", label, $name, value));
+                return Ok(true);
+            }};
+        }
+        match tag {
+            TypeTag::Unit => {
+                self.dump.line(start, depth, format_args!("{}()", label));
+                return Ok(true);
+            }
+            TypeTag::Bool => prim!("bool", bool),
+            TypeTag::I8 => prim!("i8", i8),
+            TypeTag::I16 => prim!("i16", i16),
+            TypeTag::I32 => prim!("i32", i32),
+            TypeTag::I64 => prim!("i64", i64),
+            TypeTag::I128 => prim!("i128", i128),
+            TypeTag::U8 => prim!("u8", u8),
+            TypeTag::U16 => prim!("u16", u16),
+            TypeTag::U32 => prim!("u32", u32),
+            TypeTag::U64 => prim!("u64", u64),
+            TypeTag::U128 => prim!("u128", u128),
+            TypeTag::F32 => prim!("f32", f32),
+            TypeTag::F64 => prim!("f64", f64),
+            TypeTag::Char => {
+                let value = self.dump.read_vu128::<u32>()?;
+                match char::from_u32(value) {
+                    Some(c) => self.dump.line(start, depth, format_args!("{}char {:?}", label, c)),
+                    None => self.dump.line(
+                        start,
+                        depth,
+                        format_args!("{}char (invalid {:#x})", label, value),
+                    ),
+                }
+                return Ok(true);
+            }
+            TypeTag::String => {
+                let value = self.dump.read_str()?;
+                self.dump.line(start, depth, format_args!("{}string {:?}", label, value));
+                return Ok(true);
+            }
+            TypeTag::Bytes => {
+                let len = self.dump.read_usize()?;
+                let value = self.dump.read_count(len)?.to_vec();
+                self.dump.line(start, depth, format_args!("{}bytes {:02x?}", label, value));
+                return Ok(true);
+            }
+            TypeTag::None => {
+                self.dump.line(start, depth, format_args!("{}none", label));
+                return Ok(true);
+            }
+            TypeTag::Some => {
+                self.dump.line(start, depth, format_args!("{}some", label));
+                self.value(depth + 1, "")?;
+                return Ok(true);
+            }
+            TypeTag::UnitStruct => {
+                self.dump.line(start, depth, format_args!("{}unit struct", label));
+                return Ok(true);
+            }
+            TypeTag::Tuple | TypeTag::TupleStruct | TypeTag::Seq => {
+                let len = self.dump.read_usize()?;
+                let name = match tag {
+                    TypeTag::Tuple => "tuple",
+                    TypeTag::TupleStruct => "tuple struct",
+                    _ => "seq",
+                };
+                self.dump.line(start, depth, format_args!("{}{} ({})", label, name, len));
+                for index in 0..len {
+                    self.value(depth + 1, &format!("[{}] ", index))?;
+                }
+                return Ok(true);
+            }
+            TypeTag::PackedSeq => {
+                let element = self.read_tag()?;
+                let len = self.dump.read_usize()?;
+                self.dump.line(
+                    start,
+                    depth,
+                    format_args!("{}packed seq of {:?} ({})", label, element, len),
+                );
+                for index in 0..len {
+                    let start = self.dump.offset;
+                    let result = self.packed_element(start, depth + 1, index, element);
+                    self.dump.fail(start, result)?;
+                }
+                return Ok(true);
+            }
+            TypeTag::Map => {
+                let len = self.dump.read_usize()?;
+                self.dump.line(start, depth, format_args!("{}map ({})", label, len));
+                for _ in 0..len {
+                    self.value(depth + 1, "key: ")?;
+                    self.value(depth + 1, "value: ")?;
+                }
+                return Ok(true);
+            }
+            TypeTag::Struct => {
+                let (index, fields) = self.read_enum_def_ref()?;
+                self.dump.line(start, depth, format_args!("{}struct #{}", label, index));
+                for field in fields {
+                    self.value(depth + 1, &format!("{}: ", field))?;
+                }
+                return Ok(true);
+            }
+            TypeTag::Enum => {
+                let (index, variants) = self.read_enum_def_ref()?;
+                let variant = self.dump.read_usize()?;
+                let name = variants
+                    .get(variant)
+                    .ok_or(InspectError::NoSuchVariant(variant))?;
+                self.dump.line(
+                    start,
+                    depth,
+                    format_args!("{}enum #{} variant {}", label, index, name),
+                );
+                self.value(depth + 1, "")?;
+                return Ok(true);
+            }
+            TypeTag::EnumDef => self.read_enum_def(depth)?,
+            TypeTag::LocalSchema => {
+                self.dump.line(start, depth, format_args!("local schema"));
+                self.local_enum_defs = Some(vec![]);
+            }
+            TypeTag::Skippable => {
+                let len = u32::from_le_bytes(self.dump.read_count(4)?.try_into().unwrap());
+                self.dump.line(start, depth, format_args!("skippable ({} bytes)", len));
+            }
+            TypeTag::StringDef => self.read_string_def(start, depth)?,
+            TypeTag::StringRef => {
+                let index = self.dump.read_usize()?;
+                let value = self
+                    .inspector
+                    .strings
+                    .get(index)
+                    .cloned()
+                    .flatten()
+                    .ok_or(InspectError::NoSuchString(index))?;
+                self.dump.line(
+                    start,
+                    depth,
+                    format_args!("{}string #{} {:?}", label, index, value),
+                );
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+}
@@ -0,0 +1,48 @@
+use std::io::Read;
+
+use anyhow::{anyhow, bail};
+
+use marshal_inspect::{inspect_fixed, BinInspector, Shape};
+
+const USAGE: &str = "usage: marshal-bin-inspect [--schema SAVED_SCHEMA] [--fixed TYPE] [FILE]
+
+Prints a marshal-bin message from FILE or standard input as an annotated tree. With --fixed,
+the input is read as marshal-fixed data of the given type, such as `Vec<(u8, String)>`.";
+
+fn main() -> anyhow::Result<()> {
+    let mut schema = None;
+    let mut fixed = None;
+    let mut path = None;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--schema" => schema = Some(args.next().ok_or_else(|| anyhow!("--schema requires a file"))?),
+            "--fixed" => fixed = Some(args.next().ok_or_else(|| anyhow!("--fixed requires a type"))?),
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return Ok(());
+            }
+            _ if path.is_none() && !arg.starts_with('-') => path = Some(arg),
+            _ => bail!("unexpected argument `{}'\n\n{}", arg, USAGE),
+        }
+    }
+    let data = match path {
+        Some(path) => std::fs::read(path)?,
+        None => {
+            let mut data = vec![];
+            std::io::stdin().read_to_end(&mut data)?;
+            data
+        }
+    };
+    let output = if let Some(fixed) = fixed {
+        inspect_fixed(&data, &fixed.parse::<Shape>()?)
+    } else {
+        let mut inspector = BinInspector::new();
+        if let Some(schema) = schema {
+            inspector.load_schema(&std::fs::read(schema)?)?;
+        }
+        inspector.inspect(&data)
+    };
+    print!("{}", output);
+    Ok(())
+}
@@ -0,0 +1,147 @@
+use crate::shape::{Shape, VariantShape};
+use crate::{Dump, InspectError};
+
+/// Dumps a `marshal-fixed` value, which must have the given shape because the format does not
+/// describe itself.
+pub fn inspect_fixed(data: &[u8], shape: &Shape) -> String {
+    let mut dump = Dump::new(data);
+    let result = value(&mut dump, shape, 0, "");
+    dump.finish(result)
+}
+
+fn fields<'a>(
+    dump: &mut Dump,
+    fields: impl Iterator<Item = (String, &'a Shape)>,
+    depth: usize,
+) -> anyhow::Result<()> {
+    for (label, shape) in fields {
+        value(dump, shape, depth, &label)?;
+    }
+    Ok(())
+}
+
+fn named<'a>(fields: &'a [(String, Shape)]) -> impl Iterator<Item = (String, &'a Shape)> {
+    fields.iter().map(|(name, shape)| (format!("{}: ", name), shape))
+}
+
+fn indexed<'a>(fields: &'a [Shape]) -> impl Iterator<Item = (String, &'a Shape)> {
+    fields
+        .iter()
+        .enumerate()
+        .map(|(index, shape)| (format!("[{}] ", index), shape))
+}
+
+/// Reads a discriminant in the width that `marshal-fixed` chooses for the number of variants.
+fn discriminant(dump: &mut Dump, variants: usize) -> anyhow::Result<usize> {
+    Ok(if variants <= 0x1_00 {
+        dump.read_vu128::<u8>()? as usize
+    } else if variants <= 0x1_00_00 {
+        dump.read_vu128::<u16>()? as usize
+    } else if variants as u64 <= 0x1_00_00_00_00 {
+        dump.read_vu128::<u32>()? as usize
+    } else {
+        dump.read_vu128::<u64>()? as usize
+    })
+}
+
+fn value(dump: &mut Dump, shape: &Shape, depth: usize, label: &str) -> anyhow::Result<()> {
+    let start = dump.offset;
+    let result = item(dump, shape, start, depth, label);
+    dump.fail(start, result)
+}
+
+fn item(
+    dump: &mut Dump,
+    shape: &Shape,
+    start: usize,
+    depth: usize,
+    label: &str,
+) -> anyhow::Result<()> {
+    macro_rules! prim {
+        ($name:literal, $ty:ty) => {{
+            let value = dump.read_vu128::<$ty>()?;
+            dump.line(start, depth, format_args!("{}{} {:?}", label, $name, value));
+        }};
+    }
+    match shape {
+        Shape::Unit => dump.line(start, depth, format_args!("{}()", label)),
+        Shape::Bool => prim!("bool", bool),
+        Shape::I8 => prim!("i8", i8),
+        Shape::I16 => prim!("i16", i16),
+        Shape::I32 => prim!("i32", i32),
+        Shape::I64 => prim!("i64", i64),
+        Shape::I128 => prim!("i128", i128),
+        Shape::U8 => prim!("u8", u8),
+        Shape::U16 => prim!("u16", u16),
+        Shape::U32 => prim!("u32", u32),
+        Shape::U64 => prim!("u64", u64),
+        Shape::U128 => prim!("u128", u128),
+        Shape::F32 => prim!("f32", f32),
+        Shape::F64 => prim!("f64", f64),
+        Shape::Char => {
+            let value = dump.read_vu128::<u32>()?;
+            match char::from_u32(value) {
+                Some(c) => dump.line(start, depth, format_args!("{}char {:?}", label, c)),
+                None => dump.line(start, depth, format_args!("{}char (invalid {:#x})", label, value)),
+            }
+        }
+        Shape::String => {
+            let value = dump.read_str()?;
+            dump.line(start, depth, format_args!("{}string {:?}", label, value));
+        }
+        Shape::Bytes => {
+            let len = dump.read_usize()?;
+            let value = dump.read_count(len)?.to_vec();
+            dump.line(start, depth, format_args!("{}bytes {:02x?}", label, value));
+        }
+        Shape::Option(inner) => {
+            if dump.read_vu128::<bool>()? {
+                dump.line(start, depth, format_args!("{}some", label));
+                value(dump, inner, depth + 1, "")?;
+            } else {
+                dump.line(start, depth, format_args!("{}none", label));
+            }
+        }
+        Shape::Seq(inner) => {
+            let len = dump.read_usize()?;
+            dump.line(start, depth, format_args!("{}seq ({})", label, len));
+            for index in 0..len {
+                value(dump, inner, depth + 1, &format!("[{}] ", index))?;
+            }
+        }
+        Shape::Map(key, inner) => {
+            let len = dump.read_usize()?;
+            dump.line(start, depth, format_args!("{}map ({})", label, len));
+            for _ in 0..len {
+                value(dump, key, depth + 1, "key: ")?;
+                value(dump, inner, depth + 1, "value: ")?;
+            }
+        }
+        Shape::Tuple(shapes) => {
+            dump.line(start, depth, format_args!("{}tuple ({})", label, shapes.len()));
+            fields(dump, indexed(shapes), depth + 1)?;
+        }
+        Shape::UnitStruct(name) => dump.line(start, depth, format_args!("{}{}", label, name)),
+        Shape::TupleStruct(name, shapes) => {
+            dump.line(start, depth, format_args!("{}{}", label, name));
+            fields(dump, indexed(shapes), depth + 1)?;
+        }
+        Shape::Struct(name, shapes) => {
+            dump.line(start, depth, format_args!("{}{}", label, name));
+            fields(dump, named(shapes), depth + 1)?;
+        }
+        Shape::Enum(name, variants) => {
+            let index = discriminant(dump, variants.len())?;
+            let (variant, shape) = variants
+                .get(index)
+                .ok_or(InspectError::NoSuchVariant(index))?;
+            dump.line(start, depth, format_args!("{}{}::{}", label, name, variant));
+            match shape {
+                VariantShape::Unit => {}
+                VariantShape::Tuple(shapes) => fields(dump, indexed(shapes), depth + 1)?,
+                VariantShape::Struct(shapes) => fields(dump, named(shapes), depth + 1)?,
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,121 @@
+//! Human-readable dumps of `marshal-bin` and `marshal-fixed` data, for debugging.
+
+use std::fmt::Write;
+
+use marshal_vu128::{ReadVu128, ToFromVu128, VU128_PADDING};
+
+pub use crate::bin::{inspect_bin, BinInspector};
+pub use crate::fixed::inspect_fixed;
+pub use crate::shape::{Shape, VariantShape};
+
+mod bin;
+mod fixed;
+mod shape;
+#[cfg(test)]
+mod test;
+
+#[derive(Debug)]
+pub enum InspectError {
+    Eof,
+    BadTag(u8),
+    NoSuchEnumDef(usize),
+    NoSuchVariant(usize),
+    NoSuchString(usize),
+    BadStringDef(usize),
+    TooDeep,
+    TrailingData(usize),
+}
+
+impl std::fmt::Display for InspectError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InspectError::Eof => write!(f, "unexpected end of input"),
+            InspectError::BadTag(x) => write!(f, "unknown type tag {}", x),
+            InspectError::NoSuchEnumDef(x) => write!(f, "reference to unknown enum definition #{}", x),
+            InspectError::NoSuchVariant(x) => write!(f, "variant index {} is out of range", x),
+            InspectError::NoSuchString(x) => write!(f, "reference to unknown interned string #{}", x),
+            InspectError::BadStringDef(x) => write!(f, "interned string #{} is defined out of order", x),
+            InspectError::TooDeep => write!(f, "values are nested too deeply"),
+            InspectError::TrailingData(x) => write!(f, "{} bytes of trailing data", x),
+        }
+    }
+}
+
+impl std::error::Error for InspectError {}
+
+/// Reads from a padded copy of the input and writes one line per item, prefixed with its offset.
+struct Dump {
+    data: Vec<u8>,
+    len: usize,
+    offset: usize,
+    output: String,
+    error_offset: Option<usize>,
+}
+
+impl Dump {
+    fn new(data: &[u8]) -> Self {
+        let mut padded = data.to_vec();
+        padded.resize(data.len() + VU128_PADDING, 0);
+        Dump {
+            data: padded,
+            len: data.len(),
+            offset: 0,
+            output: String::new(),
+            error_offset: None,
+        }
+    }
+    fn line(&mut self, start: usize, depth: usize, text: std::fmt::Arguments) {
+        writeln!(self.output, "{:>6}  {:width$}{}", start, "", text, width = depth * 2).unwrap();
+    }
+    /// Records the start of the innermost item that failed to decode.
+    fn fail<T>(&mut self, start: usize, result: anyhow::Result<T>) -> anyhow::Result<T> {
+        if result.is_err() {
+            self.error_offset.get_or_insert(start);
+        }
+        result
+    }
+    fn read_vu128<T: ToFromVu128>(&mut self) -> anyhow::Result<T> {
+        let mut cursor = &self.data[self.offset..];
+        let value = cursor.read_vu128::<T>()?;
+        self.advance(self.data.len() - cursor.len() - self.offset)?;
+        Ok(value)
+    }
+    fn read_usize(&mut self) -> anyhow::Result<usize> {
+        Ok(usize::try_from(self.read_vu128::<u64>()?)?)
+    }
+    fn read_count(&mut self, count: usize) -> anyhow::Result<&[u8]> {
+        let start = self.offset;
+        self.advance(count)?;
+        Ok(&self.data[start..start + count])
+    }
+    fn read_str(&mut self) -> anyhow::Result<String> {
+        let len = self.read_usize()?;
+        Ok(String::from_utf8_lossy(self.read_count(len)?).into_owned())
+    }
+    fn advance(&mut self, count: usize) -> anyhow::Result<()> {
+        if count > self.len - self.offset {
+            return Err(InspectError::Eof.into());
+        }
+        self.offset += count;
+        Ok(())
+    }
+    /// Describes the bytes after the value, which should be the encoder's padding.
+    fn finish(mut self, result: anyhow::Result<()>) -> String {
+        let result = result.and_then(|()| {
+            let rest = &self.data[self.offset..self.len];
+            if rest.len() > VU128_PADDING || rest.iter().any(|x| *x != 0) {
+                return Err(InspectError::TrailingData(rest.len()).into());
+            }
+            if !rest.is_empty() {
+                let (offset, len) = (self.offset, rest.len());
+                self.line(offset, 0, format_args!("padding ({} bytes)", len));
+            }
+            Ok(())
+        });
+        if let Err(error) = result {
+            let offset = self.error_offset.unwrap_or(self.offset);
+            self.line(offset, 0, format_args!("error: {}", error));
+        }
+        self.output
+    }
+}
@@ -0,0 +1,233 @@
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// A description of a type, for inspecting formats that are not self-describing. The text form
+/// uses Rust-like syntax, for example `Vec<struct Point { x: i32, y: i32 }>` or
+/// `enum Shape { Empty, Circle(f64), Rect { w: f64, h: f64 } }`.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Shape {
+    Unit,
+    Bool,
+    I8,
+    I16,
+    I32,
+    I64,
+    I128,
+    U8,
+    U16,
+    U32,
+    U64,
+    U128,
+    F32,
+    F64,
+    Char,
+    String,
+    Bytes,
+    Option(Box<Shape>),
+    Seq(Box<Shape>),
+    Map(Box<Shape>, Box<Shape>),
+    Tuple(Vec<Shape>),
+    UnitStruct(String),
+    TupleStruct(String, Vec<Shape>),
+    Struct(String, Vec<(String, Shape)>),
+    Enum(String, Vec<(String, VariantShape)>),
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum VariantShape {
+    Unit,
+    Tuple(Vec<Shape>),
+    Struct(Vec<(String, Shape)>),
+}
+
+#[derive(Debug)]
+pub struct ShapeParseError {
+    expected: &'static str,
+    found: String,
+}
+
+impl Display for ShapeParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "expected {} in type description, found `{}'", self.expected, self.found)
+    }
+}
+
+impl std::error::Error for ShapeParseError {}
+
+struct ShapeParser<'a> {
+    tokens: Vec<&'a str>,
+    position: usize,
+}
+
+fn tokenize(input: &str) -> Vec<&str> {
+    let mut tokens = vec![];
+    let mut rest = input.trim_start();
+    while let Some(c) = rest.chars().next() {
+        let len = if c.is_alphanumeric() || c == '_' {
+            rest.find(|c: char| !c.is_alphanumeric() && c != '_')
+                .unwrap_or(rest.len())
+        } else {
+            c.len_utf8()
+        };
+        tokens.push(&rest[..len]);
+        rest = rest[len..].trim_start();
+    }
+    tokens
+}
+
+impl<'a> ShapeParser<'a> {
+    fn peek(&self) -> &'a str {
+        self.tokens.get(self.position).copied().unwrap_or("")
+    }
+    fn next(&mut self) -> &'a str {
+        let token = self.peek();
+        self.position += 1;
+        token
+    }
+    fn expect(&mut self, token: &'static str) -> Result<(), ShapeParseError> {
+        let found = self.next();
+        if found != token {
+            return Err(ShapeParseError {
+                expected: token,
+                found: found.to_string(),
+            });
+        }
+        Ok(())
+    }
+    fn name(&mut self) -> Result<String, ShapeParseError> {
+        let found = self.next();
+        if !found.starts_with(|c: char| c.is_alphabetic() || c == '_') {
+            return Err(ShapeParseError {
+                expected: "a name",
+                found: found.to_string(),
+            });
+        }
+        Ok(found.to_string())
+    }
+    /// Parses items separated by commas, up to the closing token.
+    fn list<T>(
+        &mut self,
+        close: &'static str,
+        mut item: impl FnMut(&mut Self) -> Result<T, ShapeParseError>,
+    ) -> Result<Vec<T>, ShapeParseError> {
+        let mut result = vec![];
+        while self.peek() != close {
+            result.push(item(self)?);
+            if self.peek() != close {
+                self.expect(",")?;
+            }
+        }
+        self.expect(close)?;
+        Ok(result)
+    }
+    fn field(&mut self) -> Result<(String, Shape), ShapeParseError> {
+        let name = self.name()?;
+        self.expect(":")?;
+        Ok((name, self.shape()?))
+    }
+    fn variant(&mut self) -> Result<(String, VariantShape), ShapeParseError> {
+        let name = self.name()?;
+        let shape = match self.peek() {
+            "(" => {
+                self.next();
+                VariantShape::Tuple(self.list(")", Self::shape)?)
+            }
+            "{" => {
+                self.next();
+                VariantShape::Struct(self.list("}", Self::field)?)
+            }
+            _ => VariantShape::Unit,
+        };
+        Ok((name, shape))
+    }
+    fn shape(&mut self) -> Result<Shape, ShapeParseError> {
+        let token = self.next();
+        Ok(match token {
+            "bool" => Shape::Bool,
+            "i8" => Shape::I8,
+            "i16" => Shape::I16,
+            "i32" => Shape::I32,
+            "i64" => Shape::I64,
+            "i128" => Shape::I128,
+            "u8" => Shape::U8,
+            "u16" => Shape::U16,
+            "u32" => Shape::U32,
+            "u64" => Shape::U64,
+            "u128" => Shape::U128,
+            "f32" => Shape::F32,
+            "f64" => Shape::F64,
+            "char" => Shape::Char,
+            "String" => Shape::String,
+            "Bytes" => Shape::Bytes,
+            "Option" | "Vec" => {
+                self.expect("<")?;
+                let inner = Box::new(self.shape()?);
+                self.expect(">")?;
+                if token == "Option" {
+                    Shape::Option(inner)
+                } else {
+                    Shape::Seq(inner)
+                }
+            }
+            "Map" => {
+                self.expect("<")?;
+                let key = Box::new(self.shape()?);
+                self.expect(",")?;
+                let value = Box::new(self.shape()?);
+                self.expect(">")?;
+                Shape::Map(key, value)
+            }
+            "(" => {
+                let fields = self.list(")", Self::shape)?;
+                if fields.is_empty() {
+                    Shape::Unit
+                } else {
+                    Shape::Tuple(fields)
+                }
+            }
+            "struct" => {
+                let name = self.name()?;
+                match self.peek() {
+                    "(" => {
+                        self.next();
+                        Shape::TupleStruct(name, self.list(")", Self::shape)?)
+                    }
+                    "{" => {
+                        self.next();
+                        Shape::Struct(name, self.list("}", Self::field)?)
+                    }
+                    _ => Shape::UnitStruct(name),
+                }
+            }
+            "enum" => {
+                let name = self.name()?;
+                self.expect("{")?;
+                Shape::Enum(name, self.list("}", Self::variant)?)
+            }
+            found => {
+                return Err(ShapeParseError {
+                    expected: "a type",
+                    found: found.to_string(),
+                })
+            }
+        })
+    }
+}
+
+impl FromStr for Shape {
+    type Err = ShapeParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = ShapeParser {
+            tokens: tokenize(s),
+            position: 0,
+        };
+        let shape = parser.shape()?;
+        if parser.position < parser.tokens.len() {
+            return Err(ShapeParseError {
+                expected: "end of input",
+                found: parser.peek().to_string(),
+            });
+        }
+        Ok(shape)
+    }
+}
@@ -0,0 +1,175 @@
+use marshal::context::OwnedContext;
+use marshal_bin::encode::full::BinEncoderBuilder;
+use marshal_bin::encode::BinEncoderSchema;
+use marshal_bin::TypeTag;
+use marshal_fixed::encode::full::FixedEncoderBuilder;
+use marshal_vu128::VU128_PADDING;
+
+use crate::{inspect_bin, inspect_fixed, BinInspector, Shape, VariantShape};
+
+#[derive(marshal_derive::Serialize)]
+enum Op {
+    Nop,
+    Add(u8, u8),
+    Move { x: i32, name: String },
+}
+
+#[derive(marshal_derive::Serialize)]
+struct Program {
+    ops: Vec<Op>,
+    label: Option<String>,
+}
+
+fn program() -> Program {
+    Program {
+        ops: vec![
+            Op::Nop,
+            Op::Add(1, 2),
+            Op::Move {
+                x: -3,
+                name: "m".to_string(),
+            },
+        ],
+        label: None,
+    }
+}
+
+#[test]
+fn test_inspect_bin() -> anyhow::Result<()> {
+    let mut c = OwnedContext::new();
+    let mut schema = BinEncoderSchema::new();
+    let encoded = BinEncoderBuilder::new(&mut schema).serialize(&program(), c.borrow())?;
+    assert_eq!(
+        inspect_bin(&encoded),
+        r#"     0  enum definition #0 [ops, label]
+    12  struct #0
+    14    ops: seq (3)
+    16      enum definition #1 [Nop, Add, Move]
+    31      [0] enum #1 variant Nop
+    34        ()
+    35      [1] enum #1 variant Add
+    38        tuple struct (2)
+    40          [0] u8 1
+    42          [1] u8 2
+    44      enum definition #2 [x, name]
+    53      [2] enum #1 variant Move
+    56        struct #2
+    58          x: i32 -3
+    60          name: string "m"
+    63    label: none
+    64  padding (17 bytes)
+"#
+    );
+
+    // A second message refers to definitions from the first.
+    let second = BinEncoderBuilder::new(&mut schema).serialize(&Op::Nop, c.borrow())?;
+    let mut inspector = BinInspector::new();
+    inspector.inspect(&encoded);
+    assert_eq!(
+        inspector.inspect(&second),
+        "     0  enum #1 variant Nop\n     3    ()\n     4  padding (17 bytes)\n"
+    );
+    let mut inspector = BinInspector::new();
+    inspector.load_schema(&schema.save())?;
+    assert_eq!(
+        inspector.inspect(&second),
+        "     0  enum #1 variant Nop\n     3    ()\n     4  padding (17 bytes)\n"
+    );
+    assert_eq!(
+        inspect_bin(&second),
+        "     0  error: reference to unknown enum definition #1\n"
+    );
+
+    let mut packed_schema = BinEncoderSchema::new();
+    let mut builder = BinEncoderBuilder::new(&mut packed_schema);
+    builder.set_packed(true);
+    let packed = builder.serialize(&vec![5u32, 6], c.borrow())?;
+    assert_eq!(
+        inspect_bin(&packed),
+        "     0  packed seq of U32 (2)\n     3    [0] u32 5\n     4    [1] u32 6\n     5  padding (17 bytes)\n"
+    );
+    Ok(())
+}
+
+#[test]
+fn test_inspect_bin_error() -> anyhow::Result<()> {
+    let mut c = OwnedContext::new();
+    let encoded =
+        BinEncoderBuilder::new(&mut BinEncoderSchema::new()).serialize(&program(), c.borrow())?;
+    let truncated = &encoded[..44];
+    assert!(inspect_bin(truncated).ends_with(
+        "    40          [0] u8 1\n    42          [1] u8 2\n    44  error: unexpected end of input\n"
+    ));
+    let mut corrupted = encoded[..encoded.len() - VU128_PADDING].to_vec();
+    corrupted[12] = 0xFF;
+    assert_eq!(
+        inspect_bin(&corrupted),
+        "     0  enum definition #0 [ops, label]\n    12  error: unknown type tag 255\n"
+    );
+    let deep = [vec![TypeTag::Some as u8; 1000], vec![TypeTag::Unit as u8]].concat();
+    assert!(inspect_bin(&deep).ends_with("   101  error: values are nested too deeply\n"));
+    let skipped = [TypeTag::StringDef as u8, 1, 1, b'a', TypeTag::Unit as u8];
+    assert_eq!(
+        inspect_bin(&skipped),
+        "     0  error: interned string #1 is defined out of order\n"
+    );
+    Ok(())
+}
+
+#[test]
+fn test_inspect_fixed() -> anyhow::Result<()> {
+    let shape: Shape = "struct Program {
+        ops: Vec<enum Op { Nop, Add(u8, u8), Move { x: i32, name: String } }>,
+        label: Option<String>,
+    }"
+    .parse()?;
+    assert_eq!(
+        shape,
+        Shape::Struct(
+            "Program".to_string(),
+            vec![
+                (
+                    "ops".to_string(),
+                    Shape::Seq(Box::new(Shape::Enum(
+                        "Op".to_string(),
+                        vec![
+                            ("Nop".to_string(), VariantShape::Unit),
+                            (
+                                "Add".to_string(),
+                                VariantShape::Tuple(vec![Shape::U8, Shape::U8])
+                            ),
+                            (
+                                "Move".to_string(),
+                                VariantShape::Struct(vec![
+                                    ("x".to_string(), Shape::I32),
+                                    ("name".to_string(), Shape::String)
+                                ])
+                            ),
+                        ]
+                    )))
+                ),
+                ("label".to_string(), Shape::Option(Box::new(Shape::String))),
+            ]
+        )
+    );
+    let mut c = OwnedContext::new();
+    let encoded = FixedEncoderBuilder::new().serialize(&program(), c.borrow())?;
+    assert_eq!(
+        inspect_fixed(&encoded, &shape),
+        r#"     0  Program
+     0    ops: seq (3)
+     1      [0] Op::Nop
+     2      [1] Op::Add
+     3        [0] u8 1
+     4        [1] u8 2
+     5      [2] Op::Move
+     6        x: i32 -3
+     7        name: string "m"
+     9    label: none
+    10  padding (17 bytes)
+"#
+    );
+    assert!("Vec<u8".parse::<Shape>().is_err());
+    assert!("struct { x: u8 }".parse::<Shape>().is_err());
+    Ok(())
+}
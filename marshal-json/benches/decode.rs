@@ -4,8 +4,9 @@ use std::path::Path;
 use criterion::{BenchmarkId, black_box, Criterion, criterion_group, criterion_main};
 use serde_json::Value;
 
-use marshal::context::Context;
+use marshal::context::OwnedContext;
 use marshal_json::decode::full::JsonDecoderBuilder;
+use marshal_json::decode::path::JsonPointer;
 use marshal_json::value::JsonValue;
 
 fn parse_serde(data: &[u8]) {
@@ -15,7 +16,21 @@ fn parse_serde(data: &[u8]) {
 fn parse_marshal(data: &[u8]) {
     black_box(
         JsonDecoderBuilder::new(black_box(data))
-            .deserialize::<JsonValue>(&mut Context::new())
+            .deserialize::<JsonValue>(OwnedContext::new().borrow())
+            .unwrap(),
+    );
+}
+
+fn ignore_marshal(data: &[u8]) {
+    JsonDecoderBuilder::new(black_box(data))
+        .with(|d| d.ignore())
+        .unwrap();
+}
+
+fn extract_marshal(data: &[u8], paths: &[JsonPointer]) {
+    black_box(
+        JsonDecoderBuilder::new(black_box(data))
+            .extract::<JsonValue>(paths, OwnedContext::new().borrow())
             .unwrap(),
     );
 }
@@ -32,6 +47,22 @@ fn criterion_benchmark(c: &mut Criterion) {
         });
     }
     g.finish();
+    let mut g = c.benchmark_group("extract");
+    for (name, path) in &[
+        ("canada.json", "/features/*/properties/name"),
+        ("citm_catalog.json", "/events/*/id"),
+        ("twitter.json", "/statuses/*/id"),
+    ] {
+        let data = fs::read(Path::new("nativejson-benchmark/data").join(name)).unwrap();
+        let paths = [JsonPointer::parse(path).unwrap()];
+        g.bench_function(BenchmarkId::new("ignore_marshal", name), |b| {
+            b.iter(|| ignore_marshal(&data))
+        });
+        g.bench_function(BenchmarkId::new("extract_marshal", name), |b| {
+            b.iter(|| extract_marshal(&data, &paths))
+        });
+    }
+    g.finish();
 }
 
 criterion_group!(benches, criterion_benchmark);
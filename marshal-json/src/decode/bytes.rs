@@ -0,0 +1,76 @@
+use base64::alphabet;
+use base64::engine::general_purpose::{GeneralPurpose, GeneralPurposeConfig};
+use base64::engine::DecodePaddingMode;
+use base64::Engine;
+
+use crate::decode::error::JsonDecoderError;
+use crate::decode::SimpleJsonSpecDecoder;
+use crate::format::BytesFormat;
+
+const LENIENT_CONFIG: GeneralPurposeConfig =
+    GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent);
+const LENIENT_STANDARD: GeneralPurpose = GeneralPurpose::new(&alphabet::STANDARD, LENIENT_CONFIG);
+const LENIENT_URL_SAFE: GeneralPurpose = GeneralPurpose::new(&alphabet::URL_SAFE, LENIENT_CONFIG);
+
+fn hex_digit(c: u8) -> anyhow::Result<u8> {
+    Ok(match c {
+        b'0'..=b'9' => c - b'0',
+        b'a'..=b'f' => c - b'a' + 10,
+        b'A'..=b'F' => c - b'A' + 10,
+        _ => return Err(JsonDecoderError::BadHex.into()),
+    })
+}
+
+fn decode_hex(s: &str) -> anyhow::Result<Vec<u8>> {
+    if s.len() % 2 != 0 {
+        return Err(JsonDecoderError::BadHex.into());
+    }
+    s.as_bytes()
+        .chunks_exact(2)
+        .map(|x| Ok(hex_digit(x[0])? << 4 | hex_digit(x[1])?))
+        .collect()
+}
+
+impl<'de> SimpleJsonSpecDecoder<'de> {
+    /// Reads a string of bytes. The base64 variants can be told apart by their alphabet and
+    /// padding, so all of them are accepted unless hex was selected. A hex string is also valid
+    /// base64, so hex is only accepted when selected, and then base64 is not.
+    pub fn read_bytes_string(&mut self) -> anyhow::Result<Vec<u8>> {
+        let s = self.read_string()?;
+        match self.bytes_format {
+            BytesFormat::Hex => decode_hex(&s),
+            BytesFormat::Base64
+            | BytesFormat::Base64NoPad
+            | BytesFormat::Base64Url
+            | BytesFormat::Array => {
+                if s.contains(['-', '_']) {
+                    Ok(LENIENT_URL_SAFE.decode(s)?)
+                } else {
+                    Ok(LENIENT_STANDARD.decode(s)?)
+                }
+            }
+        }
+    }
+
+    pub fn read_bytes_array(&mut self) -> anyhow::Result<Vec<u8>> {
+        self.read_exact(b'[')?;
+        let mut result = vec![];
+        while !self.try_read_exact(b']')? {
+            if !result.is_empty() {
+                self.read_exact(b',')?;
+                self.read_whitespace()?;
+            }
+            result.push(self.read_number()?);
+        }
+        Ok(result)
+    }
+
+    pub fn read_non_finite(&mut self) -> anyhow::Result<f64> {
+        match &*self.read_string()? {
+            "NaN" => Ok(f64::NAN),
+            "Infinity" => Ok(f64::INFINITY),
+            "-Infinity" => Ok(f64::NEG_INFINITY),
+            _ => Err(JsonDecoderError::BadNumber.into()),
+        }
+    }
+}
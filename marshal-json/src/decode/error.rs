@@ -12,6 +12,7 @@ pub enum JsonDecoderError {
     UnexpectedInput,
     Utf8Error,
     ParseIntError,
+    IntegerOverflow,
     ParseFloatError,
     BadNumber,
     CharTryFromError,
@@ -29,6 +30,7 @@ pub enum JsonDecoderError {
     SchemaMismatch { hint: DecodeHint, found: PeekType },
     UnexpectedNull,
     BadOption,
+    BadHex,
 }
 
 impl Display for JsonDecoderError {
@@ -5,7 +5,9 @@ use marshal_core::decode::depth_budget::{DepthBudgetDecoder, WithDepthBudget};
 use marshal_core::decode::poison::PoisonDecoder;
 use marshal_core::derive_decoder_for_newtype;
 
+use crate::decode::path::{visit_paths, JsonPointer};
 use crate::decode::{JsonAnyDecoder, SimpleJsonSpecDecoder};
+use crate::format::BytesFormat;
 
 pub struct JsonSpecDecoder<'de>(PoisonDecoder<DepthBudgetDecoder<SimpleJsonSpecDecoder<'de>>>);
 
@@ -29,6 +31,16 @@ impl<'de> JsonDecoderBuilder<'de> {
         self.depth_budget = depth_budget;
         self
     }
+    /// Selects the byte string representations that are accepted:
+    /// - [BytesFormat::Base64], [BytesFormat::Base64NoPad], [BytesFormat::Base64Url] and
+    ///   [BytesFormat::Array] (the default is `Base64NoPad`) all accept arrays of numbers and
+    ///   base64 strings in either alphabet, with or without padding. Hex strings are not
+    ///   recognized, and are rejected or misread as base64.
+    /// - [BytesFormat::Hex] accepts only hex strings, in either case.
+    pub fn set_bytes_format(&mut self, bytes_format: BytesFormat) -> &mut Self {
+        self.decoder.0.inner_mut().inner_mut().set_bytes_format(bytes_format);
+        self
+    }
     pub fn build<'p>(&'p mut self) -> AnySpecDecoder<'p, 'de, JsonSpecDecoder<'de>> {
         let any = JsonAnyDecoder::default();
         let any = WithDepthBudget::new(self.depth_budget, any);
@@ -43,6 +55,30 @@ impl<'de> JsonDecoderBuilder<'de> {
         self.end()?;
         Ok(result)
     }
+    /// Decodes only the values at `paths`, calling `f` with the index of the matching path.
+    /// Everything else is skipped without being materialized.
+    pub fn with_paths<F>(mut self, paths: &[JsonPointer], mut f: F) -> anyhow::Result<()>
+    where
+        F: for<'p> FnMut(usize, AnySpecDecoder<'p, 'de, JsonSpecDecoder<'de>>) -> anyhow::Result<()>,
+    {
+        let mut active: Vec<usize> = (0..paths.len()).collect();
+        visit_paths(self.build(), paths, 0, &mut active, 0, &mut f)
+            .map_err(|e| e.context(self.decoder.0.inner().inner().location()))?;
+        self.end()
+    }
+    /// Deserializes every value matching each of `paths`.
+    pub fn extract<T: Deserialize<JsonDecoder>>(
+        self,
+        paths: &[JsonPointer],
+        mut ctx: Context,
+    ) -> anyhow::Result<Vec<Vec<T>>> {
+        let mut result: Vec<Vec<T>> = paths.iter().map(|_| vec![]).collect();
+        self.with_paths(paths, |index, d| {
+            result[index].push(T::deserialize(d, ctx.reborrow())?);
+            Ok(())
+        })?;
+        Ok(result)
+    }
     pub fn end(self) -> anyhow::Result<()> {
         self.decoder.0.end()?.end()?.end()?;
         Ok(())
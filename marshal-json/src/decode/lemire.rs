@@ -0,0 +1,171 @@
+use crate::decode::lemire_table::{POWERS_OF_FIVE, SMALLEST_POWER_OF_FIVE};
+
+/// A binary floating point format that decimals can be rounded into.
+pub trait Float: Copy {
+    const MANTISSA_BITS: i32;
+    const MINIMUM_EXPONENT: i32;
+    const INFINITE_POWER: i32;
+    const SMALLEST_POWER_OF_TEN: i64;
+    const LARGEST_POWER_OF_TEN: i64;
+    const MIN_EXPONENT_ROUND_TO_EVEN: i64;
+    const MAX_EXPONENT_ROUND_TO_EVEN: i64;
+    const MAX_EXPONENT_FAST_PATH: i64;
+    const MAX_MANTISSA_FAST_PATH: u64;
+    fn from_u64(x: u64) -> Self;
+    fn from_bits_u64(bits: u64) -> Self;
+    fn pow10(exponent: i64) -> Self;
+    fn mul(self, other: Self) -> Self;
+    fn div(self, other: Self) -> Self;
+}
+
+impl Float for f64 {
+    const MANTISSA_BITS: i32 = 52;
+    const MINIMUM_EXPONENT: i32 = -1023;
+    const INFINITE_POWER: i32 = 0x7FF;
+    const SMALLEST_POWER_OF_TEN: i64 = -342;
+    const LARGEST_POWER_OF_TEN: i64 = 308;
+    const MIN_EXPONENT_ROUND_TO_EVEN: i64 = -4;
+    const MAX_EXPONENT_ROUND_TO_EVEN: i64 = 23;
+    const MAX_EXPONENT_FAST_PATH: i64 = 22;
+    const MAX_MANTISSA_FAST_PATH: u64 = 2 << 52;
+    fn from_u64(x: u64) -> Self {
+        x as f64
+    }
+    fn from_bits_u64(bits: u64) -> Self {
+        f64::from_bits(bits)
+    }
+    fn pow10(exponent: i64) -> Self {
+        const TABLE: [f64; 23] = [
+            1e0, 1e1, 1e2, 1e3, 1e4, 1e5, 1e6, 1e7, 1e8, 1e9, 1e10, 1e11, 1e12, 1e13, 1e14, 1e15,
+            1e16, 1e17, 1e18, 1e19, 1e20, 1e21, 1e22,
+        ];
+        TABLE[exponent as usize]
+    }
+    fn mul(self, other: Self) -> Self {
+        self * other
+    }
+    fn div(self, other: Self) -> Self {
+        self / other
+    }
+}
+
+impl Float for f32 {
+    const MANTISSA_BITS: i32 = 23;
+    const MINIMUM_EXPONENT: i32 = -127;
+    const INFINITE_POWER: i32 = 0xFF;
+    const SMALLEST_POWER_OF_TEN: i64 = -65;
+    const LARGEST_POWER_OF_TEN: i64 = 38;
+    const MIN_EXPONENT_ROUND_TO_EVEN: i64 = -17;
+    const MAX_EXPONENT_ROUND_TO_EVEN: i64 = 10;
+    const MAX_EXPONENT_FAST_PATH: i64 = 10;
+    const MAX_MANTISSA_FAST_PATH: u64 = 2 << 23;
+    fn from_u64(x: u64) -> Self {
+        x as f32
+    }
+    fn from_bits_u64(bits: u64) -> Self {
+        f32::from_bits(bits as u32)
+    }
+    fn pow10(exponent: i64) -> Self {
+        const TABLE: [f32; 11] = [1e0, 1e1, 1e2, 1e3, 1e4, 1e5, 1e6, 1e7, 1e8, 1e9, 1e10];
+        TABLE[exponent as usize]
+    }
+    fn mul(self, other: Self) -> Self {
+        self * other
+    }
+    fn div(self, other: Self) -> Self {
+        self / other
+    }
+}
+
+/// Rounds `mantissa * 10^exponent` to the nearest `F`, or returns `None` if a slower algorithm is
+/// needed. `truncated` indicates that nonzero digits were dropped from the end of `mantissa`.
+pub fn decimal_to_float<F: Float>(mantissa: u64, exponent: i64, truncated: bool) -> Option<F> {
+    if !truncated
+        && exponent.abs() <= F::MAX_EXPONENT_FAST_PATH
+        && mantissa <= F::MAX_MANTISSA_FAST_PATH
+    {
+        // Both operands are exact, so IEEE arithmetic performs the only rounding.
+        let value = F::from_u64(mantissa);
+        return Some(if exponent < 0 {
+            value.div(F::pow10(-exponent))
+        } else {
+            value.mul(F::pow10(exponent))
+        });
+    }
+    let bits = eisel_lemire::<F>(mantissa, exponent)?;
+    if truncated && eisel_lemire::<F>(mantissa + 1, exponent)? != bits {
+        return None;
+    }
+    Some(F::from_bits_u64(bits))
+}
+
+/// The Eisel-Lemire algorithm, returning the bits of the result.
+fn eisel_lemire<F: Float>(mut mantissa: u64, exponent: i64) -> Option<u64> {
+    if mantissa == 0 || exponent < F::SMALLEST_POWER_OF_TEN {
+        return Some(0);
+    } else if exponent > F::LARGEST_POWER_OF_TEN {
+        return Some((F::INFINITE_POWER as u64) << F::MANTISSA_BITS);
+    }
+    let leading_zeros = mantissa.leading_zeros();
+    mantissa <<= leading_zeros;
+    let (low, high) = product_approx(exponent, mantissa, F::MANTISSA_BITS + 3);
+    if low == u64::MAX && !(-27..=55).contains(&exponent) {
+        return None;
+    }
+    let upper_bit = (high >> 63) as i32;
+    let shift = upper_bit + 64 - F::MANTISSA_BITS - 3;
+    let mut result = high >> shift;
+    let mut power2 = power(exponent as i32) + upper_bit - leading_zeros as i32 - F::MINIMUM_EXPONENT;
+    if power2 <= 0 {
+        // Subnormal.
+        if -power2 + 1 >= 64 {
+            return Some(0);
+        }
+        result >>= -power2 + 1;
+        result += result & 1;
+        result >>= 1;
+        power2 = (result >= (1 << F::MANTISSA_BITS)) as i32;
+        return Some(result | ((power2 as u64) << F::MANTISSA_BITS));
+    }
+    if low <= 1
+        && exponent >= F::MIN_EXPONENT_ROUND_TO_EVEN
+        && exponent <= F::MAX_EXPONENT_ROUND_TO_EVEN
+        && result & 3 == 1
+        && (result << shift) == high
+    {
+        // Exactly halfway between two floats, so round down to the even one.
+        result &= !1;
+    }
+    result += result & 1;
+    result >>= 1;
+    if result >= (2 << F::MANTISSA_BITS) {
+        result = 1 << F::MANTISSA_BITS;
+        power2 += 1;
+    }
+    result &= !(1 << F::MANTISSA_BITS);
+    if power2 >= F::INFINITE_POWER {
+        return Some((F::INFINITE_POWER as u64) << F::MANTISSA_BITS);
+    }
+    Some(result | ((power2 as u64) << F::MANTISSA_BITS))
+}
+
+/// Approximately `floor(log2(10^q)) + 63`.
+fn power(q: i32) -> i32 {
+    (q.wrapping_mul(152_170 + 65536) >> 16) + 63
+}
+
+/// Multiplies `w` by `5^q`, computing only as many bits as needed for `precision` bits of result.
+fn product_approx(q: i64, w: u64, precision: i32) -> (u64, u64) {
+    let mask = u64::MAX >> precision;
+    let (high5, low5) = POWERS_OF_FIVE[(q - SMALLEST_POWER_OF_FIVE) as usize];
+    let first = w as u128 * high5 as u128;
+    let (mut first_low, mut first_high) = (first as u64, (first >> 64) as u64);
+    if first_high & mask == mask {
+        let second_high = ((w as u128 * low5 as u128) >> 64) as u64;
+        first_low = first_low.wrapping_add(second_high);
+        if second_high > first_low {
+            first_high += 1;
+        }
+    }
+    (first_low, first_high)
+}
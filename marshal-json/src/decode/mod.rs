@@ -1,7 +1,5 @@
 use std::borrow::Cow;
 
-use base64::Engine;
-use base64::prelude::BASE64_STANDARD_NO_PAD;
 use itertools::Itertools;
 
 use marshal_core::{Primitive, PrimitiveType};
@@ -9,19 +7,28 @@ use marshal_core::decode::{DecodeHint, DecodeVariantHint, SimpleDecoderView, Spe
 
 use crate::decode::any::PeekType;
 use crate::decode::error::JsonDecoderError;
+use crate::format::BytesFormat;
 
 mod any;
+mod bytes;
 mod error;
 pub mod full;
+mod lemire;
+mod lemire_table;
 mod number;
+pub mod path;
 mod read;
+mod scan;
 mod string;
 #[cfg(test)]
 mod test;
 
+pub use number::JsonNumber;
+
 pub struct SimpleJsonSpecDecoder<'de> {
     original: &'de [u8],
     cursor: &'de [u8],
+    bytes_format: BytesFormat,
 }
 
 #[derive(Default)]
@@ -121,6 +128,14 @@ impl<'de> SpecDecoder<'de> for SimpleJsonSpecDecoder<'de> {
                     must_be_string: context.must_be_string,
                 }))
             }
+            (DecodeHint::Primitive(PrimitiveType::F32), PeekType::Null) => {
+                self.read_null()?;
+                Ok(SimpleDecoderView::Primitive(Primitive::F32(f32::NAN)))
+            }
+            (DecodeHint::Primitive(PrimitiveType::F64), PeekType::Null) => {
+                self.read_null()?;
+                Ok(SimpleDecoderView::Primitive(Primitive::F64(f64::NAN)))
+            }
             (
                 DecodeHint::Any
                 | DecodeHint::Ignore
@@ -146,7 +161,7 @@ impl<'de> SpecDecoder<'de> for SimpleJsonSpecDecoder<'de> {
                 Ok(SimpleDecoderView::Primitive(Primitive::Unit))
             }
             (DecodeHint::Bytes, PeekType::String) => {
-                Ok(SimpleDecoderView::Bytes(BASE64_STANDARD_NO_PAD.decode(self.read_string()?)?.into()))
+                Ok(SimpleDecoderView::Bytes(self.read_bytes_string()?.into()))
             }
             (DecodeHint::Primitive(PrimitiveType::Char), PeekType::String) => {
                 Ok(SimpleDecoderView::Primitive(Primitive::Char(
@@ -174,6 +189,16 @@ impl<'de> SpecDecoder<'de> for SimpleJsonSpecDecoder<'de> {
                 PeekType::String) if context.must_be_string => {
                 Ok(SimpleDecoderView::Primitive(self.read_prim_from_str(prim)?))
             },
+            (
+                DecodeHint::Primitive(prim @ (PrimitiveType::I128 | PrimitiveType::U128)),
+                PeekType::String,
+            ) => Ok(SimpleDecoderView::Primitive(self.read_prim_from_str(prim)?)),
+            (DecodeHint::Primitive(PrimitiveType::F32), PeekType::String) => Ok(
+                SimpleDecoderView::Primitive(Primitive::F32(self.read_non_finite()? as f32)),
+            ),
+            (DecodeHint::Primitive(PrimitiveType::F64), PeekType::String) => Ok(
+                SimpleDecoderView::Primitive(Primitive::F64(self.read_non_finite()?)),
+            ),
             (DecodeHint::Enum { .. }, PeekType::String) => {
                 Ok(SimpleDecoderView::Enum(JsonDiscriminantDecoder::Unit{
                     must_be_string:context.must_be_string,
@@ -193,14 +218,10 @@ impl<'de> SpecDecoder<'de> for SimpleJsonSpecDecoder<'de> {
                     | PrimitiveType::I16
                     | PrimitiveType::I32
                     | PrimitiveType::I64
-                    | PrimitiveType::I128
                     | PrimitiveType::U8
                     | PrimitiveType::U16
                     | PrimitiveType::U32
                     | PrimitiveType::U64
-                    | PrimitiveType::U128
-                    | PrimitiveType::F32
-                    | PrimitiveType::F64
                 )
                 | DecodeHint::Map
                 | DecodeHint::Seq
@@ -221,6 +242,9 @@ impl<'de> SpecDecoder<'de> for SimpleJsonSpecDecoder<'de> {
                 self.read_exact(b']')?;
                 Ok(SimpleDecoderView::Primitive(Primitive::Unit))
             }
+            (DecodeHint::Bytes, PeekType::Seq) => {
+                Ok(SimpleDecoderView::Bytes(self.read_bytes_array()?.into()))
+            }
             (_, PeekType::Seq) => {
                 self.read_exact(b'[')?;
                 Ok(SimpleDecoderView::Seq(JsonSeqDecoder { started: false }))
@@ -487,8 +511,12 @@ impl<'de> SimpleJsonSpecDecoder<'de> {
         SimpleJsonSpecDecoder {
             original: input,
             cursor: input,
+            bytes_format: BytesFormat::default(),
         }
     }
+    pub fn set_bytes_format(&mut self, bytes_format: BytesFormat) {
+        self.bytes_format = bytes_format;
+    }
     pub fn location(&self) -> String {
         let offset = self.original.len() - self.cursor.len();
         let consumed = std::str::from_utf8(&self.original[0..offset]);
@@ -1,74 +1,165 @@
+use std::ops::Neg;
 use std::str::FromStr;
 
 use crate::decode::error::JsonDecoderError;
+use crate::decode::lemire::{decimal_to_float, Float};
 use crate::decode::SimpleJsonSpecDecoder;
 
-struct SliceDecoder<'p, 'de> {
-    decoder: &'p mut SimpleJsonSpecDecoder<'de>,
-    index: usize,
+/// A number type that can be read directly from JSON text.
+pub trait JsonNumber: Sized {
+    /// Reads a number from the start of `input`, returning it and the number of bytes consumed.
+    fn read_json_number(input: &[u8]) -> anyhow::Result<(Self, usize)>;
 }
 
-impl<'p, 'de> SliceDecoder<'p, 'de> {
-    pub fn new(decoder: &'p mut SimpleJsonSpecDecoder<'de>) -> Self {
-        SliceDecoder { decoder, index: 0 }
+fn digit_at(input: &[u8], index: usize) -> Option<u8> {
+    input
+        .get(index)
+        .filter(|x| x.is_ascii_digit())
+        .map(|x| x - b'0')
+}
+
+macro_rules! impl_json_number_int {
+    ($($ty:ty),*) => {
+        $(
+            impl JsonNumber for $ty {
+                fn read_json_number(input: &[u8]) -> anyhow::Result<(Self, usize)> {
+                    let negative = input.first() == Some(&b'-');
+                    // Negative numbers are accumulated downwards so that MIN does not overflow.
+                    let step = |value: $ty, digit: u8| {
+                        let value = value.checked_mul(10)?;
+                        if negative {
+                            value.checked_sub(digit as $ty)
+                        } else {
+                            value.checked_add(digit as $ty)
+                        }
+                    };
+                    let mut index = negative as usize;
+                    let first = digit_at(input, index).ok_or(JsonDecoderError::UnexpectedInput)?;
+                    index += 1;
+                    let mut value = step(0, first).ok_or(JsonDecoderError::IntegerOverflow)?;
+                    if first != 0 {
+                        while let Some(digit) = digit_at(input, index) {
+                            value = step(value, digit).ok_or(JsonDecoderError::IntegerOverflow)?;
+                            index += 1;
+                        }
+                    }
+                    if let Some(b'.' | b'e' | b'E') = input.get(index) {
+                        return Err(JsonDecoderError::ParseIntError.into());
+                    }
+                    Ok((value, index))
+                }
+            }
+        )*
+    };
+}
+
+impl_json_number_int!(i8, i16, i32, i64, i128, u8, u16, u32, u64, u128);
+
+/// The most decimal digits that always fit in a `u64`.
+const MAX_DIGITS: usize = 19;
+
+/// A decimal number with up to [MAX_DIGITS] significant digits, `mantissa * 10^exponent`.
+#[derive(Default)]
+struct Decimal {
+    negative: bool,
+    mantissa: u64,
+    digits: usize,
+    exponent: i64,
+    truncated: bool,
+}
+
+impl Decimal {
+    fn push_digit(&mut self, digit: u8, fractional: bool) {
+        if self.mantissa == 0 && digit == 0 {
+            self.exponent -= fractional as i64;
+        } else if self.digits < MAX_DIGITS {
+            self.mantissa = self.mantissa * 10 + digit as u64;
+            self.digits += 1;
+            self.exponent -= fractional as i64;
+        } else {
+            self.truncated |= digit != 0;
+            self.exponent += !fractional as i64;
+        }
     }
-    pub fn try_consume_char(
-        &mut self,
-        expected: impl FnOnce(u8) -> bool,
-    ) -> anyhow::Result<Option<u8>> {
-        Ok(match self.decoder.try_peek_ahead(self.index)? {
-            None => None,
-            Some(c) => {
-                if expected(c) {
-                    self.index += 1;
-                    Some(c)
-                } else {
-                    None
+
+    fn read(input: &[u8]) -> anyhow::Result<(Self, usize)> {
+        let mut decimal = Decimal {
+            negative: input.first() == Some(&b'-'),
+            ..Decimal::default()
+        };
+        let mut index = decimal.negative as usize;
+        let first = digit_at(input, index).ok_or(JsonDecoderError::UnexpectedInput)?;
+        index += 1;
+        decimal.push_digit(first, false);
+        if first != 0 {
+            while let Some(digit) = digit_at(input, index) {
+                decimal.push_digit(digit, false);
+                index += 1;
+            }
+        }
+        if input.get(index) == Some(&b'.') {
+            index += 1;
+            let first = digit_at(input, index).ok_or(JsonDecoderError::UnexpectedInput)?;
+            decimal.push_digit(first, true);
+            index += 1;
+            while let Some(digit) = digit_at(input, index) {
+                decimal.push_digit(digit, true);
+                index += 1;
+            }
+        }
+        if let Some(b'e' | b'E') = input.get(index) {
+            index += 1;
+            let negative = input.get(index) == Some(&b'-');
+            if let Some(b'-' | b'+') = input.get(index) {
+                index += 1;
+            }
+            let first = digit_at(input, index).ok_or(JsonDecoderError::UnexpectedInput)?;
+            index += 1;
+            let mut exponent = first as i64;
+            while let Some(digit) = digit_at(input, index) {
+                // Anything this large is already zero or infinity.
+                if exponent < 0x10000 {
+                    exponent = exponent * 10 + digit as i64;
                 }
+                index += 1;
             }
-        })
+            decimal.exponent += if negative { -exponent } else { exponent };
+        }
+        Ok((decimal, index))
     }
-    pub fn try_consume_digit(&mut self) -> anyhow::Result<Option<u8>> {
-        Ok(self.try_consume_char(|x| x.is_ascii_digit())?)
+}
+
+fn read_float<F: Float + FromStr + Neg<Output = F>>(input: &[u8]) -> anyhow::Result<(F, usize)>
+where
+    <F as FromStr>::Err: 'static + Sync + Send + std::error::Error,
+{
+    let (decimal, len) = Decimal::read(input)?;
+    let value =
+        match decimal_to_float::<F>(decimal.mantissa, decimal.exponent, decimal.truncated) {
+            Some(value) if decimal.negative => -value,
+            Some(value) => value,
+            // The correctly rounded result depends on digits past the first nineteen.
+            None => std::str::from_utf8(&input[..len])?.parse()?,
+        };
+    Ok((value, len))
+}
+
+impl JsonNumber for f32 {
+    fn read_json_number(input: &[u8]) -> anyhow::Result<(Self, usize)> {
+        read_float(input)
     }
+}
 
-    pub fn end(self) -> anyhow::Result<&'de [u8]> {
-        self.decoder.read_count(self.index)
+impl JsonNumber for f64 {
+    fn read_json_number(input: &[u8]) -> anyhow::Result<(Self, usize)> {
+        read_float(input)
     }
 }
 
 impl<'de> SimpleJsonSpecDecoder<'de> {
-    pub fn read_number<T: FromStr>(&mut self) -> anyhow::Result<T>
-        where
-            <T as FromStr>::Err: 'static + Sync + Send + std::error::Error,
-    {
-        let mut slice = SliceDecoder::new(self);
-        slice.try_consume_char(|x| x == b'-')?;
-        match slice
-            .try_consume_digit()?
-            .ok_or(JsonDecoderError::UnexpectedInput)?
-        {
-            b'0' => {}
-            _ => while slice.try_consume_digit()?.is_some() {},
-        }
-        if slice.try_consume_char(|x| x == b'.')?.is_some() {
-            slice
-                .try_consume_digit()?
-                .ok_or(JsonDecoderError::UnexpectedInput)?;
-            while slice.try_consume_digit()?.is_some() {}
-        }
-        if slice
-            .try_consume_char(|x| x == b'E' || x == b'e')?
-            .is_some()
-        {
-            slice.try_consume_char(|x| x == b'-' || x == b'+')?;
-            slice
-                .try_consume_digit()?
-                .ok_or(JsonDecoderError::UnexpectedInput)?;
-            while slice.try_consume_digit()?.is_some() {}
-        }
-        let result = std::str::from_utf8(slice.end()?)?;
-        let result = result.parse()?;
+    pub fn read_number<T: JsonNumber>(&mut self) -> anyhow::Result<T> {
+        let (result, len) = T::read_json_number(self.cursor)?;
+        self.cursor = &self.cursor[len..];
         Ok(result)
     }
 }
@@ -0,0 +1,160 @@
+use std::error::Error;
+use std::fmt::{Debug, Display, Formatter};
+
+use marshal_core::decode::{AnySpecDecoder, DecodeHint, DecoderView};
+
+use crate::decode::full::JsonSpecDecoder;
+
+#[derive(Debug)]
+pub enum JsonPointerError {
+    MissingLeadingSlash,
+    BadEscape,
+    OverlappingPaths,
+}
+
+impl Display for JsonPointerError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Debug::fmt(self, f)
+    }
+}
+
+impl Error for JsonPointerError {}
+
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+enum Segment {
+    Wildcard,
+    Name { name: String, index: Option<usize> },
+}
+
+/// A path into a JSON document in the syntax of RFC 6901 (JSON Pointer), extended so that a
+/// segment of `*` matches every member of an object or element of an array.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct JsonPointer {
+    segments: Vec<Segment>,
+}
+
+impl JsonPointer {
+    pub fn parse(pointer: &str) -> anyhow::Result<Self> {
+        if pointer.is_empty() {
+            return Ok(JsonPointer { segments: vec![] });
+        }
+        let pointer = pointer
+            .strip_prefix('/')
+            .ok_or(JsonPointerError::MissingLeadingSlash)?;
+        let mut segments = vec![];
+        for segment in pointer.split('/') {
+            if segment == "*" {
+                segments.push(Segment::Wildcard);
+                continue;
+            }
+            let mut name = String::with_capacity(segment.len());
+            let mut chars = segment.chars();
+            while let Some(c) = chars.next() {
+                if c == '~' {
+                    match chars.next() {
+                        Some('0') => name.push('~'),
+                        Some('1') => name.push('/'),
+                        _ => return Err(JsonPointerError::BadEscape.into()),
+                    }
+                } else {
+                    name.push(c);
+                }
+            }
+            let index = if name == "0" || !name.starts_with('0') {
+                name.parse().ok()
+            } else {
+                None
+            };
+            segments.push(Segment::Name { name, index });
+        }
+        Ok(JsonPointer { segments })
+    }
+}
+
+fn matches_key(segment: &Segment, key: &str) -> bool {
+    match segment {
+        Segment::Wildcard => true,
+        Segment::Name { name, .. } => name == key,
+    }
+}
+
+fn matches_index(segment: &Segment, index: usize) -> bool {
+    match segment {
+        Segment::Wildcard => true,
+        Segment::Name { index: expected, .. } => *expected == Some(index),
+    }
+}
+
+/// Pushes the paths in `active[start..end]` whose segment at `depth` matches.
+fn push_matching(
+    active: &mut Vec<usize>,
+    start: usize,
+    end: usize,
+    mut matches: impl FnMut(usize) -> bool,
+) {
+    for position in start..end {
+        let index = active[position];
+        if matches(index) {
+            active.push(index);
+        }
+    }
+}
+
+/// Visits the subtrees at `paths`, passing everything else to [AnySpecDecoder::ignore].
+/// `active[start..]` holds the paths whose first `depth` segments lead to `d`. The paths of each
+/// nested value are pushed after them and truncated afterwards, so that one buffer is shared by
+/// the whole traversal.
+pub(crate) fn visit_paths<'p, 'de, F>(
+    d: AnySpecDecoder<'p, 'de, JsonSpecDecoder<'de>>,
+    paths: &[JsonPointer],
+    depth: usize,
+    active: &mut Vec<usize>,
+    start: usize,
+    f: &mut F,
+) -> anyhow::Result<()>
+where
+    F: for<'p2> FnMut(usize, AnySpecDecoder<'p2, 'de, JsonSpecDecoder<'de>>) -> anyhow::Result<()>,
+{
+    let end = active.len();
+    if start == end {
+        return d.ignore();
+    }
+    if let Some(&done) = active[start..]
+        .iter()
+        .find(|index| paths[**index].segments.len() == depth)
+    {
+        if end - start > 1 {
+            return Err(JsonPointerError::OverlappingPaths.into());
+        }
+        return f(done, d);
+    }
+    match d.decode(DecodeHint::Any)? {
+        DecoderView::Map(mut map) => {
+            while let Some(mut entry) = map.decode_next()? {
+                let key = entry
+                    .decode_key()?
+                    .decode(DecodeHint::String)?
+                    .try_into_string()?;
+                push_matching(active, start, end, |index| {
+                    matches_key(&paths[index].segments[depth], &key)
+                });
+                visit_paths(entry.decode_value()?, paths, depth + 1, active, end, f)?;
+                active.truncate(end);
+                entry.decode_end()?;
+            }
+        }
+        DecoderView::Seq(mut seq) => {
+            let mut position = 0;
+            while let Some(element) = seq.decode_next()? {
+                push_matching(active, start, end, |index| {
+                    matches_index(&paths[index].segments[depth], position)
+                });
+                visit_paths(element, paths, depth + 1, active, end, f)?;
+                active.truncate(end);
+                position += 1;
+            }
+        }
+        view => view.ignore()?,
+    }
+    Ok(())
+}
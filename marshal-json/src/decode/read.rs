@@ -1,7 +1,7 @@
 use marshal_core::{Primitive, PrimitiveType};
 
 use crate::decode::error::JsonDecoderError;
-use crate::decode::SimpleJsonSpecDecoder;
+use crate::decode::{scan, SimpleJsonSpecDecoder};
 
 impl<'de> SimpleJsonSpecDecoder<'de> {
     pub fn try_peek_char(&self) -> anyhow::Result<Option<u8>> {
@@ -50,7 +50,7 @@ impl<'de> SimpleJsonSpecDecoder<'de> {
         Ok(self.cursor.take(..count).ok_or(JsonDecoderError::Eof)?)
     }
     pub fn read_whitespace(&mut self) -> anyhow::Result<()> {
-        self.read_matches(|x| matches!(x, b' ' | b'\n' | b'\r' | b'\t'))?;
+        self.cursor = &self.cursor[scan::whitespace_len(self.cursor)..];
         Ok(())
     }
     pub fn try_read_exact(&mut self, expected: u8) -> anyhow::Result<bool> {
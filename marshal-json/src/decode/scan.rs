@@ -0,0 +1,74 @@
+use std::simd::prelude::*;
+
+const LANES: usize = 16;
+
+type Chunk = Simd<u8, LANES>;
+
+#[inline]
+fn is_whitespace(b: u8) -> bool {
+    matches!(b, b' ' | b'\n' | b'\r' | b'\t')
+}
+
+#[inline]
+fn is_string_special(b: u8) -> bool {
+    b == b'"' || b == b'\\' || b < 0x20
+}
+
+pub fn whitespace_len_scalar(input: &[u8]) -> usize {
+    input
+        .iter()
+        .position(|b| !is_whitespace(*b))
+        .unwrap_or(input.len())
+}
+
+pub fn string_plain_len_scalar(input: &[u8]) -> usize {
+    input
+        .iter()
+        .position(|b| is_string_special(*b))
+        .unwrap_or(input.len())
+}
+
+/// Returns the length of the run of JSON whitespace at the start of `input`.
+#[inline]
+pub fn whitespace_len(input: &[u8]) -> usize {
+    // Most runs are empty or a single separator, so check the first bytes before vectorizing.
+    match input {
+        [] => return 0,
+        [a, ..] if !is_whitespace(*a) => return 0,
+        [_, b, ..] if !is_whitespace(*b) => return 1,
+        _ => {}
+    }
+    let mut offset = 0;
+    while let Some(chunk) = input.get(offset..offset + LANES) {
+        let chunk = Chunk::from_slice(chunk);
+        let whitespace = chunk.simd_eq(Chunk::splat(b' '))
+            | chunk.simd_eq(Chunk::splat(b'\n'))
+            | chunk.simd_eq(Chunk::splat(b'\r'))
+            | chunk.simd_eq(Chunk::splat(b'\t'));
+        let other = (!whitespace).to_bitmask();
+        if other != 0 {
+            return offset + other.trailing_zeros() as usize;
+        }
+        offset += LANES;
+    }
+    offset + whitespace_len_scalar(&input[offset..])
+}
+
+/// Returns the length of the prefix of `input` that can be copied into a string verbatim,
+/// i.e. up to the next quote, backslash or control character.
+#[inline]
+pub fn string_plain_len(input: &[u8]) -> usize {
+    let mut offset = 0;
+    while let Some(chunk) = input.get(offset..offset + LANES) {
+        let chunk = Chunk::from_slice(chunk);
+        let special = chunk.simd_eq(Chunk::splat(b'"'))
+            | chunk.simd_eq(Chunk::splat(b'\\'))
+            | chunk.simd_lt(Chunk::splat(0x20));
+        let special = special.to_bitmask();
+        if special != 0 {
+            return offset + special.trailing_zeros() as usize;
+        }
+        offset += LANES;
+    }
+    offset + string_plain_len_scalar(&input[offset..])
+}
@@ -1,7 +1,7 @@
 use std::char::decode_utf16;
 
 use crate::decode::error::JsonDecoderError;
-use crate::decode::SimpleJsonSpecDecoder;
+use crate::decode::{scan, SimpleJsonSpecDecoder};
 
 impl<'de> SimpleJsonSpecDecoder<'de> {
     pub fn read_hex_u16(&mut self) -> anyhow::Result<u16> {
@@ -14,13 +14,11 @@ impl<'de> SimpleJsonSpecDecoder<'de> {
         self.read_exact(b'"')?;
         let mut result = String::new();
         loop {
-            let c = self.read_unicode()?;
-            if c as u32 <= 0x1F {
-                return Err(JsonDecoderError::StringContainsControl{control:c}.into());
-            }
-            match c {
-                '"' => break,
-                '\\' => {
+            let plain = self.read_count(scan::string_plain_len(self.cursor))?;
+            result.push_str(std::str::from_utf8(plain)?);
+            match self.read_char()? {
+                b'"' => break,
+                b'\\' => {
                     let escaped = self.read_char()?;
                     let escaped: char = match escaped {
                         b'"' => '"',
@@ -48,7 +46,12 @@ impl<'de> SimpleJsonSpecDecoder<'de> {
                     };
                     result.push(escaped);
                 }
-                x => result.push(x),
+                control => {
+                    return Err(JsonDecoderError::StringContainsControl {
+                        control: char::from(control),
+                    }
+                    .into())
+                }
             }
         }
         Ok(result)
@@ -4,9 +4,12 @@ use std::fs::read_dir;
 use marshal::context::OwnedContext;
 use marshal_core::{Primitive, PrimitiveType};
 use marshal_core::decode::{AnySpecDecoder, DecodeHint, DecoderView};
+use rand::{RngCore, SeedableRng};
+use rand_xorshift::XorShiftRng;
 
-use crate::decode::{JsonAnyDecoder, SimpleJsonSpecDecoder};
+use crate::decode::{scan, JsonAnyDecoder, JsonNumber, SimpleJsonSpecDecoder};
 use crate::decode::full::JsonDecoderBuilder;
+use crate::decode::path::JsonPointer;
 use crate::value::JsonValue;
 
 #[test]
@@ -66,3 +69,189 @@ fn test_parsing() {
         }
     }
 }
+
+#[test]
+fn test_extract() -> anyhow::Result<()> {
+    let input = br#"{
+        "meta": {"count": 3, "tags": ["a", "b"]},
+        "data": {
+            "items": [
+                {"id": 10, "name": "x", "extra": [1, 2, {"id": 99}]},
+                {"name": "y", "id": 20},
+                {"id": 30}
+            ],
+            "a/b": {"c~d": "escaped"}
+        }
+    }"#;
+    let paths = [
+        JsonPointer::parse("/data/items/*/id")?,
+        JsonPointer::parse("/meta/count")?,
+        JsonPointer::parse("/missing")?,
+    ];
+    let found = JsonDecoderBuilder::new(input)
+        .extract::<u32>(&paths, OwnedContext::new().borrow())?;
+    assert_eq!(found, vec![vec![10, 20, 30], vec![3], vec![]]);
+    let paths = [
+        JsonPointer::parse("/data/a~1b/c~0d")?,
+        JsonPointer::parse("/meta/tags/1")?,
+        JsonPointer::parse("/data/items/1/name")?,
+    ];
+    let found = JsonDecoderBuilder::new(input)
+        .extract::<String>(&paths, OwnedContext::new().borrow())?;
+    assert_eq!(
+        found,
+        vec![
+            vec!["escaped".to_string()],
+            vec!["b".to_string()],
+            vec!["y".to_string()]
+        ]
+    );
+    assert!(JsonDecoderBuilder::new(input)
+        .extract::<JsonValue>(
+            &[JsonPointer::parse("/meta")?, JsonPointer::parse("/meta/count")?],
+            OwnedContext::new().borrow(),
+        )
+        .is_err());
+    Ok(())
+}
+
+#[test]
+fn test_scan_differential() {
+    let alphabet = [
+        b' ', b'\n', b'\r', b'\t', b'"', b'\\', 0x00, 0x1f, b'a', b'/', 0x7f, 0x80, 0xff,
+    ];
+    let mut rng = XorShiftRng::seed_from_u64(0x2545f4914f6cdd1d);
+    for len in 0..100 {
+        for _ in 0..100 {
+            let input: Vec<u8> = (0..len)
+                .map(|_| alphabet[(rng.next_u64() % alphabet.len() as u64) as usize])
+                .collect();
+            assert_eq!(scan::whitespace_len(&input), scan::whitespace_len_scalar(&input));
+            assert_eq!(
+                scan::string_plain_len(&input),
+                scan::string_plain_len_scalar(&input)
+            );
+        }
+        for background in [b' ', b'\t', b'a', 0xc3] {
+            for special in alphabet {
+                for position in 0..len {
+                    let mut input = vec![background; len];
+                    input[position] = special;
+                    assert_eq!(scan::whitespace_len(&input), scan::whitespace_len_scalar(&input));
+                    assert_eq!(
+                        scan::string_plain_len(&input),
+                        scan::string_plain_len_scalar(&input)
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_read_string_spans() -> anyhow::Result<()> {
+    for prefix in 0..40 {
+        let plain = "é".repeat(prefix);
+        let input = format!("\"{}\\n{}\\u00e9\"", plain, plain);
+        let mut p = SimpleJsonSpecDecoder::new(input.as_bytes());
+        assert_eq!(p.read_string()?, format!("{}\n{}é", plain, plain));
+        let input = format!("\"{}\u{0001}\"", plain);
+        let mut p = SimpleJsonSpecDecoder::new(input.as_bytes());
+        assert!(p.read_string().is_err());
+    }
+    Ok(())
+}
+
+#[track_caller]
+fn assert_int_bounds<T: JsonNumber + std::fmt::Display + PartialEq + std::fmt::Debug>(
+    min: T,
+    max: T,
+    below: &str,
+    above: &str,
+) {
+    for x in [min, max] {
+        let text = x.to_string();
+        assert_eq!(T::read_json_number(text.as_bytes()).unwrap(), (x, text.len()));
+    }
+    assert!(T::read_json_number(below.as_bytes()).is_err());
+    assert!(T::read_json_number(above.as_bytes()).is_err());
+}
+
+#[test]
+fn test_read_int() {
+    assert_int_bounds(i8::MIN, i8::MAX, "-129", "128");
+    assert_int_bounds(i16::MIN, i16::MAX, "-32769", "32768");
+    assert_int_bounds(i32::MIN, i32::MAX, "-2147483649", "2147483648");
+    assert_int_bounds(i64::MIN, i64::MAX, "-9223372036854775809", "9223372036854775808");
+    assert_int_bounds(
+        i128::MIN,
+        i128::MAX,
+        "-170141183460469231731687303715884105729",
+        "170141183460469231731687303715884105728",
+    );
+    assert_int_bounds(u8::MIN, u8::MAX, "-1", "256");
+    assert_int_bounds(u16::MIN, u16::MAX, "-1", "65536");
+    assert_int_bounds(u32::MIN, u32::MAX, "-1", "4294967296");
+    assert_int_bounds(u64::MIN, u64::MAX, "-1", "18446744073709551616");
+    assert_int_bounds(
+        u128::MIN,
+        u128::MAX,
+        "-1",
+        "340282366920938463463374607431768211456",
+    );
+    assert_eq!(u8::read_json_number(b"-0").unwrap(), (0, 2));
+    assert_eq!(u8::read_json_number(b"012").unwrap(), (0, 1));
+    assert_eq!(u8::read_json_number(b"12]").unwrap(), (12, 2));
+    assert!(u8::read_json_number(b"1.0").is_err());
+    assert!(u8::read_json_number(b"1e2").is_err());
+    assert!(u8::read_json_number(b"-").is_err());
+}
+
+#[test]
+fn test_read_float_differential() {
+    let mut rng = XorShiftRng::seed_from_u64(0x2545f4914f6cdd1d);
+    let mut inputs = vec![
+        "0".to_string(),
+        "-0".to_string(),
+        "0.1".to_string(),
+        "1e23".to_string(),
+        "2.2250738585072011e-308".to_string(),
+        "2.2250738585072012e-308".to_string(),
+        "4.9406564584124654e-324".to_string(),
+        "2.4703282292062327e-324".to_string(),
+        "2.4703282292062328e-324".to_string(),
+        "1.7976931348623157e308".to_string(),
+        "1.7976931348623159e308".to_string(),
+        "9007199254740993".to_string(),
+        "9007199254740993.0000000000000000000001".to_string(),
+        "0.000000000000000000000000000000000000000000001e45".to_string(),
+        "1e-400".to_string(),
+        "1e400".to_string(),
+        "123456789012345678901234567890e-10".to_string(),
+        "3.4028235677973366e38".to_string(),
+        "1.00000005960464477539062499".to_string(),
+        "1.000000059604644775390625".to_string(),
+    ];
+    for _ in 0..10000 {
+        let bits = rng.next_u64();
+        inputs.push(format!("{:e}", f64::from_bits(bits)));
+        inputs.push(format!("{:e}", f32::from_bits(bits as u32)));
+        let digits = rng.next_u64() % 30 + 1;
+        let mut text: String = (0..digits)
+            .map(|_| char::from(b'0' + (rng.next_u64() % 10) as u8))
+            .collect();
+        text.insert(0, '1');
+        text.push_str(&format!("e{}", (rng.next_u64() % 700) as i64 - 350));
+        inputs.push(text);
+    }
+    for input in inputs {
+        if input.contains("inf") || input.contains("NaN") {
+            continue;
+        }
+        let (actual, len) = f64::read_json_number(input.as_bytes()).unwrap();
+        assert_eq!(len, input.len());
+        assert_eq!(actual.to_bits(), input.parse::<f64>().unwrap().to_bits(), "{}", input);
+        let (actual, _) = f32::read_json_number(input.as_bytes()).unwrap();
+        assert_eq!(actual.to_bits(), input.parse::<f32>().unwrap().to_bits(), "{}", input);
+    }
+}
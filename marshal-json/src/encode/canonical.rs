@@ -0,0 +1,69 @@
+use crate::encode::JsonEncoderError;
+
+/// The largest integer that survives a round trip through an IEEE double.
+const MAX_SAFE_INTEGER: u128 = (1 << 53) - 1;
+
+pub fn check_safe_integer(magnitude: u128) -> anyhow::Result<()> {
+    if magnitude > MAX_SAFE_INTEGER {
+        return Err(JsonEncoderError::UnsafeInteger.into());
+    }
+    Ok(())
+}
+
+/// Reads the key at the start of an encoded object member as UTF-16 code units, which is the
+/// sort order required by RFC 8785.
+fn read_key(entry: &[u8]) -> anyhow::Result<Vec<u16>> {
+    let mut chars = std::str::from_utf8(entry)?.chars();
+    if chars.next() != Some('"') {
+        return Err(JsonEncoderError::MustBeString.into());
+    }
+    let mut key = vec![];
+    let mut buffer = [0u16; 2];
+    loop {
+        let c = match chars.next().ok_or(JsonEncoderError::MustBeString)? {
+            '"' => break,
+            '\\' => match chars.next().ok_or(JsonEncoderError::MustBeString)? {
+                'b' => '\u{0008}',
+                'f' => '\u{000c}',
+                'n' => '\n',
+                'r' => '\r',
+                't' => '\t',
+                'u' => {
+                    let hex: String = chars.by_ref().take(4).collect();
+                    key.push(u16::from_str_radix(&hex, 16)?);
+                    continue;
+                }
+                c => c,
+            },
+            c => c,
+        };
+        key.extend_from_slice(c.encode_utf16(&mut buffer));
+    }
+    Ok(key)
+}
+
+/// Reorders the members of an object that begin at `starts` (and run to the end of `output`)
+/// by key, inserting the separating commas.
+pub fn sort_members(output: &mut Vec<u8>, starts: &[usize]) -> anyhow::Result<()> {
+    let Some(&first) = starts.first() else {
+        return Ok(());
+    };
+    let tail = output.split_off(first);
+    let mut members = Vec::with_capacity(starts.len());
+    for (index, start) in starts.iter().enumerate() {
+        let end = starts.get(index + 1).map_or(tail.len(), |end| end - first);
+        let member = &tail[start - first..end];
+        members.push((read_key(member)?, member));
+    }
+    members.sort_by(|(k1, _), (k2, _)| k1.cmp(k2));
+    for (index, (key, member)) in members.iter().enumerate() {
+        if index > 0 {
+            if members[index - 1].0 == *key {
+                return Err(JsonEncoderError::DuplicateKey.into());
+            }
+            output.push(b',');
+        }
+        output.extend_from_slice(member);
+    }
+    Ok(())
+}
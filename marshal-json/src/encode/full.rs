@@ -5,6 +5,7 @@ use marshal_core::encode::{AnySpecEncoder, Encoder};
 use marshal_core::encode::poison::PoisonEncoder;
 
 use crate::encode::{JsonAnySpecEncoder, SimpleJsonSpecEncoder};
+use crate::format::{BigIntFormat, BytesFormat, NonFiniteFormat};
 
 pub struct JsonSpecEncoder(PoisonEncoder<SimpleJsonSpecEncoder>);
 
@@ -20,6 +21,22 @@ impl JsonEncoderBuilder {
             inner: JsonSpecEncoder(PoisonEncoder::new(SimpleJsonSpecEncoder::new())),
         }
     }
+    pub fn set_canonical(&mut self, canonical: bool) -> &mut Self {
+        self.inner.0.inner_mut().set_canonical(canonical);
+        self
+    }
+    pub fn set_bytes_format(&mut self, bytes_format: BytesFormat) -> &mut Self {
+        self.inner.0.inner_mut().set_bytes_format(bytes_format);
+        self
+    }
+    pub fn set_big_int_format(&mut self, big_int_format: BigIntFormat) -> &mut Self {
+        self.inner.0.inner_mut().set_big_int_format(big_int_format);
+        self
+    }
+    pub fn set_non_finite_format(&mut self, non_finite_format: NonFiniteFormat) -> &mut Self {
+        self.inner.0.inner_mut().set_non_finite_format(non_finite_format);
+        self
+    }
     pub fn build(&mut self) -> AnySpecEncoder<'_, JsonSpecEncoder> {
         let any = self.inner.0.start(JsonAnySpecEncoder::new());
         AnySpecEncoder::new(&mut self.inner, any)
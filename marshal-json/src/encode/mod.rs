@@ -4,18 +4,27 @@ use std::fmt::{Debug, Display, Formatter};
 use std::io::Write;
 
 use base64::Engine;
-use base64::prelude::BASE64_STANDARD_NO_PAD;
 
 use marshal_core::encode::{AnySpecEncoder, SpecEncoder};
 use marshal_core::Primitive;
 
+use crate::encode::ryu::Shortest;
+use crate::format::{BigIntFormat, BytesFormat, NonFiniteFormat};
+
+mod canonical;
 pub mod full;
+mod ryu;
+mod ryu_table;
 #[cfg(test)]
 mod test;
 
 pub struct SimpleJsonSpecEncoder {
     output: Vec<u8>,
     current_indentation: Option<usize>,
+    canonical: bool,
+    bytes_format: BytesFormat,
+    big_int_format: BigIntFormat,
+    non_finite_format: NonFiniteFormat,
 }
 
 #[derive(Debug)]
@@ -23,6 +32,8 @@ pub enum JsonEncoderError {
     BadNumber,
     NumericOverflow,
     MustBeString,
+    UnsafeInteger,
+    DuplicateKey,
 }
 
 impl Display for JsonEncoderError {
@@ -38,8 +49,27 @@ impl SimpleJsonSpecEncoder {
         SimpleJsonSpecEncoder {
             output: vec![],
             current_indentation: Some(0),
+            canonical: false,
+            bytes_format: BytesFormat::default(),
+            big_int_format: BigIntFormat::default(),
+            non_finite_format: NonFiniteFormat::default(),
         }
     }
+    /// Produce the canonical form defined by RFC 8785 (JCS): no whitespace, object members
+    /// sorted by key, and numbers formatted as in ECMAScript. NaN and infinities are rejected,
+    /// whatever the [NonFiniteFormat].
+    pub fn set_canonical(&mut self, canonical: bool) {
+        self.canonical = canonical;
+    }
+    pub fn set_bytes_format(&mut self, bytes_format: BytesFormat) {
+        self.bytes_format = bytes_format;
+    }
+    pub fn set_big_int_format(&mut self, big_int_format: BigIntFormat) {
+        self.big_int_format = big_int_format;
+    }
+    pub fn set_non_finite_format(&mut self, non_finite_format: NonFiniteFormat) {
+        self.non_finite_format = non_finite_format;
+    }
     pub fn start(&mut self) -> AnySpecEncoder<SimpleJsonSpecEncoder> {
         AnySpecEncoder::new(
             self,
@@ -51,6 +81,9 @@ impl SimpleJsonSpecEncoder {
         )
     }
     fn set_indentation(&mut self, indentation: usize) -> anyhow::Result<()> {
+        if self.canonical {
+            return Ok(());
+        }
         if let Some(current) = self.current_indentation {
             if current != indentation {
                 writeln!(&mut self.output)?;
@@ -87,6 +120,53 @@ impl SimpleJsonSpecEncoder {
         Ok(())
     }
 
+    fn write_int(
+        &mut self,
+        e: JsonAnySpecEncoder,
+        value: impl Display,
+        magnitude: u128,
+    ) -> anyhow::Result<()> {
+        if self.canonical && !e.must_be_string {
+            canonical::check_safe_integer(magnitude)?;
+        }
+        self.write_prim(e, value)
+    }
+
+    fn write_big_int(
+        &mut self,
+        e: JsonAnySpecEncoder,
+        value: impl Display,
+        magnitude: u128,
+    ) -> anyhow::Result<()> {
+        match self.big_int_format {
+            BigIntFormat::Number => self.write_int(e, value, magnitude),
+            BigIntFormat::String => self.write_prim(
+                JsonAnySpecEncoder {
+                    must_be_string: true,
+                    ..e
+                },
+                value,
+            ),
+        }
+    }
+
+    fn write_float(&mut self, e: JsonAnySpecEncoder, value: f64) -> anyhow::Result<()> {
+        if !value.is_finite() {
+            if self.canonical {
+                return Err(JsonEncoderError::BadNumber.into());
+            }
+            return self.write_non_finite(e, value);
+        }
+        if !self.canonical {
+            self.write_prim(e, Shortest::from_f64(value).plain())
+        } else if value == 0.0 {
+            // RFC 8785 does not distinguish negative zero.
+            self.write_prim(e, Shortest::from_f64(0.0))
+        } else {
+            self.write_prim(e, Shortest::from_f64(value))
+        }
+    }
+
     fn writeln(&mut self, ctx: EncodeContext, value: impl Display) -> anyhow::Result<()> {
         self.set_indentation(ctx.indentation)?;
         write!(&mut self.output, "{}\n", value)?;
@@ -108,6 +188,27 @@ impl SimpleJsonSpecEncoder {
     fn close_list(&mut self, ctx: EncodeContext) -> anyhow::Result<()> {
         self.write(ctx, "]")
     }
+    fn write_non_finite(&mut self, e: JsonAnySpecEncoder, value: f64) -> anyhow::Result<()> {
+        match self.non_finite_format {
+            NonFiniteFormat::Error => Err(JsonEncoderError::BadNumber.into()),
+            NonFiniteFormat::Null => {
+                if e.must_be_string {
+                    return Err(JsonEncoderError::MustBeString.into());
+                }
+                self.write_null(e.ctx)
+            }
+            NonFiniteFormat::String => {
+                let name = if value.is_nan() {
+                    "NaN"
+                } else if value > 0.0 {
+                    "Infinity"
+                } else {
+                    "-Infinity"
+                };
+                self.write_str_literal(e.ctx, name)
+            }
+        }
+    }
     fn write_str_literal(&mut self, ctx: EncodeContext, s: &str) -> anyhow::Result<()> {
         self.write(ctx, "\"")?;
         for c in s.chars() {
@@ -129,10 +230,38 @@ impl SimpleJsonSpecEncoder {
         Ok(())
     }
     fn write_colon(&mut self, ctx: EncodeContext) -> anyhow::Result<()> {
-        self.write(ctx, ": ")
+        if self.canonical {
+            self.write(ctx, ":")
+        } else {
+            self.write(ctx, ": ")
+        }
     }
     fn write_comma(&mut self, ctx: EncodeContext) -> anyhow::Result<()> {
-        self.writeln(ctx, ",")
+        if self.canonical {
+            self.write(ctx, ",")
+        } else {
+            self.writeln(ctx, ",")
+        }
+    }
+    fn start_member(
+        &mut self,
+        ctx: EncodeContext,
+        started: &mut bool,
+        starts: &mut Vec<usize>,
+    ) -> anyhow::Result<()> {
+        if self.canonical {
+            starts.push(self.output.len());
+        } else if *started {
+            self.write_comma(ctx)?;
+        }
+        *started = true;
+        Ok(())
+    }
+    fn end_members(&mut self, starts: &[usize]) -> anyhow::Result<()> {
+        if self.canonical {
+            canonical::sort_members(&mut self.output, starts)?;
+        }
+        Ok(())
     }
     fn write_triv(&mut self, any: JsonAnySpecEncoder) -> anyhow::Result<()> {
         if any.cannot_be_null {
@@ -162,30 +291,24 @@ impl SpecEncoder for SimpleJsonSpecEncoder {
         match prim {
             Primitive::Unit => self.write_triv(any),
             Primitive::Bool(x) => self.write_prim(any, x),
-            Primitive::I8(x) => self.write_prim(any, x),
-            Primitive::I16(x) => self.write_prim(any, x),
-            Primitive::I32(x) => self.write_prim(any, x),
-            Primitive::I64(x) => self.write_prim(any, x),
-            Primitive::I128(x) => self.write_prim(any, x),
-            Primitive::U8(x) => self.write_prim(any, x),
-            Primitive::U16(x) => self.write_prim(any, x),
-            Primitive::U32(x) => self.write_prim(any, x),
-            Primitive::U64(x) => self.write_prim(any, x),
-            Primitive::U128(x) => self.write_prim(any, x),
+            Primitive::I8(x) => self.write_int(any, x, x.unsigned_abs() as u128),
+            Primitive::I16(x) => self.write_int(any, x, x.unsigned_abs() as u128),
+            Primitive::I32(x) => self.write_int(any, x, x.unsigned_abs() as u128),
+            Primitive::I64(x) => self.write_int(any, x, x.unsigned_abs() as u128),
+            Primitive::I128(x) => self.write_big_int(any, x, x.unsigned_abs()),
+            Primitive::U8(x) => self.write_int(any, x, x as u128),
+            Primitive::U16(x) => self.write_int(any, x, x as u128),
+            Primitive::U32(x) => self.write_int(any, x, x as u128),
+            Primitive::U64(x) => self.write_int(any, x, x as u128),
+            Primitive::U128(x) => self.write_big_int(any, x, x),
             Primitive::F32(x) => {
-                if x.is_finite() {
-                    self.write_prim(any, x)
+                if x.is_finite() && !self.canonical {
+                    self.write_prim(any, Shortest::from_f32(x).plain())
                 } else {
-                    return Err(JsonEncoderError::BadNumber)?;
-                }
-            }
-            Primitive::F64(x) => {
-                if x.is_finite() {
-                    self.write_prim(any, x)
-                } else {
-                    return Err(JsonEncoderError::BadNumber)?;
+                    self.write_float(any, x as f64)
                 }
             }
+            Primitive::F64(x) => self.write_float(any, x),
             Primitive::Char(x) => self.write_str_literal(any.ctx, x.encode_utf8(&mut [0u8; 4])),
         }
     }
@@ -195,12 +318,33 @@ impl SpecEncoder for SimpleJsonSpecEncoder {
     }
 
     fn encode_bytes(&mut self, any: Self::AnySpecEncoder, s: &[u8]) -> anyhow::Result<()> {
-        self.write(any.ctx, "\"")?;
-        let len = base64::encoded_len(s.len(), false).ok_or(JsonEncoderError::NumericOverflow)?;
-        let start = self.output.len();
-        self.output.resize(start + len, 0);
-        BASE64_STANDARD_NO_PAD.encode_slice(s, &mut self.output[start..])?;
-        self.write(any.ctx, "\"")?;
+        if let Some((engine, padding)) = self.bytes_format.base64_engine() {
+            self.write(any.ctx, "\"")?;
+            let len =
+                base64::encoded_len(s.len(), padding).ok_or(JsonEncoderError::NumericOverflow)?;
+            let start = self.output.len();
+            self.output.resize(start + len, 0);
+            engine.encode_slice(s, &mut self.output[start..])?;
+            self.write(any.ctx, "\"")?;
+            return Ok(());
+        }
+        match self.bytes_format {
+            BytesFormat::Hex => {
+                self.write(any.ctx, "\"")?;
+                for b in s {
+                    write!(&mut self.output, "{:02x}", b)?;
+                }
+                self.write(any.ctx, "\"")?;
+            }
+            _ => {
+                let mut seq = self.encode_seq(any, s.len())?;
+                for b in s {
+                    let element = self.seq_encode_element(&mut seq)?;
+                    self.encode_prim(element, Primitive::U8(*b))?;
+                }
+                self.seq_end(seq)?;
+            }
+        }
         Ok(())
     }
 
@@ -302,6 +446,7 @@ impl SpecEncoder for SimpleJsonSpecEncoder {
         Ok(JsonStructEncoder {
             ctx: any.ctx,
             started: false,
+            starts: vec![],
         })
     }
 
@@ -369,6 +514,7 @@ impl SpecEncoder for SimpleJsonSpecEncoder {
         Ok(JsonStructVariantEncoder {
             ctx: any.ctx,
             started: false,
+            starts: vec![],
         })
     }
 
@@ -417,6 +563,7 @@ impl SpecEncoder for SimpleJsonSpecEncoder {
         Ok(JsonMapEncoder {
             ctx: any.ctx,
             started: false,
+            starts: vec![],
         })
     }
 
@@ -473,10 +620,7 @@ impl SpecEncoder for SimpleJsonSpecEncoder {
         map: &mut Self::MapEncoder,
     ) -> anyhow::Result<(Self::AnySpecEncoder, Self::ValueEncoder)> {
         let ctx = map.ctx.indent();
-        if map.started {
-            self.write_comma(ctx)?;
-        }
-        map.started = true;
+        self.start_member(ctx, &mut map.started, &mut map.starts)?;
         Ok((
             JsonAnySpecEncoder {
                 ctx,
@@ -488,6 +632,7 @@ impl SpecEncoder for SimpleJsonSpecEncoder {
     }
 
     fn map_end(&mut self, map: Self::MapEncoder) -> anyhow::Result<()> {
+        self.end_members(&map.starts)?;
         self.close_map(map.ctx)?;
         Ok(())
     }
@@ -538,10 +683,7 @@ impl SpecEncoder for SimpleJsonSpecEncoder {
         key: &'static str,
     ) -> anyhow::Result<Self::AnySpecEncoder> {
         let ctx = s.ctx.indent();
-        if s.started {
-            self.write_comma(ctx)?;
-        }
-        s.started = true;
+        self.start_member(ctx, &mut s.started, &mut s.starts)?;
         self.write_str_literal(ctx, key)?;
         self.write_colon(ctx)?;
         Ok(JsonAnySpecEncoder {
@@ -552,6 +694,7 @@ impl SpecEncoder for SimpleJsonSpecEncoder {
     }
 
     fn struct_end(&mut self, s: Self::StructEncoder) -> anyhow::Result<()> {
+        self.end_members(&s.starts)?;
         self.close_map(s.ctx)?;
         Ok(())
     }
@@ -584,10 +727,7 @@ impl SpecEncoder for SimpleJsonSpecEncoder {
         key: &'static str,
     ) -> anyhow::Result<Self::AnySpecEncoder> {
         let ctx = s.ctx.indent().indent();
-        if s.started {
-            self.write_comma(ctx)?;
-        }
-        s.started = true;
+        self.start_member(ctx, &mut s.started, &mut s.starts)?;
         self.write_str_literal(ctx, key)?;
         self.write_colon(ctx)?;
         Ok(JsonAnySpecEncoder {
@@ -598,6 +738,7 @@ impl SpecEncoder for SimpleJsonSpecEncoder {
     }
 
     fn struct_variant_end(&mut self, s: Self::StructVariantEncoder) -> anyhow::Result<()> {
+        self.end_members(&s.starts)?;
         self.close_map(s.ctx.indent())?;
         self.close_map(s.ctx)?;
         Ok(())
@@ -664,6 +805,7 @@ pub struct JsonSeqEncoder {
 pub struct JsonMapEncoder {
     ctx: EncodeContext,
     started: bool,
+    starts: Vec<usize>,
 }
 
 pub struct JsonKeyEncoder {
@@ -686,6 +828,7 @@ pub struct JsonTupleStructEncoder {
 pub struct JsonStructEncoder {
     ctx: EncodeContext,
     started: bool,
+    starts: Vec<usize>,
 }
 
 pub struct JsonTupleVariantEncoder {
@@ -696,4 +839,5 @@ pub struct JsonTupleVariantEncoder {
 pub struct JsonStructVariantEncoder {
     ctx: EncodeContext,
     started: bool,
+    starts: Vec<usize>,
 }
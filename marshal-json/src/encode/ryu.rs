@@ -0,0 +1,254 @@
+use std::fmt;
+use std::fmt::{Display, Formatter};
+
+use crate::encode::ryu_table::{POW5_INV_SPLIT, POW5_SPLIT};
+
+const POW5_INV_BITCOUNT: i32 = 125;
+const POW5_BITCOUNT: i32 = 125;
+
+/// `ceil(log2(5^e))`, or 1 for `e == 0`.
+fn pow5bits(e: i32) -> i32 {
+    (((e as u32 * 1217359) >> 19) + 1) as i32
+}
+
+/// `floor(log10(2^e))`.
+fn log10_pow2(e: i32) -> u32 {
+    (e as u32 * 78913) >> 18
+}
+
+/// `floor(log10(5^e))`.
+fn log10_pow5(e: i32) -> u32 {
+    (e as u32 * 732923) >> 20
+}
+
+fn multiple_of_power_of_5(mut value: u64, p: u32) -> bool {
+    let mut count = 0;
+    while value % 5 == 0 && count < p {
+        value /= 5;
+        count += 1;
+    }
+    count >= p
+}
+
+fn multiple_of_power_of_2(value: u64, p: u32) -> bool {
+    value & ((1u64 << p) - 1) == 0
+}
+
+fn mul_shift(m: u64, (low, high): (u64, u64), j: i32) -> u64 {
+    let b0 = m as u128 * low as u128;
+    let b2 = m as u128 * high as u128;
+    (((b0 >> 64) + b2) >> (j - 64)) as u64
+}
+
+/// The shortest decimal `mantissa * 10^exponent` that rounds to a float.
+#[derive(Debug, Eq, PartialEq)]
+pub struct ShortestDecimal {
+    pub mantissa: u64,
+    pub exponent: i32,
+}
+
+impl ShortestDecimal {
+    /// Computes the shortest representation of a positive float with the given IEEE fields,
+    /// using the Ryu algorithm.
+    fn new(mantissa_bits: u32, bias: i32, ieee_mantissa: u64, ieee_exponent: u32) -> Self {
+        let (e2, m2) = if ieee_exponent == 0 {
+            (1 - bias - mantissa_bits as i32 - 2, ieee_mantissa)
+        } else {
+            (
+                ieee_exponent as i32 - bias - mantissa_bits as i32 - 2,
+                (1u64 << mantissa_bits) | ieee_mantissa,
+            )
+        };
+        let accept_bounds = m2 % 2 == 0;
+
+        // The interval of decimals that round to this float is (mm, mp) in units of 2^e2.
+        let mv = 4 * m2;
+        let mp = 4 * m2 + 2;
+        let mm_shift = (ieee_mantissa != 0 || ieee_exponent <= 1) as u64;
+        let mm = 4 * m2 - 1 - mm_shift;
+
+        let mut vr;
+        let mut vp;
+        let mut vm;
+        let e10;
+        let mut vm_is_trailing_zeros = false;
+        let mut vr_is_trailing_zeros = false;
+        if e2 >= 0 {
+            let q = log10_pow2(e2) - (e2 > 3) as u32;
+            e10 = q as i32;
+            let k = POW5_INV_BITCOUNT + pow5bits(q as i32) - 1;
+            let i = -e2 + q as i32 + k;
+            let mul = POW5_INV_SPLIT[q as usize];
+            vr = mul_shift(mv, mul, i);
+            vp = mul_shift(mp, mul, i);
+            vm = mul_shift(mm, mul, i);
+            if q <= 21 {
+                if mv % 5 == 0 {
+                    vr_is_trailing_zeros = multiple_of_power_of_5(mv, q);
+                } else if accept_bounds {
+                    vm_is_trailing_zeros = multiple_of_power_of_5(mm, q);
+                } else {
+                    vp -= multiple_of_power_of_5(mp, q) as u64;
+                }
+            }
+        } else {
+            let q = log10_pow5(-e2) - (-e2 > 1) as u32;
+            e10 = q as i32 + e2;
+            let i = -e2 - q as i32;
+            let k = pow5bits(i) - POW5_BITCOUNT;
+            let j = q as i32 - k;
+            let mul = POW5_SPLIT[i as usize];
+            vr = mul_shift(mv, mul, j);
+            vp = mul_shift(mp, mul, j);
+            vm = mul_shift(mm, mul, j);
+            if q <= 1 {
+                vr_is_trailing_zeros = true;
+                if accept_bounds {
+                    vm_is_trailing_zeros = mm_shift == 1;
+                } else {
+                    vp -= 1;
+                }
+            } else if q < 63 {
+                vr_is_trailing_zeros = multiple_of_power_of_2(mv, q);
+            }
+        }
+
+        // Remove digits while the interval still contains more than one candidate.
+        let mut removed = 0;
+        let mut last_removed_digit = 0;
+        let mantissa = if vm_is_trailing_zeros || vr_is_trailing_zeros {
+            while vp / 10 > vm / 10 {
+                vm_is_trailing_zeros &= vm % 10 == 0;
+                vr_is_trailing_zeros &= last_removed_digit == 0;
+                last_removed_digit = vr % 10;
+                vr /= 10;
+                vp /= 10;
+                vm /= 10;
+                removed += 1;
+            }
+            if vm_is_trailing_zeros {
+                while vm % 10 == 0 {
+                    vr_is_trailing_zeros &= last_removed_digit == 0;
+                    last_removed_digit = vr % 10;
+                    vr /= 10;
+                    vm /= 10;
+                    removed += 1;
+                }
+            }
+            if vr_is_trailing_zeros && last_removed_digit == 5 && vr % 2 == 0 {
+                // Exactly halfway, so round to even.
+                last_removed_digit = 4;
+            }
+            let outside = vr == vm && (!accept_bounds || !vm_is_trailing_zeros);
+            vr + (outside || last_removed_digit >= 5) as u64
+        } else {
+            let mut round_up = false;
+            while vp / 10 > vm / 10 {
+                round_up = vr % 10 >= 5;
+                vr /= 10;
+                vp /= 10;
+                vm /= 10;
+                removed += 1;
+            }
+            vr + (vr == vm || round_up) as u64
+        };
+        ShortestDecimal {
+            mantissa,
+            exponent: e10 + removed,
+        }
+    }
+
+    pub fn from_f64(x: f64) -> Self {
+        let bits = x.to_bits();
+        Self::new(52, 1023, bits & ((1 << 52) - 1), ((bits >> 52) & 0x7FF) as u32)
+    }
+
+    pub fn from_f32(x: f32) -> Self {
+        let bits = x.to_bits();
+        Self::new(23, 127, (bits & ((1 << 23) - 1)) as u64, (bits >> 23) & 0xFF)
+    }
+}
+
+/// Displays a finite float with the fewest digits that read back as the same value. The
+/// layout is that of ECMAScript's `Number.prototype.toString`, with the sign of zero preserved.
+/// A [plain](Self::plain) float never uses an exponent, like Rust's `Display`.
+pub struct Shortest {
+    negative: bool,
+    decimal: Option<ShortestDecimal>,
+    plain: bool,
+}
+
+impl Shortest {
+    pub fn from_f64(x: f64) -> Self {
+        Shortest {
+            negative: x.is_sign_negative(),
+            decimal: (x != 0.0).then(|| ShortestDecimal::from_f64(x.abs())),
+            plain: false,
+        }
+    }
+
+    pub fn from_f32(x: f32) -> Self {
+        Shortest {
+            negative: x.is_sign_negative(),
+            decimal: (x != 0.0).then(|| ShortestDecimal::from_f32(x.abs())),
+            plain: false,
+        }
+    }
+
+    pub fn plain(self) -> Self {
+        Shortest {
+            plain: true,
+            ..self
+        }
+    }
+}
+
+impl Display for Shortest {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if self.negative {
+            f.write_str("-")?;
+        }
+        let Some(decimal) = &self.decimal else {
+            return f.write_str("0");
+        };
+        let mut buffer = [0u8; 20];
+        let mut start = buffer.len();
+        let mut mantissa = decimal.mantissa;
+        while mantissa > 0 {
+            start -= 1;
+            buffer[start] = b'0' + (mantissa % 10) as u8;
+            mantissa /= 10;
+        }
+        let digits = std::str::from_utf8(&buffer[start..]).unwrap();
+        let k = digits.len() as i32;
+        // The decimal point goes after the first n digits.
+        let n = k + decimal.exponent;
+        let max_n = if self.plain { i32::MAX } else { 21 };
+        let min_n = if self.plain { i32::MIN } else { -6 };
+        if k <= n && n <= max_n {
+            f.write_str(digits)?;
+            for _ in 0..n - k {
+                f.write_str("0")?;
+            }
+        } else if 0 < n && n <= max_n {
+            f.write_str(&digits[..n as usize])?;
+            f.write_str(".")?;
+            f.write_str(&digits[n as usize..])?;
+        } else if min_n < n && n <= 0 {
+            f.write_str("0.")?;
+            for _ in 0..-n {
+                f.write_str("0")?;
+            }
+            f.write_str(digits)?;
+        } else {
+            f.write_str(&digits[..1])?;
+            if k > 1 {
+                f.write_str(".")?;
+                f.write_str(&digits[1..])?;
+            }
+            let e = n - 1;
+            write!(f, "e{}{}", if e < 0 { '-' } else { '+' }, e.abs())?;
+        }
+        Ok(())
+    }
+}
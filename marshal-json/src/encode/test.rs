@@ -1,7 +1,11 @@
 use marshal_core::encode::AnySpecEncoder;
 use marshal_core::Primitive;
+use rand::{RngCore, SeedableRng};
+use rand_xorshift::XorShiftRng;
 
+use crate::encode::ryu::{Shortest, ShortestDecimal};
 use crate::encode::SimpleJsonSpecEncoder;
+use crate::format::NonFiniteFormat;
 
 #[track_caller]
 fn run_simple(
@@ -422,3 +426,170 @@ fn test_struct_variant() -> anyhow::Result<()> {
     )?;
     Ok(())
 }
+
+#[track_caller]
+fn run_canonical(
+    expected: &str,
+    f: impl FnOnce(AnySpecEncoder<SimpleJsonSpecEncoder>) -> anyhow::Result<()>,
+) -> anyhow::Result<()> {
+    let mut w = SimpleJsonSpecEncoder::new();
+    w.set_canonical(true);
+    f(w.start())?;
+    assert_eq!(w.end()?, expected);
+    Ok(())
+}
+
+#[test]
+fn test_canonical_numbers() -> anyhow::Result<()> {
+    for (bits, expected) in [
+        (0x0000000000000000u64, "0"),
+        (0x8000000000000000, "0"),
+        (0x0000000000000001, "5e-324"),
+        (0x8000000000000001, "-5e-324"),
+        (0x7fefffffffffffff, "1.7976931348623157e+308"),
+        (0x4340000000000000, "9007199254740992"),
+        (0x4430000000000000, "295147905179352830000"),
+        (0x44b52d02c7e14af5, "9.999999999999997e+22"),
+        (0x44b52d02c7e14af6, "1e+23"),
+        (0x3eb0c6f7a0b5ed8d, "0.000001"),
+        (0x3eb0c6f7a0b5ed8c, "9.999999999999997e-7"),
+        (0x41b3de4355555553, "333333333.3333332"),
+        (0x444b1ae4d6e2ef4e, "999999999999999700000"),
+        (0x444b1ae4d6e2ef50, "1e+21"),
+        (0x3eb0c6f7a0b5ed8e, "0.0000010000000000000002"),
+    ] {
+        run_canonical(expected, |w| w.encode_prim(Primitive::F64(f64::from_bits(bits))))?;
+    }
+    run_canonical("9007199254740991", |w| {
+        w.encode_prim(Primitive::U64((1 << 53) - 1))
+    })?;
+    run_canonical("", |w| {
+        assert!(w.encode_prim(Primitive::U64(1 << 53)).is_err());
+        Ok(())
+    })?;
+    // RFC 8785 has no representation for non-finite numbers.
+    for value in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+        for prim in [Primitive::F64(value), Primitive::F32(value as f32)] {
+            let mut w = SimpleJsonSpecEncoder::new();
+            w.set_canonical(true);
+            w.set_non_finite_format(NonFiniteFormat::String);
+            assert!(w.start().encode_prim(prim).is_err());
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_canonical_sort() -> anyhow::Result<()> {
+    run_canonical(
+        "{\"\\r\":\"Carriage Return\",\"1\":\"One\",\"\u{0080}\":\"Control\",\"\u{00f6}\":\"Latin Small Letter O With Diaeresis\",\"\u{20ac}\":\"Euro Sign\",\"\u{1f600}\":\"Emoji: Grinning Face\",\"\u{fb33}\":\"Hebrew Letter Dalet With Dagesh\"}",
+        |w| {
+            let mut m = w.encode_map(7)?;
+            for (k, v) in [
+                ("\u{20ac}", "Euro Sign"),
+                ("\r", "Carriage Return"),
+                ("\u{fb33}", "Hebrew Letter Dalet With Dagesh"),
+                ("1", "One"),
+                ("\u{1f600}", "Emoji: Grinning Face"),
+                ("\u{0080}", "Control"),
+                ("\u{00f6}", "Latin Small Letter O With Diaeresis"),
+            ] {
+                let mut e = m.encode_entry()?;
+                e.encode_key()?.encode_str(k)?;
+                e.encode_value()?.encode_str(v)?;
+                e.end()?;
+            }
+            m.end()?;
+            Ok(())
+        },
+    )
+}
+
+#[test]
+fn test_canonical_nested() -> anyhow::Result<()> {
+    run_canonical(r#"{"a":[{"x":1,"y":true},null],"b":{}}"#, |w| {
+        let mut s = w.encode_struct("Outer", &["b", "a"])?;
+        s.encode_field()?.encode_map(0)?.end()?;
+        {
+            let mut a = s.encode_field()?.encode_seq(2)?;
+            {
+                let mut inner = a.encode_element()?.encode_struct("Inner", &["y", "x"])?;
+                inner.encode_field()?.encode_prim(Primitive::Bool(true))?;
+                inner.encode_field()?.encode_prim(Primitive::U32(1))?;
+                inner.end()?;
+            }
+            a.encode_element()?.encode_prim(Primitive::Unit)?;
+            a.end()?;
+        }
+        s.end()?;
+        Ok(())
+    })
+}
+
+/// Checks a shortest representation against the standard library's, which may break exact ties
+/// differently.
+#[track_caller]
+fn assert_shortest(actual: ShortestDecimal, scientific: String) {
+    let (mantissa, exponent) = scientific.split_once('e').unwrap();
+    let digits: String = mantissa.chars().filter(|c| *c != '.').collect();
+    let expected = ShortestDecimal {
+        mantissa: digits.parse().unwrap(),
+        exponent: exponent.parse::<i32>().unwrap() + 1 - digits.len() as i32,
+    };
+    assert_eq!(actual.exponent, expected.exponent, "{}", scientific);
+    assert!(actual.mantissa.abs_diff(expected.mantissa) <= 1, "{}", scientific);
+}
+
+/// Checks a plain representation against the standard library's `Display`, allowing for exact
+/// ties in the last digit.
+#[track_caller]
+fn assert_plain(actual: String, expected: String) {
+    let (actual_last, expected_last) = (actual.as_bytes().last(), expected.as_bytes().last());
+    assert_eq!(actual[..actual.len() - 1], expected[..expected.len() - 1], "{}", expected);
+    assert!(actual_last.unwrap().abs_diff(*expected_last.unwrap()) <= 1, "{}", expected);
+}
+
+#[test]
+fn test_shortest_differential() {
+    let mut rng = XorShiftRng::seed_from_u64(0x2545f4914f6cdd1d);
+    let mut inputs = vec![1, 2, 0x000fffffffffffff, 0x0010000000000000, 0x7fefffffffffffff];
+    for _ in 0..100000 {
+        inputs.push(rng.next_u64());
+    }
+    for bits in inputs {
+        let x = f64::from_bits(bits & !(1 << 63));
+        if x.is_finite() && x != 0.0 {
+            assert_shortest(ShortestDecimal::from_f64(x), format!("{:e}", x));
+            assert_eq!(Shortest::from_f64(x).to_string().parse::<f64>().unwrap(), x);
+            assert_plain(Shortest::from_f64(x).plain().to_string(), x.to_string());
+        }
+        let x = f32::from_bits(bits as u32 & !(1 << 31));
+        if x.is_finite() && x != 0.0 {
+            assert_shortest(ShortestDecimal::from_f32(x), format!("{:e}", x));
+            assert_eq!(Shortest::from_f32(x).to_string().parse::<f32>().unwrap(), x);
+            assert_plain(Shortest::from_f32(x).plain().to_string(), x.to_string());
+        }
+    }
+}
+
+#[test]
+fn test_floats() -> anyhow::Result<()> {
+    for (x, expected) in [
+        (0.0, "0"),
+        (-0.0, "-0"),
+        (1.0, "1"),
+        (-1.5, "-1.5"),
+        (0.1, "0.1"),
+        (1e21, "1000000000000000000000"),
+        (1.25e-7, "0.000000125"),
+    ] {
+        run_simple(expected, |w| w.encode_prim(Primitive::F64(x)))?;
+    }
+    // Outside canonical mode, floats are written like `Display`, without an exponent.
+    for x in [1e300, f64::MIN_POSITIVE] {
+        run_simple(&x.to_string(), |w| w.encode_prim(Primitive::F64(x)))?;
+    }
+    run_simple("0.1", |w| w.encode_prim(Primitive::F32(0.1)))?;
+    run_simple(&f32::MAX.to_string(), |w| w.encode_prim(Primitive::F32(f32::MAX)))?;
+    Ok(())
+}
@@ -0,0 +1,50 @@
+use base64::engine::general_purpose::GeneralPurpose;
+use base64::engine::general_purpose::{STANDARD, STANDARD_NO_PAD, URL_SAFE_NO_PAD};
+
+/// How byte strings are represented.
+#[derive(Debug, Copy, Clone, Eq, Ord, PartialEq, PartialOrd, Hash, Default)]
+pub enum BytesFormat {
+    /// A base64 string using the standard alphabet with padding.
+    Base64,
+    /// A base64 string using the standard alphabet without padding.
+    #[default]
+    Base64NoPad,
+    /// A base64 string using the URL-safe alphabet without padding.
+    Base64Url,
+    /// A string of lowercase hexadecimal digits.
+    Hex,
+    /// An array of numbers.
+    Array,
+}
+
+/// How `i128` and `u128` are represented.
+#[derive(Debug, Copy, Clone, Eq, Ord, PartialEq, PartialOrd, Hash, Default)]
+pub enum BigIntFormat {
+    #[default]
+    Number,
+    /// A string of decimal digits, for readers that store numbers as doubles.
+    String,
+}
+
+/// How NaN and the infinities are represented.
+#[derive(Debug, Copy, Clone, Eq, Ord, PartialEq, PartialOrd, Hash, Default)]
+pub enum NonFiniteFormat {
+    /// Fail to encode.
+    #[default]
+    Error,
+    /// Encode as `null`, which decodes as NaN.
+    Null,
+    /// Encode as `"NaN"`, `"Infinity"` or `"-Infinity"`.
+    String,
+}
+
+impl BytesFormat {
+    pub(crate) fn base64_engine(self) -> Option<(&'static GeneralPurpose, bool)> {
+        match self {
+            BytesFormat::Base64 => Some((&STANDARD, true)),
+            BytesFormat::Base64NoPad => Some((&STANDARD_NO_PAD, false)),
+            BytesFormat::Base64Url => Some((&URL_SAFE_NO_PAD, false)),
+            BytesFormat::Hex | BytesFormat::Array => None,
+        }
+    }
+}
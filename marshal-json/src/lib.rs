@@ -8,6 +8,7 @@
 #![feature(coerce_unsized)]
 #![feature(unsize)]
 #![feature(try_blocks)]
+#![feature(portable_simd)]
 
 use marshal::de::Deserialize;
 use marshal::ser::Serialize;
@@ -17,6 +18,7 @@ use crate::encode::full::JsonEncoder;
 
 pub mod decode;
 pub mod encode;
+pub mod format;
 #[cfg(test)]
 mod test;
 pub mod value;
@@ -0,0 +1,58 @@
+use marshal::context::Context;
+use marshal::de::Deserialize;
+use marshal_core::decode::depth_budget::{DepthBudgetDecoder, WithDepthBudget};
+use marshal_core::decode::poison::PoisonDecoder;
+use marshal_core::decode::{AnySpecDecoder, Decoder};
+use marshal_core::derive_decoder_for_newtype;
+
+use crate::decode::SimpleMsgpackSpecDecoder;
+
+pub struct MsgpackSpecDecoder<'de>(
+    PoisonDecoder<DepthBudgetDecoder<SimpleMsgpackSpecDecoder<'de>>>,
+);
+
+derive_decoder_for_newtype!(MsgpackSpecDecoder<'de>(PoisonDecoder<DepthBudgetDecoder<SimpleMsgpackSpecDecoder<'de>>>));
+
+pub struct MsgpackDecoderBuilder<'de> {
+    inner: MsgpackSpecDecoder<'de>,
+    depth_budget: usize,
+}
+
+impl<'de> MsgpackDecoderBuilder<'de> {
+    pub fn new(input: &'de [u8]) -> Self {
+        MsgpackDecoderBuilder {
+            inner: MsgpackSpecDecoder(PoisonDecoder::new(DepthBudgetDecoder::new(
+                SimpleMsgpackSpecDecoder::new(input),
+            ))),
+            depth_budget: 100,
+        }
+    }
+    pub fn set_budget(&mut self, depth_budget: usize) -> &mut Self {
+        self.depth_budget = depth_budget;
+        self
+    }
+    pub fn build<'p>(&'p mut self) -> AnySpecDecoder<'p, 'de, MsgpackSpecDecoder<'de>> {
+        let any = self
+            .inner
+            .0
+            .start(WithDepthBudget::new(self.depth_budget, ()));
+        AnySpecDecoder::new(&mut self.inner, any)
+    }
+    pub fn deserialize<T: Deserialize<MsgpackDecoder>>(
+        mut self,
+        ctx: Context,
+    ) -> anyhow::Result<T> {
+        let result = T::deserialize(self.build(), ctx)?;
+        self.end()?;
+        Ok(result)
+    }
+    pub fn end(self) -> anyhow::Result<()> {
+        Ok(self.inner.0.end()?.end()?.end()?)
+    }
+}
+
+pub struct MsgpackDecoder;
+
+impl Decoder for MsgpackDecoder {
+    type SpecDecoder<'de> = MsgpackSpecDecoder<'de>;
+}
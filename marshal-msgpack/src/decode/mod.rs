@@ -0,0 +1,452 @@
+use std::borrow::Cow;
+use std::fmt::{Display, Formatter};
+
+use marshal_core::decode::{
+    DecodeHint, DecodeVariantHint, SimpleDecoderView, SpecDecoder, WideInt,
+};
+use marshal_core::{Primitive, PrimitiveType};
+
+use crate::{
+    ARRAY16, ARRAY32, BIN16, BIN32, BIN8, EXT16, EXT32, EXT8, FALSE, FIXARRAY, FIXEXT1, FIXEXT16,
+    FIXEXT2, FIXEXT4, FIXEXT8, FIXMAP, FIXSTR, FLOAT32, FLOAT64, I128_EXT_TYPE, INT16, INT32,
+    INT64, INT8, MAP16, MAP32, NEGATIVE_FIXINT_MIN, NIL, POSITIVE_FIXINT_MAX, RESERVED,
+    SOME_EXT_TYPE, STR16, STR32, STR8, TRUE, U128_EXT_TYPE, UINT16, UINT32, UINT64, UINT8,
+};
+
+pub mod full;
+
+#[derive(Debug)]
+pub enum MsgpackDecoderError {
+    Eof,
+    TrailingData,
+    ReservedMarker,
+    BadEnum,
+    UnknownExt(i8),
+    BadExtLength,
+    BadSomeLength,
+}
+
+impl Display for MsgpackDecoderError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MsgpackDecoderError::Eof => write!(f, "unexpected end of input"),
+            MsgpackDecoderError::TrailingData => write!(f, "input contains trailing bytes"),
+            MsgpackDecoderError::ReservedMarker => write!(f, "reserved marker byte 0xc1"),
+            MsgpackDecoderError::BadEnum => {
+                write!(f, "enum must be a string or a map with one entry")
+            }
+            MsgpackDecoderError::UnknownExt(x) => write!(f, "unknown extension type {}", x),
+            MsgpackDecoderError::BadExtLength => {
+                write!(f, "extension has the wrong length for its type")
+            }
+            MsgpackDecoderError::BadSomeLength => {
+                write!(f, "contents of a some extension do not match its length")
+            }
+        }
+    }
+}
+
+impl std::error::Error for MsgpackDecoderError {}
+
+pub struct SimpleMsgpackSpecDecoder<'de> {
+    content: &'de [u8],
+}
+
+#[derive(Copy, Clone)]
+enum MsgpackInt {
+    Unsigned(u128),
+    Signed(i128),
+}
+
+/// One item, with the payload of an extension left unread.
+enum MsgpackItem<'de> {
+    Nil,
+    Bool(bool),
+    Int(MsgpackInt),
+    Float(Primitive),
+    Str(&'de [u8]),
+    Bin(&'de [u8]),
+    Array(usize),
+    Map(usize),
+    Ext(i8, usize),
+}
+
+pub struct MsgpackSeqDecoder {
+    remaining: usize,
+}
+
+pub enum MsgpackDiscriminantDecoder {
+    Unit,
+    Map,
+}
+
+impl<'de> SimpleMsgpackSpecDecoder<'de> {
+    pub fn new(input: &'de [u8]) -> Self {
+        SimpleMsgpackSpecDecoder { content: input }
+    }
+    pub fn end(self) -> anyhow::Result<()> {
+        if !self.content.is_empty() {
+            return Err(MsgpackDecoderError::TrailingData.into());
+        }
+        Ok(())
+    }
+    fn read_count(&mut self, count: usize) -> anyhow::Result<&'de [u8]> {
+        Ok(self.content.take(..count).ok_or(MsgpackDecoderError::Eof)?)
+    }
+    fn read_array<const N: usize>(&mut self) -> anyhow::Result<[u8; N]> {
+        Ok(self.read_count(N)?.try_into().unwrap())
+    }
+    fn read_u8(&mut self) -> anyhow::Result<usize> {
+        Ok(self.read_array::<1>()?[0] as usize)
+    }
+    fn read_u16(&mut self) -> anyhow::Result<usize> {
+        Ok(u16::from_be_bytes(self.read_array()?) as usize)
+    }
+    fn read_u32(&mut self) -> anyhow::Result<usize> {
+        Ok(usize::try_from(u32::from_be_bytes(self.read_array()?))?)
+    }
+    fn read_ext_type(&mut self, len: usize) -> anyhow::Result<MsgpackItem<'de>> {
+        Ok(MsgpackItem::Ext(self.read_array::<1>()?[0] as i8, len))
+    }
+    fn read_item(&mut self) -> anyhow::Result<MsgpackItem<'de>> {
+        let marker = self.read_array::<1>()?[0];
+        Ok(match marker {
+            0..=POSITIVE_FIXINT_MAX => MsgpackItem::Int(MsgpackInt::Unsigned(marker as u128)),
+            FIXMAP..FIXARRAY => MsgpackItem::Map((marker & 0x0f) as usize),
+            FIXARRAY..FIXSTR => MsgpackItem::Array((marker & 0x0f) as usize),
+            FIXSTR..NIL => MsgpackItem::Str(self.read_count((marker & 0x1f) as usize)?),
+            NIL => MsgpackItem::Nil,
+            RESERVED => return Err(MsgpackDecoderError::ReservedMarker.into()),
+            FALSE | TRUE => MsgpackItem::Bool(marker == TRUE),
+            BIN8 => MsgpackItem::Bin(self.read_u8().and_then(|len| self.read_count(len))?),
+            BIN16 => MsgpackItem::Bin(self.read_u16().and_then(|len| self.read_count(len))?),
+            BIN32 => MsgpackItem::Bin(self.read_u32().and_then(|len| self.read_count(len))?),
+            EXT8 => self.read_u8().and_then(|len| self.read_ext_type(len))?,
+            EXT16 => self.read_u16().and_then(|len| self.read_ext_type(len))?,
+            EXT32 => self.read_u32().and_then(|len| self.read_ext_type(len))?,
+            FLOAT32 => MsgpackItem::Float(Primitive::F32(f32::from_be_bytes(self.read_array()?))),
+            FLOAT64 => MsgpackItem::Float(Primitive::F64(f64::from_be_bytes(self.read_array()?))),
+            UINT8 => MsgpackItem::Int(MsgpackInt::Unsigned(self.read_array::<1>()?[0] as u128)),
+            UINT16 => MsgpackItem::Int(MsgpackInt::Unsigned(
+                u16::from_be_bytes(self.read_array()?) as u128,
+            )),
+            UINT32 => MsgpackItem::Int(MsgpackInt::Unsigned(
+                u32::from_be_bytes(self.read_array()?) as u128,
+            )),
+            UINT64 => MsgpackItem::Int(MsgpackInt::Unsigned(
+                u64::from_be_bytes(self.read_array()?) as u128,
+            )),
+            INT8 => MsgpackItem::Int(MsgpackInt::Signed(
+                i8::from_be_bytes(self.read_array()?) as i128
+            )),
+            INT16 => MsgpackItem::Int(MsgpackInt::Signed(
+                i16::from_be_bytes(self.read_array()?) as i128
+            )),
+            INT32 => MsgpackItem::Int(MsgpackInt::Signed(
+                i32::from_be_bytes(self.read_array()?) as i128
+            )),
+            INT64 => MsgpackItem::Int(MsgpackInt::Signed(
+                i64::from_be_bytes(self.read_array()?) as i128
+            )),
+            FIXEXT1 => self.read_ext_type(1)?,
+            FIXEXT2 => self.read_ext_type(2)?,
+            FIXEXT4 => self.read_ext_type(4)?,
+            FIXEXT8 => self.read_ext_type(8)?,
+            FIXEXT16 => self.read_ext_type(16)?,
+            STR8 => MsgpackItem::Str(self.read_u8().and_then(|len| self.read_count(len))?),
+            STR16 => MsgpackItem::Str(self.read_u16().and_then(|len| self.read_count(len))?),
+            STR32 => MsgpackItem::Str(self.read_u32().and_then(|len| self.read_count(len))?),
+            ARRAY16 => MsgpackItem::Array(self.read_u16()?),
+            ARRAY32 => MsgpackItem::Array(self.read_u32()?),
+            MAP16 => MsgpackItem::Map(self.read_u16()?),
+            MAP32 => MsgpackItem::Map(self.read_u32()?),
+            NEGATIVE_FIXINT_MIN..=u8::MAX => {
+                MsgpackItem::Int(MsgpackInt::Signed(marker as i8 as i128))
+            }
+        })
+    }
+    fn read_int128(&mut self, len: usize) -> anyhow::Result<[u8; 16]> {
+        if len != 16 {
+            return Err(MsgpackDecoderError::BadExtLength.into());
+        }
+        self.read_array()
+    }
+    /// Skips one item, including the contents of arrays and maps.
+    fn skip_item(&mut self) -> anyhow::Result<()> {
+        let mut count = 1usize;
+        while let Some(next) = count.checked_sub(1) {
+            count = next;
+            match self.read_item()? {
+                MsgpackItem::Array(len) => count = count.saturating_add(len),
+                MsgpackItem::Map(len) => count = count.saturating_add(len.saturating_mul(2)),
+                MsgpackItem::Ext(_, len) => {
+                    self.read_count(len)?;
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+    /// Moves to the item inside a `Some` extension with a payload of `len` bytes, passing over any
+    /// nested `Some` extensions, and checks that the payload holds exactly that item.
+    fn enter_some_contents(&mut self, len: usize) -> anyhow::Result<()> {
+        let end = self.ext_end(len)?;
+        loop {
+            let mut next = SimpleMsgpackSpecDecoder {
+                content: self.content,
+            };
+            match next.read_item()? {
+                MsgpackItem::Ext(SOME_EXT_TYPE, len) if next.ext_end(len)? == end => {
+                    self.content = next.content;
+                }
+                _ => break,
+            }
+        }
+        let mut item = SimpleMsgpackSpecDecoder {
+            content: self.content,
+        };
+        item.skip_item()?;
+        if item.content.len() != end {
+            return Err(MsgpackDecoderError::BadSomeLength.into());
+        }
+        Ok(())
+    }
+    /// The length of the input that follows an extension payload of `len` bytes.
+    fn ext_end(&self, len: usize) -> anyhow::Result<usize> {
+        Ok(self
+            .content
+            .len()
+            .checked_sub(len)
+            .ok_or(MsgpackDecoderError::Eof)?)
+    }
+}
+
+impl WideInt for MsgpackInt {
+    fn natural_type(self) -> PrimitiveType {
+        match self {
+            MsgpackInt::Unsigned(x) if x <= u64::MAX as u128 => PrimitiveType::U64,
+            MsgpackInt::Unsigned(_) => PrimitiveType::U128,
+            MsgpackInt::Signed(x) if x >= i64::MIN as i128 => PrimitiveType::I64,
+            MsgpackInt::Signed(_) => PrimitiveType::I128,
+        }
+    }
+    fn unsigned(self) -> anyhow::Result<u128> {
+        match self {
+            MsgpackInt::Unsigned(x) => Ok(x),
+            MsgpackInt::Signed(x) => Ok(u128::try_from(x)?),
+        }
+    }
+    fn signed(self) -> anyhow::Result<i128> {
+        match self {
+            MsgpackInt::Unsigned(x) => Ok(i128::try_from(x)?),
+            MsgpackInt::Signed(x) => Ok(x),
+        }
+    }
+    fn float(self) -> f64 {
+        match self {
+            MsgpackInt::Unsigned(x) => x as f64,
+            MsgpackInt::Signed(x) => x as f64,
+        }
+    }
+}
+
+impl<'de> SpecDecoder<'de> for SimpleMsgpackSpecDecoder<'de> {
+    type AnyDecoder = ();
+    type SeqDecoder = MsgpackSeqDecoder;
+    type MapDecoder = MsgpackSeqDecoder;
+    type KeyDecoder = ();
+    type ValueDecoder = ();
+    type DiscriminantDecoder = MsgpackDiscriminantDecoder;
+    type VariantDecoder = MsgpackDiscriminantDecoder;
+    type EnumCloser = ();
+    /// The length of the input expected after the contents of a `Some` extension.
+    type SomeDecoder = Option<usize>;
+    type SomeCloser = Option<usize>;
+
+    fn decode(
+        &mut self,
+        _any: Self::AnyDecoder,
+        hint: DecodeHint,
+    ) -> anyhow::Result<SimpleDecoderView<'de, Self>> {
+        loop {
+            let mut next = SimpleMsgpackSpecDecoder {
+                content: self.content,
+            };
+            let item = next.read_item()?;
+            match (&hint, &item) {
+                (DecodeHint::Option, MsgpackItem::Nil) => {
+                    self.content = next.content;
+                    return Ok(SimpleDecoderView::None);
+                }
+                (DecodeHint::Option, MsgpackItem::Ext(SOME_EXT_TYPE, len)) => {
+                    let end = next.ext_end(*len)?;
+                    self.content = next.content;
+                    return Ok(SimpleDecoderView::Some(Some(end)));
+                }
+                (DecodeHint::Option, _) => return Ok(SimpleDecoderView::Some(None)),
+                (DecodeHint::Enum { .. }, MsgpackItem::Int(_) | MsgpackItem::Str(_)) => {
+                    return Ok(SimpleDecoderView::Enum(MsgpackDiscriminantDecoder::Unit));
+                }
+                (DecodeHint::Enum { .. }, MsgpackItem::Map(len)) => {
+                    if *len != 1 {
+                        return Err(MsgpackDecoderError::BadEnum.into());
+                    }
+                    self.content = next.content;
+                    return Ok(SimpleDecoderView::Enum(MsgpackDiscriminantDecoder::Map));
+                }
+                _ => {}
+            }
+            self.content = next.content;
+            return Ok(match item {
+                MsgpackItem::Nil => SimpleDecoderView::Primitive(Primitive::Unit),
+                MsgpackItem::Bool(x) => SimpleDecoderView::Primitive(Primitive::Bool(x)),
+                MsgpackItem::Int(x) => SimpleDecoderView::Primitive(x.into_primitive(&hint)?),
+                MsgpackItem::Float(x) => SimpleDecoderView::Primitive(match (hint, x) {
+                    (DecodeHint::Primitive(PrimitiveType::F64), Primitive::F32(x)) => {
+                        Primitive::F64(x as f64)
+                    }
+                    (DecodeHint::Primitive(PrimitiveType::F32), Primitive::F64(x)) => {
+                        Primitive::F32(x as f32)
+                    }
+                    (_, x) => x,
+                }),
+                MsgpackItem::Str(x) => {
+                    let text = std::str::from_utf8(x)?;
+                    if let DecodeHint::Primitive(PrimitiveType::Char) = hint {
+                        let mut chars = text.chars();
+                        if let (Some(c), None) = (chars.next(), chars.next()) {
+                            return Ok(SimpleDecoderView::Primitive(Primitive::Char(c)));
+                        }
+                    }
+                    SimpleDecoderView::String(Cow::Borrowed(text))
+                }
+                MsgpackItem::Bin(x) => SimpleDecoderView::Bytes(Cow::Borrowed(x)),
+                MsgpackItem::Array(len) => {
+                    SimpleDecoderView::Seq(MsgpackSeqDecoder { remaining: len })
+                }
+                MsgpackItem::Map(len) => {
+                    SimpleDecoderView::Map(MsgpackSeqDecoder { remaining: len })
+                }
+                MsgpackItem::Ext(U128_EXT_TYPE, len) => {
+                    let value = u128::from_be_bytes(self.read_int128(len)?);
+                    SimpleDecoderView::Primitive(MsgpackInt::Unsigned(value).into_primitive(&hint)?)
+                }
+                MsgpackItem::Ext(I128_EXT_TYPE, len) => {
+                    let value = i128::from_be_bytes(self.read_int128(len)?);
+                    SimpleDecoderView::Primitive(MsgpackInt::Signed(value).into_primitive(&hint)?)
+                }
+                MsgpackItem::Ext(SOME_EXT_TYPE, len) => match hint {
+                    DecodeHint::Any | DecodeHint::Ignore => {
+                        SimpleDecoderView::Some(Some(self.ext_end(len)?))
+                    }
+                    // A reader that does not expect an option reads the contents directly.
+                    _ => {
+                        self.enter_some_contents(len)?;
+                        continue;
+                    }
+                },
+                MsgpackItem::Ext(ext_type, len) => match hint {
+                    DecodeHint::Ignore => {
+                        self.read_count(len)?;
+                        SimpleDecoderView::Primitive(Primitive::Unit)
+                    }
+                    _ => return Err(MsgpackDecoderError::UnknownExt(ext_type).into()),
+                },
+            });
+        }
+    }
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+
+    fn decode_seq_next(
+        &mut self,
+        seq: &mut Self::SeqDecoder,
+    ) -> anyhow::Result<Option<Self::AnyDecoder>> {
+        if let Some(next) = seq.remaining.checked_sub(1) {
+            seq.remaining = next;
+            Ok(Some(()))
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn decode_seq_exact_size(&self, seq: &Self::SeqDecoder) -> Option<usize> {
+        // The length is untrusted, so it is only reported if the input could hold that many items.
+        Some(seq.remaining).filter(|x| *x <= self.content.len())
+    }
+
+    fn decode_seq_end(&mut self, _seq: Self::SeqDecoder) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn decode_map_next(
+        &mut self,
+        map: &mut Self::MapDecoder,
+    ) -> anyhow::Result<Option<Self::KeyDecoder>> {
+        self.decode_seq_next(map)
+    }
+
+    fn decode_map_exact_size(&self, map: &Self::MapDecoder) -> Option<usize> {
+        Some(map.remaining).filter(|x| *x <= self.content.len() / 2)
+    }
+
+    fn decode_map_end(&mut self, _map: Self::MapDecoder) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn decode_entry_key(
+        &mut self,
+        _key: Self::KeyDecoder,
+    ) -> anyhow::Result<(Self::AnyDecoder, Self::ValueDecoder)> {
+        Ok(((), ()))
+    }
+
+    fn decode_entry_value(
+        &mut self,
+        _value: Self::ValueDecoder,
+    ) -> anyhow::Result<Self::AnyDecoder> {
+        Ok(())
+    }
+
+    fn decode_enum_discriminant(
+        &mut self,
+        e: Self::DiscriminantDecoder,
+    ) -> anyhow::Result<(Self::AnyDecoder, Self::VariantDecoder)> {
+        Ok(((), e))
+    }
+
+    fn decode_enum_variant(
+        &mut self,
+        e: Self::VariantDecoder,
+        hint: DecodeVariantHint,
+    ) -> anyhow::Result<(SimpleDecoderView<'de, Self>, Self::EnumCloser)> {
+        match e {
+            MsgpackDiscriminantDecoder::Unit => {
+                Ok((SimpleDecoderView::Primitive(Primitive::Unit), ()))
+            }
+            MsgpackDiscriminantDecoder::Map => Ok((self.decode((), hint.content_hint())?, ())),
+        }
+    }
+
+    fn decode_enum_end(&mut self, _e: Self::EnumCloser) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn decode_some_inner(
+        &mut self,
+        e: Self::SomeDecoder,
+    ) -> anyhow::Result<(Self::AnyDecoder, Self::SomeCloser)> {
+        Ok(((), e))
+    }
+
+    fn decode_some_end(&mut self, p: Self::SomeCloser) -> anyhow::Result<()> {
+        if let Some(end) = p {
+            if self.content.len() != end {
+                return Err(MsgpackDecoderError::BadSomeLength.into());
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,53 @@
+use marshal::context::Context;
+use marshal::ser::Serialize;
+use marshal_core::derive_encoder_for_newtype;
+use marshal_core::encode::poison::PoisonEncoder;
+use marshal_core::encode::{AnySpecEncoder, Encoder};
+
+use crate::encode::{MsgpackAnySpecEncoder, SimpleMsgpackSpecEncoder};
+
+pub struct MsgpackSpecEncoder(PoisonEncoder<SimpleMsgpackSpecEncoder>);
+
+derive_encoder_for_newtype!(MsgpackSpecEncoder(PoisonEncoder<SimpleMsgpackSpecEncoder>));
+
+pub struct MsgpackEncoderBuilder {
+    inner: MsgpackSpecEncoder,
+}
+
+impl MsgpackEncoderBuilder {
+    pub fn new() -> Self {
+        MsgpackEncoderBuilder {
+            inner: MsgpackSpecEncoder(PoisonEncoder::new(SimpleMsgpackSpecEncoder::new())),
+        }
+    }
+    /// Encodes structs as arrays of field values, which is smaller but relies on the reader
+    /// declaring the fields in the same order.
+    pub fn set_struct_as_array(&mut self, struct_as_array: bool) -> &mut Self {
+        self.inner
+            .0
+            .inner_mut()
+            .set_struct_as_array(struct_as_array);
+        self
+    }
+    pub fn build(&mut self) -> AnySpecEncoder<'_, MsgpackSpecEncoder> {
+        let any = self.inner.0.start(MsgpackAnySpecEncoder::new());
+        AnySpecEncoder::new(&mut self.inner, any)
+    }
+    pub fn end(self) -> anyhow::Result<Vec<u8>> {
+        self.inner.0.end()?.end()
+    }
+    pub fn serialize<T: ?Sized + Serialize<MsgpackEncoder>>(
+        mut self,
+        value: &T,
+        ctx: Context,
+    ) -> anyhow::Result<Vec<u8>> {
+        value.serialize(self.build(), ctx)?;
+        self.end()
+    }
+}
+
+pub struct MsgpackEncoder;
+
+impl Encoder for MsgpackEncoder {
+    type SpecEncoder<'en> = MsgpackSpecEncoder;
+}
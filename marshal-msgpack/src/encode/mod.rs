@@ -0,0 +1,516 @@
+use std::fmt::{Display, Formatter};
+
+use marshal_core::encode::SpecEncoder;
+use marshal_core::Primitive;
+
+use crate::{
+    ARRAY16, ARRAY32, BIN16, BIN32, BIN8, EXT16, EXT32, EXT8, FALSE, FIXARRAY, FIXEXT1, FIXEXT16,
+    FIXEXT2, FIXEXT4, FIXEXT8, FIXMAP, FIXSTR, FLOAT32, FLOAT64, I128_EXT_TYPE, INT16, INT32,
+    INT64, INT8, MAP16, MAP32, NIL, POSITIVE_FIXINT_MAX, SOME_EXT_TYPE, STR16, STR32, STR8, TRUE,
+    U128_EXT_TYPE, UINT16, UINT32, UINT64, UINT8,
+};
+
+pub mod full;
+
+#[derive(Debug)]
+pub enum MsgpackEncoderError {
+    WrongLength,
+    TooLong,
+}
+
+impl Display for MsgpackEncoderError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MsgpackEncoderError::WrongLength => {
+                write!(f, "number of elements does not match the declared length")
+            }
+            MsgpackEncoderError::TooLong => write!(f, "length does not fit in 32 bits"),
+        }
+    }
+}
+
+impl std::error::Error for MsgpackEncoderError {}
+
+/// The reserved space for the header of a `Some` extension, which is large enough for `ext 32`.
+const SOME_HEADER_RESERVE: usize = 6;
+
+pub struct SimpleMsgpackSpecEncoder {
+    output: Vec<u8>,
+    struct_as_array: bool,
+}
+
+/// Writes the smallest header for a string-like item of `len` bytes or elements. The fixed
+/// form is used below `fix_limit`, otherwise the first marker of `markers` that fits.
+fn write_len(
+    output: &mut Vec<u8>,
+    len: usize,
+    fix: u8,
+    fix_limit: usize,
+    markers: [Option<u8>; 3],
+) -> anyhow::Result<()> {
+    match markers {
+        _ if len < fix_limit => output.push(fix | len as u8),
+        [Some(marker), _, _] if len <= u8::MAX as usize => {
+            output.push(marker);
+            output.push(len as u8);
+        }
+        [_, Some(marker), _] if len <= u16::MAX as usize => {
+            output.push(marker);
+            output.extend_from_slice(&(len as u16).to_be_bytes());
+        }
+        [_, _, Some(marker)] => {
+            let len = u32::try_from(len).map_err(|_| MsgpackEncoderError::TooLong)?;
+            output.push(marker);
+            output.extend_from_slice(&len.to_be_bytes());
+        }
+        _ => unreachable!(),
+    }
+    Ok(())
+}
+
+fn write_ext_header(output: &mut Vec<u8>, ext_type: i8, len: usize) -> anyhow::Result<()> {
+    match len {
+        1 => output.push(FIXEXT1),
+        2 => output.push(FIXEXT2),
+        4 => output.push(FIXEXT4),
+        8 => output.push(FIXEXT8),
+        16 => output.push(FIXEXT16),
+        _ => write_len(output, len, 0, 0, [Some(EXT8), Some(EXT16), Some(EXT32)])?,
+    }
+    output.push(ext_type as u8);
+    Ok(())
+}
+
+impl SimpleMsgpackSpecEncoder {
+    pub fn new() -> Self {
+        SimpleMsgpackSpecEncoder {
+            output: vec![],
+            struct_as_array: false,
+        }
+    }
+    /// Encodes structs as arrays of field values instead of maps from field names.
+    pub fn set_struct_as_array(&mut self, struct_as_array: bool) {
+        self.struct_as_array = struct_as_array;
+    }
+    pub fn end(self) -> anyhow::Result<Vec<u8>> {
+        Ok(self.output)
+    }
+    /// Writes `nil`, wrapped if it would otherwise be mistaken for `None`.
+    fn write_nil(&mut self, any: MsgpackAnySpecEncoder) -> anyhow::Result<()> {
+        if any.cannot_be_null {
+            write_ext_header(&mut self.output, SOME_EXT_TYPE, 1)?;
+        }
+        self.output.push(NIL);
+        Ok(())
+    }
+    fn write_int128_ext(&mut self, ext_type: i8, bytes: [u8; 16]) -> anyhow::Result<()> {
+        write_ext_header(&mut self.output, ext_type, bytes.len())?;
+        self.output.extend_from_slice(&bytes);
+        Ok(())
+    }
+    fn write_unsigned(&mut self, value: u128) -> anyhow::Result<()> {
+        if value <= POSITIVE_FIXINT_MAX as u128 {
+            self.output.push(value as u8);
+        } else if let Ok(value) = u8::try_from(value) {
+            self.output.push(UINT8);
+            self.output.push(value);
+        } else if let Ok(value) = u16::try_from(value) {
+            self.output.push(UINT16);
+            self.output.extend_from_slice(&value.to_be_bytes());
+        } else if let Ok(value) = u32::try_from(value) {
+            self.output.push(UINT32);
+            self.output.extend_from_slice(&value.to_be_bytes());
+        } else if let Ok(value) = u64::try_from(value) {
+            self.output.push(UINT64);
+            self.output.extend_from_slice(&value.to_be_bytes());
+        } else {
+            self.write_int128_ext(U128_EXT_TYPE, value.to_be_bytes())?;
+        }
+        Ok(())
+    }
+    fn write_signed(&mut self, value: i128) -> anyhow::Result<()> {
+        if value >= 0 {
+            return self.write_unsigned(value as u128);
+        }
+        if value >= -32 {
+            self.output.push(value as i8 as u8);
+        } else if let Ok(value) = i8::try_from(value) {
+            self.output.push(INT8);
+            self.output.push(value as u8);
+        } else if let Ok(value) = i16::try_from(value) {
+            self.output.push(INT16);
+            self.output.extend_from_slice(&value.to_be_bytes());
+        } else if let Ok(value) = i32::try_from(value) {
+            self.output.push(INT32);
+            self.output.extend_from_slice(&value.to_be_bytes());
+        } else if let Ok(value) = i64::try_from(value) {
+            self.output.push(INT64);
+            self.output.extend_from_slice(&value.to_be_bytes());
+        } else {
+            self.write_int128_ext(I128_EXT_TYPE, value.to_be_bytes())?;
+        }
+        Ok(())
+    }
+    fn write_str(&mut self, value: &str) -> anyhow::Result<()> {
+        write_len(
+            &mut self.output,
+            value.len(),
+            FIXSTR,
+            32,
+            [Some(STR8), Some(STR16), Some(STR32)],
+        )?;
+        self.output.extend_from_slice(value.as_bytes());
+        Ok(())
+    }
+    fn start_array(&mut self, len: usize) -> anyhow::Result<MsgpackLenEncoder> {
+        write_len(
+            &mut self.output,
+            len,
+            FIXARRAY,
+            16,
+            [None, Some(ARRAY16), Some(ARRAY32)],
+        )?;
+        Ok(MsgpackLenEncoder { remaining: len })
+    }
+    fn start_map(&mut self, len: usize) -> anyhow::Result<MsgpackLenEncoder> {
+        write_len(
+            &mut self.output,
+            len,
+            FIXMAP,
+            16,
+            [None, Some(MAP16), Some(MAP32)],
+        )?;
+        Ok(MsgpackLenEncoder { remaining: len })
+    }
+    fn start_struct(
+        &mut self,
+        fields: &'static [&'static str],
+    ) -> anyhow::Result<MsgpackLenEncoder> {
+        if self.struct_as_array {
+            self.start_array(fields.len())
+        } else {
+            self.start_map(fields.len())
+        }
+    }
+    /// Writes the one-entry map that wraps a variant with fields.
+    fn start_variant(
+        &mut self,
+        variants: &'static [&'static str],
+        variant_index: usize,
+    ) -> anyhow::Result<()> {
+        self.start_map(1)?;
+        self.write_str(variants[variant_index])
+    }
+    fn element(
+        &mut self,
+        container: &mut MsgpackLenEncoder,
+    ) -> anyhow::Result<MsgpackAnySpecEncoder> {
+        container.remaining = container
+            .remaining
+            .checked_sub(1)
+            .ok_or(MsgpackEncoderError::WrongLength)?;
+        Ok(MsgpackAnySpecEncoder::new())
+    }
+    fn end_container(&mut self, container: MsgpackLenEncoder) -> anyhow::Result<()> {
+        if container.remaining != 0 {
+            return Err(MsgpackEncoderError::WrongLength.into());
+        }
+        Ok(())
+    }
+    fn struct_field(
+        &mut self,
+        s: &mut MsgpackLenEncoder,
+        key: &'static str,
+    ) -> anyhow::Result<MsgpackAnySpecEncoder> {
+        let any = self.element(s)?;
+        if !self.struct_as_array {
+            self.write_str(key)?;
+        }
+        Ok(any)
+    }
+}
+
+impl SpecEncoder for SimpleMsgpackSpecEncoder {
+    type AnySpecEncoder = MsgpackAnySpecEncoder;
+    type SomeCloser = Option<usize>;
+    type TupleEncoder = MsgpackLenEncoder;
+    type SeqEncoder = MsgpackLenEncoder;
+    type MapEncoder = MsgpackLenEncoder;
+    type ValueEncoder = ();
+    type EntryCloser = ();
+    type TupleStructEncoder = MsgpackLenEncoder;
+    type StructEncoder = MsgpackLenEncoder;
+    type TupleVariantEncoder = MsgpackLenEncoder;
+    type StructVariantEncoder = MsgpackLenEncoder;
+
+    fn encode_prim(&mut self, any: Self::AnySpecEncoder, prim: Primitive) -> anyhow::Result<()> {
+        match prim {
+            Primitive::Unit => self.write_nil(any)?,
+            Primitive::Bool(x) => self.output.push(if x { TRUE } else { FALSE }),
+            Primitive::I8(x) => self.write_signed(x as i128)?,
+            Primitive::I16(x) => self.write_signed(x as i128)?,
+            Primitive::I32(x) => self.write_signed(x as i128)?,
+            Primitive::I64(x) => self.write_signed(x as i128)?,
+            Primitive::I128(x) => self.write_signed(x)?,
+            Primitive::U8(x) => self.write_unsigned(x as u128)?,
+            Primitive::U16(x) => self.write_unsigned(x as u128)?,
+            Primitive::U32(x) => self.write_unsigned(x as u128)?,
+            Primitive::U64(x) => self.write_unsigned(x as u128)?,
+            Primitive::U128(x) => self.write_unsigned(x)?,
+            Primitive::F32(x) => {
+                self.output.push(FLOAT32);
+                self.output.extend_from_slice(&x.to_be_bytes());
+            }
+            Primitive::F64(x) => {
+                self.output.push(FLOAT64);
+                self.output.extend_from_slice(&x.to_be_bytes());
+            }
+            Primitive::Char(x) => self.write_str(x.encode_utf8(&mut [0u8; 4]))?,
+        }
+        Ok(())
+    }
+
+    fn encode_str(&mut self, _any: Self::AnySpecEncoder, s: &str) -> anyhow::Result<()> {
+        self.write_str(s)
+    }
+
+    fn encode_bytes(&mut self, _any: Self::AnySpecEncoder, s: &[u8]) -> anyhow::Result<()> {
+        write_len(
+            &mut self.output,
+            s.len(),
+            0,
+            0,
+            [Some(BIN8), Some(BIN16), Some(BIN32)],
+        )?;
+        self.output.extend_from_slice(s);
+        Ok(())
+    }
+
+    fn encode_none(&mut self, any: Self::AnySpecEncoder) -> anyhow::Result<()> {
+        self.write_nil(any)
+    }
+
+    fn encode_some(
+        &mut self,
+        any: Self::AnySpecEncoder,
+    ) -> anyhow::Result<(Self::AnySpecEncoder, Self::SomeCloser)> {
+        let start = if any.cannot_be_null {
+            // The header is rewritten once the length of the contents is known.
+            let start = self.output.len();
+            self.output.resize(start + SOME_HEADER_RESERVE, 0);
+            Some(start)
+        } else {
+            None
+        };
+        Ok((
+            MsgpackAnySpecEncoder {
+                cannot_be_null: true,
+            },
+            start,
+        ))
+    }
+
+    fn encode_unit_struct(
+        &mut self,
+        any: Self::AnySpecEncoder,
+        _name: &'static str,
+    ) -> anyhow::Result<()> {
+        self.write_nil(any)
+    }
+
+    fn encode_tuple_struct(
+        &mut self,
+        _any: Self::AnySpecEncoder,
+        _name: &'static str,
+        len: usize,
+    ) -> anyhow::Result<Self::TupleStructEncoder> {
+        self.start_array(len)
+    }
+
+    fn encode_struct(
+        &mut self,
+        _any: Self::AnySpecEncoder,
+        _name: &'static str,
+        fields: &'static [&'static str],
+    ) -> anyhow::Result<Self::StructEncoder> {
+        self.start_struct(fields)
+    }
+
+    fn encode_unit_variant(
+        &mut self,
+        _any: Self::AnySpecEncoder,
+        _name: &'static str,
+        variants: &'static [&'static str],
+        variant_index: usize,
+    ) -> anyhow::Result<()> {
+        self.write_str(variants[variant_index])
+    }
+
+    fn encode_tuple_variant(
+        &mut self,
+        _any: Self::AnySpecEncoder,
+        _name: &'static str,
+        variants: &'static [&'static str],
+        variant_index: usize,
+        len: usize,
+    ) -> anyhow::Result<Self::TupleVariantEncoder> {
+        self.start_variant(variants, variant_index)?;
+        self.start_array(len)
+    }
+
+    fn encode_struct_variant(
+        &mut self,
+        _any: Self::AnySpecEncoder,
+        _name: &'static str,
+        variants: &'static [&'static str],
+        variant_index: usize,
+        fields: &'static [&'static str],
+    ) -> anyhow::Result<Self::StructVariantEncoder> {
+        self.start_variant(variants, variant_index)?;
+        self.start_struct(fields)
+    }
+
+    fn encode_seq(
+        &mut self,
+        _any: Self::AnySpecEncoder,
+        len: usize,
+    ) -> anyhow::Result<Self::SeqEncoder> {
+        self.start_array(len)
+    }
+
+    fn encode_tuple(
+        &mut self,
+        _any: Self::AnySpecEncoder,
+        len: usize,
+    ) -> anyhow::Result<Self::TupleEncoder> {
+        self.start_array(len)
+    }
+
+    fn encode_map(
+        &mut self,
+        _any: Self::AnySpecEncoder,
+        len: usize,
+    ) -> anyhow::Result<Self::MapEncoder> {
+        self.start_map(len)
+    }
+
+    fn some_end(&mut self, some: Self::SomeCloser) -> anyhow::Result<()> {
+        if let Some(start) = some {
+            let contents = start + SOME_HEADER_RESERVE;
+            let mut header = vec![];
+            write_ext_header(&mut header, SOME_EXT_TYPE, self.output.len() - contents)?;
+            self.output.splice(start..contents, header);
+        }
+        Ok(())
+    }
+
+    fn tuple_encode_element(
+        &mut self,
+        tuple: &mut Self::TupleEncoder,
+    ) -> anyhow::Result<Self::AnySpecEncoder> {
+        self.element(tuple)
+    }
+
+    fn tuple_end(&mut self, tuple: Self::TupleEncoder) -> anyhow::Result<()> {
+        self.end_container(tuple)
+    }
+
+    fn seq_encode_element(
+        &mut self,
+        seq: &mut Self::SeqEncoder,
+    ) -> anyhow::Result<Self::AnySpecEncoder> {
+        self.element(seq)
+    }
+
+    fn seq_end(&mut self, seq: Self::SeqEncoder) -> anyhow::Result<()> {
+        self.end_container(seq)
+    }
+
+    fn map_encode_element(
+        &mut self,
+        map: &mut Self::MapEncoder,
+    ) -> anyhow::Result<(Self::AnySpecEncoder, Self::ValueEncoder)> {
+        Ok((self.element(map)?, ()))
+    }
+
+    fn map_end(&mut self, map: Self::MapEncoder) -> anyhow::Result<()> {
+        self.end_container(map)
+    }
+
+    fn entry_encode_value(
+        &mut self,
+        _value: Self::ValueEncoder,
+    ) -> anyhow::Result<(Self::AnySpecEncoder, Self::EntryCloser)> {
+        Ok((MsgpackAnySpecEncoder::new(), ()))
+    }
+
+    fn entry_end(&mut self, _closer: Self::EntryCloser) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn tuple_struct_encode_field(
+        &mut self,
+        tuple: &mut Self::TupleStructEncoder,
+    ) -> anyhow::Result<Self::AnySpecEncoder> {
+        self.element(tuple)
+    }
+
+    fn tuple_struct_end(&mut self, tuple: Self::TupleStructEncoder) -> anyhow::Result<()> {
+        self.end_container(tuple)
+    }
+
+    fn struct_encode_field(
+        &mut self,
+        s: &mut Self::StructEncoder,
+        key: &'static str,
+    ) -> anyhow::Result<Self::AnySpecEncoder> {
+        self.struct_field(s, key)
+    }
+
+    fn struct_end(&mut self, s: Self::StructEncoder) -> anyhow::Result<()> {
+        self.end_container(s)
+    }
+
+    fn tuple_variant_encode_field(
+        &mut self,
+        tuple: &mut Self::TupleVariantEncoder,
+    ) -> anyhow::Result<Self::AnySpecEncoder> {
+        self.element(tuple)
+    }
+
+    fn tuple_variant_end(&mut self, tuple: Self::TupleVariantEncoder) -> anyhow::Result<()> {
+        self.end_container(tuple)
+    }
+
+    fn struct_variant_encode_field(
+        &mut self,
+        s: &mut Self::StructVariantEncoder,
+        key: &'static str,
+    ) -> anyhow::Result<Self::AnySpecEncoder> {
+        self.struct_field(s, key)
+    }
+
+    fn struct_variant_end(&mut self, s: Self::StructVariantEncoder) -> anyhow::Result<()> {
+        self.end_container(s)
+    }
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+}
+
+pub struct MsgpackAnySpecEncoder {
+    cannot_be_null: bool,
+}
+
+impl MsgpackAnySpecEncoder {
+    pub fn new() -> Self {
+        MsgpackAnySpecEncoder {
+            cannot_be_null: false,
+        }
+    }
+}
+
+pub struct MsgpackLenEncoder {
+    remaining: usize,
+}
@@ -0,0 +1,65 @@
+#![feature(slice_take)]
+#![deny(unused_must_use)]
+#![feature(never_type)]
+#![feature(trait_alias)]
+
+use marshal::de::Deserialize;
+use marshal::ser::Serialize;
+
+use crate::decode::full::MsgpackDecoder;
+use crate::encode::full::MsgpackEncoder;
+
+pub mod decode;
+pub mod encode;
+#[cfg(test)]
+mod test;
+
+/// Extension type holding a 128-bit big-endian unsigned integer that does not fit in a `uint 64`.
+pub const U128_EXT_TYPE: i8 = 1;
+/// Extension type holding a 128-bit big-endian two's complement integer that does not fit in an
+/// `int 64`.
+pub const I128_EXT_TYPE: i8 = 2;
+/// Extension type wrapping a `Some` whose contents would otherwise be read as `None`, such as
+/// `Some(None)` or `Some(())`.
+pub const SOME_EXT_TYPE: i8 = 3;
+
+const POSITIVE_FIXINT_MAX: u8 = 0x7f;
+const FIXMAP: u8 = 0x80;
+const FIXARRAY: u8 = 0x90;
+const FIXSTR: u8 = 0xa0;
+const NIL: u8 = 0xc0;
+const RESERVED: u8 = 0xc1;
+const FALSE: u8 = 0xc2;
+const TRUE: u8 = 0xc3;
+const BIN8: u8 = 0xc4;
+const BIN16: u8 = 0xc5;
+const BIN32: u8 = 0xc6;
+const EXT8: u8 = 0xc7;
+const EXT16: u8 = 0xc8;
+const EXT32: u8 = 0xc9;
+const FLOAT32: u8 = 0xca;
+const FLOAT64: u8 = 0xcb;
+const UINT8: u8 = 0xcc;
+const UINT16: u8 = 0xcd;
+const UINT32: u8 = 0xce;
+const UINT64: u8 = 0xcf;
+const INT8: u8 = 0xd0;
+const INT16: u8 = 0xd1;
+const INT32: u8 = 0xd2;
+const INT64: u8 = 0xd3;
+const FIXEXT1: u8 = 0xd4;
+const FIXEXT2: u8 = 0xd5;
+const FIXEXT4: u8 = 0xd6;
+const FIXEXT8: u8 = 0xd7;
+const FIXEXT16: u8 = 0xd8;
+const STR8: u8 = 0xd9;
+const STR16: u8 = 0xda;
+const STR32: u8 = 0xdb;
+const ARRAY16: u8 = 0xdc;
+const ARRAY32: u8 = 0xdd;
+const MAP16: u8 = 0xde;
+const MAP32: u8 = 0xdf;
+const NEGATIVE_FIXINT_MIN: u8 = 0xe0;
+
+pub trait SerializeMsgpack = Serialize<MsgpackEncoder>;
+pub trait DeserializeMsgpack = Deserialize<MsgpackDecoder>;
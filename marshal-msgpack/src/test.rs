@@ -0,0 +1,309 @@
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+
+use marshal::context::OwnedContext;
+use marshal_core::decode::{AnySpecDecoder, DecodeHint};
+use marshal_derive::{Deserialize, Serialize};
+
+use crate::decode::full::MsgpackDecoderBuilder;
+use crate::decode::SimpleMsgpackSpecDecoder;
+use crate::encode::full::MsgpackEncoderBuilder;
+use crate::{DeserializeMsgpack, SerializeMsgpack};
+
+fn decode<T: DeserializeMsgpack>(input: &[u8]) -> anyhow::Result<T> {
+    let mut c = OwnedContext::new();
+    MsgpackDecoderBuilder::new(input).deserialize(c.borrow())
+}
+
+#[track_caller]
+fn test_round_trip<T: Debug + PartialEq + SerializeMsgpack + DeserializeMsgpack>(
+    input: T,
+    expected: &[u8],
+) -> anyhow::Result<()> {
+    println!("{:?}", input);
+    let mut c = OwnedContext::new();
+    let found = MsgpackEncoderBuilder::new().serialize(&input, c.borrow())?;
+    assert_eq!(found, expected);
+    assert_eq!(decode::<T>(&found)?, input);
+    Ok(())
+}
+
+fn test_transmute<T1: Debug + SerializeMsgpack, T2: Debug + PartialEq + DeserializeMsgpack>(
+    input: T1,
+    output: T2,
+    expected: &[u8],
+) -> anyhow::Result<()> {
+    println!("{:?}", input);
+    let mut c = OwnedContext::new();
+    let found = MsgpackEncoderBuilder::new().serialize(&input, c.borrow())?;
+    assert_eq!(found, expected);
+    assert_eq!(decode::<T2>(&found)?, output);
+    Ok(())
+}
+
+#[test]
+fn test_unit() -> anyhow::Result<()> {
+    test_round_trip((), &[0xc0])?;
+    Ok(())
+}
+
+#[test]
+fn test_int() -> anyhow::Result<()> {
+    test_round_trip(123u8, &[123])?;
+    test_round_trip(200u8, &[0xcc, 200])?;
+    test_round_trip(300i32, &[0xcd, 0x01, 0x2c])?;
+    test_round_trip(70000u32, &[0xce, 0x00, 0x01, 0x11, 0x70])?;
+    test_round_trip(
+        u64::MAX,
+        &[0xcf, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff],
+    )?;
+    test_round_trip(-1i8, &[0xff])?;
+    test_round_trip(-32i16, &[0xe0])?;
+    test_round_trip(-33i32, &[0xd0, 0xdf])?;
+    test_round_trip(-1000i64, &[0xd1, 0xfc, 0x18])?;
+    test_round_trip(-100000i32, &[0xd2, 0xff, 0xfe, 0x79, 0x60])?;
+    test_round_trip(i64::MIN, &[0xd3, 0x80, 0, 0, 0, 0, 0, 0, 0])?;
+    test_round_trip(1.5f32, &[0xca, 0x3f, 0xc0, 0x00, 0x00])?;
+    test_round_trip(-2.0f64, &[0xcb, 0xc0, 0, 0, 0, 0, 0, 0, 0])?;
+    assert_eq!(decode::<f64>(&[0xca, 0x3f, 0xc0, 0x00, 0x00])?, 1.5);
+    assert_eq!(decode::<i16>(&[0xcc, 200])?, 200);
+    assert!(decode::<u8>(&[0xcd, 0x01, 0x2c]).is_err());
+    assert!(decode::<u32>(&[0xff]).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_int128() -> anyhow::Result<()> {
+    test_round_trip(5u128, &[5])?;
+    test_round_trip(-5i128, &[0xfb])?;
+    test_round_trip(u128::MAX, &[[0xd8, 0x01].as_slice(), &[0xff; 16]].concat())?;
+    test_round_trip(
+        i128::MIN,
+        &[[0xd8, 0x02, 0x80].as_slice(), &[0x00; 15]].concat(),
+    )?;
+    test_transmute(
+        1u128 << 64,
+        1i128 << 64,
+        &[[0xd8, 0x01].as_slice(), &[0, 0, 0, 0, 0, 0, 0, 1], &[0; 8]].concat(),
+    )?;
+    assert!(decode::<u64>(&[[0xd8, 0x01].as_slice(), &[0xff; 16]].concat()).is_err());
+    assert!(decode::<u128>(&[0xd7, 0x01, 0, 0, 0, 0, 0, 0, 0, 1]).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_tuple() -> anyhow::Result<()> {
+    test_round_trip((123u8, 124u16), &[0x92, 123, 124])?;
+    Ok(())
+}
+
+#[test]
+fn test_vec() -> anyhow::Result<()> {
+    test_round_trip(vec![Some(1), Some(2), Some(3)], &[0x93, 1, 2, 3])?;
+    test_round_trip(
+        (0..20u8).collect::<Vec<_>>(),
+        &[[0xc4, 20].as_slice(), &(0..20u8).collect::<Vec<_>>()].concat(),
+    )?;
+    test_round_trip(
+        vec![7u32; 20],
+        &[[0xdc, 0, 20].as_slice(), &[7; 20]].concat(),
+    )?;
+    Ok(())
+}
+
+#[test]
+fn test_string() -> anyhow::Result<()> {
+    test_round_trip("abc".to_string(), &[0xa3, b'a', b'b', b'c'])?;
+    test_round_trip('x', &[0xa1, b'x'])?;
+    let long = "x".repeat(40);
+    test_round_trip(
+        long.clone(),
+        &[[0xd9, 40].as_slice(), long.as_bytes()].concat(),
+    )?;
+    let longer = "y".repeat(300);
+    test_round_trip(
+        longer.clone(),
+        &[[0xda, 0x01, 0x2c].as_slice(), longer.as_bytes()].concat(),
+    )?;
+    assert!(decode::<String>(&[0xa2, 0xff, 0xff]).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_struct() -> anyhow::Result<()> {
+    #[derive(Serialize, Deserialize, Debug, Eq, PartialEq)]
+    struct Foo {
+        abc: u8,
+        xyz: u32,
+    }
+    test_round_trip(
+        Foo { abc: 123, xyz: 124 },
+        &[
+            0x82, 0xa3, b'a', b'b', b'c', 123, 0xa3, b'x', b'y', b'z', 124, //
+        ],
+    )?;
+    test_round_trip(
+        vec![Foo { abc: 123, xyz: 124 }, Foo { abc: 125, xyz: 126 }],
+        &[
+            0x92, //
+            0x82, 0xa3, b'a', b'b', b'c', 123, 0xa3, b'x', b'y', b'z', 124, //
+            0x82, 0xa3, b'a', b'b', b'c', 125, 0xa3, b'x', b'y', b'z', 126, //
+        ],
+    )?;
+
+    #[derive(Serialize, Deserialize, Debug, Eq, PartialEq)]
+    struct OrderOne {
+        x1: u32,
+        x2: u64,
+    }
+
+    #[derive(Serialize, Deserialize, Debug, Eq, PartialEq)]
+    struct OrderTwo {
+        x2: u64,
+        x1: u32,
+    }
+
+    test_transmute(
+        OrderOne { x1: 51, x2: 52 },
+        OrderTwo { x1: 51, x2: 52 },
+        &[0x82, 0xa2, b'x', b'1', 51, 0xa2, b'x', b'2', 52],
+    )?;
+    Ok(())
+}
+
+#[test]
+fn test_struct_as_array() -> anyhow::Result<()> {
+    #[derive(Serialize, Deserialize, Debug, Eq, PartialEq)]
+    struct Foo {
+        abc: u8,
+        xyz: u32,
+    }
+    #[derive(Serialize, Deserialize, Debug, Eq, PartialEq)]
+    enum Bar {
+        V { x: u8 },
+    }
+    let mut c = OwnedContext::new();
+    let mut w = MsgpackEncoderBuilder::new();
+    w.set_struct_as_array(true);
+    let input = (Foo { abc: 123, xyz: 124 }, Bar::V { x: 5 });
+    let encoded = w.serialize(&input, c.borrow())?;
+    assert_eq!(encoded, &[0x92, 0x92, 123, 124, 0x81, 0xa1, b'V', 0x91, 5]);
+    assert_eq!(decode::<(Foo, Bar)>(&encoded)?, input);
+    Ok(())
+}
+
+#[test]
+fn test_unit_struct() -> anyhow::Result<()> {
+    #[derive(Serialize, Deserialize, Debug, Eq, PartialEq)]
+    struct Foo;
+    test_round_trip(Foo, &[0xc0])?;
+
+    Ok(())
+}
+
+#[test]
+fn test_tuple_struct() -> anyhow::Result<()> {
+    #[derive(Serialize, Deserialize, Debug, Eq, PartialEq)]
+    struct Foo(u8, u16, u32);
+    test_round_trip(Foo(50, 51, 52), &[0x93, 50, 51, 52])?;
+
+    Ok(())
+}
+
+#[test]
+fn test_enum_variants() -> anyhow::Result<()> {
+    #[derive(Serialize, Deserialize, Debug, Eq, PartialEq)]
+    enum AllVariants {
+        U,
+        T(u8, u16),
+        V { x: u32, y: u64, z: u128 },
+    }
+    test_round_trip(AllVariants::U, &[0xa1, b'U'])?;
+    test_round_trip(AllVariants::T(51, 52), &[0x81, 0xa1, b'T', 0x92, 51, 52])?;
+    test_round_trip(
+        AllVariants::V {
+            x: 53,
+            y: 54,
+            z: 55,
+        },
+        &[
+            0x81, 0xa1, b'V', //
+            0x83, 0xa1, b'x', 53, 0xa1, b'y', 54, 0xa1, b'z', 55,
+        ],
+    )?;
+    assert!(decode::<AllVariants>(&[0x82, 0xa1, b'U', 0xc0, 0xa1, b'U', 0xc0]).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_map() -> anyhow::Result<()> {
+    test_round_trip::<BTreeMap<u8, u8>>(
+        [(1, 2), (3, 4)].into_iter().collect(),
+        &[0x82, 1, 2, 3, 4],
+    )?;
+    Ok(())
+}
+
+#[test]
+fn test_none() -> anyhow::Result<()> {
+    test_round_trip::<Option<!>>(None, &[0xc0])?;
+    Ok(())
+}
+
+#[test]
+fn test_some() -> anyhow::Result<()> {
+    test_round_trip(Some(5u8), &[5])?;
+    test_round_trip(Some(()), &[0xd4, 3, 0xc0])?;
+    test_round_trip(Some(None::<u8>), &[0xd4, 3, 0xc0])?;
+    test_round_trip(Some(Some(5u8)), &[0xd4, 3, 5])?;
+    test_round_trip(Some(Some(None::<u8>)), &[0xc7, 3, 3, 0xd4, 3, 0xc0])?;
+    test_round_trip(
+        vec![None, Some(None), Some(Some("ab".to_string()))],
+        &[0x93, 0xc0, 0xd4, 3, 0xc0, 0xc7, 3, 3, 0xa2, b'a', b'b'],
+    )?;
+    // A reader that does not expect an option reads the contents directly.
+    assert_eq!(decode::<()>(&[0xd4, 3, 0xc0])?, ());
+    assert_eq!(decode::<u8>(&[0xc7, 3, 3, 0xd4, 3, 7])?, 7);
+    assert_eq!(decode::<Vec<u16>>(&[0xc7, 3, 3, 0x92, 1, 2])?, vec![1, 2]);
+    assert!(decode::<u8>(&[0xc7, 2, 3, 7, 8]).is_err());
+    assert!(decode::<Vec<u16>>(&[0xc7, 2, 3, 0x92, 1, 2]).is_err());
+    assert!(decode::<Vec<u16>>(&[0xc7, 4, 3, 0x92, 1, 2, 3]).is_err());
+    assert!(decode::<Option<Option<u8>>>(&[0xd5, 3, 0xc0, 0]).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_errors() -> anyhow::Result<()> {
+    assert!(decode::<u8>(&[1, 1]).is_err());
+    assert!(decode::<u16>(&[0xcd, 1]).is_err());
+    assert!(decode::<u8>(&[0xc1]).is_err());
+    assert!(decode::<u8>(&[0xd4, 9, 0]).is_err());
+    assert!(decode::<Vec<u8>>(&[0x93, 1, 2]).is_err());
+    let deep = [vec![0x91; 200], vec![1]].concat();
+    assert!(MsgpackDecoderBuilder::new(&deep)
+        .set_budget(10)
+        .build()
+        .ignore()
+        .is_err());
+    Ok(())
+}
+
+#[test]
+fn test_untrusted_length() -> anyhow::Result<()> {
+    fn seq_size(input: &[u8]) -> anyhow::Result<Option<usize>> {
+        let mut decoder = SimpleMsgpackSpecDecoder::new(input);
+        let any = AnySpecDecoder::new(&mut decoder, ());
+        Ok(any.decode(DecodeHint::Seq)?.try_into_seq()?.exact_size())
+    }
+    fn map_size(input: &[u8]) -> anyhow::Result<Option<usize>> {
+        let mut decoder = SimpleMsgpackSpecDecoder::new(input);
+        let any = AnySpecDecoder::new(&mut decoder, ());
+        Ok(any.decode(DecodeHint::Map)?.try_into_map()?.exact_size())
+    }
+    assert_eq!(seq_size(&[0xdd, 0xff, 0xff, 0xff, 0xff])?, None);
+    assert_eq!(seq_size(&[0x92, 1, 2])?, Some(2));
+    assert_eq!(map_size(&[0xdf, 0, 0, 0, 2, 1, 2])?, None);
+    assert_eq!(map_size(&[0x81, 1, 2])?, Some(1));
+    assert!(decode::<Vec<u64>>(&[0xdd, 0xff, 0xff, 0xff, 0xff, 1]).is_err());
+    Ok(())
+}
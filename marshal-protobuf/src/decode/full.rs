@@ -0,0 +1,76 @@
+use marshal::context::Context;
+use marshal::de::Deserialize;
+use marshal_core::decode::depth_budget::{DepthBudgetDecoder, WithDepthBudget};
+use marshal_core::decode::poison::PoisonDecoder;
+use marshal_core::decode::{AnySpecDecoder, Decoder};
+use marshal_core::derive_decoder_for_newtype;
+
+use crate::decode::{ProtobufAnyDecoder, SimpleProtobufSpecDecoder};
+use crate::SignedEncoding;
+
+pub struct ProtobufSpecDecoder<'de>(
+    PoisonDecoder<DepthBudgetDecoder<SimpleProtobufSpecDecoder<'de>>>,
+);
+
+derive_decoder_for_newtype!(ProtobufSpecDecoder<'de>(PoisonDecoder<DepthBudgetDecoder<SimpleProtobufSpecDecoder<'de>>>));
+
+pub struct ProtobufDecoderBuilder<'de> {
+    inner: ProtobufSpecDecoder<'de>,
+    depth_budget: usize,
+}
+
+impl<'de> ProtobufDecoderBuilder<'de> {
+    pub fn new(input: &'de [u8]) -> Self {
+        ProtobufDecoderBuilder {
+            inner: ProtobufSpecDecoder(PoisonDecoder::new(DepthBudgetDecoder::new(
+                SimpleProtobufSpecDecoder::new(input),
+            ))),
+            depth_budget: 100,
+        }
+    }
+    /// Limits the nesting of messages, and separately of skipped groups.
+    pub fn set_budget(&mut self, depth_budget: usize) -> &mut Self {
+        self.depth_budget = depth_budget;
+        self.inner
+            .0
+            .inner_mut()
+            .inner_mut()
+            .set_max_group_depth(depth_budget);
+        self
+    }
+    /// Selects between `sint*` and `int*` encodings for all signed integers.
+    pub fn set_signed_encoding(&mut self, signed: SignedEncoding) -> &mut Self {
+        self.inner
+            .0
+            .inner_mut()
+            .inner_mut()
+            .set_signed_encoding(signed);
+        self
+    }
+    pub fn build<'p>(&'p mut self) -> AnySpecDecoder<'p, 'de, ProtobufSpecDecoder<'de>> {
+        let any = WithDepthBudget::new(self.depth_budget, ProtobufAnyDecoder::Document);
+        let any = self.inner.0.start(any);
+        AnySpecDecoder::new(&mut self.inner, any)
+    }
+    /// Deserializes the document, reporting the offset of the value that failed.
+    pub fn deserialize<T: Deserialize<ProtobufDecoder>>(
+        mut self,
+        ctx: Context,
+    ) -> anyhow::Result<T> {
+        let result = T::deserialize(self.build(), ctx).map_err(|e| e.context(self.location()))?;
+        self.end()?;
+        Ok(result)
+    }
+    pub fn location(&self) -> String {
+        self.inner.0.inner().inner().location()
+    }
+    pub fn end(self) -> anyhow::Result<()> {
+        Ok(self.inner.0.end()?.end()?.end()?)
+    }
+}
+
+pub struct ProtobufDecoder;
+
+impl Decoder for ProtobufDecoder {
+    type SpecDecoder<'de> = ProtobufSpecDecoder<'de>;
+}
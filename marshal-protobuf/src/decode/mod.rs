@@ -0,0 +1,633 @@
+use std::borrow::Cow;
+use std::fmt::{Display, Formatter};
+
+use marshal_core::decode::{DecodeHint, DecodeVariantHint, SimpleDecoderView, SpecDecoder};
+use marshal_core::{Primitive, PrimitiveType};
+
+use crate::varint::{read_varint, zigzag_decode};
+use crate::{SignedEncoding, WIRE_EGROUP, WIRE_I32, WIRE_I64, WIRE_LEN, WIRE_SGROUP, WIRE_VARINT};
+
+pub mod full;
+
+#[derive(Debug)]
+pub enum ProtobufDecoderError {
+    Eof,
+    VarintOverflow,
+    BadWireType(u8),
+    BadFieldNumber(u64),
+    UnmatchedEndGroup,
+    WireTypeMismatch { expected: u8, found: u8 },
+    BadChar(u64),
+    BadInteger,
+    GroupTooDeep(usize),
+    BadVariantNumber(u64),
+}
+
+impl Display for ProtobufDecoderError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProtobufDecoderError::Eof => write!(f, "unexpected end of input"),
+            ProtobufDecoderError::VarintOverflow => write!(f, "varint is longer than 64 bits"),
+            ProtobufDecoderError::BadWireType(x) => write!(f, "unknown wire type {}", x),
+            ProtobufDecoderError::BadFieldNumber(x) => write!(f, "invalid field number {}", x),
+            ProtobufDecoderError::UnmatchedEndGroup => {
+                write!(f, "end of group without a matching start")
+            }
+            ProtobufDecoderError::WireTypeMismatch { expected, found } => write!(
+                f,
+                "expected wire type {}, found wire type {}",
+                expected, found
+            ),
+            ProtobufDecoderError::BadChar(x) => write!(f, "{} is not a char", x),
+            ProtobufDecoderError::BadInteger => {
+                write!(f, "128-bit integers must be 16 length-delimited bytes")
+            }
+            ProtobufDecoderError::GroupTooDeep(x) => {
+                write!(f, "groups are nested more than {} deep", x)
+            }
+            ProtobufDecoderError::BadVariantNumber(x) => {
+                write!(f, "invalid variant number {}", x)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ProtobufDecoderError {}
+
+#[derive(Copy, Clone)]
+pub enum WireValue<'de> {
+    Varint(u64),
+    I64(u64),
+    Len(&'de [u8]),
+    I32(u32),
+}
+
+/// One occurrence of a field within a message.
+#[derive(Copy, Clone)]
+pub struct Occurrence<'de> {
+    value: WireValue<'de>,
+    /// The offset of the field within the input.
+    offset: usize,
+}
+
+pub enum ProtobufAnyDecoder<'de> {
+    /// The top-level message.
+    Document,
+    /// The index of a field or variant.
+    Index(usize),
+    /// Every occurrence of a field, which is absent if there are none.
+    Field(Vec<Occurrence<'de>>),
+    /// An element of a repeated field, which may be a run of packed numbers.
+    Element(Occurrence<'de>),
+}
+
+pub enum ProtobufSeqDecoder<'de> {
+    Repeated(std::vec::IntoIter<Occurrence<'de>>),
+    /// The fields of a tuple, numbered from 1.
+    Fields(std::vec::IntoIter<Vec<Occurrence<'de>>>),
+}
+
+pub enum ProtobufMapDecoder<'de> {
+    Fields(std::iter::Enumerate<std::vec::IntoIter<Vec<Occurrence<'de>>>>),
+    /// The entries of a map, which are messages with the key as field 1 and the value as field 2.
+    Entries(std::vec::IntoIter<Occurrence<'de>>),
+}
+
+pub enum ProtobufKeyDecoder<'de> {
+    Field(usize, Vec<Occurrence<'de>>),
+    Entry(Occurrence<'de>),
+}
+
+pub struct SimpleProtobufSpecDecoder<'de> {
+    input: &'de [u8],
+    /// The rest of a packed field after the element just decoded.
+    packed: Option<Occurrence<'de>>,
+    /// The offset of the most recent value, for reporting errors.
+    offset: usize,
+    /// The deepest nesting of skipped groups, which are not visible to the depth budget.
+    max_group_depth: usize,
+    signed: SignedEncoding,
+}
+
+impl<'de> WireValue<'de> {
+    fn wire_type(&self) -> u8 {
+        match self {
+            WireValue::Varint(_) => WIRE_VARINT,
+            WireValue::I64(_) => WIRE_I64,
+            WireValue::Len(_) => WIRE_LEN,
+            WireValue::I32(_) => WIRE_I32,
+        }
+    }
+}
+
+/// The wire type of a primitive that is not length-delimited, which may be packed.
+fn packed_wire_type(prim: PrimitiveType) -> Option<u8> {
+    match prim {
+        PrimitiveType::Unit | PrimitiveType::I128 | PrimitiveType::U128 => None,
+        PrimitiveType::F32 => Some(WIRE_I32),
+        PrimitiveType::F64 => Some(WIRE_I64),
+        _ => Some(WIRE_VARINT),
+    }
+}
+
+fn read_count<'de>(input: &mut &'de [u8], count: usize) -> Result<&'de [u8], ProtobufDecoderError> {
+    if input.len() < count {
+        return Err(ProtobufDecoderError::Eof);
+    }
+    let (result, rest) = input.split_at(count);
+    *input = rest;
+    Ok(result)
+}
+
+fn read_wire_value<'de>(
+    input: &mut &'de [u8],
+    wire: u8,
+) -> Result<WireValue<'de>, ProtobufDecoderError> {
+    Ok(match wire {
+        WIRE_VARINT => WireValue::Varint(read_varint(input)?),
+        WIRE_I64 => WireValue::I64(u64::from_le_bytes(
+            read_count(input, 8)?.try_into().unwrap(),
+        )),
+        WIRE_LEN => {
+            let len = read_varint(input)?;
+            let len = usize::try_from(len).map_err(|_| ProtobufDecoderError::Eof)?;
+            WireValue::Len(read_count(input, len)?)
+        }
+        WIRE_I32 => WireValue::I32(u32::from_le_bytes(
+            read_count(input, 4)?.try_into().unwrap(),
+        )),
+        _ => return Err(ProtobufDecoderError::BadWireType(wire)),
+    })
+}
+
+fn zero(prim: PrimitiveType) -> Primitive {
+    match prim {
+        PrimitiveType::Unit => Primitive::Unit,
+        PrimitiveType::Bool => Primitive::Bool(false),
+        PrimitiveType::I8 => Primitive::I8(0),
+        PrimitiveType::I16 => Primitive::I16(0),
+        PrimitiveType::I32 => Primitive::I32(0),
+        PrimitiveType::I64 => Primitive::I64(0),
+        PrimitiveType::I128 => Primitive::I128(0),
+        PrimitiveType::U8 => Primitive::U8(0),
+        PrimitiveType::U16 => Primitive::U16(0),
+        PrimitiveType::U32 => Primitive::U32(0),
+        PrimitiveType::U64 => Primitive::U64(0),
+        PrimitiveType::U128 => Primitive::U128(0),
+        PrimitiveType::F32 => Primitive::F32(0.0),
+        PrimitiveType::F64 => Primitive::F64(0.0),
+        PrimitiveType::Char => Primitive::Char('\0'),
+    }
+}
+
+fn decode_signed(x: u64, signed: SignedEncoding) -> i64 {
+    match signed {
+        SignedEncoding::ZigZag => zigzag_decode(x),
+        SignedEncoding::TwosComplement => x as i64,
+    }
+}
+
+fn read_primitive(
+    value: WireValue,
+    prim: PrimitiveType,
+    signed: SignedEncoding,
+) -> anyhow::Result<Primitive> {
+    let expected = match packed_wire_type(prim) {
+        Some(wire) => wire,
+        None => WIRE_LEN,
+    };
+    if value.wire_type() != expected {
+        return Err(ProtobufDecoderError::WireTypeMismatch {
+            expected,
+            found: value.wire_type(),
+        }
+        .into());
+    }
+    Ok(match (value, prim) {
+        (WireValue::I32(x), _) => Primitive::F32(f32::from_bits(x)),
+        (WireValue::I64(x), _) => Primitive::F64(f64::from_bits(x)),
+        (WireValue::Len(_), PrimitiveType::Unit) => Primitive::Unit,
+        (WireValue::Len(x), PrimitiveType::U128) => Primitive::U128(u128::from_le_bytes(
+            x.try_into().map_err(|_| ProtobufDecoderError::BadInteger)?,
+        )),
+        (WireValue::Len(x), _) => Primitive::I128(i128::from_le_bytes(
+            x.try_into().map_err(|_| ProtobufDecoderError::BadInteger)?,
+        )),
+        (WireValue::Varint(x), prim) => match prim {
+            PrimitiveType::Bool => Primitive::Bool(x != 0),
+            PrimitiveType::I8 => Primitive::I8(decode_signed(x, signed).try_into()?),
+            PrimitiveType::I16 => Primitive::I16(decode_signed(x, signed).try_into()?),
+            PrimitiveType::I32 => Primitive::I32(decode_signed(x, signed).try_into()?),
+            PrimitiveType::I64 => Primitive::I64(decode_signed(x, signed)),
+            PrimitiveType::U8 => Primitive::U8(x.try_into()?),
+            PrimitiveType::U16 => Primitive::U16(x.try_into()?),
+            PrimitiveType::U32 => Primitive::U32(x.try_into()?),
+            PrimitiveType::U64 => Primitive::U64(x),
+            PrimitiveType::Char => Primitive::Char(
+                u32::try_from(x)
+                    .ok()
+                    .and_then(char::from_u32)
+                    .ok_or(ProtobufDecoderError::BadChar(x))?,
+            ),
+            _ => unreachable!(),
+        },
+    })
+}
+
+impl<'de> SimpleProtobufSpecDecoder<'de> {
+    pub fn new(input: &'de [u8]) -> Self {
+        SimpleProtobufSpecDecoder {
+            input,
+            packed: None,
+            offset: 0,
+            max_group_depth: 100,
+            signed: SignedEncoding::default(),
+        }
+    }
+    pub fn set_signed_encoding(&mut self, signed: SignedEncoding) {
+        self.signed = signed;
+    }
+    pub fn set_max_group_depth(&mut self, max_group_depth: usize) {
+        self.max_group_depth = max_group_depth;
+    }
+    pub fn end(self) -> anyhow::Result<()> {
+        Ok(())
+    }
+    pub fn location(&self) -> String {
+        format!("at byte {}", self.offset)
+    }
+    fn offset_of(&self, slice: &[u8]) -> usize {
+        slice.as_ptr() as usize - self.input.as_ptr() as usize
+    }
+    /// Reads the next field of a message, skipping groups, which are deprecated.
+    fn read_record(
+        &mut self,
+        input: &mut &'de [u8],
+    ) -> anyhow::Result<Option<(u64, Occurrence<'de>)>> {
+        loop {
+            if input.is_empty() {
+                return Ok(None);
+            }
+            self.offset = self.offset_of(input);
+            let key = read_varint(input)?;
+            let (tag, wire) = (key >> 3, (key & 7) as u8);
+            if tag == 0 || tag >= 1 << 29 {
+                return Err(ProtobufDecoderError::BadFieldNumber(tag).into());
+            }
+            match wire {
+                WIRE_SGROUP => self.skip_group(input, tag)?,
+                WIRE_EGROUP => return Err(ProtobufDecoderError::UnmatchedEndGroup.into()),
+                _ => {
+                    let value = read_wire_value(input, wire)?;
+                    let offset = self.offset;
+                    return Ok(Some((tag, Occurrence { value, offset })));
+                }
+            }
+        }
+    }
+    /// Skips a group and any groups nested within it, without recursing.
+    fn skip_group(&mut self, input: &mut &'de [u8], tag: u64) -> anyhow::Result<()> {
+        let mut open = vec![tag];
+        while let Some(&innermost) = open.last() {
+            if input.is_empty() {
+                return Err(ProtobufDecoderError::Eof.into());
+            }
+            let key = read_varint(input)?;
+            match (key & 7) as u8 {
+                WIRE_SGROUP => {
+                    if open.len() >= self.max_group_depth {
+                        return Err(ProtobufDecoderError::GroupTooDeep(self.max_group_depth).into());
+                    }
+                    open.push(key >> 3);
+                }
+                WIRE_EGROUP if key >> 3 == innermost => {
+                    open.pop();
+                }
+                WIRE_EGROUP => return Err(ProtobufDecoderError::UnmatchedEndGroup.into()),
+                wire => {
+                    read_wire_value(input, wire)?;
+                }
+            }
+        }
+        Ok(())
+    }
+    /// Groups the fields of a message by index, skipping unknown fields. Several occurrences of
+    /// a message are merged, as protobuf requires.
+    fn read_message(
+        &mut self,
+        occurrences: &[Occurrence<'de>],
+        len: usize,
+        tags: &[u32],
+    ) -> anyhow::Result<Vec<Vec<Occurrence<'de>>>> {
+        let mut fields = vec![vec![]; len];
+        for occurrence in occurrences {
+            let mut input = self.expect_len(occurrence)?;
+            while let Some((tag, field)) = self.read_record(&mut input)? {
+                let index = match tags {
+                    [] => (tag as usize).checked_sub(1).filter(|x| *x < len),
+                    tags => tags.iter().position(|x| *x as u64 == tag),
+                };
+                if let Some(index) = index {
+                    fields[index].push(field);
+                }
+            }
+        }
+        Ok(fields)
+    }
+    fn expect_len(&mut self, occurrence: &Occurrence<'de>) -> anyhow::Result<&'de [u8]> {
+        self.offset = occurrence.offset;
+        match occurrence.value {
+            WireValue::Len(x) => Ok(x),
+            value => Err(ProtobufDecoderError::WireTypeMismatch {
+                expected: WIRE_LEN,
+                found: value.wire_type(),
+            }
+            .into()),
+        }
+    }
+    /// Reads one number of a packed run, keeping the rest for the next element.
+    fn read_packed(
+        &mut self,
+        occurrence: Occurrence<'de>,
+        prim: PrimitiveType,
+    ) -> anyhow::Result<Primitive> {
+        let mut input = self.expect_len(&occurrence)?;
+        let value = read_wire_value(&mut input, packed_wire_type(prim).unwrap())?;
+        if !input.is_empty() {
+            self.packed = Some(Occurrence {
+                value: WireValue::Len(input),
+                offset: self.offset_of(input),
+            });
+        }
+        read_primitive(value, prim, self.signed)
+    }
+    /// Reads the variant of an enum, which is a number for a unit variant or a message with one
+    /// field, numbered by the variant index plus one, for a variant with data.
+    fn read_variant(
+        &mut self,
+        occurrences: Vec<Occurrence<'de>>,
+    ) -> anyhow::Result<(usize, Vec<Occurrence<'de>>)> {
+        let Some(last) = occurrences.last() else {
+            return Ok((0, vec![]));
+        };
+        self.offset = last.offset;
+        if let WireValue::Varint(x) = last.value {
+            if x == 0 {
+                return Err(ProtobufDecoderError::BadVariantNumber(x).into());
+            }
+            return Ok((usize::try_from(x - 1)?, vec![]));
+        }
+        let mut input = self.expect_len(last)?;
+        let mut variant: Option<(u64, Vec<Occurrence<'de>>)> = None;
+        while let Some((tag, field)) = self.read_record(&mut input)? {
+            match &mut variant {
+                Some((index, fields)) if *index == tag => fields.push(field),
+                _ => variant = Some((tag, vec![field])),
+            }
+        }
+        let (tag, fields) = variant.unwrap_or((1, vec![]));
+        Ok((tag as usize - 1, fields))
+    }
+    /// Decodes the occurrences of a field. The `tags` number the fields of a struct, or are empty
+    /// if they are numbered in order.
+    fn decode_field(
+        &mut self,
+        occurrences: Vec<Occurrence<'de>>,
+        hint: DecodeHint,
+        tags: &[u32],
+    ) -> anyhow::Result<SimpleDecoderView<'de, Self>> {
+        if let Some(first) = occurrences.first() {
+            self.offset = first.offset;
+        }
+        Ok(match hint {
+            DecodeHint::Option if occurrences.is_empty() => SimpleDecoderView::None,
+            DecodeHint::Option => SimpleDecoderView::Some(occurrences),
+            DecodeHint::Seq => {
+                SimpleDecoderView::Seq(ProtobufSeqDecoder::Repeated(occurrences.into_iter()))
+            }
+            DecodeHint::Map => {
+                SimpleDecoderView::Map(ProtobufMapDecoder::Entries(occurrences.into_iter()))
+            }
+            DecodeHint::Struct { fields, .. } => {
+                let fields = self.read_message(&occurrences, fields.len(), tags)?;
+                SimpleDecoderView::Map(ProtobufMapDecoder::Fields(fields.into_iter().enumerate()))
+            }
+            DecodeHint::Tuple { len } | DecodeHint::TupleStruct { len, .. } => {
+                let fields = self.read_message(&occurrences, len, &[])?;
+                SimpleDecoderView::Seq(ProtobufSeqDecoder::Fields(fields.into_iter()))
+            }
+            DecodeHint::Enum { .. } => SimpleDecoderView::Enum(occurrences),
+            DecodeHint::UnitStruct { .. } => {
+                if let Some(last) = occurrences.last() {
+                    self.expect_len(last)?;
+                }
+                SimpleDecoderView::Primitive(Primitive::Unit)
+            }
+            DecodeHint::Primitive(prim) => match occurrences.last() {
+                None => SimpleDecoderView::Primitive(zero(prim)),
+                Some(last) => {
+                    self.offset = last.offset;
+                    SimpleDecoderView::Primitive(read_primitive(last.value, prim, self.signed)?)
+                }
+            },
+            DecodeHint::String => match occurrences.last() {
+                None => SimpleDecoderView::String(Cow::Borrowed("")),
+                Some(last) => SimpleDecoderView::String(Cow::Borrowed(std::str::from_utf8(
+                    self.expect_len(last)?,
+                )?)),
+            },
+            DecodeHint::Bytes => match occurrences.last() {
+                None => SimpleDecoderView::Bytes(Cow::Borrowed(&[])),
+                Some(last) => SimpleDecoderView::Bytes(Cow::Borrowed(self.expect_len(last)?)),
+            },
+            DecodeHint::Any | DecodeHint::Identifier | DecodeHint::Ignore => {
+                match occurrences.last().map(|x| x.value) {
+                    None => SimpleDecoderView::Primitive(Primitive::Unit),
+                    Some(WireValue::Varint(x)) => SimpleDecoderView::Primitive(Primitive::U64(x)),
+                    Some(WireValue::I64(x)) => SimpleDecoderView::Primitive(Primitive::U64(x)),
+                    Some(WireValue::I32(x)) => SimpleDecoderView::Primitive(Primitive::U32(x)),
+                    Some(WireValue::Len(x)) => SimpleDecoderView::Bytes(Cow::Borrowed(x)),
+                }
+            }
+        })
+    }
+
+    fn decode_any(
+        &mut self,
+        any: ProtobufAnyDecoder<'de>,
+        hint: DecodeHint,
+        tags: &[u32],
+    ) -> anyhow::Result<SimpleDecoderView<'de, Self>> {
+        let occurrences = match any {
+            ProtobufAnyDecoder::Document => vec![Occurrence {
+                value: WireValue::Len(self.input),
+                offset: 0,
+            }],
+            ProtobufAnyDecoder::Index(index) => {
+                return Ok(SimpleDecoderView::Primitive(Primitive::U64(index as u64)));
+            }
+            ProtobufAnyDecoder::Field(occurrences) => occurrences,
+            ProtobufAnyDecoder::Element(occurrence) => {
+                if let DecodeHint::Primitive(prim) = hint {
+                    if packed_wire_type(prim).is_some() {
+                        if let WireValue::Len(_) = occurrence.value {
+                            return Ok(SimpleDecoderView::Primitive(
+                                self.read_packed(occurrence, prim)?,
+                            ));
+                        }
+                    }
+                }
+                vec![occurrence]
+            }
+        };
+        self.decode_field(occurrences, hint, tags)
+    }
+}
+
+impl<'de> SpecDecoder<'de> for SimpleProtobufSpecDecoder<'de> {
+    type AnyDecoder = ProtobufAnyDecoder<'de>;
+    type SeqDecoder = ProtobufSeqDecoder<'de>;
+    type MapDecoder = ProtobufMapDecoder<'de>;
+    type KeyDecoder = ProtobufKeyDecoder<'de>;
+    type ValueDecoder = Vec<Occurrence<'de>>;
+    type DiscriminantDecoder = Vec<Occurrence<'de>>;
+    type VariantDecoder = Vec<Occurrence<'de>>;
+    type EnumCloser = ();
+    type SomeDecoder = Vec<Occurrence<'de>>;
+    type SomeCloser = ();
+
+    fn decode(
+        &mut self,
+        any: Self::AnyDecoder,
+        hint: DecodeHint,
+    ) -> anyhow::Result<SimpleDecoderView<'de, Self>> {
+        self.decode_any(any, hint, &[])
+    }
+
+    fn decode_tagged_struct(
+        &mut self,
+        any: Self::AnyDecoder,
+        name: &'static str,
+        fields: &'static [&'static str],
+        tags: &'static [u32],
+    ) -> anyhow::Result<SimpleDecoderView<'de, Self>> {
+        self.decode_any(any, DecodeHint::Struct { name, fields }, tags)
+    }
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+
+    fn decode_seq_next(
+        &mut self,
+        seq: &mut Self::SeqDecoder,
+    ) -> anyhow::Result<Option<Self::AnyDecoder>> {
+        Ok(match seq {
+            ProtobufSeqDecoder::Repeated(elements) => match self.packed.take() {
+                Some(rest) => Some(ProtobufAnyDecoder::Element(rest)),
+                None => elements.next().map(ProtobufAnyDecoder::Element),
+            },
+            ProtobufSeqDecoder::Fields(fields) => fields.next().map(ProtobufAnyDecoder::Field),
+        })
+    }
+
+    fn decode_seq_exact_size(&self, seq: &Self::SeqDecoder) -> Option<usize> {
+        match seq {
+            ProtobufSeqDecoder::Repeated(_) => None,
+            ProtobufSeqDecoder::Fields(fields) => Some(fields.len()),
+        }
+    }
+
+    fn decode_seq_end(&mut self, _seq: Self::SeqDecoder) -> anyhow::Result<()> {
+        self.packed = None;
+        Ok(())
+    }
+
+    fn decode_map_next(
+        &mut self,
+        map: &mut Self::MapDecoder,
+    ) -> anyhow::Result<Option<Self::KeyDecoder>> {
+        Ok(match map {
+            ProtobufMapDecoder::Fields(fields) => fields
+                .next()
+                .map(|(index, field)| ProtobufKeyDecoder::Field(index, field)),
+            ProtobufMapDecoder::Entries(entries) => entries.next().map(ProtobufKeyDecoder::Entry),
+        })
+    }
+
+    fn decode_map_exact_size(&self, map: &Self::MapDecoder) -> Option<usize> {
+        match map {
+            ProtobufMapDecoder::Fields(fields) => Some(fields.len()),
+            ProtobufMapDecoder::Entries(entries) => Some(entries.len()),
+        }
+    }
+
+    fn decode_map_end(&mut self, _map: Self::MapDecoder) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn decode_entry_key(
+        &mut self,
+        key: Self::KeyDecoder,
+    ) -> anyhow::Result<(Self::AnyDecoder, Self::ValueDecoder)> {
+        match key {
+            ProtobufKeyDecoder::Field(index, field) => {
+                Ok((ProtobufAnyDecoder::Index(index), field))
+            }
+            ProtobufKeyDecoder::Entry(entry) => {
+                let mut fields = self.read_message(&[entry], 2, &[])?;
+                let value = fields.pop().unwrap();
+                let key = fields.pop().unwrap();
+                Ok((ProtobufAnyDecoder::Field(key), value))
+            }
+        }
+    }
+
+    fn decode_entry_value(
+        &mut self,
+        value: Self::ValueDecoder,
+    ) -> anyhow::Result<Self::AnyDecoder> {
+        Ok(ProtobufAnyDecoder::Field(value))
+    }
+
+    fn decode_enum_discriminant(
+        &mut self,
+        e: Self::DiscriminantDecoder,
+    ) -> anyhow::Result<(Self::AnyDecoder, Self::VariantDecoder)> {
+        let (index, fields) = self.read_variant(e)?;
+        Ok((ProtobufAnyDecoder::Index(index), fields))
+    }
+
+    fn decode_enum_variant(
+        &mut self,
+        fields: Self::VariantDecoder,
+        hint: DecodeVariantHint,
+    ) -> anyhow::Result<(SimpleDecoderView<'de, Self>, Self::EnumCloser)> {
+        let hint = match hint {
+            DecodeVariantHint::UnitVariant | DecodeVariantHint::Ignore => {
+                return Ok((SimpleDecoderView::Primitive(Primitive::Unit), ()));
+            }
+            DecodeVariantHint::TupleVariant { len } => DecodeHint::Tuple { len },
+            DecodeVariantHint::StructVariant { fields } => DecodeHint::Struct {
+                name: "<enum>",
+                fields,
+            },
+        };
+        Ok((self.decode_field(fields, hint, &[])?, ()))
+    }
+
+    fn decode_enum_end(&mut self, _e: Self::EnumCloser) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn decode_some_inner(
+        &mut self,
+        e: Self::SomeDecoder,
+    ) -> anyhow::Result<(Self::AnyDecoder, Self::SomeCloser)> {
+        Ok((ProtobufAnyDecoder::Field(e), ()))
+    }
+
+    fn decode_some_end(&mut self, _p: Self::SomeCloser) -> anyhow::Result<()> {
+        Ok(())
+    }
+}
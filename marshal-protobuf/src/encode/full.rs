@@ -0,0 +1,54 @@
+use marshal::context::Context;
+use marshal::ser::Serialize;
+use marshal_core::derive_encoder_for_newtype;
+use marshal_core::encode::poison::PoisonEncoder;
+use marshal_core::encode::{AnySpecEncoder, Encoder};
+
+use crate::encode::{ProtobufAnySpecEncoder, SimpleProtobufSpecEncoder};
+use crate::SignedEncoding;
+
+pub struct ProtobufSpecEncoder(PoisonEncoder<SimpleProtobufSpecEncoder>);
+
+derive_encoder_for_newtype!(ProtobufSpecEncoder(PoisonEncoder<SimpleProtobufSpecEncoder>));
+
+/// Writes a struct as a protobuf message. Fields are numbered by `#[marshal(tag = N)]`, or from 1
+/// in declaration order, and enum variants are numbered from 1. Signed integers use zigzag
+/// encoding, as in `sint32` and `sint64`, unless changed with
+/// [ProtobufEncoderBuilder::set_signed_encoding].
+pub struct ProtobufEncoderBuilder {
+    inner: ProtobufSpecEncoder,
+}
+
+impl ProtobufEncoderBuilder {
+    pub fn new() -> Self {
+        ProtobufEncoderBuilder {
+            inner: ProtobufSpecEncoder(PoisonEncoder::new(SimpleProtobufSpecEncoder::new())),
+        }
+    }
+    /// Selects between `sint*` and `int*` encodings for all signed integers.
+    pub fn set_signed_encoding(&mut self, signed: SignedEncoding) -> &mut Self {
+        self.inner.0.inner_mut().set_signed_encoding(signed);
+        self
+    }
+    pub fn build(&mut self) -> AnySpecEncoder<'_, ProtobufSpecEncoder> {
+        let any = self.inner.0.start(ProtobufAnySpecEncoder::Document);
+        AnySpecEncoder::new(&mut self.inner, any)
+    }
+    pub fn end(self) -> anyhow::Result<Vec<u8>> {
+        self.inner.0.end()?.end()
+    }
+    pub fn serialize<T: ?Sized + Serialize<ProtobufEncoder>>(
+        mut self,
+        value: &T,
+        ctx: Context,
+    ) -> anyhow::Result<Vec<u8>> {
+        value.serialize(self.build(), ctx)?;
+        self.end()
+    }
+}
+
+pub struct ProtobufEncoder;
+
+impl Encoder for ProtobufEncoder {
+    type SpecEncoder<'en> = ProtobufSpecEncoder;
+}
@@ -0,0 +1,514 @@
+use std::fmt::{Display, Formatter};
+
+use marshal_core::encode::SpecEncoder;
+use marshal_core::Primitive;
+
+use crate::varint::{write_varint, zigzag_encode};
+use crate::{SignedEncoding, WIRE_I32, WIRE_I64, WIRE_LEN, WIRE_VARINT};
+
+pub mod full;
+
+#[derive(Debug)]
+pub enum ProtobufEncoderError {
+    NotAMessage,
+    NestedRepeated,
+    NoneInRepeated,
+    UndeclaredField(&'static str),
+}
+
+impl Display for ProtobufEncoderError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProtobufEncoderError::NotAMessage => {
+                write!(
+                    f,
+                    "a protobuf document must be a struct, tuple or enum with data"
+                )
+            }
+            ProtobufEncoderError::NestedRepeated => {
+                write!(f, "a repeated field cannot hold sequences or maps")
+            }
+            ProtobufEncoderError::NoneInRepeated => {
+                write!(f, "a repeated field cannot hold None")
+            }
+            ProtobufEncoderError::UndeclaredField(x) => {
+                write!(f, "field {:?} is not declared or has no field number", x)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ProtobufEncoderError {}
+
+#[derive(Copy, Clone)]
+pub enum ProtobufAnySpecEncoder {
+    /// The top-level message, which has no key or length.
+    Document,
+    /// A field of a message with a field number.
+    Field(u32),
+    /// An element of a repeated field. Numbers are packed.
+    Element(u32),
+}
+
+/// A message being written, which is closed by writing it as a field of its parent.
+pub struct ProtobufMessageEncoder {
+    /// The field number, or `None` for the top-level message.
+    tag: Option<u32>,
+}
+
+pub struct ProtobufStructEncoder {
+    message: ProtobufMessageEncoder,
+    fields: &'static [&'static str],
+    tags: &'static [u32],
+}
+
+pub struct ProtobufTupleEncoder {
+    message: ProtobufMessageEncoder,
+    next: u32,
+}
+
+pub struct ProtobufTupleVariantEncoder {
+    outer: ProtobufMessageEncoder,
+    inner: ProtobufTupleEncoder,
+}
+
+pub struct ProtobufStructVariantEncoder {
+    outer: ProtobufMessageEncoder,
+    inner: ProtobufStructEncoder,
+}
+
+pub struct SimpleProtobufSpecEncoder {
+    output: Vec<u8>,
+    /// The contents of each open message.
+    messages: Vec<Vec<u8>>,
+    /// The packed numbers of each open repeated field.
+    packed: Vec<Vec<u8>>,
+    signed: SignedEncoding,
+}
+
+fn write_key(output: &mut Vec<u8>, tag: u32, wire: u8) {
+    write_varint(output, ((tag as u64) << 3) | wire as u64);
+}
+
+/// Splits a primitive into its wire type and encoded value.
+fn encode_primitive(prim: Primitive, signed: SignedEncoding) -> (u8, Vec<u8>) {
+    let mut value = vec![];
+    let wire = match prim {
+        Primitive::Unit => WIRE_LEN,
+        Primitive::Bool(x) => {
+            value.push(x as u8);
+            WIRE_VARINT
+        }
+        Primitive::I8(x) => return encode_signed(x as i64, signed),
+        Primitive::I16(x) => return encode_signed(x as i64, signed),
+        Primitive::I32(x) => return encode_signed(x as i64, signed),
+        Primitive::I64(x) => return encode_signed(x, signed),
+        Primitive::U8(x) => return encode_unsigned(x as u64),
+        Primitive::U16(x) => return encode_unsigned(x as u64),
+        Primitive::U32(x) => return encode_unsigned(x as u64),
+        Primitive::U64(x) => return encode_unsigned(x),
+        Primitive::Char(x) => return encode_unsigned(x as u64),
+        Primitive::I128(x) => {
+            value.extend_from_slice(&x.to_le_bytes());
+            WIRE_LEN
+        }
+        Primitive::U128(x) => {
+            value.extend_from_slice(&x.to_le_bytes());
+            WIRE_LEN
+        }
+        Primitive::F32(x) => {
+            value.extend_from_slice(&x.to_le_bytes());
+            WIRE_I32
+        }
+        Primitive::F64(x) => {
+            value.extend_from_slice(&x.to_le_bytes());
+            WIRE_I64
+        }
+    };
+    (wire, value)
+}
+
+fn encode_unsigned(x: u64) -> (u8, Vec<u8>) {
+    let mut value = vec![];
+    write_varint(&mut value, x);
+    (WIRE_VARINT, value)
+}
+
+fn encode_signed(x: i64, signed: SignedEncoding) -> (u8, Vec<u8>) {
+    match signed {
+        SignedEncoding::ZigZag => encode_unsigned(zigzag_encode(x)),
+        SignedEncoding::TwosComplement => encode_unsigned(x as u64),
+    }
+}
+
+impl SimpleProtobufSpecEncoder {
+    pub fn new() -> Self {
+        SimpleProtobufSpecEncoder {
+            output: vec![],
+            messages: vec![],
+            packed: vec![],
+            signed: SignedEncoding::default(),
+        }
+    }
+    pub fn set_signed_encoding(&mut self, signed: SignedEncoding) {
+        self.signed = signed;
+    }
+    pub fn end(self) -> anyhow::Result<Vec<u8>> {
+        Ok(self.output)
+    }
+    fn message(&mut self) -> &mut Vec<u8> {
+        self.messages.last_mut().unwrap()
+    }
+    fn write_record(&mut self, tag: u32, wire: u8, value: &[u8]) {
+        let message = self.message();
+        write_key(message, tag, wire);
+        if wire == WIRE_LEN {
+            write_varint(message, value.len() as u64);
+        }
+        message.extend_from_slice(value);
+    }
+    fn write_value(
+        &mut self,
+        any: ProtobufAnySpecEncoder,
+        wire: u8,
+        value: &[u8],
+    ) -> anyhow::Result<()> {
+        match any {
+            ProtobufAnySpecEncoder::Document => {
+                if wire != WIRE_LEN || !value.is_empty() {
+                    return Err(ProtobufEncoderError::NotAMessage.into());
+                }
+            }
+            ProtobufAnySpecEncoder::Element(_) if wire != WIRE_LEN => {
+                self.packed.last_mut().unwrap().extend_from_slice(value);
+            }
+            ProtobufAnySpecEncoder::Field(tag) | ProtobufAnySpecEncoder::Element(tag) => {
+                self.write_record(tag, wire, value);
+            }
+        }
+        Ok(())
+    }
+    fn open_message(&mut self, any: ProtobufAnySpecEncoder) -> ProtobufMessageEncoder {
+        self.messages.push(vec![]);
+        match any {
+            ProtobufAnySpecEncoder::Document => ProtobufMessageEncoder { tag: None },
+            ProtobufAnySpecEncoder::Field(tag) | ProtobufAnySpecEncoder::Element(tag) => {
+                ProtobufMessageEncoder { tag: Some(tag) }
+            }
+        }
+    }
+    fn close_message(&mut self, message: ProtobufMessageEncoder) {
+        let contents = self.messages.pop().unwrap();
+        match message.tag {
+            None => self.output = contents,
+            Some(tag) => self.write_record(tag, WIRE_LEN, &contents),
+        }
+    }
+    fn open_struct(
+        &mut self,
+        any: ProtobufAnySpecEncoder,
+        fields: &'static [&'static str],
+        tags: &'static [u32],
+    ) -> ProtobufStructEncoder {
+        ProtobufStructEncoder {
+            message: self.open_message(any),
+            fields,
+            tags,
+        }
+    }
+    fn open_tuple(&mut self, any: ProtobufAnySpecEncoder) -> ProtobufTupleEncoder {
+        ProtobufTupleEncoder {
+            message: self.open_message(any),
+            next: 1,
+        }
+    }
+    fn open_repeated(&mut self, any: ProtobufAnySpecEncoder) -> anyhow::Result<u32> {
+        match any {
+            ProtobufAnySpecEncoder::Document => Err(ProtobufEncoderError::NotAMessage.into()),
+            ProtobufAnySpecEncoder::Element(_) => Err(ProtobufEncoderError::NestedRepeated.into()),
+            ProtobufAnySpecEncoder::Field(tag) => Ok(tag),
+        }
+    }
+}
+
+/// The number of an enum variant, which is its index plus one. Unit variants are written as this
+/// number, and variants with data as a message with a field of this number.
+fn variant_tag(variant_index: usize) -> u32 {
+    variant_index as u32 + 1
+}
+
+impl SpecEncoder for SimpleProtobufSpecEncoder {
+    type AnySpecEncoder = ProtobufAnySpecEncoder;
+    type SomeCloser = ();
+    type TupleEncoder = ProtobufTupleEncoder;
+    type SeqEncoder = u32;
+    type MapEncoder = u32;
+    type ValueEncoder = ProtobufMessageEncoder;
+    type EntryCloser = ProtobufMessageEncoder;
+    type TupleStructEncoder = ProtobufTupleEncoder;
+    type StructEncoder = ProtobufStructEncoder;
+    type TupleVariantEncoder = ProtobufTupleVariantEncoder;
+    type StructVariantEncoder = ProtobufStructVariantEncoder;
+
+    fn encode_prim(&mut self, any: Self::AnySpecEncoder, prim: Primitive) -> anyhow::Result<()> {
+        let (wire, value) = encode_primitive(prim, self.signed);
+        self.write_value(any, wire, &value)
+    }
+
+    fn encode_str(&mut self, any: Self::AnySpecEncoder, s: &str) -> anyhow::Result<()> {
+        self.encode_bytes(any, s.as_bytes())
+    }
+
+    fn encode_bytes(&mut self, any: Self::AnySpecEncoder, s: &[u8]) -> anyhow::Result<()> {
+        if let ProtobufAnySpecEncoder::Document = any {
+            return Err(ProtobufEncoderError::NotAMessage.into());
+        }
+        self.write_value(any, WIRE_LEN, s)
+    }
+
+    fn encode_none(&mut self, any: Self::AnySpecEncoder) -> anyhow::Result<()> {
+        // An absent field is written as nothing at all.
+        match any {
+            ProtobufAnySpecEncoder::Document => Err(ProtobufEncoderError::NotAMessage.into()),
+            ProtobufAnySpecEncoder::Field(_) => Ok(()),
+            ProtobufAnySpecEncoder::Element(_) => Err(ProtobufEncoderError::NoneInRepeated.into()),
+        }
+    }
+
+    fn encode_some(
+        &mut self,
+        any: Self::AnySpecEncoder,
+    ) -> anyhow::Result<(Self::AnySpecEncoder, Self::SomeCloser)> {
+        Ok((any, ()))
+    }
+
+    fn encode_unit_struct(
+        &mut self,
+        any: Self::AnySpecEncoder,
+        _name: &'static str,
+    ) -> anyhow::Result<()> {
+        self.encode_prim(any, Primitive::Unit)
+    }
+
+    fn encode_tuple_struct(
+        &mut self,
+        any: Self::AnySpecEncoder,
+        _name: &'static str,
+        _len: usize,
+    ) -> anyhow::Result<Self::TupleStructEncoder> {
+        Ok(self.open_tuple(any))
+    }
+
+    fn encode_struct(
+        &mut self,
+        any: Self::AnySpecEncoder,
+        _name: &'static str,
+        fields: &'static [&'static str],
+    ) -> anyhow::Result<Self::StructEncoder> {
+        Ok(self.open_struct(any, fields, &[]))
+    }
+
+    fn encode_tagged_struct(
+        &mut self,
+        any: Self::AnySpecEncoder,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        tags: &'static [u32],
+    ) -> anyhow::Result<Self::StructEncoder> {
+        Ok(self.open_struct(any, fields, tags))
+    }
+
+    fn encode_unit_variant(
+        &mut self,
+        any: Self::AnySpecEncoder,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        variant_index: usize,
+    ) -> anyhow::Result<()> {
+        let (_, value) = encode_unsigned(variant_tag(variant_index) as u64);
+        match any {
+            ProtobufAnySpecEncoder::Document => Err(ProtobufEncoderError::NotAMessage.into()),
+            // Not packed, so that variants with data can be told apart by their wire type.
+            ProtobufAnySpecEncoder::Field(tag) | ProtobufAnySpecEncoder::Element(tag) => {
+                self.write_record(tag, WIRE_VARINT, &value);
+                Ok(())
+            }
+        }
+    }
+
+    fn encode_tuple_variant(
+        &mut self,
+        any: Self::AnySpecEncoder,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        variant_index: usize,
+        _len: usize,
+    ) -> anyhow::Result<Self::TupleVariantEncoder> {
+        let outer = self.open_message(any);
+        let inner = self.open_tuple(ProtobufAnySpecEncoder::Field(variant_tag(variant_index)));
+        Ok(ProtobufTupleVariantEncoder { outer, inner })
+    }
+
+    fn encode_struct_variant(
+        &mut self,
+        any: Self::AnySpecEncoder,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        variant_index: usize,
+        fields: &'static [&'static str],
+    ) -> anyhow::Result<Self::StructVariantEncoder> {
+        let outer = self.open_message(any);
+        let inner = self.open_struct(
+            ProtobufAnySpecEncoder::Field(variant_tag(variant_index)),
+            fields,
+            &[],
+        );
+        Ok(ProtobufStructVariantEncoder { outer, inner })
+    }
+
+    fn encode_seq(
+        &mut self,
+        any: Self::AnySpecEncoder,
+        _len: usize,
+    ) -> anyhow::Result<Self::SeqEncoder> {
+        let tag = self.open_repeated(any)?;
+        self.packed.push(vec![]);
+        Ok(tag)
+    }
+
+    fn encode_tuple(
+        &mut self,
+        any: Self::AnySpecEncoder,
+        _len: usize,
+    ) -> anyhow::Result<Self::TupleEncoder> {
+        Ok(self.open_tuple(any))
+    }
+
+    fn encode_map(
+        &mut self,
+        any: Self::AnySpecEncoder,
+        _len: usize,
+    ) -> anyhow::Result<Self::MapEncoder> {
+        self.open_repeated(any)
+    }
+
+    fn some_end(&mut self, _some: Self::SomeCloser) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn tuple_encode_element(
+        &mut self,
+        tuple: &mut Self::TupleEncoder,
+    ) -> anyhow::Result<Self::AnySpecEncoder> {
+        let tag = tuple.next;
+        tuple.next += 1;
+        Ok(ProtobufAnySpecEncoder::Field(tag))
+    }
+
+    fn tuple_end(&mut self, tuple: Self::TupleEncoder) -> anyhow::Result<()> {
+        self.close_message(tuple.message);
+        Ok(())
+    }
+
+    fn seq_encode_element(
+        &mut self,
+        tag: &mut Self::SeqEncoder,
+    ) -> anyhow::Result<Self::AnySpecEncoder> {
+        Ok(ProtobufAnySpecEncoder::Element(*tag))
+    }
+
+    fn seq_end(&mut self, tag: Self::SeqEncoder) -> anyhow::Result<()> {
+        let packed = self.packed.pop().unwrap();
+        if !packed.is_empty() {
+            self.write_record(tag, WIRE_LEN, &packed);
+        }
+        Ok(())
+    }
+
+    fn map_encode_element(
+        &mut self,
+        tag: &mut Self::MapEncoder,
+    ) -> anyhow::Result<(Self::AnySpecEncoder, Self::ValueEncoder)> {
+        // Each entry is a message with the key as field 1 and the value as field 2.
+        let entry = self.open_message(ProtobufAnySpecEncoder::Field(*tag));
+        Ok((ProtobufAnySpecEncoder::Field(1), entry))
+    }
+
+    fn map_end(&mut self, _tag: Self::MapEncoder) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn entry_encode_value(
+        &mut self,
+        entry: Self::ValueEncoder,
+    ) -> anyhow::Result<(Self::AnySpecEncoder, Self::EntryCloser)> {
+        Ok((ProtobufAnySpecEncoder::Field(2), entry))
+    }
+
+    fn entry_end(&mut self, entry: Self::EntryCloser) -> anyhow::Result<()> {
+        self.close_message(entry);
+        Ok(())
+    }
+
+    fn tuple_struct_encode_field(
+        &mut self,
+        tuple: &mut Self::TupleStructEncoder,
+    ) -> anyhow::Result<Self::AnySpecEncoder> {
+        self.tuple_encode_element(tuple)
+    }
+
+    fn tuple_struct_end(&mut self, tuple: Self::TupleStructEncoder) -> anyhow::Result<()> {
+        self.tuple_end(tuple)
+    }
+
+    fn struct_encode_field(
+        &mut self,
+        s: &mut Self::StructEncoder,
+        field: &'static str,
+    ) -> anyhow::Result<Self::AnySpecEncoder> {
+        let index = s.fields.iter().position(|x| *x == field);
+        let tag = match (index, s.tags) {
+            (Some(index), []) => Some(index as u32 + 1),
+            (Some(index), tags) => tags.get(index).copied(),
+            (None, _) => None,
+        };
+        let tag = tag.ok_or(ProtobufEncoderError::UndeclaredField(field))?;
+        Ok(ProtobufAnySpecEncoder::Field(tag))
+    }
+
+    fn struct_end(&mut self, s: Self::StructEncoder) -> anyhow::Result<()> {
+        self.close_message(s.message);
+        Ok(())
+    }
+
+    fn tuple_variant_encode_field(
+        &mut self,
+        v: &mut Self::TupleVariantEncoder,
+    ) -> anyhow::Result<Self::AnySpecEncoder> {
+        self.tuple_encode_element(&mut v.inner)
+    }
+
+    fn tuple_variant_end(&mut self, v: Self::TupleVariantEncoder) -> anyhow::Result<()> {
+        self.tuple_end(v.inner)?;
+        self.close_message(v.outer);
+        Ok(())
+    }
+
+    fn struct_variant_encode_field(
+        &mut self,
+        v: &mut Self::StructVariantEncoder,
+        key: &'static str,
+    ) -> anyhow::Result<Self::AnySpecEncoder> {
+        self.struct_encode_field(&mut v.inner, key)
+    }
+
+    fn struct_variant_end(&mut self, v: Self::StructVariantEncoder) -> anyhow::Result<()> {
+        self.struct_end(v.inner)?;
+        self.close_message(v.outer);
+        Ok(())
+    }
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+}
@@ -0,0 +1,35 @@
+#![deny(unused_must_use)]
+#![feature(never_type)]
+#![feature(trait_alias)]
+
+use marshal::de::Deserialize;
+use marshal::ser::Serialize;
+
+use crate::decode::full::ProtobufDecoder;
+use crate::encode::full::ProtobufEncoder;
+
+pub mod decode;
+pub mod encode;
+#[cfg(test)]
+mod test;
+mod varint;
+
+const WIRE_VARINT: u8 = 0;
+const WIRE_I64: u8 = 1;
+const WIRE_LEN: u8 = 2;
+const WIRE_SGROUP: u8 = 3;
+const WIRE_EGROUP: u8 = 4;
+const WIRE_I32: u8 = 5;
+
+/// How signed integers are written as varints. Both sides must agree, as the wire type is the same.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum SignedEncoding {
+    /// Zigzag encoding, as for `sint32` and `sint64` fields.
+    #[default]
+    ZigZag,
+    /// Two's complement, as for `int32` and `int64` fields. Negative numbers take ten bytes.
+    TwosComplement,
+}
+
+pub trait SerializeProtobuf = Serialize<ProtobufEncoder>;
+pub trait DeserializeProtobuf = Deserialize<ProtobufDecoder>;
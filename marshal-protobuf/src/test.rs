@@ -0,0 +1,347 @@
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+
+use marshal::context::OwnedContext;
+use marshal_core::encode::SpecEncoder;
+use marshal_derive::{Deserialize, Serialize};
+
+use crate::decode::full::ProtobufDecoderBuilder;
+use crate::encode::full::ProtobufEncoderBuilder;
+use crate::encode::{ProtobufAnySpecEncoder, SimpleProtobufSpecEncoder};
+use crate::{DeserializeProtobuf, SerializeProtobuf, SignedEncoding};
+
+fn hex(input: &str) -> Vec<u8> {
+    let input = input.replace(' ', "");
+    (0..input.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&input[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+fn encode<T: ?Sized + SerializeProtobuf>(value: &T) -> anyhow::Result<Vec<u8>> {
+    let mut c = OwnedContext::new();
+    ProtobufEncoderBuilder::new().serialize(value, c.borrow())
+}
+
+fn decode<T: DeserializeProtobuf>(input: &[u8]) -> anyhow::Result<T> {
+    let mut c = OwnedContext::new();
+    ProtobufDecoderBuilder::new(input).deserialize(c.borrow())
+}
+
+#[track_caller]
+fn test_round_trip<T: Debug + PartialEq + SerializeProtobuf + DeserializeProtobuf>(
+    input: T,
+    expected: &str,
+) -> anyhow::Result<()> {
+    let encoded = encode(&input)?;
+    assert_eq!(encoded, hex(expected), "{:?}", input);
+    assert_eq!(decode::<T>(&encoded)?, input);
+    Ok(())
+}
+
+#[track_caller]
+fn test_error<T: Debug + DeserializeProtobuf>(input: &str, expected: &str) {
+    let error = decode::<T>(&hex(input)).unwrap_err();
+    assert_eq!(format!("{:#}", error), expected);
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Default)]
+struct Test {
+    #[marshal(tag = 1)]
+    a: u32,
+    #[marshal(tag = 2)]
+    b: String,
+    #[marshal(tag = 4)]
+    d: Vec<u32>,
+}
+
+#[test]
+fn test_spec_examples() -> anyhow::Result<()> {
+    // Examples from the protobuf encoding guide.
+    test_round_trip(
+        Test {
+            a: 150,
+            ..Test::default()
+        },
+        "08 96 01 12 00",
+    )?;
+    test_round_trip(
+        Test {
+            b: "testing".to_string(),
+            ..Test::default()
+        },
+        "08 00 12 07 74 65 73 74 69 6e 67",
+    )?;
+    test_round_trip(
+        Test {
+            d: vec![3, 270, 86942],
+            ..Test::default()
+        },
+        "08 00 12 00 22 06 03 8e 02 9e a7 05",
+    )?;
+    // Unpacked elements, fields in any order and later scalars replacing earlier ones.
+    assert_eq!(
+        decode::<Test>(&hex("20 03 08 01 20 8e 02 08 96 01 22 03 9e a7 05"))?,
+        Test {
+            a: 150,
+            b: "".to_string(),
+            d: vec![3, 270, 86942],
+        }
+    );
+    Ok(())
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Scalars {
+    signed: i32,
+    long: i64,
+    flag: bool,
+    letter: char,
+    single: f32,
+    double: f64,
+    big: u128,
+    negative: i128,
+    unit: (),
+}
+
+#[test]
+fn test_scalars() -> anyhow::Result<()> {
+    test_round_trip(
+        Scalars {
+            signed: -1,
+            long: i64::MIN,
+            flag: true,
+            letter: 'é',
+            single: 1.5,
+            double: -0.25,
+            big: u128::MAX,
+            negative: -2,
+            unit: (),
+        },
+        "08 01 10 ff ff ff ff ff ff ff ff ff 01 18 01 20 e9 01 2d 00 00 c0 3f \
+         31 00 00 00 00 00 00 d0 bf \
+         3a 10 ff ff ff ff ff ff ff ff ff ff ff ff ff ff ff ff \
+         42 10 fe ff ff ff ff ff ff ff ff ff ff ff ff ff ff ff 4a 00",
+    )
+}
+
+#[test]
+fn test_signed_encoding() -> anyhow::Result<()> {
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Signed {
+        a: i32,
+        b: i64,
+        c: Vec<i8>,
+    }
+    let value = Signed {
+        a: 150,
+        b: -1,
+        c: vec![-2, 3],
+    };
+    assert_eq!(encode(&value)?, hex("08 ac 02 10 01 1a 02 03 06"));
+    let mut c = OwnedContext::new();
+    let mut builder = ProtobufEncoderBuilder::new();
+    builder.set_signed_encoding(SignedEncoding::TwosComplement);
+    let encoded = builder.serialize(&value, c.borrow())?;
+    assert_eq!(
+        encoded,
+        hex("08 96 01 10 ff ff ff ff ff ff ff ff ff 01 \
+             1a 0b fe ff ff ff ff ff ff ff ff 01 03")
+    );
+    let mut builder = ProtobufDecoderBuilder::new(&encoded);
+    builder.set_signed_encoding(SignedEncoding::TwosComplement);
+    assert_eq!(builder.deserialize::<Signed>(c.borrow())?, value);
+    Ok(())
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+enum Shape {
+    Empty,
+    Circle(f64),
+    Rect { width: u32, height: u32 },
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Inner {
+    name: String,
+    scores: Vec<i32>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Outer {
+    #[marshal(tag = 3)]
+    inner: Inner,
+    #[marshal(tag = 1)]
+    items: Vec<Inner>,
+    #[marshal(tag = 7)]
+    label: Option<String>,
+    #[marshal(tag = 8)]
+    missing: Option<u32>,
+    #[marshal(tag = 2)]
+    table: BTreeMap<String, u32>,
+    #[marshal(tag = 10)]
+    pair: (u8, String),
+    #[marshal(tag = 11)]
+    shapes: Vec<Shape>,
+}
+
+#[test]
+fn test_nested() -> anyhow::Result<()> {
+    test_round_trip(
+        Outer {
+            inner: Inner {
+                name: "a".to_string(),
+                scores: vec![-1, 1],
+            },
+            items: vec![
+                Inner {
+                    name: "b".to_string(),
+                    scores: vec![],
+                },
+                Inner {
+                    name: "".to_string(),
+                    scores: vec![2],
+                },
+            ],
+            label: Some("x".to_string()),
+            missing: None,
+            table: BTreeMap::from([("k".to_string(), 5)]),
+            pair: (9, "y".to_string()),
+            shapes: vec![
+                Shape::Empty,
+                Shape::Circle(0.5),
+                Shape::Rect {
+                    width: 2,
+                    height: 3,
+                },
+            ],
+        },
+        "1a 07 0a 01 61 12 02 01 02 \
+         0a 03 0a 01 62 0a 05 0a 00 12 01 04 \
+         3a 01 78 \
+         12 05 0a 01 6b 10 05 \
+         52 05 08 09 12 01 79 \
+         58 01 5a 0b 12 09 09 00 00 00 00 00 00 e0 3f 5a 06 1a 04 08 02 10 03",
+    )
+}
+
+#[test]
+fn test_variants() -> anyhow::Result<()> {
+    // Every kind of variant is numbered by its index plus one.
+    test_round_trip((Shape::Empty,), "08 01")?;
+    test_round_trip(
+        (Shape::Circle(0.5),),
+        "0a 0b 12 09 09 00 00 00 00 00 00 e0 3f",
+    )?;
+    test_round_trip(
+        (Shape::Rect {
+            width: 2,
+            height: 3,
+        },),
+        "0a 06 1a 04 08 02 10 03",
+    )?;
+    assert_eq!(decode::<(Shape,)>(&[])?, (Shape::Empty,));
+    test_error::<(Shape,)>("08 00", "at byte 0: invalid variant number 0");
+    Ok(())
+}
+
+#[test]
+fn test_defaults() -> anyhow::Result<()> {
+    // Missing fields decode as their protobuf defaults.
+    assert_eq!(
+        decode::<Outer>(&[])?,
+        Outer {
+            inner: Inner {
+                name: "".to_string(),
+                scores: vec![],
+            },
+            items: vec![],
+            label: None,
+            missing: None,
+            table: BTreeMap::new(),
+            pair: (0, "".to_string()),
+            shapes: vec![],
+        }
+    );
+    Ok(())
+}
+
+#[test]
+fn test_unknown_fields() -> anyhow::Result<()> {
+    // Fields 5 to 9 are unknown, with every wire type including a group.
+    assert_eq!(
+        decode::<Test>(&hex(
+            "28 7f 31 0102030405060708 08 2a 3a 02 ffff 45 01020304 4b 08 01 4c 12 01 7a"
+        ))?,
+        Test {
+            a: 42,
+            b: "z".to_string(),
+            d: vec![],
+        }
+    );
+    Ok(())
+}
+
+#[test]
+fn test_errors() {
+    let mut c = OwnedContext::new();
+    let error = ProtobufEncoderBuilder::new()
+        .serialize(&vec![vec![1u8]], c.borrow())
+        .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "a protobuf document must be a struct, tuple or enum with data"
+    );
+    let error = ProtobufEncoderBuilder::new()
+        .serialize(&(vec![vec![1u32]],), c.borrow())
+        .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "a repeated field cannot hold sequences or maps"
+    );
+    test_error::<Test>("08 01 12 05 61", "at byte 2: unexpected end of input");
+    test_error::<Test>(
+        "08 01 15 00 00 00 00",
+        "at byte 2: expected wire type 2, found wire type 5",
+    );
+    test_error::<Test>("0f", "at byte 0: unknown wire type 7");
+    test_error::<Test>("00 01", "at byte 0: invalid field number 0");
+    test_error::<Test>("24", "at byte 0: end of group without a matching start");
+
+    let mut encoder = SimpleProtobufSpecEncoder::new();
+    let mut message = encoder
+        .encode_tagged_struct(ProtobufAnySpecEncoder::Document, "Test", &["a", "b"], &[3])
+        .unwrap();
+    assert!(encoder.struct_encode_field(&mut message, "a").is_ok());
+    for field in ["b", "c"] {
+        assert_eq!(
+            encoder
+                .struct_encode_field(&mut message, field)
+                .err()
+                .unwrap()
+                .to_string(),
+            format!("field {:?} is not declared or has no field number", field)
+        );
+    }
+}
+
+#[test]
+fn test_nested_groups() -> anyhow::Result<()> {
+    // Groups are skipped without recursion, so deep nesting is an error and not a stack overflow.
+    let mut input = vec![0x2b; 100_000];
+    assert_eq!(
+        format!("{:#}", decode::<Test>(&input).unwrap_err()),
+        "at byte 0: groups are nested more than 100 deep"
+    );
+    input.truncate(50);
+    input.extend(vec![0x2c; 50]);
+    input.extend(hex("08 07"));
+    assert_eq!(
+        decode::<Test>(&input)?,
+        Test {
+            a: 7,
+            ..Test::default()
+        }
+    );
+    Ok(())
+}
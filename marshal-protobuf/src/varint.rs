@@ -0,0 +1,37 @@
+use crate::decode::ProtobufDecoderError;
+
+pub fn write_varint(output: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        output.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    output.push(value as u8);
+}
+
+/// Reads a varint of at most ten bytes, as protobuf allows.
+pub fn read_varint(input: &mut &[u8]) -> Result<u64, ProtobufDecoderError> {
+    let mut value = 0u64;
+    for (index, byte) in input.iter().enumerate().take(10) {
+        value |= ((byte & 0x7f) as u64) << (7 * index);
+        if byte & 0x80 == 0 {
+            if index == 9 && *byte > 1 {
+                return Err(ProtobufDecoderError::VarintOverflow);
+            }
+            *input = &input[index + 1..];
+            return Ok(value);
+        }
+    }
+    if input.len() >= 10 {
+        Err(ProtobufDecoderError::VarintOverflow)
+    } else {
+        Err(ProtobufDecoderError::Eof)
+    }
+}
+
+pub fn zigzag_encode(value: i64) -> u64 {
+    ((value << 1) ^ (value >> 63)) as u64
+}
+
+pub fn zigzag_decode(value: u64) -> i64 {
+    (value >> 1) as i64 ^ -((value & 1) as i64)
+}
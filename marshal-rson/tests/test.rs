@@ -241,5 +241,9 @@ enum Enum::StructVariant2 {
         RenamedVariant::Variant,
         r#"enum RenamedVariant::<abc::<cd>::ef>"#,
     )?;
+    #[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+    #[marshal(rename = "abc::Renamed")]
+    struct RenamedStruct;
+    test_round_trip(RenamedStruct, r#"struct <abc::Renamed>"#)?;
     Ok(())
 }
@@ -100,7 +100,9 @@ impl RcState {
     }
 }
 
+/// Named [marshal::SHARED_STRUCT_NAME].
 #[derive(Deserialize)]
+#[marshal(rename = "marshal_shared::Shared")]
 struct Shared<X> {
     id: usize,
     inner: Option<X>,
@@ -96,7 +96,9 @@ impl<WeakAny: 'static + DerefRaw> SharedSerializeContext<WeakAny> {
     }
 }
 
+/// Named [marshal::SHARED_STRUCT_NAME].
 #[derive(Serialize)]
+#[marshal(rename = "marshal_shared::Shared")]
 struct Shared<'a, T> {
     id: usize,
     inner: Option<&'a T>,
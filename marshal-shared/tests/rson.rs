@@ -0,0 +1,62 @@
+use std::any::Any;
+
+use pretty_assertions::assert_eq;
+
+use marshal::context::OwnedContext;
+use marshal::{Deserialize, Serialize};
+use marshal_pointer::raw_any::DerefRaw;
+use marshal_pointer::{Rcf, RcfWeak};
+use marshal_rson::decode::full::RsonDecoderBuilder;
+use marshal_rson::encode::full::RsonEncoderBuilder;
+use marshal_shared::de::SharedRcDeserializeContext;
+use marshal_shared::ser::SharedSerializeContext;
+use marshal_shared::{
+    derive_deserialize_rc_shared, derive_deserialize_rc_weak_shared, derive_serialize_rc_shared,
+    derive_serialize_rc_weak_shared,
+};
+
+#[derive(Serialize, Deserialize)]
+struct Foo(u8);
+
+derive_deserialize_rc_shared!(Foo);
+derive_serialize_rc_shared!(Foo);
+derive_deserialize_rc_weak_shared!(Foo);
+derive_serialize_rc_weak_shared!(Foo);
+
+type Tuple = (Rcf<Foo>, Rcf<Foo>, RcfWeak<Foo>);
+
+#[test]
+fn test_rson() -> anyhow::Result<()> {
+    let rc = Rcf::new(Foo(4));
+    let list: Tuple = (rc.clone(), rc.clone(), Rcf::downgrade(&rc));
+    let mut ser_ctx = OwnedContext::new();
+    let mut shared_ser_ctx = SharedSerializeContext::<RcfWeak<dyn Any>>::default();
+    ser_ctx.insert_mut(&mut shared_ser_ctx);
+    let encoded = RsonEncoderBuilder::new().serialize(&list, ser_ctx.borrow())?;
+    assert_eq!(
+        encoded,
+        "(
+  struct <marshal_shared::Shared> {
+    id: u64 0,
+    inner: some struct Foo(u8 4),
+  },
+  struct <marshal_shared::Shared> {
+    id: u64 0,
+    inner: none,
+  },
+  u64 0,
+)"
+    );
+    // Formats such as YAML recognize the records by this name. The decoding side is checked by
+    // the YAML tests, whose aliases only resolve when the name matches.
+    assert!(encoded.contains(&format!("struct <{}>", marshal::SHARED_STRUCT_NAME)));
+
+    let mut de_ctx = OwnedContext::new();
+    let mut shared_de_ctx = SharedRcDeserializeContext::default();
+    de_ctx.insert_mut(&mut shared_de_ctx);
+    let decoded = RsonDecoderBuilder::new(&encoded).deserialize::<Tuple>(de_ctx.borrow())?;
+    assert_eq!(decoded.0 .0, 4);
+    assert_eq!(decoded.0.deref_raw(), decoded.1.deref_raw());
+    assert_eq!(decoded.2.upgrade().unwrap().deref_raw(), decoded.0.deref_raw());
+    Ok(())
+}
@@ -0,0 +1,93 @@
+use std::any::Any;
+
+use pretty_assertions::assert_eq;
+
+use marshal::context::OwnedContext;
+use marshal::{Deserialize, Serialize};
+use marshal_pointer::raw_any::DerefRaw;
+use marshal_pointer::{Arcf, ArcfWeak};
+use marshal_shared::de::SharedArcDeserializeContext;
+use marshal_shared::ser::SharedSerializeContext;
+use marshal_shared::{
+    derive_deserialize_arc_shared, derive_deserialize_arc_weak_shared, derive_serialize_arc_shared,
+    derive_serialize_arc_weak_shared,
+};
+use marshal_yaml::decode::full::YamlDecoderBuilder;
+use marshal_yaml::encode::full::YamlEncoderBuilder;
+
+#[derive(Serialize, Deserialize, Debug)]
+struct Service {
+    name: String,
+    port: u16,
+}
+
+derive_deserialize_arc_shared!(Service);
+derive_serialize_arc_shared!(Service);
+derive_deserialize_arc_weak_shared!(Service);
+derive_serialize_arc_weak_shared!(Service);
+
+type Services = Vec<(Arcf<Service>, ArcfWeak<Service>)>;
+
+fn decode(input: &str) -> anyhow::Result<Services> {
+    let mut de_ctx = OwnedContext::new();
+    let mut shared_de_ctx = SharedArcDeserializeContext::default();
+    de_ctx.insert_mut(&mut shared_de_ctx);
+    YamlDecoderBuilder::new(input).deserialize::<Services>(de_ctx.borrow())
+}
+
+#[test]
+fn test_anchors() -> anyhow::Result<()> {
+    let web = Arcf::new(Service {
+        name: "web".to_string(),
+        port: 80,
+    });
+    let db = Arcf::new(Service {
+        name: "db".to_string(),
+        port: 5432,
+    });
+    let list: Services = vec![
+        (web.clone(), Arcf::downgrade(&db)),
+        (db.clone(), Arcf::downgrade(&web)),
+        (web.clone(), Arcf::downgrade(&web)),
+    ];
+    let mut ser_ctx = OwnedContext::new();
+    let mut shared_ser_ctx = SharedSerializeContext::<ArcfWeak<dyn Sync + Send + Any>>::default();
+    ser_ctx.insert_mut(&mut shared_ser_ctx);
+    let encoded = YamlEncoderBuilder::new().serialize(&list, ser_ctx.borrow())?;
+    assert_eq!(
+        encoded,
+        "- - &0
+    name: web
+    port: 80
+  - 1
+- - &1
+    name: db
+    port: 5432
+  - 0
+- - *0
+  - 0
+"
+    );
+    let decoded = decode(&encoded)?;
+    assert_eq!(decoded[0].0.name, "web");
+    assert_eq!(decoded[1].0.port, 5432);
+    assert_eq!(decoded[0].0.deref_raw(), decoded[2].0.deref_raw());
+    assert_eq!(
+        decoded[0].1.upgrade().unwrap().deref_raw(),
+        decoded[1].0.deref_raw()
+    );
+
+    // Anchors with names also share, while weak pointers refer to numbered anchors.
+    let decoded = decode(
+        "- [&web {name: web, port: 80}, 0]
+- [*web, 0]
+- [&0 {name: db, port: 5432}, 0]
+",
+    )?;
+    assert_eq!(decoded[0].0.deref_raw(), decoded[1].0.deref_raw());
+    assert_eq!(
+        decoded[0].1.upgrade().unwrap().deref_raw(),
+        decoded[2].0.deref_raw()
+    );
+    Ok(())
+}
@@ -0,0 +1,231 @@
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use marshal::context::Context;
+use marshal::de::Deserialize;
+use marshal::decode::{AnyDecoder, DecodeHint, Decoder};
+use marshal::encode::{AnyEncoder, Encoder};
+use marshal::ser::Serialize;
+
+use crate::decode::TomlDecoderError;
+use crate::encode::full::TomlEncoder;
+
+/// A TOML offset datetime, local datetime, local date or local time.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct TomlDatetime {
+    pub date: Option<TomlDate>,
+    pub time: Option<TomlTime>,
+    /// Only present when both the date and the time are.
+    pub offset: Option<TomlOffset>,
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct TomlDate {
+    pub year: u16,
+    pub month: u8,
+    pub day: u8,
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct TomlTime {
+    pub hour: u8,
+    pub minute: u8,
+    pub second: u8,
+    pub nanosecond: u32,
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum TomlOffset {
+    Z,
+    Custom { minutes: i16 },
+}
+
+struct DatetimeParser<'a> {
+    input: &'a [u8],
+}
+
+impl<'a> DatetimeParser<'a> {
+    fn digits(&mut self, count: usize) -> Result<u32, TomlDecoderError> {
+        let digits = self
+            .input
+            .take(..count)
+            .ok_or(TomlDecoderError::BadDatetime)?;
+        digits.iter().try_fold(0, |value, digit| match digit {
+            b'0'..=b'9' => Ok(value * 10 + (digit - b'0') as u32),
+            _ => Err(TomlDecoderError::BadDatetime),
+        })
+    }
+    fn eat(&mut self, expected: &[u8]) -> bool {
+        match self.input.first() {
+            Some(c) if expected.contains(c) => {
+                self.input = &self.input[1..];
+                true
+            }
+            _ => false,
+        }
+    }
+    fn expect(&mut self, expected: u8) -> Result<(), TomlDecoderError> {
+        if self.eat(&[expected]) {
+            Ok(())
+        } else {
+            Err(TomlDecoderError::BadDatetime)
+        }
+    }
+    fn date(&mut self) -> Result<TomlDate, TomlDecoderError> {
+        let year = self.digits(4)? as u16;
+        self.expect(b'-')?;
+        let month = self.digits(2)? as u8;
+        self.expect(b'-')?;
+        let day = self.digits(2)? as u8;
+        let leap =
+            year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400));
+        let days = match month {
+            2 if leap => 29,
+            2 => 28,
+            4 | 6 | 9 | 11 => 30,
+            1..=12 => 31,
+            _ => return Err(TomlDecoderError::BadDatetime),
+        };
+        if day == 0 || day > days {
+            return Err(TomlDecoderError::BadDatetime);
+        }
+        Ok(TomlDate { year, month, day })
+    }
+    fn time(&mut self) -> Result<TomlTime, TomlDecoderError> {
+        let hour = self.digits(2)? as u8;
+        self.expect(b':')?;
+        let minute = self.digits(2)? as u8;
+        self.expect(b':')?;
+        let second = self.digits(2)? as u8;
+        // A leap second is allowed, as in RFC 3339.
+        if hour > 23 || minute > 59 || second > 60 {
+            return Err(TomlDecoderError::BadDatetime);
+        }
+        let mut nanosecond = 0;
+        if self.eat(b".") {
+            let count = self.input.iter().take_while(|c| c.is_ascii_digit()).count();
+            if count == 0 {
+                return Err(TomlDecoderError::BadDatetime);
+            }
+            // Digits beyond nanoseconds are truncated.
+            let mut fraction = DatetimeParser {
+                input: &self.input[..count.min(9)],
+            };
+            nanosecond = fraction.digits(count.min(9))? * 10u32.pow(9 - count.min(9) as u32);
+            self.input = &self.input[count..];
+        }
+        Ok(TomlTime {
+            hour,
+            minute,
+            second,
+            nanosecond,
+        })
+    }
+    fn offset(&mut self) -> Result<Option<TomlOffset>, TomlDecoderError> {
+        if self.eat(b"Zz") {
+            return Ok(Some(TomlOffset::Z));
+        }
+        let sign = match self.input.first() {
+            Some(b'+') => 1,
+            Some(b'-') => -1,
+            _ => return Ok(None),
+        };
+        self.input = &self.input[1..];
+        let hours = self.digits(2)?;
+        self.expect(b':')?;
+        let minutes = self.digits(2)?;
+        if hours > 23 || minutes > 59 {
+            return Err(TomlDecoderError::BadDatetime);
+        }
+        Ok(Some(TomlOffset::Custom {
+            minutes: sign * (hours * 60 + minutes) as i16,
+        }))
+    }
+}
+
+impl FromStr for TomlDatetime {
+    type Err = TomlDecoderError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = DatetimeParser {
+            input: s.as_bytes(),
+        };
+        let mut result = TomlDatetime {
+            date: None,
+            time: None,
+            offset: None,
+        };
+        if s.as_bytes().get(4) == Some(&b'-') {
+            result.date = Some(parser.date()?);
+            if !parser.eat(b"Tt ") {
+                return if parser.input.is_empty() {
+                    Ok(result)
+                } else {
+                    Err(TomlDecoderError::BadDatetime)
+                };
+            }
+        }
+        result.time = Some(parser.time()?);
+        if result.date.is_some() {
+            result.offset = parser.offset()?;
+        }
+        if !parser.input.is_empty() {
+            return Err(TomlDecoderError::BadDatetime);
+        }
+        Ok(result)
+    }
+}
+
+impl Display for TomlDatetime {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if let Some(date) = &self.date {
+            write!(f, "{:04}-{:02}-{:02}", date.year, date.month, date.day)?;
+            if self.time.is_some() {
+                write!(f, "T")?;
+            }
+        }
+        if let Some(time) = &self.time {
+            write!(f, "{:02}:{:02}:{:02}", time.hour, time.minute, time.second)?;
+            if time.nanosecond != 0 {
+                let fraction = format!("{:09}", time.nanosecond);
+                write!(f, ".{}", fraction.trim_end_matches('0'))?;
+            }
+        }
+        match self.offset {
+            None => {}
+            Some(TomlOffset::Z) => write!(f, "Z")?,
+            Some(TomlOffset::Custom { minutes }) => {
+                let sign = if minutes < 0 { '-' } else { '+' };
+                let minutes = minutes.unsigned_abs();
+                write!(f, "{}{:02}:{:02}", sign, minutes / 60, minutes % 60)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<E: Encoder> Serialize<E> for TomlDatetime {
+    default fn serialize<'w, 'en>(
+        &self,
+        e: AnyEncoder<'w, 'en, E>,
+        _ctx: Context,
+    ) -> anyhow::Result<()> {
+        e.encode_str(&self.to_string())
+    }
+}
+
+impl Serialize<TomlEncoder> for TomlDatetime {
+    fn serialize<'w, 'en>(
+        &self,
+        e: AnyEncoder<'w, 'en, TomlEncoder>,
+        _ctx: Context,
+    ) -> anyhow::Result<()> {
+        let (encoder, any) = e.into_raw();
+        encoder.encode_datetime(any, self)
+    }
+}
+
+impl<D: Decoder> Deserialize<D> for TomlDatetime {
+    fn deserialize<'p, 'de>(d: AnyDecoder<'p, 'de, D>, _ctx: Context) -> anyhow::Result<Self> {
+        Ok(d.decode(DecodeHint::String)?.try_into_string()?.parse()?)
+    }
+}
@@ -0,0 +1,56 @@
+use marshal::context::Context;
+use marshal::de::Deserialize;
+use marshal_core::decode::depth_budget::{DepthBudgetDecoder, WithDepthBudget};
+use marshal_core::decode::poison::PoisonDecoder;
+use marshal_core::decode::text::DocumentAnyDecoder;
+use marshal_core::decode::{AnySpecDecoder, Decoder};
+use marshal_core::derive_decoder_for_newtype;
+
+use crate::decode::SimpleTomlSpecDecoder;
+
+pub struct TomlSpecDecoder<'de>(PoisonDecoder<DepthBudgetDecoder<SimpleTomlSpecDecoder<'de>>>);
+
+derive_decoder_for_newtype!(TomlSpecDecoder<'de>(PoisonDecoder<DepthBudgetDecoder<SimpleTomlSpecDecoder<'de>>>));
+
+pub struct TomlDecoderBuilder<'de> {
+    inner: TomlSpecDecoder<'de>,
+    depth_budget: usize,
+}
+
+impl<'de> TomlDecoderBuilder<'de> {
+    pub fn new(input: &'de str) -> Self {
+        TomlDecoderBuilder {
+            inner: TomlSpecDecoder(PoisonDecoder::new(DepthBudgetDecoder::new(
+                SimpleTomlSpecDecoder::new(input),
+            ))),
+            depth_budget: 100,
+        }
+    }
+    pub fn set_budget(&mut self, depth_budget: usize) -> &mut Self {
+        self.depth_budget = depth_budget;
+        self
+    }
+    pub fn build<'p>(&'p mut self) -> AnySpecDecoder<'p, 'de, TomlSpecDecoder<'de>> {
+        let any = WithDepthBudget::new(self.depth_budget, DocumentAnyDecoder::Document);
+        let any = self.inner.0.start(any);
+        AnySpecDecoder::new(&mut self.inner, any)
+    }
+    /// Deserializes the document, reporting the line and column of the value that failed.
+    pub fn deserialize<T: Deserialize<TomlDecoder>>(mut self, ctx: Context) -> anyhow::Result<T> {
+        let result = T::deserialize(self.build(), ctx).map_err(|e| e.context(self.location()))?;
+        self.end()?;
+        Ok(result)
+    }
+    pub fn location(&self) -> String {
+        self.inner.0.inner().inner().location()
+    }
+    pub fn end(self) -> anyhow::Result<()> {
+        Ok(self.inner.0.end()?.end()?.end()?)
+    }
+}
+
+pub struct TomlDecoder;
+
+impl Decoder for TomlDecoder {
+    type SpecDecoder<'de> = TomlSpecDecoder<'de>;
+}
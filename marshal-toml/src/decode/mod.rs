@@ -0,0 +1,285 @@
+use std::borrow::Cow;
+use std::fmt::{Display, Formatter};
+
+use marshal_core::decode::text::{location, DocumentAnyDecoder};
+use marshal_core::decode::{DecodeHint, DecodeVariantHint, SimpleDecoderView, SpecDecoder};
+use marshal_core::{Primitive, PrimitiveType};
+
+use crate::decode::parse::{TomlNode, TomlParser, TomlValue};
+
+pub mod full;
+mod parse;
+
+#[derive(Debug)]
+pub enum TomlDecoderError {
+    Eof,
+    UnexpectedCharacter(char),
+    ExpectedKey,
+    ControlCharacter,
+    BadEscape,
+    BadNumber,
+    IntegerOverflow,
+    BadDatetime,
+    DuplicateKey(String),
+    DuplicateTable(String),
+    NotATable(String),
+    TooDeep,
+    BadEnum,
+}
+
+impl Display for TomlDecoderError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TomlDecoderError::Eof => write!(f, "unexpected end of input"),
+            TomlDecoderError::UnexpectedCharacter(c) => write!(f, "unexpected character {:?}", c),
+            TomlDecoderError::ExpectedKey => write!(f, "expected a key"),
+            TomlDecoderError::ControlCharacter => write!(f, "control characters must be escaped"),
+            TomlDecoderError::BadEscape => write!(f, "invalid escape sequence"),
+            TomlDecoderError::BadNumber => write!(f, "invalid number"),
+            TomlDecoderError::IntegerOverflow => write!(f, "integer does not fit in 64 bits"),
+            TomlDecoderError::BadDatetime => write!(f, "invalid date or time"),
+            TomlDecoderError::DuplicateKey(key) => write!(f, "duplicate key {:?}", key),
+            TomlDecoderError::DuplicateTable(key) => write!(f, "table {:?} is defined twice", key),
+            TomlDecoderError::NotATable(key) => {
+                write!(f, "{:?} cannot be extended with a table header", key)
+            }
+            TomlDecoderError::TooDeep => {
+                write!(f, "tables and arrays are nested too deeply")
+            }
+            TomlDecoderError::BadEnum => {
+                write!(f, "enum must be a string or a table with one entry")
+            }
+        }
+    }
+}
+
+impl std::error::Error for TomlDecoderError {}
+
+pub struct SimpleTomlSpecDecoder<'de> {
+    input: &'de str,
+    /// The offset of the most recent value, for reporting errors.
+    offset: usize,
+}
+
+pub type TomlAnyDecoder = DocumentAnyDecoder<TomlNode>;
+
+pub enum TomlDiscriminantDecoder {
+    Unit(TomlNode),
+    Table(String, TomlNode),
+}
+
+impl<'de> SimpleTomlSpecDecoder<'de> {
+    pub fn new(input: &'de str) -> Self {
+        SimpleTomlSpecDecoder { input, offset: 0 }
+    }
+    pub fn end(self) -> anyhow::Result<()> {
+        Ok(())
+    }
+    pub fn location(&self) -> String {
+        location(self.input, self.offset)
+    }
+}
+
+fn integer_primitive(x: i64, hint: &DecodeHint) -> anyhow::Result<Primitive> {
+    Ok(match hint {
+        DecodeHint::Primitive(PrimitiveType::I8) => Primitive::I8(x.try_into()?),
+        DecodeHint::Primitive(PrimitiveType::I16) => Primitive::I16(x.try_into()?),
+        DecodeHint::Primitive(PrimitiveType::I32) => Primitive::I32(x.try_into()?),
+        DecodeHint::Primitive(PrimitiveType::I128) => Primitive::I128(x.into()),
+        DecodeHint::Primitive(PrimitiveType::U8) => Primitive::U8(x.try_into()?),
+        DecodeHint::Primitive(PrimitiveType::U16) => Primitive::U16(x.try_into()?),
+        DecodeHint::Primitive(PrimitiveType::U32) => Primitive::U32(x.try_into()?),
+        DecodeHint::Primitive(PrimitiveType::U64) => Primitive::U64(x.try_into()?),
+        DecodeHint::Primitive(PrimitiveType::U128) => Primitive::U128(x.try_into()?),
+        DecodeHint::Primitive(PrimitiveType::F32) => Primitive::F32(x as f32),
+        DecodeHint::Primitive(PrimitiveType::F64) => Primitive::F64(x as f64),
+        _ => Primitive::I64(x),
+    })
+}
+
+impl<'de> SpecDecoder<'de> for SimpleTomlSpecDecoder<'de> {
+    type AnyDecoder = TomlAnyDecoder;
+    type SeqDecoder = std::vec::IntoIter<TomlNode>;
+    type MapDecoder = std::vec::IntoIter<(String, TomlNode)>;
+    type KeyDecoder = (String, TomlNode);
+    type ValueDecoder = TomlNode;
+    type DiscriminantDecoder = TomlDiscriminantDecoder;
+    type VariantDecoder = Option<TomlNode>;
+    type EnumCloser = ();
+    type SomeDecoder = TomlNode;
+    type SomeCloser = ();
+
+    fn decode(
+        &mut self,
+        any: Self::AnyDecoder,
+        hint: DecodeHint,
+    ) -> anyhow::Result<SimpleDecoderView<'de, Self>> {
+        let node = match any {
+            DocumentAnyDecoder::Document => {
+                let mut parser = TomlParser::new(self.input);
+                match parser.read_document() {
+                    Ok(node) => node,
+                    Err(e) => {
+                        self.offset = parser.offset;
+                        return Err(e.into());
+                    }
+                }
+            }
+            DocumentAnyDecoder::Parsed(node) => node,
+        };
+        self.offset = node.offset;
+        // TOML has no null, so a present value is always `Some`.
+        if let DecodeHint::Option = hint {
+            return Ok(SimpleDecoderView::Some(node));
+        }
+        Ok(match (node.value, hint) {
+            (TomlValue::String(x), DecodeHint::Enum { .. }) => {
+                SimpleDecoderView::Enum(TomlDiscriminantDecoder::Unit(TomlNode {
+                    value: TomlValue::String(x),
+                    offset: node.offset,
+                }))
+            }
+            (TomlValue::Table(table), DecodeHint::Enum { .. }) => {
+                let mut entries = table.entries.into_iter();
+                match (entries.next(), entries.next()) {
+                    (Some((key, value)), None) => {
+                        SimpleDecoderView::Enum(TomlDiscriminantDecoder::Table(key, value))
+                    }
+                    _ => return Err(TomlDecoderError::BadEnum.into()),
+                }
+            }
+            (TomlValue::String(x), DecodeHint::Primitive(PrimitiveType::Char)) => {
+                let mut chars = x.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => SimpleDecoderView::Primitive(Primitive::Char(c)),
+                    _ => SimpleDecoderView::String(Cow::Owned(x)),
+                }
+            }
+            (TomlValue::String(x), _) => SimpleDecoderView::String(Cow::Owned(x)),
+            (TomlValue::Integer(x), hint) => {
+                SimpleDecoderView::Primitive(integer_primitive(x, &hint)?)
+            }
+            (TomlValue::Float(x), DecodeHint::Primitive(PrimitiveType::F32)) => {
+                SimpleDecoderView::Primitive(Primitive::F32(x as f32))
+            }
+            (TomlValue::Float(x), _) => SimpleDecoderView::Primitive(Primitive::F64(x)),
+            (TomlValue::Boolean(x), _) => SimpleDecoderView::Primitive(Primitive::Bool(x)),
+            (TomlValue::Datetime(x), _) => SimpleDecoderView::String(Cow::Owned(x.to_string())),
+            // TOML has no bytes, so they are encoded as an array of integers.
+            (TomlValue::Array(elements, _), DecodeHint::Bytes) => {
+                SimpleDecoderView::Bytes(Cow::Owned(
+                    elements
+                        .into_iter()
+                        .map(|element| match element.value {
+                            TomlValue::Integer(x) => Ok(u8::try_from(x)?),
+                            _ => Err(TomlDecoderError::BadNumber.into()),
+                        })
+                        .collect::<anyhow::Result<Vec<u8>>>()?,
+                ))
+            }
+            (TomlValue::Array(elements, _), _) => SimpleDecoderView::Seq(elements.into_iter()),
+            (
+                TomlValue::Table(table),
+                DecodeHint::Primitive(PrimitiveType::Unit) | DecodeHint::UnitStruct { .. },
+            ) if table.entries.is_empty() => SimpleDecoderView::Primitive(Primitive::Unit),
+            (TomlValue::Table(table), _) => SimpleDecoderView::Map(table.entries.into_iter()),
+        })
+    }
+
+    fn is_human_readable(&self) -> bool {
+        true
+    }
+
+    fn decode_seq_next(
+        &mut self,
+        seq: &mut Self::SeqDecoder,
+    ) -> anyhow::Result<Option<Self::AnyDecoder>> {
+        Ok(seq.next().map(DocumentAnyDecoder::Parsed))
+    }
+
+    fn decode_seq_exact_size(&self, seq: &Self::SeqDecoder) -> Option<usize> {
+        Some(seq.len())
+    }
+
+    fn decode_seq_end(&mut self, _seq: Self::SeqDecoder) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn decode_map_next(
+        &mut self,
+        map: &mut Self::MapDecoder,
+    ) -> anyhow::Result<Option<Self::KeyDecoder>> {
+        Ok(map.next())
+    }
+
+    fn decode_map_exact_size(&self, map: &Self::MapDecoder) -> Option<usize> {
+        Some(map.len())
+    }
+
+    fn decode_map_end(&mut self, _map: Self::MapDecoder) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn decode_entry_key(
+        &mut self,
+        (key, value): Self::KeyDecoder,
+    ) -> anyhow::Result<(Self::AnyDecoder, Self::ValueDecoder)> {
+        let key = TomlNode {
+            value: TomlValue::String(key),
+            offset: value.offset,
+        };
+        Ok((DocumentAnyDecoder::Parsed(key), value))
+    }
+
+    fn decode_entry_value(
+        &mut self,
+        value: Self::ValueDecoder,
+    ) -> anyhow::Result<Self::AnyDecoder> {
+        Ok(DocumentAnyDecoder::Parsed(value))
+    }
+
+    fn decode_enum_discriminant(
+        &mut self,
+        e: Self::DiscriminantDecoder,
+    ) -> anyhow::Result<(Self::AnyDecoder, Self::VariantDecoder)> {
+        match e {
+            TomlDiscriminantDecoder::Unit(node) => Ok((DocumentAnyDecoder::Parsed(node), None)),
+            TomlDiscriminantDecoder::Table(key, value) => {
+                let key = TomlNode {
+                    value: TomlValue::String(key),
+                    offset: value.offset,
+                };
+                Ok((DocumentAnyDecoder::Parsed(key), Some(value)))
+            }
+        }
+    }
+
+    fn decode_enum_variant(
+        &mut self,
+        e: Self::VariantDecoder,
+        hint: DecodeVariantHint,
+    ) -> anyhow::Result<(SimpleDecoderView<'de, Self>, Self::EnumCloser)> {
+        match e {
+            None => Ok((SimpleDecoderView::Primitive(Primitive::Unit), ())),
+            Some(node) => Ok((
+                self.decode(DocumentAnyDecoder::Parsed(node), hint.content_hint())?,
+                (),
+            )),
+        }
+    }
+
+    fn decode_enum_end(&mut self, _e: Self::EnumCloser) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn decode_some_inner(
+        &mut self,
+        e: Self::SomeDecoder,
+    ) -> anyhow::Result<(Self::AnyDecoder, Self::SomeCloser)> {
+        Ok((DocumentAnyDecoder::Parsed(e), ()))
+    }
+
+    fn decode_some_end(&mut self, _p: Self::SomeCloser) -> anyhow::Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,640 @@
+use std::collections::HashMap;
+
+use crate::datetime::TomlDatetime;
+use crate::decode::TomlDecoderError;
+
+/// Nesting of arrays, tables and key segments beyond this is rejected before it can overflow the
+/// stack.
+const MAX_NESTING: usize = 128;
+
+/// A parsed value and the byte offset where it starts.
+pub struct TomlNode {
+    pub(crate) value: TomlValue,
+    pub(crate) offset: usize,
+}
+
+pub(crate) enum TomlValue {
+    String(String),
+    Integer(i64),
+    Float(f64),
+    Boolean(bool),
+    Datetime(TomlDatetime),
+    /// An array and whether it was built from `[[header]]`s.
+    Array(Vec<TomlNode>, bool),
+    Table(TomlTable),
+}
+
+pub(crate) struct TomlTable {
+    pub(crate) entries: Vec<(String, TomlNode)>,
+    /// The position of each key within `entries`.
+    index: HashMap<String, usize>,
+    kind: TableKind,
+}
+
+/// How a table was created, which determines how it may be extended.
+#[derive(Copy, Clone, Eq, PartialEq)]
+enum TableKind {
+    /// Created as the prefix of a `[header]`, so it may still be defined by its own header.
+    Implicit,
+    Header,
+    Dotted,
+    Inline,
+}
+
+impl TomlTable {
+    fn new(kind: TableKind) -> Self {
+        TomlTable {
+            entries: vec![],
+            index: HashMap::new(),
+            kind,
+        }
+    }
+    fn get_mut(&mut self, key: &str) -> Option<&mut TomlNode> {
+        let index = *self.index.get(key)?;
+        Some(&mut self.entries[index].1)
+    }
+    fn insert(&mut self, key: &str, node: TomlNode) -> &mut TomlNode {
+        self.index.insert(key.to_string(), self.entries.len());
+        self.entries.push((key.to_string(), node));
+        &mut self.entries.last_mut().unwrap().1
+    }
+    /// Finds or creates the table named by a prefix of a `[header]` or `[[header]]`.
+    fn header_prefix(
+        &mut self,
+        key: &str,
+        offset: usize,
+    ) -> Result<&mut TomlTable, TomlDecoderError> {
+        if self.get_mut(key).is_none() {
+            self.insert(key, TomlNode::table(TableKind::Implicit, offset));
+        }
+        match &mut self.get_mut(key).unwrap().value {
+            TomlValue::Table(table) if table.kind != TableKind::Inline => Ok(table),
+            TomlValue::Array(elements, true) => match &mut elements.last_mut().unwrap().value {
+                TomlValue::Table(table) => Ok(table),
+                _ => unreachable!(),
+            },
+            _ => Err(TomlDecoderError::NotATable(key.to_string())),
+        }
+    }
+    /// Finds or creates the table named by a prefix of a dotted key.
+    fn dotted_prefix(
+        &mut self,
+        key: &str,
+        offset: usize,
+    ) -> Result<&mut TomlTable, TomlDecoderError> {
+        if self.get_mut(key).is_none() {
+            self.insert(key, TomlNode::table(TableKind::Dotted, offset));
+        }
+        match &mut self.get_mut(key).unwrap().value {
+            TomlValue::Table(table) if table.kind == TableKind::Dotted => Ok(table),
+            _ => Err(TomlDecoderError::DuplicateKey(key.to_string())),
+        }
+    }
+    /// Inserts a value under a dotted key into this table, which is nested `depth` levels deep.
+    fn insert_dotted(
+        &mut self,
+        depth: usize,
+        keys: &[(String, usize)],
+        value: TomlNode,
+    ) -> Result<(), TomlDecoderError> {
+        if depth + keys.len() > MAX_NESTING {
+            return Err(TomlDecoderError::TooDeep);
+        }
+        let ((last, _), prefix) = keys.split_last().unwrap();
+        let mut table = self;
+        for (key, offset) in prefix {
+            table = table.dotted_prefix(key, *offset)?;
+        }
+        if table.get_mut(last).is_some() {
+            return Err(TomlDecoderError::DuplicateKey(last.clone()));
+        }
+        table.insert(last, value);
+        Ok(())
+    }
+    /// Follows the keys of a `[header]` or `[[header]]`, creating missing tables.
+    fn header_path(
+        &mut self,
+        path: &[(String, usize)],
+    ) -> Result<&mut TomlTable, TomlDecoderError> {
+        if path.len() > MAX_NESTING {
+            return Err(TomlDecoderError::TooDeep);
+        }
+        let mut table = self;
+        for (key, offset) in path {
+            table = table.header_prefix(key, *offset)?;
+        }
+        Ok(table)
+    }
+    fn define_table(
+        &mut self,
+        keys: &[(String, usize)],
+        offset: usize,
+    ) -> Result<(), TomlDecoderError> {
+        let ((last, _), prefix) = keys.split_last().unwrap();
+        let table = self.header_path(prefix)?;
+        match table.get_mut(last) {
+            None => {
+                table.insert(last, TomlNode::table(TableKind::Header, offset));
+            }
+            Some(TomlNode {
+                value: TomlValue::Table(existing),
+                offset: existing_offset,
+            }) if existing.kind == TableKind::Implicit => {
+                existing.kind = TableKind::Header;
+                *existing_offset = offset;
+            }
+            Some(TomlNode {
+                value: TomlValue::Table(_),
+                ..
+            }) => return Err(TomlDecoderError::DuplicateTable(last.clone())),
+            Some(_) => return Err(TomlDecoderError::DuplicateKey(last.clone())),
+        }
+        Ok(())
+    }
+    fn define_array_table(
+        &mut self,
+        keys: &[(String, usize)],
+        offset: usize,
+    ) -> Result<(), TomlDecoderError> {
+        let ((last, _), prefix) = keys.split_last().unwrap();
+        let table = self.header_path(prefix)?;
+        let element = TomlNode::table(TableKind::Header, offset);
+        match table.get_mut(last) {
+            None => {
+                table.insert(
+                    last,
+                    TomlNode {
+                        value: TomlValue::Array(vec![element], true),
+                        offset,
+                    },
+                );
+            }
+            Some(TomlNode {
+                value: TomlValue::Array(elements, true),
+                ..
+            }) => elements.push(element),
+            Some(_) => return Err(TomlDecoderError::DuplicateTable(last.clone())),
+        }
+        Ok(())
+    }
+}
+
+impl TomlNode {
+    fn table(kind: TableKind, offset: usize) -> Self {
+        TomlNode {
+            value: TomlValue::Table(TomlTable::new(kind)),
+            offset,
+        }
+    }
+}
+
+pub(crate) struct TomlParser<'de> {
+    input: &'de str,
+    pub(crate) offset: usize,
+    nesting: usize,
+}
+
+fn is_bare_key(c: u8) -> bool {
+    c.is_ascii_alphanumeric() || c == b'_' || c == b'-'
+}
+
+fn is_value_char(c: u8) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, b'_' | b'+' | b'-' | b'.' | b':')
+}
+
+/// Checks that underscores in a run of digits are each surrounded by digits.
+fn check_digits(digits: &str, radix: u32) -> Result<(), TomlDecoderError> {
+    let valid = !digits.is_empty()
+        && !digits.starts_with('_')
+        && !digits.ends_with('_')
+        && !digits.contains("__")
+        && digits.chars().all(|c| c == '_' || c.is_digit(radix));
+    if valid {
+        Ok(())
+    } else {
+        Err(TomlDecoderError::BadNumber)
+    }
+}
+
+fn check_decimal_integer(digits: &str) -> Result<(), TomlDecoderError> {
+    check_digits(digits, 10)?;
+    if digits.len() > 1 && digits.starts_with('0') {
+        return Err(TomlDecoderError::BadNumber);
+    }
+    Ok(())
+}
+
+fn parse_number(token: &str) -> Result<TomlValue, TomlDecoderError> {
+    let unsigned = token.trim_start_matches(['+', '-']);
+    if token.len() - unsigned.len() > 1 {
+        return Err(TomlDecoderError::BadNumber);
+    }
+    match unsigned {
+        "inf" => {
+            return Ok(TomlValue::Float(if token.starts_with('-') {
+                f64::NEG_INFINITY
+            } else {
+                f64::INFINITY
+            }))
+        }
+        "nan" => return Ok(TomlValue::Float(f64::NAN)),
+        _ => {}
+    }
+    for (prefix, radix) in [("0x", 16), ("0o", 8), ("0b", 2)] {
+        if let Some(digits) = token.strip_prefix(prefix) {
+            check_digits(digits, radix)?;
+            let value = i64::from_str_radix(&digits.replace('_', ""), radix)
+                .map_err(|_| TomlDecoderError::IntegerOverflow)?;
+            return Ok(TomlValue::Integer(value));
+        }
+    }
+    let (mantissa, exponent) = match unsigned.split_once(['e', 'E']) {
+        Some((mantissa, exponent)) => (mantissa, Some(exponent)),
+        None => (unsigned, None),
+    };
+    let (integer, fraction) = match mantissa.split_once('.') {
+        Some((integer, fraction)) => (integer, Some(fraction)),
+        None => (mantissa, None),
+    };
+    check_decimal_integer(integer)?;
+    if let Some(fraction) = fraction {
+        check_digits(fraction, 10)?;
+    }
+    if let Some(exponent) = exponent {
+        check_digits(exponent.strip_prefix(['+', '-']).unwrap_or(exponent), 10)?;
+    }
+    let cleaned = token.replace('_', "");
+    if fraction.is_none() && exponent.is_none() {
+        Ok(TomlValue::Integer(
+            cleaned
+                .parse()
+                .map_err(|_| TomlDecoderError::IntegerOverflow)?,
+        ))
+    } else {
+        Ok(TomlValue::Float(
+            cleaned.parse().map_err(|_| TomlDecoderError::BadNumber)?,
+        ))
+    }
+}
+
+impl<'de> TomlParser<'de> {
+    pub fn new(input: &'de str) -> Self {
+        TomlParser {
+            input,
+            offset: 0,
+            nesting: 0,
+        }
+    }
+    fn rest(&self) -> &'de str {
+        &self.input[self.offset..]
+    }
+    fn peek(&self) -> Option<u8> {
+        self.input.as_bytes().get(self.offset).copied()
+    }
+    fn try_read(&mut self, token: &str) -> bool {
+        if self.rest().starts_with(token) {
+            self.offset += token.len();
+            true
+        } else {
+            false
+        }
+    }
+    fn read_exact(&mut self, token: &str) -> Result<(), TomlDecoderError> {
+        if self.try_read(token) {
+            Ok(())
+        } else {
+            Err(self.unexpected())
+        }
+    }
+    fn read_char(&mut self) -> Result<char, TomlDecoderError> {
+        let c = self.rest().chars().next().ok_or(TomlDecoderError::Eof)?;
+        self.offset += c.len_utf8();
+        Ok(c)
+    }
+    fn unexpected(&self) -> TomlDecoderError {
+        match self.rest().chars().next() {
+            None => TomlDecoderError::Eof,
+            Some(c) => TomlDecoderError::UnexpectedCharacter(c),
+        }
+    }
+    fn try_read_newline(&mut self) -> bool {
+        self.try_read("\n") || self.try_read("\r\n")
+    }
+    fn read_whitespace(&mut self) {
+        while let Some(b' ' | b'\t') = self.peek() {
+            self.offset += 1;
+        }
+    }
+    fn read_comment(&mut self) -> Result<(), TomlDecoderError> {
+        if self.try_read("#") {
+            while let Some(c) = self.peek() {
+                if c == b'\n' || c == b'\r' && self.rest().starts_with("\r\n") {
+                    break;
+                }
+                if let '\0'..='\x08' | '\x0a'..='\x1f' | '\x7f' = self.read_char()? {
+                    return Err(TomlDecoderError::ControlCharacter);
+                }
+            }
+        }
+        Ok(())
+    }
+    fn read_blank_lines(&mut self) -> Result<(), TomlDecoderError> {
+        loop {
+            self.read_whitespace();
+            self.read_comment()?;
+            if !self.try_read_newline() {
+                return Ok(());
+            }
+        }
+    }
+    fn read_line_end(&mut self) -> Result<(), TomlDecoderError> {
+        self.read_whitespace();
+        self.read_comment()?;
+        if self.peek().is_none() || self.try_read_newline() {
+            Ok(())
+        } else {
+            Err(self.unexpected())
+        }
+    }
+    fn read_key(&mut self) -> Result<Vec<(String, usize)>, TomlDecoderError> {
+        let mut keys = vec![];
+        loop {
+            self.read_whitespace();
+            let offset = self.offset;
+            let key = match self.peek() {
+                Some(b'"') => self.read_basic_string()?,
+                Some(b'\'') => self.read_literal_string()?,
+                _ => {
+                    let len = self.rest().bytes().take_while(|c| is_bare_key(*c)).count();
+                    if len == 0 {
+                        return Err(TomlDecoderError::ExpectedKey);
+                    }
+                    self.offset += len;
+                    self.input[offset..self.offset].to_string()
+                }
+            };
+            keys.push((key, offset));
+            self.read_whitespace();
+            if !self.try_read(".") {
+                return Ok(keys);
+            }
+        }
+    }
+    fn read_escape(&mut self, output: &mut String) -> Result<(), TomlDecoderError> {
+        let c = match self.read_char()? {
+            'b' => '\x08',
+            't' => '\t',
+            'n' => '\n',
+            'f' => '\x0c',
+            'r' => '\r',
+            '"' => '"',
+            '\\' => '\\',
+            c @ ('u' | 'U') => {
+                let len = if c == 'u' { 4 } else { 8 };
+                let digits = self.rest().get(..len).ok_or(TomlDecoderError::BadEscape)?;
+                if !digits.bytes().all(|c| c.is_ascii_hexdigit()) {
+                    return Err(TomlDecoderError::BadEscape);
+                }
+                self.offset += len;
+                u32::from_str_radix(digits, 16)
+                    .ok()
+                    .and_then(char::from_u32)
+                    .ok_or(TomlDecoderError::BadEscape)?
+            }
+            _ => return Err(TomlDecoderError::BadEscape),
+        };
+        output.push(c);
+        Ok(())
+    }
+    /// Reads the character of a string, rejecting control characters other than tab.
+    fn read_string_char(&mut self) -> Result<char, TomlDecoderError> {
+        match self.read_char()? {
+            '\0'..='\x08' | '\x0a'..='\x1f' | '\x7f' => Err(TomlDecoderError::ControlCharacter),
+            c => Ok(c),
+        }
+    }
+    fn read_basic_string(&mut self) -> Result<String, TomlDecoderError> {
+        self.read_exact("\"")?;
+        let mut output = String::new();
+        loop {
+            match self.read_string_char()? {
+                '"' => return Ok(output),
+                '\\' => self.read_escape(&mut output)?,
+                c => output.push(c),
+            }
+        }
+    }
+    fn read_literal_string(&mut self) -> Result<String, TomlDecoderError> {
+        self.read_exact("'")?;
+        let mut output = String::new();
+        loop {
+            match self.read_string_char()? {
+                '\'' => return Ok(output),
+                c => output.push(c),
+            }
+        }
+    }
+    /// Reads the end of a multi-line string, which may be preceded by up to two quotes that
+    /// belong to the contents.
+    fn try_read_multiline_end(
+        &mut self,
+        delimiter: &str,
+        output: &mut String,
+    ) -> Result<bool, TomlDecoderError> {
+        let quote = &delimiter[..1];
+        let count = self.rest().len() - self.rest().trim_start_matches(quote).len();
+        if count < 3 {
+            return Ok(false);
+        }
+        if count > 5 {
+            return Err(self.unexpected());
+        }
+        output.push_str(&delimiter[..count - 3]);
+        self.offset += count;
+        Ok(true)
+    }
+    fn read_multiline_string(&mut self, delimiter: &str) -> Result<String, TomlDecoderError> {
+        self.read_exact(delimiter)?;
+        self.try_read_newline();
+        let mut output = String::new();
+        loop {
+            if self.try_read_multiline_end(delimiter, &mut output)? {
+                return Ok(output);
+            }
+            if self.try_read("\r\n") {
+                output.push_str("\r\n");
+                continue;
+            }
+            match self.read_char()? {
+                '\\' if delimiter == "\"\"\"" => {
+                    // A backslash at the end of a line trims the following whitespace.
+                    let trimmed = self.rest().trim_start_matches([' ', '\t']);
+                    if trimmed.starts_with('\n') || trimmed.starts_with("\r\n") {
+                        let rest = trimmed.trim_start_matches([' ', '\t', '\r', '\n']);
+                        self.offset = self.input.len() - rest.len();
+                    } else {
+                        self.read_escape(&mut output)?;
+                    }
+                }
+                '\n' => output.push('\n'),
+                '\0'..='\x08' | '\x0a'..='\x1f' | '\x7f' => {
+                    return Err(TomlDecoderError::ControlCharacter)
+                }
+                c => output.push(c),
+            }
+        }
+    }
+    fn read_scalar(&mut self) -> Result<TomlValue, TomlDecoderError> {
+        let start = self.offset;
+        let len = self
+            .rest()
+            .bytes()
+            .take_while(|c| is_value_char(*c))
+            .count();
+        self.offset += len;
+        let mut token = &self.input[start..self.offset];
+        // A date may be separated from its time by a space.
+        let rest = self.rest().as_bytes();
+        if len == 10
+            && rest.len() > 3
+            && rest[0] == b' '
+            && rest[1].is_ascii_digit()
+            && rest[3] == b':'
+        {
+            self.offset += 1;
+            self.offset += self
+                .rest()
+                .bytes()
+                .take_while(|c| is_value_char(*c))
+                .count();
+            token = &self.input[start..self.offset];
+        }
+        let bytes = token.as_bytes();
+        let value = match token {
+            "" => return Err(self.unexpected()),
+            "true" => Ok(TomlValue::Boolean(true)),
+            "false" => Ok(TomlValue::Boolean(false)),
+            _ if bytes.len() > 4
+                && bytes[..4].iter().all(u8::is_ascii_digit)
+                && bytes[4] == b'-'
+                || bytes.len() > 2
+                    && bytes[..2].iter().all(u8::is_ascii_digit)
+                    && bytes[2] == b':' =>
+            {
+                token.parse().map(TomlValue::Datetime)
+            }
+            _ => parse_number(token),
+        };
+        if value.is_err() {
+            // Point at the start of the malformed token.
+            self.offset = start;
+        }
+        value
+    }
+    fn read_array(&mut self) -> Result<TomlValue, TomlDecoderError> {
+        self.read_exact("[")?;
+        let mut elements = vec![];
+        loop {
+            self.read_blank_lines()?;
+            if self.try_read("]") {
+                break;
+            }
+            elements.push(self.read_value()?);
+            self.read_blank_lines()?;
+            if !self.try_read(",") {
+                self.read_exact("]")?;
+                break;
+            }
+        }
+        Ok(TomlValue::Array(elements, false))
+    }
+    fn read_inline_table(&mut self) -> Result<TomlValue, TomlDecoderError> {
+        self.read_exact("{")?;
+        let mut table = TomlTable::new(TableKind::Inline);
+        self.read_whitespace();
+        if self.try_read("}") {
+            return Ok(TomlValue::Table(table));
+        }
+        loop {
+            let keys = self.read_key()?;
+            self.read_exact("=")?;
+            self.read_whitespace();
+            let value = self.read_value()?;
+            if let Err(e) = table.insert_dotted(self.nesting, &keys, value) {
+                // Point at the conflicting key.
+                self.offset = keys[0].1;
+                return Err(e);
+            }
+            self.read_whitespace();
+            if !self.try_read(",") {
+                self.read_exact("}")?;
+                return Ok(TomlValue::Table(table));
+            }
+        }
+    }
+    fn read_value(&mut self) -> Result<TomlNode, TomlDecoderError> {
+        let offset = self.offset;
+        self.nesting += 1;
+        if self.nesting > MAX_NESTING {
+            return Err(TomlDecoderError::TooDeep);
+        }
+        let value = match self.peek() {
+            Some(b'"') if self.rest().starts_with("\"\"\"") => {
+                TomlValue::String(self.read_multiline_string("\"\"\"")?)
+            }
+            Some(b'"') => TomlValue::String(self.read_basic_string()?),
+            Some(b'\'') if self.rest().starts_with("'''") => {
+                TomlValue::String(self.read_multiline_string("'''")?)
+            }
+            Some(b'\'') => TomlValue::String(self.read_literal_string()?),
+            Some(b'[') => self.read_array()?,
+            Some(b'{') => self.read_inline_table()?,
+            _ => self.read_scalar()?,
+        };
+        self.nesting -= 1;
+        Ok(TomlNode { value, offset })
+    }
+    pub fn read_document(&mut self) -> Result<TomlNode, TomlDecoderError> {
+        let mut root = TomlTable::new(TableKind::Header);
+        let mut section: Vec<(String, usize)> = vec![];
+        loop {
+            self.read_blank_lines()?;
+            if self.peek().is_none() {
+                break;
+            }
+            let offset = self.offset;
+            let defined = if self.try_read("[[") {
+                let keys = self.read_key()?;
+                self.read_exact("]]")?;
+                self.read_line_end()?;
+                let defined = root.define_array_table(&keys, offset);
+                section = keys;
+                defined
+            } else if self.try_read("[") {
+                let keys = self.read_key()?;
+                self.read_exact("]")?;
+                self.read_line_end()?;
+                let defined = root.define_table(&keys, offset);
+                section = keys;
+                defined
+            } else {
+                let keys = self.read_key()?;
+                self.read_exact("=")?;
+                self.read_whitespace();
+                let value = self.read_value()?;
+                self.read_line_end()?;
+                root.header_path(&section)
+                    .and_then(|table| table.insert_dotted(section.len(), &keys, value))
+            };
+            if defined.is_err() {
+                // Point at the start of the conflicting header or key.
+                self.offset = offset;
+            }
+            defined?;
+        }
+        Ok(TomlNode {
+            value: TomlValue::Table(root),
+            offset: 0,
+        })
+    }
+}
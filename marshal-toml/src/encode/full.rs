@@ -0,0 +1,59 @@
+use marshal::context::Context;
+use marshal::ser::Serialize;
+use marshal_core::derive_encoder_for_newtype;
+use marshal_core::encode::poison::PoisonEncoder;
+use marshal_core::encode::{AnySpecEncoder, Encoder, SpecEncoder};
+
+use crate::datetime::TomlDatetime;
+use crate::encode::SimpleTomlSpecEncoder;
+
+pub struct TomlSpecEncoder(PoisonEncoder<SimpleTomlSpecEncoder>);
+
+derive_encoder_for_newtype!(TomlSpecEncoder(PoisonEncoder<SimpleTomlSpecEncoder>));
+
+impl TomlSpecEncoder {
+    /// Encodes a datetime as a bare TOML datetime rather than a string.
+    pub(crate) fn encode_datetime(
+        &mut self,
+        any: <Self as SpecEncoder>::AnySpecEncoder,
+        datetime: &TomlDatetime,
+    ) -> anyhow::Result<()> {
+        self.0.inner_mut().set_pending_datetime(datetime.clone());
+        self.0.encode_str(any, "")
+    }
+}
+
+/// Writes nested tables as `[section]` headers after the scalar entries of their parent. TOML has
+/// no null, so `None` fields are omitted and readers should mark them `#[marshal(default)]`.
+pub struct TomlEncoderBuilder {
+    inner: TomlSpecEncoder,
+}
+
+impl TomlEncoderBuilder {
+    pub fn new() -> Self {
+        TomlEncoderBuilder {
+            inner: TomlSpecEncoder(PoisonEncoder::new(SimpleTomlSpecEncoder::new())),
+        }
+    }
+    pub fn build(&mut self) -> AnySpecEncoder<'_, TomlSpecEncoder> {
+        let any = self.inner.0.start(());
+        AnySpecEncoder::new(&mut self.inner, any)
+    }
+    pub fn end(self) -> anyhow::Result<String> {
+        self.inner.0.end()?.end()
+    }
+    pub fn serialize<T: ?Sized + Serialize<TomlEncoder>>(
+        mut self,
+        value: &T,
+        ctx: Context,
+    ) -> anyhow::Result<String> {
+        value.serialize(self.build(), ctx)?;
+        self.end()
+    }
+}
+
+pub struct TomlEncoder;
+
+impl Encoder for TomlEncoder {
+    type SpecEncoder<'en> = TomlSpecEncoder;
+}
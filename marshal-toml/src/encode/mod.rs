@@ -0,0 +1,400 @@
+use std::fmt::{Display, Formatter};
+
+use marshal_core::encode::SpecEncoder;
+use marshal_core::Primitive;
+
+use crate::datetime::TomlDatetime;
+use crate::encode::render::render_document;
+
+pub mod full;
+mod render;
+
+#[derive(Debug)]
+pub enum TomlEncoderError {
+    RootNotTable,
+    NullValue,
+    BadKey,
+    IntegerOutOfRange,
+}
+
+impl Display for TomlEncoderError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TomlEncoderError::RootNotTable => {
+                write!(f, "the root of a TOML document must be a table")
+            }
+            TomlEncoderError::NullValue => {
+                write!(f, "TOML cannot represent `None` outside of a table field")
+            }
+            TomlEncoderError::BadKey => write!(f, "table keys must be strings or scalars"),
+            TomlEncoderError::IntegerOutOfRange => write!(f, "TOML integers must fit in 64 bits"),
+        }
+    }
+}
+
+impl std::error::Error for TomlEncoderError {}
+
+pub(crate) enum TomlValue {
+    String(String),
+    Integer(i64),
+    Float(f64),
+    Boolean(bool),
+    Datetime(TomlDatetime),
+    Array(Vec<TomlValue>),
+    Table(Vec<(String, TomlValue)>),
+}
+
+/// A container that is being encoded.
+enum Frame {
+    Array(Vec<TomlValue>),
+    Table {
+        entries: Vec<(String, TomlValue)>,
+        /// The key of the entry whose value is expected next.
+        key: Option<String>,
+    },
+}
+
+pub struct SimpleTomlSpecEncoder {
+    stack: Vec<Frame>,
+    root: Option<TomlValue>,
+    /// Set by [full::TomlSpecEncoder::encode_datetime] so that the following string is emitted as
+    /// a datetime.
+    pending_datetime: Option<TomlDatetime>,
+}
+
+fn key_text(key: TomlValue) -> anyhow::Result<String> {
+    Ok(match key {
+        TomlValue::String(x) => x,
+        TomlValue::Integer(x) => x.to_string(),
+        TomlValue::Float(x) => x.to_string(),
+        TomlValue::Boolean(x) => x.to_string(),
+        TomlValue::Datetime(x) => x.to_string(),
+        TomlValue::Array(_) | TomlValue::Table(_) => return Err(TomlEncoderError::BadKey.into()),
+    })
+}
+
+fn integer<T: TryInto<i64>>(x: T) -> anyhow::Result<TomlValue> {
+    Ok(TomlValue::Integer(
+        x.try_into()
+            .map_err(|_| TomlEncoderError::IntegerOutOfRange)?,
+    ))
+}
+
+impl SimpleTomlSpecEncoder {
+    pub fn new() -> Self {
+        SimpleTomlSpecEncoder {
+            stack: vec![],
+            root: None,
+            pending_datetime: None,
+        }
+    }
+    pub fn end(self) -> anyhow::Result<String> {
+        match self.root {
+            Some(TomlValue::Table(entries)) => Ok(render_document(&entries)),
+            _ => Err(TomlEncoderError::RootNotTable.into()),
+        }
+    }
+    pub(crate) fn set_pending_datetime(&mut self, datetime: TomlDatetime) {
+        self.pending_datetime = Some(datetime);
+    }
+    /// Adds a completed value to the innermost container.
+    fn emit(&mut self, value: TomlValue) -> anyhow::Result<()> {
+        match self.stack.last_mut() {
+            None => match value {
+                TomlValue::Table(_) => self.root = Some(value),
+                _ => return Err(TomlEncoderError::RootNotTable.into()),
+            },
+            Some(Frame::Array(elements)) => elements.push(value),
+            Some(Frame::Table { entries, key }) => match key.take() {
+                None => *key = Some(key_text(value)?),
+                Some(key) => entries.push((key, value)),
+            },
+        }
+        Ok(())
+    }
+    fn open_table(&mut self, key: Option<&str>) {
+        self.stack.push(Frame::Table {
+            entries: vec![],
+            key: key.map(str::to_string),
+        });
+    }
+    fn open_array(&mut self) {
+        self.stack.push(Frame::Array(vec![]));
+    }
+    fn close(&mut self) -> anyhow::Result<()> {
+        let value = match self.stack.pop() {
+            Some(Frame::Array(elements)) => TomlValue::Array(elements),
+            Some(Frame::Table { entries, .. }) => TomlValue::Table(entries),
+            None => unreachable!(),
+        };
+        self.emit(value)
+    }
+    fn set_key(&mut self, field: &'static str) {
+        if let Some(Frame::Table { key, .. }) = self.stack.last_mut() {
+            *key = Some(field.to_string());
+        }
+    }
+}
+
+impl SpecEncoder for SimpleTomlSpecEncoder {
+    type AnySpecEncoder = ();
+    type SomeCloser = ();
+    type TupleEncoder = ();
+    type SeqEncoder = ();
+    type MapEncoder = ();
+    type ValueEncoder = ();
+    type EntryCloser = ();
+    type TupleStructEncoder = ();
+    type StructEncoder = ();
+    type TupleVariantEncoder = ();
+    type StructVariantEncoder = ();
+
+    fn encode_prim(&mut self, _any: Self::AnySpecEncoder, prim: Primitive) -> anyhow::Result<()> {
+        let value = match prim {
+            Primitive::Unit => TomlValue::Table(vec![]),
+            Primitive::Bool(x) => TomlValue::Boolean(x),
+            Primitive::I8(x) => integer(x)?,
+            Primitive::I16(x) => integer(x)?,
+            Primitive::I32(x) => integer(x)?,
+            Primitive::I64(x) => integer(x)?,
+            Primitive::I128(x) => integer(x)?,
+            Primitive::U8(x) => integer(x)?,
+            Primitive::U16(x) => integer(x)?,
+            Primitive::U32(x) => integer(x)?,
+            Primitive::U64(x) => integer(x)?,
+            Primitive::U128(x) => integer(x)?,
+            // Going through the shortest decimal form keeps `0.1f32` from becoming
+            // `0.10000000149011612`.
+            Primitive::F32(x) => TomlValue::Float(x.to_string().parse()?),
+            Primitive::F64(x) => TomlValue::Float(x),
+            Primitive::Char(x) => TomlValue::String(x.to_string()),
+        };
+        self.emit(value)
+    }
+
+    fn encode_str(&mut self, _any: Self::AnySpecEncoder, s: &str) -> anyhow::Result<()> {
+        match self.pending_datetime.take() {
+            Some(datetime) => self.emit(TomlValue::Datetime(datetime)),
+            None => self.emit(TomlValue::String(s.to_string())),
+        }
+    }
+
+    fn encode_bytes(&mut self, _any: Self::AnySpecEncoder, s: &[u8]) -> anyhow::Result<()> {
+        self.emit(TomlValue::Array(
+            s.iter().map(|x| TomlValue::Integer(*x as i64)).collect(),
+        ))
+    }
+
+    fn encode_none(&mut self, _any: Self::AnySpecEncoder) -> anyhow::Result<()> {
+        // TOML has no null, so the entry is left out.
+        match self.stack.last_mut() {
+            Some(Frame::Table {
+                key: key @ Some(_), ..
+            }) => {
+                *key = None;
+                Ok(())
+            }
+            _ => Err(TomlEncoderError::NullValue.into()),
+        }
+    }
+
+    fn encode_some(
+        &mut self,
+        _any: Self::AnySpecEncoder,
+    ) -> anyhow::Result<(Self::AnySpecEncoder, Self::SomeCloser)> {
+        Ok(((), ()))
+    }
+
+    fn encode_unit_struct(
+        &mut self,
+        _any: Self::AnySpecEncoder,
+        _name: &'static str,
+    ) -> anyhow::Result<()> {
+        self.emit(TomlValue::Table(vec![]))
+    }
+
+    fn encode_tuple_struct(
+        &mut self,
+        _any: Self::AnySpecEncoder,
+        _name: &'static str,
+        _len: usize,
+    ) -> anyhow::Result<Self::TupleStructEncoder> {
+        self.open_array();
+        Ok(())
+    }
+
+    fn encode_struct(
+        &mut self,
+        _any: Self::AnySpecEncoder,
+        _name: &'static str,
+        _fields: &'static [&'static str],
+    ) -> anyhow::Result<Self::StructEncoder> {
+        self.open_table(None);
+        Ok(())
+    }
+
+    fn encode_unit_variant(
+        &mut self,
+        _any: Self::AnySpecEncoder,
+        _name: &'static str,
+        variants: &'static [&'static str],
+        variant_index: usize,
+    ) -> anyhow::Result<()> {
+        self.emit(TomlValue::String(variants[variant_index].to_string()))
+    }
+
+    fn encode_tuple_variant(
+        &mut self,
+        _any: Self::AnySpecEncoder,
+        _name: &'static str,
+        variants: &'static [&'static str],
+        variant_index: usize,
+        _len: usize,
+    ) -> anyhow::Result<Self::TupleVariantEncoder> {
+        self.open_table(Some(variants[variant_index]));
+        self.open_array();
+        Ok(())
+    }
+
+    fn encode_struct_variant(
+        &mut self,
+        _any: Self::AnySpecEncoder,
+        _name: &'static str,
+        variants: &'static [&'static str],
+        variant_index: usize,
+        _fields: &'static [&'static str],
+    ) -> anyhow::Result<Self::StructVariantEncoder> {
+        self.open_table(Some(variants[variant_index]));
+        self.open_table(None);
+        Ok(())
+    }
+
+    fn encode_seq(
+        &mut self,
+        _any: Self::AnySpecEncoder,
+        _len: usize,
+    ) -> anyhow::Result<Self::SeqEncoder> {
+        self.open_array();
+        Ok(())
+    }
+
+    fn encode_tuple(
+        &mut self,
+        _any: Self::AnySpecEncoder,
+        _len: usize,
+    ) -> anyhow::Result<Self::TupleEncoder> {
+        self.open_array();
+        Ok(())
+    }
+
+    fn encode_map(
+        &mut self,
+        _any: Self::AnySpecEncoder,
+        _len: usize,
+    ) -> anyhow::Result<Self::MapEncoder> {
+        self.open_table(None);
+        Ok(())
+    }
+
+    fn some_end(&mut self, _some: Self::SomeCloser) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn tuple_encode_element(
+        &mut self,
+        _tuple: &mut Self::TupleEncoder,
+    ) -> anyhow::Result<Self::AnySpecEncoder> {
+        Ok(())
+    }
+
+    fn tuple_end(&mut self, _tuple: Self::TupleEncoder) -> anyhow::Result<()> {
+        self.close()
+    }
+
+    fn seq_encode_element(
+        &mut self,
+        _seq: &mut Self::SeqEncoder,
+    ) -> anyhow::Result<Self::AnySpecEncoder> {
+        Ok(())
+    }
+
+    fn seq_end(&mut self, _seq: Self::SeqEncoder) -> anyhow::Result<()> {
+        self.close()
+    }
+
+    fn map_encode_element(
+        &mut self,
+        _map: &mut Self::MapEncoder,
+    ) -> anyhow::Result<(Self::AnySpecEncoder, Self::ValueEncoder)> {
+        Ok(((), ()))
+    }
+
+    fn map_end(&mut self, _map: Self::MapEncoder) -> anyhow::Result<()> {
+        self.close()
+    }
+
+    fn entry_encode_value(
+        &mut self,
+        _value: Self::ValueEncoder,
+    ) -> anyhow::Result<(Self::AnySpecEncoder, Self::EntryCloser)> {
+        Ok(((), ()))
+    }
+
+    fn entry_end(&mut self, _closer: Self::EntryCloser) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn tuple_struct_encode_field(
+        &mut self,
+        _s: &mut Self::TupleStructEncoder,
+    ) -> anyhow::Result<Self::AnySpecEncoder> {
+        Ok(())
+    }
+
+    fn tuple_struct_end(&mut self, _s: Self::TupleStructEncoder) -> anyhow::Result<()> {
+        self.close()
+    }
+
+    fn struct_encode_field(
+        &mut self,
+        _s: &mut Self::StructEncoder,
+        field: &'static str,
+    ) -> anyhow::Result<Self::AnySpecEncoder> {
+        self.set_key(field);
+        Ok(())
+    }
+
+    fn struct_end(&mut self, _s: Self::StructEncoder) -> anyhow::Result<()> {
+        self.close()
+    }
+
+    fn tuple_variant_encode_field(
+        &mut self,
+        _v: &mut Self::TupleVariantEncoder,
+    ) -> anyhow::Result<Self::AnySpecEncoder> {
+        Ok(())
+    }
+
+    fn tuple_variant_end(&mut self, _v: Self::TupleVariantEncoder) -> anyhow::Result<()> {
+        self.close()?;
+        self.close()
+    }
+
+    fn struct_variant_encode_field(
+        &mut self,
+        _v: &mut Self::StructVariantEncoder,
+        key: &'static str,
+    ) -> anyhow::Result<Self::AnySpecEncoder> {
+        self.set_key(key);
+        Ok(())
+    }
+
+    fn struct_variant_end(&mut self, _v: Self::StructVariantEncoder) -> anyhow::Result<()> {
+        self.close()?;
+        self.close()
+    }
+
+    fn is_human_readable(&self) -> bool {
+        true
+    }
+}
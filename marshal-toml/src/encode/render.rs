@@ -0,0 +1,144 @@
+use std::fmt::Write;
+
+use crate::encode::TomlValue;
+
+fn is_table(value: &TomlValue) -> bool {
+    matches!(value, TomlValue::Table(_))
+}
+
+fn is_array_of_tables(value: &TomlValue) -> bool {
+    match value {
+        TomlValue::Array(elements) => !elements.is_empty() && elements.iter().all(is_table),
+        _ => false,
+    }
+}
+
+fn write_key(output: &mut String, key: &str) {
+    let bare = !key.is_empty()
+        && key
+            .bytes()
+            .all(|c| c.is_ascii_alphanumeric() || c == b'_' || c == b'-');
+    if bare {
+        output.push_str(key);
+    } else {
+        write_string(output, key);
+    }
+}
+
+fn write_string(output: &mut String, value: &str) {
+    output.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => output.push_str("\\\""),
+            '\\' => output.push_str("\\\\"),
+            '\n' => output.push_str("\\n"),
+            '\r' => output.push_str("\\r"),
+            '\t' => output.push_str("\\t"),
+            '\u{8}' => output.push_str("\\b"),
+            '\u{c}' => output.push_str("\\f"),
+            c if c.is_control() => write!(output, "\\u{:04X}", c as u32).unwrap(),
+            c => output.push(c),
+        }
+    }
+    output.push('"');
+}
+
+fn write_inline(output: &mut String, value: &TomlValue) {
+    match value {
+        TomlValue::String(x) => write_string(output, x),
+        TomlValue::Integer(x) => write!(output, "{}", x).unwrap(),
+        TomlValue::Float(x) if x.is_nan() => output.push_str("nan"),
+        TomlValue::Float(x) if x.is_infinite() => {
+            output.push_str(if *x > 0.0 { "inf" } else { "-inf" })
+        }
+        TomlValue::Float(x) => write!(output, "{:?}", x).unwrap(),
+        TomlValue::Boolean(x) => write!(output, "{}", x).unwrap(),
+        TomlValue::Datetime(x) => write!(output, "{}", x).unwrap(),
+        TomlValue::Array(elements) => {
+            output.push('[');
+            for (index, element) in elements.iter().enumerate() {
+                if index > 0 {
+                    output.push_str(", ");
+                }
+                write_inline(output, element);
+            }
+            output.push(']');
+        }
+        TomlValue::Table(entries) if entries.is_empty() => output.push_str("{}"),
+        TomlValue::Table(entries) => {
+            output.push_str("{ ");
+            for (index, (key, value)) in entries.iter().enumerate() {
+                if index > 0 {
+                    output.push_str(", ");
+                }
+                write_key(output, key);
+                output.push_str(" = ");
+                write_inline(output, value);
+            }
+            output.push_str(" }");
+        }
+    }
+}
+
+fn write_header(output: &mut String, path: &[&str], array: bool) {
+    if !output.is_empty() {
+        output.push('\n');
+    }
+    output.push_str(if array { "[[" } else { "[" });
+    for (index, key) in path.iter().enumerate() {
+        if index > 0 {
+            output.push('.');
+        }
+        write_key(output, key);
+    }
+    output.push_str(if array { "]]\n" } else { "]\n" });
+}
+
+/// Writes the scalar and inline entries of a table, followed by its subtables and arrays of
+/// tables as sections.
+fn write_table<'a>(
+    output: &mut String,
+    path: &mut Vec<&'a str>,
+    entries: &'a [(String, TomlValue)],
+) {
+    for (key, value) in entries {
+        if !is_table(value) && !is_array_of_tables(value) {
+            write_key(output, key);
+            output.push_str(" = ");
+            write_inline(output, value);
+            output.push('\n');
+        }
+    }
+    for (key, value) in entries {
+        path.push(key);
+        match value {
+            TomlValue::Table(inner) => {
+                // A table holding only sections is defined implicitly by their headers.
+                let implicit = !inner.is_empty()
+                    && inner
+                        .iter()
+                        .all(|(_, value)| is_table(value) || is_array_of_tables(value));
+                if !implicit {
+                    write_header(output, path, false);
+                }
+                write_table(output, path, inner);
+            }
+            TomlValue::Array(elements) if is_array_of_tables(value) => {
+                for element in elements {
+                    if let TomlValue::Table(inner) = element {
+                        write_header(output, path, true);
+                        write_table(output, path, inner);
+                    }
+                }
+            }
+            _ => {}
+        }
+        path.pop();
+    }
+}
+
+pub(crate) fn render_document(entries: &[(String, TomlValue)]) -> String {
+    let mut output = String::new();
+    write_table(&mut output, &mut vec![], entries);
+    output
+}
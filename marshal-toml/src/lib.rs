@@ -0,0 +1,21 @@
+#![feature(slice_take)]
+#![deny(unused_must_use)]
+#![feature(never_type)]
+#![feature(trait_alias)]
+#![feature(specialization)]
+#![allow(incomplete_features)]
+
+use marshal::de::Deserialize;
+use marshal::ser::Serialize;
+
+use crate::decode::full::TomlDecoder;
+use crate::encode::full::TomlEncoder;
+
+pub mod datetime;
+pub mod decode;
+pub mod encode;
+#[cfg(test)]
+mod test;
+
+pub trait SerializeToml = Serialize<TomlEncoder>;
+pub trait DeserializeToml = Deserialize<TomlDecoder>;
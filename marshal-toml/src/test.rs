@@ -0,0 +1,412 @@
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+
+use marshal::context::OwnedContext;
+use marshal_derive::{Deserialize, Serialize};
+
+use crate::datetime::{TomlDate, TomlDatetime, TomlOffset, TomlTime};
+use crate::decode::full::TomlDecoderBuilder;
+use crate::encode::full::TomlEncoderBuilder;
+use crate::{DeserializeToml, SerializeToml};
+
+fn decode<T: DeserializeToml>(input: &str) -> anyhow::Result<T> {
+    let mut c = OwnedContext::new();
+    TomlDecoderBuilder::new(input).deserialize(c.borrow())
+}
+
+#[track_caller]
+fn test_round_trip<T: Debug + PartialEq + SerializeToml + DeserializeToml>(
+    input: T,
+    expected: &str,
+) -> anyhow::Result<()> {
+    println!("{:?}", input);
+    let mut c = OwnedContext::new();
+    let found = TomlEncoderBuilder::new().serialize(&input, c.borrow())?;
+    assert_eq!(found, expected);
+    assert_eq!(decode::<T>(&found)?, input);
+    Ok(())
+}
+
+#[track_caller]
+fn test_error<T: Debug + DeserializeToml>(input: &str, expected: &str) {
+    let error = decode::<T>(input).unwrap_err();
+    assert_eq!(format!("{:#}", error), expected);
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Scalars {
+    name: String,
+    count: u32,
+    ratio: f64,
+    enabled: bool,
+    letter: char,
+}
+
+#[test]
+fn test_scalars() -> anyhow::Result<()> {
+    test_round_trip(
+        Scalars {
+            name: "a \"b\"\n".to_string(),
+            count: 7,
+            ratio: 0.5,
+            enabled: true,
+            letter: 'x',
+        },
+        "name = \"a \\\"b\\\"\\n\"\ncount = 7\nratio = 0.5\nenabled = true\nletter = \"x\"\n",
+    )?;
+    assert_eq!(
+        decode::<Scalars>(
+            r#"
+            # comment
+            letter = 'x'
+            name = '''
+raw \n'''
+            count = 0x07 # trailing
+            ratio = 5e-1
+            enabled = true
+            "#
+        )?,
+        Scalars {
+            name: "raw \\n".to_string(),
+            count: 7,
+            ratio: 0.5,
+            enabled: true,
+            letter: 'x',
+        }
+    );
+    Ok(())
+}
+
+#[test]
+fn test_numbers() -> anyhow::Result<()> {
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Numbers {
+        a: i64,
+        b: f32,
+        c: Vec<f64>,
+    }
+    test_round_trip(
+        Numbers {
+            a: -1_000,
+            b: 0.1,
+            c: vec![1.0, 1e100, f64::INFINITY, f64::NEG_INFINITY],
+        },
+        "a = -1000\nb = 0.1\nc = [1.0, 1e100, inf, -inf]\n",
+    )?;
+    let parsed = decode::<Numbers>("a = +1_000\nb = 3\nc = [0o17, 0b11, nan, 1_0.2_5]")?;
+    assert_eq!(parsed.a, 1000);
+    assert_eq!(parsed.b, 3.0);
+    assert_eq!(parsed.c[..2], [15.0, 3.0]);
+    assert!(parsed.c[2].is_nan());
+    assert_eq!(parsed.c[3], 10.25);
+    let mut c = OwnedContext::new();
+    let mut input = BTreeMap::new();
+    input.insert("x".to_string(), u64::MAX);
+    assert!(TomlEncoderBuilder::new()
+        .serialize(&input, c.borrow())
+        .is_err());
+    test_error::<BTreeMap<String, i64>>("x = 1__0", "at line 1 column 5: invalid number");
+    test_error::<BTreeMap<String, i64>>("x = 01", "at line 1 column 5: invalid number");
+    test_error::<BTreeMap<String, i64>>(
+        "x = 9223372036854775808",
+        "at line 1 column 5: integer does not fit in 64 bits",
+    );
+    Ok(())
+}
+
+#[test]
+fn test_sections() -> anyhow::Result<()> {
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Server {
+        host: String,
+        ports: Vec<u16>,
+    }
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Limits {
+        cpu: u8,
+    }
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Nested {
+        limits: Limits,
+    }
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Config {
+        server: Server,
+        nested: Nested,
+        title: String,
+        empty: (),
+    }
+    test_round_trip(
+        Config {
+            server: Server {
+                host: "localhost".to_string(),
+                ports: vec![80, 443],
+            },
+            nested: Nested {
+                limits: Limits { cpu: 4 },
+            },
+            title: "example".to_string(),
+            empty: (),
+        },
+        r#"title = "example"
+
+[server]
+host = "localhost"
+ports = [80, 443]
+
+[nested.limits]
+cpu = 4
+
+[empty]
+"#,
+    )?;
+    Ok(())
+}
+
+#[test]
+fn test_array_of_tables() -> anyhow::Result<()> {
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Physical {
+        color: String,
+    }
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Fruit {
+        name: String,
+        physical: Physical,
+    }
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Basket {
+        fruits: Vec<Fruit>,
+        points: Vec<(u8, u8)>,
+    }
+    let basket = Basket {
+        fruits: vec![
+            Fruit {
+                name: "apple".to_string(),
+                physical: Physical {
+                    color: "red".to_string(),
+                },
+            },
+            Fruit {
+                name: "banana".to_string(),
+                physical: Physical {
+                    color: "yellow".to_string(),
+                },
+            },
+        ],
+        points: vec![(1, 2), (3, 4)],
+    };
+    test_round_trip(
+        basket,
+        r#"points = [[1, 2], [3, 4]]
+
+[[fruits]]
+name = "apple"
+
+[fruits.physical]
+color = "red"
+
+[[fruits]]
+name = "banana"
+
+[fruits.physical]
+color = "yellow"
+"#,
+    )?;
+    Ok(())
+}
+
+#[test]
+fn test_dotted_and_inline() -> anyhow::Result<()> {
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Point {
+        x: i32,
+        y: i32,
+    }
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Shape {
+        origin: Point,
+        corners: Vec<Point>,
+    }
+    let expected = Shape {
+        origin: Point { x: 1, y: 2 },
+        corners: vec![Point { x: 3, y: 4 }, Point { x: 5, y: 6 }],
+    };
+    assert_eq!(
+        decode::<Shape>("origin.x = 1\norigin.y = 2\ncorners = [{x = 3, y = 4}, {x = 5, y = 6}]")?,
+        expected
+    );
+    assert_eq!(
+        decode::<Shape>(
+            "corners = [\n  { x = 3, y = 4 }, # first\n  { x = 5, \"y\" = 6 },\n]\n[origin]\nx = 1\ny = 2\n"
+        )?,
+        expected
+    );
+    type Doc = BTreeMap<String, BTreeMap<String, BTreeMap<String, u8>>>;
+    assert_eq!(
+        decode::<Doc>("[a.\"b c\"]\n[a]\nd.e = 1")?,
+        decode::<Doc>("a = { 'b c' = {}, d = { e = 1 } }")?
+    );
+    test_round_trip::<BTreeMap<String, BTreeMap<String, String>>>(
+        [(
+            "a b".to_string(),
+            [("c.d".to_string(), "e".to_string())].into_iter().collect(),
+        )]
+        .into_iter()
+        .collect(),
+        "[\"a b\"]\n\"c.d\" = \"e\"\n",
+    )?;
+    Ok(())
+}
+
+#[test]
+fn test_enums_and_options() -> anyhow::Result<()> {
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    enum Mode {
+        Off,
+        Fixed(u8, u8),
+        Range { low: u8, high: u8 },
+    }
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Settings {
+        modes: Vec<Mode>,
+        #[marshal(default)]
+        limit: Option<u32>,
+        label: Option<String>,
+    }
+    test_round_trip(
+        Settings {
+            modes: vec![
+                Mode::Off,
+                Mode::Fixed(1, 2),
+                Mode::Range { low: 3, high: 4 },
+            ],
+            limit: None,
+            label: Some("x".to_string()),
+        },
+        "modes = [\"Off\", { Fixed = [1, 2] }, { Range = { low = 3, high = 4 } }]\nlabel = \"x\"\n",
+    )?;
+    test_error::<BTreeMap<String, Mode>>(
+        "x = { Off = {}, Fixed = [1, 2] }",
+        "at line 1 column 5: enum must be a string or a table with one entry",
+    );
+    let mut c = OwnedContext::new();
+    assert!(TomlEncoderBuilder::new()
+        .serialize(&vec![None::<u8>], c.borrow())
+        .is_err());
+    assert!(TomlEncoderBuilder::new()
+        .serialize(&5u8, c.borrow())
+        .is_err());
+    Ok(())
+}
+
+#[test]
+fn test_datetime() -> anyhow::Result<()> {
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Times {
+        offset: TomlDatetime,
+        local: TomlDatetime,
+        date: TomlDatetime,
+        time: TomlDatetime,
+    }
+    let times = Times {
+        offset: TomlDatetime {
+            date: Some(TomlDate {
+                year: 1979,
+                month: 5,
+                day: 27,
+            }),
+            time: Some(TomlTime {
+                hour: 0,
+                minute: 32,
+                second: 0,
+                nanosecond: 999_000_000,
+            }),
+            offset: Some(TomlOffset::Custom { minutes: -420 }),
+        },
+        local: "2024-02-29T07:32:00".parse()?,
+        date: "2024-02-29".parse()?,
+        time: "07:32:00.5".parse()?,
+    };
+    test_round_trip(
+        times,
+        "offset = 1979-05-27T00:32:00.999-07:00\nlocal = 2024-02-29T07:32:00\ndate = 2024-02-29\ntime = 07:32:00.5\n",
+    )?;
+    assert_eq!(
+        decode::<BTreeMap<String, String>>("a = 1979-05-27 07:32:00Z")?["a"],
+        "1979-05-27T07:32:00Z"
+    );
+    assert!("2023-02-29".parse::<TomlDatetime>().is_err());
+    assert!("24:00:00".parse::<TomlDatetime>().is_err());
+    test_error::<BTreeMap<String, String>>(
+        "a = 1\nb = 2023-13-01",
+        "at line 2 column 5: invalid date or time",
+    );
+    Ok(())
+}
+
+#[test]
+fn test_errors() -> anyhow::Result<()> {
+    type Doc = BTreeMap<String, BTreeMap<String, u8>>;
+    test_error::<Doc>(
+        "[a]\nx = 1\n[a]",
+        "at line 3 column 1: table \"a\" is defined twice",
+    );
+    test_error::<Doc>(
+        "[a]\nx = 1\nx = 2",
+        "at line 3 column 1: duplicate key \"x\"",
+    );
+    test_error::<Doc>("a = 1\n[a]", "at line 2 column 1: duplicate key \"a\"");
+    test_error::<Doc>(
+        "a = { x = 1 }\na.y = 2",
+        "at line 2 column 1: duplicate key \"a\"",
+    );
+    test_error::<Doc>(
+        "a = 1\n[a.b]",
+        "at line 2 column 1: \"a\" cannot be extended with a table header",
+    );
+    test_error::<Doc>("[a]\nx = ", "at line 2 column 5: unexpected end of input");
+    test_error::<Doc>(
+        "[a]\nx = 1 y",
+        "at line 2 column 7: unexpected character 'y'",
+    );
+    test_error::<Doc>(
+        "[a]\nx = \"\\q\"",
+        "at line 2 column 8: invalid escape sequence",
+    );
+    test_error::<Doc>(
+        "[a]\nx = 300",
+        "at line 2 column 5: out of range integral type conversion attempted",
+    );
+    let deep = format!("x = {}{}", "[".repeat(200), "]".repeat(200));
+    assert!(decode::<BTreeMap<String, ()>>(&deep).is_err());
+    let deep_key = vec!["a"; 100000].join(".");
+    test_error::<Doc>(
+        &format!("{} = 1", deep_key),
+        "at line 1 column 1: tables and arrays are nested too deeply",
+    );
+    test_error::<Doc>(
+        &format!("[{}]", deep_key),
+        "at line 1 column 1: tables and arrays are nested too deeply",
+    );
+    test_error::<Doc>(
+        &format!("[{}]\nb.c = 1", vec!["a"; 127].join(".")),
+        "at line 2 column 1: tables and arrays are nested too deeply",
+    );
+    test_error::<Doc>(
+        &format!("x = {{ {} = 1 }}", deep_key),
+        "at line 1 column 7: tables and arrays are nested too deeply",
+    );
+    Ok(())
+}
+
+#[test]
+fn test_many_keys() -> anyhow::Result<()> {
+    let input: String = (0..50000).map(|i| format!("k{} = {}\n", i, i % 256)).collect();
+    assert_eq!(decode::<BTreeMap<String, u8>>(&input)?.len(), 50000);
+    test_error::<BTreeMap<String, u8>>(
+        &format!("{}k0 = 1\n", input),
+        "at line 50001 column 1: duplicate key \"k0\"",
+    );
+    Ok(())
+}
@@ -1,8 +1,15 @@
 #![deny(unused_must_use)]
 
+use marshal::context::OwnedContext;
 use marshal_derive::{Deserialize, Serialize};
+use marshal_protobuf::decode::full::{ProtobufDecoder, ProtobufDecoderBuilder};
+use marshal_protobuf::encode::full::{ProtobufEncoder, ProtobufEncoderBuilder};
+use marshal_update::de::DeserializeUpdate as DeserializeUpdateTrait;
 use marshal_update::hash_map::UpdateHashMap;
 use marshal_update::push_vec::PushVec;
+use marshal_update::ser::{
+    SerializeStream as SerializeStreamTrait, SerializeUpdate as SerializeUpdateTrait,
+};
 use marshal_update::tester::Tester;
 use marshal_update::{DeserializeUpdate, SerializeStream, SerializeUpdate};
 
@@ -156,3 +163,41 @@ fn test_push_vec() -> anyhow::Result<()> {
     assert_eq!(&**tester.output(), &[1, 2, 3, 4, 5]);
     Ok(())
 }
+
+#[test]
+fn test_tagged_struct() -> anyhow::Result<()> {
+    #[derive(Serialize, Deserialize, DeserializeUpdate, SerializeStream, SerializeUpdate)]
+    struct Foo {
+        #[marshal(tag = 3)]
+        x: u8,
+        #[marshal(tag = 7)]
+        y: u16,
+    }
+    let mut c = OwnedContext::new();
+    let mut input = Foo { x: 4, y: 8 };
+    let encoded = ProtobufEncoderBuilder::new().serialize(&input, c.borrow())?;
+    assert_eq!(encoded, [0x18, 4, 0x38, 8]);
+    let mut stream = SerializeStreamTrait::start_stream(&input, c.borrow())?;
+    let mut output: Foo = ProtobufDecoderBuilder::new(&encoded).deserialize(c.borrow())?;
+
+    input.y = 300;
+    let mut encoder = ProtobufEncoderBuilder::new();
+    <Foo as SerializeUpdateTrait<ProtobufEncoder>>::serialize_update(
+        &input,
+        &mut stream,
+        encoder.build(),
+        c.borrow(),
+    )?;
+    let update = encoder.end()?;
+    assert_eq!(update, [0x38, 0xAC, 0x02]);
+
+    let mut decoder = ProtobufDecoderBuilder::new(&update);
+    <Foo as DeserializeUpdateTrait<ProtobufDecoder>>::deserialize_update(
+        &mut output,
+        decoder.build(),
+        c.borrow(),
+    )?;
+    decoder.end()?;
+    assert_eq!((output.x, output.y), (4, 300));
+    Ok(())
+}
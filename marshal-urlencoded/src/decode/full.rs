@@ -0,0 +1,66 @@
+use marshal::context::Context;
+use marshal::de::Deserialize;
+use marshal_core::decode::depth_budget::{DepthBudgetDecoder, WithDepthBudget};
+use marshal_core::decode::poison::PoisonDecoder;
+use marshal_core::decode::text::DocumentAnyDecoder;
+use marshal_core::decode::{AnySpecDecoder, Decoder};
+use marshal_core::derive_decoder_for_newtype;
+
+use crate::decode::SimpleUrlEncodedSpecDecoder;
+
+pub struct UrlEncodedSpecDecoder<'de>(
+    PoisonDecoder<DepthBudgetDecoder<SimpleUrlEncodedSpecDecoder<'de>>>,
+);
+
+derive_decoder_for_newtype!(UrlEncodedSpecDecoder<'de>(PoisonDecoder<DepthBudgetDecoder<SimpleUrlEncodedSpecDecoder<'de>>>));
+
+/// Reads `application/x-www-form-urlencoded` text or a query string. Keys may use bracket notation
+/// for nested fields (`a[b]=1`) and sequences (`a=1&a=2` or `a[]=1&a[]=2`). Values are parsed
+/// according to the requested type, and missing keys need `#[marshal(default)]`.
+pub struct UrlEncodedDecoderBuilder<'de> {
+    inner: UrlEncodedSpecDecoder<'de>,
+    depth_budget: usize,
+}
+
+impl<'de> UrlEncodedDecoderBuilder<'de> {
+    pub fn new(input: &'de str) -> Self {
+        UrlEncodedDecoderBuilder {
+            inner: UrlEncodedSpecDecoder(PoisonDecoder::new(DepthBudgetDecoder::new(
+                SimpleUrlEncodedSpecDecoder::new(input),
+            ))),
+            depth_budget: 100,
+        }
+    }
+    /// Limits the nesting of the decoded value, and the number of segments in each key.
+    pub fn set_budget(&mut self, depth_budget: usize) -> &mut Self {
+        self.depth_budget = depth_budget;
+        self.inner.0.inner_mut().inner_mut().set_max_segments(depth_budget);
+        self
+    }
+    pub fn build<'p>(&'p mut self) -> AnySpecDecoder<'p, 'de, UrlEncodedSpecDecoder<'de>> {
+        let any = WithDepthBudget::new(self.depth_budget, DocumentAnyDecoder::Document);
+        let any = self.inner.0.start(any);
+        AnySpecDecoder::new(&mut self.inner, any)
+    }
+    /// Deserializes the document, reporting the offset of the pair that failed.
+    pub fn deserialize<T: Deserialize<UrlEncodedDecoder>>(
+        mut self,
+        ctx: Context,
+    ) -> anyhow::Result<T> {
+        let result = T::deserialize(self.build(), ctx).map_err(|e| e.context(self.location()))?;
+        self.end()?;
+        Ok(result)
+    }
+    pub fn location(&self) -> String {
+        self.inner.0.inner().inner().location()
+    }
+    pub fn end(self) -> anyhow::Result<()> {
+        Ok(self.inner.0.end()?.end()?.end()?)
+    }
+}
+
+pub struct UrlEncodedDecoder;
+
+impl Decoder for UrlEncodedDecoder {
+    type SpecDecoder<'de> = UrlEncodedSpecDecoder<'de>;
+}
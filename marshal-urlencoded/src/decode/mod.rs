@@ -0,0 +1,296 @@
+use std::borrow::Cow;
+use std::fmt::{Display, Formatter};
+
+use marshal_core::decode::text::{parse_primitive, DocumentAnyDecoder};
+use marshal_core::decode::{DecodeHint, DecodeVariantHint, SimpleDecoderView, SpecDecoder};
+use marshal_core::{Primitive, PrimitiveType};
+
+use crate::decode::parse::{parse_pairs, Node, Value};
+
+pub mod full;
+mod parse;
+
+#[derive(Debug)]
+pub enum UrlEncodedDecoderError {
+    BadEscape,
+    BadUtf8,
+    BadKey,
+    Conflict(String),
+    ExpectedValue,
+    ExpectedNested,
+    NestedSequence,
+    ExpectedEmpty,
+    BadEnum,
+}
+
+impl Display for UrlEncodedDecoderError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UrlEncodedDecoderError::BadEscape => write!(f, "invalid percent-encoding"),
+            UrlEncodedDecoderError::BadUtf8 => write!(f, "invalid UTF-8"),
+            UrlEncodedDecoderError::BadKey => write!(f, "malformed key"),
+            UrlEncodedDecoderError::Conflict(key) => {
+                write!(f, "key {:?} holds both a value and nested keys", key)
+            }
+            UrlEncodedDecoderError::ExpectedValue => {
+                write!(f, "expected a value, found nested keys")
+            }
+            UrlEncodedDecoderError::ExpectedNested => {
+                write!(f, "expected nested keys, found a value")
+            }
+            UrlEncodedDecoderError::NestedSequence => {
+                write!(f, "sequence elements must be scalars")
+            }
+            UrlEncodedDecoderError::ExpectedEmpty => write!(f, "expected an empty value"),
+            UrlEncodedDecoderError::BadEnum => {
+                write!(f, "enum must be a value or have exactly one nested key")
+            }
+        }
+    }
+}
+
+impl std::error::Error for UrlEncodedDecoderError {}
+
+pub struct SimpleUrlEncodedSpecDecoder<'de> {
+    input: &'de str,
+    /// The offset of the most recent pair, for reporting errors.
+    offset: usize,
+    max_segments: usize,
+}
+
+pub type UrlEncodedAnyDecoder = DocumentAnyDecoder<UrlEncodedItem>;
+
+pub enum UrlEncodedItem {
+    Node(Node),
+    /// An element of a sequence.
+    Value(Value),
+    /// A map key or enum discriminant.
+    Key(String),
+}
+
+impl<'de> SimpleUrlEncodedSpecDecoder<'de> {
+    pub fn new(input: &'de str) -> Self {
+        SimpleUrlEncodedSpecDecoder {
+            input,
+            offset: 0,
+            max_segments: 100,
+        }
+    }
+    /// Sets the maximum number of segments in a key such as `a[b][c]`.
+    pub fn set_max_segments(&mut self, max_segments: usize) {
+        self.max_segments = max_segments;
+    }
+    pub fn end(self) -> anyhow::Result<()> {
+        Ok(())
+    }
+    pub fn location(&self) -> String {
+        format!("at byte {}", self.offset)
+    }
+    fn decode_node(
+        &mut self,
+        node: Node,
+        hint: DecodeHint,
+    ) -> anyhow::Result<SimpleDecoderView<'de, Self>> {
+        match node {
+            Node::Map(offset, mut entries) => {
+                self.offset = offset;
+                Ok(match hint {
+                    DecodeHint::Struct { .. }
+                    | DecodeHint::Map
+                    | DecodeHint::Any
+                    | DecodeHint::Ignore => SimpleDecoderView::Map(entries.into_iter()),
+                    DecodeHint::Option => SimpleDecoderView::Some(
+                        UrlEncodedItem::Node(Node::Map(offset, entries)).into(),
+                    ),
+                    DecodeHint::Enum { .. } if entries.len() == 1 => {
+                        let (variant, node) = entries.pop().unwrap();
+                        SimpleDecoderView::Enum((variant, Some(node)))
+                    }
+                    DecodeHint::Enum { .. } => return Err(UrlEncodedDecoderError::BadEnum.into()),
+                    _ => return Err(UrlEncodedDecoderError::ExpectedValue.into()),
+                })
+            }
+            Node::Values(mut values) => {
+                self.offset = values.last().unwrap().offset;
+                match hint {
+                    DecodeHint::Seq | DecodeHint::Tuple { .. } | DecodeHint::TupleStruct { .. } => {
+                        Ok(SimpleDecoderView::Seq(values.into_iter()))
+                    }
+                    // A repeated key holding a scalar takes the last value.
+                    _ => self.decode_value(values.pop().unwrap(), hint),
+                }
+            }
+        }
+    }
+    fn decode_value(
+        &mut self,
+        value: Value,
+        hint: DecodeHint,
+    ) -> anyhow::Result<SimpleDecoderView<'de, Self>> {
+        self.offset = value.offset;
+        match hint {
+            DecodeHint::Bytes => Ok(SimpleDecoderView::Bytes(Cow::Owned(value.bytes))),
+            DecodeHint::Option => Ok(SimpleDecoderView::Some(UrlEncodedItem::Value(value).into())),
+            DecodeHint::Seq | DecodeHint::Tuple { .. } | DecodeHint::TupleStruct { .. } => {
+                Err(UrlEncodedDecoderError::NestedSequence.into())
+            }
+            _ => {
+                let text =
+                    String::from_utf8(value.bytes).map_err(|_| UrlEncodedDecoderError::BadUtf8)?;
+                self.decode_text(text, hint)
+            }
+        }
+    }
+    fn decode_text(
+        &mut self,
+        text: String,
+        hint: DecodeHint,
+    ) -> anyhow::Result<SimpleDecoderView<'de, Self>> {
+        Ok(match hint {
+            DecodeHint::Primitive(PrimitiveType::Unit) | DecodeHint::UnitStruct { .. } => {
+                if !text.is_empty() {
+                    return Err(UrlEncodedDecoderError::ExpectedEmpty.into());
+                }
+                SimpleDecoderView::Primitive(Primitive::Unit)
+            }
+            DecodeHint::Primitive(prim) => {
+                SimpleDecoderView::Primitive(parse_primitive(&text, prim)?)
+            }
+            DecodeHint::Option => SimpleDecoderView::Some(UrlEncodedItem::Key(text).into()),
+            DecodeHint::Enum { .. } => SimpleDecoderView::Enum((text, None)),
+            DecodeHint::Seq
+            | DecodeHint::Tuple { .. }
+            | DecodeHint::TupleStruct { .. }
+            | DecodeHint::Map
+            | DecodeHint::Struct { .. } => {
+                return Err(UrlEncodedDecoderError::ExpectedNested.into())
+            }
+            _ => SimpleDecoderView::String(Cow::Owned(text)),
+        })
+    }
+}
+
+impl<'de> SpecDecoder<'de> for SimpleUrlEncodedSpecDecoder<'de> {
+    type AnyDecoder = UrlEncodedAnyDecoder;
+    type SeqDecoder = std::vec::IntoIter<Value>;
+    type MapDecoder = std::vec::IntoIter<(String, Node)>;
+    type KeyDecoder = (String, Node);
+    type ValueDecoder = Node;
+    type DiscriminantDecoder = (String, Option<Node>);
+    type VariantDecoder = Option<Node>;
+    type EnumCloser = ();
+    type SomeDecoder = UrlEncodedAnyDecoder;
+    type SomeCloser = ();
+
+    fn decode(
+        &mut self,
+        any: Self::AnyDecoder,
+        hint: DecodeHint,
+    ) -> anyhow::Result<SimpleDecoderView<'de, Self>> {
+        match any {
+            DocumentAnyDecoder::Document => {
+                let entries = parse_pairs(self.input, self.max_segments).map_err(|(offset, e)| {
+                    self.offset = offset;
+                    e
+                })?;
+                self.decode_node(Node::Map(0, entries), hint)
+            }
+            DocumentAnyDecoder::Parsed(item) => match item {
+                UrlEncodedItem::Node(node) => self.decode_node(node, hint),
+                UrlEncodedItem::Value(value) => self.decode_value(value, hint),
+                UrlEncodedItem::Key(text) => self.decode_text(text, hint),
+            },
+        }
+    }
+
+    fn is_human_readable(&self) -> bool {
+        true
+    }
+
+    fn decode_seq_next(
+        &mut self,
+        seq: &mut Self::SeqDecoder,
+    ) -> anyhow::Result<Option<Self::AnyDecoder>> {
+        Ok(seq.next().map(|x| UrlEncodedItem::Value(x).into()))
+    }
+
+    fn decode_seq_exact_size(&self, seq: &Self::SeqDecoder) -> Option<usize> {
+        Some(seq.len())
+    }
+
+    fn decode_seq_end(&mut self, _seq: Self::SeqDecoder) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn decode_map_next(
+        &mut self,
+        map: &mut Self::MapDecoder,
+    ) -> anyhow::Result<Option<Self::KeyDecoder>> {
+        Ok(map.next())
+    }
+
+    fn decode_map_exact_size(&self, map: &Self::MapDecoder) -> Option<usize> {
+        Some(map.len())
+    }
+
+    fn decode_map_end(&mut self, _map: Self::MapDecoder) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn decode_entry_key(
+        &mut self,
+        (key, node): Self::KeyDecoder,
+    ) -> anyhow::Result<(Self::AnyDecoder, Self::ValueDecoder)> {
+        Ok((UrlEncodedItem::Key(key).into(), node))
+    }
+
+    fn decode_entry_value(
+        &mut self,
+        value: Self::ValueDecoder,
+    ) -> anyhow::Result<Self::AnyDecoder> {
+        Ok(UrlEncodedItem::Node(value).into())
+    }
+
+    fn decode_enum_discriminant(
+        &mut self,
+        (variant, node): Self::DiscriminantDecoder,
+    ) -> anyhow::Result<(Self::AnyDecoder, Self::VariantDecoder)> {
+        Ok((UrlEncodedItem::Key(variant).into(), node))
+    }
+
+    fn decode_enum_variant(
+        &mut self,
+        node: Self::VariantDecoder,
+        hint: DecodeVariantHint,
+    ) -> anyhow::Result<(SimpleDecoderView<'de, Self>, Self::EnumCloser)> {
+        let hint = match hint {
+            DecodeVariantHint::UnitVariant => DecodeHint::Primitive(PrimitiveType::Unit),
+            DecodeVariantHint::TupleVariant { len } => DecodeHint::Tuple { len },
+            DecodeVariantHint::StructVariant { fields } => DecodeHint::Struct { name: "", fields },
+            DecodeVariantHint::Ignore => DecodeHint::Ignore,
+        };
+        let view = match (node, hint) {
+            (Some(node), hint) => self.decode_node(node, hint)?,
+            (None, DecodeHint::Primitive(PrimitiveType::Unit) | DecodeHint::Ignore) => {
+                SimpleDecoderView::Primitive(Primitive::Unit)
+            }
+            (None, _) => return Err(UrlEncodedDecoderError::BadEnum.into()),
+        };
+        Ok((view, ()))
+    }
+
+    fn decode_enum_end(&mut self, _e: Self::EnumCloser) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn decode_some_inner(
+        &mut self,
+        e: Self::SomeDecoder,
+    ) -> anyhow::Result<(Self::AnyDecoder, Self::SomeCloser)> {
+        Ok((e, ()))
+    }
+
+    fn decode_some_end(&mut self, _p: Self::SomeCloser) -> anyhow::Result<()> {
+        Ok(())
+    }
+}
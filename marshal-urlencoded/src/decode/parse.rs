@@ -0,0 +1,112 @@
+use std::collections::HashMap;
+
+use crate::decode::UrlEncodedDecoderError;
+use crate::percent::percent_decode;
+
+pub struct Value {
+    pub bytes: Vec<u8>,
+    /// The offset of the pair within the input.
+    pub offset: usize,
+}
+
+pub enum Node {
+    /// The values of every pair with the same key, which is never empty.
+    Values(Vec<Value>),
+    /// The entries under a key such as `a[b]`, in the order they first appear.
+    Map(usize, Vec<(String, Node)>),
+}
+
+/// The position of each key within the entries of a [Node::Map], and the index of its own entries.
+#[derive(Default)]
+struct Index(HashMap<String, (usize, Index)>);
+
+/// Parses the pairs of the input into a tree of keys, where no key has more than `max_segments`
+/// segments.
+pub fn parse_pairs(
+    input: &str,
+    max_segments: usize,
+) -> Result<Vec<(String, Node)>, (usize, UrlEncodedDecoderError)> {
+    let mut root = vec![];
+    let mut index = Index::default();
+    let mut offset = 0;
+    for pair in input.split('&') {
+        let start = offset;
+        offset += pair.len() + 1;
+        if pair.is_empty() {
+            continue;
+        }
+        let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+        let segments = parse_key(key, max_segments).map_err(|e| (start, e))?;
+        let value = Value {
+            bytes: percent_decode(value).ok_or((start, UrlEncodedDecoderError::BadEscape))?,
+            offset: start,
+        };
+        insert(&mut root, &mut index, start, &segments, value).map_err(|e| (start, e))?;
+    }
+    Ok(root)
+}
+
+/// Splits a key such as `a[b]` into its decoded segments. A trailing `[]` is ignored, so that `a[]`
+/// is the same key as `a`. Keys with more than `max_segments` segments are rejected, since the tree
+/// of keys is built and dropped recursively.
+fn parse_key(key: &str, max_segments: usize) -> Result<Vec<String>, UrlEncodedDecoderError> {
+    let (base, mut rest) = key.split_at(key.find('[').unwrap_or(key.len()));
+    if base.contains(']') {
+        return Err(UrlEncodedDecoderError::BadKey);
+    }
+    let mut segments = vec![decode_segment(base)?];
+    let mut append = false;
+    while !rest.is_empty() {
+        if append {
+            return Err(UrlEncodedDecoderError::BadKey);
+        }
+        let (segment, next) = rest
+            .strip_prefix('[')
+            .and_then(|x| x.split_once(']'))
+            .ok_or(UrlEncodedDecoderError::BadKey)?;
+        if segment.is_empty() {
+            append = true;
+        } else if segments.len() == max_segments {
+            return Err(UrlEncodedDecoderError::BadKey);
+        } else {
+            segments.push(decode_segment(segment)?);
+        }
+        rest = next;
+    }
+    Ok(segments)
+}
+
+fn decode_segment(segment: &str) -> Result<String, UrlEncodedDecoderError> {
+    if segment.is_empty() {
+        return Err(UrlEncodedDecoderError::BadKey);
+    }
+    let bytes = percent_decode(segment).ok_or(UrlEncodedDecoderError::BadEscape)?;
+    String::from_utf8(bytes).map_err(|_| UrlEncodedDecoderError::BadUtf8)
+}
+
+fn insert(
+    map: &mut Vec<(String, Node)>,
+    index: &mut Index,
+    offset: usize,
+    segments: &[String],
+    value: Value,
+) -> Result<(), UrlEncodedDecoderError> {
+    let (segment, rest) = segments.split_first().unwrap();
+    let (position, child) = index.0.entry(segment.clone()).or_insert_with(|| {
+        let node = if rest.is_empty() {
+            Node::Values(vec![])
+        } else {
+            Node::Map(offset, vec![])
+        };
+        map.push((segment.clone(), node));
+        (map.len() - 1, Index::default())
+    });
+    match (&mut map[*position].1, rest.is_empty()) {
+        (Node::Values(values), true) => {
+            values.push(value);
+            Ok(())
+        }
+        (Node::Map(_, entries), false) => insert(entries, child, offset, rest, value),
+        _ => Err(UrlEncodedDecoderError::Conflict(segment.clone())),
+    }
+}
@@ -0,0 +1,56 @@
+use marshal::context::Context;
+use marshal::ser::Serialize;
+use marshal_core::derive_encoder_for_newtype;
+use marshal_core::encode::poison::PoisonEncoder;
+use marshal_core::encode::{AnySpecEncoder, Encoder};
+
+use crate::encode::{SimpleUrlEncodedSpecEncoder, UrlEncodedAnySpecEncoder};
+
+pub struct UrlEncodedSpecEncoder(PoisonEncoder<SimpleUrlEncodedSpecEncoder>);
+
+derive_encoder_for_newtype!(UrlEncodedSpecEncoder(PoisonEncoder<SimpleUrlEncodedSpecEncoder>));
+
+/// Writes a struct or map as `application/x-www-form-urlencoded` text, which is also the syntax
+/// of query strings. Nested fields use bracketed keys such as `a[b]=1`, sequences repeat their
+/// key, and `None` fields are omitted. Empty sequences are omitted too, so fields that can be
+/// `None` or empty only decode if they have `#[marshal(default)]`.
+pub struct UrlEncodedEncoderBuilder {
+    inner: UrlEncodedSpecEncoder,
+}
+
+impl UrlEncodedEncoderBuilder {
+    pub fn new() -> Self {
+        UrlEncodedEncoderBuilder {
+            inner: UrlEncodedSpecEncoder(PoisonEncoder::new(SimpleUrlEncodedSpecEncoder::new())),
+        }
+    }
+    /// Writes sequences as `a[]=1&a[]=2` instead of `a=1&a=2`.
+    pub fn set_bracket_sequences(&mut self, bracket_sequences: bool) -> &mut Self {
+        self.inner
+            .0
+            .inner_mut()
+            .set_bracket_sequences(bracket_sequences);
+        self
+    }
+    pub fn build(&mut self) -> AnySpecEncoder<'_, UrlEncodedSpecEncoder> {
+        let any = self.inner.0.start(UrlEncodedAnySpecEncoder::Document);
+        AnySpecEncoder::new(&mut self.inner, any)
+    }
+    pub fn end(self) -> anyhow::Result<String> {
+        self.inner.0.end()?.end()
+    }
+    pub fn serialize<T: ?Sized + Serialize<UrlEncodedEncoder>>(
+        mut self,
+        value: &T,
+        ctx: Context,
+    ) -> anyhow::Result<String> {
+        value.serialize(self.build(), ctx)?;
+        self.end()
+    }
+}
+
+pub struct UrlEncodedEncoder;
+
+impl Encoder for UrlEncodedEncoder {
+    type SpecEncoder<'en> = UrlEncodedSpecEncoder;
+}
@@ -0,0 +1,377 @@
+use std::fmt::{Display, Formatter};
+
+use marshal_core::encode::SpecEncoder;
+use marshal_core::Primitive;
+
+use crate::percent::percent_encode;
+
+pub mod full;
+
+#[derive(Debug)]
+pub enum UrlEncodedEncoderError {
+    NotAStruct,
+    NestedSequence,
+    BadKey,
+    NoneInSequence,
+}
+
+impl Display for UrlEncodedEncoderError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UrlEncodedEncoderError::NotAStruct => {
+                write!(f, "a form must be a struct, map or enum with data")
+            }
+            UrlEncodedEncoderError::NestedSequence => {
+                write!(f, "sequence elements must be scalars")
+            }
+            UrlEncodedEncoderError::BadKey => write!(f, "map keys must be scalars"),
+            UrlEncodedEncoderError::NoneInSequence => write!(f, "sequences cannot hold None"),
+        }
+    }
+}
+
+impl std::error::Error for UrlEncodedEncoderError {}
+
+/// Keys are stored escaped, with literal brackets between the segments, as in `a[b][c]`.
+pub enum UrlEncodedAnySpecEncoder {
+    Document,
+    Key(String),
+    /// An element of a sequence, which is written under the key of the sequence.
+    Element(String),
+    /// A map key, which becomes the next segment of the keys within the entry.
+    MapKey,
+}
+
+pub struct SimpleUrlEncodedSpecEncoder {
+    output: String,
+    /// Whether sequences are written as `a[]=1&a[]=2` rather than `a=1&a=2`.
+    bracket_sequences: bool,
+    /// The escaped text of the most recent map key.
+    map_key: Option<String>,
+}
+
+fn child(prefix: &Option<String>, segment: &str) -> String {
+    match prefix {
+        None => segment.to_string(),
+        Some(prefix) => format!("{}[{}]", prefix, segment),
+    }
+}
+
+fn escape(segment: &[u8]) -> String {
+    let mut output = String::new();
+    percent_encode(segment, &mut output);
+    output
+}
+
+impl SimpleUrlEncodedSpecEncoder {
+    pub fn new() -> Self {
+        SimpleUrlEncodedSpecEncoder {
+            output: String::new(),
+            bracket_sequences: false,
+            map_key: None,
+        }
+    }
+    pub fn set_bracket_sequences(&mut self, bracket_sequences: bool) {
+        self.bracket_sequences = bracket_sequences;
+    }
+    pub fn end(self) -> anyhow::Result<String> {
+        Ok(self.output)
+    }
+    fn write_value(&mut self, any: UrlEncodedAnySpecEncoder, value: &[u8]) -> anyhow::Result<()> {
+        match any {
+            UrlEncodedAnySpecEncoder::Document => {
+                return Err(UrlEncodedEncoderError::NotAStruct.into())
+            }
+            UrlEncodedAnySpecEncoder::Key(key) | UrlEncodedAnySpecEncoder::Element(key) => {
+                if !self.output.is_empty() {
+                    self.output.push('&');
+                }
+                self.output.push_str(&key);
+                self.output.push('=');
+                percent_encode(value, &mut self.output);
+            }
+            UrlEncodedAnySpecEncoder::MapKey => self.map_key = Some(escape(value)),
+        }
+        Ok(())
+    }
+    /// Returns the prefix for the fields or entries of a struct or map.
+    fn open_nested(&mut self, any: UrlEncodedAnySpecEncoder) -> anyhow::Result<Option<String>> {
+        match any {
+            UrlEncodedAnySpecEncoder::Document => Ok(None),
+            UrlEncodedAnySpecEncoder::Key(key) => Ok(Some(key)),
+            UrlEncodedAnySpecEncoder::Element(_) => {
+                Err(UrlEncodedEncoderError::NestedSequence.into())
+            }
+            UrlEncodedAnySpecEncoder::MapKey => Err(UrlEncodedEncoderError::BadKey.into()),
+        }
+    }
+    /// Returns the key under which the elements of a sequence are written.
+    fn open_seq(&mut self, any: UrlEncodedAnySpecEncoder) -> anyhow::Result<String> {
+        match any {
+            UrlEncodedAnySpecEncoder::Document => Err(UrlEncodedEncoderError::NotAStruct.into()),
+            UrlEncodedAnySpecEncoder::Key(key) if self.bracket_sequences => {
+                Ok(format!("{}[]", key))
+            }
+            UrlEncodedAnySpecEncoder::Key(key) => Ok(key),
+            UrlEncodedAnySpecEncoder::Element(_) => {
+                Err(UrlEncodedEncoderError::NestedSequence.into())
+            }
+            UrlEncodedAnySpecEncoder::MapKey => Err(UrlEncodedEncoderError::BadKey.into()),
+        }
+    }
+}
+
+impl SpecEncoder for SimpleUrlEncodedSpecEncoder {
+    type AnySpecEncoder = UrlEncodedAnySpecEncoder;
+    type SomeCloser = ();
+    type TupleEncoder = String;
+    type SeqEncoder = String;
+    type MapEncoder = Option<String>;
+    type ValueEncoder = Option<String>;
+    type EntryCloser = ();
+    type TupleStructEncoder = String;
+    type StructEncoder = Option<String>;
+    type TupleVariantEncoder = String;
+    type StructVariantEncoder = Option<String>;
+
+    fn encode_prim(&mut self, any: Self::AnySpecEncoder, prim: Primitive) -> anyhow::Result<()> {
+        let value = match prim {
+            Primitive::Unit => String::new(),
+            Primitive::Bool(x) => x.to_string(),
+            Primitive::I8(x) => x.to_string(),
+            Primitive::I16(x) => x.to_string(),
+            Primitive::I32(x) => x.to_string(),
+            Primitive::I64(x) => x.to_string(),
+            Primitive::I128(x) => x.to_string(),
+            Primitive::U8(x) => x.to_string(),
+            Primitive::U16(x) => x.to_string(),
+            Primitive::U32(x) => x.to_string(),
+            Primitive::U64(x) => x.to_string(),
+            Primitive::U128(x) => x.to_string(),
+            Primitive::F32(x) => x.to_string(),
+            Primitive::F64(x) => x.to_string(),
+            Primitive::Char(x) => x.to_string(),
+        };
+        self.write_value(any, value.as_bytes())
+    }
+
+    fn encode_str(&mut self, any: Self::AnySpecEncoder, s: &str) -> anyhow::Result<()> {
+        self.write_value(any, s.as_bytes())
+    }
+
+    fn encode_bytes(&mut self, any: Self::AnySpecEncoder, s: &[u8]) -> anyhow::Result<()> {
+        self.write_value(any, s)
+    }
+
+    fn encode_none(&mut self, any: Self::AnySpecEncoder) -> anyhow::Result<()> {
+        // A missing key, which is read with `#[marshal(default)]`.
+        match any {
+            UrlEncodedAnySpecEncoder::Document => Err(UrlEncodedEncoderError::NotAStruct.into()),
+            UrlEncodedAnySpecEncoder::Key(_) => Ok(()),
+            UrlEncodedAnySpecEncoder::Element(_) => {
+                Err(UrlEncodedEncoderError::NoneInSequence.into())
+            }
+            UrlEncodedAnySpecEncoder::MapKey => Err(UrlEncodedEncoderError::BadKey.into()),
+        }
+    }
+
+    fn encode_some(
+        &mut self,
+        any: Self::AnySpecEncoder,
+    ) -> anyhow::Result<(Self::AnySpecEncoder, Self::SomeCloser)> {
+        Ok((any, ()))
+    }
+
+    fn encode_unit_struct(
+        &mut self,
+        any: Self::AnySpecEncoder,
+        _name: &'static str,
+    ) -> anyhow::Result<()> {
+        self.write_value(any, &[])
+    }
+
+    fn encode_tuple_struct(
+        &mut self,
+        any: Self::AnySpecEncoder,
+        _name: &'static str,
+        _len: usize,
+    ) -> anyhow::Result<Self::TupleStructEncoder> {
+        self.open_seq(any)
+    }
+
+    fn encode_struct(
+        &mut self,
+        any: Self::AnySpecEncoder,
+        _name: &'static str,
+        _fields: &'static [&'static str],
+    ) -> anyhow::Result<Self::StructEncoder> {
+        self.open_nested(any)
+    }
+
+    fn encode_unit_variant(
+        &mut self,
+        any: Self::AnySpecEncoder,
+        _name: &'static str,
+        variants: &'static [&'static str],
+        variant_index: usize,
+    ) -> anyhow::Result<()> {
+        self.write_value(any, variants[variant_index].as_bytes())
+    }
+
+    fn encode_tuple_variant(
+        &mut self,
+        any: Self::AnySpecEncoder,
+        _name: &'static str,
+        variants: &'static [&'static str],
+        variant_index: usize,
+        _len: usize,
+    ) -> anyhow::Result<Self::TupleVariantEncoder> {
+        let prefix = self.open_nested(any)?;
+        let key = child(&prefix, &escape(variants[variant_index].as_bytes()));
+        self.open_seq(UrlEncodedAnySpecEncoder::Key(key))
+    }
+
+    fn encode_struct_variant(
+        &mut self,
+        any: Self::AnySpecEncoder,
+        _name: &'static str,
+        variants: &'static [&'static str],
+        variant_index: usize,
+        _fields: &'static [&'static str],
+    ) -> anyhow::Result<Self::StructVariantEncoder> {
+        let prefix = self.open_nested(any)?;
+        Ok(Some(child(
+            &prefix,
+            &escape(variants[variant_index].as_bytes()),
+        )))
+    }
+
+    fn encode_seq(
+        &mut self,
+        any: Self::AnySpecEncoder,
+        _len: usize,
+    ) -> anyhow::Result<Self::SeqEncoder> {
+        self.open_seq(any)
+    }
+
+    fn encode_tuple(
+        &mut self,
+        any: Self::AnySpecEncoder,
+        _len: usize,
+    ) -> anyhow::Result<Self::TupleEncoder> {
+        self.open_seq(any)
+    }
+
+    fn encode_map(
+        &mut self,
+        any: Self::AnySpecEncoder,
+        _len: usize,
+    ) -> anyhow::Result<Self::MapEncoder> {
+        self.open_nested(any)
+    }
+
+    fn some_end(&mut self, _some: Self::SomeCloser) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn tuple_encode_element(
+        &mut self,
+        tuple: &mut Self::TupleEncoder,
+    ) -> anyhow::Result<Self::AnySpecEncoder> {
+        Ok(UrlEncodedAnySpecEncoder::Element(tuple.clone()))
+    }
+
+    fn tuple_end(&mut self, _tuple: Self::TupleEncoder) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn seq_encode_element(
+        &mut self,
+        seq: &mut Self::SeqEncoder,
+    ) -> anyhow::Result<Self::AnySpecEncoder> {
+        Ok(UrlEncodedAnySpecEncoder::Element(seq.clone()))
+    }
+
+    fn seq_end(&mut self, _seq: Self::SeqEncoder) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn map_encode_element(
+        &mut self,
+        map: &mut Self::MapEncoder,
+    ) -> anyhow::Result<(Self::AnySpecEncoder, Self::ValueEncoder)> {
+        Ok((UrlEncodedAnySpecEncoder::MapKey, map.clone()))
+    }
+
+    fn map_end(&mut self, _map: Self::MapEncoder) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn entry_encode_value(
+        &mut self,
+        value: Self::ValueEncoder,
+    ) -> anyhow::Result<(Self::AnySpecEncoder, Self::EntryCloser)> {
+        let key = self.map_key.take().ok_or(UrlEncodedEncoderError::BadKey)?;
+        Ok((UrlEncodedAnySpecEncoder::Key(child(&value, &key)), ()))
+    }
+
+    fn entry_end(&mut self, _closer: Self::EntryCloser) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn tuple_struct_encode_field(
+        &mut self,
+        s: &mut Self::TupleStructEncoder,
+    ) -> anyhow::Result<Self::AnySpecEncoder> {
+        Ok(UrlEncodedAnySpecEncoder::Element(s.clone()))
+    }
+
+    fn tuple_struct_end(&mut self, _s: Self::TupleStructEncoder) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn struct_encode_field(
+        &mut self,
+        s: &mut Self::StructEncoder,
+        field: &'static str,
+    ) -> anyhow::Result<Self::AnySpecEncoder> {
+        Ok(UrlEncodedAnySpecEncoder::Key(child(
+            s,
+            &escape(field.as_bytes()),
+        )))
+    }
+
+    fn struct_end(&mut self, _s: Self::StructEncoder) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn tuple_variant_encode_field(
+        &mut self,
+        v: &mut Self::TupleVariantEncoder,
+    ) -> anyhow::Result<Self::AnySpecEncoder> {
+        Ok(UrlEncodedAnySpecEncoder::Element(v.clone()))
+    }
+
+    fn tuple_variant_end(&mut self, _v: Self::TupleVariantEncoder) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn struct_variant_encode_field(
+        &mut self,
+        v: &mut Self::StructVariantEncoder,
+        key: &'static str,
+    ) -> anyhow::Result<Self::AnySpecEncoder> {
+        Ok(UrlEncodedAnySpecEncoder::Key(child(
+            v,
+            &escape(key.as_bytes()),
+        )))
+    }
+
+    fn struct_variant_end(&mut self, _v: Self::StructVariantEncoder) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn is_human_readable(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,18 @@
+#![deny(unused_must_use)]
+#![feature(never_type)]
+#![feature(trait_alias)]
+
+use marshal::de::Deserialize;
+use marshal::ser::Serialize;
+
+use crate::decode::full::UrlEncodedDecoder;
+use crate::encode::full::UrlEncodedEncoder;
+
+pub mod decode;
+pub mod encode;
+mod percent;
+#[cfg(test)]
+mod test;
+
+pub trait SerializeUrlEncoded = Serialize<UrlEncodedEncoder>;
+pub trait DeserializeUrlEncoded = Deserialize<UrlEncodedDecoder>;
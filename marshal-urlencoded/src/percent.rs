@@ -0,0 +1,43 @@
+/// Appends `input` to `output`, escaping everything except the unreserved characters of RFC 3986.
+/// Spaces are written as `+`, as in `application/x-www-form-urlencoded`.
+pub fn percent_encode(input: &[u8], output: &mut String) {
+    for &b in input {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                output.push(b as char)
+            }
+            b' ' => output.push('+'),
+            _ => {
+                output.push('%');
+                output.push(
+                    char::from_digit((b >> 4) as u32, 16)
+                        .unwrap()
+                        .to_ascii_uppercase(),
+                );
+                output.push(
+                    char::from_digit((b & 15) as u32, 16)
+                        .unwrap()
+                        .to_ascii_uppercase(),
+                );
+            }
+        }
+    }
+}
+
+/// Decodes `%XX` escapes and `+`, returning `None` if an escape is malformed.
+pub fn percent_decode(input: &str) -> Option<Vec<u8>> {
+    let mut output = Vec::with_capacity(input.len());
+    let mut bytes = input.bytes();
+    while let Some(b) = bytes.next() {
+        match b {
+            b'%' => {
+                let high = (bytes.next()? as char).to_digit(16)?;
+                let low = (bytes.next()? as char).to_digit(16)?;
+                output.push((high * 16 + low) as u8);
+            }
+            b'+' => output.push(b' '),
+            _ => output.push(b),
+        }
+    }
+    Some(output)
+}
@@ -0,0 +1,239 @@
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+
+use marshal::context::OwnedContext;
+use marshal_derive::{Deserialize, Serialize};
+
+use crate::decode::full::UrlEncodedDecoderBuilder;
+use crate::encode::full::UrlEncodedEncoderBuilder;
+use crate::{DeserializeUrlEncoded, SerializeUrlEncoded};
+
+fn decode<T: DeserializeUrlEncoded>(input: &str) -> anyhow::Result<T> {
+    let mut c = OwnedContext::new();
+    UrlEncodedDecoderBuilder::new(input).deserialize(c.borrow())
+}
+
+#[track_caller]
+fn test_round_trip<T: Debug + PartialEq + SerializeUrlEncoded + DeserializeUrlEncoded>(
+    input: T,
+    expected: &str,
+) -> anyhow::Result<()> {
+    println!("{:?}", input);
+    let mut c = OwnedContext::new();
+    let found = UrlEncodedEncoderBuilder::new().serialize(&input, c.borrow())?;
+    assert_eq!(found, expected);
+    assert_eq!(decode::<T>(&found)?, input);
+    Ok(())
+}
+
+#[track_caller]
+fn test_error<T: Debug + DeserializeUrlEncoded>(input: &str, expected: &str) {
+    let error = decode::<T>(input).unwrap_err();
+    assert_eq!(format!("{:#}", error), expected);
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+enum Order {
+    Asc,
+    Desc,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Search {
+    q: String,
+    page: u32,
+    exact: bool,
+    score: f64,
+    order: Order,
+    initial: char,
+}
+
+#[test]
+fn test_flat() -> anyhow::Result<()> {
+    test_round_trip(
+        Search {
+            q: "rust & café".to_string(),
+            page: 2,
+            exact: false,
+            score: -0.5,
+            order: Order::Desc,
+            initial: '=',
+        },
+        "q=rust+%26+caf%C3%A9&page=2&exact=false&score=-0.5&order=Desc&initial=%3D",
+    )?;
+    assert_eq!(
+        decode::<Search>("order=Asc&&page=%33&q=a%2bb+c&score=1e3&exact=true&initial=x&extra")?,
+        Search {
+            q: "a+b c".to_string(),
+            page: 3,
+            exact: true,
+            score: 1000.0,
+            order: Order::Asc,
+            initial: 'x',
+        }
+    );
+    Ok(())
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Filter {
+    #[marshal(default)]
+    tags: Vec<String>,
+    #[marshal(default)]
+    limit: Option<u8>,
+    label: Option<String>,
+    range: (i32, i32),
+}
+
+#[test]
+fn test_sequences_and_options() -> anyhow::Result<()> {
+    test_round_trip(
+        Filter {
+            tags: vec!["a".to_string(), "b c".to_string()],
+            limit: None,
+            label: Some("".to_string()),
+            range: (-1, 1),
+        },
+        "tags=a&tags=b+c&label=&range=-1&range=1",
+    )?;
+    assert_eq!(
+        decode::<Filter>("range[]=3&range[]=4&limit=9&label=x")?,
+        Filter {
+            tags: vec![],
+            limit: Some(9),
+            label: Some("x".to_string()),
+            range: (3, 4),
+        }
+    );
+    test_round_trip(
+        Filter {
+            tags: vec![],
+            limit: None,
+            label: Some("y".to_string()),
+            range: (0, 0),
+        },
+        "label=y&range=0&range=0",
+    )?;
+    let mut c = OwnedContext::new();
+    let mut encoder = UrlEncodedEncoderBuilder::new();
+    encoder.set_bracket_sequences(true);
+    let encoded = encoder.serialize(
+        &Filter {
+            tags: vec!["x".to_string()],
+            limit: Some(1),
+            label: None,
+            range: (0, 0),
+        },
+        c.borrow(),
+    )?;
+    assert_eq!(encoded, "tags[]=x&limit=1&range[]=0&range[]=0");
+    // The omitted `label` has no default, so it cannot be read back.
+    test_error::<Filter>(
+        &encoded,
+        "at byte 27: MissingField { field_name: \"label\" }",
+    );
+    Ok(())
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+enum Shape {
+    Point,
+    Circle(u32),
+    Rect { width: u32, height: u32 },
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Address {
+    city: String,
+    zip: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct User {
+    name: String,
+    address: Address,
+    scores: BTreeMap<String, u8>,
+    shapes: BTreeMap<String, Shape>,
+}
+
+#[test]
+fn test_nested() -> anyhow::Result<()> {
+    test_round_trip(
+        User {
+            name: "ann".to_string(),
+            address: Address {
+                city: "Oslo".to_string(),
+                zip: Some(150),
+            },
+            scores: BTreeMap::from([("math".to_string(), 9), ("a[b]".to_string(), 1)]),
+            shapes: BTreeMap::from([
+                ("a".to_string(), Shape::Point),
+                ("b".to_string(), Shape::Circle(2)),
+                (
+                    "c".to_string(),
+                    Shape::Rect {
+                        width: 3,
+                        height: 4,
+                    },
+                ),
+            ]),
+        },
+        "name=ann&address[city]=Oslo&address[zip]=150\
+         &scores[a%5Bb%5D]=1&scores[math]=9\
+         &shapes[a]=Point&shapes[b][Circle]=2&shapes[c][Rect][width]=3&shapes[c][Rect][height]=4",
+    )?;
+    assert_eq!(
+        decode::<BTreeMap<String, Vec<u8>>>("a=%00%FF&b=")?,
+        BTreeMap::from([("a".to_string(), vec![0, 255]), ("b".to_string(), vec![])])
+    );
+    Ok(())
+}
+
+#[test]
+fn test_errors() {
+    let mut c = OwnedContext::new();
+    let error = UrlEncodedEncoderBuilder::new()
+        .serialize(&vec![1u8], c.borrow())
+        .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "a form must be a struct, map or enum with data"
+    );
+    let error = UrlEncodedEncoderBuilder::new()
+        .serialize(
+            &BTreeMap::from([("x".to_string(), vec![vec![1u32]])]),
+            c.borrow(),
+        )
+        .unwrap_err();
+    assert_eq!(error.to_string(), "sequence elements must be scalars");
+    test_error::<Address>("city=a&zip=x", "at byte 7: invalid digit found in string");
+    test_error::<Address>("city=%zz", "at byte 0: invalid percent-encoding");
+    test_error::<Address>("city=%FF", "at byte 0: invalid UTF-8");
+    test_error::<Address>("zip=1&city]=a", "at byte 6: malformed key");
+    test_error::<Address>(
+        "city=a&city[x]=b",
+        "at byte 7: key \"city\" holds both a value and nested keys",
+    );
+    test_error::<User>(
+        "name=a&address=b",
+        "at byte 7: expected nested keys, found a value",
+    );
+    test_error::<Address>(
+        "city[x]=b",
+        "at byte 0: expected a value, found nested keys",
+    );
+    test_error::<BTreeMap<String, Shape>>(
+        "a[Point]=&a[Circle]=1",
+        "at byte 0: enum must be a value or have exactly one nested key",
+    );
+    test_error::<Address>("zip=1", "at byte 0: MissingField { field_name: \"city\" }");
+    let deep = format!("zip=1&city{}=a", "[x]".repeat(100_000));
+    test_error::<Address>(&deep, "at byte 6: malformed key");
+    let mut c = OwnedContext::new();
+    let mut decoder = UrlEncodedDecoderBuilder::new("a[b][c]=1");
+    decoder.set_budget(2);
+    let error = decoder
+        .deserialize::<BTreeMap<String, BTreeMap<String, BTreeMap<String, u8>>>>(c.borrow())
+        .unwrap_err();
+    assert_eq!(format!("{:#}", error), "at byte 0: malformed key");
+}
@@ -6,6 +6,7 @@ mod test;
 
 use std::array::TryFromSliceError;
 use std::borrow::Borrow;
+use std::ops::BitOr;
 use vu128::{
     decode_f32, decode_f64, decode_i128, decode_i32, decode_i64, decode_u128, decode_u32,
     decode_u64, encode_f32, encode_f64, encode_i128, encode_i32, encode_i64, encode_u128,
@@ -35,12 +36,178 @@ where
     }
 }
 
-pub trait ToFromVu128: Sized {
+pub trait ToFromVu128: Sized + Copy {
     type Buffer: Array<Item = u8>;
     fn encode_vu128(buf: &mut Self::Buffer, value: Self) -> usize;
     fn decode_vu128(buf: &Self::Buffer) -> (Self, usize);
+    #[inline]
+    fn encode_vu128_slice(output: &mut Vec<u8>, values: &[Self]) {
+        encode_slice_scalar(output, values)
+    }
+    #[inline]
+    fn decode_vu128_slice(
+        input: &mut &[u8],
+        len: usize,
+        output: &mut Vec<Self>,
+    ) -> Result<(), TryFromSliceError> {
+        decode_slice_scalar(input, len, output)
+    }
+}
+
+/// Encodes directly into the output, reserving space for the longest encodings up front.
+#[inline]
+fn encode_slice_scalar<T: ToFromVu128>(output: &mut Vec<u8>, values: &[T]) {
+    let start = output.len();
+    output.resize(start + values.len() * T::Buffer::ARRAY_LEN, 0);
+    let mut end = start;
+    for value in values {
+        let buf = &mut output[end..end + T::Buffer::ARRAY_LEN];
+        let buf = T::Buffer::try_from_slice_mut(buf).unwrap();
+        end += T::encode_vu128(buf, *value);
+    }
+    output.truncate(end);
+}
+
+#[inline]
+fn decode_slice_scalar<T: ToFromVu128>(
+    input: &mut &[u8],
+    len: usize,
+    output: &mut Vec<T>,
+) -> Result<(), TryFromSliceError> {
+    // Every value takes at least one byte, so a corrupt length cannot cause a huge allocation.
+    output.reserve(len.min(input.len()));
+    for _ in 0..len {
+        output.push(input.read_vu128()?);
+    }
+    Ok(())
 }
 
+const SMALL_CHUNK: usize = 16;
+
+/// Unsigned values below this bound are encoded as a single byte equal to the value.
+const SMALL_BOUND: u8 = 0x80;
+
+/// Copies runs of small values as single bytes, which the compiler can vectorize.
+#[inline]
+fn encode_slice_small<T: ToFromVu128 + BitOr<Output = T> + PartialOrd + From<u8> + Into<u128>>(
+    output: &mut Vec<u8>,
+    values: &[T],
+) {
+    let mut chunks = values.chunks_exact(SMALL_CHUNK);
+    for chunk in &mut chunks {
+        if chunk.iter().fold(T::from(0), |a, x| a | *x) < T::from(SMALL_BOUND) {
+            output.extend(chunk.iter().map(|x| (*x).into() as u8));
+        } else {
+            encode_slice_scalar(output, chunk);
+        }
+    }
+    encode_slice_scalar(output, chunks.remainder());
+}
+
+/// Signed values in `-SIGNED_BOUND..SIGNED_BOUND` are zigzag encoded as a single byte.
+const SIGNED_BOUND: i8 = 0x40;
+
+trait SmallSigned: ToFromVu128 + PartialOrd + From<i8> {
+    /// Truncates to the low byte, which is exact for values within `SIGNED_BOUND`.
+    fn low_i8(self) -> i8;
+}
+
+#[inline]
+fn zigzag(x: i8) -> u8 {
+    ((x << 1) ^ (x >> 7)) as u8
+}
+
+#[inline]
+fn unzigzag(x: u8) -> i8 {
+    (x >> 1) as i8 ^ -((x & 1) as i8)
+}
+
+#[inline]
+fn encode_slice_small_signed<T: SmallSigned>(output: &mut Vec<u8>, values: &[T]) {
+    let (low, high) = (T::from(-SIGNED_BOUND), T::from(SIGNED_BOUND));
+    let mut chunks = values.chunks_exact(SMALL_CHUNK);
+    for chunk in &mut chunks {
+        if chunk.iter().fold(true, |a, x| a & (*x >= low) & (*x < high)) {
+            output.extend(chunk.iter().map(|x| zigzag(x.low_i8())));
+        } else {
+            encode_slice_scalar(output, chunk);
+        }
+    }
+    encode_slice_scalar(output, chunks.remainder());
+}
+
+/// Copies runs of single-byte encodings, converting each byte with `from_byte`.
+#[inline]
+fn decode_slice_small<T: ToFromVu128>(
+    input: &mut &[u8],
+    len: usize,
+    output: &mut Vec<T>,
+    from_byte: impl Fn(u8) -> T,
+) -> Result<(), TryFromSliceError> {
+    output.reserve(len.min(input.len()));
+    let mut remaining = len;
+    while remaining >= SMALL_CHUNK {
+        match input.first_chunk::<SMALL_CHUNK>() {
+            Some(chunk) if chunk.iter().fold(0, |a, x| a | *x) < SMALL_BOUND => {
+                output.extend(chunk.iter().map(|x| from_byte(*x)));
+                *input = &input[SMALL_CHUNK..];
+                remaining -= SMALL_CHUNK;
+            }
+            _ => {
+                output.push(input.read_vu128()?);
+                remaining -= 1;
+            }
+        }
+    }
+    decode_slice_scalar(input, remaining, output)
+}
+
+macro_rules! small_slices {
+    () => {
+        #[inline]
+        fn encode_vu128_slice(output: &mut Vec<u8>, values: &[Self]) {
+            encode_slice_small(output, values)
+        }
+        #[inline]
+        fn decode_vu128_slice(
+            input: &mut &[u8],
+            len: usize,
+            output: &mut Vec<Self>,
+        ) -> Result<(), TryFromSliceError> {
+            decode_slice_small(input, len, output, Self::from)
+        }
+    };
+    (signed) => {
+        #[inline]
+        fn encode_vu128_slice(output: &mut Vec<u8>, values: &[Self]) {
+            encode_slice_small_signed(output, values)
+        }
+        #[inline]
+        fn decode_vu128_slice(
+            input: &mut &[u8],
+            len: usize,
+            output: &mut Vec<Self>,
+        ) -> Result<(), TryFromSliceError> {
+            decode_slice_small(input, len, output, |x| Self::from(unzigzag(x)))
+        }
+    };
+}
+
+macro_rules! small_signed {
+    ($($t:ty),*) => {
+        $(
+            impl SmallSigned for $t {
+                #[inline]
+                fn low_i8(self) -> i8 {
+                    self as i8
+                }
+            }
+        )*
+    };
+}
+
+small_signed!(i8, i16, i32, i64, i128);
+
 impl ToFromVu128 for bool {
     type Buffer = [u8; 1];
     #[inline]
@@ -65,6 +232,7 @@ impl ToFromVu128 for u8 {
         let (v, c) = decode_u32(buf);
         (v as u8, c)
     }
+    small_slices!();
 }
 
 impl ToFromVu128 for i8 {
@@ -78,6 +246,7 @@ impl ToFromVu128 for i8 {
         let (v, c) = decode_i32(buf);
         (v as i8, c)
     }
+    small_slices!(signed);
 }
 
 impl ToFromVu128 for u16 {
@@ -91,6 +260,7 @@ impl ToFromVu128 for u16 {
         let (v, c) = decode_u32(buf);
         (v as u16, c)
     }
+    small_slices!();
 }
 
 impl ToFromVu128 for i16 {
@@ -104,6 +274,7 @@ impl ToFromVu128 for i16 {
         let (v, c) = decode_i32(buf);
         (v as i16, c)
     }
+    small_slices!(signed);
 }
 
 impl ToFromVu128 for u32 {
@@ -116,6 +287,7 @@ impl ToFromVu128 for u32 {
     fn decode_vu128(buf: &Self::Buffer) -> (Self, usize) {
         decode_u32(buf)
     }
+    small_slices!();
 }
 
 impl ToFromVu128 for i32 {
@@ -128,6 +300,7 @@ impl ToFromVu128 for i32 {
     fn decode_vu128(buf: &Self::Buffer) -> (Self, usize) {
         decode_i32(buf)
     }
+    small_slices!(signed);
 }
 
 impl ToFromVu128 for f32 {
@@ -152,6 +325,7 @@ impl ToFromVu128 for u64 {
     fn decode_vu128(buf: &Self::Buffer) -> (Self, usize) {
         decode_u64(buf)
     }
+    small_slices!();
 }
 
 impl ToFromVu128 for i64 {
@@ -164,6 +338,7 @@ impl ToFromVu128 for i64 {
     fn decode_vu128(buf: &Self::Buffer) -> (Self, usize) {
         decode_i64(buf)
     }
+    small_slices!(signed);
 }
 
 impl ToFromVu128 for f64 {
@@ -188,6 +363,7 @@ impl ToFromVu128 for u128 {
     fn decode_vu128(buf: &Self::Buffer) -> (Self, usize) {
         decode_u128(buf)
     }
+    small_slices!();
 }
 
 impl ToFromVu128 for i128 {
@@ -200,10 +376,12 @@ impl ToFromVu128 for i128 {
     fn decode_vu128(buf: &Self::Buffer) -> (Self, usize) {
         decode_i128(buf)
     }
+    small_slices!(signed);
 }
 
 pub trait WriteVu128 {
     fn write_vu128<T: ToFromVu128>(&mut self, value: T);
+    fn write_vu128_slice<T: ToFromVu128>(&mut self, values: &[T]);
 }
 
 impl WriteVu128 for Vec<u8> {
@@ -213,20 +391,39 @@ impl WriteVu128 for Vec<u8> {
         let len = T::encode_vu128(&mut bytes, value);
         self.extend_from_slice(&bytes.borrow()[0..len]);
     }
+    #[inline]
+    fn write_vu128_slice<T: ToFromVu128>(&mut self, values: &[T]) {
+        T::encode_vu128_slice(self, values)
+    }
 }
 
 pub trait ReadVu128 {
     fn read_vu128<T: ToFromVu128>(&mut self) -> Result<T, TryFromSliceError>;
+    /// Reads `len` values, appending them to `output`.
+    fn read_vu128_slice<T: ToFromVu128>(
+        &mut self,
+        len: usize,
+        output: &mut Vec<T>,
+    ) -> Result<(), TryFromSliceError>;
 }
 
 impl<'a> ReadVu128 for &'a [u8] {
     #[inline]
     fn read_vu128<T: ToFromVu128>(&mut self) -> Result<T, TryFromSliceError> {
-        let (value, len) =
-            T::decode_vu128(T::Buffer::try_from_slice(&self[..T::Buffer::ARRAY_LEN])?);
+        // A short input fails the conversion rather than panicking.
+        let buf = self.get(..T::Buffer::ARRAY_LEN).unwrap_or(self);
+        let (value, len) = T::decode_vu128(T::Buffer::try_from_slice(buf)?);
         *self = &self[len..];
         Ok(value)
     }
+    #[inline]
+    fn read_vu128_slice<T: ToFromVu128>(
+        &mut self,
+        len: usize,
+        output: &mut Vec<T>,
+    ) -> Result<(), TryFromSliceError> {
+        T::decode_vu128_slice(self, len, output)
+    }
 }
 
 pub const VU128_PADDING: usize = 17;
@@ -1,6 +1,7 @@
 extern crate test;
 
-use crate::{ReadVu128, WriteVu128, VU128_PADDING};
+use crate::{ReadVu128, ToFromVu128, WriteVu128, SMALL_BOUND, VU128_PADDING};
+use std::fmt::Debug;
 use rand::{thread_rng, Rng};
 use std::hint::black_box;
 use test::Bencher;
@@ -33,6 +34,97 @@ fn bench_1(bencher: &mut Bencher) {
     })
 }
 
+#[bench]
+fn bench_slice(bencher: &mut Bencher) {
+    let mut rng = thread_rng();
+    let values: Vec<u32> = (0..COUNT)
+        .map(|_| 2.0f32.powf(rng.gen_range(0.0f32..10.0f32)) as u32)
+        .collect();
+    let mut encoded = vec![];
+    let mut decoded = vec![];
+    bencher.iter(|| {
+        encoded.clear();
+        encoded.write_vu128_slice(black_box(&values));
+        encoded.resize(encoded.len() + VU128_PADDING, 0);
+        decoded.clear();
+        encoded
+            .as_slice()
+            .read_vu128_slice::<u32>(values.len(), &mut decoded)
+            .unwrap();
+    })
+}
+
+#[track_caller]
+fn test_slice<T: ToFromVu128 + Debug + PartialEq>(values: &[T]) {
+    let mut expected = vec![];
+    for value in values {
+        expected.write_vu128(*value);
+    }
+    let mut found = vec![];
+    found.write_vu128_slice(values);
+    assert_eq!(expected, found);
+    found.resize(found.len() + VU128_PADDING, 0);
+    let mut input = found.as_slice();
+    let mut decoded = vec![];
+    input.read_vu128_slice::<T>(values.len(), &mut decoded).unwrap();
+    assert_eq!(values, decoded);
+    assert_eq!(input.len(), VU128_PADDING);
+}
+
+#[test]
+fn test_small_bound() {
+    fn check<T: ToFromVu128 + From<u8> + Debug + PartialEq>() {
+        for x in 0..=SMALL_BOUND {
+            let mut output = vec![];
+            output.write_vu128(T::from(x));
+            if x < SMALL_BOUND {
+                assert_eq!(output, [x]);
+            }
+            output.resize(output.len() + VU128_PADDING, 0);
+            assert_eq!(output.as_slice().read_vu128::<T>().unwrap(), T::from(x));
+        }
+    }
+    check::<u8>();
+    check::<u16>();
+    check::<u32>();
+    check::<u64>();
+    check::<u128>();
+}
+
+#[test]
+fn test_slices() {
+    let mut rng = thread_rng();
+    for len in [0, 1, 15, 16, 17, 100] {
+        let small: Vec<u32> = (0..len).map(|_| rng.gen_range(0..0x80)).collect();
+        test_slice(&small);
+        let mut mixed = small.clone();
+        if len > 0 {
+            mixed[len / 2] = u32::MAX;
+        }
+        test_slice(&mixed);
+        let signed: Vec<i32> = (0..len).map(|_| rng.gen_range(-0x40..0x40)).collect();
+        test_slice(&signed);
+        let mut mixed = signed.clone();
+        if len > 0 {
+            mixed[len / 2] = i32::MIN;
+        }
+        test_slice(&mixed);
+        test_slice(&(0..len).map(|_| rng.gen::<i8>()).collect::<Vec<_>>());
+        test_slice(&(0..len).map(|_| rng.gen::<i64>() >> rng.gen_range(0..64)).collect::<Vec<_>>());
+        test_slice(&(0..len).map(|_| rng.gen::<i128>()).collect::<Vec<_>>());
+        test_slice(&(0..len).map(|_| rng.gen::<u64>() >> rng.gen_range(0..64)).collect::<Vec<_>>());
+        test_slice(&(0..len).map(|_| rng.gen::<u8>()).collect::<Vec<_>>());
+        test_slice(&(0..len).map(|_| rng.gen::<u128>()).collect::<Vec<_>>());
+        test_slice(&(0..len).map(|_| rng.gen::<i16>()).collect::<Vec<_>>());
+        test_slice(&(0..len).map(|_| rng.gen::<f64>()).collect::<Vec<_>>());
+        test_slice(&(0..len).map(|_| rng.gen::<bool>()).collect::<Vec<_>>());
+    }
+    let mut truncated = vec![];
+    truncated.write_vu128_slice(&[1u32; 20]);
+    let mut decoded = vec![];
+    assert!(truncated.as_slice().read_vu128_slice::<u32>(usize::MAX, &mut decoded).is_err());
+}
+
 #[test]
 fn test() {
     let n = 1722628047u64;
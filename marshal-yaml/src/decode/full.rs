@@ -0,0 +1,76 @@
+use marshal::context::Context;
+use marshal::de::Deserialize;
+use marshal_core::decode::depth_budget::{DepthBudgetDecoder, WithDepthBudget};
+use marshal_core::decode::poison::PoisonDecoder;
+use marshal_core::decode::{AnySpecDecoder, Decoder};
+use marshal_core::derive_decoder_for_newtype;
+
+use crate::decode::{SimpleYamlSpecDecoder, YamlAnyDecoder};
+
+pub struct YamlSpecDecoder<'de>(PoisonDecoder<DepthBudgetDecoder<SimpleYamlSpecDecoder<'de>>>);
+
+derive_decoder_for_newtype!(YamlSpecDecoder<'de>(PoisonDecoder<DepthBudgetDecoder<SimpleYamlSpecDecoder<'de>>>));
+
+pub struct YamlDecoderBuilder<'de> {
+    inner: YamlSpecDecoder<'de>,
+    depth_budget: usize,
+}
+
+impl<'de> YamlDecoderBuilder<'de> {
+    pub fn new(input: &'de str) -> Self {
+        YamlDecoderBuilder {
+            inner: YamlSpecDecoder(PoisonDecoder::new(DepthBudgetDecoder::new(
+                SimpleYamlSpecDecoder::new(input),
+            ))),
+            depth_budget: 100,
+        }
+    }
+    pub fn set_budget(&mut self, depth_budget: usize) -> &mut Self {
+        self.depth_budget = depth_budget;
+        self
+    }
+    /// Decodes the only document of the input. An empty input is a single null document.
+    pub fn build<'p>(&'p mut self) -> AnySpecDecoder<'p, 'de, YamlSpecDecoder<'de>> {
+        self.build_any(YamlAnyDecoder::Document)
+    }
+    /// Decodes every document of the input as a sequence.
+    pub fn build_stream<'p>(&'p mut self) -> AnySpecDecoder<'p, 'de, YamlSpecDecoder<'de>> {
+        self.build_any(YamlAnyDecoder::Stream)
+    }
+    fn build_any<'p>(
+        &'p mut self,
+        any: YamlAnyDecoder,
+    ) -> AnySpecDecoder<'p, 'de, YamlSpecDecoder<'de>> {
+        let any = WithDepthBudget::new(self.depth_budget, any);
+        let any = self.inner.0.start(any);
+        AnySpecDecoder::new(&mut self.inner, any)
+    }
+    /// Deserializes the document, reporting the line and column of the node that failed.
+    pub fn deserialize<T: Deserialize<YamlDecoder>>(mut self, ctx: Context) -> anyhow::Result<T> {
+        let result = T::deserialize(self.build(), ctx).map_err(|e| e.context(self.location()))?;
+        self.end()?;
+        Ok(result)
+    }
+    /// Deserializes each document of a multi-document stream.
+    pub fn deserialize_stream<T: Deserialize<YamlDecoder>>(
+        mut self,
+        ctx: Context,
+    ) -> anyhow::Result<Vec<T>> {
+        let result = Vec::<T>::deserialize(self.build_stream(), ctx)
+            .map_err(|e| e.context(self.location()))?;
+        self.end()?;
+        Ok(result)
+    }
+    pub fn location(&self) -> String {
+        self.inner.0.inner().inner().location()
+    }
+    pub fn end(self) -> anyhow::Result<()> {
+        Ok(self.inner.0.end()?.end()?.end()?)
+    }
+}
+
+pub struct YamlDecoder;
+
+impl Decoder for YamlDecoder {
+    type SpecDecoder<'de> = YamlSpecDecoder<'de>;
+}
@@ -0,0 +1,429 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+
+use marshal_core::decode::text::location;
+use marshal_core::decode::{
+    DecodeHint, DecodeVariantHint, SimpleDecoderView, SpecDecoder, WideInt,
+};
+use marshal_core::{Primitive, PrimitiveType};
+
+use crate::decode::parse::{YamlNode, YamlParser, YamlValue};
+use crate::is_shared;
+use crate::schema::{Resolved, YamlInt};
+
+pub mod full;
+mod parse;
+
+#[derive(Debug)]
+pub enum YamlDecoderError {
+    Eof,
+    UnexpectedCharacter(char),
+    BadIndentation,
+    BadEscape,
+    ExpectedColon,
+    MappingNotAllowed,
+    ComplexKey,
+    DuplicateKey(String),
+    UnknownAlias(String),
+    AliasBudget,
+    TooDeep,
+    IntegerOverflow,
+    BadBytes,
+    BadEnum,
+    MultipleDocuments(usize),
+}
+
+impl Display for YamlDecoderError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            YamlDecoderError::Eof => write!(f, "unexpected end of input"),
+            YamlDecoderError::UnexpectedCharacter(c) => write!(f, "unexpected character {:?}", c),
+            YamlDecoderError::BadIndentation => write!(f, "unexpected indentation"),
+            YamlDecoderError::BadEscape => write!(f, "invalid escape sequence"),
+            YamlDecoderError::ExpectedColon => write!(f, "expected `:` after a mapping key"),
+            YamlDecoderError::MappingNotAllowed => {
+                write!(f, "a mapping cannot start on the same line as its key")
+            }
+            YamlDecoderError::ComplexKey => write!(f, "`?` complex keys are not supported"),
+            YamlDecoderError::DuplicateKey(key) => write!(f, "duplicate key {:?}", key),
+            YamlDecoderError::UnknownAlias(name) => write!(f, "undefined alias {:?}", name),
+            YamlDecoderError::AliasBudget => write!(f, "aliases expand to too many nodes"),
+            YamlDecoderError::TooDeep => write!(f, "collections are nested too deeply"),
+            YamlDecoderError::IntegerOverflow => write!(f, "integer out of range"),
+            YamlDecoderError::BadBytes => write!(f, "bytes must be a sequence of integers"),
+            YamlDecoderError::BadEnum => {
+                write!(f, "enum must be a string or a mapping with one entry")
+            }
+            YamlDecoderError::MultipleDocuments(count) => {
+                write!(f, "expected one document, found {}", count)
+            }
+        }
+    }
+}
+
+impl std::error::Error for YamlDecoderError {}
+
+pub struct SimpleYamlSpecDecoder<'de> {
+    input: &'de str,
+    /// The offset of the most recent node, for reporting errors.
+    offset: usize,
+    /// Shared pointer ids for anchors whose names are not numbers.
+    anchor_ids: HashMap<String, usize>,
+}
+
+pub enum YamlAnyDecoder {
+    /// The single document of the input, which is parsed when it is first decoded.
+    Document,
+    /// Every document of the input, decoded as a sequence.
+    Stream,
+    Node {
+        node: YamlNode,
+        /// Set within `Some`, where a null is written as `{None: null}` to stay distinct.
+        cannot_be_null: bool,
+    },
+}
+
+pub enum YamlDiscriminantDecoder {
+    Unit(YamlNode),
+    Map(YamlNode, YamlNode),
+}
+
+impl<'de> SimpleYamlSpecDecoder<'de> {
+    pub fn new(input: &'de str) -> Self {
+        SimpleYamlSpecDecoder {
+            input,
+            offset: 0,
+            anchor_ids: HashMap::new(),
+        }
+    }
+    pub fn end(self) -> anyhow::Result<()> {
+        Ok(())
+    }
+    pub fn location(&self) -> String {
+        location(self.input, self.offset)
+    }
+    fn read_stream(&mut self) -> anyhow::Result<Vec<YamlNode>> {
+        let mut parser = YamlParser::new(self.input);
+        match parser.read_stream() {
+            Ok(documents) => Ok(documents),
+            Err(e) => {
+                self.offset = parser.offset;
+                Err(e.into())
+            }
+        }
+    }
+    /// The shared pointer id of an anchor. Numeric anchors, as written by the encoder, keep their
+    /// number, and other anchors count down from the largest id.
+    fn anchor_id(&mut self, anchor: &str) -> usize {
+        if let Ok(id) = anchor.parse() {
+            return id;
+        }
+        let next = usize::MAX - self.anchor_ids.len();
+        *self.anchor_ids.entry(anchor.to_string()).or_insert(next)
+    }
+    fn shared(&mut self, anchor: &str, inner: YamlNode, offset: usize) -> YamlNode {
+        let key = |name: &str| YamlNode::scalar(name.to_string(), false, offset);
+        let id = YamlNode::scalar(self.anchor_id(anchor).to_string(), true, offset);
+        YamlNode::new(
+            YamlValue::Map(vec![(key("id"), id), (key("inner"), inner)]),
+            offset,
+        )
+    }
+}
+
+impl WideInt for YamlInt {
+    fn natural_type(self) -> PrimitiveType {
+        match self {
+            YamlInt::Positive(x) if x <= u64::MAX as u128 => PrimitiveType::U64,
+            YamlInt::Positive(_) => PrimitiveType::U128,
+            YamlInt::Negative(x) if x <= i64::MAX as u128 + 1 => PrimitiveType::I64,
+            YamlInt::Negative(_) => PrimitiveType::I128,
+        }
+    }
+    fn unsigned(self) -> anyhow::Result<u128> {
+        match self {
+            YamlInt::Positive(x) => Ok(x),
+            YamlInt::Negative(_) => Err(YamlDecoderError::IntegerOverflow.into()),
+        }
+    }
+    fn signed(self) -> anyhow::Result<i128> {
+        match self {
+            YamlInt::Positive(x) => Ok(i128::try_from(x)?),
+            YamlInt::Negative(x) => Ok(0i128
+                .checked_sub_unsigned(x)
+                .ok_or(YamlDecoderError::IntegerOverflow)?),
+        }
+    }
+    fn float(self) -> f64 {
+        match self {
+            YamlInt::Positive(x) => x as f64,
+            YamlInt::Negative(x) => -(x as f64),
+        }
+    }
+}
+
+fn is_shared_hint(hint: &DecodeHint) -> bool {
+    matches!(hint, DecodeHint::Struct { name, .. } if is_shared(name))
+}
+
+/// Matches a mapping of the form `{None: null}` or `{Some: x}`, which holds an option within
+/// `Some`.
+fn nested_option(node: &YamlNode) -> Option<bool> {
+    match &node.value {
+        YamlValue::Map(entries) if entries.len() == 1 => match &entries[0].0.value {
+            YamlValue::Scalar(key) if key.text == "None" => Some(false),
+            YamlValue::Scalar(key) if key.text == "Some" => Some(true),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+impl<'de> SpecDecoder<'de> for SimpleYamlSpecDecoder<'de> {
+    type AnyDecoder = YamlAnyDecoder;
+    type SeqDecoder = std::vec::IntoIter<YamlNode>;
+    type MapDecoder = std::vec::IntoIter<(YamlNode, YamlNode)>;
+    type KeyDecoder = (YamlNode, YamlNode);
+    type ValueDecoder = YamlNode;
+    type DiscriminantDecoder = YamlDiscriminantDecoder;
+    type VariantDecoder = Option<YamlNode>;
+    type EnumCloser = ();
+    type SomeDecoder = (YamlNode, bool);
+    type SomeCloser = ();
+
+    fn decode(
+        &mut self,
+        any: Self::AnyDecoder,
+        hint: DecodeHint,
+    ) -> anyhow::Result<SimpleDecoderView<'de, Self>> {
+        let (mut node, cannot_be_null) = match any {
+            YamlAnyDecoder::Document => {
+                let mut documents = self.read_stream()?;
+                if documents.len() > 1 {
+                    self.offset = documents[1].offset;
+                    return Err(YamlDecoderError::MultipleDocuments(documents.len()).into());
+                }
+                let node = documents
+                    .pop()
+                    .unwrap_or_else(|| YamlNode::scalar(String::new(), true, 0));
+                (node, false)
+            }
+            YamlAnyDecoder::Stream => {
+                return Ok(SimpleDecoderView::Seq(self.read_stream()?.into_iter()));
+            }
+            YamlAnyDecoder::Node {
+                node,
+                cannot_be_null,
+            } => (node, cannot_be_null),
+        };
+        let offset = node.offset;
+        self.offset = offset;
+        // Shared pointers are anchored where they are first written and aliased afterwards.
+        if let YamlValue::Alias(name, target) = &node.value {
+            node = if is_shared_hint(&hint) {
+                let null = YamlNode::scalar(String::new(), true, offset);
+                self.shared(name, null, offset)
+            } else {
+                let mut target = (**target).clone();
+                target.offset = offset;
+                target
+            };
+        } else if is_shared_hint(&hint) {
+            if let Some(anchor) = node.anchor.take() {
+                node = self.shared(&anchor, node, offset);
+            }
+        }
+        if let DecodeHint::Option = hint {
+            if cannot_be_null {
+                if let Some(some) = nested_option(&node) {
+                    let YamlValue::Map(mut entries) = node.value else {
+                        unreachable!()
+                    };
+                    let (_, inner) = entries.pop().unwrap();
+                    return Ok(if some {
+                        SimpleDecoderView::Some((inner, false))
+                    } else {
+                        SimpleDecoderView::None
+                    });
+                }
+            }
+            if node.is_null() {
+                return Ok(SimpleDecoderView::None);
+            }
+            return Ok(SimpleDecoderView::Some((node, true)));
+        }
+        Ok(match (node.value, hint) {
+            (YamlValue::Alias(..), _) => unreachable!(),
+            (YamlValue::Scalar(scalar), hint) => match (scalar.resolve(), hint) {
+                (_, DecodeHint::String | DecodeHint::Identifier) => {
+                    SimpleDecoderView::String(Cow::Owned(scalar.text))
+                }
+                (Resolved::Str, DecodeHint::Enum { .. }) => SimpleDecoderView::Enum(
+                    YamlDiscriminantDecoder::Unit(YamlNode::scalar(scalar.text, false, offset)),
+                ),
+                (Resolved::Str, DecodeHint::Primitive(PrimitiveType::Char)) => {
+                    let mut chars = scalar.text.chars();
+                    match (chars.next(), chars.next()) {
+                        (Some(c), None) => SimpleDecoderView::Primitive(Primitive::Char(c)),
+                        _ => SimpleDecoderView::String(Cow::Owned(scalar.text)),
+                    }
+                }
+                (Resolved::Str, _) => SimpleDecoderView::String(Cow::Owned(scalar.text)),
+                (Resolved::Null, _) => SimpleDecoderView::Primitive(Primitive::Unit),
+                (Resolved::Bool(x), _) => SimpleDecoderView::Primitive(Primitive::Bool(x)),
+                (Resolved::Int(x), hint) => SimpleDecoderView::Primitive(x.into_primitive(&hint)?),
+                (Resolved::Float(x), DecodeHint::Primitive(PrimitiveType::F32)) => {
+                    SimpleDecoderView::Primitive(Primitive::F32(x as f32))
+                }
+                (Resolved::Float(x), _) => SimpleDecoderView::Primitive(Primitive::F64(x)),
+            },
+            // YAML has no bytes, so they are encoded as a sequence of integers.
+            (YamlValue::Seq(elements), DecodeHint::Bytes) => SimpleDecoderView::Bytes(Cow::Owned(
+                elements
+                    .into_iter()
+                    .map(|element| match element.value {
+                        YamlValue::Scalar(scalar) => match scalar.resolve() {
+                            Resolved::Int(x) => Ok(u8::try_from(x.unsigned()?)?),
+                            _ => Err(YamlDecoderError::BadBytes.into()),
+                        },
+                        _ => Err(YamlDecoderError::BadBytes.into()),
+                    })
+                    .collect::<anyhow::Result<Vec<u8>>>()?,
+            )),
+            (
+                YamlValue::Seq(elements),
+                DecodeHint::Primitive(PrimitiveType::Unit) | DecodeHint::UnitStruct { .. },
+            ) if elements.is_empty() => SimpleDecoderView::Primitive(Primitive::Unit),
+            (YamlValue::Seq(elements), _) => SimpleDecoderView::Seq(elements.into_iter()),
+            (YamlValue::Map(entries), DecodeHint::Enum { .. }) => {
+                let mut entries = entries.into_iter();
+                match (entries.next(), entries.next()) {
+                    (Some((key, value)), None) => {
+                        SimpleDecoderView::Enum(YamlDiscriminantDecoder::Map(key, value))
+                    }
+                    _ => return Err(YamlDecoderError::BadEnum.into()),
+                }
+            }
+            (YamlValue::Map(entries), _) => SimpleDecoderView::Map(entries.into_iter()),
+        })
+    }
+
+    fn is_human_readable(&self) -> bool {
+        true
+    }
+
+    fn decode_seq_next(
+        &mut self,
+        seq: &mut Self::SeqDecoder,
+    ) -> anyhow::Result<Option<Self::AnyDecoder>> {
+        Ok(seq.next().map(|node| YamlAnyDecoder::Node {
+            node,
+            cannot_be_null: false,
+        }))
+    }
+
+    fn decode_seq_exact_size(&self, seq: &Self::SeqDecoder) -> Option<usize> {
+        Some(seq.len())
+    }
+
+    fn decode_seq_end(&mut self, _seq: Self::SeqDecoder) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn decode_map_next(
+        &mut self,
+        map: &mut Self::MapDecoder,
+    ) -> anyhow::Result<Option<Self::KeyDecoder>> {
+        Ok(map.next())
+    }
+
+    fn decode_map_exact_size(&self, map: &Self::MapDecoder) -> Option<usize> {
+        Some(map.len())
+    }
+
+    fn decode_map_end(&mut self, _map: Self::MapDecoder) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn decode_entry_key(
+        &mut self,
+        (key, value): Self::KeyDecoder,
+    ) -> anyhow::Result<(Self::AnyDecoder, Self::ValueDecoder)> {
+        Ok((
+            YamlAnyDecoder::Node {
+                node: key,
+                cannot_be_null: false,
+            },
+            value,
+        ))
+    }
+
+    fn decode_entry_value(
+        &mut self,
+        value: Self::ValueDecoder,
+    ) -> anyhow::Result<Self::AnyDecoder> {
+        Ok(YamlAnyDecoder::Node {
+            node: value,
+            cannot_be_null: false,
+        })
+    }
+
+    fn decode_enum_discriminant(
+        &mut self,
+        e: Self::DiscriminantDecoder,
+    ) -> anyhow::Result<(Self::AnyDecoder, Self::VariantDecoder)> {
+        let (key, value) = match e {
+            YamlDiscriminantDecoder::Unit(node) => (node, None),
+            YamlDiscriminantDecoder::Map(key, value) => (key, Some(value)),
+        };
+        Ok((
+            YamlAnyDecoder::Node {
+                node: key,
+                cannot_be_null: false,
+            },
+            value,
+        ))
+    }
+
+    fn decode_enum_variant(
+        &mut self,
+        e: Self::VariantDecoder,
+        hint: DecodeVariantHint,
+    ) -> anyhow::Result<(SimpleDecoderView<'de, Self>, Self::EnumCloser)> {
+        match e {
+            None => Ok((SimpleDecoderView::Primitive(Primitive::Unit), ())),
+            Some(node) => Ok((
+                self.decode(
+                    YamlAnyDecoder::Node {
+                        node,
+                        cannot_be_null: false,
+                    },
+                    hint.content_hint(),
+                )?,
+                (),
+            )),
+        }
+    }
+
+    fn decode_enum_end(&mut self, _e: Self::EnumCloser) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn decode_some_inner(
+        &mut self,
+        (node, cannot_be_null): Self::SomeDecoder,
+    ) -> anyhow::Result<(Self::AnyDecoder, Self::SomeCloser)> {
+        Ok((
+            YamlAnyDecoder::Node {
+                node,
+                cannot_be_null,
+            },
+            (),
+        ))
+    }
+
+    fn decode_some_end(&mut self, _p: Self::SomeCloser) -> anyhow::Result<()> {
+        Ok(())
+    }
+}
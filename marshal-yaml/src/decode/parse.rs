@@ -0,0 +1,866 @@
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+
+use crate::decode::YamlDecoderError;
+use crate::schema::{resolve_plain, Resolved};
+
+const MAX_NESTING: usize = 128;
+/// The most nodes that aliases may copy in one stream, which bounds the work done for inputs
+/// such as "billion laughs".
+const MAX_ALIAS_EXPANSION: usize = 1 << 20;
+
+#[derive(Clone)]
+pub struct YamlNode {
+    pub(crate) value: YamlValue,
+    pub(crate) anchor: Option<String>,
+    pub(crate) offset: usize,
+    /// The number of nodes in this subtree, including the targets of aliases.
+    size: usize,
+}
+
+#[derive(Clone)]
+pub(crate) enum YamlValue {
+    Scalar(YamlScalar),
+    Seq(Vec<YamlNode>),
+    Map(Vec<(YamlNode, YamlNode)>),
+    Alias(String, Rc<YamlNode>),
+}
+
+#[derive(Clone)]
+pub(crate) struct YamlScalar {
+    pub(crate) text: String,
+    /// Whether the core schema resolves the text to a null, boolean or number when possible.
+    pub(crate) plain: bool,
+}
+
+impl YamlNode {
+    pub(crate) fn new(value: YamlValue, offset: usize) -> Self {
+        let size = match &value {
+            YamlValue::Scalar(_) => 1,
+            YamlValue::Seq(elements) => elements
+                .iter()
+                .fold(1usize, |size, element| size.saturating_add(element.size)),
+            YamlValue::Map(entries) => entries.iter().fold(1usize, |size, (key, value)| {
+                size.saturating_add(key.size).saturating_add(value.size)
+            }),
+            YamlValue::Alias(_, target) => target.size,
+        };
+        YamlNode {
+            value,
+            anchor: None,
+            offset,
+            size,
+        }
+    }
+    pub(crate) fn scalar(text: String, plain: bool, offset: usize) -> Self {
+        YamlNode::new(YamlValue::Scalar(YamlScalar { text, plain }), offset)
+    }
+    pub(crate) fn is_null(&self) -> bool {
+        match &self.value {
+            YamlValue::Scalar(scalar) => matches!(scalar.resolve(), Resolved::Null),
+            YamlValue::Alias(_, target) => target.is_null(),
+            _ => false,
+        }
+    }
+}
+
+impl YamlScalar {
+    pub(crate) fn resolve(&self) -> Resolved {
+        if !self.plain {
+            return Resolved::Str;
+        }
+        resolve_plain(&self.text)
+    }
+}
+
+/// The anchor and tag written before a node.
+#[derive(Default)]
+struct Properties {
+    anchor: Option<String>,
+    tag: Option<String>,
+}
+
+pub(crate) struct YamlParser<'de> {
+    input: &'de str,
+    pub(crate) offset: usize,
+    line_start: usize,
+    nesting: usize,
+    anchors: HashMap<String, Rc<YamlNode>>,
+    expansion: usize,
+}
+
+fn is_blank(c: u8) -> bool {
+    c == b' ' || c == b'\t'
+}
+
+fn is_flow_indicator(c: u8) -> bool {
+    matches!(c, b',' | b'[' | b']' | b'{' | b'}')
+}
+
+impl<'de> YamlParser<'de> {
+    pub fn new(input: &'de str) -> Self {
+        YamlParser {
+            input,
+            offset: 0,
+            line_start: 0,
+            nesting: 0,
+            anchors: HashMap::new(),
+            expansion: 0,
+        }
+    }
+    fn rest(&self) -> &'de str {
+        &self.input[self.offset..]
+    }
+    fn peek(&self) -> Option<u8> {
+        self.peek_at(0)
+    }
+    fn peek_at(&self, index: usize) -> Option<u8> {
+        self.input.as_bytes().get(self.offset + index).copied()
+    }
+    fn column(&self) -> isize {
+        (self.offset - self.line_start) as isize
+    }
+    /// Whether the byte at `index` ends a token, as a blank, a line break or the end of input.
+    fn is_separator_at(&self, index: usize) -> bool {
+        match self.peek_at(index) {
+            None => true,
+            Some(c) => is_blank(c) || c == b'\n' || c == b'\r',
+        }
+    }
+    fn unexpected(&self) -> YamlDecoderError {
+        match self.rest().chars().next() {
+            Some(c) => YamlDecoderError::UnexpectedCharacter(c),
+            None => YamlDecoderError::Eof,
+        }
+    }
+    fn read_exact(&mut self, c: u8) -> Result<(), YamlDecoderError> {
+        if self.peek() == Some(c) {
+            self.offset += 1;
+            Ok(())
+        } else {
+            Err(self.unexpected())
+        }
+    }
+    fn read_char(&mut self) -> Result<char, YamlDecoderError> {
+        let c = self.rest().chars().next().ok_or(YamlDecoderError::Eof)?;
+        self.offset += c.len_utf8();
+        Ok(c)
+    }
+    fn enter(&mut self) -> Result<(), YamlDecoderError> {
+        self.nesting += 1;
+        if self.nesting > MAX_NESTING {
+            return Err(YamlDecoderError::TooDeep);
+        }
+        Ok(())
+    }
+    fn exit(&mut self) {
+        self.nesting -= 1;
+    }
+    fn try_read_newline(&mut self) -> bool {
+        let len = if self.rest().starts_with("\r\n") {
+            2
+        } else if let Some(b'\n' | b'\r') = self.peek() {
+            1
+        } else {
+            return false;
+        };
+        self.offset += len;
+        self.line_start = self.offset;
+        true
+    }
+    fn read_space(&mut self) {
+        while let Some(c) = self.peek() {
+            if !is_blank(c) {
+                break;
+            }
+            self.offset += 1;
+        }
+    }
+    fn is_comment(&self) -> bool {
+        self.peek() == Some(b'#')
+            && (self.offset == self.line_start || is_blank(self.input.as_bytes()[self.offset - 1]))
+    }
+    fn read_comment(&mut self) {
+        if self.is_comment() {
+            while let Some(c) = self.peek() {
+                if c == b'\n' || c == b'\r' {
+                    break;
+                }
+                self.offset += 1;
+            }
+        }
+    }
+    /// Whether only blanks and a comment remain on the current line.
+    fn at_line_end(&mut self) -> bool {
+        self.read_space();
+        matches!(self.peek(), None | Some(b'\n' | b'\r')) || self.is_comment()
+    }
+    fn read_line_end(&mut self) -> Result<(), YamlDecoderError> {
+        if !self.at_line_end() {
+            return Err(self.unexpected());
+        }
+        Ok(())
+    }
+    /// Skips blanks, comments and line breaks, stopping at the next content.
+    fn read_blank_lines(&mut self) {
+        loop {
+            self.read_space();
+            self.read_comment();
+            if !self.try_read_newline() {
+                break;
+            }
+        }
+    }
+    fn is_document_marker(&self) -> bool {
+        self.offset == self.line_start
+            && (self.rest().starts_with("---") || self.rest().starts_with("..."))
+            && self.is_separator_at(3)
+    }
+    fn try_read_marker(&mut self, marker: &str) -> bool {
+        if self.is_document_marker() && self.rest().starts_with(marker) {
+            self.offset += 3;
+            true
+        } else {
+            false
+        }
+    }
+    fn at_document_end(&self) -> bool {
+        self.peek().is_none() || self.is_document_marker()
+    }
+    fn is_seq_entry(&self) -> bool {
+        self.peek() == Some(b'-') && self.is_separator_at(1)
+    }
+    fn empty_node(&self) -> YamlNode {
+        YamlNode::scalar(String::new(), true, self.offset)
+    }
+    fn read_name(&mut self) -> Result<String, YamlDecoderError> {
+        let start = self.offset;
+        while let Some(c) = self.peek() {
+            if is_blank(c) || c == b'\n' || c == b'\r' || is_flow_indicator(c) {
+                break;
+            }
+            self.offset += 1;
+        }
+        if start == self.offset {
+            return Err(self.unexpected());
+        }
+        Ok(self.input[start..self.offset].to_string())
+    }
+    fn read_properties(&mut self, flow: bool) -> Result<Properties, YamlDecoderError> {
+        let mut properties = Properties::default();
+        loop {
+            match self.peek() {
+                Some(b'&') if properties.anchor.is_none() => {
+                    self.offset += 1;
+                    properties.anchor = Some(self.read_name()?);
+                }
+                Some(b'!') if properties.tag.is_none() => {
+                    properties.tag = Some(self.read_name()?);
+                }
+                _ => return Ok(properties),
+            }
+            if flow {
+                self.read_flow_space();
+            } else {
+                self.read_space();
+            }
+        }
+    }
+    fn apply(&mut self, properties: Properties, mut node: YamlNode) -> YamlNode {
+        if let (Some(tag), YamlValue::Scalar(scalar)) = (&properties.tag, &mut node.value) {
+            match &**tag {
+                "!!str" => scalar.plain = false,
+                "!!null" | "!!bool" | "!!int" | "!!float" => scalar.plain = true,
+                _ => {}
+            }
+        }
+        if let Some(anchor) = properties.anchor {
+            node.anchor = Some(anchor.clone());
+            self.anchors.insert(anchor, Rc::new(node.clone()));
+        }
+        node
+    }
+    fn read_alias(&mut self) -> Result<YamlNode, YamlDecoderError> {
+        let start = self.offset;
+        self.read_exact(b'*')?;
+        let name = self.read_name()?;
+        let Some(target) = self.anchors.get(&name).cloned() else {
+            self.offset = start;
+            return Err(YamlDecoderError::UnknownAlias(name));
+        };
+        self.expansion = self.expansion.saturating_add(target.size);
+        if self.expansion > MAX_ALIAS_EXPANSION {
+            self.offset = start;
+            return Err(YamlDecoderError::AliasBudget);
+        }
+        Ok(YamlNode::new(YamlValue::Alias(name, target), start))
+    }
+    fn read_hex(&mut self, len: usize) -> Result<char, YamlDecoderError> {
+        let digits = self.rest().get(..len).ok_or(YamlDecoderError::BadEscape)?;
+        let code = u32::from_str_radix(digits, 16).map_err(|_| YamlDecoderError::BadEscape)?;
+        let c = char::from_u32(code).ok_or(YamlDecoderError::BadEscape)?;
+        self.offset += len;
+        Ok(c)
+    }
+    fn read_escape(&mut self, output: &mut String) -> Result<(), YamlDecoderError> {
+        let c = self.read_char()?;
+        match c {
+            '0' => output.push('\0'),
+            'a' => output.push('\u{7}'),
+            'b' => output.push('\u{8}'),
+            't' | '\t' => output.push('\t'),
+            'n' => output.push('\n'),
+            'v' => output.push('\u{b}'),
+            'f' => output.push('\u{c}'),
+            'r' => output.push('\r'),
+            'e' => output.push('\u{1b}'),
+            ' ' | '"' | '/' | '\\' => output.push(c),
+            'N' => output.push('\u{85}'),
+            '_' => output.push('\u{a0}'),
+            'L' => output.push('\u{2028}'),
+            'P' => output.push('\u{2029}'),
+            'x' => output.push(self.read_hex(2)?),
+            'u' => output.push(self.read_hex(4)?),
+            'U' => output.push(self.read_hex(8)?),
+            _ => {
+                self.offset -= c.len_utf8();
+                return Err(YamlDecoderError::BadEscape);
+            }
+        }
+        Ok(())
+    }
+    /// Folds a line break within a quoted scalar: a single break becomes a space and each
+    /// following empty line becomes a newline.
+    fn read_quoted_break(&mut self, output: &mut String) {
+        output.truncate(output.trim_end_matches([' ', '\t']).len());
+        self.try_read_newline();
+        let mut breaks = 0;
+        loop {
+            self.read_space();
+            if !self.try_read_newline() {
+                break;
+            }
+            breaks += 1;
+        }
+        if breaks == 0 {
+            output.push(' ');
+        }
+        for _ in 0..breaks {
+            output.push('\n');
+        }
+    }
+    fn read_double_quoted(&mut self) -> Result<String, YamlDecoderError> {
+        self.read_exact(b'"')?;
+        let mut output = String::new();
+        loop {
+            match self.peek() {
+                None => return Err(YamlDecoderError::Eof),
+                Some(b'"') => {
+                    self.offset += 1;
+                    return Ok(output);
+                }
+                Some(b'\\') => {
+                    self.offset += 1;
+                    if self.try_read_newline() {
+                        // An escaped line break joins the lines without a space.
+                        self.read_space();
+                    } else {
+                        self.read_escape(&mut output)?;
+                    }
+                }
+                Some(b'\n' | b'\r') => self.read_quoted_break(&mut output),
+                Some(_) => output.push(self.read_char()?),
+            }
+        }
+    }
+    fn read_single_quoted(&mut self) -> Result<String, YamlDecoderError> {
+        self.read_exact(b'\'')?;
+        let mut output = String::new();
+        loop {
+            match self.peek() {
+                None => return Err(YamlDecoderError::Eof),
+                Some(b'\'') if self.peek_at(1) == Some(b'\'') => {
+                    self.offset += 2;
+                    output.push('\'');
+                }
+                Some(b'\'') => {
+                    self.offset += 1;
+                    return Ok(output);
+                }
+                Some(b'\n' | b'\r') => self.read_quoted_break(&mut output),
+                Some(_) => output.push(self.read_char()?),
+            }
+        }
+    }
+    /// Reads the rest of a plain scalar on the current line.
+    fn read_plain_line(&mut self, flow: bool, first: bool) -> &'de str {
+        let start = self.offset;
+        if first {
+            match self.peek() {
+                Some(b'-' | b'?' | b':') if !self.is_separator_at(1) => {}
+                Some(
+                    b'-' | b'?' | b':' | b',' | b'[' | b']' | b'{' | b'}' | b'#' | b'&' | b'*'
+                    | b'!' | b'|' | b'>' | b'\'' | b'"' | b'%' | b'@' | b'`',
+                ) => return "",
+                _ => {}
+            }
+        }
+        let mut end = self.offset;
+        while let Some(c) = self.peek() {
+            match c {
+                b'\n' | b'\r' => break,
+                b':' if self.is_separator_at(1)
+                    || flow && self.peek_at(1).is_some_and(is_flow_indicator) =>
+                {
+                    break
+                }
+                b'#' if self.offset > start && is_blank(self.input.as_bytes()[self.offset - 1]) => {
+                    break
+                }
+                _ if flow && is_flow_indicator(c) => break,
+                _ => {}
+            }
+            self.offset += self.rest().chars().next().unwrap().len_utf8();
+            if !is_blank(c) {
+                end = self.offset;
+            }
+        }
+        &self.input[start..end]
+    }
+    /// Continues a plain scalar onto following lines, which must be indented more than the
+    /// parent in block context.
+    fn read_plain_continuation(&mut self, text: &mut String, parent_indent: Option<isize>) {
+        loop {
+            let (offset, line_start) = (self.offset, self.line_start);
+            self.read_space();
+            if self.is_comment() || !self.try_read_newline() {
+                (self.offset, self.line_start) = (offset, line_start);
+                return;
+            }
+            let mut breaks = 1;
+            loop {
+                self.read_space();
+                if !self.try_read_newline() {
+                    break;
+                }
+                breaks += 1;
+            }
+            let stop = match parent_indent {
+                Some(indent) => self.column() <= indent,
+                None => self
+                    .peek()
+                    .is_some_and(|c| is_flow_indicator(c) || c == b':'),
+            };
+            if stop || self.at_document_end() || self.is_comment() {
+                (self.offset, self.line_start) = (offset, line_start);
+                return;
+            }
+            let line = self.read_plain_line(parent_indent.is_none(), false);
+            if line.is_empty() {
+                (self.offset, self.line_start) = (offset, line_start);
+                return;
+            }
+            if breaks == 1 {
+                text.push(' ');
+            }
+            for _ in 1..breaks {
+                text.push('\n');
+            }
+            text.push_str(line);
+        }
+    }
+    fn read_block_scalar(&mut self, parent_indent: isize) -> Result<String, YamlDecoderError> {
+        let folded = self.peek() == Some(b'>');
+        self.offset += 1;
+        let mut keep = None;
+        let mut explicit = None;
+        for _ in 0..2 {
+            match self.peek() {
+                Some(b'+') if keep.is_none() => keep = Some(true),
+                Some(b'-') if keep.is_none() => keep = Some(false),
+                Some(c @ b'1'..=b'9') if explicit.is_none() => explicit = Some((c - b'0') as isize),
+                _ => break,
+            }
+            self.offset += 1;
+        }
+        self.read_line_end()?;
+        self.read_comment();
+        if !self.try_read_newline() {
+            return Ok(String::new());
+        }
+        let min_indent = (parent_indent + 1).max(1);
+        let indent = match explicit {
+            Some(explicit) => parent_indent.max(0) + explicit,
+            None => {
+                // The first line with content sets the indentation.
+                let mut indent = 0;
+                for line in self.rest().split('\n') {
+                    let line = line.trim_end_matches('\r');
+                    if !line.trim_start_matches(' ').is_empty() {
+                        indent = (line.len() - line.trim_start_matches(' ').len()) as isize;
+                        break;
+                    }
+                }
+                indent.max(min_indent)
+            }
+        };
+        let mut lines: Vec<&str> = vec![];
+        loop {
+            let line_begin = self.offset;
+            let mut spaces = 0;
+            while spaces < indent && self.peek() == Some(b' ') {
+                self.offset += 1;
+                spaces += 1;
+            }
+            if self.try_read_newline() {
+                lines.push("");
+                continue;
+            }
+            if self.peek().is_none() || spaces < indent {
+                self.offset = line_begin;
+                break;
+            }
+            let start = self.offset;
+            while !matches!(self.peek(), None | Some(b'\n' | b'\r')) {
+                self.offset += 1;
+            }
+            lines.push(&self.input[start..self.offset]);
+            if !self.try_read_newline() {
+                break;
+            }
+        }
+        let content = lines
+            .iter()
+            .rposition(|line| !line.is_empty())
+            .map_or(0, |x| x + 1);
+        let mut output = String::new();
+        let mut breaks = 0;
+        let mut previous: Option<&str> = None;
+        let indented = |line: &str| line.starts_with([' ', '\t']);
+        for line in &lines[..content] {
+            if line.is_empty() {
+                breaks += 1;
+                continue;
+            }
+            if let Some(previous) = previous {
+                // Folding joins adjacent lines with a space, and empty lines between them
+                // stand for the line breaks.
+                if !folded || indented(previous) || indented(line) {
+                    output.push('\n');
+                } else if breaks == 0 {
+                    output.push(' ');
+                }
+            }
+            for _ in 0..breaks {
+                output.push('\n');
+            }
+            output.push_str(line);
+            previous = Some(line);
+            breaks = 0;
+        }
+        match keep {
+            Some(false) => {}
+            None if content > 0 => output.push('\n'),
+            None => {}
+            Some(true) => {
+                if content > 0 {
+                    output.push('\n');
+                }
+                for _ in content..lines.len() {
+                    output.push('\n');
+                }
+            }
+        }
+        Ok(output)
+    }
+    /// Skips blanks, line breaks and comments within a flow collection.
+    fn read_flow_space(&mut self) {
+        loop {
+            self.read_space();
+            self.read_comment();
+            if !self.try_read_newline() {
+                break;
+            }
+        }
+    }
+    /// Whether a `:` at the current position separates a key from its value.
+    fn at_value_indicator(&self, flow: bool, json_key: bool) -> bool {
+        self.peek() == Some(b':')
+            && (json_key
+                || self.is_separator_at(1)
+                || flow && self.peek_at(1).is_some_and(is_flow_indicator))
+    }
+    fn read_flow_value(&mut self) -> Result<YamlNode, YamlDecoderError> {
+        self.read_flow_space();
+        match self.peek() {
+            Some(b',' | b']' | b'}') => Ok(self.empty_node()),
+            _ => Ok(self.read_flow_node()?.0),
+        }
+    }
+    fn read_flow_seq(&mut self) -> Result<YamlValue, YamlDecoderError> {
+        self.read_exact(b'[')?;
+        let mut elements = vec![];
+        loop {
+            self.read_flow_space();
+            if self.peek() == Some(b']') {
+                self.offset += 1;
+                break;
+            }
+            let (mut element, json_key) = self.read_flow_node()?;
+            self.read_flow_space();
+            if self.at_value_indicator(true, json_key) {
+                // A single pair is a mapping with one entry.
+                self.offset += 1;
+                let offset = element.offset;
+                let value = self.read_flow_value()?;
+                element = YamlNode::new(YamlValue::Map(vec![(element, value)]), offset);
+            }
+            elements.push(element);
+            self.read_flow_space();
+            if self.peek() == Some(b',') {
+                self.offset += 1;
+            } else {
+                self.read_exact(b']')?;
+                break;
+            }
+        }
+        Ok(YamlValue::Seq(elements))
+    }
+    fn read_flow_map(&mut self) -> Result<YamlValue, YamlDecoderError> {
+        self.read_exact(b'{')?;
+        let mut entries = vec![];
+        let mut keys = HashSet::new();
+        loop {
+            self.read_flow_space();
+            if self.peek() == Some(b'}') {
+                self.offset += 1;
+                break;
+            }
+            let (key, json_key) = self.read_flow_node()?;
+            self.read_flow_space();
+            let value = if self.at_value_indicator(true, json_key) {
+                self.offset += 1;
+                self.read_flow_value()?
+            } else {
+                self.empty_node()
+            };
+            self.check_duplicate(&mut keys, &key)?;
+            entries.push((key, value));
+            self.read_flow_space();
+            if self.peek() == Some(b',') {
+                self.offset += 1;
+            } else {
+                self.read_exact(b'}')?;
+                break;
+            }
+        }
+        Ok(YamlValue::Map(entries))
+    }
+    /// Reads a node in flow context, and whether it may be a key directly followed by `:`.
+    fn read_flow_node(&mut self) -> Result<(YamlNode, bool), YamlDecoderError> {
+        self.enter()?;
+        let properties = self.read_properties(true)?;
+        let (node, json_key) = self.read_inline_node(None)?;
+        self.exit();
+        Ok((self.apply(properties, node), json_key))
+    }
+    /// Reads a node that starts on the current line: a flow collection, a quoted scalar, an
+    /// alias or a plain scalar. Plain scalars only read the current line in block context,
+    /// where `parent_indent` is set.
+    fn read_inline_node(
+        &mut self,
+        parent_indent: Option<isize>,
+    ) -> Result<(YamlNode, bool), YamlDecoderError> {
+        let start = self.offset;
+        let flow = parent_indent.is_none();
+        let value = match self.peek() {
+            Some(b'[') => self.read_flow_seq()?,
+            Some(b'{') => self.read_flow_map()?,
+            Some(b'"') => YamlValue::Scalar(YamlScalar {
+                text: self.read_double_quoted()?,
+                plain: false,
+            }),
+            Some(b'\'') => YamlValue::Scalar(YamlScalar {
+                text: self.read_single_quoted()?,
+                plain: false,
+            }),
+            Some(b'*') => return Ok((self.read_alias()?, false)),
+            Some(b',' | b']' | b'}') if flow => return Ok((self.empty_node(), false)),
+            _ => {
+                let mut text = self.read_plain_line(flow, true).to_string();
+                if text.is_empty() {
+                    return Err(self.unexpected());
+                }
+                if flow {
+                    self.read_plain_continuation(&mut text, None);
+                }
+                return Ok((YamlNode::scalar(text, true, start), false));
+            }
+        };
+        Ok((YamlNode::new(value, start), true))
+    }
+    /// Records the key of a new mapping entry in `keys`, the scalar keys seen so far.
+    fn check_duplicate(
+        &mut self,
+        keys: &mut HashSet<String>,
+        key: &YamlNode,
+    ) -> Result<(), YamlDecoderError> {
+        if let YamlValue::Scalar(scalar) = &key.value {
+            if !keys.insert(scalar.text.clone()) {
+                self.offset = key.offset;
+                return Err(YamlDecoderError::DuplicateKey(scalar.text.clone()));
+            }
+        }
+        Ok(())
+    }
+    fn read_block_seq(&mut self) -> Result<YamlNode, YamlDecoderError> {
+        let column = self.column();
+        let start = self.offset;
+        let mut elements = vec![];
+        loop {
+            self.offset += 1;
+            elements.push(self.read_value(column, false)?);
+            self.read_blank_lines();
+            if self.at_document_end() || self.column() < column {
+                break;
+            }
+            if self.column() > column {
+                return Err(YamlDecoderError::BadIndentation);
+            }
+            if !self.is_seq_entry() {
+                break;
+            }
+        }
+        Ok(YamlNode::new(YamlValue::Seq(elements), start))
+    }
+    fn read_block_map(
+        &mut self,
+        column: isize,
+        first_key: YamlNode,
+    ) -> Result<YamlNode, YamlDecoderError> {
+        let start = first_key.offset;
+        let mut entries = vec![];
+        let mut keys = HashSet::new();
+        let mut key = first_key;
+        loop {
+            self.read_exact(b':')?;
+            let value = self.read_value(column, true)?;
+            self.check_duplicate(&mut keys, &key)?;
+            entries.push((key, value));
+            self.read_blank_lines();
+            if self.at_document_end() || self.column() < column || self.is_seq_entry() {
+                break;
+            }
+            if self.column() > column {
+                return Err(YamlDecoderError::BadIndentation);
+            }
+            if self.peek() == Some(b'?') && self.is_separator_at(1) {
+                return Err(YamlDecoderError::ComplexKey);
+            }
+            let properties = self.read_properties(false)?;
+            let (node, json_key) = self.read_inline_node(Some(column))?;
+            key = self.apply(properties, node);
+            self.read_space();
+            if !self.at_value_indicator(false, json_key) {
+                self.offset = key.offset;
+                return Err(YamlDecoderError::ExpectedColon);
+            }
+        }
+        Ok(YamlNode::new(YamlValue::Map(entries), start))
+    }
+    /// Reads a block node whose content starts at the current position. Properties written on
+    /// the same line as the first key of a mapping belong to that key.
+    fn read_block_node(
+        &mut self,
+        parent_indent: isize,
+        allow_map: bool,
+        properties: Properties,
+    ) -> Result<YamlNode, YamlDecoderError> {
+        let column = self.column();
+        let start = self.offset;
+        if self.is_seq_entry() {
+            let node = self.read_block_seq()?;
+            return Ok(self.apply(properties, node));
+        }
+        match self.peek() {
+            Some(b'|' | b'>') => {
+                let text = self.read_block_scalar(parent_indent)?;
+                let node = YamlNode::scalar(text, false, start);
+                return Ok(self.apply(properties, node));
+            }
+            Some(b'?') if self.is_separator_at(1) => return Err(YamlDecoderError::ComplexKey),
+            _ => {}
+        }
+        let (node, json_key) = self.read_inline_node(Some(parent_indent))?;
+        self.read_space();
+        if self.at_value_indicator(false, json_key) {
+            if !allow_map {
+                return Err(YamlDecoderError::MappingNotAllowed);
+            }
+            let key = self.apply(properties, node);
+            return self.read_block_map(column, key);
+        }
+        let mut node = node;
+        if let YamlValue::Scalar(YamlScalar { text, plain: true }) = &mut node.value {
+            self.read_plain_continuation(text, Some(parent_indent));
+        }
+        self.read_line_end()?;
+        Ok(self.apply(properties, node))
+    }
+    /// Reads the value after a `-`, a `key:` or the start of a document.
+    fn read_value(
+        &mut self,
+        parent_indent: isize,
+        in_map: bool,
+    ) -> Result<YamlNode, YamlDecoderError> {
+        self.enter()?;
+        self.read_space();
+        let properties = self.read_properties(false)?;
+        let node = if self.at_line_end() {
+            self.read_blank_lines();
+            let indent = self.column();
+            let node = if self.at_document_end() {
+                self.empty_node()
+            } else if indent > parent_indent {
+                self.read_block_node(parent_indent, true, Properties::default())?
+            } else if in_map && indent == parent_indent && self.is_seq_entry() {
+                // A sequence may be indented as much as the key that holds it.
+                self.read_block_seq()?
+            } else {
+                self.empty_node()
+            };
+            self.apply(properties, node)
+        } else {
+            self.read_block_node(parent_indent, !in_map, properties)?
+        };
+        self.exit();
+        Ok(node)
+    }
+    pub fn read_stream(&mut self) -> Result<Vec<YamlNode>, YamlDecoderError> {
+        let mut documents = vec![];
+        loop {
+            self.read_blank_lines();
+            while self.column() == 0 && self.peek() == Some(b'%') {
+                // Directives do not change how the core schema is read.
+                while !matches!(self.peek(), None | Some(b'\n' | b'\r')) {
+                    self.offset += 1;
+                }
+                self.read_blank_lines();
+            }
+            if self.peek().is_none() {
+                break;
+            }
+            if self.try_read_marker("...") {
+                continue;
+            }
+            self.try_read_marker("---");
+            documents.push(self.read_value(-1, false)?);
+            self.read_blank_lines();
+            if self.peek().is_none() || self.try_read_marker("...") || self.is_document_marker() {
+                continue;
+            }
+            return Err(self.unexpected());
+        }
+        Ok(documents)
+    }
+}
@@ -0,0 +1,57 @@
+use marshal::context::Context;
+use marshal::ser::Serialize;
+use marshal_core::derive_encoder_for_newtype;
+use marshal_core::encode::poison::PoisonEncoder;
+use marshal_core::encode::{AnySpecEncoder, Encoder};
+
+use crate::encode::{SimpleYamlSpecEncoder, YamlAnySpecEncoder};
+
+pub struct YamlSpecEncoder(PoisonEncoder<SimpleYamlSpecEncoder>);
+
+derive_encoder_for_newtype!(YamlSpecEncoder(PoisonEncoder<SimpleYamlSpecEncoder>));
+
+/// Writes block style YAML, choosing the plainest scalar style that reads back as the same value.
+/// Each value built with [Self::build] becomes one document of the stream.
+pub struct YamlEncoderBuilder {
+    inner: YamlSpecEncoder,
+}
+
+impl YamlEncoderBuilder {
+    pub fn new() -> Self {
+        YamlEncoderBuilder {
+            inner: YamlSpecEncoder(PoisonEncoder::new(SimpleYamlSpecEncoder::new())),
+        }
+    }
+    pub fn build(&mut self) -> AnySpecEncoder<'_, YamlSpecEncoder> {
+        let any = self.inner.0.start(YamlAnySpecEncoder::new());
+        AnySpecEncoder::new(&mut self.inner, any)
+    }
+    pub fn end(self) -> anyhow::Result<String> {
+        self.inner.0.end()?.end()
+    }
+    pub fn serialize<T: ?Sized + Serialize<YamlEncoder>>(
+        mut self,
+        value: &T,
+        ctx: Context,
+    ) -> anyhow::Result<String> {
+        value.serialize(self.build(), ctx)?;
+        self.end()
+    }
+    /// Serializes each value as a document of a multi-document stream.
+    pub fn serialize_stream<T: Serialize<YamlEncoder>>(
+        mut self,
+        values: &[T],
+        mut ctx: Context,
+    ) -> anyhow::Result<String> {
+        for value in values {
+            value.serialize(self.build(), ctx.reborrow())?;
+        }
+        self.end()
+    }
+}
+
+pub struct YamlEncoder;
+
+impl Encoder for YamlEncoder {
+    type SpecEncoder<'en> = YamlSpecEncoder;
+}
@@ -0,0 +1,461 @@
+use std::fmt::{Display, Formatter};
+
+use marshal_core::encode::SpecEncoder;
+use marshal_core::Primitive;
+
+use crate::encode::render::render_stream;
+use crate::is_shared;
+
+pub mod full;
+mod render;
+
+#[derive(Debug)]
+pub enum YamlEncoderError {
+    SharedWithoutId,
+}
+
+impl Display for YamlEncoderError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            YamlEncoderError::SharedWithoutId => {
+                write!(f, "shared pointer was encoded without an integer id")
+            }
+        }
+    }
+}
+
+impl std::error::Error for YamlEncoderError {}
+
+pub(crate) enum YamlValue {
+    Null,
+    Bool(bool),
+    /// An integer in decimal, which may not fit in 64 bits.
+    Integer(String),
+    Float(f64),
+    String(String),
+    Seq(Vec<YamlNode>),
+    Map(Vec<(YamlNode, YamlNode)>),
+    Alias(usize),
+}
+
+pub(crate) struct YamlNode {
+    pub(crate) anchor: Option<usize>,
+    pub(crate) value: YamlValue,
+}
+
+/// A container that is being encoded.
+enum Frame {
+    Seq(Vec<YamlNode>),
+    Map {
+        entries: Vec<(YamlNode, YamlNode)>,
+        /// The key of the entry whose value is expected next.
+        key: Option<YamlNode>,
+    },
+    /// A shared pointer from `marshal-shared`, which becomes an anchor where `inner` is present
+    /// and an alias otherwise.
+    Shared {
+        id: Option<usize>,
+        inner: Option<YamlNode>,
+        field: &'static str,
+    },
+}
+
+#[derive(Copy, Clone)]
+pub struct YamlAnySpecEncoder {
+    /// Set within `Some`, where null would be ambiguous with `None`.
+    cannot_be_null: bool,
+}
+
+pub struct YamlSomeCloser {
+    cannot_be_null: bool,
+}
+
+pub struct SimpleYamlSpecEncoder {
+    stack: Vec<Frame>,
+    documents: Vec<YamlNode>,
+}
+
+impl YamlAnySpecEncoder {
+    pub(crate) fn new() -> Self {
+        YamlAnySpecEncoder {
+            cannot_be_null: false,
+        }
+    }
+}
+
+fn value(value: YamlValue) -> YamlNode {
+    YamlNode {
+        anchor: None,
+        value,
+    }
+}
+
+fn string(x: &str) -> YamlNode {
+    value(YamlValue::String(x.to_string()))
+}
+
+impl SimpleYamlSpecEncoder {
+    pub fn new() -> Self {
+        SimpleYamlSpecEncoder {
+            stack: vec![],
+            documents: vec![],
+        }
+    }
+    pub fn end(self) -> anyhow::Result<String> {
+        Ok(render_stream(&self.documents))
+    }
+    /// Adds a completed node to the innermost container, or as a document at the root.
+    fn emit(&mut self, node: YamlNode) -> anyhow::Result<()> {
+        match self.stack.last_mut() {
+            None => self.documents.push(node),
+            Some(Frame::Seq(elements)) => elements.push(node),
+            Some(Frame::Map { entries, key }) => match key.take() {
+                None => *key = Some(node),
+                Some(key) => entries.push((key, node)),
+            },
+            Some(Frame::Shared { id, inner, field }) => match (*field, node.value) {
+                ("id", YamlValue::Integer(x)) => *id = x.parse().ok(),
+                ("inner", YamlValue::Null) => {}
+                ("inner", value) => {
+                    *inner = Some(YamlNode {
+                        anchor: node.anchor,
+                        value,
+                    })
+                }
+                _ => return Err(YamlEncoderError::SharedWithoutId.into()),
+            },
+        }
+        Ok(())
+    }
+    fn emit_triv(&mut self, any: YamlAnySpecEncoder) -> anyhow::Result<()> {
+        if any.cannot_be_null {
+            self.emit(value(YamlValue::Seq(vec![])))
+        } else {
+            self.emit(value(YamlValue::Null))
+        }
+    }
+    fn open_map(&mut self, key: Option<&str>) {
+        self.stack.push(Frame::Map {
+            entries: vec![],
+            key: key.map(string),
+        });
+    }
+    fn open_seq(&mut self) {
+        self.stack.push(Frame::Seq(vec![]));
+    }
+    fn close(&mut self) -> anyhow::Result<()> {
+        let node = match self.stack.pop() {
+            Some(Frame::Seq(elements)) => value(YamlValue::Seq(elements)),
+            Some(Frame::Map { entries, .. }) => value(YamlValue::Map(entries)),
+            Some(Frame::Shared { id, inner, .. }) => {
+                let id = id.ok_or(YamlEncoderError::SharedWithoutId)?;
+                match inner {
+                    Some(mut inner) => {
+                        inner.anchor = Some(id);
+                        inner
+                    }
+                    None => value(YamlValue::Alias(id)),
+                }
+            }
+            None => unreachable!(),
+        };
+        self.emit(node)
+    }
+    fn set_key(&mut self, name: &'static str) {
+        match self.stack.last_mut() {
+            Some(Frame::Map { key, .. }) => *key = Some(string(name)),
+            Some(Frame::Shared { field, .. }) => *field = name,
+            _ => {}
+        }
+    }
+}
+
+impl SpecEncoder for SimpleYamlSpecEncoder {
+    type AnySpecEncoder = YamlAnySpecEncoder;
+    type SomeCloser = YamlSomeCloser;
+    type TupleEncoder = ();
+    type SeqEncoder = ();
+    type MapEncoder = ();
+    type ValueEncoder = ();
+    type EntryCloser = ();
+    type TupleStructEncoder = ();
+    type StructEncoder = ();
+    type TupleVariantEncoder = ();
+    type StructVariantEncoder = ();
+
+    fn encode_prim(&mut self, any: Self::AnySpecEncoder, prim: Primitive) -> anyhow::Result<()> {
+        let value = value(match prim {
+            Primitive::Unit => return self.emit_triv(any),
+            Primitive::Bool(x) => YamlValue::Bool(x),
+            Primitive::I8(x) => YamlValue::Integer(x.to_string()),
+            Primitive::I16(x) => YamlValue::Integer(x.to_string()),
+            Primitive::I32(x) => YamlValue::Integer(x.to_string()),
+            Primitive::I64(x) => YamlValue::Integer(x.to_string()),
+            Primitive::I128(x) => YamlValue::Integer(x.to_string()),
+            Primitive::U8(x) => YamlValue::Integer(x.to_string()),
+            Primitive::U16(x) => YamlValue::Integer(x.to_string()),
+            Primitive::U32(x) => YamlValue::Integer(x.to_string()),
+            Primitive::U64(x) => YamlValue::Integer(x.to_string()),
+            Primitive::U128(x) => YamlValue::Integer(x.to_string()),
+            // Going through the shortest decimal form keeps `0.1f32` from becoming
+            // `0.10000000149011612`.
+            Primitive::F32(x) => YamlValue::Float(x.to_string().parse()?),
+            Primitive::F64(x) => YamlValue::Float(x),
+            Primitive::Char(x) => YamlValue::String(x.to_string()),
+        });
+        self.emit(value)
+    }
+
+    fn encode_str(&mut self, _any: Self::AnySpecEncoder, s: &str) -> anyhow::Result<()> {
+        self.emit(string(s))
+    }
+
+    fn encode_bytes(&mut self, _any: Self::AnySpecEncoder, s: &[u8]) -> anyhow::Result<()> {
+        self.emit(value(YamlValue::Seq(
+            s.iter()
+                .map(|x| value(YamlValue::Integer(x.to_string())))
+                .collect(),
+        )))
+    }
+
+    fn encode_none(&mut self, any: Self::AnySpecEncoder) -> anyhow::Result<()> {
+        if any.cannot_be_null {
+            self.emit(value(YamlValue::Map(vec![(
+                string("None"),
+                value(YamlValue::Null),
+            )])))
+        } else {
+            self.emit(value(YamlValue::Null))
+        }
+    }
+
+    fn encode_some(
+        &mut self,
+        any: Self::AnySpecEncoder,
+    ) -> anyhow::Result<(Self::AnySpecEncoder, Self::SomeCloser)> {
+        if any.cannot_be_null {
+            self.open_map(Some("Some"));
+            Ok((
+                YamlAnySpecEncoder {
+                    cannot_be_null: false,
+                },
+                YamlSomeCloser {
+                    cannot_be_null: true,
+                },
+            ))
+        } else {
+            Ok((
+                YamlAnySpecEncoder {
+                    cannot_be_null: true,
+                },
+                YamlSomeCloser {
+                    cannot_be_null: false,
+                },
+            ))
+        }
+    }
+
+    fn encode_unit_struct(
+        &mut self,
+        any: Self::AnySpecEncoder,
+        _name: &'static str,
+    ) -> anyhow::Result<()> {
+        self.emit_triv(any)
+    }
+
+    fn encode_tuple_struct(
+        &mut self,
+        _any: Self::AnySpecEncoder,
+        _name: &'static str,
+        _len: usize,
+    ) -> anyhow::Result<Self::TupleStructEncoder> {
+        self.open_seq();
+        Ok(())
+    }
+
+    fn encode_struct(
+        &mut self,
+        _any: Self::AnySpecEncoder,
+        name: &'static str,
+        _fields: &'static [&'static str],
+    ) -> anyhow::Result<Self::StructEncoder> {
+        if is_shared(name) {
+            self.stack.push(Frame::Shared {
+                id: None,
+                inner: None,
+                field: "",
+            });
+        } else {
+            self.open_map(None);
+        }
+        Ok(())
+    }
+
+    fn encode_unit_variant(
+        &mut self,
+        _any: Self::AnySpecEncoder,
+        _name: &'static str,
+        variants: &'static [&'static str],
+        variant_index: usize,
+    ) -> anyhow::Result<()> {
+        self.emit(string(variants[variant_index]))
+    }
+
+    fn encode_tuple_variant(
+        &mut self,
+        _any: Self::AnySpecEncoder,
+        _name: &'static str,
+        variants: &'static [&'static str],
+        variant_index: usize,
+        _len: usize,
+    ) -> anyhow::Result<Self::TupleVariantEncoder> {
+        self.open_map(Some(variants[variant_index]));
+        self.open_seq();
+        Ok(())
+    }
+
+    fn encode_struct_variant(
+        &mut self,
+        _any: Self::AnySpecEncoder,
+        _name: &'static str,
+        variants: &'static [&'static str],
+        variant_index: usize,
+        _fields: &'static [&'static str],
+    ) -> anyhow::Result<Self::StructVariantEncoder> {
+        self.open_map(Some(variants[variant_index]));
+        self.open_map(None);
+        Ok(())
+    }
+
+    fn encode_seq(
+        &mut self,
+        _any: Self::AnySpecEncoder,
+        _len: usize,
+    ) -> anyhow::Result<Self::SeqEncoder> {
+        self.open_seq();
+        Ok(())
+    }
+
+    fn encode_tuple(
+        &mut self,
+        _any: Self::AnySpecEncoder,
+        _len: usize,
+    ) -> anyhow::Result<Self::TupleEncoder> {
+        self.open_seq();
+        Ok(())
+    }
+
+    fn encode_map(
+        &mut self,
+        _any: Self::AnySpecEncoder,
+        _len: usize,
+    ) -> anyhow::Result<Self::MapEncoder> {
+        self.open_map(None);
+        Ok(())
+    }
+
+    fn some_end(&mut self, some: Self::SomeCloser) -> anyhow::Result<()> {
+        if some.cannot_be_null {
+            self.close()?;
+        }
+        Ok(())
+    }
+
+    fn tuple_encode_element(
+        &mut self,
+        _tuple: &mut Self::TupleEncoder,
+    ) -> anyhow::Result<Self::AnySpecEncoder> {
+        Ok(YamlAnySpecEncoder::new())
+    }
+
+    fn tuple_end(&mut self, _tuple: Self::TupleEncoder) -> anyhow::Result<()> {
+        self.close()
+    }
+
+    fn seq_encode_element(
+        &mut self,
+        _seq: &mut Self::SeqEncoder,
+    ) -> anyhow::Result<Self::AnySpecEncoder> {
+        Ok(YamlAnySpecEncoder::new())
+    }
+
+    fn seq_end(&mut self, _seq: Self::SeqEncoder) -> anyhow::Result<()> {
+        self.close()
+    }
+
+    fn map_encode_element(
+        &mut self,
+        _map: &mut Self::MapEncoder,
+    ) -> anyhow::Result<(Self::AnySpecEncoder, Self::ValueEncoder)> {
+        Ok((YamlAnySpecEncoder::new(), ()))
+    }
+
+    fn map_end(&mut self, _map: Self::MapEncoder) -> anyhow::Result<()> {
+        self.close()
+    }
+
+    fn entry_encode_value(
+        &mut self,
+        _value: Self::ValueEncoder,
+    ) -> anyhow::Result<(Self::AnySpecEncoder, Self::EntryCloser)> {
+        Ok((YamlAnySpecEncoder::new(), ()))
+    }
+
+    fn entry_end(&mut self, _closer: Self::EntryCloser) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn tuple_struct_encode_field(
+        &mut self,
+        _s: &mut Self::TupleStructEncoder,
+    ) -> anyhow::Result<Self::AnySpecEncoder> {
+        Ok(YamlAnySpecEncoder::new())
+    }
+
+    fn tuple_struct_end(&mut self, _s: Self::TupleStructEncoder) -> anyhow::Result<()> {
+        self.close()
+    }
+
+    fn struct_encode_field(
+        &mut self,
+        _s: &mut Self::StructEncoder,
+        field: &'static str,
+    ) -> anyhow::Result<Self::AnySpecEncoder> {
+        self.set_key(field);
+        Ok(YamlAnySpecEncoder::new())
+    }
+
+    fn struct_end(&mut self, _s: Self::StructEncoder) -> anyhow::Result<()> {
+        self.close()
+    }
+
+    fn tuple_variant_encode_field(
+        &mut self,
+        _v: &mut Self::TupleVariantEncoder,
+    ) -> anyhow::Result<Self::AnySpecEncoder> {
+        Ok(YamlAnySpecEncoder::new())
+    }
+
+    fn tuple_variant_end(&mut self, _v: Self::TupleVariantEncoder) -> anyhow::Result<()> {
+        self.close()?;
+        self.close()
+    }
+
+    fn struct_variant_encode_field(
+        &mut self,
+        _v: &mut Self::StructVariantEncoder,
+        key: &'static str,
+    ) -> anyhow::Result<Self::AnySpecEncoder> {
+        self.set_key(key);
+        Ok(YamlAnySpecEncoder::new())
+    }
+
+    fn struct_variant_end(&mut self, _v: Self::StructVariantEncoder) -> anyhow::Result<()> {
+        self.close()?;
+        self.close()
+    }
+
+    fn is_human_readable(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,238 @@
+use std::fmt::Write;
+
+use crate::encode::{YamlNode, YamlValue};
+use crate::schema::{resolve_plain, Resolved};
+
+/// Where a node is written, which decides how a block collection starts.
+#[derive(Copy, Clone)]
+enum Position {
+    Document,
+    /// After `key:`.
+    Value,
+    /// After `-`.
+    Element,
+}
+
+fn is_plain(x: &str) -> bool {
+    let Some(first) = x.chars().next() else {
+        return false;
+    };
+    if !matches!(resolve_plain(x), Resolved::Str) {
+        return false;
+    }
+    // YAML 1.1 readers, which are still common in deployment tooling, read these as booleans.
+    if let "y" | "Y" | "yes" | "Yes" | "YES" | "n" | "N" | "no" | "No" | "NO" | "on" | "On" | "ON"
+    | "off" | "Off" | "OFF" = x
+    {
+        return false;
+    }
+    !"-?:,[]{}#&*!|>'\"%@`".contains(first)
+        && !x.starts_with([' ', '.'])
+        && !x.ends_with([' ', ':'])
+        && !x.contains(": ")
+        && !x.contains(" #")
+        && !x
+            .chars()
+            .any(|c| c.is_control() || "\u{feff},[]{}".contains(c))
+}
+
+/// Whether a string spans several lines and can be written as a literal block scalar.
+fn is_literal(x: &str) -> bool {
+    x.trim_end_matches('\n').contains('\n')
+        && !x
+            .chars()
+            .any(|c| c.is_control() && c != '\n' && c != '\t' || c == '\u{feff}')
+}
+
+fn write_double_quoted(output: &mut String, x: &str) {
+    output.push('"');
+    for c in x.chars() {
+        match c {
+            '"' => output.push_str("\\\""),
+            '\\' => output.push_str("\\\\"),
+            '\0' => output.push_str("\\0"),
+            '\n' => output.push_str("\\n"),
+            '\r' => output.push_str("\\r"),
+            '\t' => output.push_str("\\t"),
+            c if c.is_control() || c == '\u{feff}' => {
+                if (c as u32) < 0x100 {
+                    write!(output, "\\x{:02X}", c as u32).unwrap();
+                } else {
+                    write!(output, "\\u{:04X}", c as u32).unwrap();
+                }
+            }
+            c => output.push(c),
+        }
+    }
+    output.push('"');
+}
+
+fn write_string(output: &mut String, x: &str) {
+    if is_plain(x) {
+        output.push_str(x);
+    } else if x.chars().any(|c| c.is_control() || c == '\u{feff}') {
+        write_double_quoted(output, x);
+    } else {
+        output.push('\'');
+        output.push_str(&x.replace('\'', "''"));
+        output.push('\'');
+    }
+}
+
+/// Writes a multi-line string as a `|` block scalar whose lines are indented to `indent`.
+fn write_literal(output: &mut String, x: &str, indent: usize) {
+    let (body, chomping) = match x.strip_suffix('\n') {
+        None => (x, "-"),
+        Some(body) if body.ends_with('\n') => (body, "+"),
+        Some(body) => (body, ""),
+    };
+    output.push('|');
+    let first = body.split('\n').find(|line| !line.is_empty());
+    if first.is_some_and(|line| line.starts_with(' ')) {
+        output.push('2');
+    }
+    output.push_str(chomping);
+    output.push('\n');
+    for line in body.split('\n') {
+        if !line.is_empty() {
+            write!(output, "{:indent$}{}", "", line, indent = indent).unwrap();
+        }
+        output.push('\n');
+    }
+}
+
+/// Writes a node on a single line, using flow style for collections.
+fn write_flow(output: &mut String, node: &YamlNode) {
+    if let Some(anchor) = node.anchor {
+        write!(output, "&{} ", anchor).unwrap();
+    }
+    match &node.value {
+        YamlValue::Null => output.push_str("null"),
+        YamlValue::Bool(x) => write!(output, "{}", x).unwrap(),
+        YamlValue::Integer(x) => output.push_str(x),
+        YamlValue::Float(x) if x.is_nan() => output.push_str(".nan"),
+        YamlValue::Float(x) if x.is_infinite() => {
+            output.push_str(if *x > 0.0 { ".inf" } else { "-.inf" })
+        }
+        YamlValue::Float(x) => write!(output, "{:?}", x).unwrap(),
+        YamlValue::String(x) => write_string(output, x),
+        YamlValue::Seq(elements) => {
+            output.push('[');
+            for (index, element) in elements.iter().enumerate() {
+                if index > 0 {
+                    output.push_str(", ");
+                }
+                write_flow(output, element);
+            }
+            output.push(']');
+        }
+        YamlValue::Map(entries) => {
+            output.push('{');
+            for (index, (key, value)) in entries.iter().enumerate() {
+                if index > 0 {
+                    output.push_str(", ");
+                }
+                write_key(output, key);
+                output.push(' ');
+                write_flow(output, value);
+            }
+            output.push('}');
+        }
+        YamlValue::Alias(id) => write!(output, "*{}", id).unwrap(),
+    }
+}
+
+fn write_key(output: &mut String, key: &YamlNode) {
+    write_flow(output, key);
+    if let YamlValue::Alias(_) = key.value {
+        // An alias name would otherwise run into the colon.
+        output.push(' ');
+    }
+    output.push(':');
+}
+
+fn is_block(node: &YamlNode) -> bool {
+    match &node.value {
+        YamlValue::Seq(elements) => !elements.is_empty(),
+        YamlValue::Map(entries) => !entries.is_empty(),
+        _ => false,
+    }
+}
+
+/// Writes the entries of a block collection at `indent`. The first entry continues the current
+/// line when `compact` is set.
+fn write_block(output: &mut String, node: &YamlNode, indent: usize, compact: bool) {
+    let pad = |output: &mut String, index: usize| {
+        if index > 0 || !compact {
+            write!(output, "{:indent$}", "", indent = indent).unwrap();
+        }
+    };
+    match &node.value {
+        YamlValue::Seq(elements) => {
+            for (index, element) in elements.iter().enumerate() {
+                pad(output, index);
+                output.push('-');
+                write_node(output, element, indent, Position::Element);
+            }
+        }
+        YamlValue::Map(entries) => {
+            for (index, (key, value)) in entries.iter().enumerate() {
+                pad(output, index);
+                write_key(output, key);
+                write_node(output, value, indent, Position::Value);
+            }
+        }
+        _ => unreachable!(),
+    }
+}
+
+/// Writes a node and the line break after it. `indent` is the column of the collection holding
+/// the node.
+fn write_node(output: &mut String, node: &YamlNode, indent: usize, position: Position) {
+    let nested = match position {
+        Position::Document => 0,
+        Position::Value | Position::Element => indent + 2,
+    };
+    if !matches!(position, Position::Document) {
+        output.push(' ');
+    }
+    if is_block(node) {
+        match (node.anchor, position) {
+            (None, Position::Element) => write_block(output, node, nested, true),
+            (None, Position::Document) => write_block(output, node, nested, false),
+            (None, Position::Value) => {
+                output.pop();
+                output.push('\n');
+                write_block(output, node, nested, false);
+            }
+            (Some(anchor), _) => {
+                writeln!(output, "&{}", anchor).unwrap();
+                write_block(output, node, nested, false);
+            }
+        }
+        return;
+    }
+    match &node.value {
+        YamlValue::String(x) if is_literal(x) => {
+            if let Some(anchor) = node.anchor {
+                write!(output, "&{} ", anchor).unwrap();
+            }
+            write_literal(output, x, indent + 2);
+        }
+        _ => {
+            write_flow(output, node);
+            output.push('\n');
+        }
+    }
+}
+
+pub(crate) fn render_stream(documents: &[YamlNode]) -> String {
+    let mut output = String::new();
+    for (index, document) in documents.iter().enumerate() {
+        if index > 0 {
+            output.push_str("---\n");
+        }
+        write_node(&mut output, document, 0, Position::Document);
+    }
+    output
+}
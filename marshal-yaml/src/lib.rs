@@ -0,0 +1,26 @@
+#![deny(unused_must_use)]
+#![feature(never_type)]
+#![feature(trait_alias)]
+#![feature(specialization)]
+#![allow(incomplete_features)]
+
+use marshal::de::Deserialize;
+use marshal::ser::Serialize;
+
+use crate::decode::full::YamlDecoder;
+use crate::encode::full::YamlEncoder;
+
+pub mod decode;
+pub mod encode;
+mod schema;
+#[cfg(test)]
+mod test;
+
+pub trait SerializeYaml = Serialize<YamlEncoder>;
+pub trait DeserializeYaml = Deserialize<YamlDecoder>;
+
+/// Whether a struct is the `{ id, inner }` record written by `marshal-shared`, which YAML
+/// represents with anchors and aliases.
+pub(crate) fn is_shared(name: &str) -> bool {
+    name == marshal_core::SHARED_STRUCT_NAME
+}
@@ -0,0 +1,96 @@
+/// A plain scalar resolved with the YAML 1.2 core schema.
+pub(crate) enum Resolved {
+    Null,
+    Bool(bool),
+    Int(YamlInt),
+    Float(f64),
+    Str,
+}
+
+/// An integer, as a sign and a magnitude.
+#[derive(Copy, Clone)]
+pub(crate) enum YamlInt {
+    Positive(u128),
+    Negative(u128),
+}
+
+fn parse_magnitude(digits: &str, radix: u32) -> Option<u128> {
+    if digits.is_empty() || !digits.chars().all(|c| c.is_digit(radix)) {
+        return None;
+    }
+    u128::from_str_radix(digits, radix).ok()
+}
+
+fn is_float(text: &str) -> bool {
+    let digits = text.strip_prefix(['-', '+']).unwrap_or(text);
+    let (mantissa, exponent) = match digits.find(['e', 'E']) {
+        Some(index) => (&digits[..index], Some(&digits[index + 1..])),
+        None => (digits, None),
+    };
+    let (whole, fraction) = match mantissa.split_once('.') {
+        Some((whole, fraction)) => (whole, Some(fraction)),
+        None => (mantissa, None),
+    };
+    let all_digits = |s: &str| s.bytes().all(|c| c.is_ascii_digit());
+    let mantissa_ok = match fraction {
+        None => !whole.is_empty() && all_digits(whole),
+        Some(fraction) => {
+            all_digits(whole) && all_digits(fraction) && !(whole.is_empty() && fraction.is_empty())
+        }
+    };
+    let exponent_ok = match exponent {
+        None => true,
+        Some(exponent) => {
+            let exponent = exponent.strip_prefix(['-', '+']).unwrap_or(exponent);
+            !exponent.is_empty() && all_digits(exponent)
+        }
+    };
+    mantissa_ok && exponent_ok && (fraction.is_some() || exponent.is_some())
+}
+
+pub(crate) fn resolve_plain(text: &str) -> Resolved {
+    match text {
+        "" | "~" | "null" | "Null" | "NULL" => return Resolved::Null,
+        "true" | "True" | "TRUE" => return Resolved::Bool(true),
+        "false" | "False" | "FALSE" => return Resolved::Bool(false),
+        ".nan" | ".NaN" | ".NAN" => return Resolved::Float(f64::NAN),
+        _ => {}
+    }
+    let (negative, unsigned) = match text.strip_prefix('-') {
+        Some(unsigned) => (true, unsigned),
+        None => (false, text.strip_prefix('+').unwrap_or(text)),
+    };
+    if let ".inf" | ".Inf" | ".INF" = unsigned {
+        return Resolved::Float(if negative {
+            f64::NEG_INFINITY
+        } else {
+            f64::INFINITY
+        });
+    }
+    let int = if let Some(digits) = text.strip_prefix("0o") {
+        parse_magnitude(digits, 8)
+    } else if let Some(digits) = text.strip_prefix("0x") {
+        parse_magnitude(digits, 16)
+    } else if !unsigned.is_empty() && unsigned.bytes().all(|c| c.is_ascii_digit()) {
+        match parse_magnitude(unsigned, 10) {
+            Some(magnitude) => Some(magnitude),
+            // Too large for an integer, but still a number.
+            None => return Resolved::Float(text.parse().unwrap()),
+        }
+    } else {
+        None
+    };
+    if let Some(magnitude) = int {
+        return Resolved::Int(if negative && magnitude != 0 {
+            YamlInt::Negative(magnitude)
+        } else {
+            YamlInt::Positive(magnitude)
+        });
+    }
+    if is_float(text) {
+        if let Ok(x) = text.parse() {
+            return Resolved::Float(x);
+        }
+    }
+    Resolved::Str
+}
@@ -0,0 +1,384 @@
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+
+use marshal::context::OwnedContext;
+use marshal_derive::{Deserialize, Serialize};
+
+use crate::decode::full::YamlDecoderBuilder;
+use crate::encode::full::YamlEncoderBuilder;
+use crate::{DeserializeYaml, SerializeYaml};
+
+fn decode<T: DeserializeYaml>(input: &str) -> anyhow::Result<T> {
+    let mut c = OwnedContext::new();
+    YamlDecoderBuilder::new(input).deserialize(c.borrow())
+}
+
+#[track_caller]
+fn test_round_trip<T: Debug + PartialEq + SerializeYaml + DeserializeYaml>(
+    input: T,
+    expected: &str,
+) -> anyhow::Result<()> {
+    println!("{:?}", input);
+    let mut c = OwnedContext::new();
+    let found = YamlEncoderBuilder::new().serialize(&input, c.borrow())?;
+    assert_eq!(found, expected);
+    assert_eq!(decode::<T>(&found)?, input);
+    Ok(())
+}
+
+#[track_caller]
+fn test_error<T: Debug + DeserializeYaml>(input: &str, expected: &str) {
+    let error = decode::<T>(input).unwrap_err();
+    assert_eq!(format!("{:#}", error), expected);
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Container {
+    name: String,
+    image: String,
+    ports: Vec<u16>,
+    env: BTreeMap<String, String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Pod {
+    kind: String,
+    replicas: u32,
+    containers: Vec<Container>,
+    labels: BTreeMap<String, String>,
+}
+
+#[test]
+fn test_block() -> anyhow::Result<()> {
+    let pod = Pod {
+        kind: "Pod".to_string(),
+        replicas: 3,
+        containers: vec![Container {
+            name: "web".to_string(),
+            image: "nginx:1.25".to_string(),
+            ports: vec![80, 443],
+            env: BTreeMap::from([("MODE".to_string(), "on".to_string())]),
+        }],
+        labels: BTreeMap::new(),
+    };
+    test_round_trip(
+        pod,
+        "kind: Pod
+replicas: 3
+containers:
+  - name: web
+    image: nginx:1.25
+    ports:
+      - 80
+      - 443
+    env:
+      MODE: 'on'
+labels: {}
+",
+    )?;
+    assert_eq!(
+        decode::<Pod>(
+            "# a manifest
+kind: Pod   # trailing comment
+replicas: 0x3
+containers:
+- name: web
+  image: \"nginx:1.25\"
+  ports: [80,
+    443]
+  env: {MODE: on}
+labels: {}
+"
+        )?,
+        decode::<Pod>(
+            "{kind: Pod, replicas: 3, containers: [{name: web, image: 'nginx:1.25', \
+            ports: [80, 443], env: {\"MODE\":\"on\"}}], labels: {}}"
+        )?
+    );
+    test_round_trip(
+        vec![vec![1u8, 2], vec![], vec![3]],
+        "- - 1\n  - 2\n- []\n- - 3\n",
+    )?;
+    test_round_trip(
+        BTreeMap::from([((1u8, 2u8), vec![BTreeMap::from([(3u8, true)])])]),
+        "[1, 2]:\n  - 3: true\n",
+    )?;
+    Ok(())
+}
+
+#[test]
+fn test_scalars() -> anyhow::Result<()> {
+    test_round_trip(
+        vec![
+            "plain text".to_string(),
+            "true".to_string(),
+            "12".to_string(),
+            "".to_string(),
+            "it's: quoted".to_string(),
+            "tab\there".to_string(),
+            "- item".to_string(),
+        ],
+        "- plain text
+- 'true'
+- '12'
+- ''
+- 'it''s: quoted'
+- \"tab\\there\"
+- '- item'
+",
+    )?;
+    test_round_trip(
+        (-7i8, 1.5f64, f64::INFINITY, u128::MAX, 'x', ()),
+        "- -7\n- 1.5\n- .inf\n- 340282366920938463463374607431768211455\n- x\n- null\n",
+    )?;
+    assert!(decode::<f64>(".nan")?.is_nan());
+    assert_eq!(
+        decode::<Vec<bool>>("[true, False, TRUE]")?,
+        vec![true, false, true]
+    );
+    assert_eq!(decode::<Vec<i32>>("[0o17, -12, +3]")?, vec![15, -12, 3]);
+    assert_eq!(
+        decode::<Vec<f32>>("[1e3, -.5, 2]")?,
+        vec![1000.0, -0.5, 2.0]
+    );
+    assert_eq!(decode::<String>("!!str 12")?, "12");
+    assert_eq!(decode::<u8>("!!int \"12\"")?, 12);
+    assert_eq!(
+        decode::<String>("\"esc\\x41\\u00e9\\n\\\n  joined\"")?,
+        "escAé\njoined"
+    );
+    assert_eq!(
+        decode::<Vec<String>>(
+            "- plain
+  folded
+
+  lines
+- 'single
+  ''quoted'''
+- \"double
+  quoted\"
+"
+        )?,
+        vec!["plain folded\nlines", "single 'quoted'", "double quoted"]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_block_scalars() -> anyhow::Result<()> {
+    test_round_trip(
+        BTreeMap::from([
+            ("clip".to_string(), "a\n  b\n".to_string()),
+            ("keep".to_string(), "a\nb\n\n".to_string()),
+            ("strip".to_string(), "a\nb".to_string()),
+            ("space".to_string(), " a\nb".to_string()),
+        ]),
+        "clip: |
+  a
+    b
+keep: |+
+  a
+  b
+
+space: |2-
+   a
+  b
+strip: |-
+  a
+  b
+",
+    )?;
+    assert_eq!(
+        decode::<BTreeMap<String, String>>(
+            "folded: >
+  one
+  two
+
+  three
+    indented
+  four
+literal: |-
+    x
+
+    y
+last: |
+  end"
+        )?,
+        BTreeMap::from([
+            (
+                "folded".to_string(),
+                "one two\nthree\n  indented\nfour\n".to_string()
+            ),
+            ("literal".to_string(), "x\n\ny".to_string()),
+            ("last".to_string(), "end\n".to_string()),
+        ])
+    );
+    Ok(())
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+enum Mode {
+    Off,
+    Fixed(u8, u8),
+    Range { low: u8, high: u8 },
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Options {
+    modes: Vec<Mode>,
+    a: Option<u8>,
+    b: Option<Option<u8>>,
+    c: Option<Option<u8>>,
+    d: Option<()>,
+    e: Vec<u8>,
+}
+
+#[test]
+fn test_enums_and_options() -> anyhow::Result<()> {
+    test_round_trip(
+        Options {
+            modes: vec![
+                Mode::Off,
+                Mode::Fixed(1, 2),
+                Mode::Range { low: 3, high: 4 },
+            ],
+            a: None,
+            b: Some(None),
+            c: Some(Some(5)),
+            d: Some(()),
+            e: vec![],
+        },
+        "modes:
+  - 'Off'
+  - Fixed:
+      - 1
+      - 2
+  - Range:
+      low: 3
+      high: 4
+a: null
+b:
+  None: null
+c:
+  Some: 5
+d: []
+e: []
+",
+    )?;
+    assert_eq!(
+        decode::<Options>("modes: []\na: ~\nb:\nc: 1\nd: null\ne: [1, 2]\n")?,
+        Options {
+            modes: vec![],
+            a: None,
+            b: None,
+            c: Some(Some(1)),
+            d: None,
+            e: vec![1, 2],
+        }
+    );
+    Ok(())
+}
+
+#[test]
+fn test_streams_and_anchors() -> anyhow::Result<()> {
+    let mut c = OwnedContext::new();
+    let found = YamlEncoderBuilder::new().serialize_stream(&[vec![1u8], vec![2, 3]], c.borrow())?;
+    assert_eq!(found, "- 1\n---\n- 2\n- 3\n");
+    assert_eq!(
+        YamlDecoderBuilder::new("%YAML 1.2\n--- [1]\n...\n---\n- 2\n- 3\n")
+            .deserialize_stream::<Vec<u8>>(c.borrow())?,
+        vec![vec![1], vec![2, 3]]
+    );
+    assert_eq!(
+        decode::<BTreeMap<String, Vec<u8>>>("base: &list [1, 2]\ncopy: *list\n")?,
+        BTreeMap::from([
+            ("base".to_string(), vec![1, 2]),
+            ("copy".to_string(), vec![1, 2])
+        ])
+    );
+    test_error::<Vec<u8>>(
+        "--- [1]\n--- [2]\n",
+        "at line 2 column 5: expected one document, found 2",
+    );
+    test_error::<Vec<u8>>(
+        "[*missing]",
+        "at line 1 column 2: undefined alias \"missing\"",
+    );
+    let mut laughs = "a: &a [x, x, x, x, x, x, x, x, x, x]\n".to_string();
+    for (name, previous) in ["b", "c", "d", "e", "f", "g", "h"]
+        .iter()
+        .zip(["a", "b", "c", "d", "e", "f", "g"])
+    {
+        laughs.push_str(&format!(
+            "{}: &{} [*{p}, *{p}, *{p}, *{p}, *{p}, *{p}, *{p}, *{p}, *{p}, *{p}]\n",
+            name,
+            name,
+            p = previous
+        ));
+    }
+    assert_eq!(
+        format!("{:#}", decode::<BTreeMap<String, ()>>(&laughs).unwrap_err()),
+        "at line 6 column 40: aliases expand to too many nodes"
+    );
+    Ok(())
+}
+
+#[test]
+fn test_struct_named_shared() -> anyhow::Result<()> {
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Shared {
+        id: usize,
+        inner: Option<u8>,
+    }
+    test_round_trip(
+        vec![
+            Shared {
+                id: 0,
+                inner: Some(1),
+            },
+            Shared { id: 0, inner: None },
+        ],
+        "- id: 0\n  inner: 1\n- id: 0\n  inner: null\n",
+    )
+}
+
+#[test]
+fn test_errors() {
+    test_error::<BTreeMap<String, u8>>("a: 1\na: 2\n", "at line 2 column 1: duplicate key \"a\"");
+    test_error::<BTreeMap<String, u8>>(
+        "a: b: 1\n",
+        "at line 1 column 5: a mapping cannot start on the same line as its key",
+    );
+    test_error::<BTreeMap<String, BTreeMap<String, u8>>>(
+        "a:\n  b: 1\n c: 2\n",
+        "at line 3 column 2: unexpected indentation",
+    );
+    test_error::<BTreeMap<String, u8>>(
+        "a: 1\nb\n",
+        "at line 2 column 1: expected `:` after a mapping key",
+    );
+    test_error::<BTreeMap<String, u8>>(
+        "a: 300\n",
+        "at line 1 column 4: out of range integral type conversion attempted",
+    );
+    test_error::<Vec<u8>>("[1, 2", "at line 1 column 6: unexpected end of input");
+    test_error::<String>("\"\\q\"", "at line 1 column 3: invalid escape sequence");
+    test_error::<Mode>(
+        "{Off: null, On: null}",
+        "at line 1 column 1: enum must be a string or a mapping with one entry",
+    );
+}
+
+#[test]
+fn test_many_keys() -> anyhow::Result<()> {
+    let block: String = (0..50000).map(|i| format!("k{}: {}\n", i, i % 256)).collect();
+    assert_eq!(decode::<BTreeMap<String, u8>>(&block)?.len(), 50000);
+    let entries: Vec<String> = (0..50000).map(|i| format!("k{}: 1", i)).collect();
+    let flow = format!("{{{}}}", entries.join(", "));
+    assert_eq!(decode::<BTreeMap<String, u8>>(&flow)?.len(), 50000);
+    test_error::<BTreeMap<String, u8>>(
+        &format!("{}k0: 1\n", block),
+        "at line 50001 column 1: duplicate key \"k0\"",
+    );
+    Ok(())
+}
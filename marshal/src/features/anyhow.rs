@@ -5,6 +5,7 @@ use crate::de::Deserialize;
 use marshal_core::encode::{AnyEncoder, Encoder};
 
 use crate::ser::Serialize;
+use crate::shape::{ShapeHasher, TypeShape};
 
 impl<D: Decoder> Deserialize<D> for anyhow::Error {
     fn deserialize<'p, 'de>(d: AnyDecoder<'p, 'de, D>, _ctx: Context) -> anyhow::Result<Self> {
@@ -21,3 +22,9 @@ impl<E: Encoder> Serialize<E> for anyhow::Error {
         <String as Serialize<E>>::serialize(&self.to_string(), e, ctx)
     }
 }
+
+impl TypeShape for anyhow::Error {
+    fn hash_shape(hasher: &mut ShapeHasher) {
+        hasher.write_string();
+    }
+}
@@ -7,6 +7,7 @@ use marshal_core::encode::{AnyEncoder, Encoder};
 use marshal_pointer::boxed::BoxRef;
 
 use crate::ser::Serialize;
+use crate::shape::{ShapeHasher, TypeShape};
 
 impl<D: Decoder, T: Deserialize<D>> Deserialize<D> for Box<T> {
     fn deserialize<'p, 'de>(p: AnyDecoder<'p, 'de, D>, ctx: Context) -> anyhow::Result<Self> {
@@ -25,3 +26,9 @@ impl<E: Encoder, T: Serialize<E>> Serialize<E> for BoxRef<T> {
         (**self).serialize(w, ctx)
     }
 }
+
+impl<T: TypeShape> TypeShape for Box<T> {
+    fn hash_shape(hasher: &mut ShapeHasher) {
+        T::hash_shape(hasher)
+    }
+}
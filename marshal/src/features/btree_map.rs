@@ -4,6 +4,7 @@ use marshal_core::encode::{AnyEncoder, Encoder};
 use crate::context::Context;
 use crate::de::Deserialize;
 use crate::ser::Serialize;
+use crate::shape::{ShapeHasher, TypeShape};
 
 impl<W: Encoder, K: Ord + Serialize<W>, V: Serialize<W>> Serialize<W> for BTreeMap<K, V> {
     fn serialize<'w, 'en>(
@@ -34,4 +35,12 @@ impl<D: Decoder, K: Ord + Deserialize<D>, V: Deserialize<D>> Deserialize<D> for
             )
             .collect()
     }
-}
\ No newline at end of file
+}
+
+impl<K: TypeShape, V: TypeShape> TypeShape for BTreeMap<K, V> {
+    fn hash_shape(hasher: &mut ShapeHasher) {
+        hasher.write_map();
+        hasher.visit::<K>();
+        hasher.visit::<V>();
+    }
+}
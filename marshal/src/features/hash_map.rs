@@ -8,6 +8,7 @@ use crate::de::Deserialize;
 use marshal_core::encode::{AnyEncoder, Encoder};
 
 use crate::ser::Serialize;
+use crate::shape::{ShapeHasher, TypeShape};
 
 impl<D: Decoder, K: Hash + Eq + Deserialize<D>, V: Deserialize<D>> Deserialize<D>
     for HashMap<K, V>
@@ -41,3 +42,11 @@ impl<W: Encoder, K: Eq + Hash + Serialize<W>, V: Serialize<W>> Serialize<W> for
         Ok(())
     }
 }
+
+impl<K: TypeShape, V: TypeShape> TypeShape for HashMap<K, V> {
+    fn hash_shape(hasher: &mut ShapeHasher) {
+        hasher.write_map();
+        hasher.visit::<K>();
+        hasher.visit::<V>();
+    }
+}
@@ -6,6 +6,7 @@ use crate::de::Deserialize;
 use marshal_core::encode::{AnyEncoder, Encoder};
 
 use crate::ser::Serialize;
+use crate::shape::{ShapeHasher, TypeShape};
 
 impl<D: Decoder> Deserialize<D> for ! {
     fn deserialize<'p, 'de>(_: AnyDecoder<'p, 'de, D>, _ctx: Context) -> anyhow::Result<Self> {
@@ -18,3 +19,9 @@ impl<W: Encoder> Serialize<W> for ! {
         *self
     }
 }
+
+impl TypeShape for ! {
+    fn hash_shape(hasher: &mut ShapeHasher) {
+        hasher.write_enum(&[]);
+    }
+}
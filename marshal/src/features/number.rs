@@ -4,6 +4,7 @@ use marshal_core::encode::{AnyEncoder, Encoder};
 use crate::context::Context;
 use crate::de::Deserialize;
 use crate::ser::Serialize;
+use crate::shape::{ShapeHasher, TypeShape};
 
 macro_rules! derive_number {
     ($t:ty, $v:ident) => {
@@ -24,6 +25,11 @@ macro_rules! derive_number {
                 w.encode_prim(Primitive::$v(*self))
             }
         }
+        impl TypeShape for $t {
+            fn hash_shape(hasher: &mut ShapeHasher) {
+                hasher.write_primitive(PrimitiveType::$v);
+            }
+        }
     };
 }
 
@@ -82,4 +88,16 @@ impl<W: Encoder> Serialize<W> for isize {
     ) -> anyhow::Result<()> {
         w.encode_prim(Primitive::I64(*self as i64))
     }
-}
\ No newline at end of file
+}
+
+impl TypeShape for usize {
+    fn hash_shape(hasher: &mut ShapeHasher) {
+        hasher.write_primitive(PrimitiveType::U64);
+    }
+}
+
+impl TypeShape for isize {
+    fn hash_shape(hasher: &mut ShapeHasher) {
+        hasher.write_primitive(PrimitiveType::I64);
+    }
+}
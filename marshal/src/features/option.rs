@@ -5,6 +5,7 @@ use crate::de::Deserialize;
 use marshal_core::encode::{AnyEncoder, Encoder};
 
 use crate::ser::Serialize;
+use crate::shape::{ShapeHasher, TypeShape};
 
 impl<D: Decoder, T: Deserialize<D>> Deserialize<D> for Option<T> {
     fn deserialize<'p, 'de>(p: AnyDecoder<'p, 'de, D>, ctx: Context) -> anyhow::Result<Self> {
@@ -33,3 +34,10 @@ impl<W: Encoder, T: Serialize<W>> Serialize<W> for Option<T> {
         }
     }
 }
+
+impl<T: TypeShape> TypeShape for Option<T> {
+    fn hash_shape(hasher: &mut ShapeHasher) {
+        hasher.write_option();
+        hasher.visit::<T>();
+    }
+}
@@ -2,9 +2,16 @@ use marshal_core::encode::{AnyEncoder, Encoder};
 
 use crate::context::Context;
 use crate::ser::Serialize;
+use crate::shape::{ShapeHasher, TypeShape};
 
 impl<'a, E: Encoder, T: Serialize<E>> Serialize<E> for &'a T {
     fn serialize<'w, 'en>(&self, e: AnyEncoder<'w, 'en, E>, ctx: Context) -> anyhow::Result<()> {
         (**self).serialize(e, ctx)
     }
 }
+
+impl<'a, T: ?Sized + TypeShape> TypeShape for &'a T {
+    fn hash_shape(hasher: &mut ShapeHasher) {
+        T::hash_shape(hasher)
+    }
+}
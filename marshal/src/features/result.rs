@@ -6,6 +6,7 @@ use crate::de::Deserialize;
 use marshal_core::encode::{AnyEncoder, Encoder};
 
 use crate::ser::Serialize;
+use crate::shape::{ShapeHasher, TypeShape};
 
 impl<D: Decoder, A: Deserialize<D>, B: Deserialize<D>> Deserialize<D> for Result<A, B> {
     fn deserialize<'p, 'de>(d: AnyDecoder<'p, 'de, D>, ctx: Context) -> anyhow::Result<Self> {
@@ -49,4 +50,14 @@ impl<E: Encoder, A: Serialize<E>, B: Serialize<E>> Serialize<E> for Result<A, B>
             }
         }
     }
-}
\ No newline at end of file
+}
+
+impl<A: TypeShape, B: TypeShape> TypeShape for Result<A, B> {
+    fn hash_shape(hasher: &mut ShapeHasher) {
+        hasher.write_enum(&["Ok", "Err"]);
+        hasher.write_tuple(1);
+        hasher.visit::<A>();
+        hasher.write_tuple(1);
+        hasher.visit::<B>();
+    }
+}
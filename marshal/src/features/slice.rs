@@ -1,6 +1,7 @@
 use crate::context::Context;
 use crate::ser::Serialize;
 use marshal_core::encode::{AnyEncoder, Encoder};
+use crate::shape::{ShapeHasher, TypeShape};
 
 impl<E: Encoder, T: Serialize<E>> Serialize<E> for [T] {
     default fn serialize<'w, 'en>(
@@ -23,3 +24,16 @@ impl<E: Encoder> Serialize<E> for [u8] {
         Ok(())
     }
 }
+
+impl<T: TypeShape> TypeShape for [T] {
+    default fn hash_shape(hasher: &mut ShapeHasher) {
+        hasher.write_seq();
+        hasher.visit::<T>();
+    }
+}
+
+impl TypeShape for [u8] {
+    fn hash_shape(hasher: &mut ShapeHasher) {
+        hasher.write_bytes();
+    }
+}
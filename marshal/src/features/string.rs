@@ -6,6 +6,7 @@ use marshal_core::encode::{AnyEncoder, Encoder};
 use crate::ser::Serialize;
 
 use crate::de::Deserialize;
+use crate::shape::{ShapeHasher, TypeShape};
 
 impl<D: Decoder> Deserialize<D> for String {
     fn deserialize<'p, 'de>(d: AnyDecoder<'p, 'de, D>, _ctx: Context) -> anyhow::Result<Self> {
@@ -20,3 +21,9 @@ impl<W: Encoder> Serialize<W> for String {
         w.encode_str(self)
     }
 }
+
+impl TypeShape for String {
+    fn hash_shape(hasher: &mut ShapeHasher) {
+        hasher.write_string();
+    }
+}
@@ -4,6 +4,7 @@ use crate::ser::Serialize;
 use marshal_core::decode::{AnyDecoder, Decoder};
 use marshal_core::encode::{AnyEncoder, Encoder};
 use std::time::{Duration, Instant, SystemTime};
+use crate::shape::{ShapeHasher, TypeShape};
 
 impl<E: Encoder> Serialize<E> for Duration {
     fn serialize<'w, 'en>(&self, e: AnyEncoder<'w, 'en, E>, ctx: Context) -> anyhow::Result<()> {
@@ -61,3 +62,21 @@ impl<D: Decoder> Deserialize<D> for Instant {
         }
     }
 }
+
+impl TypeShape for Duration {
+    fn hash_shape(hasher: &mut ShapeHasher) {
+        <(u64, u32)>::hash_shape(hasher)
+    }
+}
+
+impl TypeShape for SystemTime {
+    fn hash_shape(hasher: &mut ShapeHasher) {
+        Duration::hash_shape(hasher)
+    }
+}
+
+impl TypeShape for Instant {
+    fn hash_shape(hasher: &mut ShapeHasher) {
+        <(i64, u32)>::hash_shape(hasher)
+    }
+}
@@ -5,6 +5,7 @@ use crate::context::Context;
 use crate::de::Deserialize;
 use marshal_core::encode::{AnyEncoder, Encoder};
 use crate::ser::Serialize;
+use crate::shape::{ShapeHasher, TypeShape};
 
 
 impl<D: Decoder> Deserialize<D> for () {
@@ -55,6 +56,14 @@ macro_rules! derive_tuple {
                 Ok(())
             }
         }
+        impl<$( $T: TypeShape ),*> TypeShape for ($($T,)*) {
+            fn hash_shape(hasher: &mut ShapeHasher) {
+                hasher.write_tuple(${count($T)});
+                $(
+                    hasher.visit::<$T>();
+                )*
+            }
+        }
     };
 }
 
@@ -81,3 +90,9 @@ impl<W: Encoder> Serialize<W> for () {
         w.encode_prim(Primitive::Unit)
     }
 }
+
+impl TypeShape for () {
+    fn hash_shape(hasher: &mut ShapeHasher) {
+        hasher.write_primitive(PrimitiveType::Unit);
+    }
+}
@@ -31,6 +31,7 @@ impl<D: Decoder> Deserialize<D> for Vec<u8> {
 use marshal_core::encode::{AnyEncoder, Encoder};
 
 use crate::ser::Serialize;
+use crate::shape::{ShapeHasher, TypeShape};
 
 impl<W: Encoder, T: Serialize<W>> Serialize<W> for Vec<T> {
     default fn serialize<'w, 'en>(
@@ -56,3 +57,16 @@ impl<W: Encoder> Serialize<W> for Vec<u8> {
         w.encode_bytes(self)
     }
 }
+
+impl<T: TypeShape> TypeShape for Vec<T> {
+    default fn hash_shape(hasher: &mut ShapeHasher) {
+        hasher.write_seq();
+        hasher.visit::<T>();
+    }
+}
+
+impl TypeShape for Vec<u8> {
+    fn hash_shape(hasher: &mut ShapeHasher) {
+        hasher.write_bytes();
+    }
+}
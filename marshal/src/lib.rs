@@ -48,10 +48,12 @@ extern crate self as marshal;
 pub use marshal_core::*;
 pub use marshal_derive::Deserialize;
 pub use marshal_derive::Serialize;
+pub use marshal_derive::TypeShape;
 
 pub mod context;
 pub mod de;
 pub mod ser;
+pub mod shape;
 mod features;
 
 #[doc(hidden)]
@@ -0,0 +1,139 @@
+//! Stable fingerprints of the serialized shape of a type.
+//!
+//! Formats that are not self-describing can store a [fingerprint] so that a reader can detect
+//! that it is using a different version of a type than the writer. The shape covers field names,
+//! variant names, primitive types and nesting, but not the names of types.
+
+use marshal_core::PrimitiveType;
+
+/// A type whose serialized shape can be hashed. Usually implemented with
+/// `#[derive(TypeShape)]`.
+pub trait TypeShape {
+    fn hash_shape(hasher: &mut ShapeHasher);
+}
+
+/// Returns a stable hash of the serialized shape of `T`.
+pub fn fingerprint<T: ?Sized + TypeShape>() -> u64 {
+    let mut hasher = ShapeHasher::new();
+    hasher.visit::<T>();
+    hasher.finish()
+}
+
+enum ShapeTag {
+    Primitive,
+    String,
+    Bytes,
+    Option,
+    Seq,
+    Map,
+    Tuple,
+    Struct,
+    Enum,
+    Recursive,
+}
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// Accumulates an FNV-1a hash of a type's shape.
+pub struct ShapeHasher {
+    hash: u64,
+    stack: Vec<&'static str>,
+}
+
+impl ShapeHasher {
+    fn new() -> Self {
+        ShapeHasher {
+            hash: FNV_OFFSET_BASIS,
+            stack: vec![],
+        }
+    }
+    fn finish(&self) -> u64 {
+        self.hash
+    }
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.hash ^= *byte as u64;
+            self.hash = self.hash.wrapping_mul(FNV_PRIME);
+        }
+    }
+    fn write_tag(&mut self, tag: ShapeTag) {
+        self.write(&[tag as u8]);
+    }
+    fn write_len(&mut self, len: usize) {
+        self.write(&(len as u64).to_le_bytes());
+    }
+    fn write_names(&mut self, names: &[&str]) {
+        self.write_len(names.len());
+        for name in names {
+            self.write_len(name.len());
+            self.write(name.as_bytes());
+        }
+    }
+    /// Hashes the shape of `T`, or a back-reference if `T` contains itself.
+    pub fn visit<T: ?Sized + TypeShape>(&mut self) {
+        let name = std::any::type_name::<T>();
+        if let Some(depth) = self.stack.iter().rev().position(|x| *x == name) {
+            self.write_tag(ShapeTag::Recursive);
+            self.write_len(depth);
+            return;
+        }
+        self.stack.push(name);
+        T::hash_shape(self);
+        self.stack.pop();
+    }
+    pub fn write_primitive(&mut self, prim: PrimitiveType) {
+        self.write_tag(ShapeTag::Primitive);
+        // Spelled out so that reordering `PrimitiveType` does not change fingerprints.
+        self.write(&[match prim {
+            PrimitiveType::Unit => 0,
+            PrimitiveType::Bool => 1,
+            PrimitiveType::I8 => 2,
+            PrimitiveType::I16 => 3,
+            PrimitiveType::I32 => 4,
+            PrimitiveType::I64 => 5,
+            PrimitiveType::I128 => 6,
+            PrimitiveType::U8 => 7,
+            PrimitiveType::U16 => 8,
+            PrimitiveType::U32 => 9,
+            PrimitiveType::U64 => 10,
+            PrimitiveType::U128 => 11,
+            PrimitiveType::F32 => 12,
+            PrimitiveType::F64 => 13,
+            PrimitiveType::Char => 14,
+        }]);
+    }
+    pub fn write_string(&mut self) {
+        self.write_tag(ShapeTag::String);
+    }
+    pub fn write_bytes(&mut self) {
+        self.write_tag(ShapeTag::Bytes);
+    }
+    /// Starts an option, followed by the shape of its contents.
+    pub fn write_option(&mut self) {
+        self.write_tag(ShapeTag::Option);
+    }
+    /// Starts a sequence, followed by the shape of its elements.
+    pub fn write_seq(&mut self) {
+        self.write_tag(ShapeTag::Seq);
+    }
+    /// Starts a map, followed by the shapes of its keys and values.
+    pub fn write_map(&mut self) {
+        self.write_tag(ShapeTag::Map);
+    }
+    /// Starts a tuple or tuple struct, followed by the shapes of its fields.
+    pub fn write_tuple(&mut self, len: usize) {
+        self.write_tag(ShapeTag::Tuple);
+        self.write_len(len);
+    }
+    /// Starts a struct, followed by the shapes of its fields.
+    pub fn write_struct(&mut self, fields: &[&str]) {
+        self.write_tag(ShapeTag::Struct);
+        self.write_names(fields);
+    }
+    /// Starts an enum, followed by the shape of each variant as a tuple, struct or unit.
+    pub fn write_enum(&mut self, variants: &[&str]) {
+        self.write_tag(ShapeTag::Enum);
+        self.write_names(variants);
+    }
+}
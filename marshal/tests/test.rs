@@ -3,7 +3,7 @@
 #[no_implicit_prelude]
 mod test_no_prelude {
     extern crate marshal;
-    #[derive(marshal::Serialize, marshal::Deserialize)]
+    #[derive(marshal::Serialize, marshal::Deserialize, marshal::TypeShape)]
     struct Foo;
     marshal::derive_deserialize_rc_transparent!(Foo);
     marshal::derive_deserialize_arc_transparent!(Foo);